@@ -2,11 +2,12 @@ use clap::Parser;
 use env_logger::Env;
 use example_raft_key_value::network::raft_network_impl::ExampleNetwork;
 use example_raft_key_value::start_example_raft_node;
-use example_raft_key_value::store::ExampleStore;
 use example_raft_key_value::ExampleTypeConfig;
+use example_raft_key_value::LogStore;
+use example_raft_key_value::StateMachineStore;
 use openraft::Raft;
 
-pub type ExampleRaft = Raft<ExampleTypeConfig, ExampleNetwork, ExampleStore>;
+pub type ExampleRaft = Raft<ExampleTypeConfig, ExampleNetwork, LogStore, StateMachineStore>;
 
 #[derive(Parser, Clone, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -5,6 +5,7 @@ use actix_web::middleware::Logger;
 use actix_web::web::Data;
 use actix_web::App;
 use actix_web::HttpServer;
+use openraft::storage::Adaptor;
 use openraft::Config;
 use openraft::Raft;
 
@@ -29,7 +30,9 @@ openraft::declare_raft_types!(
     pub ExampleTypeConfig: D = ExampleRequest, R = ExampleResponse, NodeId = ExampleNodeId
 );
 
-pub type ExampleRaft = Raft<ExampleTypeConfig, ExampleNetwork, Arc<ExampleStore>>;
+pub type ExampleRaft = Raft<ExampleTypeConfig, ExampleNetwork, LogStore, StateMachineStore>;
+pub type LogStore = Adaptor<ExampleTypeConfig, Arc<ExampleStore>>;
+pub type StateMachineStore = Adaptor<ExampleTypeConfig, Arc<ExampleStore>>;
 
 pub async fn start_example_raft_node(node_id: ExampleNodeId, http_addr: String) -> std::io::Result<()> {
     // Create a configuration for the raft instance.
@@ -43,7 +46,8 @@ pub async fn start_example_raft_node(node_id: ExampleNodeId, http_addr: String)
     let network = ExampleNetwork {};
 
     // Create a local raft instance.
-    let raft = Raft::new(node_id, config.clone(), network, store.clone());
+    let (log_store, state_machine) = Adaptor::new(store.clone());
+    let raft = Raft::new(node_id, config.clone(), network, log_store, state_machine);
 
     // Create an application that will store all the instances created above, this will
     // be later used on the actix-web services.
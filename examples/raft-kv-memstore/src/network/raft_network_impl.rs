@@ -1,19 +1,27 @@
+use std::io::Cursor;
+
 use async_trait::async_trait;
 use openraft::error::AppendEntriesError;
 use openraft::error::InstallSnapshotError;
 use openraft::error::NetworkError;
 use openraft::error::RPCError;
 use openraft::error::RemoteError;
+use openraft::error::StreamingError;
 use openraft::error::VoteError;
+use openraft::network::Chunked;
+use openraft::network::SnapshotStreaming;
 use openraft::raft::AppendEntriesRequest;
 use openraft::raft::AppendEntriesResponse;
 use openraft::raft::InstallSnapshotRequest;
 use openraft::raft::InstallSnapshotResponse;
+use openraft::raft::SnapshotResponse;
 use openraft::raft::VoteRequest;
 use openraft::raft::VoteResponse;
+use openraft::storage::Snapshot;
 use openraft::Node;
 use openraft::RaftNetwork;
 use openraft::RaftNetworkFactory;
+use openraft::Vote;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
@@ -85,6 +93,15 @@ impl RaftNetwork<ExampleTypeConfig> for ExampleNetworkConnection {
         self.owner.send_rpc(self.target, self.target_node.as_ref(), "raft-snapshot", req).await
     }
 
+    async fn full_snapshot(
+        &mut self,
+        vote: Vote<ExampleNodeId>,
+        snapshot: Snapshot<ExampleNodeId, Cursor<Vec<u8>>>,
+        streaming: SnapshotStreaming,
+    ) -> Result<SnapshotResponse<ExampleNodeId>, StreamingError<ExampleNodeId>> {
+        Chunked::send_snapshot(self, vote, snapshot, streaming).await
+    }
+
     async fn send_vote(
         &mut self,
         req: VoteRequest<ExampleNodeId>,
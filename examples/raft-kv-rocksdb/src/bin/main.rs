@@ -2,10 +2,11 @@ use clap::Parser;
 use openraft::Raft;
 use raft_key_value_rocks::network::raft_network_impl::ExampleNetwork;
 use raft_key_value_rocks::start_example_raft_node;
-use raft_key_value_rocks::store::ExampleStore;
 use raft_key_value_rocks::ExampleTypeConfig;
+use raft_key_value_rocks::LogStore;
+use raft_key_value_rocks::StateMachineStore;
 
-pub type ExampleRaft = Raft<ExampleTypeConfig, ExampleNetwork, ExampleStore>;
+pub type ExampleRaft = Raft<ExampleTypeConfig, ExampleNetwork, LogStore, StateMachineStore>;
 
 #[derive(Parser, Clone, Debug)]
 #[clap(author, version, about, long_about = None)]
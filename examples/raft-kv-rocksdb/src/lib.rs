@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use async_std::net::TcpListener;
 use async_std::task;
+use openraft::storage::Adaptor;
 use openraft::Config;
 use openraft::Raft;
 
@@ -26,7 +27,9 @@ openraft::declare_raft_types!(
     pub ExampleTypeConfig: D = ExampleRequest, R = ExampleResponse, NodeId = ExampleNodeId
 );
 
-pub type ExampleRaft = Raft<ExampleTypeConfig, ExampleNetwork, Arc<ExampleStore>>;
+pub type ExampleRaft = Raft<ExampleTypeConfig, ExampleNetwork, LogStore, StateMachineStore>;
+pub type LogStore = Adaptor<ExampleTypeConfig, Arc<ExampleStore>>;
+pub type StateMachineStore = Adaptor<ExampleTypeConfig, Arc<ExampleStore>>;
 type Server = tide::Server<Arc<ExampleApp>>;
 pub async fn start_example_raft_node<P>(
     node_id: ExampleNodeId,
@@ -48,7 +51,8 @@ where
     let network = ExampleNetwork {};
 
     // Create a local raft instance.
-    let raft = Raft::new(node_id, config.clone(), network, store.clone());
+    let (log_store, state_machine) = Adaptor::new(store.clone());
+    let raft = Raft::new(node_id, config.clone(), network, log_store, state_machine);
 
     let app = Arc::new(ExampleApp {
         id: node_id,
@@ -2,24 +2,34 @@
 
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::fmt;
 use std::fmt::Debug;
 use std::io::Cursor;
 use std::ops::RangeBounds;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
 
 use openraft::async_trait::async_trait;
+use openraft::storage::LogFlushed;
 use openraft::storage::LogState;
+use openraft::storage::PulledStateMachine;
 use openraft::storage::RaftLogReader;
 use openraft::storage::RaftSnapshotBuilder;
 use openraft::storage::Snapshot;
+use openraft::storage::SnapshotProgressReporter;
 use openraft::AnyError;
 use openraft::EffectiveMembership;
 use openraft::Entry;
 use openraft::EntryPayload;
 use openraft::ErrorSubject;
 use openraft::ErrorVerb;
+use openraft::LeaderId;
 use openraft::LogId;
+use openraft::RaftLogStorage;
 use openraft::RaftStorage;
 use openraft::RaftStorageDebug;
 use openraft::SnapshotMeta;
@@ -68,11 +78,27 @@ impl IntoMemClientRequest<ClientRequest> for ClientRequest {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ClientResponse(Option<String>);
 
+/// The error a `MemStore` rejects a client request with, see [`MemStore::set_rejected_client()`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RejectedRequest {
+    pub client: String,
+    pub serial: u64,
+}
+
+impl fmt::Display for RejectedRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "request {} of client {} is rejected", self.serial, self.client)
+    }
+}
+
+impl std::error::Error for RejectedRequest {}
+
 pub type MemNodeId = u64;
 
 openraft::declare_raft_types!(
     /// Declare the type configuration for `MemStore`.
-    pub Config: D = ClientRequest, R = ClientResponse, NodeId = MemNodeId
+    pub Config: D = ClientRequest, R = ClientResponse, NodeId = MemNodeId, SnapshotData = Cursor<Vec<u8>>,
+        AppError = RejectedRequest
 );
 
 /// The application snapshot type which the `MemStore` works with.
@@ -114,6 +140,56 @@ pub struct MemStore {
 
     /// The current snapshot.
     current_snapshot: RwLock<Option<MemStoreSnapshot>>,
+
+    /// The number of the following `append_to_log()` calls to fail with a transient error.
+    transient_append_failures: AtomicU64,
+
+    /// The number of appends that saved the vote along with the logs.
+    appends_with_vote: AtomicU64,
+
+    /// The number of calls to `try_get_log_entries()`.
+    log_reads: AtomicU64,
+
+    /// Whether `save_committed()` saves the committed log id.
+    save_committed: AtomicBool,
+
+    /// The committed log id saved by `save_committed()`.
+    committed: Mutex<Option<LogId<MemNodeId>>>,
+
+    /// The data a leader appends when it is established, instead of a blank entry.
+    leader_established_data: Mutex<Option<ClientRequest>>,
+
+    /// The client whose requests `try_apply_to_state_machine()` rejects.
+    rejected_client: Mutex<Option<String>>,
+
+    /// The number of steps a snapshot build takes and the delay of every step, reported as its progress.
+    snapshot_build_steps: Mutex<(u64, Duration)>,
+
+    /// The reporter of the progress of the following snapshot build.
+    snapshot_progress_reporter: Mutex<Option<SnapshotProgressReporter>>,
+
+    /// How `RaftLogStorage::append()` reports the logs are flushed.
+    flush_mode: Mutex<FlushMode>,
+
+    /// The index of the first log and the callback of every append that is not yet flushed, in
+    /// [`FlushMode::Delayed`].
+    pending_flushes: Mutex<Vec<(u64, LogFlushed<Config>)>>,
+}
+
+/// How [`RaftLogStorage::append()`] of a `MemStore` reports that the logs are flushed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushMode {
+    /// Call the callback before `append()` returns.
+    Immediate,
+
+    /// Keep the callbacks until [`MemStore::flush_logs()`], like a storage that syncs several appends at once.
+    Delayed,
+}
+
+impl Default for FlushMode {
+    fn default() -> Self {
+        Self::Immediate
+    }
 }
 
 impl MemStore {
@@ -130,12 +206,167 @@ impl MemStore {
             vote: RwLock::new(None),
             snapshot_idx: Arc::new(Mutex::new(0)),
             current_snapshot,
+            transient_append_failures: AtomicU64::new(0),
+            appends_with_vote: AtomicU64::new(0),
+            log_reads: AtomicU64::new(0),
+            save_committed: AtomicBool::new(false),
+            committed: Mutex::new(None),
+            leader_established_data: Mutex::new(None),
+            rejected_client: Mutex::new(None),
+            snapshot_build_steps: Mutex::new((0, Duration::from_millis(0))),
+            snapshot_progress_reporter: Mutex::new(None),
+            flush_mode: Mutex::new(FlushMode::default()),
+            pending_flushes: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Set how `RaftLogStorage::append()` reports the logs are flushed.
+    ///
+    /// Switching to [`FlushMode::Immediate`] flushes the pending appends.
+    #[cfg(feature = "testing")]
+    pub fn set_flush_mode(&self, mode: FlushMode) {
+        *self.flush_mode.lock().unwrap() = mode;
+        if mode == FlushMode::Immediate {
+            self.flush_logs();
+        }
+    }
+
+    /// Report every pending append as flushed, in the order they are appended.
+    ///
+    /// It returns the number of the appends that are flushed.
+    #[cfg(feature = "testing")]
+    pub fn flush_logs(&self) -> usize {
+        let pending = std::mem::take(&mut *self.pending_flushes.lock().unwrap());
+        let n = pending.len();
+        for (_, callback) in pending {
+            callback.log_io_completed(Ok(()));
+        }
+        n
+    }
+
+    /// Delete the logs of every pending append and drop their callbacks, like a crash before the logs are flushed.
+    ///
+    /// It returns the number of the appends that are discarded.
+    #[cfg(feature = "testing")]
+    pub async fn discard_unflushed_logs(&self) -> usize {
+        let pending = std::mem::take(&mut *self.pending_flushes.lock().unwrap());
+
+        if let Some((first, _)) = pending.first() {
+            let mut log = self.log.write().await;
+            log.split_off(first);
         }
+        pending.len()
+    }
+
+    /// Make the next `n` calls to `append_to_log()` fail with a transient error without appending anything.
+    ///
+    /// It is used for testing the retrying of transient storage errors.
+    #[cfg(feature = "testing")]
+    pub fn fail_appends_transiently(&self, n: u64) {
+        self.transient_append_failures.store(n, Ordering::Relaxed);
+    }
+
+    /// The number of appends that saved the vote along with the logs, with `append_to_log_with_vote()` or
+    /// `append_with_vote()`.
+    #[cfg(feature = "testing")]
+    pub fn appends_with_vote(&self) -> u64 {
+        self.appends_with_vote.load(Ordering::Relaxed)
+    }
+
+    /// The number of calls to `RaftLogReader::try_get_log_entries()`, by every reader of this store.
+    #[cfg(feature = "testing")]
+    pub fn log_reads(&self) -> u64 {
+        self.log_reads.load(Ordering::Relaxed)
+    }
+
+    /// Let `save_committed()` save the committed log id and `read_committed()` return it, which are no-ops by default.
+    #[cfg(feature = "testing")]
+    pub fn set_save_committed(&self, enabled: bool) {
+        self.save_committed.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            *self.committed.lock().unwrap() = None;
+        }
+    }
+
+    /// Reset the state machine to the initial state, like a state machine kept only in memory when the process
+    /// restarts.
+    #[cfg(feature = "testing")]
+    pub async fn clear_state_machine(&self) {
+        *self.sm.write().await = MemStoreStateMachine::default();
+    }
+
+    /// Let a leader append `data` when it is established, instead of a blank entry, or a blank entry if it is `None`.
+    ///
+    /// The serial of the appended request is the term of the leader, thus every leader's request is applied.
+    #[cfg(feature = "testing")]
+    pub fn set_leader_established_data(&self, data: Option<ClientRequest>) {
+        *self.leader_established_data.lock().unwrap() = data;
+    }
+
+    /// Let `try_apply_to_state_machine()` reject the requests of `client` with [`RejectedRequest`], without changing
+    /// the state machine, or reject nothing if it is `None`.
+    ///
+    /// `apply_to_state_machine()` rejects nothing.
+    #[cfg(feature = "testing")]
+    pub fn set_rejected_client(&self, client: Option<String>) {
+        *self.rejected_client.lock().unwrap() = client;
+    }
+
+    /// Let a snapshot build take `steps` steps of `delay` each, and report the progress after every step.
+    ///
+    /// A build reports no progress and is not delayed if `steps` is 0.
+    #[cfg(feature = "testing")]
+    pub fn set_snapshot_build_steps(&self, steps: u64, delay: Duration) {
+        *self.snapshot_build_steps.lock().unwrap() = (steps, delay);
     }
 
     pub async fn new_async() -> Arc<Self> {
         Arc::new(Self::new())
     }
+
+    /// Apply `entries`, and reject the requests of the `rejected` client.
+    async fn apply_entries(
+        &self,
+        entries: &[&Entry<Config>],
+        rejected: Option<&str>,
+    ) -> Vec<Result<ClientResponse, RejectedRequest>> {
+        let mut res = Vec::with_capacity(entries.len());
+
+        let mut sm = self.sm.write().await;
+
+        for entry in entries {
+            tracing::debug!(%entry.log_id, "replicate to sm");
+
+            sm.last_applied_log = Some(entry.log_id);
+
+            match entry.payload {
+                EntryPayload::Blank => res.push(Ok(ClientResponse(None))),
+                EntryPayload::Normal(ref data) => {
+                    if rejected == Some(data.client.as_str()) {
+                        res.push(Err(RejectedRequest {
+                            client: data.client.clone(),
+                            serial: data.serial,
+                        }));
+                        continue;
+                    }
+                    if let Some((serial, r)) = sm.client_serial_responses.get(&data.client) {
+                        if serial == &data.serial {
+                            res.push(Ok(ClientResponse(r.clone())));
+                            continue;
+                        }
+                    }
+                    let previous = sm.client_status.insert(data.client.clone(), data.status.clone());
+                    sm.client_serial_responses.insert(data.client.clone(), (data.serial, previous.clone()));
+                    res.push(Ok(ClientResponse(previous)));
+                }
+                EntryPayload::Membership(ref mem) => {
+                    sm.last_membership = EffectiveMembership::new(Some(entry.log_id), mem.clone());
+                    res.push(Ok(ClientResponse(None)))
+                }
+            };
+        }
+        res
+    }
 }
 
 impl Default for MemStore {
@@ -158,6 +389,8 @@ impl RaftLogReader<Config> for Arc<MemStore> {
         &mut self,
         range: RB,
     ) -> Result<Vec<Entry<Config>>, StorageError<MemNodeId>> {
+        self.log_reads.fetch_add(1, Ordering::Relaxed);
+
         let res = {
             let log = self.log.read().await;
             log.range(range.clone()).map(|(_, val)| val.clone()).collect::<Vec<_>>()
@@ -188,19 +421,24 @@ impl RaftLogReader<Config> for Arc<MemStore> {
 impl RaftSnapshotBuilder<Config, Cursor<Vec<u8>>> for Arc<MemStore> {
     #[tracing::instrument(level = "trace", skip(self))]
     async fn build_snapshot(&mut self) -> Result<Snapshot<MemNodeId, Cursor<Vec<u8>>>, StorageError<MemNodeId>> {
-        let data;
-        let last_applied_log;
-        let last_membership;
+        // Take a copy of the state machine and release the lock at once, so that logs are applied while the copy is
+        // being serialized.
+        let sm = self.sm.read().await.clone();
+
+        let (steps, delay) = *self.snapshot_build_steps.lock().unwrap();
+        let reporter = self.snapshot_progress_reporter.lock().unwrap().clone();
+        for i in 1..=steps {
+            tokio::time::sleep(delay).await;
+            if let Some(r) = &reporter {
+                r.report(i, steps);
+            }
+        }
 
-        {
-            // Serialize the data of the state machine.
-            let sm = self.sm.read().await;
-            data = serde_json::to_vec(&*sm)
-                .map_err(|e| StorageIOError::new(ErrorSubject::StateMachine, ErrorVerb::Read, AnyError::new(&e)))?;
+        let data = serde_json::to_vec(&sm)
+            .map_err(|e| StorageIOError::new(ErrorSubject::StateMachine, ErrorVerb::Read, AnyError::new(&e)))?;
 
-            last_applied_log = sm.last_applied_log;
-            last_membership = sm.last_membership.clone();
-        }
+        let last_applied_log = sm.last_applied_log;
+        let last_membership = sm.last_membership;
 
         let last_applied_log = match last_applied_log {
             None => {
@@ -245,12 +483,115 @@ impl RaftSnapshotBuilder<Config, Cursor<Vec<u8>>> for Arc<MemStore> {
             snapshot: Box::new(Cursor::new(data)),
         })
     }
+
+    fn set_progress_reporter(&mut self, reporter: SnapshotProgressReporter) {
+        *self.snapshot_progress_reporter.lock().unwrap() = Some(reporter);
+    }
 }
 
+/// `MemStore` is also a log store by itself, which can delay reporting that the logs are flushed, see [`FlushMode`].
 #[async_trait]
-impl RaftStorage<Config> for Arc<MemStore> {
-    type SnapshotData = Cursor<Vec<u8>>;
+impl RaftLogStorage<Config> for Arc<MemStore> {
+    type LogReader = Self;
+
+    async fn save_vote(&mut self, vote: &Vote<MemNodeId>) -> Result<(), StorageError<MemNodeId>> {
+        RaftStorage::save_vote(self, vote).await
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<MemNodeId>>, StorageError<MemNodeId>> {
+        RaftStorage::read_vote(self).await
+    }
 
+    async fn save_committed(&mut self, committed: Option<LogId<MemNodeId>>) -> Result<(), StorageError<MemNodeId>> {
+        RaftStorage::save_committed(self, committed).await
+    }
+
+    async fn read_committed(&mut self) -> Result<Option<LogId<MemNodeId>>, StorageError<MemNodeId>> {
+        RaftStorage::read_committed(self).await
+    }
+
+    async fn get_log_reader(&mut self) -> Self::LogReader {
+        self.clone()
+    }
+
+    #[tracing::instrument(level = "trace", skip(self, entries, callback))]
+    async fn append(
+        &mut self,
+        entries: &[&Entry<Config>],
+        callback: LogFlushed<Config>,
+    ) -> Result<(), StorageError<MemNodeId>> {
+        self.append_to_log(entries).await?;
+
+        let mode = *self.flush_mode.lock().unwrap();
+        match (mode, entries.first()) {
+            (FlushMode::Delayed, Some(first)) => {
+                self.pending_flushes.lock().unwrap().push((first.log_id.index, callback));
+            }
+            _ => callback.log_io_completed(Ok(())),
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "trace", skip(self, entries, callback))]
+    async fn append_with_vote(
+        &mut self,
+        vote: &Vote<MemNodeId>,
+        entries: &[&Entry<Config>],
+        callback: LogFlushed<Config>,
+    ) -> Result<(), StorageError<MemNodeId>> {
+        self.appends_with_vote.fetch_add(1, Ordering::Relaxed);
+
+        // Nothing is persisted by `MemStore`: saving the vote before appending is enough.
+        RaftLogStorage::save_vote(self, vote).await?;
+        RaftLogStorage::append(self, entries, callback).await
+    }
+
+    async fn delete_conflict_logs_since(&mut self, log_id: LogId<MemNodeId>) -> Result<(), StorageError<MemNodeId>> {
+        RaftStorage::delete_conflict_logs_since(self, log_id).await
+    }
+
+    async fn purge_logs_upto(&mut self, log_id: LogId<MemNodeId>) -> Result<(), StorageError<MemNodeId>> {
+        RaftStorage::purge_logs_upto(self, log_id).await
+    }
+}
+
+/// `MemStore` can serve a [`ChannelStateMachine`](`openraft::storage::ChannelStateMachine`), with the logs applied by
+/// the application with `RaftStorage::apply_to_state_machine()`.
+#[async_trait]
+impl PulledStateMachine<Config> for Arc<MemStore> {
+    type SnapshotBuilder = Self;
+
+    async fn last_applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<MemNodeId>>, EffectiveMembership<MemNodeId>), StorageError<MemNodeId>> {
+        RaftStorage::last_applied_state(self).await
+    }
+
+    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder {
+        self.clone()
+    }
+
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<Cursor<Vec<u8>>>, StorageError<MemNodeId>> {
+        RaftStorage::begin_receiving_snapshot(self).await
+    }
+
+    async fn install_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<MemNodeId>,
+        snapshot: Box<Cursor<Vec<u8>>>,
+    ) -> Result<StateMachineChanges<Config>, StorageError<MemNodeId>> {
+        RaftStorage::install_snapshot(self, meta, snapshot).await
+    }
+
+    async fn get_current_snapshot(
+        &mut self,
+    ) -> Result<Option<Snapshot<MemNodeId, Cursor<Vec<u8>>>>, StorageError<MemNodeId>> {
+        RaftStorage::get_current_snapshot(self).await
+    }
+}
+
+#[async_trait]
+impl RaftStorage<Config> for Arc<MemStore> {
     #[tracing::instrument(level = "trace", skip(self))]
     async fn save_vote(&mut self, vote: &Vote<MemNodeId>) -> Result<(), StorageError<MemNodeId>> {
         tracing::debug!(?vote, "save_vote");
@@ -264,6 +605,17 @@ impl RaftStorage<Config> for Arc<MemStore> {
         Ok(*self.vote.read().await)
     }
 
+    async fn save_committed(&mut self, committed: Option<LogId<MemNodeId>>) -> Result<(), StorageError<MemNodeId>> {
+        if self.save_committed.load(Ordering::Relaxed) {
+            *self.committed.lock().unwrap() = committed;
+        }
+        Ok(())
+    }
+
+    async fn read_committed(&mut self) -> Result<Option<LogId<MemNodeId>>, StorageError<MemNodeId>> {
+        Ok(*self.committed.lock().unwrap())
+    }
+
     async fn last_applied_state(
         &mut self,
     ) -> Result<(Option<LogId<MemNodeId>>, EffectiveMembership<MemNodeId>), StorageError<MemNodeId>> {
@@ -311,6 +663,16 @@ impl RaftStorage<Config> for Arc<MemStore> {
 
     #[tracing::instrument(level = "trace", skip(self, entries))]
     async fn append_to_log(&mut self, entries: &[&Entry<Config>]) -> Result<(), StorageError<MemNodeId>> {
+        let failures = self.transient_append_failures.load(Ordering::Relaxed);
+        if failures > 0 {
+            self.transient_append_failures.store(failures - 1, Ordering::Relaxed);
+            return Err(StorageError::from_transient_io_error(
+                ErrorSubject::Logs,
+                ErrorVerb::Write,
+                std::io::Error::new(std::io::ErrorKind::Other, "injected transient failure"),
+            ));
+        }
+
         let mut log = self.log.write().await;
         for entry in entries {
             log.insert(entry.log_id.index, (*entry).clone());
@@ -319,43 +681,60 @@ impl RaftStorage<Config> for Arc<MemStore> {
     }
 
     #[tracing::instrument(level = "trace", skip(self, entries))]
-    async fn apply_to_state_machine(
+    async fn append_to_log_with_vote(
         &mut self,
+        vote: &Vote<MemNodeId>,
         entries: &[&Entry<Config>],
-    ) -> Result<Vec<ClientResponse>, StorageError<MemNodeId>> {
-        let mut res = Vec::with_capacity(entries.len());
+    ) -> Result<(), StorageError<MemNodeId>> {
+        self.appends_with_vote.fetch_add(1, Ordering::Relaxed);
 
-        let mut sm = self.sm.write().await;
+        // Nothing is persisted by `MemStore`: saving the vote before appending is enough.
+        RaftStorage::save_vote(self, vote).await?;
+        RaftStorage::append_to_log(self, entries).await
+    }
 
-        for entry in entries {
-            tracing::debug!(%entry.log_id, "replicate to sm");
+    #[tracing::instrument(level = "trace", skip(self, entries))]
+    async fn apply_to_state_machine(
+        &mut self,
+        entries: &[&Entry<Config>],
+    ) -> Result<Vec<ClientResponse>, StorageError<MemNodeId>> {
+        let res = self.apply_entries(entries, None).await;
+
+        // Nothing is rejected without a rejected client. Still, a rejection is a storage error here.
+        entries
+            .iter()
+            .zip(res)
+            .map(|(entry, r)| {
+                r.map_err(|e| StorageError::IO {
+                    source: StorageIOError::new(
+                        ErrorSubject::Apply(entry.log_id),
+                        ErrorVerb::Write,
+                        AnyError::new(&e),
+                    ),
+                })
+            })
+            .collect()
+    }
 
-            sm.last_applied_log = Some(entry.log_id);
+    #[tracing::instrument(level = "trace", skip(self, entries))]
+    async fn try_apply_to_state_machine(
+        &mut self,
+        entries: &[&Entry<Config>],
+    ) -> Result<Vec<Result<ClientResponse, RejectedRequest>>, StorageError<MemNodeId>> {
+        let rejected = self.rejected_client.lock().unwrap().clone();
+        Ok(self.apply_entries(entries, rejected.as_deref()).await)
+    }
 
-            match entry.payload {
-                EntryPayload::Blank => res.push(ClientResponse(None)),
-                EntryPayload::Normal(ref data) => {
-                    if let Some((serial, r)) = sm.client_serial_responses.get(&data.client) {
-                        if serial == &data.serial {
-                            res.push(ClientResponse(r.clone()));
-                            continue;
-                        }
-                    }
-                    let previous = sm.client_status.insert(data.client.clone(), data.status.clone());
-                    sm.client_serial_responses.insert(data.client.clone(), (data.serial, previous.clone()));
-                    res.push(ClientResponse(previous));
-                }
-                EntryPayload::Membership(ref mem) => {
-                    sm.last_membership = EffectiveMembership::new(Some(entry.log_id), mem.clone());
-                    res.push(ClientResponse(None))
-                }
-            };
-        }
-        Ok(res)
+    async fn leader_established_data(&mut self, leader_id: &LeaderId<MemNodeId>) -> Option<ClientRequest> {
+        let data = self.leader_established_data.lock().unwrap().clone();
+        data.map(|d| ClientRequest {
+            serial: leader_id.term,
+            ..d
+        })
     }
 
     #[tracing::instrument(level = "trace", skip(self))]
-    async fn begin_receiving_snapshot(&mut self) -> Result<Box<Self::SnapshotData>, StorageError<MemNodeId>> {
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<Cursor<Vec<u8>>>, StorageError<MemNodeId>> {
         Ok(Box::new(Cursor::new(Vec::new())))
     }
 
@@ -363,7 +742,7 @@ impl RaftStorage<Config> for Arc<MemStore> {
     async fn install_snapshot(
         &mut self,
         meta: &SnapshotMeta<MemNodeId>,
-        snapshot: Box<Self::SnapshotData>,
+        snapshot: Box<Cursor<Vec<u8>>>,
     ) -> Result<StateMachineChanges<Config>, StorageError<MemNodeId>> {
         tracing::info!(
             { snapshot_size = snapshot.get_ref().len() },
@@ -386,7 +765,7 @@ impl RaftStorage<Config> for Arc<MemStore> {
         {
             let new_sm: MemStoreStateMachine = serde_json::from_slice(&new_snapshot.data).map_err(|e| {
                 StorageIOError::new(
-                    ErrorSubject::Snapshot(new_snapshot.meta.clone()),
+                    ErrorSubject::Snapshot(new_snapshot.meta.signature()),
                     ErrorVerb::Read,
                     AnyError::new(&e),
                 )
@@ -407,7 +786,7 @@ impl RaftStorage<Config> for Arc<MemStore> {
     #[tracing::instrument(level = "trace", skip(self))]
     async fn get_current_snapshot(
         &mut self,
-    ) -> Result<Option<Snapshot<MemNodeId, Self::SnapshotData>>, StorageError<MemNodeId>> {
+    ) -> Result<Option<Snapshot<MemNodeId, Cursor<Vec<u8>>>>, StorageError<MemNodeId>> {
         match &*self.current_snapshot.read().await {
             Some(snapshot) => {
                 let data = snapshot.data.clone();
@@ -0,0 +1,138 @@
+//! Multi-producer, multi-consumer channels, in which every receiver receives every value sent, created by
+//! [`AsyncRuntime::broadcast()`](`crate::AsyncRuntime::broadcast`).
+//!
+//! A runtime provides its own channel by implementing the `*Impl` traits for it. Openraft delivers the applied events
+//! and tells the waiters of a snapshot build through the wrappers of them.
+
+use std::fmt;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+
+pub use crate::async_runtime::mpsc::SendError;
+
+/// The error returned by [`Receiver::recv()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum RecvError {
+    /// Every sender is dropped, and every value sent is received.
+    #[error("channel closed")]
+    Closed,
+
+    /// The receiver fell behind by this number of values, which are dropped since the channel is full.
+    /// The next call receives the oldest value still in the channel.
+    #[error("channel lagged by {0}")]
+    Lagged(u64),
+}
+
+/// The sending half of a broadcast channel, implemented by an [`AsyncRuntime`](`crate::AsyncRuntime`).
+pub trait SenderImpl<T>: Send + Sync + 'static {
+    /// Send `value` to every receiver, and return the number of them. It fails if there is no receiver.
+    fn send(&self, value: T) -> Result<usize, SendError<T>>;
+
+    /// Create a receiver that receives the values sent after this call.
+    fn subscribe(&self) -> Receiver<T>;
+
+    /// The number of receivers.
+    fn receiver_count(&self) -> usize;
+
+    /// Create another sender of the same channel.
+    fn boxed_clone(&self) -> Box<dyn SenderImpl<T>>;
+}
+
+/// The receiving half of a broadcast channel, implemented by an [`AsyncRuntime`](`crate::AsyncRuntime`).
+pub trait ReceiverImpl<T>: Send + 'static {
+    /// Receive the next value.
+    fn recv(&mut self) -> BoxFuture<'_, Result<T, RecvError>>;
+}
+
+/// The sending half of a broadcast channel.
+pub struct Sender<T> {
+    inner: Box<dyn SenderImpl<T>>,
+}
+
+impl<T> Sender<T> {
+    pub fn new(inner: impl SenderImpl<T>) -> Self {
+        Self { inner: Box::new(inner) }
+    }
+
+    /// Send `value` to every receiver, and return the number of them. It fails if there is no receiver.
+    pub fn send(&self, value: T) -> Result<usize, SendError<T>> {
+        self.inner.send(value)
+    }
+
+    /// Create a receiver that receives the values sent after this call.
+    pub fn subscribe(&self) -> Receiver<T> {
+        self.inner.subscribe()
+    }
+
+    /// The number of receivers.
+    pub fn receiver_count(&self) -> usize {
+        self.inner.receiver_count()
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.boxed_clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+/// The receiving half of a broadcast channel.
+pub struct Receiver<T> {
+    inner: Box<dyn ReceiverImpl<T>>,
+}
+
+impl<T> Receiver<T> {
+    pub fn new(inner: impl ReceiverImpl<T>) -> Self {
+        Self { inner: Box::new(inner) }
+    }
+
+    /// Receive the next value.
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        self.inner.recv().await
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> SenderImpl<T> for tokio::sync::broadcast::Sender<T> {
+    fn send(&self, value: T) -> Result<usize, SendError<T>> {
+        tokio::sync::broadcast::Sender::send(self, value).map_err(|e| SendError(e.0))
+    }
+
+    fn subscribe(&self) -> Receiver<T> {
+        Receiver::new(tokio::sync::broadcast::Sender::subscribe(self))
+    }
+
+    fn receiver_count(&self) -> usize {
+        tokio::sync::broadcast::Sender::receiver_count(self)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn SenderImpl<T>> {
+        Box::new(self.clone())
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> ReceiverImpl<T> for tokio::sync::broadcast::Receiver<T> {
+    fn recv(&mut self) -> BoxFuture<'_, Result<T, RecvError>> {
+        async move {
+            tokio::sync::broadcast::Receiver::recv(self).await.map_err(|e| match e {
+                tokio::sync::broadcast::error::RecvError::Closed => RecvError::Closed,
+                tokio::sync::broadcast::error::RecvError::Lagged(n) => RecvError::Lagged(n),
+            })
+        }
+        .boxed()
+    }
+}
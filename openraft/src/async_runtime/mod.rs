@@ -0,0 +1,259 @@
+pub mod broadcast;
+pub mod mpsc;
+pub mod watch;
+
+use std::error::Error;
+use std::fmt::Debug;
+use std::future::Future;
+use std::ops::Add;
+use std::ops::AddAssign;
+use std::ops::Sub;
+use std::ops::SubAssign;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+
+use crate::RaftTypeConfig;
+
+/// A handle to a task spawned by an [`AsyncRuntime`].
+///
+/// Awaiting it returns the output of the task. Dropping it detaches the task.
+pub type JoinHandle<T, E> = BoxFuture<'static, Result<T, E>>;
+
+/// The [`Instant`] type of the [`AsyncRuntime`] of a [`RaftTypeConfig`].
+pub type InstantOf<C> = <<C as RaftTypeConfig>::AsyncRuntime as AsyncRuntime>::Instant;
+
+/// A measurement of a monotonically nondecreasing clock, the time type of an [`AsyncRuntime`].
+///
+/// It is implemented for `tokio::time::Instant` and `std::time::Instant`.
+pub trait Instant:
+    Add<Duration, Output = Self>
+    + AddAssign<Duration>
+    + Sub<Duration, Output = Self>
+    + Sub<Self, Output = Duration>
+    + SubAssign<Duration>
+    + Debug
+    + Clone
+    + Copy
+    + PartialEq
+    + Eq
+    + PartialOrd
+    + Ord
+    + Send
+    + Sync
+    + Unpin
+    + 'static
+{
+    /// The current time of the wall clock.
+    fn now() -> Self;
+
+    /// The time elapsed from `earlier` to this one. It panics if `earlier` is later than this one.
+    fn duration_since(&self, earlier: Self) -> Duration {
+        *self - earlier
+    }
+
+    /// The time elapsed from `earlier` to this one, or zero if `earlier` is later than this one.
+    fn saturating_duration_since(&self, earlier: Self) -> Duration {
+        if *self > earlier {
+            *self - earlier
+        } else {
+            Duration::default()
+        }
+    }
+}
+
+impl Instant for tokio::time::Instant {
+    fn now() -> Self {
+        tokio::time::Instant::now()
+    }
+}
+
+impl Instant for std::time::Instant {
+    fn now() -> Self {
+        std::time::Instant::now()
+    }
+}
+
+/// The async runtime openraft runs on: how to spawn tasks, how to read the time and how to wait for a while.
+///
+/// openraft spawns `RaftCore`, replication streams and RPC tasks with `spawn()`, reads the current time with `now()`,
+/// and drives its timers with `sleep()`, `sleep_until()` and `timeout()`. By default it runs on tokio with
+/// [`TokioRuntime`]. Implement this trait to run it on another executor, e.g., `async-std` or a single-threaded one,
+/// and specify it with `AsyncRuntime = MyRuntime` in [`declare_raft_types!`](`crate::declare_raft_types`).
+///
+/// The time openraft measures the election timeout, heartbeats and the leader lease with is of the runtime's
+/// [`Instant`](`AsyncRuntime::Instant`) type, and timers are told to quit with the runtime's oneshot channel. The
+/// channels between its own tasks, and the ones the metrics and the applied events are published through, are
+/// created by the runtime too, see [`mpsc`], [`watch`] and [`broadcast`].
+///
+/// This trait is unrelated to the internal runtime that executes the commands output by the raft `Engine`.
+pub trait AsyncRuntime: Debug + Default + Send + Sync + 'static {
+    /// The error returned by a [`JoinHandle`] if the task does not finish normally.
+    type JoinError: Error + Send + Sync + 'static;
+
+    /// The error returned by [`timeout()`](`AsyncRuntime::timeout`) if the future does not finish in time.
+    type TimeoutError: Error + Send + Sync + 'static;
+
+    /// The time type of the clock of this runtime, returned by [`now()`](`AsyncRuntime::now`).
+    type Instant: Instant;
+
+    /// The sending half of a oneshot channel created by [`oneshot()`](`AsyncRuntime::oneshot`).
+    type OneshotSender: Send + Sync + 'static;
+
+    /// The receiving half of a oneshot channel created by [`oneshot()`](`AsyncRuntime::oneshot`).
+    ///
+    /// It resolves when the sender sends or is dropped.
+    type OneshotReceiver: Future + Send + Unpin + 'static;
+
+    /// Spawn a new task to run `future` in background.
+    fn spawn<T>(future: T) -> JoinHandle<T::Output, Self::JoinError>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static;
+
+    /// Check if a task failed because it panicked.
+    fn is_panic(join_error: &Self::JoinError) -> bool;
+
+    /// The current time, from which the election timeout, heartbeats and the leader lease are measured.
+    ///
+    /// A runtime that controls the time, e.g., for tests, returns its own clock here, and has the sleeps follow it.
+    fn now() -> Self::Instant {
+        <Self::Instant as Instant>::now()
+    }
+
+    /// Wait until `duration` has elapsed.
+    fn sleep(duration: Duration) -> BoxFuture<'static, ()>;
+
+    /// Wait until `deadline` is reached.
+    fn sleep_until(deadline: Self::Instant) -> BoxFuture<'static, ()>;
+
+    /// Run `future` but give up if it does not finish in `duration`.
+    fn timeout<'a, F>(duration: Duration, future: F) -> BoxFuture<'a, Result<F::Output, Self::TimeoutError>>
+    where F: Future + Send + 'a;
+
+    /// Create a oneshot channel, with which a task tells another one to quit, by sending or by dropping the sender.
+    ///
+    /// No value is carried: a channel of any value type can not be an associated type without generic associated
+    /// types, which the supported toolchain does not have.
+    fn oneshot() -> (Self::OneshotSender, Self::OneshotReceiver);
+
+    /// Create an unbounded mpsc channel, e.g., the one `RaftCore` receives its messages from.
+    fn mpsc_unbounded<T: Send + 'static>() -> (mpsc::UnboundedSender<T>, mpsc::UnboundedReceiver<T>);
+
+    /// Create an mpsc channel that holds at most `buffer` values, e.g., the one entries are pulled from by a
+    /// [`PulledStateMachine`](`crate::storage::PulledStateMachine`).
+    fn mpsc<T: Send + 'static>(buffer: usize) -> (mpsc::Sender<T>, mpsc::Receiver<T>);
+
+    /// Create a watch channel holding `init`, e.g., the one [`Raft::metrics()`](`crate::Raft::metrics`) is read
+    /// from.
+    fn watch<T: Send + Sync + 'static>(init: T) -> (watch::Sender<T>, watch::Receiver<T>);
+
+    /// Create a broadcast channel that keeps at most `capacity` values for a receiver to receive, e.g., the one
+    /// [`Raft::subscribe_applied()`](`crate::Raft::subscribe_applied`) subscribes to.
+    fn broadcast<T: Clone + Send + Sync + 'static>(capacity: usize) -> (broadcast::Sender<T>, broadcast::Receiver<T>);
+}
+
+/// The default [`AsyncRuntime`]: tokio.
+///
+/// With feature `manual-clock`, it reads the time from [`ManualClock`](`crate::testing::ManualClock`) instead, which
+/// goes with the wall clock until a test pauses it. Its sleeps and timeouts then wake up by that clock too.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TokioRuntime;
+
+impl AsyncRuntime for TokioRuntime {
+    type JoinError = tokio::task::JoinError;
+
+    #[cfg(not(feature = "manual-clock"))]
+    type TimeoutError = tokio::time::error::Elapsed;
+
+    #[cfg(feature = "manual-clock")]
+    type TimeoutError = crate::testing::Elapsed;
+
+    type Instant = tokio::time::Instant;
+    type OneshotSender = tokio::sync::oneshot::Sender<()>;
+    type OneshotReceiver = tokio::sync::oneshot::Receiver<()>;
+
+    fn spawn<T>(future: T) -> JoinHandle<T::Output, Self::JoinError>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        tokio::spawn(future).boxed()
+    }
+
+    fn is_panic(join_error: &Self::JoinError) -> bool {
+        join_error.is_panic()
+    }
+
+    #[cfg(not(feature = "manual-clock"))]
+    fn sleep(duration: Duration) -> BoxFuture<'static, ()> {
+        tokio::time::sleep(duration).boxed()
+    }
+
+    #[cfg(not(feature = "manual-clock"))]
+    fn sleep_until(deadline: Self::Instant) -> BoxFuture<'static, ()> {
+        tokio::time::sleep_until(deadline).boxed()
+    }
+
+    #[cfg(not(feature = "manual-clock"))]
+    fn timeout<'a, F>(duration: Duration, future: F) -> BoxFuture<'a, Result<F::Output, Self::TimeoutError>>
+    where F: Future + Send + 'a {
+        tokio::time::timeout(duration, future).boxed()
+    }
+
+    #[cfg(feature = "manual-clock")]
+    fn now() -> Self::Instant {
+        crate::testing::ManualClock::now()
+    }
+
+    #[cfg(feature = "manual-clock")]
+    fn sleep(duration: Duration) -> BoxFuture<'static, ()> {
+        Self::sleep_until(Self::now() + duration)
+    }
+
+    #[cfg(feature = "manual-clock")]
+    fn sleep_until(deadline: Self::Instant) -> BoxFuture<'static, ()> {
+        let wall_sleep_until = |at: Self::Instant| tokio::time::sleep_until(at).boxed();
+        crate::testing::ManualClock::sleep_until(deadline, wall_sleep_until).boxed()
+    }
+
+    #[cfg(feature = "manual-clock")]
+    fn timeout<'a, F>(duration: Duration, future: F) -> BoxFuture<'a, Result<F::Output, Self::TimeoutError>>
+    where F: Future + Send + 'a {
+        let fut = future.boxed();
+        let sleep = Self::sleep(duration);
+
+        async move {
+            match futures::future::select(fut, sleep).await {
+                futures::future::Either::Left((output, _)) => Ok(output),
+                futures::future::Either::Right(_) => Err(crate::testing::Elapsed),
+            }
+        }
+        .boxed()
+    }
+
+    fn oneshot() -> (Self::OneshotSender, Self::OneshotReceiver) {
+        tokio::sync::oneshot::channel()
+    }
+
+    fn mpsc_unbounded<T: Send + 'static>() -> (mpsc::UnboundedSender<T>, mpsc::UnboundedReceiver<T>) {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        (mpsc::UnboundedSender::new(tx), mpsc::UnboundedReceiver::new(rx))
+    }
+
+    fn mpsc<T: Send + 'static>(buffer: usize) -> (mpsc::Sender<T>, mpsc::Receiver<T>) {
+        let (tx, rx) = tokio::sync::mpsc::channel(buffer);
+        (mpsc::Sender::new(tx), mpsc::Receiver::new(rx))
+    }
+
+    fn watch<T: Send + Sync + 'static>(init: T) -> (watch::Sender<T>, watch::Receiver<T>) {
+        let (tx, rx) = tokio::sync::watch::channel(init);
+        (watch::Sender::new(tx), watch::Receiver::new(rx))
+    }
+
+    fn broadcast<T: Clone + Send + Sync + 'static>(capacity: usize) -> (broadcast::Sender<T>, broadcast::Receiver<T>) {
+        let (tx, rx) = tokio::sync::broadcast::channel(capacity);
+        (broadcast::Sender::new(tx), broadcast::Receiver::new(rx))
+    }
+}
@@ -0,0 +1,205 @@
+//! Multi-producer, single-consumer channels, created by
+//! [`AsyncRuntime::mpsc_unbounded()`](`crate::AsyncRuntime::mpsc_unbounded`) and
+//! [`AsyncRuntime::mpsc()`](`crate::AsyncRuntime::mpsc`).
+//!
+//! A runtime provides its own channel by implementing the `*Impl` traits for it. Openraft sends the messages to
+//! `RaftCore` and to the replication streams through the wrappers of them.
+
+use std::fmt;
+use std::task::Context;
+use std::task::Poll;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+
+/// The error returned by a sender if the receiver is dropped. It holds the value that is not sent.
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "channel closed")
+    }
+}
+
+impl<T> std::error::Error for SendError<T> {}
+
+/// The sending half of an unbounded channel, implemented by an [`AsyncRuntime`](`crate::AsyncRuntime`).
+pub trait UnboundedSenderImpl<T>: Send + Sync + 'static {
+    /// Send `value` without waiting. It fails only if the receiver is dropped.
+    fn send(&self, value: T) -> Result<(), SendError<T>>;
+
+    /// Create another sender of the same channel.
+    fn boxed_clone(&self) -> Box<dyn UnboundedSenderImpl<T>>;
+}
+
+/// The receiving half of an unbounded channel, implemented by an [`AsyncRuntime`](`crate::AsyncRuntime`).
+pub trait UnboundedReceiverImpl<T>: Send + Sync + 'static {
+    /// Poll to receive the next value. It is ready with `None` when every sender is dropped and the channel is
+    /// drained.
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>>;
+}
+
+/// The sending half of a bounded channel, implemented by an [`AsyncRuntime`](`crate::AsyncRuntime`).
+pub trait SenderImpl<T>: Send + Sync + 'static {
+    /// Send `value`, waiting for a free slot if the channel is full. It fails only if the receiver is dropped.
+    fn send(&self, value: T) -> BoxFuture<'_, Result<(), SendError<T>>>;
+
+    /// Create another sender of the same channel.
+    fn boxed_clone(&self) -> Box<dyn SenderImpl<T>>;
+}
+
+/// The receiving half of a bounded channel, implemented by an [`AsyncRuntime`](`crate::AsyncRuntime`).
+pub trait ReceiverImpl<T>: Send + Sync + 'static {
+    /// Poll to receive the next value. It is ready with `None` when every sender is dropped and the channel is
+    /// drained.
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>>;
+}
+
+/// The sending half of an unbounded channel.
+pub struct UnboundedSender<T> {
+    inner: Box<dyn UnboundedSenderImpl<T>>,
+}
+
+impl<T> UnboundedSender<T> {
+    pub fn new(inner: impl UnboundedSenderImpl<T>) -> Self {
+        Self { inner: Box::new(inner) }
+    }
+
+    /// Send `value` without waiting. It fails only if the receiver is dropped.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.inner.send(value)
+    }
+}
+
+impl<T> Clone for UnboundedSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.boxed_clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for UnboundedSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnboundedSender").finish_non_exhaustive()
+    }
+}
+
+/// The receiving half of an unbounded channel.
+pub struct UnboundedReceiver<T> {
+    inner: Box<dyn UnboundedReceiverImpl<T>>,
+}
+
+impl<T> UnboundedReceiver<T> {
+    pub fn new(inner: impl UnboundedReceiverImpl<T>) -> Self {
+        Self { inner: Box::new(inner) }
+    }
+
+    /// Receive the next value, or `None` if every sender is dropped and the channel is drained.
+    pub async fn recv(&mut self) -> Option<T> {
+        futures::future::poll_fn(|cx| self.inner.poll_recv(cx)).await
+    }
+}
+
+impl<T> fmt::Debug for UnboundedReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnboundedReceiver").finish_non_exhaustive()
+    }
+}
+
+/// The sending half of a bounded channel.
+pub struct Sender<T> {
+    inner: Box<dyn SenderImpl<T>>,
+}
+
+impl<T> Sender<T> {
+    pub fn new(inner: impl SenderImpl<T>) -> Self {
+        Self { inner: Box::new(inner) }
+    }
+
+    /// Send `value`, waiting for a free slot if the channel is full. It fails only if the receiver is dropped.
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.inner.send(value).await
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.boxed_clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+/// The receiving half of a bounded channel.
+pub struct Receiver<T> {
+    inner: Box<dyn ReceiverImpl<T>>,
+}
+
+impl<T> Receiver<T> {
+    pub fn new(inner: impl ReceiverImpl<T>) -> Self {
+        Self { inner: Box::new(inner) }
+    }
+
+    /// Receive the next value, or `None` if every sender is dropped and the channel is drained.
+    pub async fn recv(&mut self) -> Option<T> {
+        futures::future::poll_fn(|cx| self.inner.poll_recv(cx)).await
+    }
+
+    /// Receive the next value in a synchronous context, blocking the current thread.
+    ///
+    /// It must not be called from within an async task, which it would block.
+    pub fn blocking_recv(&mut self) -> Option<T> {
+        futures::executor::block_on(self.recv())
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
+
+impl<T: Send + 'static> UnboundedSenderImpl<T> for tokio::sync::mpsc::UnboundedSender<T> {
+    fn send(&self, value: T) -> Result<(), SendError<T>> {
+        tokio::sync::mpsc::UnboundedSender::send(self, value).map_err(|e| SendError(e.0))
+    }
+
+    fn boxed_clone(&self) -> Box<dyn UnboundedSenderImpl<T>> {
+        Box::new(self.clone())
+    }
+}
+
+impl<T: Send + 'static> UnboundedReceiverImpl<T> for tokio::sync::mpsc::UnboundedReceiver<T> {
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        tokio::sync::mpsc::UnboundedReceiver::poll_recv(self, cx)
+    }
+}
+
+impl<T: Send + 'static> SenderImpl<T> for tokio::sync::mpsc::Sender<T> {
+    fn send(&self, value: T) -> BoxFuture<'_, Result<(), SendError<T>>> {
+        async move { tokio::sync::mpsc::Sender::send(self, value).await.map_err(|e| SendError(e.0)) }.boxed()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn SenderImpl<T>> {
+        Box::new(self.clone())
+    }
+}
+
+impl<T: Send + 'static> ReceiverImpl<T> for tokio::sync::mpsc::Receiver<T> {
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        tokio::sync::mpsc::Receiver::poll_recv(self, cx)
+    }
+}
@@ -0,0 +1,148 @@
+//! Single-producer, multi-consumer channels that only keep the last value sent, created by
+//! [`AsyncRuntime::watch()`](`crate::AsyncRuntime::watch`).
+//!
+//! A runtime provides its own channel by implementing the `*Impl` traits for it. Openraft publishes the metrics and
+//! the committed log id through the wrappers of them.
+
+use std::fmt;
+use std::ops::Deref;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+
+pub use crate::async_runtime::mpsc::SendError;
+
+/// The error returned by [`Receiver::changed()`] if the sender is dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("channel closed")]
+pub struct RecvError;
+
+/// A reference to the value in a watch channel.
+///
+/// The channel may be locked while it is held, thus it should be dropped soon.
+pub struct Ref<'a, T> {
+    inner: Box<dyn Deref<Target = T> + 'a>,
+}
+
+impl<'a, T> Ref<'a, T> {
+    pub fn new(inner: impl Deref<Target = T> + 'a) -> Self {
+        Self { inner: Box::new(inner) }
+    }
+}
+
+impl<'a, T> Deref for Ref<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.inner.deref()
+    }
+}
+
+/// The sending half of a watch channel, implemented by an [`AsyncRuntime`](`crate::AsyncRuntime`).
+pub trait SenderImpl<T>: Send + Sync + 'static {
+    /// Replace the value in the channel with `value`, and notify the receivers. It fails if every receiver is
+    /// dropped.
+    fn send(&self, value: T) -> Result<(), SendError<T>>;
+
+    /// Borrow the last value sent.
+    fn borrow(&self) -> Ref<'_, T>;
+}
+
+/// The receiving half of a watch channel, implemented by an [`AsyncRuntime`](`crate::AsyncRuntime`).
+pub trait ReceiverImpl<T>: Send + Sync + 'static {
+    /// Borrow the last value sent.
+    fn borrow(&self) -> Ref<'_, T>;
+
+    /// Wait until a value is sent, that is not yet seen by this receiver. It fails if the sender is dropped.
+    fn changed(&mut self) -> BoxFuture<'_, Result<(), RecvError>>;
+
+    /// Create another receiver of the same channel, which has seen the same values as this one.
+    fn boxed_clone(&self) -> Box<dyn ReceiverImpl<T>>;
+}
+
+/// The sending half of a watch channel.
+pub struct Sender<T> {
+    inner: Box<dyn SenderImpl<T>>,
+}
+
+impl<T> Sender<T> {
+    pub fn new(inner: impl SenderImpl<T>) -> Self {
+        Self { inner: Box::new(inner) }
+    }
+
+    /// Replace the value in the channel with `value`, and notify the receivers. It fails if every receiver is
+    /// dropped.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.inner.send(value)
+    }
+
+    /// Borrow the last value sent.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.inner.borrow()
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+/// The receiving half of a watch channel.
+pub struct Receiver<T> {
+    inner: Box<dyn ReceiverImpl<T>>,
+}
+
+impl<T> Receiver<T> {
+    pub fn new(inner: impl ReceiverImpl<T>) -> Self {
+        Self { inner: Box::new(inner) }
+    }
+
+    /// Borrow the last value sent.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.inner.borrow()
+    }
+
+    /// Wait until a value is sent, that is not yet seen by this receiver. It fails if the sender is dropped.
+    pub async fn changed(&mut self) -> Result<(), RecvError> {
+        self.inner.changed().await
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.boxed_clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
+
+impl<T: Send + Sync + 'static> SenderImpl<T> for tokio::sync::watch::Sender<T> {
+    fn send(&self, value: T) -> Result<(), SendError<T>> {
+        tokio::sync::watch::Sender::send(self, value).map_err(|e| SendError(e.0))
+    }
+
+    fn borrow(&self) -> Ref<'_, T> {
+        Ref::new(tokio::sync::watch::Sender::borrow(self))
+    }
+}
+
+impl<T: Send + Sync + 'static> ReceiverImpl<T> for tokio::sync::watch::Receiver<T> {
+    fn borrow(&self) -> Ref<'_, T> {
+        Ref::new(tokio::sync::watch::Receiver::borrow(self))
+    }
+
+    fn changed(&mut self) -> BoxFuture<'_, Result<(), RecvError>> {
+        async move { tokio::sync::watch::Receiver::changed(self).await.map_err(|_| RecvError) }.boxed()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ReceiverImpl<T>> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,774 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::future::Future;
+use std::io::Cursor;
+use std::ops::RangeBounds;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::RwLock;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::time::Duration;
+
+use anyerror::AnyError;
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use futures::StreamExt;
+
+use crate::async_runtime::broadcast;
+use crate::async_runtime::mpsc;
+use crate::async_runtime::watch;
+use crate::error::AppendEntriesError;
+use crate::error::InstallSnapshotError;
+use crate::error::RPCError;
+use crate::error::StreamingError;
+use crate::error::Unreachable;
+use crate::error::VoteError;
+use crate::network::SnapshotStreaming;
+use crate::raft::AppendEntriesRequest;
+use crate::raft::AppendEntriesResponse;
+use crate::raft::ClientWriteRequest;
+use crate::raft::InstallSnapshotRequest;
+use crate::raft::InstallSnapshotResponse;
+use crate::raft::SnapshotResponse;
+use crate::raft::VoteRequest;
+use crate::raft::VoteResponse;
+use crate::storage::Adaptor;
+use crate::storage::LogState;
+use crate::storage::Snapshot;
+use crate::timer::RaftTimer;
+use crate::timer::Timeout;
+use crate::AsyncRuntime;
+use crate::Config;
+use crate::EffectiveMembership;
+use crate::Entry;
+use crate::EntryPayload;
+use crate::ErrorSubject;
+use crate::ErrorVerb;
+use crate::LogId;
+use crate::Node;
+use crate::Raft;
+use crate::RaftLogReader;
+use crate::RaftNetwork;
+use crate::RaftNetworkFactory;
+use crate::RaftSnapshotBuilder;
+use crate::RaftStorage;
+use crate::SnapshotMeta;
+use crate::StateMachineChanges;
+use crate::StorageError;
+use crate::StorageIOError;
+use crate::TokioRuntime;
+use crate::Vote;
+
+#[async_entry::test(worker_threads = 3)]
+async fn test_tokio_runtime() -> anyhow::Result<()> {
+    check_runtime::<TokioRuntime>().await
+}
+
+/// The runtime abstraction is complete if openraft's timers, tasks and channels work without a tokio runtime at all.
+#[test]
+fn test_thread_runtime() -> anyhow::Result<()> {
+    futures::executor::block_on(check_runtime::<ThreadRuntime>())
+}
+
+/// `RaftCore`, its timers and the channels between openraft's tasks run without a tokio runtime.
+#[test]
+fn test_raft_on_thread_runtime() -> anyhow::Result<()> {
+    futures::executor::block_on(async {
+        let config = Arc::new(Config::default().validate()?);
+        let (log_store, state_machine) = Adaptor::new(SumStore::default());
+        let raft = Raft::new(1, config, UnreachableNetwork, log_store, state_machine);
+
+        raft.initialize(BTreeSet::from([1])).await?;
+        raft.wait(Some(Duration::from_secs(3))).current_leader(1, "single node becomes leader").await?;
+
+        tracing::info!("--- write and apply");
+        {
+            let resp = raft.client_write(ClientWriteRequest::new(EntryPayload::Normal(2))).await?;
+            assert_eq!(2, resp.data);
+
+            let resp = raft.client_write(ClientWriteRequest::new(EntryPayload::Normal(3))).await?;
+            assert_eq!(5, resp.data);
+
+            raft.wait(Some(Duration::from_secs(1)))
+                .applied_index(resp.log_id.index, "writes are applied")
+                .await?;
+        }
+
+        raft.shutdown().await?;
+        Ok(())
+    })
+}
+
+async fn check_runtime<RT: AsyncRuntime>() -> anyhow::Result<()> {
+    tracing::info!("--- spawn, join the result");
+    {
+        let res = RT::spawn(async { 3u64 }).await?;
+        assert_eq!(3, res);
+    }
+
+    tracing::info!("--- a panicked task");
+    {
+        let res = RT::spawn(async { panic!("foo") }).await;
+        let err = res.unwrap_err();
+        assert!(RT::is_panic(&err));
+    }
+
+    tracing::info!("--- sleep, sleep_until");
+    {
+        let now = RT::now();
+        RT::sleep(Duration::from_millis(200)).await;
+        assert!(RT::now() - now >= Duration::from_millis(200));
+
+        let now = RT::now();
+        RT::sleep_until(now + Duration::from_millis(200)).await;
+        assert!(RT::now() - now >= Duration::from_millis(200));
+    }
+
+    tracing::info!("--- timeout");
+    {
+        let res = RT::timeout(Duration::from_millis(200), async { 3u64 }).await;
+        assert_eq!(3, res?);
+
+        let res = RT::timeout(Duration::from_millis(200), RT::sleep(Duration::from_secs(10))).await;
+        assert!(res.is_err());
+    }
+
+    tracing::info!("--- oneshot: dropping the sender wakes up the receiver");
+    {
+        let (tx, rx) = RT::oneshot();
+        RT::spawn(async move {
+            RT::sleep(Duration::from_millis(10)).await;
+            drop(tx);
+        });
+        let _ = rx.await;
+    }
+
+    tracing::info!("--- mpsc channels work across tasks of the runtime");
+    {
+        let (tx, mut rx) = RT::mpsc_unbounded();
+        RT::spawn(async move {
+            RT::sleep(Duration::from_millis(10)).await;
+            tx.send(5u64).unwrap();
+        });
+        assert_eq!(Some(5), rx.recv().await);
+        assert_eq!(None, rx.recv().await, "the sender is dropped");
+
+        let (tx, mut rx) = RT::mpsc(1);
+        RT::spawn(async move {
+            for i in 0..3u64 {
+                tx.send(i).await.unwrap();
+            }
+        });
+        for i in 0..3u64 {
+            assert_eq!(Some(i), rx.recv().await);
+        }
+        assert_eq!(None, rx.recv().await, "the sender is dropped");
+    }
+
+    tracing::info!("--- watch: a receiver sees the last value");
+    {
+        let (tx, mut rx) = RT::watch(0u64);
+        assert_eq!(0, *rx.borrow());
+
+        RT::spawn(async move {
+            RT::sleep(Duration::from_millis(10)).await;
+            tx.send(2).unwrap();
+        });
+        rx.changed().await?;
+        assert_eq!(2, *rx.borrow());
+        assert!(rx.changed().await.is_err(), "the sender is dropped");
+    }
+
+    tracing::info!("--- broadcast: every receiver receives every value");
+    {
+        let (tx, mut rx1) = RT::broadcast(2);
+        let mut rx2 = tx.subscribe();
+        assert_eq!(2, tx.receiver_count());
+
+        tx.send(0u64)?;
+        assert_eq!(0, rx1.recv().await?);
+
+        tx.send(1)?;
+        tx.send(2)?;
+        assert_eq!(1, rx1.recv().await?);
+        assert_eq!(2, rx1.recv().await?);
+
+        assert_eq!(Err(broadcast::RecvError::Lagged(1)), rx2.recv().await, "0 is dropped");
+        assert_eq!(1, rx2.recv().await?);
+        assert_eq!(2, rx2.recv().await?);
+
+        RT::spawn(async move {
+            RT::sleep(Duration::from_millis(10)).await;
+            tx.send(3).unwrap();
+        });
+        assert_eq!(3, rx1.recv().await?);
+        assert_eq!(
+            Err(broadcast::RecvError::Closed),
+            rx1.recv().await,
+            "the sender is dropped"
+        );
+    }
+
+    tracing::info!("--- Timeout is driven by the runtime");
+    {
+        let (tx, rx) = RT::oneshot();
+        let now = RT::now();
+        let _t = Timeout::<RT>::new(move || drop(tx), Duration::from_millis(200));
+        let _ = rx.await;
+        assert!(RT::now() - now >= Duration::from_millis(200));
+    }
+
+    Ok(())
+}
+
+/// A runtime without any executor of its own: every task runs on a new thread with `block_on()`, and every timer
+/// is a sleeping thread. Its channels are those of `futures`, or hand-written ones where `futures` has none.
+#[derive(Debug, Default)]
+struct ThreadRuntime;
+
+#[derive(Debug, thiserror::Error)]
+enum ThreadJoinError {
+    #[error("task panicked")]
+    Panicked,
+
+    #[error("task is gone")]
+    Canceled,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("deadline has elapsed")]
+struct Elapsed;
+
+impl AsyncRuntime for ThreadRuntime {
+    type JoinError = ThreadJoinError;
+    type TimeoutError = Elapsed;
+    type Instant = std::time::Instant;
+    type OneshotSender = futures::channel::oneshot::Sender<()>;
+    type OneshotReceiver = futures::channel::oneshot::Receiver<()>;
+
+    fn spawn<T>(future: T) -> BoxFuture<'static, Result<T::Output, Self::JoinError>>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        let (tx, rx) = futures::channel::oneshot::channel();
+
+        std::thread::spawn(move || {
+            let res = std::panic::catch_unwind(AssertUnwindSafe(|| futures::executor::block_on(future)));
+            let _ = tx.send(res.map_err(|_| ThreadJoinError::Panicked));
+        });
+
+        async move { rx.await.unwrap_or(Err(ThreadJoinError::Canceled)) }.boxed()
+    }
+
+    fn is_panic(join_error: &Self::JoinError) -> bool {
+        matches!(join_error, ThreadJoinError::Panicked)
+    }
+
+    fn sleep(duration: Duration) -> BoxFuture<'static, ()> {
+        let (tx, rx) = futures::channel::oneshot::channel::<()>();
+
+        std::thread::spawn(move || {
+            std::thread::sleep(duration);
+            let _ = tx.send(());
+        });
+
+        async move {
+            let _ = rx.await;
+        }
+        .boxed()
+    }
+
+    fn sleep_until(deadline: Self::Instant) -> BoxFuture<'static, ()> {
+        Self::sleep(deadline.saturating_duration_since(std::time::Instant::now()))
+    }
+
+    fn timeout<'a, F>(duration: Duration, future: F) -> BoxFuture<'a, Result<F::Output, Self::TimeoutError>>
+    where F: Future + Send + 'a {
+        let fut = future.boxed();
+        let sleep = Self::sleep(duration);
+
+        async move {
+            match futures::future::select(fut, sleep).await {
+                futures::future::Either::Left((output, _)) => Ok(output),
+                futures::future::Either::Right(_) => Err(Elapsed),
+            }
+        }
+        .boxed()
+    }
+
+    fn oneshot() -> (Self::OneshotSender, Self::OneshotReceiver) {
+        futures::channel::oneshot::channel()
+    }
+
+    fn mpsc_unbounded<T: Send + 'static>() -> (mpsc::UnboundedSender<T>, mpsc::UnboundedReceiver<T>) {
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        (mpsc::UnboundedSender::new(tx), mpsc::UnboundedReceiver::new(rx))
+    }
+
+    fn mpsc<T: Send + 'static>(buffer: usize) -> (mpsc::Sender<T>, mpsc::Receiver<T>) {
+        let (tx, rx) = futures::channel::mpsc::channel(buffer);
+        (mpsc::Sender::new(tx), mpsc::Receiver::new(rx))
+    }
+
+    fn watch<T: Send + Sync + 'static>(init: T) -> (watch::Sender<T>, watch::Receiver<T>) {
+        let shared = Arc::new(WatchShared {
+            value: RwLock::new(init),
+            state: Mutex::new(WatchState {
+                version: 0,
+                closed: false,
+                receivers: 1,
+                wakers: vec![],
+            }),
+        });
+
+        let rx = WatchReceiver {
+            shared: shared.clone(),
+            seen: 0,
+        };
+        (watch::Sender::new(WatchSender(shared)), watch::Receiver::new(rx))
+    }
+
+    fn broadcast<T: Clone + Send + Sync + 'static>(capacity: usize) -> (broadcast::Sender<T>, broadcast::Receiver<T>) {
+        let shared = Arc::new(Mutex::new(BroadcastState {
+            capacity,
+            values: VecDeque::new(),
+            head: 0,
+            senders: 1,
+            receivers: 1,
+            wakers: vec![],
+        }));
+
+        let rx = BroadcastReceiver {
+            shared: shared.clone(),
+            next: 0,
+        };
+        (
+            broadcast::Sender::new(BroadcastSender(shared)),
+            broadcast::Receiver::new(rx),
+        )
+    }
+}
+
+impl<T: Send + 'static> mpsc::UnboundedSenderImpl<T> for futures::channel::mpsc::UnboundedSender<T> {
+    fn send(&self, value: T) -> Result<(), mpsc::SendError<T>> {
+        self.unbounded_send(value).map_err(|e| mpsc::SendError(e.into_inner()))
+    }
+
+    fn boxed_clone(&self) -> Box<dyn mpsc::UnboundedSenderImpl<T>> {
+        Box::new(self.clone())
+    }
+}
+
+impl<T: Send + 'static> mpsc::UnboundedReceiverImpl<T> for futures::channel::mpsc::UnboundedReceiver<T> {
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.poll_next_unpin(cx)
+    }
+}
+
+impl<T: Send + 'static> mpsc::SenderImpl<T> for futures::channel::mpsc::Sender<T> {
+    fn send(&self, value: T) -> BoxFuture<'_, Result<(), mpsc::SendError<T>>> {
+        // Sending takes `&mut`: send with a clone, which takes a slot of its own.
+        let mut tx = self.clone();
+
+        async move {
+            if futures::future::poll_fn(|cx| tx.poll_ready(cx)).await.is_err() {
+                return Err(mpsc::SendError(value));
+            }
+            tx.try_send(value).map_err(|e| mpsc::SendError(e.into_inner()))
+        }
+        .boxed()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn mpsc::SenderImpl<T>> {
+        Box::new(self.clone())
+    }
+}
+
+impl<T: Send + 'static> mpsc::ReceiverImpl<T> for futures::channel::mpsc::Receiver<T> {
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.poll_next_unpin(cx)
+    }
+}
+
+/// A watch channel without tokio: the value behind a lock, and a version that is bumped by every send.
+struct WatchShared<T> {
+    value: RwLock<T>,
+    state: Mutex<WatchState>,
+}
+
+struct WatchState {
+    version: u64,
+    closed: bool,
+    receivers: usize,
+    wakers: Vec<Waker>,
+}
+
+struct WatchSender<T>(Arc<WatchShared<T>>);
+
+struct WatchReceiver<T> {
+    shared: Arc<WatchShared<T>>,
+
+    /// The version of the last value this receiver has seen.
+    seen: u64,
+}
+
+impl<T: Send + Sync + 'static> watch::SenderImpl<T> for WatchSender<T> {
+    fn send(&self, value: T) -> Result<(), watch::SendError<T>> {
+        let mut st = self.0.state.lock().unwrap();
+        if st.receivers == 0 {
+            return Err(watch::SendError(value));
+        }
+
+        *self.0.value.write().unwrap() = value;
+        st.version += 1;
+        st.wakers.drain(..).for_each(Waker::wake);
+        Ok(())
+    }
+
+    fn borrow(&self) -> watch::Ref<'_, T> {
+        watch::Ref::new(self.0.value.read().unwrap())
+    }
+}
+
+impl<T> Drop for WatchSender<T> {
+    fn drop(&mut self) {
+        let mut st = self.0.state.lock().unwrap();
+        st.closed = true;
+        st.wakers.drain(..).for_each(Waker::wake);
+    }
+}
+
+impl<T: Send + Sync + 'static> watch::ReceiverImpl<T> for WatchReceiver<T> {
+    fn borrow(&self) -> watch::Ref<'_, T> {
+        watch::Ref::new(self.shared.value.read().unwrap())
+    }
+
+    fn changed(&mut self) -> BoxFuture<'_, Result<(), watch::RecvError>> {
+        futures::future::poll_fn(move |cx| {
+            let mut st = self.shared.state.lock().unwrap();
+            if st.version != self.seen {
+                self.seen = st.version;
+                return Poll::Ready(Ok(()));
+            }
+            if st.closed {
+                return Poll::Ready(Err(watch::RecvError));
+            }
+            st.wakers.push(cx.waker().clone());
+            Poll::Pending
+        })
+        .boxed()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn watch::ReceiverImpl<T>> {
+        self.shared.state.lock().unwrap().receivers += 1;
+        Box::new(WatchReceiver {
+            shared: self.shared.clone(),
+            seen: self.seen,
+        })
+    }
+}
+
+impl<T> Drop for WatchReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().receivers -= 1;
+    }
+}
+
+/// A broadcast channel without tokio: the last `capacity` values and the sequence number of the oldest one.
+struct BroadcastState<T> {
+    capacity: usize,
+    values: VecDeque<T>,
+
+    /// The sequence number of `values[0]`.
+    head: u64,
+
+    senders: usize,
+    receivers: usize,
+    wakers: Vec<Waker>,
+}
+
+struct BroadcastSender<T>(Arc<Mutex<BroadcastState<T>>>);
+
+struct BroadcastReceiver<T> {
+    shared: Arc<Mutex<BroadcastState<T>>>,
+
+    /// The sequence number of the next value to receive.
+    next: u64,
+}
+
+impl<T: Clone + Send + Sync + 'static> broadcast::SenderImpl<T> for BroadcastSender<T> {
+    fn send(&self, value: T) -> Result<usize, broadcast::SendError<T>> {
+        let mut st = self.0.lock().unwrap();
+        if st.receivers == 0 {
+            return Err(broadcast::SendError(value));
+        }
+
+        st.values.push_back(value);
+        if st.values.len() > st.capacity {
+            st.values.pop_front();
+            st.head += 1;
+        }
+        st.wakers.drain(..).for_each(Waker::wake);
+        Ok(st.receivers)
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<T> {
+        let mut st = self.0.lock().unwrap();
+        st.receivers += 1;
+        broadcast::Receiver::new(BroadcastReceiver {
+            shared: self.0.clone(),
+            next: st.head + st.values.len() as u64,
+        })
+    }
+
+    fn receiver_count(&self) -> usize {
+        self.0.lock().unwrap().receivers
+    }
+
+    fn boxed_clone(&self) -> Box<dyn broadcast::SenderImpl<T>> {
+        self.0.lock().unwrap().senders += 1;
+        Box::new(BroadcastSender(self.0.clone()))
+    }
+}
+
+impl<T> Drop for BroadcastSender<T> {
+    fn drop(&mut self) {
+        let mut st = self.0.lock().unwrap();
+        st.senders -= 1;
+        if st.senders == 0 {
+            st.wakers.drain(..).for_each(Waker::wake);
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> broadcast::ReceiverImpl<T> for BroadcastReceiver<T> {
+    fn recv(&mut self) -> BoxFuture<'_, Result<T, broadcast::RecvError>> {
+        futures::future::poll_fn(move |cx| {
+            let mut st = self.shared.lock().unwrap();
+            if self.next < st.head {
+                let lagged = st.head - self.next;
+                self.next = st.head;
+                return Poll::Ready(Err(broadcast::RecvError::Lagged(lagged)));
+            }
+
+            if let Some(value) = st.values.get((self.next - st.head) as usize) {
+                self.next += 1;
+                return Poll::Ready(Ok(value.clone()));
+            }
+            if st.senders == 0 {
+                return Poll::Ready(Err(broadcast::RecvError::Closed));
+            }
+            st.wakers.push(cx.waker().clone());
+            Poll::Pending
+        })
+        .boxed()
+    }
+}
+
+impl<T> Drop for BroadcastReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.lock().unwrap().receivers -= 1;
+    }
+}
+
+crate::declare_raft_types!(
+    /// A type config whose tasks, timers and channels are those of [`ThreadRuntime`].
+    ThreadConfig: D = u64,
+    R = u64,
+    NodeId = u64,
+    AsyncRuntime = ThreadRuntime
+);
+
+/// An in-memory storage whose state machine sums up the applied values, and responds with the sum.
+#[derive(Clone, Default)]
+struct SumStore {
+    inner: Arc<Mutex<SumStoreState>>,
+}
+
+#[derive(Default)]
+struct SumStoreState {
+    vote: Option<Vote<u64>>,
+    log: BTreeMap<u64, Entry<ThreadConfig>>,
+    last_purged_log_id: Option<LogId<u64>>,
+    last_applied_log: Option<LogId<u64>>,
+    last_membership: EffectiveMembership<u64>,
+    sum: u64,
+}
+
+fn snapshot_unsupported() -> StorageError<u64> {
+    StorageIOError::new(
+        ErrorSubject::StateMachine,
+        ErrorVerb::Read,
+        AnyError::error("snapshot is not supported"),
+    )
+    .into()
+}
+
+#[async_trait]
+impl RaftLogReader<ThreadConfig> for SumStore {
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<Entry<ThreadConfig>>, StorageError<u64>> {
+        let st = self.inner.lock().unwrap();
+        Ok(st.log.range(range).map(|(_, ent)| ent.clone()).collect())
+    }
+
+    async fn get_log_state(&mut self) -> Result<LogState<ThreadConfig>, StorageError<u64>> {
+        let st = self.inner.lock().unwrap();
+        let last = st.log.values().next_back().map(|ent| ent.log_id.clone());
+
+        Ok(LogState {
+            last_purged_log_id: st.last_purged_log_id.clone(),
+            last_log_id: last.or_else(|| st.last_purged_log_id.clone()),
+        })
+    }
+}
+
+#[async_trait]
+impl RaftSnapshotBuilder<ThreadConfig, Cursor<Vec<u8>>> for SumStore {
+    async fn build_snapshot(&mut self) -> Result<Snapshot<u64, Cursor<Vec<u8>>>, StorageError<u64>> {
+        Err(snapshot_unsupported())
+    }
+}
+
+#[async_trait]
+impl RaftStorage<ThreadConfig> for SumStore {
+    type LogReader = Self;
+    type SnapshotBuilder = Self;
+
+    async fn save_vote(&mut self, vote: &Vote<u64>) -> Result<(), StorageError<u64>> {
+        self.inner.lock().unwrap().vote = Some(vote.clone());
+        Ok(())
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<u64>>, StorageError<u64>> {
+        Ok(self.inner.lock().unwrap().vote.clone())
+    }
+
+    async fn get_log_reader(&mut self) -> Self::LogReader {
+        self.clone()
+    }
+
+    async fn append_to_log(&mut self, entries: &[&Entry<ThreadConfig>]) -> Result<(), StorageError<u64>> {
+        let mut st = self.inner.lock().unwrap();
+        for ent in entries {
+            st.log.insert(ent.log_id.index, (*ent).clone());
+        }
+        Ok(())
+    }
+
+    async fn delete_conflict_logs_since(&mut self, log_id: LogId<u64>) -> Result<(), StorageError<u64>> {
+        self.inner.lock().unwrap().log.split_off(&log_id.index);
+        Ok(())
+    }
+
+    async fn purge_logs_upto(&mut self, log_id: LogId<u64>) -> Result<(), StorageError<u64>> {
+        let mut st = self.inner.lock().unwrap();
+        st.log = st.log.split_off(&(log_id.index + 1));
+        st.last_purged_log_id = Some(log_id);
+        Ok(())
+    }
+
+    async fn last_applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<u64>>, EffectiveMembership<u64>), StorageError<u64>> {
+        let st = self.inner.lock().unwrap();
+        Ok((st.last_applied_log.clone(), st.last_membership.clone()))
+    }
+
+    async fn apply_to_state_machine(
+        &mut self,
+        entries: &[&Entry<ThreadConfig>],
+    ) -> Result<Vec<u64>, StorageError<u64>> {
+        let mut st = self.inner.lock().unwrap();
+        let mut res = Vec::with_capacity(entries.len());
+
+        for ent in entries {
+            st.last_applied_log = Some(ent.log_id.clone());
+
+            match &ent.payload {
+                EntryPayload::Blank => {}
+                EntryPayload::Normal(v) => st.sum += v,
+                EntryPayload::Membership(mem) => {
+                    st.last_membership = EffectiveMembership::new(Some(ent.log_id.clone()), mem.clone());
+                }
+            }
+            res.push(st.sum);
+        }
+        Ok(res)
+    }
+
+    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder {
+        self.clone()
+    }
+
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<Cursor<Vec<u8>>>, StorageError<u64>> {
+        Err(snapshot_unsupported())
+    }
+
+    async fn install_snapshot(
+        &mut self,
+        _meta: &SnapshotMeta<u64>,
+        _snapshot: Box<Cursor<Vec<u8>>>,
+    ) -> Result<StateMachineChanges<ThreadConfig>, StorageError<u64>> {
+        Err(snapshot_unsupported())
+    }
+
+    async fn get_current_snapshot(&mut self) -> Result<Option<Snapshot<u64, Cursor<Vec<u8>>>>, StorageError<u64>> {
+        Ok(None)
+    }
+}
+
+/// The network of a single node cluster: there is no other node to reach.
+struct UnreachableNetwork;
+
+fn no_other_node() -> Unreachable {
+    Unreachable::new(&AnyError::error("there is no other node"))
+}
+
+#[async_trait]
+impl RaftNetworkFactory<ThreadConfig> for UnreachableNetwork {
+    type Network = Self;
+
+    async fn connect(&mut self, _target: u64, _node: Option<&Node>) -> Self::Network {
+        UnreachableNetwork
+    }
+}
+
+#[async_trait]
+impl RaftNetwork<ThreadConfig> for UnreachableNetwork {
+    async fn send_append_entries(
+        &mut self,
+        _rpc: AppendEntriesRequest<ThreadConfig>,
+    ) -> Result<AppendEntriesResponse<u64>, RPCError<u64, AppendEntriesError<u64>>> {
+        Err(no_other_node().into())
+    }
+
+    async fn send_install_snapshot(
+        &mut self,
+        _rpc: InstallSnapshotRequest<ThreadConfig>,
+    ) -> Result<InstallSnapshotResponse<u64>, RPCError<u64, InstallSnapshotError<u64>>> {
+        Err(no_other_node().into())
+    }
+
+    async fn send_vote(&mut self, _rpc: VoteRequest<u64>) -> Result<VoteResponse<u64>, RPCError<u64, VoteError<u64>>> {
+        Err(no_other_node().into())
+    }
+
+    async fn full_snapshot(
+        &mut self,
+        _vote: Vote<u64>,
+        _snapshot: Snapshot<u64, Cursor<Vec<u8>>>,
+        _streaming: SnapshotStreaming,
+    ) -> Result<SnapshotResponse<u64>, StreamingError<u64>> {
+        Err(no_other_node().into())
+    }
+}
@@ -8,6 +8,29 @@ pub enum ChangeMembers<NID: NodeId> {
     Add(BTreeSet<NID>),
     Remove(BTreeSet<NID>),
     Replace(BTreeSet<NID>),
+
+    /// Change to exactly the given voters and learners.
+    ///
+    /// Every node in `voters` or `learners` has to be already known to the cluster, i.e., added as a learner.
+    /// A node in both sets is a voter. A node in neither set is removed from the cluster.
+    /// A witness in `learners` but not in `voters` is refused, since it can not become a learner.
+    Exact {
+        voters: BTreeSet<NID>,
+        learners: BTreeSet<NID>,
+    },
+
+    /// Remove the given learners from the cluster, and stop replicating to them. The voters are not changed.
+    ///
+    /// A voter removed with `turn_to_learner` keeps receiving logs as a learner, until it is removed with this.
+    /// A node in the set that is a voter or not in the cluster is ignored.
+    RemoveLearners(BTreeSet<NID>),
+
+    /// Turn the given learners into witness voters, see
+    /// [`Membership::with_witness()`](`crate::Membership::with_witness`).
+    ///
+    /// Every node in the set has to be already added as a learner, or be a voter. A witness can not be turned back
+    /// into a learner.
+    AddWitnesses(BTreeSet<NID>),
 }
 
 /// Convert a series of ids to a `Replace` operation.
@@ -29,6 +52,9 @@ impl<NID: NodeId> ChangeMembers<NID> {
             ChangeMembers::Replace(c) => c,
             ChangeMembers::Add(add_members) => old.union(&add_members).cloned().collect::<BTreeSet<_>>(),
             ChangeMembers::Remove(remove_members) => old.difference(&remove_members).cloned().collect::<BTreeSet<_>>(),
+            ChangeMembers::Exact { voters, .. } => voters,
+            ChangeMembers::RemoveLearners(_) => old.clone(),
+            ChangeMembers::AddWitnesses(witnesses) => old.union(&witnesses).cloned().collect::<BTreeSet<_>>(),
         }
     }
 }
@@ -0,0 +1,85 @@
+extern crate test;
+
+use std::sync::Arc;
+
+use test::black_box;
+use test::Bencher;
+
+use crate::compression::CompressedEntries;
+use crate::compression::CompressionAlgo;
+use crate::Entry;
+use crate::EntryPayload;
+use crate::LeaderId;
+use crate::LogId;
+
+crate::declare_raft_types!(
+   pub(crate) Config: D = String, R = (), NodeId = u64
+);
+
+/// 1000 JSON payloads, which is a typical batch of `max_payload_entries`.
+fn json_entries() -> Vec<Arc<Entry<Config>>> {
+    (0..1000)
+        .map(|i| Entry {
+            log_id: LogId::new(LeaderId::new(1, 1), i),
+            payload: EntryPayload::Normal(format!(
+                r#"{{"table":"users","op":"update","key":"user-{}","value":{{"name":"foo","age":{},"active":true}}}}"#,
+                i,
+                i % 100
+            )),
+            context: None,
+        })
+        .map(Arc::new)
+        .collect()
+}
+
+fn bench_compress(b: &mut Bencher, algo: Option<CompressionAlgo>) {
+    let entries = json_entries();
+    let raw = bincode::serialize(&entries).unwrap();
+
+    b.bytes = raw.len() as u64;
+    b.iter(|| match algo {
+        None => black_box(bincode::serialize(black_box(&entries)).unwrap().len()),
+        Some(algo) => black_box(CompressedEntries::compress(algo, black_box(&entries)).unwrap().data.len()),
+    })
+}
+
+#[bench]
+fn compress_none(b: &mut Bencher) {
+    bench_compress(b, None)
+}
+
+#[bench]
+fn compress_lz4(b: &mut Bencher) {
+    bench_compress(b, Some(CompressionAlgo::Lz4))
+}
+
+#[bench]
+fn compress_zstd(b: &mut Bencher) {
+    bench_compress(b, Some(CompressionAlgo::Zstd))
+}
+
+/// The compressed sizes of `json_entries()`, printed once to read along with the throughput of the benchmarks.
+#[test]
+fn compressed_size() {
+    let entries = json_entries();
+    let raw = bincode::serialize(&entries).unwrap().len();
+
+    for algo in [CompressionAlgo::Lz4, CompressionAlgo::Zstd] {
+        let size = CompressedEntries::compress(algo, &entries).unwrap().data.len();
+        println!("{:?}: {} bytes for {} raw bytes", algo, size, raw);
+
+        assert!(size < raw, "{:?} shrinks repetitive JSON payloads", algo);
+    }
+}
+
+#[bench]
+fn decompress_lz4(b: &mut Bencher) {
+    let compressed = CompressedEntries::compress(CompressionAlgo::Lz4, &json_entries()).unwrap();
+    b.iter(|| black_box(compressed.decompress::<Config>().unwrap().len()))
+}
+
+#[bench]
+fn decompress_zstd(b: &mut Bencher) {
+    let compressed = CompressedEntries::compress(CompressionAlgo::Zstd, &json_entries()).unwrap();
+    b.iter(|| black_box(compressed.decompress::<Config>().unwrap().len()))
+}
@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+
+use crate::compression::CompressedEntries;
+use crate::compression::CompressionAlgo;
+use crate::Entry;
+use crate::EntryPayload;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+
+crate::declare_raft_types!(
+   pub(crate) Config: D = String, R = (), NodeId = u64
+);
+
+fn entries() -> Vec<Arc<Entry<Config>>> {
+    let mut entries = vec![
+        Entry {
+            log_id: LogId::new(LeaderId::new(1, 1), 1),
+            payload: EntryPayload::Blank,
+            context: None,
+        },
+        Entry {
+            log_id: LogId::new(LeaderId::new(1, 1), 2),
+            payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2,3}], None)),
+            context: None,
+        },
+    ];
+
+    for i in 3..100 {
+        entries.push(Entry {
+            log_id: LogId::new(LeaderId::new(1, 1), i),
+            payload: EntryPayload::Normal(format!(r#"{{"client":"foo","serial":{},"status":"ok"}}"#, i)),
+            context: None,
+        });
+    }
+
+    entries.into_iter().map(Arc::new).collect()
+}
+
+#[test]
+fn test_compress_round_trip() -> anyhow::Result<()> {
+    let entries = entries();
+    let want = entries.iter().map(|e| (e.log_id.clone(), e.payload.clone())).collect::<Vec<_>>();
+
+    for algo in [CompressionAlgo::Lz4, CompressionAlgo::Zstd] {
+        let compressed = CompressedEntries::compress(algo, &entries)?;
+        assert_eq!(algo, compressed.algo);
+        assert_eq!(entries.len() as u64, compressed.count);
+
+        let got = compressed.decompress::<Config>()?;
+        let got = got.into_iter().map(|e| (e.log_id, e.payload)).collect::<Vec<_>>();
+
+        assert_eq!(want, got, "{}: round trip yields identical entries", algo);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_compress_reduces_size() -> anyhow::Result<()> {
+    let entries = entries();
+    let raw = bincode::serialize(&entries)?;
+
+    for algo in [CompressionAlgo::Lz4, CompressionAlgo::Zstd] {
+        let compressed = CompressedEntries::compress(algo, &entries)?;
+        assert!(
+            compressed.data.len() < raw.len() / 2,
+            "{}: compressed: {}, raw: {}",
+            algo,
+            compressed.data.len(),
+            raw.len()
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_decompress_corrupted() -> anyhow::Result<()> {
+    let entries = entries();
+
+    for algo in [CompressionAlgo::Lz4, CompressionAlgo::Zstd] {
+        let mut compressed = CompressedEntries::compress(algo, &entries)?;
+        compressed.data.truncate(compressed.data.len() / 2);
+
+        let res = compressed.decompress::<Config>();
+        assert!(res.is_err(), "{}: corrupted data can not be decompressed", algo);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+//! Compression of the log entries sent with an `AppendEntriesRequest`.
+//!
+//! Entries are serialized with `bincode` before being compressed, thus compressing requires feature `compression`,
+//! which enables `serde`. It only changes what is sent over the network: the logs in the storage are never
+//! compressed.
+
+#[cfg(feature = "bench")]
+#[cfg(feature = "compression")]
+#[cfg(test)]
+mod bench;
+
+#[cfg(feature = "compression")]
+#[cfg(test)]
+mod compression_test;
+
+use std::fmt;
+#[cfg(feature = "compression")] use std::sync::Arc;
+
+use crate::error::CompressionError;
+use crate::Entry;
+use crate::RaftTypeConfig;
+
+/// An algorithm to compress the entries in an `AppendEntriesRequest` with, see
+/// [`Config::replication_compression`](`crate::Config::replication_compression`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum CompressionAlgo {
+    /// LZ4: fast, with a moderate compression ratio.
+    Lz4,
+
+    /// Zstandard: a better compression ratio at the cost of more CPU.
+    Zstd,
+}
+
+impl fmt::Display for CompressionAlgo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompressionAlgo::Lz4 => write!(f, "lz4"),
+            CompressionAlgo::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+/// Log entries that are serialized and then compressed with a [`CompressionAlgo`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct CompressedEntries {
+    pub algo: CompressionAlgo,
+
+    /// The number of entries.
+    pub count: u64,
+
+    /// The compressed bytes.
+    pub data: Vec<u8>,
+}
+
+impl CompressedEntries {
+    /// Serialize and compress `entries` with `algo`.
+    #[cfg(feature = "compression")]
+    pub fn compress<C: RaftTypeConfig>(
+        algo: CompressionAlgo,
+        entries: &[Arc<Entry<C>>],
+    ) -> Result<Self, CompressionError> {
+        let raw = bincode::serialize(entries).map_err(|e| CompressionError::new(algo, &e))?;
+
+        let data = match algo {
+            CompressionAlgo::Lz4 => lz4_flex::compress_prepend_size(&raw),
+            CompressionAlgo::Zstd => {
+                zstd::encode_all(raw.as_slice(), 0).map_err(|e| CompressionError::new(algo, &e))?
+            }
+        };
+
+        Ok(Self {
+            algo,
+            count: entries.len() as u64,
+            data,
+        })
+    }
+
+    /// Decompress and deserialize the entries.
+    ///
+    /// Without feature `compression`, it always returns an error.
+    pub fn decompress<C: RaftTypeConfig>(&self) -> Result<Vec<Entry<C>>, CompressionError> {
+        #[cfg(feature = "compression")]
+        {
+            let algo = self.algo;
+
+            let raw = match algo {
+                CompressionAlgo::Lz4 => {
+                    lz4_flex::decompress_size_prepended(&self.data).map_err(|e| CompressionError::new(algo, &e))?
+                }
+                CompressionAlgo::Zstd => {
+                    zstd::decode_all(self.data.as_slice()).map_err(|e| CompressionError::new(algo, &e))?
+                }
+            };
+
+            bincode::deserialize(&raw).map_err(|e| CompressionError::new(algo, &e))
+        }
+
+        #[cfg(not(feature = "compression"))]
+        {
+            Err(CompressionError::new(
+                self.algo,
+                &anyerror::AnyError::error("feature `compression` is not enabled"),
+            ))
+        }
+    }
+}
@@ -1,10 +1,14 @@
 //! Raft runtime configuration.
 
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
 use clap::Parser;
-use rand::thread_rng;
-use rand::Rng;
 
+use crate::compression::CompressionAlgo;
 use crate::config::error::ConfigError;
+use crate::rng;
 
 /// Log compaction and snapshot policy.
 ///
@@ -18,6 +22,167 @@ pub enum SnapshotPolicy {
     /// A snapshot will be generated once the log has grown the specified number of logs since
     /// the last snapshot.
     LogsSinceLast(u64),
+
+    /// A snapshot will be generated once the specified number of bytes of logs are appended since the last snapshot.
+    ///
+    /// The size of a log is its application data size, as returned by
+    /// [`RaftTypeConfig::data_size()`](`crate::RaftTypeConfig::data_size`).
+    SinceLastBytes(u64),
+
+    /// A snapshot will be generated once the specified time elapsed since the last snapshot, if any log is applied
+    /// since then.
+    ///
+    /// It is checked on every tick, see [`Config::tick_interval`], thus a snapshot may be generated upto one tick
+    /// late.
+    Interval(Duration),
+
+    /// A snapshot will be generated when any of the policies triggers.
+    Any(Vec<SnapshotPolicy>),
+
+    /// A snapshot will be generated when the application defined predicate returns `true`.
+    ///
+    /// The predicate is evaluated every time logs are applied to the state machine. Unlike `LogsSinceLast`, the
+    /// leader does not send a snapshot to a lagging follower unless the logs the follower lacks are purged.
+    ///
+    /// This variant can not be parsed from command line or serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(SnapshotPredicate),
+}
+
+/// Where a target that lags too far behind receives a snapshot from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum SnapshotSource {
+    /// The leader always sends its own snapshot.
+    Leader,
+
+    /// The leader asks the target to fetch a snapshot from a voter that has replicated the logs included in the
+    /// leader's snapshot, with [`RaftNetwork::get_snapshot_from`](`crate::RaftNetwork::get_snapshot_from`).
+    ///
+    /// The leader sends its own snapshot if there is no such voter, or the target fails to fetch a snapshot that is
+    /// at least as new as the leader's one.
+    Voters,
+}
+
+/// How many voters have to acknowledge a log before the leader commits it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum CommitQuorum {
+    /// A majority of the voters in every config of the membership, as the standard raft does.
+    Majority,
+
+    /// Every voter in the membership.
+    ///
+    /// A committed log is then stored on every voter, and is not lost unless every voter loses its storage. But no
+    /// log is committed while any voter is down or partitioned away: the cluster can not make progress until it is
+    /// back or is removed from the membership, which itself has to be committed by every voter. Elections and the
+    /// leader lease still require only a majority.
+    ///
+    /// A witness stores no log payload, thus it is not required, although it still counts toward the majority. In a
+    /// joint membership, every voter of every config is required.
+    All,
+}
+
+/// The state passed to a [`SnapshotPolicy::Custom`] predicate to decide whether to build a snapshot.
+///
+/// `Config` is not generic over the node id type, thus the last applied log id is provided as its term and index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotPolicyContext {
+    /// The term of the last applied log.
+    pub last_applied_term: u64,
+
+    /// The index of the last applied log.
+    pub last_applied_index: u64,
+
+    /// The number of logs applied since the last snapshot.
+    pub logs_since_last: u64,
+
+    /// The number of bytes of logs appended since the last snapshot, see [`SnapshotPolicy::SinceLastBytes`].
+    pub bytes_since_last: u64,
+
+    /// The time elapsed since the last snapshot is built or installed, or since this node starts if there is none.
+    pub since_last: Duration,
+}
+
+impl SnapshotPolicy {
+    /// Returns `true` if a snapshot should be built.
+    pub fn should_snapshot(&self, ctx: &SnapshotPolicyContext) -> bool {
+        match self {
+            SnapshotPolicy::LogsSinceLast(threshold) => ctx.logs_since_last >= *threshold,
+            SnapshotPolicy::SinceLastBytes(threshold) => ctx.bytes_since_last >= *threshold,
+            SnapshotPolicy::Interval(interval) => ctx.logs_since_last > 0 && ctx.since_last >= *interval,
+            SnapshotPolicy::Any(policies) => policies.iter().any(|p| p.should_snapshot(ctx)),
+            SnapshotPolicy::Custom(predicate) => predicate.check(ctx),
+        }
+    }
+
+    /// The number of logs a follower may lag behind before the leader sends it a snapshot, if there is one.
+    ///
+    /// Only `LogsSinceLast` defines it. With other policies, a snapshot is sent only when the logs to replicate are
+    /// purged.
+    pub(crate) fn logs_since_last_threshold(&self) -> Option<u64> {
+        match self {
+            SnapshotPolicy::LogsSinceLast(threshold) => Some(*threshold),
+            SnapshotPolicy::Any(policies) => policies.iter().filter_map(|p| p.logs_since_last_threshold()).min(),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if it has to be checked on every tick, i.e., it depends on time.
+    pub(crate) fn is_time_based(&self) -> bool {
+        match self {
+            SnapshotPolicy::Interval(_) => true,
+            SnapshotPolicy::Any(policies) => policies.iter().any(|p| p.is_time_based()),
+            _ => false,
+        }
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        match self {
+            SnapshotPolicy::SinceLastBytes(0) => Err(ConfigError::ZeroSnapshotPolicy {
+                policy: format!("{:?}", self),
+            }),
+            SnapshotPolicy::Interval(d) if d.is_zero() => Err(ConfigError::ZeroSnapshotPolicy {
+                policy: format!("{:?}", self),
+            }),
+            SnapshotPolicy::Any(policies) if policies.is_empty() => Err(ConfigError::ZeroSnapshotPolicy {
+                policy: format!("{:?}", self),
+            }),
+            SnapshotPolicy::Any(policies) => policies.iter().try_for_each(|p| p.validate()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An application defined predicate to decide whether to build a snapshot, see [`SnapshotPolicy::Custom`].
+///
+/// Two predicates are equal only if they are clones of the same one.
+#[derive(Clone)]
+pub struct SnapshotPredicate(Arc<dyn Fn(&SnapshotPolicyContext) -> bool + Send + Sync>);
+
+impl SnapshotPredicate {
+    pub fn new<F>(f: F) -> Self
+    where F: Fn(&SnapshotPolicyContext) -> bool + Send + Sync + 'static {
+        Self(Arc::new(f))
+    }
+
+    /// Returns `true` if a snapshot should be built.
+    pub fn check(&self, ctx: &SnapshotPolicyContext) -> bool {
+        (self.0)(ctx)
+    }
+}
+
+impl fmt::Debug for SnapshotPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SnapshotPredicate(..)")
+    }
+}
+
+impl PartialEq for SnapshotPredicate {
+    fn eq(&self, other: &Self) -> bool {
+        // Compare only the data pointers: vtable pointers of the same type may differ.
+        std::ptr::eq(Arc::as_ptr(&self.0) as *const u8, Arc::as_ptr(&other.0) as *const u8)
+    }
 }
 
 /// Parse number with unit such as 5.3 KB
@@ -30,27 +195,77 @@ fn parse_bytes_with_unit(src: &str) -> Result<u64, ConfigError> {
     Ok(res.get_bytes() as u64)
 }
 
+/// Parse a snapshot policy, or several policies separated by `,`, which are combined with [`SnapshotPolicy::Any`].
 fn parse_snapshot_policy(src: &str) -> Result<SnapshotPolicy, ConfigError> {
+    let mut policies = src.split(',').map(parse_single_snapshot_policy).collect::<Result<Vec<_>, _>>()?;
+
+    if policies.len() == 1 {
+        Ok(policies.remove(0))
+    } else {
+        Ok(SnapshotPolicy::Any(policies))
+    }
+}
+
+fn parse_commit_quorum(src: &str) -> Result<CommitQuorum, ConfigError> {
+    match src {
+        "majority" => Ok(CommitQuorum::Majority),
+        "all" => Ok(CommitQuorum::All),
+        _ => Err(ConfigError::InvalidCommitQuorum {
+            invalid: src.to_string(),
+            syntax: "majority|all".to_string(),
+        }),
+    }
+}
+
+fn parse_snapshot_source(src: &str) -> Result<SnapshotSource, ConfigError> {
+    match src {
+        "leader" => Ok(SnapshotSource::Leader),
+        "voters" => Ok(SnapshotSource::Voters),
+        _ => Err(ConfigError::InvalidSnapshotSource {
+            invalid: src.to_string(),
+            syntax: "leader|voters".to_string(),
+        }),
+    }
+}
+
+fn parse_compression_algo(src: &str) -> Result<CompressionAlgo, ConfigError> {
+    match src {
+        "lz4" => Ok(CompressionAlgo::Lz4),
+        "zstd" => Ok(CompressionAlgo::Zstd),
+        _ => Err(ConfigError::InvalidCompressionAlgo {
+            invalid: src.to_string(),
+            syntax: "lz4|zstd".to_string(),
+        }),
+    }
+}
+
+fn parse_single_snapshot_policy(src: &str) -> Result<SnapshotPolicy, ConfigError> {
+    let syntax = "since_last:<num>|since_last_bytes:<size>|interval:<ms>";
+
     let elts = src.split(':').collect::<Vec<_>>();
     if elts.len() != 2 {
         return Err(ConfigError::InvalidSnapshotPolicy {
-            syntax: "since_last:<num>".to_string(),
+            syntax: syntax.to_string(),
             invalid: src.to_string(),
         });
     }
 
-    if elts[0] != "since_last" {
-        return Err(ConfigError::InvalidSnapshotPolicy {
-            syntax: "since_last:<num>".to_string(),
+    let parse_u64 = |s: &str| {
+        s.parse::<u64>().map_err(|e| ConfigError::InvalidNumber {
             invalid: src.to_string(),
-        });
-    }
+            reason: e.to_string(),
+        })
+    };
 
-    let n_logs = elts[1].parse::<u64>().map_err(|e| ConfigError::InvalidNumber {
-        invalid: src.to_string(),
-        reason: e.to_string(),
-    })?;
-    Ok(SnapshotPolicy::LogsSinceLast(n_logs))
+    match elts[0] {
+        "since_last" => Ok(SnapshotPolicy::LogsSinceLast(parse_u64(elts[1])?)),
+        "since_last_bytes" => Ok(SnapshotPolicy::SinceLastBytes(parse_bytes_with_unit(elts[1])?)),
+        "interval" => Ok(SnapshotPolicy::Interval(Duration::from_millis(parse_u64(elts[1])?))),
+        _ => Err(ConfigError::InvalidSnapshotPolicy {
+            syntax: syntax.to_string(),
+            invalid: src.to_string(),
+        }),
+    }
 }
 
 /// The runtime configuration for a Raft node.
@@ -92,14 +307,81 @@ pub struct Config {
     #[clap(long, env = "RAFT_ELECTION_TIMEOUT_MAX", default_value = "300")]
     pub election_timeout_max: u64,
 
+    /// The maximum extra random delay in milliseconds added to every election timeout.
+    ///
+    /// An election timeout is picked from `[election_timeout_min, election_timeout_max)`, then a delay picked from
+    /// `[0, election_timeout_jitter]` is added to it. A wider spread makes it less likely that nodes started at the
+    /// same time, e.g., after a cluster-wide restart, time out together and split the vote again and again.
+    ///
+    /// Both values are drawn from `rand::thread_rng()`, a cryptographically secure RNG seeded from the OS entropy
+    /// for every thread. Thus the timeouts of different nodes or processes are independent of each other: no node
+    /// shares a deterministic sequence with another. With feature `manual-clock`, a test can replay them with
+    /// [`SeededRng`](`crate::testing::SeededRng`).
+    #[clap(long, env = "RAFT_ELECTION_TIMEOUT_JITTER", default_value = "0")]
+    pub election_timeout_jitter: u64,
+
     /// The heartbeat interval in milliseconds at which leaders will send heartbeats to followers
     #[clap(long, env = "RAFT_HEARTBEAT_INTERVAL", default_value = "50")]
     pub heartbeat_interval: u64,
 
+    /// The interval in milliseconds at which `RaftCore` checks its timers.
+    ///
+    /// Elections, check-quorum, the leadership transfer timeout and the time based snapshot policy are evaluated on
+    /// a tick, thus any of them fires upto one tick after it is due. Heartbeats are not driven by the tick: a leader
+    /// sends them every `heartbeat_interval` on their own timers.
+    ///
+    /// `None`, by default, means `min(heartbeat_interval * 3 / 2, election_timeout_min / 2)`. An explicit value
+    /// must not be 0 and `election_timeout_min` must be at least 2 ticks, or an election timeout can not be told
+    /// apart from the tick granularity. It can not be changed with [`Raft::update_config()`].
+    ///
+    /// [`Raft::update_config()`]: `crate::Raft::update_config`
+    #[clap(long, env = "RAFT_TICK_INTERVAL")]
+    pub tick_interval: Option<u64>,
+
+    /// The timeout for sending an AppendEntries RPC that carries logs, in millisecond.
+    ///
+    /// An AppendEntries RPC without logs, i.e., a heartbeat, times out in `heartbeat_interval`.
+    #[clap(long, env = "RAFT_APPEND_ENTRIES_TIMEOUT", default_value = "50")]
+    pub append_entries_timeout: u64,
+
     /// The timeout for sending a snapshot segment, in millisecond
     #[clap(long, env = "RAFT_INSTALL_SNAPSHOT_TIMEOUT", default_value = "200")]
     pub install_snapshot_timeout: u64,
 
+    /// The delay in milliseconds before the first retry to replicate to a target that is unreachable.
+    ///
+    /// See [`max_replication_backoff`](`Config::max_replication_backoff`).
+    #[clap(long, env = "RAFT_REPLICATION_BACKOFF_BASE", default_value = "50")]
+    pub replication_backoff_base: u64,
+
+    /// The maximum delay in milliseconds before retrying to replicate to a target that is unreachable.
+    ///
+    /// After consecutive [`Unreachable`](`crate::error::Unreachable`) errors from a target, the leader doubles the
+    /// delay before the next retry, starting from `replication_backoff_base`, up to this value, with jitter. The delay
+    /// is reset once an RPC succeeds. Other targets are not affected. A value not greater than
+    /// `replication_backoff_base` retries at a fixed interval. An application can replace this policy with
+    /// [`RaftNetwork::backoff()`](`crate::RaftNetwork::backoff`).
+    #[clap(long, env = "RAFT_MAX_REPLICATION_BACKOFF", default_value = "500")]
+    pub max_replication_backoff: u64,
+
+    /// The number of purged logs a target may lack, at which the leader sends it a snapshot without further probing.
+    ///
+    /// When a target rejects an AppendEntries, the leader probes for the last log it has. If the target may lack at
+    /// least this many logs that are already purged by the leader, the leader stops probing and sends a snapshot at
+    /// once. A smaller value prefers snapshot more aggressively. `0` disables it: a snapshot is sent only when a log
+    /// the target needs is found to be purged.
+    #[clap(long, env = "RAFT_SNAPSHOT_CATCH_UP_GAP", default_value = "0")]
+    pub snapshot_catch_up_gap: u64,
+
+    /// The minimum interval in milliseconds between two replication progress reports of a target.
+    ///
+    /// A replication task reports its progress to [`RaftMetrics::replication_progress`] at most once in this
+    /// interval, so that a busy cluster does not flood the metrics channel.
+    ///
+    /// [`RaftMetrics::replication_progress`]: `crate::RaftMetrics::replication_progress`
+    #[clap(long, env = "RAFT_REPLICATION_PROGRESS_INTERVAL", default_value = "100")]
+    pub replication_progress_interval: u64,
+
     /// The maximum number of entries per payload allowed to be transmitted during replication
     ///
     /// If this is too low, it will take longer for the nodes to be brought up to
@@ -107,6 +389,62 @@ pub struct Config {
     #[clap(long, env = "RAFT_MAX_PAYLOAD_ENTRIES", default_value = "300")]
     pub max_payload_entries: u64,
 
+    /// The maximum number of bytes of the entries per payload allowed to be transmitted during replication, e.g.,
+    /// `4MiB`, to keep an AppendEntries below the message size limit of the transport.
+    ///
+    /// The size of an entry is [`RaftTypeConfig::entry_size()`](`crate::RaftTypeConfig::entry_size`). A payload
+    /// carries at least one entry even if it is larger. Not limited by default.
+    #[clap(long, env = "RAFT_MAX_PAYLOAD_BYTES", parse(try_from_str=parse_bytes_with_unit))]
+    pub max_payload_bytes: Option<u64>,
+
+    /// The number of logs a replication stream reads from the log storage in one call, when they are not in the log
+    /// cache.
+    ///
+    /// The logs read beyond those sent in one AppendEntries are kept by the stream for the following requests, thus a
+    /// follower that is far behind is caught up with fewer storage reads, at the cost of memory. A value not greater
+    /// than `max_payload_entries`, such as the default `0`, reads only the logs to send.
+    #[clap(long, env = "RAFT_REPLICATION_PREFETCH_ENTRIES", default_value = "0")]
+    pub replication_prefetch_entries: u64,
+
+    /// The maximum number of AppendEntries RPCs a replication stream keeps outstanding to a target.
+    ///
+    /// With `1`, a replication stream sends the next batch of logs only after the previous one is acknowledged. A
+    /// larger value pipelines the batches, which raises the throughput on a link with a long round trip time. A
+    /// stream opens this many connections to its target, and always sends one RPC at a time while it is searching
+    /// for the last log the target matches.
+    #[clap(long, env = "RAFT_MAX_INFLIGHT_APPENDS", default_value = "1")]
+    pub max_inflight_appends: u64,
+
+    /// The algorithm to compress the entries sent to a follower with: `lz4` or `zstd`. Not compressed by default.
+    ///
+    /// A follower decompresses received entries before storing them, thus every node has to be built with feature
+    /// `compression`. The logs in the storage are not affected.
+    #[clap(long, env = "RAFT_REPLICATION_COMPRESSION", parse(try_from_str=parse_compression_algo))]
+    pub replication_compression: Option<CompressionAlgo>,
+
+    /// The maximum number of recent logs a leader keeps in memory for the replication streams.
+    ///
+    /// Every replication stream reads the cached logs without copying them, and reads the log storage only for the
+    /// logs that are not cached. A log is evicted once every target has replicated it, or the oldest logs are evicted
+    /// when the cache is full. The hit rate is reported in `RaftMetrics::log_cache`.
+    ///
+    /// `0` disables the cache, which is the default: a leader then reads every log it replicates from the log storage.
+    #[clap(long, env = "RAFT_LOG_CACHE_MAX_ENTRIES", default_value = "0")]
+    pub log_cache_max_entries: u64,
+
+    /// The maximum size in bytes of the recent logs a leader keeps in memory for the replication streams, e.g.,
+    /// `64MiB`.
+    ///
+    /// The size of a log is measured with [`RaftTypeConfig::data_size()`](`crate::RaftTypeConfig::data_size`).
+    /// It applies only when the cache is enabled with `log_cache_max_entries`. `0` disables the cache.
+    #[clap(
+        long,
+        env = "RAFT_LOG_CACHE_MAX_BYTES",
+        default_value = "64MiB",
+        parse(try_from_str=parse_bytes_with_unit)
+    )]
+    pub log_cache_max_bytes: u64,
+
     /// The distance behind in log replication a follower must fall before it is considered lagging
     ///
     /// Once a replication stream transition into line-rate state, the target node will be considered safe to join a
@@ -114,7 +452,26 @@ pub struct Config {
     #[clap(long, env = "RAFT_REPLICATION_LAG_THRESHOLD", default_value = "1000")]
     pub replication_lag_threshold: u64,
 
+    /// How many voters have to acknowledge a log before it is committed: `majority` or `all`, see [`CommitQuorum`].
+    #[clap(
+        long,
+        env = "RAFT_COMMIT_QUORUM",
+        default_value = "majority",
+        parse(try_from_str=parse_commit_quorum)
+    )]
+    pub commit_quorum: CommitQuorum,
+
+    /// The timeout in milliseconds for a blocking `Raft::add_learner()` to wait for the learner to catch up.
+    ///
+    /// If the learner does not lag behind by no more than `replication_lag_threshold` within this time, e.g., it is
+    /// unreachable, `add_learner()` returns `AddLearnerError::NotCaughtUp`, and the learner stays in the membership.
+    #[clap(long, env = "RAFT_ADD_LEARNER_TIMEOUT", default_value = "10000")]
+    pub add_learner_timeout: u64,
+
     /// The snapshot policy to use for a Raft node.
+    ///
+    /// On command line it is one of `since_last:<num>`, `since_last_bytes:<size>` or `interval:<ms>`, or several of
+    /// them separated by `,` to snapshot when any of them triggers.
     #[clap(
         long,
         env = "RAFT_SNAPSHOT_POLICY",
@@ -147,13 +504,161 @@ pub struct Config {
     #[clap(long, env = "RAFT_SNAPSHOT_MAX_CHUNK_SIZE", default_value = "3MiB", parse(try_from_str=parse_bytes_with_unit))]
     pub snapshot_max_chunk_size: u64,
 
+    /// The maximum number of snapshot bytes a leader sends to a target per second, e.g., `10MiB`.
+    ///
+    /// Snapshot chunks are held back when the limit is exceeded, and heartbeats are sent to the target meanwhile.
+    /// Not limited by default.
+    #[clap(long, env = "RAFT_INSTALL_SNAPSHOT_MAX_BYTES_PER_SEC", parse(try_from_str=parse_bytes_with_unit))]
+    pub install_snapshot_max_bytes_per_sec: Option<u64>,
+
+    /// Whether to send CRC32 checksums of every snapshot chunk and of the whole snapshot.
+    ///
+    /// A target verifies a chunk before writing it, and the whole snapshot before installing it. A mismatch is
+    /// returned to the leader, which re-sends the chunk or the snapshot.
+    /// To checksum the whole snapshot, the leader reads the snapshot once more before streaming it.
+    #[clap(long, env = "RAFT_ENABLE_SNAPSHOT_CHECKSUM")]
+    pub enable_snapshot_checksum: bool,
+
+    /// Where a lagging target receives a snapshot from: `leader` or `voters`, see [`SnapshotSource`].
+    #[clap(
+        long,
+        env = "RAFT_SNAPSHOT_SOURCE",
+        default_value = "leader",
+        parse(try_from_str=parse_snapshot_source)
+    )]
+    pub snapshot_source: SnapshotSource,
+
     /// The maximum number of applied logs to keep before purging
     #[clap(long, env = "RAFT_MAX_APPLIED_LOG_TO_KEEP", default_value = "1000")]
     pub max_applied_log_to_keep: u64,
 
+    /// The number of logs included in the latest snapshot to keep when `keep_unsnapshoted_log` is set.
+    ///
+    /// A follower that lags behind by less than this number of logs can still be caught up with AppendEntries,
+    /// instead of a snapshot transfer. `0` means every log included in the snapshot can be purged.
+    #[clap(long, env = "RAFT_MAX_IN_SNAPSHOT_LOG_TO_KEEP", default_value = "0")]
+    pub max_in_snapshot_log_to_keep: u64,
+
     /// The minimal number of applied logs to purge in a batch.
     #[clap(long, default_value = "1")]
     pub purge_batch_size: u64,
+
+    /// Whether to run a pre-vote phase before starting an election.
+    ///
+    /// With pre-vote, a node whose election timeout expires first asks the other voters whether they would grant it
+    /// a vote, without increasing its term. Only when a quorum would grant it does the real election start.
+    /// This prevents a node that rejoins after being partitioned from disrupting a healthy leader.
+    ///
+    /// It is enabled by default. Disable it with `--enable-prevote=false`.
+    #[clap(long, env = "RAFT_ENABLE_PREVOTE", default_value_t = true, action = clap::ArgAction::Set)]
+    pub enable_prevote: bool,
+
+    /// The maximum clock drift between nodes in milliseconds, assumed when serving reads with a lease.
+    ///
+    /// A leader that was acknowledged by a quorum at time `t` may serve linearizable reads locally until
+    /// `t + election_timeout_min - clock_drift_bound`. See [`Raft::get_read_lease()`](`crate::Raft::get_read_lease`).
+    /// A leader has no lease if it is not less than `election_timeout_min`.
+    #[clap(long, env = "RAFT_CLOCK_DRIFT_BOUND", default_value = "10")]
+    pub clock_drift_bound: u64,
+
+    /// The timeout in milliseconds for a leadership transfer.
+    ///
+    /// If the target does not catch up with the leader and start an election within this time, the transfer is
+    /// aborted and the leader resumes accepting client writes.
+    #[clap(long, env = "RAFT_TRANSFER_LEADER_TIMEOUT", default_value = "300")]
+    pub transfer_leader_timeout: u64,
+
+    /// Whether a leader steps down when it is not acknowledged by a quorum within the max election timeout.
+    ///
+    /// Without check-quorum, a leader that is partitioned away from the cluster keeps accepting client writes that can
+    /// never be committed by it.
+    #[clap(long, env = "RAFT_ENABLE_CHECK_QUORUM")]
+    pub enable_check_quorum: bool,
+
+    /// Whether `Raft::change_membership()` refuses a change that would leave the cluster without a live quorum.
+    ///
+    /// When enabled, the leader refuses the change with `ChangeMembershipError::QuorumNotEnough` unless every config
+    /// the change goes through has a quorum of live voters. A voter is live only if it has acknowledged a request that
+    /// this leader sent within the last `election_timeout_max` milliseconds; the leader itself is always live. A voter
+    /// whose liveness is unknown, e.g., one that has not responded yet since this node became the leader, is taken as
+    /// offline. Thus a change proposed right after an election may be refused until the voters have responded.
+    #[clap(long, env = "RAFT_GUARD_MEMBERSHIP_QUORUM")]
+    pub guard_membership_quorum: bool,
+
+    /// Whether a follower or candidate does not start an election when its election timeout expires.
+    ///
+    /// With automatic elections disabled, an election is started only by
+    /// [`Raft::trigger_elect()`](`crate::Raft::trigger_elect`). It lets an external orchestrator, or a test, fully
+    /// control which node becomes the leader.
+    #[clap(long, env = "RAFT_DISABLE_AUTO_ELECT")]
+    pub disable_auto_elect: bool,
+
+    /// Whether a leader does not send heartbeats on their own.
+    ///
+    /// By default a leader broadcasts a heartbeat to every target every `heartbeat_interval`, an AppendEntries that
+    /// carries only the vote and the committed log id. It is sent by a dedicated task for every target, thus it is
+    /// delivered in time even when the replication stream to the target is busy sending a large batch of logs or a
+    /// snapshot. An acknowledged heartbeat extends the leader lease and is counted by check-quorum. When disabled,
+    /// only the requests sent by the replication streams refresh the lease on the followers.
+    #[clap(long, env = "RAFT_DISABLE_HEARTBEAT")]
+    pub disable_heartbeat: bool,
+
+    /// Whether a learner added with `Raft::add_learner()` is promoted to a voter once it catches up with the leader.
+    ///
+    /// A learner is considered caught up when it lags behind the leader by no more than `replication_lag_threshold`.
+    /// The promotion status of every learner is reported in `RaftMetrics::learner_promotions`.
+    #[clap(long, env = "RAFT_AUTO_PROMOTE_LEARNER")]
+    pub auto_promote_learner: bool,
+
+    /// The number of consecutive heartbeat intervals a learner has to stay caught up before it is promoted.
+    ///
+    /// A learner whose lag goes beyond `replication_lag_threshold` starts over. With the default `0`, a learner is
+    /// promoted as soon as it catches up.
+    #[clap(long, env = "RAFT_LEARNER_PROMOTION_ROUNDS", default_value = "0")]
+    pub learner_promotion_rounds: u64,
+
+    /// Whether a non-leader forwards `Raft::client_write()` to the leader it knows of.
+    ///
+    /// When enabled, the write is sent with `RaftNetwork::forward_client_write()` and the leader's result is returned
+    /// to the caller. If no leader is known, or the forwarding fails, `ForwardToLeader` is returned as usual.
+    #[clap(long, env = "RAFT_ENABLE_FORWARD_CLIENT_WRITE")]
+    pub enable_forward_client_write: bool,
+
+    /// The timeout in milliseconds for a forwarded client write to be responded by the leader.
+    #[clap(long, env = "RAFT_FORWARD_CLIENT_WRITE_TIMEOUT", default_value = "1000")]
+    pub forward_client_write_timeout: u64,
+
+    /// The maximum number of times a client write is forwarded.
+    ///
+    /// A forwarded write may be forwarded again if the leader changes while it is in flight. Once this limit is
+    /// reached, `ForwardToLeader` is returned.
+    #[clap(long, env = "RAFT_MAX_FORWARD_CLIENT_WRITE_HOPS", default_value = "3")]
+    pub max_forward_client_write_hops: u64,
+
+    /// The timeout in milliseconds for `Raft::shutdown_gracefully()` to wait for the accepted logs to be applied.
+    ///
+    /// When it expires, the node shuts down anyway.
+    #[clap(long, env = "RAFT_GRACEFUL_SHUTDOWN_TIMEOUT", default_value = "3000")]
+    pub graceful_shutdown_timeout: u64,
+
+    /// The number of times `RaftCore` retries a storage operation that fails with a transient error.
+    ///
+    /// See [`StorageError::Transient`](`crate::StorageError::Transient`). When the retries are exhausted, the error
+    /// is treated as fatal and `RaftCore` shuts down. `0` disables retrying.
+    #[clap(long, env = "RAFT_STORAGE_RETRY_LIMIT", default_value = "3")]
+    pub storage_retry_limit: u64,
+
+    /// The delay in milliseconds before the first retry of a storage operation that fails with a transient error.
+    ///
+    /// The delay is doubled after every retry.
+    #[clap(long, env = "RAFT_STORAGE_RETRY_INTERVAL", default_value = "50")]
+    pub storage_retry_interval: u64,
+
+    /// The maximum number of events buffered for a subscriber of `Raft::subscribe_applied()`.
+    ///
+    /// A subscriber that falls behind more than this loses the oldest events.
+    #[clap(long, env = "RAFT_APPLIED_EVENT_BUFFER_SIZE", default_value = "1024")]
+    pub applied_event_buffer_size: u64,
 }
 
 impl Default for Config {
@@ -163,9 +668,33 @@ impl Default for Config {
 }
 
 impl Config {
-    /// Generate a new random election timeout within the configured min & max.
+    /// Generate a new random election timeout within the configured min & max, plus a random jitter upto
+    /// `election_timeout_jitter`.
+    ///
+    /// I.e., `election_timeout_min + rand(0..election_timeout_max - election_timeout_min) +
+    /// rand(0..=election_timeout_jitter)`. The timeout is checked on every tick, see [`Config::tick_interval`].
+    ///
+    /// The randomness comes from the thread local, OS seeded RNG, see [`Config::election_timeout_jitter`].
     pub fn new_rand_election_timeout(&self) -> u64 {
-        thread_rng().gen_range(self.election_timeout_min..self.election_timeout_max)
+        let timeout = rng::gen_range(self.election_timeout_min..self.election_timeout_max);
+
+        if self.election_timeout_jitter == 0 {
+            return timeout;
+        }
+
+        timeout + rng::gen_range(0..=self.election_timeout_jitter)
+    }
+
+    /// The tick interval in milliseconds in effect, i.e., [`Config::tick_interval`] or the default derived from
+    /// `heartbeat_interval` and `election_timeout_min`.
+    pub fn effective_tick_interval(&self) -> u64 {
+        match self.tick_interval {
+            Some(t) => t,
+            None => std::cmp::max(
+                1,
+                std::cmp::min(self.heartbeat_interval * 3 / 2, self.election_timeout_min / 2),
+            ),
+        }
     }
 
     pub fn build(args: &[&str]) -> Result<Config, ConfigError> {
@@ -189,10 +718,99 @@ impl Config {
             });
         }
 
+        if let Some(tick_interval) = self.tick_interval {
+            if tick_interval == 0 {
+                return Err(ConfigError::TickIntervalIs0);
+            }
+
+            if self.election_timeout_min < tick_interval * 2 {
+                return Err(ConfigError::ElectionTimeoutLTTwoTicks {
+                    election_timeout_min: self.election_timeout_min,
+                    tick_interval,
+                });
+            }
+        }
+
         if self.max_payload_entries == 0 {
             return Err(ConfigError::MaxPayloadIs0);
         }
 
+        if self.replication_backoff_base == 0 {
+            return Err(ConfigError::ReplicationBackoffBaseIs0);
+        }
+
+        if self.max_payload_bytes == Some(0) {
+            return Err(ConfigError::MaxPayloadBytesIs0);
+        }
+
+        if self.max_inflight_appends == 0 {
+            return Err(ConfigError::MaxInflightAppendsIs0);
+        }
+
+        if self.install_snapshot_max_bytes_per_sec == Some(0) {
+            return Err(ConfigError::InstallSnapshotMaxBytesPerSecIs0);
+        }
+
+        if let Some(algo) = self.replication_compression {
+            if !cfg!(feature = "compression") {
+                return Err(ConfigError::CompressionNotEnabled { algo });
+            }
+        }
+
+        self.snapshot_policy.validate()?;
+
         Ok(self)
     }
 }
+
+/// The timing knobs of a [`Config`] that can be changed on a running node, with
+/// [`Raft::update_config()`](`crate::Raft::update_config`).
+///
+/// A field that is `None` is left unchanged. The updated config is validated with the same rules as
+/// [`Config::validate()`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConfigUpdate {
+    /// See [`Config::heartbeat_interval`].
+    pub heartbeat_interval: Option<u64>,
+
+    /// See [`Config::election_timeout_min`].
+    pub election_timeout_min: Option<u64>,
+
+    /// See [`Config::election_timeout_max`].
+    pub election_timeout_max: Option<u64>,
+
+    /// See [`Config::max_payload_entries`].
+    pub max_payload_entries: Option<u64>,
+
+    /// See [`Config::snapshot_policy`].
+    pub snapshot_policy: Option<SnapshotPolicy>,
+}
+
+impl Config {
+    /// Build a new config by applying `update` to this one, and validate it.
+    ///
+    /// The tick interval of a running node does not change, thus it is pinned to the one in effect before the
+    /// update, and the updated election timeout is validated against it.
+    pub fn with_update(&self, update: &ConfigUpdate) -> Result<Config, ConfigError> {
+        let mut c = self.clone();
+        c.tick_interval = Some(self.effective_tick_interval());
+
+        if let Some(v) = update.heartbeat_interval {
+            c.heartbeat_interval = v;
+        }
+        if let Some(v) = update.election_timeout_min {
+            c.election_timeout_min = v;
+        }
+        if let Some(v) = update.election_timeout_max {
+            c.election_timeout_max = v;
+        }
+        if let Some(v) = update.max_payload_entries {
+            c.max_payload_entries = v;
+        }
+        if let Some(v) = &update.snapshot_policy {
+            c.snapshot_policy = v.clone();
+        }
+
+        c.validate()
+    }
+}
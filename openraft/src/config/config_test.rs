@@ -1,6 +1,14 @@
+use std::time::Duration;
+
 use crate::config::error::ConfigError;
+use crate::CommitQuorum;
+use crate::CompressionAlgo;
 use crate::Config;
+use crate::ConfigUpdate;
 use crate::SnapshotPolicy;
+use crate::SnapshotPolicyContext;
+use crate::SnapshotPredicate;
+use crate::SnapshotSource;
 
 #[test]
 fn test_config_defaults() {
@@ -8,13 +16,49 @@ fn test_config_defaults() {
 
     assert!(cfg.election_timeout_min >= 150);
     assert!(cfg.election_timeout_max <= 300);
+    assert_eq!(0, cfg.election_timeout_jitter);
 
     assert_eq!(50, cfg.heartbeat_interval);
+    assert_eq!(None, cfg.tick_interval);
+    assert_eq!(75, cfg.effective_tick_interval());
     assert_eq!(300, cfg.max_payload_entries);
+    assert_eq!(None, cfg.max_payload_bytes);
+    assert_eq!(0, cfg.replication_prefetch_entries);
+    assert_eq!(1, cfg.max_inflight_appends);
+    assert_eq!(None, cfg.replication_compression);
+    assert_eq!(0, cfg.log_cache_max_entries);
+    assert_eq!(64 * 1024 * 1024, cfg.log_cache_max_bytes);
     assert_eq!(1000, cfg.replication_lag_threshold);
+    assert_eq!(CommitQuorum::Majority, cfg.commit_quorum);
+    assert_eq!(10_000, cfg.add_learner_timeout);
 
     assert_eq!(3 * 1024 * 1024, cfg.snapshot_max_chunk_size);
     assert_eq!(SnapshotPolicy::LogsSinceLast(5000), cfg.snapshot_policy);
+    assert_eq!(true, cfg.enable_prevote);
+    assert_eq!(10, cfg.clock_drift_bound);
+    assert_eq!(300, cfg.transfer_leader_timeout);
+    assert_eq!(false, cfg.enable_check_quorum);
+    assert_eq!(false, cfg.guard_membership_quorum);
+    assert_eq!(false, cfg.disable_auto_elect);
+    assert_eq!(false, cfg.disable_heartbeat);
+    assert_eq!(false, cfg.auto_promote_learner);
+    assert_eq!(0, cfg.learner_promotion_rounds);
+    assert_eq!(50, cfg.replication_backoff_base);
+    assert_eq!(500, cfg.max_replication_backoff);
+    assert_eq!(false, cfg.enable_forward_client_write);
+    assert_eq!(1000, cfg.forward_client_write_timeout);
+    assert_eq!(3, cfg.max_forward_client_write_hops);
+    assert_eq!(3000, cfg.graceful_shutdown_timeout);
+    assert_eq!(3, cfg.storage_retry_limit);
+    assert_eq!(50, cfg.storage_retry_interval);
+    assert_eq!(1024, cfg.applied_event_buffer_size);
+    assert_eq!(50, cfg.append_entries_timeout);
+    assert_eq!(100, cfg.replication_progress_interval);
+    assert_eq!(0, cfg.snapshot_catch_up_gap);
+    assert_eq!(0, cfg.max_in_snapshot_log_to_keep);
+    assert_eq!(None, cfg.install_snapshot_max_bytes_per_sec);
+    assert_eq!(false, cfg.enable_snapshot_checksum);
+    assert_eq!(SnapshotSource::Leader, cfg.snapshot_source);
 }
 
 #[test]
@@ -42,6 +86,53 @@ fn test_invalid_election_timeout_config_produces_expected_error() {
         election_timeout_min: 1000,
         heartbeat_interval: 1500
     });
+
+    let config = Config {
+        election_timeout_min: 10,
+        election_timeout_max: 20,
+        heartbeat_interval: 5,
+        clock_drift_bound: 10,
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    assert!(res.is_ok(), "a clock drift without a lease is valid: {:?}", res);
+
+    let config = Config {
+        install_snapshot_max_bytes_per_sec: Some(0),
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    let err = res.unwrap_err();
+    assert_eq!(err, ConfigError::InstallSnapshotMaxBytesPerSecIs0);
+
+    let config = Config {
+        max_payload_bytes: Some(0),
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    let err = res.unwrap_err();
+    assert_eq!(err, ConfigError::MaxPayloadBytesIs0);
+
+    let config = Config {
+        max_inflight_appends: 0,
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    let err = res.unwrap_err();
+    assert_eq!(err, ConfigError::MaxInflightAppendsIs0);
+
+    let config = Config {
+        replication_backoff_base: 0,
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    let err = res.unwrap_err();
+    assert_eq!(err, ConfigError::ReplicationBackoffBaseIs0);
 }
 
 #[test]
@@ -51,29 +142,98 @@ fn test_build() -> anyhow::Result<()> {
         "--cluster-name=bar",
         "--election-timeout-min=10",
         "--election-timeout-max=20",
+        "--election-timeout-jitter=226",
         "--heartbeat-interval=5",
+        "--tick-interval=4",
+        "--append-entries-timeout=214",
         "--install-snapshot-timeout=200",
+        "--replication-backoff-base=224",
+        "--max-replication-backoff=209",
+        "--replication-progress-interval=215",
+        "--snapshot-catch-up-gap=216",
         "--max-payload-entries=201",
+        "--max-payload-bytes=225",
+        "--replication-prefetch-entries=227",
+        "--max-inflight-appends=223",
         "--replication-lag-threshold=202",
+        "--commit-quorum=all",
+        "--add-learner-timeout=228",
+        "--log-cache-max-entries=222",
+        "--log-cache-max-bytes=221",
         "--snapshot-policy=since_last:203",
         "--keep-unsnapshoted-log",
         "--snapshot-max-chunk-size=204",
         "--max-applied-log-to-keep=205",
+        "--max-in-snapshot-log-to-keep=219",
+        "--install-snapshot-max-bytes-per-sec=220",
+        "--enable-snapshot-checksum",
+        "--snapshot-source=voters",
         "--purge-batch-size=207",
+        "--enable-prevote=false",
+        "--clock-drift-bound=3",
+        "--transfer-leader-timeout=208",
+        "--enable-check-quorum",
+        "--guard-membership-quorum",
+        "--disable-auto-elect",
+        "--disable-heartbeat",
+        "--auto-promote-learner",
+        "--learner-promotion-rounds=229",
+        "--enable-forward-client-write",
+        "--forward-client-write-timeout=210",
+        "--max-forward-client-write-hops=211",
+        "--graceful-shutdown-timeout=212",
+        "--applied-event-buffer-size=213",
+        "--storage-retry-limit=217",
+        "--storage-retry-interval=218",
     ])?;
 
     assert_eq!("bar", config.cluster_name);
     assert_eq!(10, config.election_timeout_min);
     assert_eq!(20, config.election_timeout_max);
+    assert_eq!(226, config.election_timeout_jitter);
     assert_eq!(5, config.heartbeat_interval);
+    assert_eq!(Some(4), config.tick_interval);
+    assert_eq!(4, config.effective_tick_interval());
+    assert_eq!(214, config.append_entries_timeout);
     assert_eq!(200, config.install_snapshot_timeout);
+    assert_eq!(224, config.replication_backoff_base);
+    assert_eq!(209, config.max_replication_backoff);
+    assert_eq!(215, config.replication_progress_interval);
+    assert_eq!(216, config.snapshot_catch_up_gap);
     assert_eq!(201, config.max_payload_entries);
+    assert_eq!(Some(225), config.max_payload_bytes);
+    assert_eq!(227, config.replication_prefetch_entries);
+    assert_eq!(223, config.max_inflight_appends);
     assert_eq!(202, config.replication_lag_threshold);
+    assert_eq!(CommitQuorum::All, config.commit_quorum);
+    assert_eq!(228, config.add_learner_timeout);
+    assert_eq!(222, config.log_cache_max_entries);
+    assert_eq!(221, config.log_cache_max_bytes);
     assert_eq!(SnapshotPolicy::LogsSinceLast(203), config.snapshot_policy);
     assert_eq!(true, config.keep_unsnapshoted_log);
     assert_eq!(204, config.snapshot_max_chunk_size);
     assert_eq!(205, config.max_applied_log_to_keep);
+    assert_eq!(219, config.max_in_snapshot_log_to_keep);
+    assert_eq!(Some(220), config.install_snapshot_max_bytes_per_sec);
+    assert_eq!(true, config.enable_snapshot_checksum);
+    assert_eq!(SnapshotSource::Voters, config.snapshot_source);
     assert_eq!(207, config.purge_batch_size);
+    assert_eq!(false, config.enable_prevote);
+    assert_eq!(3, config.clock_drift_bound);
+    assert_eq!(208, config.transfer_leader_timeout);
+    assert_eq!(true, config.enable_check_quorum);
+    assert_eq!(true, config.guard_membership_quorum);
+    assert_eq!(true, config.disable_auto_elect);
+    assert_eq!(true, config.disable_heartbeat);
+    assert_eq!(true, config.auto_promote_learner);
+    assert_eq!(229, config.learner_promotion_rounds);
+    assert_eq!(true, config.enable_forward_client_write);
+    assert_eq!(210, config.forward_client_write_timeout);
+    assert_eq!(211, config.max_forward_client_write_hops);
+    assert_eq!(212, config.graceful_shutdown_timeout);
+    assert_eq!(213, config.applied_event_buffer_size);
+    assert_eq!(217, config.storage_retry_limit);
+    assert_eq!(218, config.storage_retry_interval);
 
     Ok(())
 }
@@ -88,3 +248,286 @@ fn test_config_build_() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_config_enable_prevote() -> anyhow::Result<()> {
+    assert_eq!(true, Config::default().enable_prevote, "pre-vote is enabled by default");
+
+    let config = Config::build(&["foo"])?;
+    assert_eq!(true, config.enable_prevote);
+
+    let config = Config::build(&["foo", "--enable-prevote=false"])?;
+    assert_eq!(false, config.enable_prevote);
+
+    let config = Config::build(&["foo", "--enable-prevote=true"])?;
+    assert_eq!(true, config.enable_prevote);
+
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_predicate() -> anyhow::Result<()> {
+    let p = SnapshotPredicate::new(|ctx| ctx.logs_since_last >= 3);
+
+    let ctx = |logs_since_last| SnapshotPolicyContext {
+        last_applied_term: 1,
+        last_applied_index: 10,
+        logs_since_last,
+        bytes_since_last: 0,
+        since_last: Duration::from_secs(1),
+    };
+    assert_eq!(false, p.check(&ctx(2)));
+    assert_eq!(true, p.check(&ctx(3)));
+
+    // Only clones of the same predicate are equal.
+    assert_eq!(SnapshotPolicy::Custom(p.clone()), SnapshotPolicy::Custom(p.clone()));
+    assert_ne!(
+        SnapshotPolicy::Custom(p),
+        SnapshotPolicy::Custom(SnapshotPredicate::new(|ctx| ctx.logs_since_last >= 3))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_new_rand_election_timeout_with_jitter() -> anyhow::Result<()> {
+    let config = Config {
+        election_timeout_min: 150,
+        election_timeout_max: 300,
+        election_timeout_jitter: 100,
+        ..Default::default()
+    }
+    .validate()?;
+
+    let mut max_seen = 0;
+    for _ in 0..1000 {
+        let t = config.new_rand_election_timeout();
+        assert!((150..=400).contains(&t), "timeout out of range: {}", t);
+        max_seen = max_seen.max(t);
+    }
+    assert!(max_seen >= 300, "jitter extends the range: {}", max_seen);
+
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_policy_should_snapshot() -> anyhow::Result<()> {
+    let ctx = |logs_since_last, bytes_since_last, since_last_ms| SnapshotPolicyContext {
+        last_applied_term: 1,
+        last_applied_index: 10,
+        logs_since_last,
+        bytes_since_last,
+        since_last: Duration::from_millis(since_last_ms),
+    };
+
+    let p = SnapshotPolicy::LogsSinceLast(3);
+    assert_eq!(false, p.should_snapshot(&ctx(2, 1000, 1000)));
+    assert_eq!(true, p.should_snapshot(&ctx(3, 0, 0)));
+
+    let p = SnapshotPolicy::SinceLastBytes(100);
+    assert_eq!(false, p.should_snapshot(&ctx(1000, 99, 1000)));
+    assert_eq!(true, p.should_snapshot(&ctx(1, 100, 0)));
+
+    let p = SnapshotPolicy::Interval(Duration::from_millis(100));
+    assert_eq!(false, p.should_snapshot(&ctx(1000, 1000, 99)));
+    assert_eq!(true, p.should_snapshot(&ctx(1, 0, 100)));
+    assert_eq!(
+        false,
+        p.should_snapshot(&ctx(0, 0, 100)),
+        "no log applied since the last snapshot"
+    );
+
+    let p = SnapshotPolicy::Any(vec![
+        SnapshotPolicy::LogsSinceLast(3),
+        SnapshotPolicy::SinceLastBytes(100),
+    ]);
+    assert_eq!(false, p.should_snapshot(&ctx(2, 99, 0)));
+    assert_eq!(true, p.should_snapshot(&ctx(3, 0, 0)));
+    assert_eq!(true, p.should_snapshot(&ctx(1, 100, 0)));
+
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_policy_build_and_validate() -> anyhow::Result<()> {
+    let config = Config::build(&["foo", "--snapshot-policy=since_last_bytes:1KiB"])?;
+    assert_eq!(SnapshotPolicy::SinceLastBytes(1024), config.snapshot_policy);
+
+    let config = Config::build(&["foo", "--snapshot-policy=interval:500"])?;
+    assert_eq!(
+        SnapshotPolicy::Interval(Duration::from_millis(500)),
+        config.snapshot_policy
+    );
+
+    let config = Config::build(&["foo", "--snapshot-policy=since_last:10,interval:500"])?;
+    assert_eq!(
+        SnapshotPolicy::Any(vec![
+            SnapshotPolicy::LogsSinceLast(10),
+            SnapshotPolicy::Interval(Duration::from_millis(500)),
+        ]),
+        config.snapshot_policy
+    );
+
+    for policy in [
+        SnapshotPolicy::SinceLastBytes(0),
+        SnapshotPolicy::Interval(Duration::from_millis(0)),
+        SnapshotPolicy::Any(vec![]),
+        SnapshotPolicy::Any(vec![
+            SnapshotPolicy::LogsSinceLast(10),
+            SnapshotPolicy::SinceLastBytes(0),
+        ]),
+    ] {
+        let res = Config {
+            snapshot_policy: policy.clone(),
+            ..Default::default()
+        }
+        .validate();
+        assert!(
+            matches!(res, Err(ConfigError::ZeroSnapshotPolicy { .. })),
+            "policy: {:?}, got: {:?}",
+            policy,
+            res
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_replication_compression_build_and_validate() -> anyhow::Result<()> {
+    let res = Config::build(&["foo", "--replication-compression=zstd"]);
+
+    #[cfg(feature = "compression")]
+    assert_eq!(Some(CompressionAlgo::Zstd), res?.replication_compression);
+
+    #[cfg(not(feature = "compression"))]
+    assert_eq!(
+        Err(ConfigError::CompressionNotEnabled {
+            algo: CompressionAlgo::Zstd
+        }),
+        res.map(|_| ())
+    );
+
+    let res = Config {
+        replication_compression: Some(CompressionAlgo::Lz4),
+        ..Default::default()
+    }
+    .validate();
+    assert_eq!(cfg!(feature = "compression"), res.is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn test_config_with_update() -> anyhow::Result<()> {
+    let config = Config::default();
+
+    let c = config.with_update(&ConfigUpdate::default())?;
+    assert_eq!(config.heartbeat_interval, c.heartbeat_interval);
+    assert_eq!(config.election_timeout_min, c.election_timeout_min);
+
+    let c = config.with_update(&ConfigUpdate {
+        heartbeat_interval: Some(100),
+        election_timeout_min: Some(500),
+        election_timeout_max: Some(1000),
+        max_payload_entries: Some(10),
+        snapshot_policy: Some(SnapshotPolicy::LogsSinceLast(20)),
+    })?;
+    assert_eq!(100, c.heartbeat_interval);
+    assert_eq!(500, c.election_timeout_min);
+    assert_eq!(1000, c.election_timeout_max);
+    assert_eq!(10, c.max_payload_entries);
+    assert_eq!(SnapshotPolicy::LogsSinceLast(20), c.snapshot_policy);
+    assert_eq!(config.cluster_name, c.cluster_name, "other fields are not changed");
+
+    let res = config.with_update(&ConfigUpdate {
+        election_timeout_min: Some(config.election_timeout_max),
+        ..Default::default()
+    });
+    assert_eq!(
+        Err(ConfigError::ElectionTimeout {
+            min: config.election_timeout_max,
+            max: config.election_timeout_max
+        }),
+        res.map(|_| ())
+    );
+
+    let res = config.with_update(&ConfigUpdate {
+        heartbeat_interval: Some(config.election_timeout_min),
+        ..Default::default()
+    });
+    assert_eq!(
+        Err(ConfigError::ElectionTimeoutLTHeartBeat {
+            election_timeout_min: config.election_timeout_min,
+            heartbeat_interval: config.election_timeout_min,
+        }),
+        res.map(|_| ())
+    );
+
+    let res = config.with_update(&ConfigUpdate {
+        max_payload_entries: Some(0),
+        ..Default::default()
+    });
+    assert_eq!(Err(ConfigError::MaxPayloadIs0), res.map(|_| ()));
+
+    let res = config.with_update(&ConfigUpdate {
+        heartbeat_interval: Some(10),
+        election_timeout_min: Some(100),
+        ..Default::default()
+    });
+    assert_eq!(
+        Err(ConfigError::ElectionTimeoutLTTwoTicks {
+            election_timeout_min: 100,
+            tick_interval: 75,
+        }),
+        res.map(|_| ()),
+        "the tick interval of a running node is not changed"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_tick_interval() -> anyhow::Result<()> {
+    let config = Config {
+        heartbeat_interval: 50,
+        election_timeout_min: 100,
+        election_timeout_max: 200,
+        ..Default::default()
+    };
+    assert_eq!(
+        50,
+        config.effective_tick_interval(),
+        "the default tick fits 2 in an election timeout"
+    );
+
+    let config = Config {
+        tick_interval: Some(10),
+        ..Default::default()
+    }
+    .validate()?;
+    assert_eq!(10, config.effective_tick_interval());
+
+    let res = Config {
+        tick_interval: Some(0),
+        ..Default::default()
+    }
+    .validate();
+    assert_eq!(Err(ConfigError::TickIntervalIs0), res.map(|_| ()));
+
+    let res = Config {
+        election_timeout_min: 150,
+        tick_interval: Some(76),
+        ..Default::default()
+    }
+    .validate();
+    assert_eq!(
+        Err(ConfigError::ElectionTimeoutLTTwoTicks {
+            election_timeout_min: 150,
+            tick_interval: 76,
+        }),
+        res.map(|_| ())
+    );
+
+    Ok(())
+}
@@ -1,5 +1,7 @@
+use crate::compression::CompressionAlgo;
+
 /// Error variants related to configuration.
-#[derive(Debug, thiserror::Error, PartialEq)]
+#[derive(Debug, Clone, thiserror::Error, PartialEq)]
 pub enum ConfigError {
     #[error("election timeout: min({min}) must be < max({max})")]
     ElectionTimeout { min: u64, max: u64 },
@@ -7,15 +9,51 @@ pub enum ConfigError {
     #[error("max_payload_entries must be > 0")]
     MaxPayloadIs0,
 
+    #[error("max_payload_bytes must be > 0")]
+    MaxPayloadBytesIs0,
+
+    #[error("max_inflight_appends must be > 0")]
+    MaxInflightAppendsIs0,
+
+    #[error("replication_backoff_base must be > 0")]
+    ReplicationBackoffBaseIs0,
+
+    #[error("install_snapshot_max_bytes_per_sec must be > 0")]
+    InstallSnapshotMaxBytesPerSecIs0,
+
     #[error("election_timeout_min({election_timeout_min}) must be > heartbeat_interval({heartbeat_interval})")]
     ElectionTimeoutLTHeartBeat {
         election_timeout_min: u64,
         heartbeat_interval: u64,
     },
 
+    #[error("tick_interval must be > 0")]
+    TickIntervalIs0,
+
+    #[error("election_timeout_min({election_timeout_min}) must be >= 2 * tick_interval({tick_interval})")]
+    ElectionTimeoutLTTwoTicks {
+        election_timeout_min: u64,
+        tick_interval: u64,
+    },
+
     #[error("snapshot policy string is invalid: '{invalid:?}' expect: '{syntax}'")]
     InvalidSnapshotPolicy { invalid: String, syntax: String },
 
+    #[error("snapshot source string is invalid: '{invalid:?}' expect: '{syntax}'")]
+    InvalidSnapshotSource { invalid: String, syntax: String },
+
+    #[error("commit quorum string is invalid: '{invalid:?}' expect: '{syntax}'")]
+    InvalidCommitQuorum { invalid: String, syntax: String },
+
+    #[error("compression algorithm string is invalid: '{invalid:?}' expect: '{syntax}'")]
+    InvalidCompressionAlgo { invalid: String, syntax: String },
+
+    #[error("replication_compression {algo} requires feature `compression`")]
+    CompressionNotEnabled { algo: CompressionAlgo },
+
+    #[error("snapshot policy must not be zero or empty: {policy}")]
+    ZeroSnapshotPolicy { policy: String },
+
     #[error("{reason} when parsing {invalid:?}")]
     InvalidNumber { invalid: String, reason: String },
 }
@@ -3,6 +3,11 @@ mod error;
 
 #[cfg(test)] mod config_test;
 
+pub use config::CommitQuorum;
 pub use config::Config;
+pub use config::ConfigUpdate;
 pub use config::SnapshotPolicy;
+pub use config::SnapshotPolicyContext;
+pub use config::SnapshotPredicate;
+pub use config::SnapshotSource;
 pub use error::ConfigError;
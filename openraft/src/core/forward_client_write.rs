@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use tracing::Instrument;
+
+use crate::core::RaftCore;
+use crate::error::ClientWriteError;
+use crate::error::RPCError;
+use crate::raft::ClientWriteRequest;
+use crate::raft::ClientWriteResponse;
+use crate::raft::ForwardClientWriteRequest;
+use crate::raft::RaftRespTx;
+use crate::summary::MessageSummary;
+use crate::AsyncRuntime;
+use crate::RaftLogStorage;
+use crate::RaftNetwork;
+use crate::RaftNetworkFactory;
+use crate::RaftStateMachine;
+use crate::RaftTypeConfig;
+
+impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>>
+    RaftCore<C, N, LS, SM>
+{
+    /// Handle a client write received by a non-leader.
+    ///
+    /// If forwarding is enabled, the write is forwarded to the known leader and the leader's result is sent to `tx`.
+    /// Otherwise, or if no leader is known, or the write has been forwarded `hops` times, `ForwardToLeader` is sent.
+    ///
+    /// If the forwarding fails or times out, the caller receives the `ForwardToLeader` this node would have returned.
+    #[tracing::instrument(level = "debug", skip(self, rpc, tx))]
+    pub(super) async fn forward_client_write(
+        &mut self,
+        rpc: ClientWriteRequest<C>,
+        hops: u64,
+        tx: RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>>,
+    ) {
+        let err = self.forward_to_leader();
+
+        if !self.config.enable_forward_client_write {
+            let _ = tx.send(Err(err.into()));
+            return;
+        }
+
+        if hops >= self.config.max_forward_client_write_hops {
+            tracing::info!(hops, "client write has been forwarded too many times");
+            let _ = tx.send(Err(err.into()));
+            return;
+        }
+
+        let leader_id = match &err.leader_id {
+            Some(id) => id.clone(),
+            None => {
+                let _ = tx.send(Err(err.into()));
+                return;
+            }
+        };
+
+        let req = ForwardClientWriteRequest { rpc, hops: hops + 1 };
+        tracing::debug!(
+            leader_id = display(&leader_id),
+            req = display(req.summary()),
+            "forward client write to leader"
+        );
+
+        let mut network = self.network.connect(leader_id.clone(), err.leader_node.as_ref()).await;
+        let ttl = Duration::from_millis(self.config.forward_client_write_timeout);
+
+        let _ = C::AsyncRuntime::spawn(
+            async move {
+                let res = match C::AsyncRuntime::timeout(ttl, network.forward_client_write(req)).await {
+                    Ok(Ok(resp)) => Ok(resp),
+                    Ok(Err(RPCError::RemoteError(remote_err))) => Err(remote_err.source),
+                    Ok(Err(rpc_err)) => {
+                        tracing::warn!(
+                            leader_id = display(&leader_id),
+                            error = display(&rpc_err),
+                            "failed to forward client write"
+                        );
+                        Err(err.into())
+                    }
+                    Err(_timeout) => {
+                        tracing::warn!(
+                            leader_id = display(&leader_id),
+                            "timeout forwarding client write after {:?}",
+                            ttl
+                        );
+                        Err(err.into())
+                    }
+                };
+
+                let _ = tx.send(res);
+            }
+            .instrument(tracing::debug_span!("forward_client_write")),
+        );
+    }
+}
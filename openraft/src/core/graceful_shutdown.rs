@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use crate::async_runtime::InstantOf;
+use crate::core::RaftCore;
+use crate::core::ServerState;
+use crate::error::Fatal;
+use crate::raft::RaftMsg;
+use crate::raft::ShutdownReport;
+use crate::raft_types::LogIdOptionExt;
+use crate::AsyncRuntime;
+use crate::RaftLogStorage;
+use crate::RaftNetworkFactory;
+use crate::RaftStateMachine;
+use crate::RaftTypeConfig;
+use crate::Responder;
+
+/// A graceful shutdown in progress: `RaftCore` stops accepting client writes and waits for the logs to be applied.
+pub(crate) struct Drain<C: RaftTypeConfig> {
+    /// If the logs are not all applied before this time, `RaftCore` shuts down anyway.
+    pub(crate) deadline: InstantOf<C>,
+
+    /// Channel to send the report back to the caller, right before `RaftCore` shuts down.
+    pub(crate) tx: oneshot::Sender<ShutdownReport>,
+}
+
+impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>>
+    RaftCore<C, N, LS, SM>
+{
+    /// Start to shut down gracefully.
+    ///
+    /// If a graceful shutdown is already in progress, the previous caller is answered at once with the current state,
+    /// and the deadline is not extended.
+    #[tracing::instrument(level = "debug", skip(self, tx))]
+    pub(super) fn handle_drain(&mut self, tx: oneshot::Sender<ShutdownReport>) {
+        let deadline = match self.drain.take() {
+            Some(prev) => {
+                let _ = prev.tx.send(self.shutdown_report());
+                prev.deadline
+            }
+            None => C::AsyncRuntime::now() + Duration::from_millis(self.config.graceful_shutdown_timeout),
+        };
+
+        tracing::info!("start graceful shutdown, deadline: {:?}", deadline);
+
+        self.drain = Some(Drain { deadline, tx });
+    }
+
+    /// Shut down if a graceful shutdown is in progress and the logs are all applied, or the deadline is reached.
+    ///
+    /// Only a leader waits for its logs: a non-leader can not commit logs by itself.
+    pub(super) fn check_drain(&mut self) {
+        let deadline = match &self.drain {
+            Some(d) => d.deadline,
+            None => return,
+        };
+
+        let report = self.shutdown_report();
+
+        let drained = report.unapplied == 0 || self.engine.state.server_state != ServerState::Leader;
+        if !drained && C::AsyncRuntime::now() < deadline {
+            return;
+        }
+
+        if drained {
+            tracing::info!("all logs are applied, shutdown");
+        } else {
+            tracing::warn!(
+                unapplied = report.unapplied,
+                "graceful shutdown timeout, shutdown anyway"
+            );
+        }
+
+        if let Some(d) = self.drain.take() {
+            let _ = d.tx.send(report);
+        }
+        self.set_target_state(ServerState::Shutdown);
+    }
+
+    /// Reject a client write with `Fatal::Stopped` if a graceful shutdown is in progress.
+    ///
+    /// It returns the message back if it is not rejected.
+    pub(super) fn reject_client_write_on_drain(&self, msg: RaftMsg<C, N, LS, SM>) -> Option<RaftMsg<C, N, LS, SM>> {
+        if self.drain.is_none() {
+            return Some(msg);
+        }
+
+        match msg {
+            RaftMsg::ClientWriteRequest { tx, .. } => {
+                let _ = tx.send(Err(Fatal::Stopped.into()));
+            }
+            RaftMsg::ForwardClientWrite { tx, .. } => {
+                let _ = tx.send(Err(Fatal::Stopped.into()));
+            }
+            RaftMsg::ClientWriteWithResponder { responder, .. } => {
+                responder.send(Err(Fatal::Stopped.into()));
+            }
+            RaftMsg::ClientWriteFFRequest { tx, .. } => {
+                let _ = tx.send(Err(Fatal::Stopped.into()));
+            }
+            RaftMsg::ClientWriteManyRequest { txs, .. } => {
+                for tx in txs {
+                    let _ = tx.send(Err(Fatal::Stopped.into()));
+                }
+            }
+            _ => return Some(msg),
+        }
+
+        None
+    }
+
+    fn shutdown_report(&self) -> ShutdownReport {
+        let last_log_id = self.engine.state.last_log_id();
+        let applied = &self.engine.state.last_applied;
+
+        ShutdownReport {
+            unapplied: last_log_id.next_index().saturating_sub(applied.next_index()),
+        }
+    }
+}
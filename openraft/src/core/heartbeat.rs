@@ -0,0 +1,180 @@
+//! The heartbeat worker sends a leader's heartbeats to a target, independent of the replication stream to it.
+
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+use tracing_futures::Instrument;
+
+use crate::async_runtime::mpsc;
+use crate::async_runtime::watch;
+use crate::async_runtime::JoinHandle;
+use crate::error::RPCError;
+use crate::network::is_connection_error;
+use crate::network::RPCOption;
+use crate::raft::AppendEntriesRequest;
+use crate::raft::AppendEntriesResponse;
+use crate::raft::RaftMsg;
+use crate::replication::backoff::Backoff;
+use crate::AsyncRuntime;
+use crate::Config;
+use crate::LogId;
+use crate::RaftLogStorage;
+use crate::RaftNetwork;
+use crate::RaftNetworkFactory;
+use crate::RaftStateMachine;
+use crate::RaftTypeConfig;
+use crate::Vote;
+
+/// The handle to a spawned heartbeat worker.
+///
+/// The worker quits when this handle is dropped.
+pub(crate) struct HeartbeatHandle<C: RaftTypeConfig> {
+    #[allow(dead_code)]
+    handle: JoinHandle<(), <C::AsyncRuntime as AsyncRuntime>::JoinError>,
+
+    /// Dropping it tells the worker to quit.
+    #[allow(dead_code)]
+    tx_shutdown: oneshot::Sender<()>,
+}
+
+/// Sends a heartbeat to a target every `heartbeat_interval`, on its own timer.
+///
+/// A heartbeat is an AppendEntries that carries only the vote and the committed log id of the leader. It does not
+/// wait for the replication stream to the same target, thus a follower that is receiving a large batch of logs or a
+/// snapshot still sees the leader in time, and does not start an election.
+///
+/// Every acknowledged heartbeat is reported to `RaftCore` with its sending time, which extends the leader lease. A
+/// heartbeat rejected by a higher vote reverts the leader to a follower.
+pub(crate) struct HeartbeatWorker<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
+    target: C::NodeId,
+
+    /// The vote of the leader that spawned this worker.
+    vote: Vote<C::NodeId>,
+
+    network: N::Network,
+
+    interval: Duration,
+
+    /// Keeps sending heartbeats to an unreachable target less often.
+    backoff: Backoff,
+
+    /// The committed log id of the leader, updated by `RaftCore`.
+    rx_committed: watch::Receiver<Option<LogId<C::NodeId>>>,
+
+    rx_shutdown: oneshot::Receiver<()>,
+
+    tx_raft_core: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
+}
+
+impl<C, N, LS, SM> HeartbeatWorker<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
+    pub(crate) fn spawn(
+        target: C::NodeId,
+        vote: Vote<C::NodeId>,
+        config: &Config,
+        network: N::Network,
+        rx_committed: watch::Receiver<Option<LogId<C::NodeId>>>,
+        tx_raft_core: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
+        span: tracing::Span,
+    ) -> HeartbeatHandle<C> {
+        let (tx_shutdown, rx_shutdown) = oneshot::channel();
+        let backoff = Backoff::new(network.backoff(config));
+
+        let this = Self {
+            target,
+            vote,
+            network,
+            interval: Duration::from_millis(config.heartbeat_interval),
+            backoff,
+            rx_committed,
+            rx_shutdown,
+            tx_raft_core,
+        };
+
+        let handle = C::AsyncRuntime::spawn(this.main().instrument(span));
+
+        HeartbeatHandle { handle, tx_shutdown }
+    }
+
+    async fn main(mut self) {
+        let mut next = C::AsyncRuntime::now() + self.interval;
+
+        loop {
+            tokio::select! {
+                _ = C::AsyncRuntime::sleep_until(next) => {}
+                _ = &mut self.rx_shutdown => {
+                    tracing::debug!("heartbeat worker is shut down");
+                    return;
+                }
+            }
+
+            let delay = match self.send_heartbeat().await {
+                Some(delay) => delay,
+                None => return,
+            };
+
+            next = C::AsyncRuntime::now() + delay;
+        }
+    }
+
+    /// Send a heartbeat and return the delay before the next one, or `None` if this leader is deposed.
+    async fn send_heartbeat(&mut self) -> Option<Duration> {
+        let sending_time = C::AsyncRuntime::now();
+        let committed = self.rx_committed.borrow().clone();
+        let rpc = AppendEntriesRequest::new_heartbeat(self.vote.clone(), committed);
+
+        let option = RPCOption::new(self.interval);
+        let res = C::AsyncRuntime::timeout(self.interval, self.network.append_entries(rpc, option)).await;
+
+        let resp = match res {
+            Ok(Ok(resp)) => resp,
+            Ok(Err(RPCError::Unreachable(err))) => {
+                self.network.on_connection_error();
+                let delay = self.backoff.on_failure();
+                tracing::debug!(error = %err, "heartbeat target is unreachable, retry after {:?}", delay);
+                return Some(std::cmp::max(delay, self.interval));
+            }
+            Ok(Err(err)) => {
+                tracing::debug!(error = %err, "failed to send heartbeat");
+                if is_connection_error(&err) {
+                    self.network.on_connection_error();
+                }
+                return Some(self.interval);
+            }
+            Err(_timeout) => {
+                tracing::debug!("timeout while sending heartbeat: {:?}", self.interval);
+                return Some(self.interval);
+            }
+        };
+
+        self.backoff.on_success();
+
+        if let AppendEntriesResponse::HigherVote(higher) = resp {
+            let _ = self.tx_raft_core.send(RaftMsg::RevertToFollower {
+                target: self.target.clone(),
+                new_vote: higher,
+                vote: self.vote.clone(),
+            });
+            return None;
+        }
+
+        let _ = self.tx_raft_core.send(RaftMsg::ReplicationAcked {
+            target: self.target.clone(),
+            sending_time,
+            vote: self.vote.clone(),
+        });
+
+        Some(self.interval)
+    }
+}
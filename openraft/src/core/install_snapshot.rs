@@ -1,232 +1,141 @@
-use std::io::SeekFrom;
-
-use anyerror::AnyError;
-use tokio::io::AsyncSeekExt;
-use tokio::io::AsyncWriteExt;
-
 use crate::core::RaftCore;
 use crate::core::ServerState;
 use crate::core::SnapshotState;
-use crate::error::InstallSnapshotError;
-use crate::error::SnapshotMismatch;
-use crate::raft::InstallSnapshotRequest;
-use crate::raft::InstallSnapshotResponse;
+use crate::error::InstallLocalSnapshotError;
+use crate::error::SnapshotBehind;
+use crate::raft::AppliedEvent;
+use crate::raft::SnapshotResponse;
+use crate::storage::Snapshot;
+use crate::AsyncRuntime;
 use crate::Entry;
-use crate::ErrorSubject;
-use crate::ErrorVerb;
-use crate::MessageSummary;
+use crate::RaftLogStorage;
 use crate::RaftNetworkFactory;
-use crate::RaftStorage;
+use crate::RaftStateMachine;
 use crate::RaftTypeConfig;
-use crate::SnapshotSegmentId;
+use crate::SnapshotMeta;
 use crate::StorageError;
-use crate::StorageIOError;
+use crate::Vote;
 
-impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C, N, S> {
-    /// Invoked by leader to send chunks of a snapshot to a follower (§7).
+impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>>
+    RaftCore<C, N, LS, SM>
+{
+    /// Install a snapshot that is completely received by the application, instead of streamed in chunks.
     ///
-    /// Leaders always send chunks in order. It is important to note that, according to the Raft spec,
-    /// a log may only have one snapshot at any time. As snapshot contents are application specific,
-    /// the Raft log will only store a pointer to the snapshot file along with the index & term.
-    #[tracing::instrument(level = "debug", skip_all)]
-    pub(super) async fn handle_install_snapshot_request(
+    /// A snapshot being streamed or built is dropped in favor of it.
+    #[tracing::instrument(level = "debug", skip_all, fields(vote = display(&vote), meta = ?snapshot.meta))]
+    pub(super) async fn handle_install_full_snapshot(
         &mut self,
-        req: InstallSnapshotRequest<C>,
-    ) -> Result<InstallSnapshotResponse<C::NodeId>, InstallSnapshotError<C::NodeId>> {
-        tracing::debug!(req = display(req.summary()));
-
-        if req.vote < self.engine.state.vote {
-            tracing::debug!(?self.engine.state.vote, %req.vote, "InstallSnapshot RPC term is less than current term");
-
-            return Ok(InstallSnapshotResponse {
-                vote: self.engine.state.vote,
+        vote: Vote<C::NodeId>,
+        snapshot: Snapshot<C::NodeId, C::SnapshotData, C::Node>,
+    ) -> Result<SnapshotResponse<C::NodeId>, StorageError<C::NodeId>> {
+        if !self.accept_snapshot_vote(&vote).await? {
+            return Ok(SnapshotResponse {
+                vote: self.engine.state.vote.clone(),
             });
         }
 
-        self.set_next_election_time(false);
-        self.reject_election_for_a_while();
-
-        if req.vote > self.engine.state.vote {
-            self.engine.state.vote = req.vote;
-            self.save_vote().await?;
+        self.drop_snapshot_in_progress();
 
-            // If not follower, become follower.
-            if !self.engine.state.server_state.is_follower() && !self.engine.state.server_state.is_learner() {
-                self.set_target_state(ServerState::Follower); // State update will emit metrics.
-            }
-
-            self.engine.metrics_flags.set_data_changed();
+        if Some(&snapshot.meta.last_log_id) <= self.engine.state.last_applied.as_ref() {
+            tracing::info!(
+                last_applied = debug(&self.engine.state.last_applied),
+                "snapshot is not newer than the last applied log, skip installing it"
+            );
+        } else {
+            self.install_received_snapshot(snapshot.meta, snapshot.snapshot).await?;
         }
 
-        // Compare current snapshot state with received RPC and handle as needed.
-        // - Init a new state if it is empty or building a snapshot locally.
-        // - Mismatched id with offset=0 indicates a new stream has been sent, the old one should be dropped and start
-        //   to receive the new snapshot,
-        // - Mismatched id with offset greater than 0 is an out of order message that should be rejected.
-        match self.snapshot_state.take() {
-            None => {
-                return self.begin_installing_snapshot(req).await;
-            }
-            Some(SnapshotState::Snapshotting { handle, .. }) => {
-                handle.abort(); // Abort the current compaction in favor of installation from leader.
-                return self.begin_installing_snapshot(req).await;
-            }
-            Some(SnapshotState::Streaming { snapshot, id, offset }) => {
-                if req.meta.snapshot_id == id {
-                    return self.continue_installing_snapshot(req, offset, snapshot).await;
-                }
-
-                if req.offset == 0 {
-                    return self.begin_installing_snapshot(req).await;
-                }
-
-                Err(SnapshotMismatch {
-                    expect: SnapshotSegmentId { id: id.clone(), offset },
-                    got: SnapshotSegmentId {
-                        id: req.meta.snapshot_id.clone(),
-                        offset: req.offset,
-                    },
-                }
-                .into())
-            }
-        }
+        Ok(SnapshotResponse {
+            vote: self.engine.state.vote.clone(),
+        })
     }
 
-    #[tracing::instrument(level = "debug", skip_all)]
-    async fn begin_installing_snapshot(
+    /// Install a snapshot provided by the application, regardless of the vote.
+    ///
+    /// A snapshot being streamed or built is dropped in favor of it.
+    #[tracing::instrument(level = "debug", skip_all, fields(meta = ?meta))]
+    pub(super) async fn handle_install_local_snapshot(
         &mut self,
-        req: InstallSnapshotRequest<C>,
-    ) -> Result<InstallSnapshotResponse<C::NodeId>, InstallSnapshotError<C::NodeId>> {
-        tracing::debug!(req = display(req.summary()));
-
-        let id = req.meta.snapshot_id.clone();
-
-        if req.offset > 0 {
-            return Err(SnapshotMismatch {
-                expect: SnapshotSegmentId {
-                    id: id.clone(),
-                    offset: 0,
-                },
-                got: SnapshotSegmentId { id, offset: req.offset },
+        meta: SnapshotMeta<C::NodeId, C::Node>,
+        snapshot: Box<C::SnapshotData>,
+    ) -> Result<(), InstallLocalSnapshotError<C::NodeId>> {
+        let last_applied = self.engine.state.last_applied.clone();
+
+        if Some(&meta.last_log_id) < last_applied.as_ref() {
+            return Err(SnapshotBehind {
+                last_log_id: meta.last_log_id,
+                last_applied,
             }
             .into());
         }
 
-        // Create a new snapshot and begin writing its contents.
-        let mut snapshot = self.storage.begin_receiving_snapshot().await?;
-        snapshot.as_mut().write_all(&req.data).await.map_err(|e| StorageError::IO {
-            source: StorageIOError::new(
-                ErrorSubject::Snapshot(req.meta.clone()),
-                ErrorVerb::Write,
-                AnyError::new(&e),
-            ),
-        })?;
+        self.drop_snapshot_in_progress();
 
-        // If this was a small snapshot, and it is already done, then finish up.
-        if req.done {
-            self.finalize_snapshot_installation(req, snapshot).await?;
-            return Ok(InstallSnapshotResponse {
-                vote: self.engine.state.vote,
-            });
+        if Some(&meta.last_log_id) == last_applied.as_ref() {
+            tracing::info!("snapshot is at the last applied log, skip installing it");
+            return Ok(());
         }
 
-        // Else, retain snapshot components for later segments & respond.
-        self.snapshot_state = Some(SnapshotState::Streaming {
-            offset: req.data.len() as u64,
-            id,
-            snapshot,
-        });
-        Ok(InstallSnapshotResponse {
-            vote: self.engine.state.vote,
-        })
+        self.install_received_snapshot(meta, snapshot).await?;
+        Ok(())
+    }
+
+    /// Abort building a snapshot and drop the snapshot being streamed, because another snapshot is to be installed.
+    fn drop_snapshot_in_progress(&mut self) {
+        match self.snapshot_state.take() {
+            None => {}
+            Some(SnapshotState::Snapshotting { handle, .. }) => {
+                handle.abort();
+            }
+            Some(SnapshotState::Streaming { id, .. }) => {
+                tracing::info!(id = display(&id), "drop the snapshot being streamed");
+            }
+        }
     }
 
-    #[tracing::instrument(level = "debug", skip_all)]
-    async fn continue_installing_snapshot(
+    /// Update the vote with the one of a leader sending a snapshot.
+    ///
+    /// It returns `false` if the vote is less than the current one, in which case the snapshot is rejected.
+    pub(super) async fn accept_snapshot_vote(
         &mut self,
-        req: InstallSnapshotRequest<C>,
-        mut offset: u64,
-        mut snapshot: Box<S::SnapshotData>,
-    ) -> Result<InstallSnapshotResponse<C::NodeId>, InstallSnapshotError<C::NodeId>> {
-        tracing::debug!(req = display(req.summary()));
+        vote: &Vote<C::NodeId>,
+    ) -> Result<bool, StorageError<C::NodeId>> {
+        if vote < &self.engine.state.vote {
+            tracing::debug!(?self.engine.state.vote, %vote, "InstallSnapshot RPC term is less than current term");
+            return Ok(false);
+        }
+
+        self.set_next_election_time(false);
+        self.reject_election_for_a_while();
 
-        let id = req.meta.snapshot_id.clone();
+        if vote > &self.engine.state.vote {
+            self.engine.state.vote = vote.clone();
+            self.save_vote().await?;
 
-        // Always seek to the target offset if not an exact match.
-        if req.offset != offset {
-            if let Err(err) = snapshot.as_mut().seek(SeekFrom::Start(req.offset)).await {
-                self.snapshot_state = Some(SnapshotState::Streaming { offset, id, snapshot });
-                return Err(StorageError::from_io_error(
-                    ErrorSubject::Snapshot(req.meta.clone()),
-                    ErrorVerb::Seek,
-                    err,
-                )
-                .into());
+            // If not follower, become follower.
+            if !self.engine.state.server_state.is_follower() && !self.engine.state.server_state.is_learner() {
+                self.set_target_state(ServerState::Follower); // State update will emit metrics.
             }
-            offset = req.offset;
-        }
 
-        // Write the next segment & update offset.
-        if let Err(err) = snapshot.as_mut().write_all(&req.data).await {
-            self.snapshot_state = Some(SnapshotState::Streaming { offset, id, snapshot });
-            return Err(
-                StorageError::from_io_error(ErrorSubject::Snapshot(req.meta.clone()), ErrorVerb::Write, err).into(),
-            );
+            self.engine.metrics_flags.set_data_changed();
         }
-        offset += req.data.len() as u64;
 
-        // If the snapshot stream is done, then finalize.
-        if req.done {
-            self.finalize_snapshot_installation(req, snapshot).await?;
-        } else {
-            self.snapshot_state = Some(SnapshotState::Streaming { offset, id, snapshot });
-        }
-        Ok(InstallSnapshotResponse {
-            vote: self.engine.state.vote,
-        })
+        Ok(true)
     }
 
-    /// Finalize the installation of a new snapshot.
-    ///
-    /// Any errors which come up from this routine will cause the Raft node to go into shutdown.
-    #[tracing::instrument(level = "debug", skip_all)]
-    async fn finalize_snapshot_installation(
+    /// Install a received snapshot into the state machine, and update the logs and the membership config in
+    /// accordance with it.
+    pub(super) async fn install_received_snapshot(
         &mut self,
-        req: InstallSnapshotRequest<C>,
-        mut snapshot: Box<S::SnapshotData>,
+        meta: SnapshotMeta<C::NodeId, C::Node>,
+        snapshot: Box<C::SnapshotData>,
     ) -> Result<(), StorageError<C::NodeId>> {
-        tracing::debug!(req = display(req.summary()));
-
-        snapshot.as_mut().shutdown().await.map_err(|e| StorageError::IO {
-            source: StorageIOError::new(
-                ErrorSubject::Snapshot(req.meta.clone()),
-                ErrorVerb::Write,
-                AnyError::new(&e),
-            ),
-        })?;
-
-        // Caveat: All changes to state machine has to be serialized.
-        //
-        // If `finalize_snapshot_installation` is run in RaftCore thread,
-        // there is chance the last_applied being reset to a previous value:
-        //
-        // ```
-        // RaftCore: -.    install-snapc,            .-> replicate_to_sm_handle.next(),
-        //            |    update last_applied=5     |   update last_applied=2
-        //            |                              |
-        //            v                              |
-        // task:      apply 2------------------------'
-        // --------------------------------------------------------------------> time
-        // ```
-
-        // TODO(xp): do not install if self.engine.st.last_applied >= snapshot.meta.last_applied
-
-        let snap_last_log_id = req.meta.last_log_id;
+        let snap_last_log_id = meta.last_log_id.clone();
 
         // Unlike normal append-entries RPC, if conflicting logs are found, it is not **necessary** to delete them.
         // See: [Snapshot-replication](https://datafuselabs.github.io/openraft/replication.html#snapshot-replication)
         {
-            let local = self.storage.try_get_log_entry(snap_last_log_id.index).await?;
+            let local = self.log_store.try_get_log_entry(snap_last_log_id.index).await?;
 
             if let Some(local_log) = local {
                 if local_log.log_id != snap_last_log_id {
@@ -244,27 +153,35 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
 
         let st = &mut self.engine.state;
 
-        let changes = self.storage.install_snapshot(&req.meta, snapshot).await?;
+        let changes = self.state_machine.install_snapshot(&meta, snapshot).await?;
         tracing::debug!("update after apply or install-snapshot: {:?}", changes);
 
         let last_applied = changes.last_applied;
 
-        if st.committed < Some(last_applied) {
-            st.committed = Some(last_applied);
+        if st.committed < Some(last_applied.clone()) {
+            st.committed = Some(last_applied.clone());
         }
-        if st.last_applied < Some(last_applied) {
-            st.last_applied = Some(last_applied);
+        if st.last_applied < Some(last_applied.clone()) {
+            st.last_applied = Some(last_applied.clone());
         }
 
-        debug_assert!(st.last_purged_log_id() <= Some(last_applied));
+        debug_assert!(st.last_purged_log_id() <= Some(last_applied.clone()));
 
         // A local log that is <= last_applied may be inconsistent with the leader.
         // It has to purge all of them to prevent these log form being replicated, when this node becomes leader.
-        self.engine.snapshot_last_log_id = Some(last_applied); // update and make last applied log removable
+        self.engine.snapshot_last_log_id = Some(last_applied.clone()); // update and make last applied log removable
+        self.last_snapshot_time = C::AsyncRuntime::now();
+        self.bytes_since_last_snapshot = 0;
+
+        // Sending fails only if there is no subscriber.
+        let _ = self.tx_applied.send(AppliedEvent::Snapshot {
+            last_log_id: last_applied.clone(),
+        });
+
         self.engine.purge_log(last_applied);
         self.run_engine_commands::<Entry<C>>(&[]).await?;
 
-        self.engine.update_committed_membership(req.meta.last_membership);
+        self.engine.update_committed_membership(meta.last_membership);
         self.run_engine_commands::<Entry<C>>(&[]).await?;
 
         self.engine.metrics_flags.set_data_changed();
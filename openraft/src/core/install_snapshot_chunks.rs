@@ -0,0 +1,351 @@
+use std::io;
+
+use anyerror::AnyError;
+use futures::future::BoxFuture;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+
+use crate::core::ChunkAction;
+use crate::core::RaftCore;
+use crate::core::SnapshotState;
+use crate::error::ChecksumMismatch;
+use crate::error::InstallSnapshotError;
+use crate::error::SnapshotMismatch;
+use crate::raft::InstallSnapshotRequest;
+use crate::raft::InstallSnapshotResponse;
+use crate::ErrorSubject;
+use crate::ErrorVerb;
+use crate::MessageSummary;
+use crate::RaftLogStorage;
+use crate::RaftNetworkFactory;
+use crate::RaftStateMachine;
+use crate::RaftTypeConfig;
+use crate::SnapshotSegmentId;
+use crate::StorageError;
+use crate::StorageIOError;
+
+/// Writes the chunks of a snapshot into [`RaftTypeConfig::SnapshotData`].
+///
+/// `SnapshotData` is not required to be file-like, thus `RaftCore` can not write into it by itself. An instance is
+/// built by [`Raft::install_snapshot`](`crate::Raft::install_snapshot`), where the snapshot data is known to be
+/// `AsyncWrite`, and is passed to `RaftCore` along with every chunk.
+pub(crate) struct ChunkWriter<C: RaftTypeConfig> {
+    write_all: for<'a> fn(&'a mut C::SnapshotData, &'a [u8]) -> BoxFuture<'a, io::Result<()>>,
+    shutdown: for<'a> fn(&'a mut C::SnapshotData) -> BoxFuture<'a, io::Result<()>>,
+}
+
+impl<C: RaftTypeConfig> ChunkWriter<C>
+where C::SnapshotData: AsyncWrite + Unpin
+{
+    pub(crate) fn new() -> Self {
+        Self {
+            write_all: |snapshot, data| Box::pin(snapshot.write_all(data)),
+            shutdown: |snapshot| Box::pin(snapshot.shutdown()),
+        }
+    }
+}
+
+impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>>
+    RaftCore<C, N, LS, SM>
+{
+    /// Invoked by leader to send chunks of a snapshot to a follower (§7).
+    ///
+    /// Leaders always send chunks in order. It is important to note that, according to the Raft spec,
+    /// a log may only have one snapshot at any time. As snapshot contents are application specific,
+    /// the Raft log will only store a pointer to the snapshot file along with the index & term.
+    ///
+    /// The response tells the leader the offset of the next chunk to send, so that a leader that starts over
+    /// streaming the same snapshot resumes from where the previous attempt left off.
+    #[tracing::instrument(
+        name = "raft.snapshot",
+        level = "debug",
+        skip_all,
+        fields(
+            node_id = display(&self.id),
+            target = display(&req.vote.node_id),
+            term = req.vote.term,
+            log_index = req.meta.last_log_id.index
+        )
+    )]
+    pub(super) async fn handle_install_snapshot_request(
+        &mut self,
+        req: InstallSnapshotRequest<C>,
+        writer: ChunkWriter<C>,
+    ) -> Result<InstallSnapshotResponse<C::NodeId>, InstallSnapshotError<C::NodeId>> {
+        tracing::debug!(req = display(req.summary()));
+
+        if !self.accept_snapshot_vote(&req.vote).await? {
+            return Ok(InstallSnapshotResponse {
+                vote: self.engine.state.vote.clone(),
+                next_offset: None,
+            });
+        }
+
+        // Compare current snapshot state with received RPC and handle as needed.
+        // - Init a new state if it is empty or building a snapshot locally.
+        // - Mismatched id with offset=0 indicates a new stream has been sent, the old one should be dropped and start
+        //   to receive the new snapshot,
+        // - Mismatched id with offset greater than 0 is an out of order message that should be rejected.
+        match self.snapshot_state.take() {
+            None => {
+                return self.begin_installing_snapshot(req, writer).await;
+            }
+            Some(SnapshotState::Snapshotting { handle, .. }) => {
+                handle.abort(); // Abort the current compaction in favor of installation from leader.
+                return self.begin_installing_snapshot(req, writer).await;
+            }
+            Some(SnapshotState::Streaming {
+                snapshot,
+                id,
+                offset,
+                checksum,
+            }) => {
+                if req.meta.snapshot_id == id {
+                    return self.continue_installing_snapshot(req, writer, offset, snapshot, checksum).await;
+                }
+
+                if req.offset == 0 {
+                    return self.begin_installing_snapshot(req, writer).await;
+                }
+
+                Err(SnapshotMismatch {
+                    expect: SnapshotSegmentId { id: id.clone(), offset },
+                    got: SnapshotSegmentId {
+                        id: req.meta.snapshot_id.clone(),
+                        offset: req.offset,
+                    },
+                }
+                .into())
+            }
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn begin_installing_snapshot(
+        &mut self,
+        req: InstallSnapshotRequest<C>,
+        writer: ChunkWriter<C>,
+    ) -> Result<InstallSnapshotResponse<C::NodeId>, InstallSnapshotError<C::NodeId>> {
+        tracing::debug!(req = display(req.summary()));
+
+        let id = req.meta.snapshot_id.clone();
+
+        if req.offset > 0 {
+            return Err(SnapshotMismatch {
+                expect: SnapshotSegmentId {
+                    id: id.clone(),
+                    offset: 0,
+                },
+                got: SnapshotSegmentId { id, offset: req.offset },
+            }
+            .into());
+        }
+
+        check_chunk_checksum(&req)?;
+
+        // Create a new snapshot and begin writing its contents.
+        let mut snapshot = self.state_machine.begin_receiving_snapshot().await?;
+        (writer.write_all)(snapshot.as_mut(), &req.data).await.map_err(|e| StorageError::IO {
+            source: StorageIOError::new(
+                ErrorSubject::Snapshot(req.meta.signature()),
+                ErrorVerb::Write,
+                AnyError::new(&e),
+            ),
+        })?;
+
+        let mut checksum = crc32fast::Hasher::new();
+        checksum.update(&req.data);
+
+        // If this was a small snapshot, and it is already done, then finish up.
+        if req.done {
+            self.finalize_snapshot_installation(req, writer, snapshot, checksum).await?;
+            return Ok(InstallSnapshotResponse {
+                vote: self.engine.state.vote.clone(),
+                next_offset: None,
+            });
+        }
+
+        // Else, retain snapshot components for later segments & respond.
+        let offset = req.data.len() as u64;
+        self.snapshot_state = Some(SnapshotState::Streaming {
+            offset,
+            id,
+            snapshot,
+            checksum,
+        });
+        Ok(InstallSnapshotResponse {
+            vote: self.engine.state.vote.clone(),
+            next_offset: Some(offset),
+        })
+    }
+
+    /// Write a chunk of the snapshot being received.
+    ///
+    /// A chunk whose bytes are all received is ignored, and the part of a chunk that is already received is not
+    /// written again. A chunk that starts after the received bytes is rejected with the offset to resume from.
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn continue_installing_snapshot(
+        &mut self,
+        req: InstallSnapshotRequest<C>,
+        writer: ChunkWriter<C>,
+        mut offset: u64,
+        mut snapshot: Box<C::SnapshotData>,
+        mut checksum: crc32fast::Hasher,
+    ) -> Result<InstallSnapshotResponse<C::NodeId>, InstallSnapshotError<C::NodeId>> {
+        tracing::debug!(req = display(req.summary()), offset);
+
+        let id = req.meta.snapshot_id.clone();
+        let len = req.data.len() as u64;
+
+        let action = ChunkAction::new(offset, req.offset, len);
+        tracing::debug!(?action, "received snapshot chunk");
+
+        if action == ChunkAction::Gap {
+            let err = SnapshotMismatch {
+                expect: SnapshotSegmentId { id: id.clone(), offset },
+                got: SnapshotSegmentId {
+                    id: id.clone(),
+                    offset: req.offset,
+                },
+            };
+            self.snapshot_state = Some(SnapshotState::Streaming {
+                offset,
+                id,
+                snapshot,
+                checksum,
+            });
+            return Err(err.into());
+        }
+
+        if let Err(e) = check_chunk_checksum(&req) {
+            self.snapshot_state = Some(SnapshotState::Streaming {
+                offset,
+                id,
+                snapshot,
+                checksum,
+            });
+            return Err(e.into());
+        }
+
+        // Write the next segment & update offset.
+        if let ChunkAction::Write { skip } = action {
+            let data = &req.data[skip..];
+
+            if let Err(err) = (writer.write_all)(snapshot.as_mut(), data).await {
+                self.snapshot_state = Some(SnapshotState::Streaming {
+                    offset,
+                    id,
+                    snapshot,
+                    checksum,
+                });
+                return Err(StorageError::from_io_error(
+                    ErrorSubject::Snapshot(req.meta.signature()),
+                    ErrorVerb::Write,
+                    err,
+                )
+                .into());
+            }
+
+            checksum.update(data);
+            offset += data.len() as u64;
+        }
+
+        // If the snapshot stream is done, then finalize.
+        if req.done && req.offset + len == offset {
+            self.finalize_snapshot_installation(req, writer, snapshot, checksum).await?;
+            return Ok(InstallSnapshotResponse {
+                vote: self.engine.state.vote.clone(),
+                next_offset: None,
+            });
+        }
+
+        self.snapshot_state = Some(SnapshotState::Streaming {
+            offset,
+            id,
+            snapshot,
+            checksum,
+        });
+        Ok(InstallSnapshotResponse {
+            vote: self.engine.state.vote.clone(),
+            next_offset: Some(offset),
+        })
+    }
+
+    /// Finalize the installation of a new snapshot.
+    ///
+    /// Any errors which come up from this routine will cause the Raft node to go into shutdown, except a checksum
+    /// mismatch.
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn finalize_snapshot_installation(
+        &mut self,
+        req: InstallSnapshotRequest<C>,
+        writer: ChunkWriter<C>,
+        mut snapshot: Box<C::SnapshotData>,
+        checksum: crc32fast::Hasher,
+    ) -> Result<(), InstallSnapshotError<C::NodeId>> {
+        tracing::debug!(req = display(req.summary()));
+
+        // The received snapshot is dropped: the leader has to send it again from the beginning.
+        if let Some(expect) = req.snapshot_checksum {
+            let got = checksum.finalize();
+            if got != expect {
+                tracing::warn!(expect, got, "snapshot checksum mismatch, drop the received snapshot");
+                return Err(ChecksumMismatch {
+                    id: req.meta.snapshot_id.clone(),
+                    offset: None,
+                    expect,
+                    got,
+                }
+                .into());
+            }
+        }
+
+        (writer.shutdown)(snapshot.as_mut()).await.map_err(|e| StorageError::IO {
+            source: StorageIOError::new(
+                ErrorSubject::Snapshot(req.meta.signature()),
+                ErrorVerb::Write,
+                AnyError::new(&e),
+            ),
+        })?;
+
+        // Caveat: All changes to state machine has to be serialized.
+        //
+        // If `finalize_snapshot_installation` is run in RaftCore thread,
+        // there is chance the last_applied being reset to a previous value:
+        //
+        // ```
+        // RaftCore: -.    install-snapc,            .-> replicate_to_sm_handle.next(),
+        //            |    update last_applied=5     |   update last_applied=2
+        //            |                              |
+        //            v                              |
+        // task:      apply 2------------------------'
+        // --------------------------------------------------------------------> time
+        // ```
+
+        // TODO(xp): do not install if self.engine.st.last_applied >= snapshot.meta.last_applied
+
+        self.install_received_snapshot(req.meta, snapshot).await?;
+
+        Ok(())
+    }
+}
+
+/// Verify the checksum of the data in a snapshot chunk, if the leader sent one.
+fn check_chunk_checksum<C: RaftTypeConfig>(req: &InstallSnapshotRequest<C>) -> Result<(), ChecksumMismatch> {
+    let expect = match req.chunk_checksum {
+        None => return Ok(()),
+        Some(x) => x,
+    };
+
+    let got = crc32fast::hash(&req.data);
+    if got != expect {
+        return Err(ChecksumMismatch {
+            id: req.meta.snapshot_id.clone(),
+            offset: Some(req.offset),
+            expect,
+            got,
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,359 @@
+use std::time::Duration;
+
+use tracing::Instrument;
+
+use crate::async_runtime::InstantOf;
+use crate::core::raft_core::HeartbeatRound;
+use crate::core::RaftCore;
+use crate::error::ForwardToLeader;
+use crate::error::IsWitness;
+use crate::error::NotInMembers;
+use crate::error::Timeout;
+use crate::error::TransferLeaderError;
+use crate::error::TransferLeaderRejected;
+use crate::error::VoteError;
+use crate::network::RPCTypes;
+use crate::progress::Progress;
+use crate::raft::RaftMsg;
+use crate::raft::RaftRespTx;
+use crate::raft::TimeoutNowRequest;
+use crate::raft::TimeoutNowResponse;
+use crate::summary::MessageSummary;
+use crate::AsyncRuntime;
+use crate::Entry;
+use crate::RaftLogStorage;
+use crate::RaftNetwork;
+use crate::RaftNetworkFactory;
+use crate::RaftStateMachine;
+use crate::RaftTypeConfig;
+
+/// An ongoing leadership transfer on a leader.
+///
+/// While it is present, the leader does not accept client writes.
+pub(crate) struct LeaderTransfer<C: RaftTypeConfig> {
+    /// The node to transfer leadership to.
+    pub(crate) target: C::NodeId,
+
+    /// If the transfer is not done before this time, it is aborted and the leader resumes accepting writes.
+    pub(crate) deadline: InstantOf<C>,
+
+    /// Whether the transfer has been announced to the voters, which then grant the target's vote request within the
+    /// lease of this leader.
+    pub(crate) announced: bool,
+
+    /// Whether TimeoutNow RPC has been sent to the target.
+    pub(crate) timeout_now_sent: bool,
+
+    /// Channel to send the result back to the caller. It is `None` once the result is sent.
+    pub(crate) tx: Option<RaftRespTx<(), TransferLeaderError<C::NodeId, C::Node>>>,
+}
+
+impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>>
+    RaftCore<C, N, LS, SM>
+{
+    /// Start to transfer leadership to `target`.
+    #[tracing::instrument(level = "debug", skip(self, tx))]
+    pub(super) async fn handle_transfer_leader(
+        &mut self,
+        target: C::NodeId,
+        tx: RaftRespTx<(), TransferLeaderError<C::NodeId, C::Node>>,
+    ) {
+        if target == self.id {
+            let _ = tx.send(Ok(()));
+            return;
+        }
+
+        let em = &self.engine.state.membership_state.effective;
+        if !em.is_voter(&target) {
+            let _ = tx.send(Err(NotInMembers {
+                node_id: target,
+                membership: em.membership.clone(),
+            }
+            .into()));
+            return;
+        }
+
+        if self.engine.is_witness(&target) {
+            let _ = tx.send(Err(IsWitness { node_id: target }.into()));
+            return;
+        }
+
+        // Only one transfer at a time: redirect the caller to the node that is becoming the leader.
+        if self.leader_transfer_target().is_some() {
+            self.reject_with_forward_to_transfer_target(tx);
+            return;
+        }
+
+        tracing::info!(target = display(&target), "start to transfer leadership");
+
+        let deadline = C::AsyncRuntime::now() + Duration::from_millis(self.config.transfer_leader_timeout);
+        if let Some(l) = &mut self.leader_data {
+            l.transfer = Some(LeaderTransfer {
+                target,
+                deadline,
+                announced: false,
+                timeout_now_sent: false,
+                tx: Some(tx),
+            });
+        } else {
+            unreachable!("it has to be a leader!!!");
+        }
+        self.engine.metrics_flags.set_cluster_changed();
+
+        self.try_send_timeout_now().await;
+    }
+
+    /// Start to transfer leadership to the most up-to-date voter, when this leader is removed from the cluster.
+    ///
+    /// It is started only once for a leader: if it times out or is rejected, the leader steps down without it.
+    pub(super) fn start_leader_transfer_on_removal(&mut self) {
+        let leaving = self.leader_data.as_ref().map(|l| l.leaving).unwrap_or(true);
+        if leaving || self.leader_transfer_target().is_some() {
+            return;
+        }
+
+        let em = &self.engine.state.membership_state.effective;
+        let target = self.engine.state.internal_server_state.leading().and_then(|l| {
+            l.progress
+                .iter()
+                .filter(|(id, _)| id != &self.id && em.is_voter(id) && !self.engine.is_witness(id))
+                .max_by(|(_, a), (_, b)| a.cmp(b))
+                .map(|(id, _)| id.clone())
+        });
+
+        let deadline = C::AsyncRuntime::now() + Duration::from_millis(self.config.transfer_leader_timeout);
+        if let Some(l) = &mut self.leader_data {
+            l.leaving = true;
+
+            if let Some(target) = target {
+                tracing::info!(target = display(&target), "leader is removed, transfer leadership");
+
+                l.transfer = Some(LeaderTransfer {
+                    target,
+                    deadline,
+                    announced: false,
+                    timeout_now_sent: false,
+                    tx: None,
+                });
+            }
+        }
+        self.engine.metrics_flags.set_cluster_changed();
+    }
+
+    /// The target of the ongoing leadership transfer, if there is one.
+    pub(crate) fn leader_transfer_target(&self) -> Option<C::NodeId> {
+        self.leader_data.as_ref().and_then(|l| l.transfer.as_ref()).map(|t| t.target.clone())
+    }
+
+    /// Reject a request because leadership is being transferred, and let the caller retry on the target.
+    pub(crate) fn reject_with_forward_to_transfer_target<T, E>(&self, tx: RaftRespTx<T, E>)
+    where E: From<ForwardToLeader<C::NodeId, C::Node>> {
+        let _ = tx.send(Err(self.forward_to_transfer_target().into()));
+    }
+
+    /// The error to tell a client to send its request to the node this leader is transferring leadership to.
+    pub(crate) fn forward_to_transfer_target(&self) -> ForwardToLeader<C::NodeId, C::Node> {
+        let target = self.leader_transfer_target();
+        ForwardToLeader {
+            leader_id: target.clone(),
+            leader_node: self.get_leader_node(target),
+        }
+    }
+
+    /// Send TimeoutNow RPC to the transfer target if it has caught up with the leader's log.
+    pub(super) async fn try_send_timeout_now(&mut self) {
+        let target = match self.leader_data.as_ref().and_then(|l| l.transfer.as_ref()) {
+            Some(t) if !t.timeout_now_sent => t.target.clone(),
+            _ => return,
+        };
+
+        let last_log_id = self.engine.state.last_log_id();
+
+        let matched = self
+            .engine
+            .state
+            .internal_server_state
+            .leading()
+            .and_then(|l| l.progress.iter().find(|(id, _)| id == &target).map(|(_, v)| v.clone()))
+            .unwrap_or_default();
+
+        if matched < last_log_id {
+            tracing::debug!(
+                target = display(&target),
+                matched = display(matched.summary()),
+                last_log_id = display(last_log_id.summary()),
+                "transfer target is not yet up to date"
+            );
+            return;
+        }
+
+        // The voters reject the vote request of the target within the lease of this leader, unless they have been
+        // told about the transfer. Thus a quorum has to know about it before the target starts the election.
+        if let Some(t) = self.leader_data.as_mut().and_then(|l| l.transfer.as_mut()) {
+            t.announced = true;
+        }
+
+        match self.heartbeat_to_voters(Some(target.clone())).await {
+            HeartbeatRound::Granted => {}
+            HeartbeatRound::HigherVote => return,
+            HeartbeatRound::NotEnough(granted) => {
+                tracing::debug!(
+                    target = display(&target),
+                    granted = debug(&granted),
+                    "leadership transfer is not yet acknowledged by a quorum"
+                );
+                return;
+            }
+        }
+
+        if let Some(t) = self.leader_data.as_mut().and_then(|l| l.transfer.as_mut()) {
+            t.timeout_now_sent = true;
+        }
+
+        let req = TimeoutNowRequest {
+            vote: self.engine.state.vote.clone(),
+            last_log_id,
+        };
+        let vote = self.engine.state.vote.clone();
+        let my_id = self.id.clone();
+        let target_node = self.engine.state.membership_state.effective.get_node(&target).cloned();
+        let mut network = self.network.connect(target.clone(), target_node.as_ref()).await;
+        let tx = self.tx_api.clone();
+        let ttl = Duration::from_millis(self.config.election_timeout_min);
+
+        let _ = C::AsyncRuntime::spawn(
+            async move {
+                let result = match C::AsyncRuntime::timeout(ttl, network.send_timeout_now(req)).await {
+                    Ok(Ok(resp)) => Ok(resp),
+                    Ok(Err(err)) => Err(err.into()),
+                    Err(_timeout) => Err(Timeout {
+                        action: RPCTypes::TimeoutNow,
+                        id: my_id,
+                        target: target.clone(),
+                        timeout: ttl,
+                    }
+                    .into()),
+                };
+
+                let _ = tx.send(RaftMsg::TimeoutNowResult { target, result, vote });
+            }
+            .instrument(tracing::debug_span!("send_timeout_now")),
+        );
+    }
+
+    /// Handle the result of TimeoutNow RPC sent to the transfer target.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(super) fn handle_timeout_now_result(
+        &mut self,
+        target: C::NodeId,
+        result: Result<TimeoutNowResponse<C::NodeId>, TransferLeaderError<C::NodeId, C::Node>>,
+    ) {
+        let transfer = match self.leader_data.as_mut().and_then(|l| l.transfer.as_mut()) {
+            Some(t) if t.target == target => t,
+            _ => return,
+        };
+
+        let res = match result {
+            Ok(resp) if resp.election_started => {
+                tracing::info!(
+                    target = display(&target),
+                    "transfer leadership: target started election"
+                );
+
+                // Keep refusing writes until this node steps down, or the transfer timeout.
+                if let Some(tx) = transfer.tx.take() {
+                    let _ = tx.send(Ok(()));
+                }
+                return;
+            }
+            Ok(resp) => Err(TransferLeaderRejected {
+                target: target.clone(),
+                target_vote: resp.vote,
+            }
+            .into()),
+            Err(e) => Err(e),
+        };
+
+        tracing::info!(
+            target = display(&target),
+            result = debug(&res),
+            "transfer leadership failed, resume"
+        );
+
+        if let Some(tx) = transfer.tx.take() {
+            let _ = tx.send(res);
+        }
+        self.clear_leader_transfer();
+    }
+
+    /// Abort the leadership transfer if it is not done before the deadline.
+    pub(super) fn check_leader_transfer_timeout(&mut self) {
+        let now = C::AsyncRuntime::now();
+
+        let transfer = match self.leader_data.as_mut().and_then(|l| l.transfer.as_mut()) {
+            Some(t) if now >= t.deadline => t,
+            _ => return,
+        };
+
+        tracing::info!(
+            target = display(&transfer.target),
+            "transfer leadership timeout, resume"
+        );
+
+        if let Some(tx) = transfer.tx.take() {
+            let _ = tx.send(Err(Timeout {
+                action: RPCTypes::TimeoutNow,
+                id: self.id.clone(),
+                target: transfer.target.clone(),
+                timeout: Duration::from_millis(self.config.transfer_leader_timeout),
+            }
+            .into()));
+        }
+        self.clear_leader_transfer();
+    }
+
+    /// Respond to the caller of an unfinished leadership transfer when this node quits leader state.
+    pub(super) fn finish_leader_transfer_on_step_down(&mut self) {
+        let transfer = match self.leader_data.as_mut().and_then(|l| l.transfer.take()) {
+            Some(t) => t,
+            None => return,
+        };
+
+        if let Some(tx) = transfer.tx {
+            if self.engine.state.vote.node_id == transfer.target {
+                let _ = tx.send(Ok(()));
+            } else {
+                let _ = tx.send(Err(self.forward_to_leader().into()));
+            }
+        }
+        self.engine.metrics_flags.set_cluster_changed();
+    }
+
+    fn clear_leader_transfer(&mut self) {
+        let transfer = self.leader_data.as_mut().and_then(|l| l.transfer.take());
+
+        // Once the transfer is announced, voters may grant the target regardless of the lease of this leader.
+        // The acknowledgements before that can not be trusted any more.
+        if transfer.map(|t| t.announced).unwrap_or(false) {
+            self.engine.reset_leader_clock();
+        }
+        self.engine.metrics_flags.set_cluster_changed();
+    }
+
+    /// Handle TimeoutNow RPC sent by the leader that is transferring leadership to this node.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(super) async fn handle_timeout_now_request(
+        &mut self,
+        req: TimeoutNowRequest<C::NodeId>,
+    ) -> Result<TimeoutNowResponse<C::NodeId>, VoteError<C::NodeId>> {
+        tracing::debug!(req = display(req.summary()), "handle_timeout_now_request");
+
+        let election_started = self.engine.handle_timeout_now(req);
+        self.run_engine_commands::<Entry<C>>(&[]).await?;
+
+        Ok(TimeoutNowResponse {
+            vote: self.engine.state.vote.clone(),
+            election_started,
+        })
+    }
+}
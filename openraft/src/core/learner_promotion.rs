@@ -0,0 +1,299 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use maplit::btreeset;
+use tokio::sync::oneshot;
+
+use crate::async_runtime::InstantOf;
+use crate::core::Expectation;
+use crate::core::RaftCore;
+use crate::error::AddLearnerError;
+use crate::error::ChangeMembershipError;
+use crate::error::ClientWriteError;
+use crate::error::Fatal;
+use crate::error::InProgress;
+use crate::error::LearnerNotFound;
+use crate::metrics::PromotionStage;
+use crate::raft::ClientWriteResponse;
+use crate::raft::RaftRespTx;
+use crate::AsyncRuntime;
+use crate::ChangeMembers;
+use crate::EntryPayload;
+use crate::LogId;
+use crate::RaftLogStorage;
+use crate::RaftNetworkFactory;
+use crate::RaftStateMachine;
+use crate::RaftTypeConfig;
+
+/// A learner that is waiting to be promoted to a voter.
+///
+/// A promotion proceeds through the stages in [`PromotionStage`]:
+/// it waits for the learner to catch up, then proposes a joint config with the learner, then the uniform config.
+pub(crate) struct LearnerPromotion<C: RaftTypeConfig> {
+    pub(crate) stage: PromotionStage,
+
+    /// The log id of the membership config proposed in the current stage.
+    pub(crate) membership_log_id: Option<LogId<C::NodeId>>,
+
+    /// Since when the learner has stayed caught up, in the `CatchingUp` stage.
+    pub(crate) caught_up_since: Option<InstantOf<C>>,
+
+    /// Channel to send the result back to the caller. It is `None` once it is handed to the uniform config log, or if
+    /// the promotion is started by `Config::auto_promote_learner`.
+    pub(crate) tx: Option<RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>>>,
+}
+
+impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>>
+    RaftCore<C, N, LS, SM>
+{
+    /// Add a learner and promote it to a voter once it catches up.
+    #[tracing::instrument(level = "debug", skip(self, tx))]
+    pub(super) async fn handle_add_learner_and_promote(
+        &mut self,
+        target: C::NodeId,
+        node: Option<C::Node>,
+        tx: RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>>,
+    ) -> Result<(), Fatal<C::NodeId>> {
+        if self.learner_promotions().contains_key(&target) {
+            let st = &self.engine.state;
+            let _ = tx.send(Err(ChangeMembershipError::InProgress(InProgress {
+                committed: st.committed.clone(),
+                membership_log_id: st.membership_state.effective.log_id.clone(),
+            })
+            .into()));
+            return Ok(());
+        }
+
+        let (add_tx, mut add_rx) = oneshot::channel();
+        self.add_learner(target.clone(), node, add_tx).await?;
+
+        // `add_learner()` always responds before returning.
+        match add_rx.try_recv() {
+            Ok(Ok(_resp)) => {}
+            Ok(Err(AddLearnerError::ForwardToLeader(e))) => {
+                let _ = tx.send(Err(e.into()));
+                return Ok(());
+            }
+            Ok(Err(AddLearnerError::MissingNodeInfo(e))) => {
+                let _ = tx.send(Err(ChangeMembershipError::MissingNodeInfo(e).into()));
+                return Ok(());
+            }
+            Ok(Err(AddLearnerError::Fatal(e))) => {
+                let _ = tx.send(Err(e.into()));
+                return Ok(());
+            }
+            Ok(Err(AddLearnerError::NotCaughtUp(_))) => unreachable!("RaftCore does not wait for the learner"),
+            Err(_) => unreachable!("add_learner() did not respond"),
+        }
+
+        self.start_learner_promotion(target, Some(tx));
+        self.try_promote_learners().await
+    }
+
+    /// Register a learner to promote. A promotion started by `Config::auto_promote_learner` is replaced.
+    pub(super) fn start_learner_promotion(
+        &mut self,
+        target: C::NodeId,
+        tx: Option<RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>>>,
+    ) {
+        tracing::info!(target = display(&target), "start to promote learner");
+
+        if let Some(l) = &mut self.leader_data {
+            l.promotions.insert(target, LearnerPromotion {
+                stage: PromotionStage::CatchingUp,
+                membership_log_id: None,
+                caught_up_since: None,
+                tx,
+            });
+        } else {
+            unreachable!("it has to be a leader!!!");
+        }
+        self.engine.metrics_flags.set_cluster_changed();
+    }
+
+    /// The stage of every ongoing learner promotion on this leader.
+    pub(crate) fn learner_promotions(&self) -> BTreeMap<C::NodeId, PromotionStage> {
+        match &self.leader_data {
+            Some(l) => l.promotions.iter().map(|(id, p)| (id.clone(), p.stage)).collect(),
+            None => BTreeMap::new(),
+        }
+    }
+
+    /// Move every ongoing promotion one stage forward if it is ready to.
+    ///
+    /// Membership changes are serialized: nothing is proposed until the effective membership is committed.
+    pub(super) async fn try_promote_learners(&mut self) -> Result<(), Fatal<C::NodeId>> {
+        let targets = match &self.leader_data {
+            Some(l) => l.promotions.keys().cloned().collect::<Vec<_>>(),
+            None => return Ok(()),
+        };
+
+        for target in targets {
+            if !self.engine.state.is_membership_committed() {
+                break;
+            }
+            self.try_promote_learner(target).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn try_promote_learner(&mut self, target: C::NodeId) -> Result<(), Fatal<C::NodeId>> {
+        let em = self.engine.state.membership_state.effective.clone();
+
+        let (stage, proposed) = match self.leader_data.as_ref().and_then(|l| l.promotions.get(&target)) {
+            Some(p) => (p.stage, p.membership_log_id.clone()),
+            None => return Ok(()),
+        };
+
+        // Whether to revert the joint config, because the learner falls behind before it is promoted.
+        let mut cancel = false;
+
+        let goal = match stage {
+            PromotionStage::CatchingUp => {
+                if !em.contains(&target) {
+                    tracing::info!(target = display(&target), "learner is removed, stop promoting");
+
+                    let p = self.remove_learner_promotion(&target);
+                    if let Some(tx) = p.and_then(|p| p.tx) {
+                        let _ = tx.send(Err(ChangeMembershipError::LearnerNotFound(LearnerNotFound {
+                            node_id: target,
+                        })
+                        .into()));
+                    }
+                    return Ok(());
+                }
+
+                // Do not promote a lagging learner: retry when it catches up again.
+                let lagging = self.check_replication_states([&target].into_iter(), Some(Expectation::AtLineRate));
+                if let Err(e) = lagging {
+                    tracing::debug!(error = display(&e), "learner is not yet ready to promote");
+                    self.set_caught_up_since(&target, None);
+                    return Ok(());
+                }
+
+                // Promote only a learner that stays caught up for `learner_promotion_rounds` heartbeat intervals.
+                let now = C::AsyncRuntime::now();
+                let since = self.caught_up_since(&target).unwrap_or(now);
+                self.set_caught_up_since(&target, Some(since));
+
+                let stable =
+                    Duration::from_millis(self.config.heartbeat_interval * self.config.learner_promotion_rounds);
+                if now < since + stable {
+                    tracing::debug!(
+                        target = display(&target),
+                        "learner is caught up, wait for it to stay caught up"
+                    );
+                    return Ok(());
+                }
+
+                let last = em.membership.get_joint_config().last().unwrap();
+                ChangeMembers::Add(btreeset! {target.clone()}).apply_to(last)
+            }
+            PromotionStage::Joint => {
+                if em.log_id != proposed {
+                    // The joint config is replaced by another membership change, start over.
+                    tracing::info!(target = display(&target), "joint config is replaced, restart promotion");
+
+                    self.set_promotion_stage(&target, PromotionStage::CatchingUp, None);
+                    return Ok(());
+                }
+
+                let lagging = self.check_replication_states([&target].into_iter(), Some(Expectation::AtLineRate));
+                if let Err(e) = lagging {
+                    // Revert to the config without the learner, and start over when it catches up again.
+                    tracing::info!(
+                        target = display(&target),
+                        error = display(&e),
+                        "learner falls behind, cancel promotion"
+                    );
+
+                    cancel = true;
+                    em.membership.get_joint_config().first().unwrap().clone()
+                } else {
+                    em.membership.get_joint_config().last().unwrap().clone()
+                }
+            }
+            PromotionStage::Uniform => {
+                // The caller is responded when the uniform config log is applied.
+                tracing::info!(target = display(&target), "learner is promoted");
+
+                self.remove_learner_promotion(&target);
+                return Ok(());
+            }
+        };
+
+        // A cancelled learner is kept as a learner.
+        let new_config = match em.membership.next_safe(goal, cancel) {
+            Ok(x) => x,
+            Err(e) => {
+                let p = self.remove_learner_promotion(&target);
+                if let Some(tx) = p.and_then(|p| p.tx) {
+                    let _ = tx.send(Err(ChangeMembershipError::MissingNodeInfo(e).into()));
+                }
+                return Ok(());
+            }
+        };
+
+        let (next_stage, tx) = if cancel {
+            self.set_caught_up_since(&target, None);
+            (PromotionStage::CatchingUp, None)
+        } else if new_config.is_in_joint_consensus() {
+            (PromotionStage::Joint, None)
+        } else {
+            let tx = self.leader_data.as_mut().and_then(|l| l.promotions.get_mut(&target)).and_then(|p| p.tx.take());
+            (PromotionStage::Uniform, tx)
+        };
+
+        tracing::info!(
+            target = display(&target),
+            stage = debug(next_stage),
+            new_config = debug(&new_config),
+            "promote learner"
+        );
+
+        let log_id = self.write_entry(EntryPayload::Membership(new_config), None, tx.map(Into::into)).await?;
+        self.set_promotion_stage(&target, next_stage, Some(log_id));
+
+        Ok(())
+    }
+
+    fn set_promotion_stage(&mut self, target: &C::NodeId, stage: PromotionStage, log_id: Option<LogId<C::NodeId>>) {
+        if let Some(p) = self.leader_data.as_mut().and_then(|l| l.promotions.get_mut(target)) {
+            p.stage = stage;
+            p.membership_log_id = log_id;
+        }
+        self.engine.metrics_flags.set_cluster_changed();
+    }
+
+    fn caught_up_since(&self, target: &C::NodeId) -> Option<InstantOf<C>> {
+        self.leader_data.as_ref().and_then(|l| l.promotions.get(target)).and_then(|p| p.caught_up_since)
+    }
+
+    fn set_caught_up_since(&mut self, target: &C::NodeId, since: Option<InstantOf<C>>) {
+        if let Some(p) = self.leader_data.as_mut().and_then(|l| l.promotions.get_mut(target)) {
+            p.caught_up_since = since;
+        }
+    }
+
+    fn remove_learner_promotion(&mut self, target: &C::NodeId) -> Option<LearnerPromotion<C>> {
+        let p = self.leader_data.as_mut().and_then(|l| l.promotions.remove(target));
+        self.engine.metrics_flags.set_cluster_changed();
+        p
+    }
+
+    /// Respond to the callers of unfinished promotions when this node quits leader state.
+    pub(super) fn finish_learner_promotions_on_step_down(&mut self) {
+        let promotions = match &mut self.leader_data {
+            Some(l) => std::mem::take(&mut l.promotions),
+            None => return,
+        };
+
+        for (_, p) in promotions {
+            if let Some(tx) = p.tx {
+                let _ = tx.send(Err(self.forward_to_leader().into()));
+            }
+        }
+        self.engine.metrics_flags.set_cluster_changed();
+    }
+}
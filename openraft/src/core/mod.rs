@@ -4,7 +4,13 @@
 //! Also it receives and execute `Command` emitted by `Engine` to apply raft state to underlying storage or forward
 //! messages to other raft nodes.
 
+mod forward_client_write;
+mod graceful_shutdown;
+mod heartbeat;
 mod install_snapshot;
+mod install_snapshot_chunks;
+mod leader_transfer;
+mod learner_promotion;
 mod raft_core;
 pub(crate) mod replication;
 mod replication_expectation;
@@ -13,10 +19,17 @@ mod server_state;
 mod snapshot_state;
 mod tick;
 
+#[cfg(test)] mod snapshot_state_test;
+
+pub(crate) use graceful_shutdown::Drain;
+pub(crate) use install_snapshot_chunks::ChunkWriter;
+pub(crate) use leader_transfer::LeaderTransfer;
+pub(crate) use learner_promotion::LearnerPromotion;
 pub use raft_core::RaftCore;
 pub(crate) use replication_expectation::Expectation;
 pub(crate) use replication_state::replication_lag;
 pub use server_state::ServerState;
+pub(crate) use snapshot_state::ChunkAction;
 pub(crate) use snapshot_state::SnapshotState;
 pub(crate) use snapshot_state::SnapshotUpdate;
 pub(crate) use tick::Tick;
@@ -1,33 +1,39 @@
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::fmt::Display;
+use std::io;
 use std::mem::swap;
 use std::sync::Arc;
+use std::time::Duration;
 
+use anyerror::AnyError;
 use futures::future::AbortHandle;
 use futures::future::Abortable;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
 use futures::TryFutureExt;
 use maplit::btreeset;
-use tokio::sync::broadcast;
-use tokio::sync::mpsc;
 use tokio::sync::oneshot;
-use tokio::sync::watch;
-use tokio::task::JoinHandle;
-use tokio::time::timeout;
-use tokio::time::Duration;
-use tokio::time::Instant;
 use tracing::trace_span;
 use tracing::Instrument;
 use tracing::Level;
 use tracing::Span;
 
+use crate::async_runtime::broadcast;
+use crate::async_runtime::mpsc;
+use crate::async_runtime::watch;
+use crate::async_runtime::Instant;
+use crate::async_runtime::InstantOf;
+use crate::async_runtime::JoinHandle;
 use crate::config::Config;
-use crate::config::SnapshotPolicy;
+use crate::config::SnapshotPolicyContext;
+use crate::core::heartbeat::HeartbeatHandle;
+use crate::core::heartbeat::HeartbeatWorker;
 use crate::core::replication::snapshot_is_within_half_of_threshold;
 use crate::core::replication_lag;
+use crate::core::Drain;
 use crate::core::Expectation;
+use crate::core::LearnerPromotion;
 use crate::core::ServerState;
 use crate::core::SnapshotState;
 use crate::core::SnapshotUpdate;
@@ -43,162 +49,326 @@ use crate::error::ClientWriteError;
 use crate::error::EmptyMembership;
 use crate::error::ExtractFatal;
 use crate::error::Fatal;
+use crate::error::FollowerReadError;
 use crate::error::ForwardToLeader;
 use crate::error::InProgress;
 use crate::error::InitializeError;
+use crate::error::IsWitness;
 use crate::error::LearnerIsLagging;
 use crate::error::LearnerNotFound;
+use crate::error::NotInMembers;
+use crate::error::PurgeLogsError;
 use crate::error::QuorumNotEnough;
 use crate::error::RPCError;
+use crate::error::StaleRead;
 use crate::error::Timeout;
+use crate::error::TriggerElectError;
 use crate::error::VoteError;
+use crate::error::WitnessToLearner;
+use crate::metrics::RaftDataMetrics;
 use crate::metrics::RaftMetrics;
+use crate::metrics::RaftServerMetrics;
 use crate::metrics::RemoveTarget;
+use crate::metrics::ReplicationBackoff;
+use crate::metrics::ReplicationLag;
 use crate::metrics::ReplicationMetrics;
+use crate::metrics::ReplicationProgress;
+use crate::metrics::SnapshotBuildMetrics;
+use crate::metrics::SnapshotBuildProgress;
+use crate::metrics::StepDown;
+use crate::metrics::StepDownReason;
 use crate::metrics::UpdateMatchedLogId;
+use crate::network::RPCOption;
 use crate::progress::Progress;
 use crate::quorum::QuorumSet;
 use crate::raft::AddLearnerResponse;
 use crate::raft::AppendEntriesRequest;
 use crate::raft::AppendEntriesResponse;
+use crate::raft::AppliedEvent;
+use crate::raft::ClientWriteRequest;
 use crate::raft::ClientWriteResponse;
 use crate::raft::RaftMsg;
 use crate::raft::RaftRespTx;
+use crate::raft::RaftStateSummary;
+use crate::raft::TriggerSnapshotResult;
 use crate::raft::VoteRequest;
 use crate::raft::VoteResponse;
 use crate::raft_types::LogIdOptionExt;
 use crate::raft_types::RaftLogId;
+use crate::replication::log_cache::LogCache;
 use crate::replication::ReplicationCore;
 use crate::replication::ReplicationStream;
 use crate::replication::UpdateReplication;
+use crate::responder::ClientResponder;
 use crate::runtime::RaftRuntime;
+use crate::storage::LogFlushed;
 use crate::storage::RaftSnapshotBuilder;
 use crate::storage::Snapshot;
+use crate::storage::SnapshotProgressReporter;
+use crate::storage::SplitStorage;
 use crate::storage::StorageHelper;
 use crate::versioned::Updatable;
 use crate::versioned::Versioned;
+use crate::AsyncRuntime;
 use crate::ChangeMembers;
 use crate::Entry;
 use crate::EntryPayload;
+use crate::ErrorSubject;
+use crate::ErrorVerb;
 use crate::LogId;
 use crate::Membership;
 use crate::MessageSummary;
-use crate::Node;
+use crate::NodeId;
+use crate::NodeInfo;
 use crate::RPCTypes;
+use crate::RaftLogStorage;
 use crate::RaftNetwork;
 use crate::RaftNetworkFactory;
-use crate::RaftStorage;
+use crate::RaftState;
+use crate::RaftStateMachine;
 use crate::RaftTypeConfig;
+use crate::Responder;
 use crate::StorageError;
+use crate::StorageIOError;
 use crate::Update;
 use crate::Vote;
 
+/// The result of a round of heartbeats a leader sends to the voters.
+pub(super) enum HeartbeatRound<NID: NodeId> {
+    /// A quorum acknowledged this leader.
+    Granted,
+
+    /// A higher vote is seen and this node is going to quit leader state.
+    HigherVote,
+
+    /// A quorum can not be reached; it contains the nodes that acknowledged this leader.
+    NotEnough(BTreeSet<NID>),
+}
+
 /// Data for a Leader.
 ///
 /// It is created when RaftCore enters leader state, and will be dropped when it quits leader state.
 pub(crate) struct LeaderData<C: RaftTypeConfig> {
     /// Channels to send result back to client when logs are committed.
-    pub(crate) client_resp_channels: BTreeMap<u64, RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId>>>,
+    pub(crate) client_resp_channels: BTreeMap<u64, ClientResponder<C>>,
 
     /// A mapping of node IDs the replication state of the target node.
     // TODO(xp): make it a field of RaftCore. it does not have to belong to leader.
     //           It requires the Engine to emit correct add/remove replication commands
-    pub(super) nodes: BTreeMap<C::NodeId, ReplicationStream<C::NodeId>>,
+    pub(super) nodes: BTreeMap<C::NodeId, ReplicationStream<C>>,
 
     /// The metrics of all replication streams
     pub(crate) replication_metrics: Versioned<ReplicationMetrics<C::NodeId>>,
+
+    /// The ongoing leadership transfer, if any.
+    pub(crate) transfer: Option<LeaderTransfer<C>>,
+
+    /// Whether this leader is removed by a committed membership and has started to hand its leadership over.
+    pub(crate) leaving: bool,
+
+    /// When this node became the leader.
+    pub(crate) established_at: InstantOf<C>,
+
+    /// The vote of this leader.
+    pub(crate) vote: Vote<C::NodeId>,
+
+    /// Learners that are waiting to be promoted to voters.
+    pub(crate) promotions: BTreeMap<C::NodeId, LearnerPromotion<C>>,
+
+    /// The heartbeat workers of every target, unless `Config::disable_heartbeat` is set.
+    pub(super) heartbeats: BTreeMap<C::NodeId, HeartbeatHandle<C>>,
+
+    /// Sends the committed log id to the heartbeat workers.
+    pub(super) tx_committed: watch::Sender<Option<LogId<C::NodeId>>>,
+
+    /// The receiver every heartbeat worker is given a clone of.
+    pub(super) rx_committed: watch::Receiver<Option<LogId<C::NodeId>>>,
+
+    /// Replication targets that are backed off from because they can not be reached.
+    pub(crate) replication_backoff: BTreeMap<C::NodeId, ReplicationBackoff>,
+
+    /// The latest progress reported by every replication stream.
+    pub(crate) replication_progress: BTreeMap<C::NodeId, ReplicationProgress<C::NodeId, InstantOf<C>>>,
+
+    /// The sending time of the latest request that every target, voter or learner, acknowledged.
+    pub(crate) acked_at: BTreeMap<C::NodeId, InstantOf<C>>,
 }
 
 impl<C: RaftTypeConfig> LeaderData<C> {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(vote: Vote<C::NodeId>) -> Self {
+        let (tx_committed, rx_committed) = C::AsyncRuntime::watch(None);
         Self {
             client_resp_channels: Default::default(),
             nodes: BTreeMap::new(),
             replication_metrics: Versioned::new(ReplicationMetrics::default()),
+            transfer: None,
+            leaving: false,
+            established_at: C::AsyncRuntime::now(),
+            vote,
+            promotions: BTreeMap::new(),
+            heartbeats: BTreeMap::new(),
+            tx_committed,
+            rx_committed,
+            replication_backoff: BTreeMap::new(),
+            replication_progress: BTreeMap::new(),
+            acked_at: BTreeMap::new(),
         }
     }
 }
 
 /// The core type implementing the Raft protocol.
-pub struct RaftCore<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> {
+pub struct RaftCore<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>> {
     /// This node's ID.
     pub(crate) id: C::NodeId,
 
     /// This node's runtime config.
     pub(crate) config: Arc<Config>,
 
+    /// The config updated by `Raft::update_config()`, which is applied at the next tick.
+    pub(crate) pending_config: Option<Config>,
+
     /// The `RaftNetworkFactory` implementation.
     pub(crate) network: N,
 
-    /// The `RaftStorage` implementation.
-    pub(crate) storage: S,
+    /// The `RaftLogStorage` implementation, which stores the vote and the logs.
+    pub(crate) log_store: LS,
 
-    pub(crate) engine: Engine<C::NodeId>,
+    /// The `RaftStateMachine` implementation, which applies logs and builds snapshots.
+    pub(crate) state_machine: SM,
+
+    pub(crate) engine: Engine<C::NodeId, C::Node, InstantOf<C>>,
 
     pub(crate) leader_data: Option<LeaderData<C>>,
 
+    /// The recent logs this leader appended, which are not yet replicated to every target.
+    ///
+    /// It is shared by the replication streams, and is emptied when this node becomes a leader.
+    pub(crate) log_cache: Arc<LogCache<C>>,
+
     /// The node's current snapshot state.
-    pub(crate) snapshot_state: Option<SnapshotState<S::SnapshotData>>,
+    pub(crate) snapshot_state: Option<SnapshotState<C::SnapshotData>>,
+
+    /// The latest progress reported by the builder of the snapshot being built.
+    pub(crate) snapshot_progress: Option<SnapshotBuildProgress>,
+
+    /// Why this leader is stepping down, if it decides so by itself, e.g., by check-quorum.
+    pub(crate) step_down_reason: Option<StepDownReason>,
+
+    /// The latest step down from the leader.
+    pub(crate) last_step_down: Option<StepDown<C::NodeId>>,
 
     /// The last time a heartbeat was received.
-    pub(crate) last_heartbeat: Option<Instant>,
+    pub(crate) last_heartbeat: Option<InstantOf<C>>,
+
+    /// The leadership transfer announced by the leader of this follower: the vote of the leader and the target.
+    pub(crate) leader_transfer_announced: Option<(Vote<C::NodeId>, C::NodeId)>,
+
+    /// The last time a snapshot is built or installed, or the time this node starts.
+    pub(crate) last_snapshot_time: InstantOf<C>,
+
+    /// The number of bytes of logs appended since the last snapshot, for `SnapshotPolicy::SinceLastBytes`.
+    pub(crate) bytes_since_last_snapshot: u64,
+
+    /// A vote whose `SaveVote` command is deferred, to be saved along with the logs of the following
+    /// `AppendInputEntries` with one `RaftLogStorage::append_with_vote()` call.
+    pub(crate) vote_to_save: Option<Vote<C::NodeId>>,
 
     /// The time to elect if a follower does not receive any append-entry message.
-    pub(crate) next_election_time: VoteWiseTime<C::NodeId>,
+    pub(crate) next_election_time: VoteWiseTime<C::NodeId, InstantOf<C>>,
+
+    pub(crate) tx_api: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
+    pub(crate) rx_api: mpsc::UnboundedReceiver<RaftMsg<C, N, LS, SM>>,
+
+    tx_metrics: watch::Sender<RaftMetrics<C::NodeId, C::Node, InstantOf<C>>>,
 
-    pub(crate) tx_api: mpsc::UnboundedSender<RaftMsg<C, N, S>>,
-    pub(crate) rx_api: mpsc::UnboundedReceiver<RaftMsg<C, N, S>>,
+    /// Sends the server metrics, only when they change. See `Raft::server_metrics()`.
+    tx_server_metrics: watch::Sender<RaftServerMetrics<C::NodeId, C::Node>>,
 
-    tx_metrics: watch::Sender<RaftMetrics<C::NodeId>>,
+    /// Sends the data metrics, only when they change. See `Raft::data_metrics()`.
+    tx_data_metrics: watch::Sender<RaftDataMetrics<C::NodeId>>,
+
+    /// Sends an event to the subscribers of `Raft::subscribe_applied()`.
+    pub(crate) tx_applied: broadcast::Sender<AppliedEvent<C>>,
 
     pub(crate) rx_shutdown: oneshot::Receiver<()>,
 
+    /// A graceful shutdown in progress, started by `Raft::shutdown_gracefully()`.
+    pub(crate) drain: Option<Drain<C>>,
+
     pub(crate) span: Span,
 }
 
-impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C, N, S> {
+impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>>
+    RaftCore<C, N, LS, SM>
+{
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn spawn(
         id: C::NodeId,
         config: Arc<Config>,
         network: N,
-        storage: S,
-        tx_api: mpsc::UnboundedSender<RaftMsg<C, N, S>>,
-        rx_api: mpsc::UnboundedReceiver<RaftMsg<C, N, S>>,
-        tx_metrics: watch::Sender<RaftMetrics<C::NodeId>>,
+        log_store: LS,
+        state_machine: SM,
+        tx_api: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
+        rx_api: mpsc::UnboundedReceiver<RaftMsg<C, N, LS, SM>>,
+        tx_metrics: watch::Sender<RaftMetrics<C::NodeId, C::Node, InstantOf<C>>>,
+        tx_server_metrics: watch::Sender<RaftServerMetrics<C::NodeId, C::Node>>,
+        tx_data_metrics: watch::Sender<RaftDataMetrics<C::NodeId>>,
+        tx_applied: broadcast::Sender<AppliedEvent<C>>,
         rx_shutdown: oneshot::Receiver<()>,
-    ) -> JoinHandle<Result<(), Fatal<C::NodeId>>> {
+    ) -> JoinHandle<Result<(), Fatal<C::NodeId>>, <C::AsyncRuntime as AsyncRuntime>::JoinError> {
         let span = tracing::span!(
             parent: tracing::Span::current(),
             Level::DEBUG,
             "RaftCore",
-            id = display(id),
+            id = display(&id),
             cluster = display(&config.cluster_name)
         );
 
+        // The real initial state is loaded from storage in `do_main()`.
+        let init_state = RaftState::new(id.clone());
+        let init_vote = init_state.vote.clone();
+        let log_cache = Arc::new(LogCache::new(config.log_cache_max_entries, config.log_cache_max_bytes));
+
         let this = Self {
+            engine: Engine::new(id.clone(), &init_state, EngineConfig::default()),
+
             id,
             config,
+            pending_config: None,
             network,
-            storage,
+            log_store,
+            state_machine,
 
-            engine: Engine::default(),
             leader_data: None,
+            log_cache,
 
             snapshot_state: None,
+            snapshot_progress: None,
+            step_down_reason: None,
+            last_step_down: None,
             last_heartbeat: None,
-            next_election_time: VoteWiseTime::new(Vote::default(), Instant::now() + Duration::from_secs(86400)),
+            leader_transfer_announced: None,
+            last_snapshot_time: C::AsyncRuntime::now(),
+            bytes_since_last_snapshot: 0,
+            vote_to_save: None,
+            next_election_time: VoteWiseTime::new(init_vote, C::AsyncRuntime::now() + Duration::from_secs(86400)),
 
             tx_api,
             rx_api,
 
             tx_metrics,
+            tx_server_metrics,
+            tx_data_metrics,
+            tx_applied,
 
             rx_shutdown,
 
+            drain: None,
+
             span,
         };
 
-        tokio::spawn(this.main().instrument(trace_span!("spawn").or_current()))
+        C::AsyncRuntime::spawn(this.main().instrument(trace_span!("spawn").or_current()))
     }
 
     /// The main loop of the Raft protocol.
@@ -219,32 +389,40 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         }
     }
 
-    #[tracing::instrument(level="trace", skip(self), fields(id=display(self.id), cluster=%self.config.cluster_name))]
+    #[tracing::instrument(level="trace", skip(self), fields(id=display(&self.id), cluster=%self.config.cluster_name))]
     async fn do_main(&mut self) -> Result<(), Fatal<C::NodeId>> {
         tracing::debug!("raft node is initializing");
 
         let state = {
-            let mut helper = StorageHelper::new(&mut self.storage);
-            helper.get_initial_state().await?
+            let mut helper = StorageHelper::new(&mut SplitStorage {
+                log_store: &mut self.log_store,
+                state_machine: &mut self.state_machine,
+            });
+            helper.get_initial_state(self.id.clone()).await?
         };
 
         // TODO(xp): this is not necessary.
-        self.storage.save_vote(&state.vote).await?;
+        self.log_store.save_vote(&state.vote).await?;
 
-        self.engine = Engine::new(self.id, &state, EngineConfig {
+        self.engine = Engine::new(self.id.clone(), &state, EngineConfig {
             max_applied_log_to_keep: self.config.max_applied_log_to_keep,
             purge_batch_size: self.config.purge_batch_size,
             keep_unsnapshoted_log: self.config.keep_unsnapshoted_log,
+            max_in_snapshot_log_to_keep: self.config.max_in_snapshot_log_to_keep,
+            enable_prevote: self.config.enable_prevote,
+            snapshot_source: self.config.snapshot_source.clone(),
+            commit_quorum: self.config.commit_quorum.clone(),
         });
 
         self.engine.state.last_applied = state.last_applied;
 
         // NOTE: The commit index must be determined by a leader after
-        // successfully committing a new log to the cluster.
+        // successfully committing a new log to the cluster, unless it is saved by the log store.
         self.engine.state.committed = None;
+        self.apply_saved_committed().await?;
 
         // Fetch the most recent snapshot in the system.
-        if let Some(snapshot) = self.storage.get_current_snapshot().await? {
+        if let Some(snapshot) = self.state_machine.get_current_snapshot().await? {
             self.engine.snapshot_last_log_id = Some(snapshot.meta.last_log_id);
             self.engine.metrics_flags.set_data_changed();
         }
@@ -298,11 +476,16 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         // controllers and simply awaits the delegated loop to return, which will only take place
         // if some error has been encountered, or if a state change is required.
         loop {
+            self.record_step_down();
+            self.finish_leader_transfer_on_step_down();
+            self.finish_learner_promotions_on_step_down();
+            self.finish_client_writes_on_step_down();
             self.leader_data = None;
 
             match &self.engine.state.server_state {
                 ServerState::Leader => {
-                    self.leader_data = Some(LeaderData::new());
+                    self.leader_data = Some(LeaderData::new(self.engine.state.vote.clone()));
+                    self.log_cache.clear();
                     self.leader_loop().await?;
                 }
                 ServerState::Candidate | ServerState::Follower | ServerState::Learner => {
@@ -330,26 +513,59 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     /// request (its information may be stale if a more recent leader has been elected). Raft
     /// handles this by having the leader exchange heartbeat messages with a majority of the
     /// cluster before responding to read-only requests.
+    ///
+    /// On success it sends back the read log id: the log id that has to be applied to the state machine before a
+    /// linearizable read can be served, i.e., the read-index.
     #[tracing::instrument(level = "trace", skip(self, tx))]
-    pub(super) async fn handle_check_is_leader_request(&mut self, tx: RaftRespTx<(), CheckIsLeaderError<C::NodeId>>) {
+    pub(super) async fn handle_check_is_leader_request(
+        &mut self,
+        tx: RaftRespTx<LogId<C::NodeId>, CheckIsLeaderError<C::NodeId, C::Node>>,
+    ) {
+        let read_log_id = self.read_log_id();
+
+        match self.heartbeat_to_voters(None).await {
+            HeartbeatRound::Granted => {
+                let _ = tx.send(Ok(read_log_id));
+            }
+            HeartbeatRound::HigherVote => {
+                // Leadership is lost in the middle of the heartbeat round: the read log id can not be trusted.
+                self.reject_with_forward_to_leader(tx);
+            }
+            HeartbeatRound::NotEnough(granted) => {
+                let _ = tx.send(Err(QuorumNotEnough {
+                    cluster: self.engine.state.membership_state.effective.membership.summary(),
+                    got: granted,
+                }
+                .into()));
+            }
+        }
+    }
+
+    /// Send a heartbeat to every other voter and wait until a quorum acknowledges this leader.
+    ///
+    /// If `leader_transfer_to` is `Some`, the heartbeat announces the leadership transfer to this node.
+    pub(super) async fn heartbeat_to_voters(
+        &mut self,
+        leader_transfer_to: Option<C::NodeId>,
+    ) -> HeartbeatRound<C::NodeId> {
         // Setup sentinel values to track when we've received majority confirmation of leadership.
 
         let em = &self.engine.state.membership_state.effective;
-        let mut granted = btreeset! {self.id};
+        let mut granted = btreeset! {self.id.clone()};
 
         if em.is_quorum(granted.iter()) {
-            let _ = tx.send(Ok(()));
-            return;
+            return HeartbeatRound::Granted;
         }
 
         // Spawn parallel requests, all with the standard timeout for heartbeats.
         let mut pending = FuturesUnordered::new();
+        let sending_time = C::AsyncRuntime::now();
 
         let voter_progresses = if let Some(l) = &self.engine.state.internal_server_state.leading() {
             l.progress
                 .iter()
                 .filter(|(id, _v)| l.progress.is_voter(id) == Some(true))
-                .copied()
+                .cloned()
                 .collect::<Vec<_>>()
         } else {
             unreachable!("it has to be a leader!!!");
@@ -361,35 +577,39 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             }
 
             let rpc = AppendEntriesRequest {
-                vote: self.engine.state.vote,
+                vote: self.engine.state.vote.clone(),
                 prev_log_id: matched,
                 entries: vec![],
-                leader_commit: self.engine.state.committed,
+                leader_commit: self.engine.state.committed.clone(),
+                compressed_entries: None,
+                leader_transfer_to: leader_transfer_to.clone(),
             };
 
-            let my_id = self.id;
+            let my_id = self.id.clone();
             let target_node = self.engine.state.membership_state.effective.get_node(&target).cloned();
-            let mut network = self.network.connect(target, target_node.as_ref()).await;
+            let mut network = self.network.connect(target.clone(), target_node.as_ref()).await;
 
             let ttl = Duration::from_millis(self.config.heartbeat_interval);
 
-            let task = tokio::spawn(
+            let task_target = target.clone();
+            let task = C::AsyncRuntime::spawn(
                 async move {
-                    let outer_res = timeout(ttl, network.send_append_entries(rpc)).await;
+                    let option = RPCOption::new(ttl);
+                    let outer_res = C::AsyncRuntime::timeout(ttl, network.append_entries(rpc, option)).await;
                     match outer_res {
                         Ok(append_res) => match append_res {
-                            Ok(x) => Ok((target, x)),
-                            Err(err) => Err((target, err)),
+                            Ok(x) => Ok((task_target, x)),
+                            Err(err) => Err((task_target, err)),
                         },
                         Err(_timeout) => {
                             let timeout_err = Timeout {
                                 action: RPCTypes::AppendEntries,
                                 id: my_id,
-                                target,
+                                target: task_target.clone(),
                                 timeout: ttl,
                             };
 
-                            Err((target, RPCError::Timeout(timeout_err)))
+                            Err((task_target, RPCError::Timeout(timeout_err)))
                         }
                     }
                 }
@@ -406,7 +626,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             let (target, data) = match res {
                 Ok(Ok(res)) => res,
                 Ok(Err((target, err))) => {
-                    tracing::error!(target=display(target), error=%err, "timeout while confirming leadership for read request");
+                    tracing::error!(target=display(target), error=%err, "timeout while confirming leadership");
                     continue;
                 }
                 Err((target, err)) => {
@@ -420,30 +640,230 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                 // TODO: there is no guarantee the response vote is greater than local. Because local vote may already
                 //       changed.
                 assert!(vote > self.engine.state.vote);
+                self.engine.reset_leader_clock();
                 self.engine.state.vote = vote;
                 // TODO(xp): deal with storage error
                 self.save_vote().await.unwrap();
-                // TODO(xp): if receives error about a higher term, it should stop at once?
                 self.set_target_state(ServerState::Follower);
+                return HeartbeatRound::HigherVote;
             }
 
+            // A heartbeat response also extends the read lease.
+            self.update_acked(target.clone(), sending_time);
             granted.insert(target);
 
             let mem = &self.engine.state.membership_state.effective;
             if mem.is_quorum(granted.iter()) {
-                let _ = tx.send(Ok(()));
-                return;
+                return HeartbeatRound::Granted;
             }
         }
 
         // If we've hit this location, then we've failed to gather needed confirmations due to
         // request failures.
+        HeartbeatRound::NotEnough(granted)
+    }
+
+    /// Returns the log id a leader has to apply before serving a linearizable read.
+    ///
+    /// It is the committed log id if the leader has already committed a log in its own term.
+    /// Otherwise the committed log id may be stale, and the last log id, which includes the blank log this leader
+    /// appended at the start of its term, is used instead.
+    fn read_log_id(&self) -> LogId<C::NodeId> {
+        let st = &self.engine.state;
+
+        if let Some(committed) = &st.committed {
+            if committed.leader_id == st.vote.leader_id() {
+                return committed.clone();
+            }
+        }
+
+        st.last_log_id().expect("a leader has at least the blank log of its term")
+    }
+
+    /// Handle a request for the read lease of this leader.
+    ///
+    /// The lease is valid only when this leader has committed a log in its own term, i.e., it knows about all
+    /// committed logs, and a quorum has acknowledged it.
+    #[tracing::instrument(level = "trace", skip(self, tx))]
+    pub(super) fn handle_get_read_lease(&self, tx: RaftRespTx<InstantOf<C>, CheckIsLeaderError<C::NodeId, C::Node>>) {
+        if self.leader_transfer_target().is_some() {
+            self.reject_with_forward_to_transfer_target(tx);
+            return;
+        }
+
+        let st = &self.engine.state;
+
+        if st.vote.node_id != self.id || !st.vote.committed {
+            self.reject_with_forward_to_leader(tx);
+            return;
+        }
+
+        let committed_in_term = st.committed.as_ref().map(|c| c.leader_id == st.vote.leader_id()).unwrap_or(false);
+
+        let quorum_acked = match (committed_in_term, self.engine.leader_quorum_acked()) {
+            (true, Some(t)) => t,
+            _ => {
+                let _ = tx.send(Err(QuorumNotEnough {
+                    cluster: st.membership_state.effective.membership.summary(),
+                    got: self.leader_acked_by(None),
+                }
+                .into()));
+                return;
+            }
+        };
+
+        // No lease if the clock drift is not less than the election timeout: it expires when it is acked.
+        let lease_ms = self.config.election_timeout_min.saturating_sub(self.config.clock_drift_bound);
+        let lease = quorum_acked + Duration::from_millis(lease_ms);
+
+        let _ = tx.send(Ok(lease));
+    }
+
+    /// Handle a read on a follower, which is served only if the leader is heard from within `max_staleness`.
+    #[tracing::instrument(level = "trace", skip(self, tx))]
+    pub(super) fn handle_follower_read(
+        &self,
+        max_staleness: Duration,
+        tx: RaftRespTx<Option<LogId<C::NodeId>>, FollowerReadError<C::NodeId, C::Node>>,
+    ) {
+        let st = &self.engine.state;
+
+        if !st.vote.committed || st.vote.node_id == self.id {
+            self.reject_with_forward_to_leader(tx);
+            return;
+        }
+
+        let elapsed = self.engine.leader_contact_elapsed(C::AsyncRuntime::now());
+
+        match elapsed {
+            Some(e) if e <= max_staleness => {
+                let _ = tx.send(Ok(st.last_applied.clone()));
+            }
+            _ => {
+                let _ = tx.send(Err(StaleRead {
+                    forward: self.forward_to_leader(),
+                    elapsed,
+                    max_staleness,
+                }
+                .into()));
+            }
+        }
+    }
+
+    /// The nodes that have acknowledged this leader, with a request sent no earlier than `since`.
+    fn leader_acked_by(&self, since: Option<InstantOf<C>>) -> BTreeSet<C::NodeId> {
+        let leader = match self.engine.state.internal_server_state.leading() {
+            Some(l) => l,
+            None => return BTreeSet::new(),
+        };
+
+        leader
+            .clock_progress
+            .iter()
+            .filter(|(_, t)| t.is_some() && *t >= since)
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Record that `target` acknowledged a request this leader sent at `sending_time`.
+    fn update_acked(&mut self, target: C::NodeId, sending_time: InstantOf<C>) {
+        if let Some(l) = &mut self.leader_data {
+            let t = l.acked_at.entry(target.clone()).or_insert(sending_time);
+            if *t < sending_time {
+                *t = sending_time;
+            }
+        }
+        self.engine.update_leader_clock(target, sending_time);
+    }
+
+    /// Check-quorum: step down if this leader is not acknowledged by a quorum within the max election timeout.
+    ///
+    /// The pending client writes can not be committed by this node any more, and are failed with `QuorumNotEnough`.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) async fn check_quorum(&mut self) -> Result<(), StorageError<C::NodeId>> {
+        if !self.config.enable_check_quorum {
+            return Ok(());
+        }
+
+        let established_at = match &self.leader_data {
+            Some(l) => l.established_at,
+            None => return Ok(()),
+        };
+
+        let em = &self.engine.state.membership_state.effective;
+        if em.is_quorum([self.id.clone()].iter()) {
+            // A single voter cluster never loses its quorum.
+            return Ok(());
+        }
+
+        let timeout = Duration::from_millis(self.config.election_timeout_max);
+        let now = C::AsyncRuntime::now();
+
+        let last_acked = match self.engine.leader_quorum_acked() {
+            Some(t) => std::cmp::max(t, established_at),
+            None => established_at,
+        };
+
+        if now < last_acked + timeout {
+            return Ok(());
+        }
+
+        let err = QuorumNotEnough {
+            cluster: em.membership.summary(),
+            got: self.leader_acked_by(Some(now - timeout)),
+        };
+
+        tracing::info!(
+            last_acked = debug(&last_acked),
+            error = display(&err),
+            "check-quorum: leader is not acknowledged by a quorum, step down"
+        );
+
+        if let Some(l) = &mut self.leader_data {
+            for (_, tx) in std::mem::take(&mut l.client_resp_channels) {
+                tx.send(Err(err.clone().into()));
+            }
+        }
+
+        self.step_down_reason = Some(StepDownReason::LostQuorum);
+        self.engine.leader_lost_quorum();
+        self.run_engine_commands::<Entry<C>>(&[]).await?;
 
-        let _ = tx.send(Err(QuorumNotEnough {
-            cluster: self.engine.state.membership_state.effective.membership.summary(),
-            got: granted,
+        Ok(())
+    }
+
+    /// Record why this node quits the leader state, for `RaftMetrics::last_step_down`.
+    ///
+    /// A step down not decided by this leader itself is caused by a transfer it started, a membership change that
+    /// removes it, or otherwise a higher vote it saw.
+    fn record_step_down(&mut self) {
+        let reason = self.step_down_reason.take();
+
+        let l = match &self.leader_data {
+            Some(l) => l,
+            None => return,
+        };
+
+        let server_state = self.engine.state.server_state;
+        if server_state == ServerState::Leader || server_state == ServerState::Shutdown {
+            return;
         }
-        .into()));
+
+        let transferred = l.transfer.as_ref().map(|t| t.timeout_now_sent).unwrap_or(false);
+
+        let reason = match reason {
+            Some(r) => r,
+            None if transferred => StepDownReason::Transferred,
+            None if !self.engine.state.membership_state.effective.is_voter(&self.id) => StepDownReason::Removed,
+            None => StepDownReason::HigherVote,
+        };
+
+        tracing::info!(vote = debug(&l.vote), reason = debug(reason), "leader steps down");
+
+        self.last_step_down = Some(StepDown {
+            vote: l.vote.clone(),
+            reason,
+        });
     }
 
     /// Add a new node to the cluster as a learner, bringing it up-to-speed, and then responding
@@ -461,8 +881,8 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     pub(super) async fn add_learner(
         &mut self,
         target: C::NodeId,
-        node: Option<Node>,
-        tx: RaftRespTx<AddLearnerResponse<C::NodeId>, AddLearnerError<C::NodeId>>,
+        node: Option<C::Node>,
+        tx: RaftRespTx<AddLearnerResponse<C::NodeId>, AddLearnerError<C::NodeId, C::Node>>,
     ) -> Result<(), Fatal<C::NodeId>> {
         if let Some(l) = &self.leader_data {
             tracing::debug!(
@@ -480,7 +900,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         let curr = &self.engine.state.membership_state.effective;
         if curr.contains(&target) {
             let matched = if let Some(l) = &self.engine.state.internal_server_state.leading() {
-                *l.progress.get(&target)
+                l.progress.get(&target).clone()
             } else {
                 unreachable!("it has to be a leader!!!");
             };
@@ -492,14 +912,14 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             );
 
             let _ = tx.send(Ok(AddLearnerResponse {
-                membership_log_id: self.engine.state.membership_state.effective.log_id,
+                membership_log_id: self.engine.state.membership_state.effective.log_id.clone(),
                 matched,
             }));
             return Ok(());
         }
 
         let curr = &self.engine.state.membership_state.effective.membership;
-        let res = curr.add_learner(target, node);
+        let res = curr.add_learner(target.clone(), node);
         let new_membership = match res {
             Ok(x) => x,
             Err(e) => {
@@ -510,7 +930,11 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
 
         tracing::debug!(?new_membership, "new_membership with added learner: {}", target);
 
-        let log_id = self.write_entry(EntryPayload::Membership(new_membership), None).await?;
+        let log_id = self.write_entry(EntryPayload::Membership(new_membership), None, None).await?;
+
+        if self.config.auto_promote_learner {
+            self.start_learner_promotion(target.clone(), None);
+        }
 
         tracing::debug!(
             "after add target node {} as learner {:?}",
@@ -529,30 +953,92 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     /// Submit change-membership by writing a Membership log entry, if the `expect` is satisfied.
     ///
     /// If `turn_to_learner` is `true`, removed `voter` will becomes `learner`. Otherwise they will be just removed.
+    /// `turn_to_learner` is ignored for `ChangeMembers::Exact` and `ChangeMembers::RemoveLearners`, which specify the
+    /// learners explicitly.
     #[tracing::instrument(level = "debug", skip(self, tx))]
     pub(super) async fn change_membership(
         &mut self,
         changes: ChangeMembers<C::NodeId>,
         expectation: Option<Expectation>,
         turn_to_learner: bool,
-        tx: RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId>>,
+        tx: RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>>,
     ) -> Result<(), Fatal<C::NodeId>> {
+        let new_config = match self.check_change_membership(changes, expectation, turn_to_learner) {
+            Ok(x) => x,
+            Err(e) => {
+                let _ = tx.send(Err(ClientWriteError::ChangeMembershipError(e)));
+                return Ok(());
+            }
+        };
+
+        self.write_entry(EntryPayload::Membership(new_config), None, Some(tx.into())).await?;
+        Ok(())
+    }
+
+    /// Check if a change-membership would be accepted, without writing any log.
+    ///
+    /// It returns the membership config the change ends up with: if the change goes through a joint config, it is the
+    /// uniform config proposed after the joint config is committed.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(super) fn change_membership_dry_run(
+        &self,
+        changes: ChangeMembers<C::NodeId>,
+        expectation: Option<Expectation>,
+        turn_to_learner: bool,
+    ) -> Result<Membership<C::NodeId, C::Node>, ChangeMembershipError<C::NodeId>> {
+        let new_config = self.check_change_membership(changes.clone(), expectation, turn_to_learner)?;
+
+        if !new_config.is_in_joint_consensus() {
+            return Ok(new_config);
+        }
+
+        let last = new_config.get_joint_config().last().unwrap();
+        let members = changes.clone().apply_to(last);
+        let uniform = match &changes {
+            ChangeMembers::Exact { learners, .. } => new_config.next_safe_exact(members, learners)?,
+            _ => new_config.next_safe(members, turn_to_learner)?,
+        };
+
+        Ok(uniform)
+    }
+
+    /// Check if a change-membership is allowed and build the membership config to propose.
+    fn check_change_membership(
+        &self,
+        changes: ChangeMembers<C::NodeId>,
+        expectation: Option<Expectation>,
+        turn_to_learner: bool,
+    ) -> Result<Membership<C::NodeId, C::Node>, ChangeMembershipError<C::NodeId>> {
         let last = self.engine.state.membership_state.effective.membership.get_joint_config().last().unwrap();
+
+        // Removing learners is changing to exactly the current voters and the other learners.
+        let changes = match changes {
+            ChangeMembers::RemoveLearners(removed) => {
+                let learners = self.engine.state.membership_state.effective.membership.learner_ids();
+                ChangeMembers::Exact {
+                    voters: last.clone(),
+                    learners: learners.filter(|id| !removed.contains(id)).collect(),
+                }
+            }
+            c => c,
+        };
+
+        let exact_learners = match &changes {
+            ChangeMembers::Exact { learners, .. } => Some(learners.clone()),
+            _ => None,
+        };
+        let witnesses = match &changes {
+            ChangeMembers::AddWitnesses(witnesses) => Some(witnesses.clone()),
+            _ => None,
+        };
         let members = changes.apply_to(last);
 
         // Ensure cluster will have at least one node.
         if members.is_empty() {
-            let _ = tx.send(Err(ClientWriteError::ChangeMembershipError(
-                ChangeMembershipError::EmptyMembership(EmptyMembership {}),
-            )));
-            return Ok(());
+            return Err(ChangeMembershipError::EmptyMembership(EmptyMembership {}));
         }
 
-        let res = self.check_membership_committed();
-        if let Err(e) = res {
-            let _ = tx.send(Err(ClientWriteError::ChangeMembershipError(e)));
-            return Ok(());
-        }
+        self.check_membership_committed()?;
 
         let mem = &self.engine.state.membership_state.effective;
         let curr = mem.membership.clone();
@@ -560,36 +1046,66 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         let old_members = mem.voter_ids().collect::<BTreeSet<_>>();
         let only_in_new = members.difference(&old_members);
 
-        let new_config = {
-            let res = curr.next_safe(members.clone(), turn_to_learner);
-            match res {
-                Ok(x) => x,
-                Err(e) => {
-                    let change_err = ChangeMembershipError::MissingNodeInfo(e);
-                    let _ = tx.send(Err(ClientWriteError::ChangeMembershipError(change_err)));
-                    return Ok(());
-                }
+        // A node that is not added as a learner is reported before building the new config, which otherwise fails
+        // with a `MissingNodeInfo` if the other nodes have node infos.
+        let learners = exact_learners.iter().flatten();
+        for node_id in only_in_new.clone().chain(learners) {
+            if !mem.contains(node_id) {
+                return Err(ChangeMembershipError::LearnerNotFound(LearnerNotFound {
+                    node_id: node_id.clone(),
+                }));
             }
+        }
+
+        // A witness has no log payloads to serve as a learner.
+        if let Some(node_id) = exact_learners.iter().flatten().find(|id| curr.is_witness(id) && !members.contains(id)) {
+            return Err(ChangeMembershipError::WitnessToLearner(WitnessToLearner {
+                node_id: node_id.clone(),
+            }));
+        }
+
+        let new_config = match (&exact_learners, &witnesses) {
+            (Some(learners), _) => curr.next_safe_exact(members.clone(), learners)?,
+            (None, Some(witnesses)) => curr.next_safe_witnesses(witnesses)?,
+            (None, None) => curr.next_safe(members.clone(), turn_to_learner)?,
         };
 
         tracing::debug!(?new_config, "new_config");
 
-        for node_id in only_in_new.clone() {
-            if !mem.contains(node_id) {
-                let not_found = LearnerNotFound { node_id: *node_id };
-                let _ = tx.send(Err(ClientWriteError::ChangeMembershipError(
-                    ChangeMembershipError::LearnerNotFound(not_found),
-                )));
-                return Ok(());
-            }
+        self.check_replication_states(only_in_new, expectation)?;
+
+        if self.config.guard_membership_quorum {
+            self.check_live_quorum(&new_config)?;
         }
 
-        if let Err(e) = self.check_replication_states(only_in_new, expectation) {
-            let _ = tx.send(Err(e.into()));
-            return Ok(());
+        Ok(new_config)
+    }
+
+    /// Check that every config in `new_config` has a quorum of live voters, for `Config::guard_membership_quorum`.
+    ///
+    /// A voter is live if it acknowledged a request this leader sent within the last `election_timeout_max`. The
+    /// leader is always live, and a voter that has not acknowledged any request of this leader is taken as offline.
+    fn check_live_quorum(&self, new_config: &Membership<C::NodeId, C::Node>) -> Result<(), QuorumNotEnough<C::NodeId>> {
+        let since = C::AsyncRuntime::now() - Duration::from_millis(self.config.election_timeout_max);
+
+        let mut live = self
+            .leader_data
+            .iter()
+            .flat_map(|l| l.acked_at.iter())
+            .filter(|(_, t)| **t >= since)
+            .map(|(id, _)| id.clone())
+            .collect::<BTreeSet<_>>();
+        live.insert(self.id.clone());
+
+        for voters in new_config.get_joint_config() {
+            if !voters.is_quorum(live.iter()) {
+                return Err(QuorumNotEnough {
+                    cluster: new_config.summary(),
+                    got: live.into_iter().filter(|id| new_config.is_voter(id)).collect(),
+                });
+            }
         }
 
-        self.write_entry(EntryPayload::Membership(new_config), Some(tx)).await?;
         Ok(())
     }
 
@@ -602,13 +1118,13 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         }
 
         Err(ChangeMembershipError::InProgress(InProgress {
-            committed: st.committed,
-            membership_log_id: st.membership_state.effective.log_id,
+            committed: st.committed.clone(),
+            membership_log_id: st.membership_state.effective.log_id.clone(),
         }))
     }
 
     /// return Ok if all the current replication states satisfy the `expectation` for changing membership.
-    fn check_replication_states<'n>(
+    pub(super) fn check_replication_states<'n>(
         &self,
         nodes: impl Iterator<Item = &'n C::NodeId>,
         expectation: Option<Expectation>,
@@ -629,19 +1145,19 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                     // Expect to be at line rate but not.
 
                     let matched = if let Some(l) = &self.engine.state.internal_server_state.leading() {
-                        *l.progress.get(node_id)
+                        l.progress.get(node_id).clone()
                     } else {
                         unreachable!("it has to be a leader!!!");
                     };
 
-                    let distance = replication_lag(&matched.map(|x| x.index), &last_log_id.map(|x| x.index));
+                    let distance = replication_lag(&matched.index(), &last_log_id.index());
 
                     if distance <= self.config.replication_lag_threshold {
                         continue;
                     }
 
                     let lagging = LearnerIsLagging {
-                        node_id: *node_id,
+                        node_id: node_id.clone(),
                         matched,
                         distance,
                     };
@@ -659,30 +1175,64 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     /// I.e.: append the log entry to local store, forward it to a quorum(including the leader), waiting for it to be
     /// committed and applied.
     ///
+    /// `context` is the application metadata stored along with the payload, see
+    /// [`Entry::context`](`crate::Entry::context`).
+    ///
     /// The result of applying it to state machine is sent to `resp_tx`, if it is not `None`.
     /// The calling side may not receive a result from `resp_tx`, if raft is shut down.
-    #[tracing::instrument(level = "debug", skip_all, fields(id = display(self.id)))]
+    #[tracing::instrument(level = "debug", skip_all, fields(id = display(&self.id)))]
     pub async fn write_entry(
         &mut self,
         payload: EntryPayload<C>,
-        resp_tx: Option<RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId>>>,
+        context: Option<Vec<u8>>,
+        resp_tx: Option<ClientResponder<C>>,
     ) -> Result<LogId<C::NodeId>, Fatal<C::NodeId>> {
         tracing::debug!(payload = display(payload.summary()), "write_entry");
 
-        let mut entry_refs = [EntryRef::new(&payload)];
+        let mut entry_refs = [EntryRef::with_context(&payload, context.as_deref())];
         // TODO: it should returns membership config error etc. currently this is done by the caller.
         self.engine.leader_append_entries(&mut entry_refs);
 
         // Install callback channels.
         if let Some(tx) = resp_tx {
             if let Some(l) = &mut self.leader_data {
-                l.client_resp_channels.insert(entry_refs[0].log_id.index, tx);
+                l.client_resp_channels.insert(entry_refs[0].get_log_id().index, tx);
+            }
+        }
+
+        self.run_engine_commands(&entry_refs).await?;
+
+        Ok(entry_refs[0].get_log_id().clone())
+    }
+
+    /// Write a batch of log entries to the cluster through raft protocol.
+    ///
+    /// The entries are appended as consecutive logs in the order of `rpcs`, in a single pass.
+    /// The result of applying the i-th entry is sent to the i-th channel in `resp_txs`.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = display(&self.id), n = rpcs.len()))]
+    pub async fn write_entries(
+        &mut self,
+        rpcs: Vec<ClientWriteRequest<C>>,
+        resp_txs: Vec<ClientResponder<C>>,
+    ) -> Result<(), Fatal<C::NodeId>> {
+        debug_assert_eq!(rpcs.len(), resp_txs.len());
+
+        let mut entry_refs = rpcs
+            .iter()
+            .map(|rpc| EntryRef::with_context(&rpc.payload, rpc.context.as_deref()))
+            .collect::<Vec<_>>();
+        self.engine.leader_append_entries(&mut entry_refs);
+
+        // Install callback channels.
+        if let Some(l) = &mut self.leader_data {
+            for (ent, tx) in entry_refs.iter().zip(resp_txs) {
+                l.client_resp_channels.insert(ent.get_log_id().index, tx);
             }
         }
 
         self.run_engine_commands(&entry_refs).await?;
 
-        Ok(*entry_refs[0].get_log_id())
+        Ok(())
     }
 
     /// Flush cached changes of metrics to notify metrics watchers with updated metrics.
@@ -712,23 +1262,47 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             Update::AsIs => self.tx_metrics.borrow().replication.clone(),
         };
 
+        self.report_server_metrics();
+        self.report_data_metrics(&replication);
+
         let m = RaftMetrics {
             running_state: Ok(()),
-            id: self.id,
+            id: self.id.clone(),
 
             // --- data ---
             current_term: self.engine.state.vote.term,
             last_log_index: self.engine.state.last_log_id().map(|id| id.index),
-            last_applied: self.engine.state.last_applied,
-            snapshot: self.engine.snapshot_last_log_id,
+            last_applied: self.engine.state.last_applied.clone(),
+            snapshot: self.engine.snapshot_last_log_id.clone(),
+            purged: self.engine.state.last_purged_log_id(),
+            snapshot_build: SnapshotBuildMetrics {
+                building: matches!(self.snapshot_state, Some(SnapshotState::Snapshotting { .. })),
+                last_snapshot_log_id: self.engine.snapshot_last_log_id.clone(),
+                progress: self.snapshot_progress,
+            },
 
             // --- cluster ---
             state: self.engine.state.server_state,
             current_leader: self.current_leader(),
             membership_config: self.engine.state.membership_state.effective.clone(),
+            transferring_leader_to: self.leader_transfer_target(),
+            learner_promotions: self.learner_promotions(),
+            last_quorum_acked: self.engine.leader_quorum_acked(),
+            last_election_pre_vote: self.engine.last_election_pre_vote,
+            last_step_down: self.last_step_down.clone(),
 
             // --- replication ---
             replication,
+            replication_backoff: match &self.leader_data {
+                Some(l) => l.replication_backoff.clone(),
+                None => BTreeMap::new(),
+            },
+            replication_progress: match &self.leader_data {
+                Some(l) => l.replication_progress.clone(),
+                None => BTreeMap::new(),
+            },
+            replication_lag: self.replication_lag(),
+            log_cache: self.log_cache.metrics(),
         };
 
         {
@@ -743,20 +1317,95 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         let res = self.tx_metrics.send(m);
 
         if let Err(err) = res {
-            tracing::error!(error=%err, id=display(self.id), "error reporting metrics");
+            tracing::error!(error=%err, id=display(&self.id), "error reporting metrics");
+        }
+    }
+
+    /// Build the lag of every replication target from the leader's progress, or an empty map if it is not a leader.
+    fn replication_lag(&self) -> BTreeMap<C::NodeId, ReplicationLag<C::NodeId>> {
+        let leader = match self.engine.state.internal_server_state.leading() {
+            Some(l) => l,
+            None => return BTreeMap::new(),
+        };
+
+        let last_index = self.engine.state.last_log_id().next_index();
+
+        leader
+            .progress
+            .iter()
+            .filter(|(id, _)| *id != self.id)
+            .map(|(id, matched)| {
+                let lag = last_index.saturating_sub(matched.next_index());
+                (id.clone(), ReplicationLag {
+                    matched: matched.clone(),
+                    lag,
+                })
+            })
+            .collect()
+    }
+
+    fn state_summary(&self) -> RaftStateSummary<C::NodeId> {
+        let st = &self.engine.state;
+        RaftStateSummary {
+            term: st.vote.term,
+            vote: st.vote.clone(),
+            last_log_id: st.last_log_id(),
+            committed: st.committed.clone(),
+            last_applied: st.last_applied.clone(),
+            current_leader: self.current_leader(),
+        }
+    }
+
+    /// Report the server metrics, if one of its fields changed.
+    fn report_server_metrics(&self) {
+        let m = RaftServerMetrics {
+            id: self.id.clone(),
+            vote: self.engine.state.vote.clone(),
+            state: self.engine.state.server_state,
+            current_leader: self.current_leader(),
+            membership_config: self.engine.state.membership_state.effective.clone(),
+            last_step_down: self.last_step_down.clone(),
+        };
+
+        if m == *self.tx_server_metrics.borrow() {
+            return;
+        }
+
+        tracing::debug!("report_server_metrics: {:?}", m);
+        let _ = self.tx_server_metrics.send(m);
+    }
+
+    /// Report the data metrics, if one of its fields changed.
+    fn report_data_metrics(&self, replication: &Option<Versioned<ReplicationMetrics<C::NodeId>>>) {
+        let m = RaftDataMetrics {
+            last_log: self.engine.state.last_log_id(),
+            last_applied: self.engine.state.last_applied.clone(),
+            snapshot: self.engine.snapshot_last_log_id.clone(),
+            replication: replication.clone(),
+        };
+
+        if m == *self.tx_data_metrics.borrow() {
+            return;
         }
+
+        let _ = self.tx_data_metrics.send(m);
     }
 
     /// Handle the admin command `initialize`.
     ///
-    /// It is allowed to initialize only when `last_log_id.is_none()` and `vote==(0,0)`.
+    /// `learner_nodes` that are not in `member_nodes` are added to the initial membership as learners.
+    ///
+    /// It is allowed to initialize only when `last_log_id.is_none()` and `vote==(0,self.id)`.
     /// See: [Conditions for initialization](https://datafuselabs.github.io/openraft/cluster-formation.html#conditions-for-initialization)
     #[tracing::instrument(level = "debug", skip(self))]
     pub(crate) async fn handle_initialize(
         &mut self,
-        member_nodes: BTreeMap<C::NodeId, Option<Node>>,
-    ) -> Result<(), InitializeError<C::NodeId>> {
-        let membership = Membership::try_from(member_nodes)?;
+        member_nodes: BTreeMap<C::NodeId, Option<C::Node>>,
+        learner_nodes: BTreeMap<C::NodeId, Option<C::Node>>,
+    ) -> Result<(), InitializeError<C::NodeId, C::Node>> {
+        let member_ids = member_nodes.keys().cloned().collect::<BTreeSet<_>>();
+        let nodes = Membership::extend_nodes(member_nodes, &learner_nodes);
+        let membership = Membership::with_nodes(vec![member_ids], nodes)?;
         let payload = EntryPayload::<C>::Membership(membership);
 
         let mut entry_refs = [EntryRef::new(&payload)];
@@ -769,13 +1418,13 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     /// Save the Raft node's current hard state to disk.
     #[tracing::instrument(level = "trace", skip(self))]
     pub(crate) async fn save_vote(&mut self) -> Result<(), StorageError<C::NodeId>> {
-        self.storage.save_vote(&self.engine.state.vote).await
+        self.log_store.save_vote(&self.engine.state.vote).await
     }
 
     /// Update core's target state, ensuring all invariants are upheld.
-    #[tracing::instrument(level = "trace", skip(self), fields(id=display(self.id)))]
+    #[tracing::instrument(level = "trace", skip(self), fields(id=display(&self.id)))]
     pub(crate) fn set_target_state(&mut self, target_state: ServerState) {
-        tracing::debug!(id = display(self.id), ?target_state, "set_target_state");
+        tracing::debug!(id = display(&self.id), ?target_state, "set_target_state");
 
         if target_state == ServerState::Follower
             && !self.engine.state.membership_state.effective.membership.is_voter(&self.id)
@@ -789,7 +1438,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     /// Get the next election timeout, generating a new value if not set.
     /// TODO: get() should not have a side effect of updating the timer.
     #[tracing::instrument(level = "trace", skip(self))]
-    pub(crate) fn get_next_election_time(&mut self) -> Instant {
+    pub(crate) fn get_next_election_time(&mut self) -> InstantOf<C> {
         let current_vote = &self.engine.state.vote;
 
         let time = self.next_election_time.get_time(current_vote);
@@ -799,9 +1448,9 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             let t = Duration::from_millis(self.config.new_rand_election_timeout());
             tracing::debug!("create election timeout after: {:?}", t);
 
-            let t = Instant::now() + t;
+            let t = C::AsyncRuntime::now() + t;
 
-            self.next_election_time = VoteWiseTime::new(*current_vote, t);
+            self.next_election_time = VoteWiseTime::new(current_vote.clone(), t);
 
             t
         }
@@ -810,33 +1459,51 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     /// Set a value for the next election timeout.
     #[tracing::instrument(level = "trace", skip(self))]
     pub(crate) fn set_next_election_time(&mut self, can_be_leader: bool) {
-        let now = Instant::now();
+        let now = C::AsyncRuntime::now();
 
         let mut t = Duration::from_millis(self.config.new_rand_election_timeout());
         if !can_be_leader {
             t *= 2;
         }
+
+        // A node with lower election priority waits longer, to let a higher priority node elect first.
+        let rank = self.engine.election_priority_rank();
+        t += Duration::from_millis(self.config.election_timeout_max * rank);
+
         tracing::debug!(
             "update election timeout after: {:?}, can_be_leader: {}",
             t,
             can_be_leader
         );
 
-        self.next_election_time = VoteWiseTime::new(self.engine.state.vote, now + t);
+        self.next_election_time = VoteWiseTime::new(self.engine.state.vote.clone(), now + t);
     }
 
     #[tracing::instrument(level = "trace", skip(self))]
     pub(crate) fn reject_election_for_a_while(&mut self) {
-        let now = Instant::now();
+        let now = C::AsyncRuntime::now();
         self.last_heartbeat = Some(now);
     }
 
     /// Update the system's snapshot state based on the given data.
     #[tracing::instrument(level = "trace", skip(self))]
     pub(crate) fn update_snapshot_state(&mut self, update: SnapshotUpdate<C::NodeId>) {
+        if let SnapshotUpdate::SnapshotProgress(progress) = update {
+            // A report from a build that has finished is ignored.
+            if let Some(SnapshotState::Snapshotting { .. }) = &self.snapshot_state {
+                self.snapshot_progress = Some(progress);
+                self.engine.metrics_flags.set_data_changed();
+            }
+            return;
+        }
+
+        self.snapshot_progress = None;
+        self.engine.metrics_flags.set_data_changed();
+
         if let SnapshotUpdate::SnapshotComplete(log_id) = update {
             self.engine.snapshot_last_log_id = Some(log_id);
-            self.engine.metrics_flags.set_data_changed();
+            self.last_snapshot_time = C::AsyncRuntime::now();
+            self.bytes_since_last_snapshot = 0;
         }
         // If snapshot state is anything other than streaming, then drop it.
         if let Some(state @ SnapshotState::Streaming { .. }) = self.snapshot_state.take() {
@@ -844,16 +1511,121 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         }
     }
 
-    /// Trigger a log compaction (snapshot) job if needed.
-    /// If force is True, it will skip the threshold check and start creating snapshot as demanded.
+    /// Start an election at once, as requested by `Raft::trigger_elect()`.
+    ///
+    /// A non-voter can not be elected, `NotInMembers` is sent to the caller. Nor can a witness, `IsWitness` is sent.
+    #[tracing::instrument(level = "debug", skip(self, tx))]
+    async fn trigger_elect(
+        &mut self,
+        tx: RaftRespTx<(), TriggerElectError<C::NodeId, C::Node>>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        let effective = &self.engine.state.membership_state.effective;
+        if !effective.is_voter(&self.id) {
+            let err = NotInMembers {
+                node_id: self.id.clone(),
+                membership: effective.membership.clone(),
+            };
+            let _ = tx.send(Err(err.into()));
+            return Ok(());
+        }
+
+        if self.engine.is_witness(&self.id) {
+            let _ = tx.send(Err(IsWitness {
+                node_id: self.id.clone(),
+            }
+            .into()));
+            return Ok(());
+        }
+
+        tracing::info!("trigger election");
+
+        self.engine.elect();
+        self.run_engine_commands::<Entry<C>>(&[]).await?;
+
+        let _ = tx.send(Ok(()));
+        Ok(())
+    }
+
+    /// Purge logs up to `upto` at once, as requested by `Raft::trigger_purge_log()`.
+    ///
+    /// It returns the last log id purged, or None if there is nothing to purge.
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn trigger_purge_log(
+        &mut self,
+        upto: u64,
+        force: bool,
+    ) -> Result<Option<LogId<C::NodeId>>, StorageError<C::NodeId>> {
+        let purge_upto = match self.engine.calc_trigger_purge_upto(upto, force) {
+            Some(log_id) => log_id,
+            None => return Ok(None),
+        };
+
+        tracing::info!("trigger purge log upto: {}", purge_upto);
+
+        self.engine.purge_log(purge_upto.clone());
+        self.run_engine_commands::<Entry<C>>(&[]).await?;
+        self.engine.metrics_flags.set_data_changed();
+
+        Ok(Some(purge_upto))
+    }
+
+    /// Purge logs up to `log_id` at once, as requested by `Raft::purge_logs_upto()`.
+    ///
+    /// It responds with an error without purging anything if the logs can not be purged safely.
+    #[tracing::instrument(level = "debug", skip(self, tx))]
+    async fn purge_logs_upto(
+        &mut self,
+        log_id: LogId<C::NodeId>,
+        tx: RaftRespTx<(), PurgeLogsError<C::NodeId>>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        let purge_upto = match self.engine.calc_purge_logs_upto(&log_id) {
+            Ok(Some(x)) => x,
+            Ok(None) => {
+                let _ = tx.send(Ok(()));
+                return Ok(());
+            }
+            Err(e) => {
+                tracing::info!(error = display(&e), "refuse to purge logs");
+                let _ = tx.send(Err(e));
+                return Ok(());
+            }
+        };
+
+        tracing::info!("purge logs upto: {}", purge_upto);
+
+        self.engine.purge_log(purge_upto);
+        self.run_engine_commands::<Entry<C>>(&[]).await?;
+        self.engine.metrics_flags.set_data_changed();
+
+        let _ = tx.send(Ok(()));
+        Ok(())
+    }
+
+    /// Start building a snapshot at once, as requested by `Raft::trigger_snapshot()`.
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn trigger_snapshot(&mut self) -> TriggerSnapshotResult {
+        if self.snapshot_state.is_some() {
+            return TriggerSnapshotResult::InProgress;
+        }
+
+        if self.engine.state.last_applied.is_none()
+            || self.engine.state.last_applied.index() <= self.engine.snapshot_last_log_id.index()
+        {
+            return TriggerSnapshotResult::NothingToSnapshot;
+        }
+
+        self.trigger_log_compaction_if_needed(true).await;
+        TriggerSnapshotResult::Scheduled
+    }
+
+    /// Trigger a log compaction (snapshot) job if needed.
+    /// If force is True, it will skip the threshold check and start creating snapshot as demanded.
     #[tracing::instrument(level = "trace", skip(self))]
     pub(crate) async fn trigger_log_compaction_if_needed(&mut self, force: bool) {
         if self.snapshot_state.is_some() {
             return;
         }
-        let SnapshotPolicy::LogsSinceLast(threshold) = &self.config.snapshot_policy;
-
-        let last_applied = match self.engine.state.last_applied {
+        let last_applied = match &self.engine.state.last_applied {
             None => {
                 return;
             }
@@ -866,24 +1638,42 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         }
 
         if !force {
-            // If we are below the threshold, then there is nothing to do.
-            if self.engine.state.last_applied.next_index() - self.engine.snapshot_last_log_id.next_index() < *threshold
-            {
+            let logs_since_last =
+                self.engine.state.last_applied.next_index() - self.engine.snapshot_last_log_id.next_index();
+
+            let needed = self.config.snapshot_policy.should_snapshot(&SnapshotPolicyContext {
+                last_applied_term: last_applied.leader_id.term,
+                last_applied_index: last_applied.index,
+                logs_since_last,
+                bytes_since_last: self.bytes_since_last_snapshot,
+                since_last: C::AsyncRuntime::now().saturating_duration_since(self.last_snapshot_time),
+            });
+
+            if !needed {
                 return;
             }
         }
 
         // At this point, we are clear to begin a new compaction process.
-        let mut builder = self.storage.get_snapshot_builder().await;
+        let mut builder = self.state_machine.get_snapshot_builder().await;
         let (handle, reg) = AbortHandle::new_pair();
-        let (chan_tx, _) = broadcast::channel(1);
+        let (chan_tx, _) = C::AsyncRuntime::broadcast(1);
         let tx_api = self.tx_api.clone();
         self.snapshot_state = Some(SnapshotState::Snapshotting {
             handle,
             sender: chan_tx.clone(),
         });
+        self.snapshot_progress = None;
+        self.engine.metrics_flags.set_data_changed();
+
+        let tx_progress = self.tx_api.clone();
+        builder.set_progress_reporter(SnapshotProgressReporter::new(move |progress| {
+            let _ = tx_progress.send(RaftMsg::SnapshotUpdate {
+                update: SnapshotUpdate::SnapshotProgress(progress),
+            });
+        }));
 
-        tokio::spawn(
+        let _ = C::AsyncRuntime::spawn(
             async move {
                 let f = builder.build_snapshot();
                 let res = Abortable::new(f, reg).await;
@@ -891,7 +1681,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                     Ok(res) => match res {
                         Ok(snapshot) => {
                             let _ = tx_api.send(RaftMsg::SnapshotUpdate {
-                                update: SnapshotUpdate::SnapshotComplete(snapshot.meta.last_log_id),
+                                update: SnapshotUpdate::SnapshotComplete(snapshot.meta.last_log_id.clone()),
                             });
                             // This will always succeed.
                             let _ = chan_tx.send(snapshot.meta.last_log_id.index);
@@ -917,14 +1707,31 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     /// Reject a request due to the Raft node being in a state which prohibits the request.
     #[tracing::instrument(level = "trace", skip(self, tx))]
     pub(crate) fn reject_with_forward_to_leader<T, E>(&self, tx: RaftRespTx<T, E>)
-    where E: From<ForwardToLeader<C::NodeId>> {
+    where E: From<ForwardToLeader<C::NodeId, C::Node>> {
+        let _ = tx.send(Err(self.forward_to_leader().into()));
+    }
+
+    /// The error to tell a client to send its request to the current leader.
+    pub(crate) fn forward_to_leader(&self) -> ForwardToLeader<C::NodeId, C::Node> {
         let l = self.current_leader();
-        let err = ForwardToLeader {
-            leader_id: l,
+        ForwardToLeader {
+            leader_id: l.clone(),
             leader_node: self.get_leader_node(l),
+        }
+    }
+
+    /// Respond to the clients whose writes are not yet committed when this node quits leader state.
+    ///
+    /// These entries may or may not be committed by the next leader.
+    fn finish_client_writes_on_step_down(&mut self) {
+        let txs = match &mut self.leader_data {
+            Some(l) => std::mem::take(&mut l.client_resp_channels),
+            None => return,
         };
 
-        let _ = tx.send(Err(err.into()));
+        for (_, tx) in txs {
+            tx.send(Err(self.forward_to_leader().into()));
+        }
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
@@ -933,7 +1740,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             return None;
         }
 
-        let id = self.engine.state.vote.node_id;
+        let id = self.engine.state.vote.node_id.clone();
 
         if id == self.id {
             if self.engine.state.server_state == ServerState::Leader {
@@ -946,13 +1753,52 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         }
     }
 
-    pub(crate) fn get_leader_node(&self, leader_id: Option<C::NodeId>) -> Option<Node> {
+    pub(crate) fn get_leader_node(&self, leader_id: Option<C::NodeId>) -> Option<C::Node> {
         match leader_id {
             None => None,
             Some(id) => self.engine.state.membership_state.effective.get_node(&id).cloned(),
         }
     }
 
+    /// Restore the committed log id saved by the log store, and apply the logs upto it.
+    ///
+    /// A saved committed log id that is not in the local logs, e.g., saved before the logs are flushed, is ignored.
+    async fn apply_saved_committed(&mut self) -> Result<(), StorageError<C::NodeId>> {
+        let committed = match self.log_store.read_committed().await? {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+
+        if Some(&committed) <= self.engine.state.last_applied.as_ref() {
+            return Ok(());
+        }
+
+        if !self.engine.state.has_log_id(&committed) {
+            tracing::warn!(
+                committed = display(&committed),
+                "saved committed log id is not found in the local logs, ignore it"
+            );
+            return Ok(());
+        }
+
+        tracing::info!(
+            committed = display(&committed),
+            "apply logs upto the saved committed log id"
+        );
+
+        self.engine.state.update_committed(&Some(committed.clone()));
+        self.apply_to_state_machine(committed.index).await?;
+        self.engine.metrics_flags.set_data_changed();
+
+        Ok(())
+    }
+
+    /// Save the committed log id with the log store, before applying logs upto it.
+    async fn save_committed(&mut self) -> Result<(), StorageError<C::NodeId>> {
+        let committed = self.engine.state.committed.clone();
+        self.log_store.save_committed(committed).await
+    }
+
     #[tracing::instrument(level = "debug", skip_all)]
     pub(crate) async fn apply_to_state_machine(&mut self, upto_index: u64) -> Result<(), StorageError<C::NodeId>> {
         tracing::debug!(upto_index = display(upto_index), "apply_to_state_machine");
@@ -971,16 +1817,43 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             return Ok(());
         }
 
-        let entries = self.storage.get_log_entries(since..end).await?;
+        let entries = self.log_store.get_log_entries(since..end).await?;
         tracing::debug!(entries=%entries.as_slice().summary(), "about to apply");
 
         let entry_refs = entries.iter().collect::<Vec<_>>();
-        let apply_results = self.storage.apply_to_state_machine(&entry_refs).await?;
+        let apply_results = self.state_machine.try_apply_to_state_machine(&entry_refs).await?;
+
+        if apply_results.len() != entries.len() {
+            let last = entries[entries.len() - 1].log_id.clone();
+            return Err(StorageError::IO {
+                source: StorageIOError::new(
+                    ErrorSubject::Apply(last),
+                    ErrorVerb::Write,
+                    AnyError::error(format!(
+                        "state machine returned {} results for {} entries",
+                        apply_results.len(),
+                        entries.len()
+                    )),
+                ),
+            });
+        }
 
-        let last_applied = entries[entries.len() - 1].log_id;
-        self.engine.state.last_applied = Some(last_applied);
+        let last_applied = entries[entries.len() - 1].log_id.clone();
+        let mut prev_applied = std::mem::replace(&mut self.engine.state.last_applied, Some(last_applied.clone()));
 
-        tracing::debug!(last_applied = display(last_applied), "update last_applied");
+        // Sending fails only if there is no subscriber.
+        if self.tx_applied.receiver_count() > 0 {
+            for entry in entries.iter() {
+                let _ = self.tx_applied.send(AppliedEvent::Entry {
+                    log_id: entry.log_id.clone(),
+                    payload: entry.payload.clone(),
+                    leader_established: entry.is_leader_established(prev_applied.as_ref()),
+                });
+                prev_applied = Some(entry.log_id.clone());
+            }
+        }
+
+        tracing::debug!(last_applied = display(&last_applied), "update last_applied");
 
         if let Some(l) = &mut self.leader_data {
             let mut results = apply_results.into_iter();
@@ -1004,8 +1877,8 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     #[tracing::instrument(level = "debug", skip_all)]
     pub(super) async fn send_response(
         entry: &Entry<C>,
-        resp: C::R,
-        tx: Option<RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId>>>,
+        resp: Result<C::R, C::AppError>,
+        tx: Option<ClientResponder<C>>,
     ) {
         tracing::debug!(entry = display(entry.summary()), "send_response");
 
@@ -1020,29 +1893,85 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             None
         };
 
-        let res = Ok(ClientWriteResponse {
-            log_id: entry.log_id,
-            data: resp,
-            membership,
-        });
+        let res = match resp {
+            Ok(data) => Ok(ClientWriteResponse {
+                log_id: entry.log_id.clone(),
+                data,
+                membership,
+            }),
+            Err(app_err) => Err(ClientWriteError::AppError(app_err)),
+        };
 
-        let send_res = tx.send(res);
-        tracing::debug!(
-            "send client response through tx, send_res is error: {}",
-            send_res.is_err()
-        );
+        tx.send(res);
     }
 
     /// Handle the post-commit logic for a client request.
     #[tracing::instrument(level = "debug", skip(self))]
     pub(super) async fn leader_commit(&mut self, log_index: u64) -> Result<(), StorageError<C::NodeId>> {
-        self.leader_step_down();
+        self.leader_step_down().await;
 
         self.apply_to_state_machine(log_index).await?;
 
         Ok(())
     }
 
+    /// Append `entries`, the last of which is `last_log_id`, to the log store.
+    ///
+    /// A leader does not wait for the entries to be flushed: the flush is reported with `RaftMsg::LogFlushed`, and
+    /// only then the leader's own log counts in the commit quorum. Meanwhile the entries are replicated, and they can
+    /// be committed by followers. Others wait for the flush, because the leader takes
+    /// a successful response as the entries being persisted.
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn append_to_log(
+        &mut self,
+        entries: &[&Entry<C>],
+        last_log_id: Option<LogId<C::NodeId>>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        if self.engine.state.internal_server_state.is_leading() {
+            let tx_api = self.tx_api.clone();
+            let vote = self.engine.state.vote.clone();
+            let callback = LogFlushed::new(last_log_id, move |log_id, result| {
+                let _ = tx_api.send(RaftMsg::LogFlushed { log_id, result, vote });
+            });
+            return self.append_to_log_store(entries, callback).await;
+        }
+
+        let (tx, rx) = oneshot::channel();
+        let callback = LogFlushed::new(last_log_id, move |_log_id, result| {
+            let _ = tx.send(result);
+        });
+        self.append_to_log_store(entries, callback).await?;
+
+        let result = match rx.await {
+            Ok(res) => res,
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::Other,
+                "LogFlushed is dropped without being called",
+            )),
+        };
+
+        result.map_err(|e| StorageError::IO {
+            source: StorageIOError::new(ErrorSubject::Logs, ErrorVerb::Write, AnyError::new(&e)),
+        })
+    }
+
+    /// Append entries to the log store, together with the deferred vote if there is one.
+    async fn append_to_log_store(
+        &mut self,
+        entries: &[&Entry<C>],
+        callback: LogFlushed<C>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        let vote = match &self.vote_to_save {
+            Some(vote) => vote.clone(),
+            None => return self.log_store.append(entries, callback).await,
+        };
+
+        self.log_store.append_with_vote(&vote, entries, callback).await?;
+        // Cleared only when it succeeds, thus a retried append saves the vote again.
+        self.vote_to_save = None;
+        Ok(())
+    }
+
     /// Begin replicating upto the given log id.
     ///
     /// It does not block until the entry is committed or actually sent out.
@@ -1052,14 +1981,16 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         if let Some(l) = &self.leader_data {
             if tracing::enabled!(Level::DEBUG) {
                 for node_id in l.nodes.keys() {
-                    tracing::debug!(node_id = display(node_id), log_id = display(log_id), "replicate_entry");
+                    tracing::debug!(node_id = display(node_id), log_id = display(&log_id), "replicate_entry");
                 }
             }
 
             for node in l.nodes.values() {
                 let _ = node.repl_tx.send(UpdateReplication {
-                    last_log_id: Some(log_id),
-                    committed: self.engine.state.committed,
+                    last_log_id: Some(log_id.clone()),
+                    committed: self.engine.state.committed.clone(),
+                    config: None,
+                    witness: None,
                 });
             }
         } else {
@@ -1070,23 +2001,94 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     /// Spawn a new replication stream returning its replication state handle.
     #[tracing::instrument(level = "debug", skip(self))]
     #[allow(clippy::type_complexity)]
-    pub(crate) async fn spawn_replication_stream(&mut self, target: C::NodeId) -> ReplicationStream<C::NodeId> {
+    pub(crate) async fn spawn_replication_stream(&mut self, target: C::NodeId) -> ReplicationStream<C> {
         let target_node = self.engine.state.membership_state.effective.get_node(&target);
 
-        ReplicationCore::<C, N, S>::spawn(
-            target,
+        // One connection for every AppendEntries that may be outstanding.
+        let mut networks = Vec::with_capacity(self.config.max_inflight_appends as usize);
+        for _ in 0..self.config.max_inflight_appends {
+            networks.push(self.network.connect(target.clone(), target_node).await);
+        }
+
+        ReplicationCore::<C, N, LS, SM>::spawn(
+            target.clone(),
             target_node.cloned(),
-            self.engine.state.vote,
+            self.engine.is_witness(&target),
+            self.engine.state.vote.clone(),
             self.config.clone(),
             self.engine.state.last_log_id(),
-            self.engine.state.committed,
-            self.network.connect(target, target_node).await,
-            self.storage.get_log_reader().await,
+            self.engine.state.committed.clone(),
+            networks,
+            self.log_store.get_log_reader().await,
+            self.log_cache.clone(),
             self.tx_api.clone(),
-            tracing::span!(parent: &self.span, Level::DEBUG, "replication", id=display(self.id), target=display(target)),
+            tracing::span!(parent: &self.span, Level::DEBUG, "replication", id=display(&self.id), target=display(&target)),
         )
     }
 
+    /// Apply the config updated by `Raft::update_config()`, if there is one.
+    ///
+    /// A leader passes it to every replication stream, and restarts the heartbeat workers with the new interval.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) async fn apply_pending_config(&mut self) {
+        let config = match self.pending_config.take() {
+            None => return,
+            Some(x) => x,
+        };
+
+        tracing::info!("apply updated config: {:?}", config);
+        self.config = Arc::new(config);
+
+        let targets = match &self.leader_data {
+            None => return,
+            Some(l) => {
+                for node in l.nodes.values() {
+                    let _ = node.repl_tx.send(UpdateReplication {
+                        last_log_id: self.engine.state.last_log_id(),
+                        committed: self.engine.state.committed.clone(),
+                        config: Some(self.config.clone()),
+                        witness: None,
+                    });
+                }
+                l.heartbeats.keys().cloned().collect::<Vec<_>>()
+            }
+        };
+
+        // The replaced handle is dropped, which shuts the previous worker down.
+        for target in targets {
+            self.spawn_heartbeat(target).await;
+        }
+    }
+
+    /// Spawn a heartbeat worker for a target, which sends heartbeats regardless of the replication stream to it.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) async fn spawn_heartbeat(&mut self, target: C::NodeId) {
+        if self.config.disable_heartbeat {
+            return;
+        }
+
+        let target_node = self.engine.state.membership_state.effective.get_node(&target);
+        let network = self.network.connect(target.clone(), target_node).await;
+
+        let l = match &mut self.leader_data {
+            Some(l) => l,
+            None => unreachable!("it has to be a leader!!!"),
+        };
+
+        let _ = l.tx_committed.send(self.engine.state.committed.clone());
+
+        let handle = HeartbeatWorker::<C, N, LS, SM>::spawn(
+            target.clone(),
+            self.engine.state.vote.clone(),
+            &self.config,
+            network,
+            l.rx_committed.clone(),
+            self.tx_api.clone(),
+            tracing::span!(parent: &self.span, Level::DEBUG, "heartbeat", id=display(&self.id), target=display(&target)),
+        );
+        l.heartbeats.insert(target, handle);
+    }
+
     /// Remove a replication if the membership that does not include it has committed.
     ///
     /// Return true if removed.
@@ -1114,6 +2116,10 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         }
 
         if let Some(l) = &mut self.leader_data {
+            // Dropping the handle shuts the heartbeat worker down.
+            l.heartbeats.remove(&target);
+            l.replication_backoff.remove(&target);
+            l.replication_progress.remove(&target);
             l.replication_metrics.update(RemoveTarget { target });
         } else {
             unreachable!("It has to be a leader!!!");
@@ -1128,9 +2134,17 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
 
     /// Leader will keep working until the effective membership that removes it committed.
     ///
+    /// Then it transfers its leadership to the most up-to-date remaining voter, so that the cluster does not have to
+    /// wait for an election timeout, and steps down to a learner once TimeoutNow is sent. If the target does not catch
+    /// up before `Config::transfer_leader_timeout`, or it rejects, the leader steps down without a successor.
+    ///
     /// This is ony called by leader.
     #[tracing::instrument(level = "debug", skip_all)]
-    pub(super) fn leader_step_down(&mut self) {
+    pub(super) async fn leader_step_down(&mut self) {
+        if self.engine.state.server_state != ServerState::Leader {
+            return;
+        }
+
         let em = &self.engine.state.membership_state.effective;
 
         if self.engine.state.committed < em.log_id {
@@ -1139,17 +2153,30 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
 
         // TODO: Leader does not need to step down. It can keep working.
         //       This requires to separate Leader(Proposer) and Acceptors.
-        if !em.is_voter(&self.id) {
-            tracing::debug!("leader is stepping down");
+        if em.is_voter(&self.id) {
+            return;
+        }
+
+        self.start_leader_transfer_on_removal();
+        self.try_send_timeout_now().await;
 
-            // TODO(xp): transfer leadership
-            self.set_target_state(ServerState::Learner);
-            self.engine.metrics_flags.set_cluster_changed();
+        let transferring = self.leader_data.as_ref().and_then(|l| l.transfer.as_ref()).map(|t| !t.timeout_now_sent);
+        if transferring == Some(true) {
+            tracing::debug!("removed leader waits for the transfer target to catch up");
+            return;
         }
+
+        tracing::info!("removed leader is stepping down");
+
+        self.step_down_reason = Some(StepDownReason::Removed);
+        self.set_target_state(ServerState::Learner);
+        self.engine.metrics_flags.set_cluster_changed();
     }
 }
 
-impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C, N, S> {
+impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>>
+    RaftCore<C, N, LS, SM>
+{
     #[tracing::instrument(level = "debug", skip_all)]
     pub(crate) async fn run_engine_commands<'e, Ent>(
         &mut self,
@@ -1169,14 +2196,59 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         let mut curr = 0;
         let mut commands = vec![];
         swap(&mut self.engine.commands, &mut commands);
-        for cmd in commands {
-            self.run_command(input_entries, &mut curr, &cmd).await?;
+        for (i, cmd) in commands.iter().enumerate() {
+            if let Command::SaveVote { vote } = cmd {
+                if is_vote_saved_with_append(&commands[i + 1..]) {
+                    self.vote_to_save = Some(vote.clone());
+                    continue;
+                }
+            }
+            self.run_command_with_retry(input_entries, &mut curr, cmd).await?;
         }
 
         Ok(())
     }
 
-    #[tracing::instrument(level="debug", skip_all, fields(id=display(self.id), raft_state="leader"))]
+    /// Run a command, retrying it with backoff if it fails with a transient storage error.
+    ///
+    /// A transient error means the storage operation had no effect, thus running the command again is safe.
+    /// It gives up after `Config::storage_retry_limit` retries and returns the last error.
+    async fn run_command_with_retry<'e, Ent>(
+        &mut self,
+        input_entries: &'e [Ent],
+        curr: &mut usize,
+        cmd: &Command<C::NodeId, C::Node>,
+    ) -> Result<(), StorageError<C::NodeId>>
+    where
+        Ent: RaftLogId<C::NodeId> + Sync + Send + 'e,
+        &'e Ent: Into<Entry<C>>,
+    {
+        let mut retries = 0;
+        let mut delay = Duration::from_millis(self.config.storage_retry_interval);
+
+        loop {
+            let res = self.run_command(input_entries, curr, cmd).await;
+
+            match res {
+                Err(e) if e.is_transient() && retries < self.config.storage_retry_limit => {
+                    retries += 1;
+                    tracing::warn!(
+                        error = display(&e),
+                        retries,
+                        "transient storage error, retry command {:?} after {:?}",
+                        cmd,
+                        delay
+                    );
+
+                    C::AsyncRuntime::sleep(delay).await;
+                    delay *= 2;
+                }
+                _ => return res,
+            }
+        }
+    }
+
+    #[tracing::instrument(level="debug", skip_all, fields(id=display(&self.id), raft_state="leader"))]
     pub(crate) async fn leader_loop(&mut self) -> Result<(), Fatal<C::NodeId>> {
         // Setup state as leader.
         self.last_heartbeat = None;
@@ -1187,22 +2259,28 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         let targets = {
             let mem = &self.engine.state.membership_state.effective;
 
-            let node_ids = mem.nodes().map(|(&nid, _)| nid);
+            let node_ids = mem.nodes().map(|(nid, _)| nid.clone());
             node_ids.filter(|elem| elem != &self.id).collect::<Vec<_>>()
         };
 
         // TODO(xp): make this Engine::Command driven.
         for target in targets {
-            let state = self.spawn_replication_stream(target).await;
+            let state = self.spawn_replication_stream(target.clone()).await;
             if let Some(l) = &mut self.leader_data {
-                l.nodes.insert(target, state);
+                l.nodes.insert(target.clone(), state);
             } else {
                 unreachable!("it has to be a leader!!!");
             }
+            self.spawn_heartbeat(target).await;
         }
 
-        // Commit the initial entry when new leader established.
-        self.write_entry(EntryPayload::Blank, None).await?;
+        // Commit the initial entry when new leader established, with the application data if it provides any.
+        let leader_id = self.engine.state.vote.leader_id();
+        let payload = match self.state_machine.leader_established_data(&leader_id).await {
+            Some(data) => EntryPayload::Normal(data),
+            None => EntryPayload::Blank,
+        };
+        self.write_entry(payload, None, None).await?;
 
         // report the leader metrics every time there came to a new leader
         // if not `report_metrics` before the leader loop, the leader metrics may not be updated cause no coming event.
@@ -1218,9 +2296,11 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     }
 
     /// Run an event handling loop until server state changes
-    #[tracing::instrument(level="debug", skip(self), fields(id=display(self.id)))]
+    #[tracing::instrument(level="debug", skip(self), fields(id=display(&self.id)))]
     async fn runtime_loop(&mut self, server_state: ServerState) -> Result<(), Fatal<C::NodeId>> {
         loop {
+            self.check_drain();
+
             if self.engine.state.server_state != server_state {
                 tracing::info!(
                     "id={} server_state becomes: {:?}",
@@ -1234,7 +2314,13 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
 
             tokio::select! {
                 Some(msg) = self.rx_api.recv() => {
-                    self.handle_api_msg(msg).await?;
+                    let span = tracing::debug_span!(
+                        "raft.core",
+                        node_id = display(&self.id),
+                        term = self.engine.state.vote.term,
+                        log_index = self.engine.state.last_log_id().index()
+                    );
+                    self.handle_api_msg(msg).instrument(span).await?;
                 },
 
                 Ok(_) = &mut self.rx_shutdown => {
@@ -1250,39 +2336,64 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     async fn spawn_parallel_vote_requests(&mut self, vote_req: &VoteRequest<C::NodeId>) {
         let members = self.engine.state.membership_state.effective.voter_ids();
 
-        let vote = vote_req.vote;
-
         for target in members {
             if target == self.id {
                 continue;
             }
 
             let req = vote_req.clone();
+            let pre_vote = vote_req.pre_vote;
+            // A pre-vote does not change the vote of this node, thus the response is matched against the current vote.
+            let vote = if pre_vote {
+                self.engine.state.vote.clone()
+            } else {
+                vote_req.vote.clone()
+            };
             let target_node = self.engine.state.membership_state.effective.get_node(&target).cloned();
-            let mut network = self.network.connect(target, target_node.as_ref()).await;
+            let mut network = self.network.connect(target.clone(), target_node.as_ref()).await;
             let tx = self.tx_api.clone();
+            let option = RPCOption::new(Duration::from_millis(self.config.election_timeout_min));
+
+            let span = tracing::debug_span!(
+                parent: &Span::current(),
+                "raft.vote",
+                node_id = display(&self.id),
+                target = display(&target),
+                term = vote_req.vote.term,
+                log_index = vote_req.last_log_id.index()
+            );
 
-            let _ = tokio::spawn(
+            let _ = C::AsyncRuntime::spawn(
                 async move {
-                    let res = network.send_vote(req).await;
+                    let res = network.vote(req, option).await;
 
                     match res {
                         Ok(resp) => {
-                            let _ = tx.send(RaftMsg::VoteResponse { target, resp, vote });
+                            if pre_vote {
+                                let _ = tx.send(RaftMsg::PreVoteResponse { target, resp, vote });
+                            } else {
+                                let _ = tx.send(RaftMsg::VoteResponse { target, resp, vote });
+                            }
                         }
                         Err(err) => tracing::error!({error=%err, target=display(target)}, "while requesting vote"),
                     }
                 }
-                .instrument(tracing::debug_span!(
-                    parent: &Span::current(),
-                    "send_vote_req",
-                    target = display(target)
-                )),
+                .instrument(span),
             );
         }
     }
 
-    #[tracing::instrument(level = "debug", skip_all)]
+    #[tracing::instrument(
+        name = "raft.vote",
+        level = "debug",
+        skip_all,
+        fields(
+            node_id = display(&self.id),
+            target = display(&req.vote.node_id),
+            term = req.vote.term,
+            log_index = req.last_log_id.index()
+        )
+    )]
     pub(super) async fn handle_vote_request(
         &mut self,
         req: VoteRequest<C::NodeId>,
@@ -1293,8 +2404,10 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         //           if we have finished using blank log for heartbeat:
         //           https://github.com/datafuselabs/openraft/issues/151
         // Do not respond to the request if we've received a heartbeat within the election timeout minimum.
-        if let Some(inst) = &self.last_heartbeat {
-            let now = Instant::now();
+        // Unless the leader has announced to transfer its leadership to the candidate.
+        let transfer_to_candidate = req.leader_transfer && self.is_leader_transfer_announced(&req.vote);
+        if let (false, Some(inst)) = (transfer_to_candidate, &self.last_heartbeat) {
+            let now = C::AsyncRuntime::now();
             let delta = now.duration_since(*inst);
             if self.config.election_timeout_min >= (delta.as_millis() as u64) {
                 tracing::debug!(
@@ -1303,19 +2416,36 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                     "rejecting vote request received within election timeout minimum"
                 );
                 return Ok(VoteResponse {
-                    vote: self.engine.state.vote,
+                    vote: self.engine.state.vote.clone(),
                     vote_granted: false,
                     last_log_id: self.engine.state.last_log_id(),
                 });
             }
         }
 
+        if req.pre_vote {
+            return Ok(self.engine.handle_pre_vote_req(req));
+        }
+
         let resp = self.engine.handle_vote_req(req);
         self.run_engine_commands::<Entry<C>>(&[]).await?;
 
         Ok(resp)
     }
 
+    /// Whether the leader of this node has announced to transfer its leadership to the candidate of `vote`.
+    ///
+    /// The candidate has to be elected in the term right after the leader's, with which it starts the election upon
+    /// TimeoutNow.
+    fn is_leader_transfer_announced(&self, vote: &Vote<C::NodeId>) -> bool {
+        match &self.leader_transfer_announced {
+            Some((leader_vote, target)) => {
+                leader_vote == &self.engine.state.vote && target == &vote.node_id && vote.term == leader_vote.term + 1
+            }
+            None => false,
+        }
+    }
+
     /// Handle response from a vote request sent to a peer.
     #[tracing::instrument(level = "debug", skip(self, resp))]
     async fn handle_vote_resp(
@@ -1325,7 +2455,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     ) -> Result<(), StorageError<C::NodeId>> {
         tracing::debug!(
             resp = debug(&resp),
-            target = display(target),
+            target = display(&target),
             my_vote = display(&self.engine.state.vote),
             my_last_log_id = debug(self.engine.state.last_log_id()),
             "recv vote response"
@@ -1337,16 +2467,52 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         Ok(())
     }
 
-    #[tracing::instrument(level = "debug", skip(self, msg), fields(state = debug(self.engine.state.server_state), id=display(self.id)))]
-    pub(crate) async fn handle_api_msg(&mut self, msg: RaftMsg<C, N, S>) -> Result<(), Fatal<C::NodeId>> {
+    /// Handle response from a pre-vote request sent to a peer.
+    #[tracing::instrument(level = "debug", skip(self, resp))]
+    async fn handle_pre_vote_resp(
+        &mut self,
+        resp: VoteResponse<C::NodeId>,
+        target: C::NodeId,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        tracing::debug!(
+            resp = debug(&resp),
+            target = display(&target),
+            my_vote = display(&self.engine.state.vote),
+            my_last_log_id = debug(self.engine.state.last_log_id()),
+            "recv pre-vote response"
+        );
+
+        self.engine.handle_pre_vote_resp(target, resp);
+        self.run_engine_commands::<Entry<C>>(&[]).await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, msg), fields(state = debug(self.engine.state.server_state), id=display(&self.id)))]
+    pub(crate) async fn handle_api_msg(&mut self, msg: RaftMsg<C, N, LS, SM>) -> Result<(), Fatal<C::NodeId>> {
         tracing::debug!("recv from rx_api: {}", msg.summary());
 
+        let msg = match self.reject_client_write_on_drain(msg) {
+            Some(msg) => msg,
+            None => return Ok(()),
+        };
+
         let is_leader = || self.engine.state.server_state == ServerState::Leader;
 
         match msg {
             RaftMsg::AppendEntries { rpc, tx } => {
-                let resp =
-                    self.engine.handle_append_entries_req(&rpc.vote, rpc.prev_log_id, &rpc.entries, rpc.leader_commit);
+                let now = C::AsyncRuntime::now();
+                let resp = if rpc.is_heartbeat() {
+                    self.engine.handle_heartbeat_req(&rpc.vote, rpc.leader_commit)
+                } else {
+                    self.engine.handle_append_entries_req(&rpc.vote, rpc.prev_log_id, &rpc.entries, rpc.leader_commit)
+                };
+                self.engine.follower_leader_contacted(&rpc.vote, now);
+                if let Some(target) = &rpc.leader_transfer_to {
+                    if self.engine.state.vote == rpc.vote {
+                        self.leader_transfer_announced = Some((rpc.vote.clone(), target.clone()));
+                    }
+                }
                 self.run_engine_commands(rpc.entries.as_slice()).await?;
                 let _ = tx.send(Ok(resp));
             }
@@ -1358,12 +2524,62 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                     self.handle_vote_resp(resp, target).await?;
                 }
             }
-            RaftMsg::InstallSnapshot { rpc, tx } => {
-                let _ = tx.send(self.handle_install_snapshot_request(rpc).await.extract_fatal()?);
+            RaftMsg::PreVoteResponse { target, resp, vote } => {
+                if self.does_vote_match(vote, "PreVoteResponse") {
+                    self.handle_pre_vote_resp(resp, target).await?;
+                }
+            }
+            RaftMsg::InstallSnapshot { rpc, writer, tx } => {
+                let _ = tx.send(self.handle_install_snapshot_request(rpc, writer).await.extract_fatal()?);
+            }
+            RaftMsg::GetSnapshot { tx } => {
+                let snapshot = self.state_machine.get_current_snapshot().await?;
+                let _ = tx.send(Ok(snapshot));
+            }
+            RaftMsg::BeginReceivingSnapshot { tx } => {
+                let data = self.state_machine.begin_receiving_snapshot().await?;
+                let _ = tx.send(Ok(data));
+            }
+            RaftMsg::InstallFullSnapshot { vote, snapshot, tx } => {
+                let resp = self.handle_install_full_snapshot(vote, snapshot).await?;
+                let _ = tx.send(Ok(resp));
+            }
+            RaftMsg::InstallLocalSnapshot { meta, data, tx } => {
+                let _ = tx.send(self.handle_install_local_snapshot(meta, data).await.extract_fatal()?);
             }
             RaftMsg::SnapshotUpdate { update } => {
                 self.update_snapshot_state(update);
             }
+            RaftMsg::TimeoutNow { rpc, tx } => {
+                let _ = tx.send(self.handle_timeout_now_request(rpc).await.extract_fatal()?);
+            }
+            RaftMsg::TransferLeader { target, tx } => {
+                if is_leader() {
+                    self.handle_transfer_leader(target, tx).await;
+                } else {
+                    self.reject_with_forward_to_leader(tx);
+                }
+            }
+            RaftMsg::TimeoutNowResult { target, result, vote } => {
+                if self.does_vote_match(vote, "TimeoutNowResult") {
+                    self.handle_timeout_now_result(target, result);
+                }
+            }
+            RaftMsg::ClientWriteManyRequest { rpcs, txs } => {
+                if is_leader() {
+                    if self.leader_transfer_target().is_some() {
+                        for tx in txs {
+                            self.reject_with_forward_to_transfer_target(tx);
+                        }
+                    } else {
+                        self.write_entries(rpcs, txs.into_iter().map(Into::into).collect()).await?;
+                    }
+                } else {
+                    for tx in txs {
+                        self.reject_with_forward_to_leader(tx);
+                    }
+                }
+            }
             RaftMsg::CheckIsLeaderRequest { tx } => {
                 if is_leader() {
                     self.handle_check_is_leader_request(tx).await;
@@ -1371,15 +2587,66 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                     self.reject_with_forward_to_leader(tx);
                 }
             }
-            RaftMsg::ClientWriteRequest { rpc, tx } => {
+            RaftMsg::GetReadLease { tx } => {
+                if is_leader() {
+                    self.handle_get_read_lease(tx);
+                } else {
+                    self.reject_with_forward_to_leader(tx);
+                }
+            }
+            RaftMsg::FollowerRead { max_staleness, tx } => {
+                self.handle_follower_read(max_staleness, tx);
+            }
+            RaftMsg::ClientWriteRequest { rpc, tx, forward } => {
+                if is_leader() {
+                    if self.leader_transfer_target().is_some() {
+                        self.reject_with_forward_to_transfer_target(tx);
+                    } else {
+                        self.write_entry(rpc.payload, rpc.context, Some(tx.into())).await?;
+                    }
+                } else if forward {
+                    self.forward_client_write(rpc, 0, tx).await;
+                } else {
+                    self.reject_with_forward_to_leader(tx);
+                }
+            }
+            RaftMsg::ForwardClientWrite { rpc, tx } => {
+                if is_leader() {
+                    if self.leader_transfer_target().is_some() {
+                        self.reject_with_forward_to_transfer_target(tx);
+                    } else {
+                        self.write_entry(rpc.rpc.payload, rpc.rpc.context, Some(tx.into())).await?;
+                    }
+                } else {
+                    self.forward_client_write(rpc.rpc, rpc.hops, tx).await;
+                }
+            }
+            RaftMsg::ClientWriteWithResponder { rpc, responder } => {
+                if is_leader() {
+                    if self.leader_transfer_target().is_some() {
+                        responder.send(Err(self.forward_to_transfer_target().into()));
+                    } else {
+                        self.write_entry(rpc.payload, rpc.context, Some(ClientResponder::App(responder))).await?;
+                    }
+                } else {
+                    responder.send(Err(self.forward_to_leader().into()));
+                }
+            }
+            RaftMsg::ClientWriteFFRequest { rpc, tx } => {
                 if is_leader() {
-                    self.write_entry(rpc.payload, Some(tx)).await?;
+                    if self.leader_transfer_target().is_some() {
+                        self.reject_with_forward_to_transfer_target(tx);
+                    } else {
+                        // No responder is installed: the apply result is dropped.
+                        self.write_entry(rpc.payload, rpc.context, None).await?;
+                        let _ = tx.send(Ok(()));
+                    }
                 } else {
                     self.reject_with_forward_to_leader(tx);
                 }
             }
-            RaftMsg::Initialize { members, tx } => {
-                let _ = tx.send(self.handle_initialize(members).await.extract_fatal()?);
+            RaftMsg::Initialize { members, learners, tx } => {
+                let _ = tx.send(self.handle_initialize(members, learners).await.extract_fatal()?);
             }
             RaftMsg::AddLearner { id, node, tx } => {
                 if is_leader() {
@@ -1388,6 +2655,13 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                     self.reject_with_forward_to_leader(tx);
                 }
             }
+            RaftMsg::AddLearnerAndPromote { id, node, tx } => {
+                if is_leader() {
+                    self.handle_add_learner_and_promote(id, node, tx).await?;
+                } else {
+                    self.reject_with_forward_to_leader(tx);
+                }
+            }
             RaftMsg::ChangeMembership {
                 changes,
                 when,
@@ -1400,23 +2674,75 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                     self.reject_with_forward_to_leader(tx);
                 }
             }
+            RaftMsg::ChangeMembershipDryRun {
+                changes,
+                when,
+                turn_to_learner,
+                tx,
+            } => {
+                if is_leader() {
+                    let res = self.change_membership_dry_run(changes, when, turn_to_learner);
+                    let _ = tx.send(res.map_err(ClientWriteError::ChangeMembershipError));
+                } else {
+                    self.reject_with_forward_to_leader(tx);
+                }
+            }
             RaftMsg::ExternalRequest { req } => {
-                req(&self.engine.state, &mut self.storage, &mut self.network);
+                req(&self.engine.state, &mut self.log_store, &mut self.network);
+            }
+            RaftMsg::TriggerSnapshot { tx } => {
+                let res = self.trigger_snapshot().await;
+                let _ = tx.send(Ok(res));
+            }
+            RaftMsg::TriggerElect { tx } => {
+                self.trigger_elect(tx).await?;
+            }
+            RaftMsg::TriggerPurgeLog { upto, force, tx } => {
+                let res = self.trigger_purge_log(upto, force).await?;
+                let _ = tx.send(Ok(res));
+            }
+            RaftMsg::PurgeLogsUpto { log_id, tx } => {
+                self.purge_logs_upto(log_id, tx).await?;
+            }
+            RaftMsg::GetStateSummary { tx } => {
+                let _ = tx.send(Ok(self.state_summary()));
+            }
+            RaftMsg::UpdateConfig { update, tx } => {
+                let res = self.pending_config.as_ref().unwrap_or(self.config.as_ref()).with_update(&update);
+                let _ = match res {
+                    Ok(config) => {
+                        self.pending_config = Some(config);
+                        tx.send(Ok(()))
+                    }
+                    Err(err) => tx.send(Err(err.into())),
+                };
+            }
+            RaftMsg::GetConfig { tx } => {
+                let _ = tx.send(Ok(self.config.clone()));
+            }
+            RaftMsg::Drain { tx } => {
+                self.handle_drain(tx);
             }
             RaftMsg::Tick { i } => {
                 // check every timer
 
                 tracing::debug!("received tick: {}", i);
 
+                self.apply_pending_config().await;
+
                 let current_vote = &self.engine.state.vote;
 
                 // Follower/Candidate timer: next election
                 if let Some(t) = self.next_election_time.get_time(current_vote) {
                     #[allow(clippy::collapsible_else_if)]
-                    if Instant::now() < t {
+                    if C::AsyncRuntime::now() < t {
                         // timeout has not expired.
+                    } else if self.config.disable_auto_elect {
+                        // Elections are started only by `Raft::trigger_elect()`.
                     } else {
-                        if self.engine.state.membership_state.effective.is_voter(&self.id) {
+                        if self.engine.is_witness(&self.id) {
+                            // A witness never elects.
+                        } else if self.engine.state.membership_state.effective.is_voter(&self.id) {
                             self.engine.elect();
                             self.run_engine_commands::<Entry<C>>(&[]).await?;
                         } else {
@@ -1424,6 +2750,57 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                         }
                     }
                 }
+
+                // Build a snapshot if the time based snapshot policy triggers
+                if self.config.snapshot_policy.is_time_based() {
+                    self.trigger_log_compaction_if_needed(false).await;
+                }
+
+                // Leader timer: abort a leadership transfer that takes too long
+                self.check_leader_transfer_timeout();
+
+                // Leader timer: a removed leader steps down if handing its leadership over failed
+                self.leader_step_down().await;
+
+                // Leader timer: step down if a quorum is lost
+                self.check_quorum().await?;
+
+                // Leader timer: retry promoting learners that were lagging
+                self.try_promote_learners().await?;
+            }
+
+            RaftMsg::UpdateReplicationBackoff { target, backoff, vote } => {
+                if self.does_vote_match(vote, "UpdateReplicationBackoff") {
+                    self.handle_update_replication_backoff(target, backoff);
+                }
+            }
+            RaftMsg::UpdateReplicationProgress { target, progress, vote } => {
+                if self.does_vote_match(vote, "UpdateReplicationProgress") {
+                    self.handle_update_replication_progress(target, progress);
+                }
+            }
+            RaftMsg::ReplicationAcked {
+                target,
+                sending_time,
+                vote,
+            } => {
+                if self.does_vote_match(vote, "ReplicationAcked") {
+                    self.update_acked(target, sending_time);
+                }
+            }
+
+            RaftMsg::LogFlushed { log_id, result, vote } => {
+                if let Err(e) = result {
+                    return Err(StorageError::IO {
+                        source: StorageIOError::new(ErrorSubject::Logs, ErrorVerb::Write, AnyError::new(&e)),
+                    }
+                    .into());
+                }
+
+                if self.does_vote_match(vote, "LogFlushed") {
+                    self.engine.leader_log_flushed(log_id);
+                    self.run_engine_commands::<Entry<C>>(&[]).await?;
+                }
             }
 
             RaftMsg::RevertToFollower { target, new_vote, vote } => {
@@ -1435,9 +2812,19 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             RaftMsg::UpdateReplicationMatched { target, result, vote } => {
                 if self.does_vote_match(vote, "UpdateReplicationMatched") {
                     self.handle_update_matched(target, result).await?;
+                    self.try_promote_learners().await?;
                 }
             }
 
+            RaftMsg::PickSnapshotSource { target, need, tx, vote } => {
+                if self.does_vote_match(vote, "PickSnapshotSource") {
+                    let source = self.engine.pick_snapshot_source(&target, &need).map(|id| {
+                        let node = self.engine.state.membership_state.effective.get_node(&id).cloned();
+                        (id, node)
+                    });
+                    let _ = tx.send(source);
+                }
+            }
             RaftMsg::NeedsSnapshot {
                 target: _,
                 must_include,
@@ -1463,6 +2850,8 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         vote: Vote<C::NodeId>,
     ) -> Result<(), StorageError<C::NodeId>> {
         if vote > self.engine.state.vote {
+            // The read lease is invalid once a higher vote is seen.
+            self.engine.reset_leader_clock();
             self.engine.state.vote = vote;
             self.save_vote().await?;
             // TODO: when switching to Follower, the next election time has to be set.
@@ -1471,6 +2860,37 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         Ok(())
     }
 
+    /// Record the backoff state of a replication target, for metrics.
+    fn handle_update_replication_backoff(&mut self, target: C::NodeId, backoff: Option<ReplicationBackoff>) {
+        let l = match &mut self.leader_data {
+            // A message from a removed replication stream is ignored.
+            Some(l) if l.nodes.contains_key(&target) => l,
+            _ => return,
+        };
+
+        match backoff {
+            Some(b) => l.replication_backoff.insert(target, b),
+            None => l.replication_backoff.remove(&target),
+        };
+        self.engine.metrics_flags.set_replication_changed();
+    }
+
+    /// Record the progress of a replication target, for metrics.
+    fn handle_update_replication_progress(
+        &mut self,
+        target: C::NodeId,
+        progress: ReplicationProgress<C::NodeId, InstantOf<C>>,
+    ) {
+        let l = match &mut self.leader_data {
+            // A message from a removed replication stream is ignored.
+            Some(l) if l.nodes.contains_key(&target) => l,
+            _ => return,
+        };
+
+        l.replication_progress.insert(target, progress);
+        self.engine.metrics_flags.set_replication_changed();
+    }
+
     #[tracing::instrument(level = "debug", skip_all)]
     async fn handle_update_matched(
         &mut self,
@@ -1480,7 +2900,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         // Update target's match index & check if it is awaiting removal.
 
         tracing::debug!(
-            target = display(target),
+            target = display(&target),
             result = debug(&result),
             "handle_update_matched"
         );
@@ -1493,7 +2913,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         } else {
             // no longer a leader.
             tracing::warn!(
-                target = display(target),
+                target = display(&target),
                 result = debug(&result),
                 "received replication update but no longer a leader"
             );
@@ -1509,14 +2929,33 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             }
         };
 
-        self.engine.update_progress(target, Some(matched));
+        self.engine.update_progress(target.clone(), Some(matched.clone()));
         self.run_engine_commands::<Entry<C>>(&[]).await?;
 
+        self.evict_replicated_logs();
+        self.try_send_timeout_now().await;
+        self.leader_step_down().await;
+
         self.update_replication_metrics(target, matched);
 
         Ok(())
     }
 
+    /// Evict the cached logs that every replication target has matched.
+    fn evict_replicated_logs(&self) {
+        let leader = match self.engine.state.internal_server_state.leading() {
+            Some(x) => x,
+            None => return,
+        };
+
+        let min_matched =
+            leader.progress.iter().filter(|(id, _)| id != &self.id).map(|(_, matched)| matched.index()).min();
+
+        if let Some(Some(index)) = min_matched {
+            self.log_cache.evict_upto(index);
+        }
+    }
+
     #[tracing::instrument(level = "trace", skip(self))]
     fn update_replication_metrics(&mut self, target: C::NodeId, matched: LogId<C::NodeId>) {
         tracing::debug!(%target, ?matched, "update_leader_metrics");
@@ -1552,15 +2991,14 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     async fn handle_needs_snapshot(
         &mut self,
         must_include: Option<LogId<C::NodeId>>,
-        tx: oneshot::Sender<Snapshot<C::NodeId, S::SnapshotData>>,
+        tx: oneshot::Sender<Snapshot<C::NodeId, C::SnapshotData, C::Node>>,
     ) -> Result<(), StorageError<C::NodeId>> {
         // Ensure snapshotting is configured, else do nothing.
-        let threshold = match &self.config.snapshot_policy {
-            SnapshotPolicy::LogsSinceLast(threshold) => *threshold,
-        };
+        // Without a `LogsSinceLast` policy, there is no threshold for a snapshot to be too old: any snapshot is sent.
+        let threshold = self.config.snapshot_policy.logs_since_last_threshold();
 
         // Check for existence of current snapshot.
-        let current_snapshot_opt = self.storage.get_current_snapshot().await?;
+        let current_snapshot_opt = self.state_machine.get_current_snapshot().await?;
 
         if let Some(snapshot) = current_snapshot_opt {
             if let Some(must_inc) = must_include {
@@ -1571,11 +3009,15 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             } else {
                 // If snapshot exists, ensure its distance from the leader's last log index is <= half
                 // of the configured snapshot threshold, else create a new snapshot.
-                if snapshot_is_within_half_of_threshold(
-                    &snapshot.meta.last_log_id.index,
-                    &self.engine.state.last_log_id().unwrap_or_default().index,
-                    &threshold,
-                ) {
+                let fresh = match threshold {
+                    Some(threshold) => snapshot_is_within_half_of_threshold(
+                        &snapshot.meta.last_log_id.index,
+                        &self.engine.state.last_log_id().index().unwrap_or_default(),
+                        &threshold,
+                    ),
+                    None => true,
+                };
+                if fresh {
                     let _ = tx.send(snapshot);
                     return Ok(());
                 }
@@ -1588,7 +3030,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         // Else we just drop any other state and continue. Leaders never enter `Streaming` state.
         if let Some(SnapshotState::Snapshotting { handle, sender }) = self.snapshot_state.take() {
             let mut chan = sender.subscribe();
-            tokio::spawn(
+            let _ = C::AsyncRuntime::spawn(
                 async move {
                     let _ = chan.recv().await;
                     // TODO(xp): send another ReplicaEvent::NeedSnapshot to raft core
@@ -1615,12 +3057,14 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
 }
 
 #[async_trait::async_trait]
-impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime<C> for RaftCore<C, N, S> {
+impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>> RaftRuntime<C>
+    for RaftCore<C, N, LS, SM>
+{
     async fn run_command<'e, Ent>(
         &mut self,
         input_ref_entries: &'e [Ent],
         cur: &mut usize,
-        cmd: &Command<C::NodeId>,
+        cmd: &Command<C::NodeId, C::Node>,
     ) -> Result<(), StorageError<C::NodeId>>
     where
         Ent: RaftLogId<C::NodeId> + Sync + Send + 'e,
@@ -1644,11 +3088,25 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
                 // Build a slice of references.
                 let entry_refs = entries.iter().collect::<Vec<_>>();
 
-                self.storage.append_to_log(&entry_refs).await?
+                let last_log_id = entries.last().map(|ent| ent.log_id.clone());
+                self.append_to_log(&entry_refs, last_log_id).await?;
+
+                for ent in entries.iter() {
+                    if let EntryPayload::Normal(data) = &ent.payload {
+                        self.bytes_since_last_snapshot += C::data_size(data);
+                    }
+                }
+
+                // The replication streams read the logs a leader appended from the cache, without copying them.
+                if let Some(l) = &self.leader_data {
+                    if !l.nodes.is_empty() {
+                        self.log_cache.append(entries.into_iter().map(Arc::new));
+                    }
+                }
             }
             Command::MoveInputCursorBy { n } => *cur += n,
             Command::SaveVote { vote } => {
-                self.storage.save_vote(vote).await?;
+                self.log_store.save_vote(vote).await?;
             }
             Command::InstallElectionTimer { can_be_leader } => {
                 self.set_next_election_time(*can_be_leader);
@@ -1656,9 +3114,13 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
             Command::RejectElection {} => {
                 self.reject_election_for_a_while();
             }
-            Command::PurgeLog { upto } => self.storage.purge_logs_upto(*upto).await?,
+            Command::PurgeLog { upto } => {
+                self.log_store.purge_logs_upto(upto.clone()).await?;
+                self.log_cache.evict_upto(upto.index);
+            }
             Command::DeleteConflictLog { since } => {
-                self.storage.delete_conflict_logs_since(*since).await?;
+                self.log_store.delete_conflict_logs_since(since.clone()).await?;
+                self.log_cache.truncate(since.index);
             }
             Command::BuildSnapshot { .. } => {}
             Command::SendVote { vote_req } => {
@@ -1669,44 +3131,81 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
                     for node in l.nodes.values() {
                         let _ = node.repl_tx.send(UpdateReplication {
                             last_log_id: None,
-                            committed: *committed,
+                            committed: committed.clone(),
+                            config: None,
+                            witness: None,
                         });
                     }
+                    let _ = l.tx_committed.send(committed.clone());
                 } else {
                     unreachable!("it has to be a leader!!!");
                 }
             }
             Command::LeaderCommit { ref upto, .. } => {
+                self.save_committed().await?;
                 for i in self.engine.state.last_applied.next_index()..(upto.index + 1) {
                     self.leader_commit(i).await?;
                 }
             }
             Command::FollowerCommit { upto, .. } => {
+                self.save_committed().await?;
                 self.apply_to_state_machine(upto.index).await?;
             }
             Command::ReplicateInputEntries { range } => {
                 if let Some(last) = range.clone().last() {
-                    self.replicate_entry(*input_ref_entries[last].get_log_id());
+                    self.replicate_entry(input_ref_entries[last].get_log_id().clone());
                 }
             }
             Command::UpdateReplicationStreams { remove, add } => {
                 for (node_id, _matched) in remove.iter() {
-                    self.remove_replication(*node_id).await;
+                    self.remove_replication(node_id.clone()).await;
                 }
                 for (node_id, _matched) in add.iter() {
-                    let state = self.spawn_replication_stream(*node_id).await;
+                    let state = self.spawn_replication_stream(node_id.clone()).await;
                     if let Some(l) = &mut self.leader_data {
-                        l.nodes.insert(*node_id, state);
+                        l.nodes.insert(node_id.clone(), state);
                     } else {
                         unreachable!("it has to be a leader!!!");
                     }
+                    self.spawn_heartbeat(node_id.clone()).await;
                 }
             }
-            Command::UpdateMembership { .. } => {
-                // TODO: not used
+            Command::UpdateMembership { membership } => {
+                // A target becomes a witness or is no longer one with the membership.
+                if let Some(l) = &self.leader_data {
+                    for (target, node) in l.nodes.iter() {
+                        let _ = node.repl_tx.send(UpdateReplication {
+                            last_log_id: None,
+                            committed: self.engine.state.committed.clone(),
+                            config: None,
+                            witness: Some(membership.membership.is_witness(target)),
+                        });
+                    }
+                }
             }
         }
 
         Ok(())
     }
 }
+
+/// Whether a `SaveVote` can be deferred to the `AppendInputEntries` in the `following` commands, to save the vote and
+/// the logs with one storage call.
+///
+/// Only the commands that do not send any message may run in between, thus nothing observes the vote before it is
+/// saved. E.g., a leader starts replication before appending its first log, and it must not send the vote before it
+/// is saved, thus the vote is saved on its own.
+fn is_vote_saved_with_append<NID: NodeId, N: NodeInfo>(following: &[Command<NID, N>]) -> bool {
+    for cmd in following {
+        match cmd {
+            Command::AppendInputEntries { range } => return !range.is_empty(),
+            Command::InstallElectionTimer { .. }
+            | Command::RejectElection {}
+            | Command::DeleteConflictLog { .. }
+            | Command::UpdateMembership { .. }
+            | Command::UpdateServerState { .. } => {}
+            _ => return false,
+        }
+    }
+    false
+}
@@ -1,6 +1,7 @@
 use futures::future::AbortHandle;
-use tokio::sync::broadcast;
 
+use crate::async_runtime::broadcast;
+use crate::metrics::SnapshotBuildProgress;
 use crate::LogId;
 use crate::NodeId;
 
@@ -21,9 +22,43 @@ pub(crate) enum SnapshotState<S> {
         id: String,
         /// A handle to the snapshot writer.
         snapshot: Box<S>,
+        /// The checksum of the bytes written so far.
+        checksum: crc32fast::Hasher,
     },
 }
 
+/// How to handle a received snapshot chunk, given the number of bytes of the same snapshot already received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ChunkAction {
+    /// Write the chunk data except the first `skip` bytes, which are already received.
+    Write { skip: usize },
+
+    /// Every byte in the chunk is already received, e.g., the chunk is re-sent.
+    Duplicate,
+
+    /// The chunk starts after the received bytes: the sender has to resume from the received offset.
+    Gap,
+}
+
+impl ChunkAction {
+    /// Decide how to handle a chunk of `len` bytes at `offset`, when bytes `[0, received)` are already written.
+    ///
+    /// Bytes are always written in order, so that the checksum of the written bytes can be updated incrementally.
+    pub(crate) fn new(received: u64, offset: u64, len: u64) -> Self {
+        if offset > received {
+            return ChunkAction::Gap;
+        }
+
+        if offset + len <= received {
+            return ChunkAction::Duplicate;
+        }
+
+        ChunkAction::Write {
+            skip: (received - offset) as usize,
+        }
+    }
+}
+
 /// An update on a snapshot creation process.
 #[derive(Debug, Clone)]
 pub(crate) enum SnapshotUpdate<NID: NodeId> {
@@ -32,4 +67,7 @@ pub(crate) enum SnapshotUpdate<NID: NodeId> {
 
     /// Snapshot creation failed.
     SnapshotFailed,
+
+    /// The builder reported how far the snapshot creation has gone.
+    SnapshotProgress(SnapshotBuildProgress),
 }
@@ -0,0 +1,35 @@
+use crate::core::snapshot_state::ChunkAction;
+
+#[test]
+fn test_chunk_action() -> anyhow::Result<()> {
+    // received, offset, len, want
+    let cases = vec![
+        // the next chunk
+        (0, 0, 3, ChunkAction::Write { skip: 0 }),
+        (3, 3, 3, ChunkAction::Write { skip: 0 }),
+        // overlapping with received bytes
+        (3, 1, 3, ChunkAction::Write { skip: 2 }),
+        (3, 0, 4, ChunkAction::Write { skip: 3 }),
+        // duplicate
+        (3, 0, 3, ChunkAction::Duplicate),
+        (6, 0, 3, ChunkAction::Duplicate),
+        (6, 3, 3, ChunkAction::Duplicate),
+        (6, 6, 0, ChunkAction::Duplicate),
+        // out of order
+        (3, 4, 3, ChunkAction::Gap),
+        (0, 3, 3, ChunkAction::Gap),
+    ];
+
+    for (received, offset, len, want) in cases {
+        assert_eq!(
+            want,
+            ChunkAction::new(received, offset, len),
+            "case: received: {}, offset: {}, len: {}",
+            received,
+            offset,
+            len
+        );
+    }
+
+    Ok(())
+}
@@ -2,18 +2,19 @@
 
 use std::time::Duration;
 
-use tokio::sync::mpsc;
-use tokio::task::JoinHandle;
-use tokio::time::sleep_until;
-use tokio::time::Instant;
 use tracing::Level;
 use tracing::Span;
 use tracing_futures::Instrument;
 
+use crate::async_runtime::mpsc;
+use crate::async_runtime::Instant;
+use crate::async_runtime::JoinHandle;
 use crate::raft::RaftMsg;
+use crate::AsyncRuntime;
 use crate::NodeId;
+use crate::RaftLogStorage;
 use crate::RaftNetworkFactory;
-use crate::RaftStorage;
+use crate::RaftStateMachine;
 use crate::RaftTypeConfig;
 use crate::Vote;
 
@@ -22,18 +23,18 @@ use crate::Vote;
 /// If the vote on a node changes, the timeout belonging to a previous vote becomes invalid.
 /// See: https://datafuselabs.github.io/openraft/vote.html
 #[derive(Debug)]
-pub(crate) struct VoteWiseTime<NID: NodeId> {
+pub(crate) struct VoteWiseTime<NID: NodeId, I: Instant> {
     pub(crate) vote: Vote<NID>,
-    pub(crate) time: Instant,
+    pub(crate) time: I,
 }
 
-impl<NID: NodeId> VoteWiseTime<NID> {
-    pub(crate) fn new(vote: Vote<NID>, time: Instant) -> Self {
+impl<NID: NodeId, I: Instant> VoteWiseTime<NID, I> {
+    pub(crate) fn new(vote: Vote<NID>, time: I) -> Self {
         Self { vote, time }
     }
 
     /// Return the time if vote does not change since it is set.
-    pub(crate) fn get_time(&self, current_vote: &Vote<NID>) -> Option<Instant> {
+    pub(crate) fn get_time(&self, current_vote: &Vote<NID>) -> Option<I> {
         debug_assert!(&self.vote <= current_vote);
 
         if &self.vote == current_vote {
@@ -44,34 +45,39 @@ impl<NID: NodeId> VoteWiseTime<NID> {
     }
 }
 
-pub(crate) struct Tick<C, N, S>
+pub(crate) struct Tick<C, N, LS, SM>
 where
     C: RaftTypeConfig,
     N: RaftNetworkFactory<C>,
-    S: RaftStorage<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
 {
     interval: Duration,
 
-    tx: mpsc::UnboundedSender<RaftMsg<C, N, S>>,
+    tx: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
 }
 
-impl<C, N, S> Tick<C, N, S>
+impl<C, N, LS, SM> Tick<C, N, LS, SM>
 where
     C: RaftTypeConfig,
     N: RaftNetworkFactory<C>,
-    S: RaftStorage<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
 {
-    pub(crate) fn spawn(interval: Duration, tx: mpsc::UnboundedSender<RaftMsg<C, N, S>>) -> JoinHandle<()> {
+    pub(crate) fn spawn(
+        interval: Duration,
+        tx: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
+    ) -> JoinHandle<(), <C::AsyncRuntime as AsyncRuntime>::JoinError> {
         let t = Tick { interval, tx };
 
-        tokio::spawn(
+        C::AsyncRuntime::spawn(
             async move {
                 let mut i = 0;
                 loop {
                     i += 1;
 
-                    let at = Instant::now() + t.interval;
-                    sleep_until(at).await;
+                    let at = C::AsyncRuntime::now() + t.interval;
+                    C::AsyncRuntime::sleep_until(at).await;
 
                     let send_res = t.tx.send(RaftMsg::Tick { i });
                     if let Err(_e) = send_res {
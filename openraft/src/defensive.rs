@@ -76,7 +76,7 @@ where
 
         if last_log_id.index() > last_applied.index() && last_log_id < last_applied {
             return Err(
-                DefensiveError::new(ErrorSubject::Log(last_log_id.unwrap()), Violation::DirtyLog {
+                DefensiveError::new(ErrorSubject::Log(last_log_id.clone().unwrap()), Violation::DirtyLog {
                     higher_index_log_id: last_log_id.unwrap(),
                     lower_index_log_id: last_applied.unwrap(),
                 })
@@ -96,7 +96,11 @@ where
 
         let h = self.inner().read_vote().await?;
 
-        let curr = h.unwrap_or_default();
+        // Any vote is acceptable if there is no vote yet.
+        let curr = match h {
+            None => return Ok(()),
+            Some(x) => x,
+        };
 
         if vote.term < curr.term {
             return Err(DefensiveError::new(ErrorSubject::Vote, Violation::TermNotAscending {
@@ -109,7 +113,11 @@ where
         if vote >= &curr {
             Ok(())
         } else {
-            Err(DefensiveError::new(ErrorSubject::Vote, Violation::NonIncrementalVote { curr, to: *vote }).into())
+            Err(DefensiveError::new(ErrorSubject::Vote, Violation::NonIncrementalVote {
+                curr,
+                to: vote.clone(),
+            })
+            .into())
         }
     }
 
@@ -123,18 +131,18 @@ where
             return Ok(());
         }
 
-        let mut prev_log_id = entries[0].log_id;
+        let mut prev_log_id = entries[0].log_id.clone();
 
         for e in entries.iter().skip(1) {
             if e.log_id.index != prev_log_id.index + 1 {
                 return Err(DefensiveError::new(ErrorSubject::Logs, Violation::LogsNonConsecutive {
                     prev: Some(prev_log_id),
-                    next: e.log_id,
+                    next: e.log_id.clone(),
                 })
                 .into());
             }
 
-            prev_log_id = e.log_id;
+            prev_log_id = e.log_id.clone();
         }
 
         Ok(())
@@ -166,10 +174,10 @@ where
 
         let last_id = self.inner().get_log_state().await?.last_log_id;
 
-        let first_id = entries[0].log_id;
+        let first_id = entries[0].log_id.clone();
         if last_id.next_index() != first_id.index {
             return Err(
-                DefensiveError::new(ErrorSubject::Log(first_id), Violation::LogsNonConsecutive {
+                DefensiveError::new(ErrorSubject::Log(first_id.clone()), Violation::LogsNonConsecutive {
                     prev: last_id,
                     next: first_id,
                 })
@@ -188,12 +196,12 @@ where
 
         let last_id = self.inner().get_log_state().await?.last_log_id;
 
-        let first_id = entries[0].log_id;
+        let first_id = entries[0].log_id.clone();
         // TODO(xp): test first eq last.
         // TODO(xp): test last == None is ok
-        if last_id.is_some() && Some(first_id) <= last_id {
+        if last_id.is_some() && Some(first_id.clone()) <= last_id {
             return Err(
-                DefensiveError::new(ErrorSubject::Log(first_id), Violation::LogsNonConsecutive {
+                DefensiveError::new(ErrorSubject::Log(first_id.clone()), Violation::LogsNonConsecutive {
                     prev: last_id,
                     next: first_id,
                 })
@@ -211,7 +219,7 @@ where
         let (last_applied, _) = self.inner().last_applied_state().await?;
         if Some(upto.index) > last_applied.index() {
             return Err(
-                DefensiveError::new(ErrorSubject::Log(upto), Violation::PurgeNonApplied {
+                DefensiveError::new(ErrorSubject::Log(upto.clone()), Violation::PurgeNonApplied {
                     last_applied,
                     purge_upto: upto,
                 })
@@ -228,7 +236,7 @@ where
         let (last_applied, _) = self.inner().last_applied_state().await?;
         if Some(since.index) <= last_applied.index() {
             return Err(
-                DefensiveError::new(ErrorSubject::Log(since), Violation::AppliedWontConflict {
+                DefensiveError::new(ErrorSubject::Log(since.clone()), Violation::AppliedWontConflict {
                     last_applied,
                     first_conflict_log_id: since,
                 })
@@ -249,10 +257,10 @@ where
 
         let (last_id, _) = self.inner().last_applied_state().await?;
 
-        let first_id = entries[0].log_id;
+        let first_id = entries[0].log_id.clone();
         if last_id.next_index() != first_id.index {
             return Err(
-                DefensiveError::new(ErrorSubject::Apply(first_id), Violation::ApplyNonConsecutive {
+                DefensiveError::new(ErrorSubject::Apply(first_id.clone()), Violation::ApplyNonConsecutive {
                     prev: last_id,
                     next: first_id,
                 })
@@ -310,11 +318,11 @@ where
 
         let (last_id, _) = self.inner().last_applied_state().await?;
 
-        let first_id = entries[0].log_id;
+        let first_id = entries[0].log_id.clone();
         // TODO(xp): test first eq last
-        if Some(first_id) <= last_id {
+        if Some(first_id.clone()) <= last_id {
             return Err(
-                DefensiveError::new(ErrorSubject::Apply(first_id), Violation::ApplyNonConsecutive {
+                DefensiveError::new(ErrorSubject::Apply(first_id.clone()), Violation::ApplyNonConsecutive {
                     prev: last_id,
                     next: first_id,
                 })
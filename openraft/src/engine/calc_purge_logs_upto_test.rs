@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+
+use crate::engine::Engine;
+use crate::engine::EngineConfig;
+use crate::engine::LogIdList;
+use crate::error::LogNotApplied;
+use crate::error::PurgeLogsError;
+use crate::error::ReplicationLagging;
+use crate::progress::Progress;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::RaftState;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 0 },
+        index,
+    }
+}
+
+/// members: {1,2}, learners: {3}
+fn m12_3() -> Membership<u64> {
+    Membership::<u64>::new(vec![btreeset! {1,2}], Some(btreeset! {3}))
+}
+
+fn eng() -> Engine<u64> {
+    let mut eng = Engine::<u64>::new(1, &RaftState::new(1), EngineConfig::default());
+    eng.state.log_ids = LogIdList::new(vec![
+        //
+        log_id(0, 0),
+        log_id(1, 1),
+        log_id(3, 3),
+        log_id(5, 5),
+    ]);
+    eng.state.last_applied = Some(log_id(3, 4));
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m12_3()));
+    eng
+}
+
+#[test]
+fn test_calc_purge_logs_upto() -> anyhow::Result<()> {
+    // last_purged_log_id, upto, want
+    let cases = vec![
+        //
+        (None, log_id(1, 2), Some(log_id(1, 2))),
+        (None, log_id(3, 4), Some(log_id(3, 4))),
+        //
+        (Some(log_id(1, 2)), log_id(1, 1), None),
+        (Some(log_id(1, 2)), log_id(1, 2), None),
+        (Some(log_id(1, 2)), log_id(3, 3), Some(log_id(3, 3))),
+    ];
+
+    for (last_purged, upto, want) in cases {
+        let mut eng = eng();
+
+        if let Some(last_purged) = &last_purged {
+            eng.state.log_ids.purge(last_purged);
+        }
+
+        let got = eng.calc_purge_logs_upto(&upto)?;
+        assert_eq!(want, got, "case: last_purged: {:?}, upto: {}", last_purged, upto);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_calc_purge_logs_upto_not_applied() -> anyhow::Result<()> {
+    let eng = eng();
+
+    let res = eng.calc_purge_logs_upto(&log_id(5, 5));
+    match res {
+        Err(PurgeLogsError::NotApplied(e)) => {
+            assert_eq!(
+                LogNotApplied {
+                    log_id: log_id(5, 5),
+                    last_applied: Some(log_id(3, 4)),
+                },
+                e
+            );
+        }
+        _ => {
+            panic!("expect PurgeLogsError::NotApplied, got: {:?}", res);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_calc_purge_logs_upto_replication_lagging() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.state.new_leader(&eng.config.commit_quorum);
+
+    // The leader itself is not a replication target.
+    let leader = eng.state.internal_server_state.leading_mut().unwrap();
+    let _ = leader.progress.update(&2, Some(log_id(3, 4)));
+    let _ = leader.progress.update(&3, Some(log_id(1, 2)));
+
+    assert_eq!(Some(log_id(1, 2)), eng.calc_purge_logs_upto(&log_id(1, 2))?);
+
+    // Learner 3 still needs log 3.
+    let res = eng.calc_purge_logs_upto(&log_id(3, 3));
+    match res {
+        Err(PurgeLogsError::ReplicationLagging(e)) => {
+            assert_eq!(
+                ReplicationLagging {
+                    log_id: log_id(3, 3),
+                    target: 3,
+                    matched: Some(log_id(1, 2)),
+                },
+                e
+            );
+        }
+        _ => {
+            panic!("expect PurgeLogsError::ReplicationLagging, got: {:?}", res);
+        }
+    }
+
+    let leader = eng.state.internal_server_state.leading_mut().unwrap();
+    let _ = leader.progress.update(&3, Some(log_id(3, 4)));
+
+    assert_eq!(Some(log_id(3, 4)), eng.calc_purge_logs_upto(&log_id(3, 4))?);
+
+    Ok(())
+}
@@ -1,7 +1,9 @@
 use crate::engine::Engine;
+use crate::engine::EngineConfig;
 use crate::engine::LogIdList;
 use crate::LeaderId;
 use crate::LogId;
+use crate::RaftState;
 
 fn log_id(term: u64, index: u64) -> LogId<u64> {
     LogId::<u64> {
@@ -11,7 +13,7 @@ fn log_id(term: u64, index: u64) -> LogId<u64> {
 }
 
 fn eng() -> Engine<u64> {
-    let mut eng = Engine::<u64>::default();
+    let mut eng = Engine::<u64>::new(0, &RaftState::new(0), EngineConfig::default());
     eng.state.log_ids = LogIdList::new(vec![
         //
         log_id(0, 0),
@@ -121,3 +123,45 @@ fn test_keep_unsnapshoted() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+// keep_unsnapshoted_log is set to true,
+// logs included in the snapshot are purged except the last `max_in_snapshot_log_to_keep` ones.
+fn test_max_in_snapshot_log_to_keep() -> anyhow::Result<()> {
+    let cases = vec![
+        // last_purged, in_snapshot_keep, want
+        (None, 0, Some(log_id(5, 5))),
+        (None, 2, Some(log_id(3, 3))),
+        (None, 5, Some(log_id(0, 0))),
+        (None, 6, None),
+        (None, 100, None),
+        // never go backwards
+        (Some(log_id(3, 3)), 1, Some(log_id(3, 4))),
+        (Some(log_id(3, 3)), 2, None),
+        (Some(log_id(3, 3)), 3, None),
+    ];
+
+    for (purged, in_snapshot_keep, want) in cases {
+        let mut eng = eng();
+        eng.config.keep_unsnapshoted_log = true;
+        eng.config.max_applied_log_to_keep = 0;
+        eng.config.max_in_snapshot_log_to_keep = in_snapshot_keep;
+        eng.config.purge_batch_size = 1;
+
+        if let Some(last_purged) = purged {
+            eng.state.log_ids.purge(&last_purged);
+        }
+        eng.state.committed = Some(log_id(5, 5));
+        eng.snapshot_last_log_id = Some(log_id(5, 5));
+
+        let got = eng.calc_purge_upto();
+
+        assert_eq!(
+            want, got,
+            "case: purged: {:?}, in_snapshot_keep: {}",
+            purged, in_snapshot_keep
+        )
+    }
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+
+use crate::engine::Engine;
+use crate::engine::EngineConfig;
+use crate::engine::LogIdList;
+use crate::progress::Progress;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::RaftState;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 0 },
+        index,
+    }
+}
+
+fn m12() -> Membership<u64> {
+    Membership::<u64>::new(vec![btreeset! {1,2}], None)
+}
+
+fn eng() -> Engine<u64> {
+    let mut eng = Engine::<u64>::new(1, &RaftState::new(1), EngineConfig::default());
+    eng.state.log_ids = LogIdList::new(vec![
+        //
+        log_id(0, 0),
+        log_id(1, 1),
+        log_id(3, 3),
+        log_id(5, 5),
+    ]);
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m12()));
+    eng
+}
+
+#[test]
+fn test_calc_trigger_purge_upto() -> anyhow::Result<()> {
+    // last_purged_log_id, snapshot, upto, want
+    let cases = vec![
+        //
+        (None, None, 3, None),
+        //
+        (None, Some(log_id(3, 4)), 2, Some(log_id(1, 2))),
+        (None, Some(log_id(3, 4)), 4, Some(log_id(3, 4))),
+        (None, Some(log_id(3, 4)), 10, Some(log_id(3, 4))),
+        (None, Some(log_id(3, 4)), u64::MAX, Some(log_id(3, 4))),
+        //
+        (Some(log_id(1, 2)), Some(log_id(3, 4)), 1, None),
+        (Some(log_id(1, 2)), Some(log_id(3, 4)), 2, None),
+        (Some(log_id(1, 2)), Some(log_id(3, 4)), 3, Some(log_id(3, 3))),
+        (Some(log_id(3, 4)), Some(log_id(3, 4)), 10, None),
+    ];
+
+    for (last_purged, snapshot, upto, want) in cases {
+        let mut eng = eng();
+
+        if let Some(last_purged) = &last_purged {
+            eng.state.log_ids.purge(last_purged);
+        }
+        eng.snapshot_last_log_id = snapshot.clone();
+
+        let got = eng.calc_trigger_purge_upto(upto, false);
+        assert_eq!(
+            want, got,
+            "case: last_purged: {:?}, snapshot: {:?}, upto: {}",
+            last_purged, snapshot, upto
+        );
+
+        // Without a leader, `force` makes no difference.
+        let got = eng.calc_trigger_purge_upto(upto, true);
+        assert_eq!(
+            want, got,
+            "force, case: last_purged: {:?}, snapshot: {:?}, upto: {}",
+            last_purged, snapshot, upto
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_calc_trigger_purge_upto_keep_logs_for_replication() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.snapshot_last_log_id = Some(log_id(3, 4));
+    eng.state.new_leader(&eng.config.commit_quorum);
+
+    let leader = eng.state.internal_server_state.leading_mut().unwrap();
+    let _ = leader.progress.update(&1, Some(log_id(5, 5)));
+
+    // node-2 has matched nothing: no log can be purged.
+    assert_eq!(None, eng.calc_trigger_purge_upto(10, false));
+    assert_eq!(Some(log_id(3, 4)), eng.calc_trigger_purge_upto(10, true));
+
+    // node-2 has matched log 2: log 3 is needed.
+    let leader = eng.state.internal_server_state.leading_mut().unwrap();
+    let _ = leader.progress.update(&2, Some(log_id(1, 2)));
+
+    assert_eq!(Some(log_id(1, 2)), eng.calc_trigger_purge_upto(10, false));
+    assert_eq!(Some(log_id(1, 1)), eng.calc_trigger_purge_upto(1, false));
+    assert_eq!(Some(log_id(3, 4)), eng.calc_trigger_purge_upto(10, true));
+
+    // node-2 has matched every log: only the snapshot limits the purge.
+    let leader = eng.state.internal_server_state.leading_mut().unwrap();
+    let _ = leader.progress.update(&2, Some(log_id(5, 5)));
+
+    assert_eq!(Some(log_id(3, 4)), eng.calc_trigger_purge_upto(10, false));
+
+    Ok(())
+}
@@ -5,13 +5,15 @@ use crate::raft::VoteRequest;
 use crate::EffectiveMembership;
 use crate::LogId;
 use crate::MetricsChangeFlags;
+use crate::Node;
 use crate::NodeId;
+use crate::NodeInfo;
 use crate::ServerState;
 use crate::Vote;
 
 /// Commands to send to `RaftRuntime` to execute, to update the application state.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) enum Command<NID: NodeId> {
+pub(crate) enum Command<NID: NodeId, N: NodeInfo = Node> {
     /// Update server state, e.g., Leader, Follower etc.
     /// TODO: consider removing this variant. A runtime does not need to know about this. It is only meant for metrics
     ///       report.
@@ -42,7 +44,7 @@ pub(crate) enum Command<NID: NodeId> {
     /// Membership config changed, need to update replication streams.
     UpdateMembership {
         // TODO: not used yet.
-        membership: Arc<EffectiveMembership<NID>>,
+        membership: Arc<EffectiveMembership<NID, N>>,
     },
 
     /// Membership config changed, need to update replication streams.
@@ -92,7 +94,7 @@ pub(crate) enum Command<NID: NodeId> {
     BuildSnapshot {},
 }
 
-impl<NID: NodeId> Command<NID> {
+impl<NID: NodeId, N: NodeInfo> Command<NID, N> {
     /// Update the flag of the metrics that needs to be updated when this command is executed.
     pub(crate) fn update_metrics_flags(&self, flags: &mut MetricsChangeFlags) {
         match &self {
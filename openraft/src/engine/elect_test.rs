@@ -6,6 +6,7 @@ use pretty_assertions::assert_eq;
 use crate::core::ServerState;
 use crate::engine::Command;
 use crate::engine::Engine;
+use crate::engine::EngineConfig;
 use crate::engine::LogIdList;
 use crate::raft::VoteRequest;
 use crate::EffectiveMembership;
@@ -13,6 +14,7 @@ use crate::LeaderId;
 use crate::LogId;
 use crate::Membership;
 use crate::MetricsChangeFlags;
+use crate::RaftState;
 use crate::Vote;
 
 fn log_id(term: u64, index: u64) -> LogId<u64> {
@@ -31,7 +33,7 @@ fn m12() -> Membership<u64> {
 }
 
 fn eng() -> Engine<u64> {
-    Engine::<u64>::default()
+    Engine::<u64>::new(1, &RaftState::new(1), EngineConfig::default())
 }
 
 #[test]
@@ -39,7 +41,6 @@ fn test_elect() -> anyhow::Result<()> {
     tracing::info!("--- single node: become leader at once");
     {
         let mut eng = eng();
-        eng.id = 1;
         eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(0, 1)), m1()));
 
         eng.elect();
@@ -76,12 +77,11 @@ fn test_elect() -> anyhow::Result<()> {
     tracing::info!("--- single node: electing again will override previous state");
     {
         let mut eng = eng();
-        eng.id = 1;
         eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(0, 1)), m1()));
 
         // Build in-progress election state
         eng.state.vote = Vote::new_committed(1, 2);
-        eng.state.new_leader();
+        eng.state.new_leader(&eng.config.commit_quorum);
         eng.state.internal_server_state.leading_mut().map(|l| l.vote_granted_by.insert(1));
 
         eng.elect();
@@ -118,7 +118,6 @@ fn test_elect() -> anyhow::Result<()> {
     tracing::info!("--- multi nodes: enter candidate state");
     {
         let mut eng = eng();
-        eng.id = 1;
         eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(0, 1)), m12()));
         eng.state.log_ids = LogIdList::new(vec![log_id(1, 1)]);
 
@@ -155,3 +154,108 @@ fn test_elect() -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_elect_non_copy_node_id() -> anyhow::Result<()> {
+    let s = |x: &str| x.to_string();
+
+    tracing::info!("--- single node with String id: become leader at once");
+    {
+        let mut eng = Engine::<String>::new(s("a"), &RaftState::new(s("a")), EngineConfig::default());
+        eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(
+            Some(LogId::new(LeaderId::new(0, s("a")), 1)),
+            Membership::new(vec![btreeset! {s("a")}], None),
+        ));
+
+        eng.elect();
+
+        assert_eq!(Vote::new_committed(1, s("a")), eng.state.vote);
+        assert_eq!(ServerState::Leader, eng.state.server_state);
+
+        assert_eq!(
+            vec![
+                Command::SaveVote {
+                    vote: Vote::new(1, s("a"))
+                },
+                Command::SaveVote {
+                    vote: Vote::new_committed(1, s("a"))
+                },
+                Command::UpdateServerState {
+                    server_state: ServerState::Leader
+                }
+            ],
+            eng.commands
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_elect_with_pre_vote() -> anyhow::Result<()> {
+    let eng = || {
+        Engine::<u64>::new(1, &RaftState::new(1), EngineConfig {
+            enable_prevote: true,
+            ..Default::default()
+        })
+    };
+
+    tracing::info!("--- single node: pre-vote is granted at once, become leader at once");
+    {
+        let mut eng = eng();
+        eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(0, 1)), m1()));
+
+        eng.elect();
+
+        assert_eq!(Vote::new_committed(1, 1), eng.state.vote);
+        assert_eq!(None, eng.pre_vote_granted_by);
+        assert!(eng.last_election_pre_vote);
+
+        assert_eq!(ServerState::Leader, eng.state.server_state);
+        assert_eq!(
+            vec![
+                Command::SaveVote { vote: Vote::new(1, 1) },
+                Command::SaveVote {
+                    vote: Vote::new_committed(1, 1)
+                },
+                Command::UpdateServerState {
+                    server_state: ServerState::Leader
+                }
+            ],
+            eng.commands
+        );
+    }
+
+    tracing::info!("--- multi nodes: send pre-vote without changing vote");
+    {
+        let mut eng = eng();
+        eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(0, 1)), m12()));
+        eng.state.log_ids = LogIdList::new(vec![log_id(1, 1)]);
+
+        eng.elect();
+
+        assert_eq!(Vote::new(0, 1), eng.state.vote);
+        assert!(eng.state.internal_server_state.is_following());
+        assert_eq!(Some(btreeset! {1}), eng.pre_vote_granted_by);
+        assert!(!eng.last_election_pre_vote);
+
+        assert_eq!(ServerState::Follower, eng.state.server_state);
+        assert_eq!(
+            MetricsChangeFlags {
+                leader: false,
+                other_metrics: false
+            },
+            eng.metrics_flags
+        );
+
+        assert_eq!(
+            vec![
+                Command::SendVote {
+                    vote_req: VoteRequest::new_pre_vote(Vote::new(1, 1), Some(log_id(1, 1)))
+                },
+                Command::InstallElectionTimer { can_be_leader: true },
+            ],
+            eng.commands
+        );
+    }
+    Ok(())
+}
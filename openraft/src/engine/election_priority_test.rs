@@ -0,0 +1,190 @@
+use std::sync::Arc;
+
+use maplit::btreemap;
+use maplit::btreeset;
+
+use crate::core::ServerState;
+use crate::engine::Command;
+use crate::engine::Engine;
+use crate::engine::EngineConfig;
+use crate::engine::LogIdList;
+use crate::raft::VoteRequest;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::Node;
+use crate::RaftState;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+/// Voters 1,2,3 with priority 10, 5 and none; learner 4 with priority 20.
+fn m1234() -> Membership<u64> {
+    Membership::<u64>::with_nodes(vec![btreeset! {1,2,3}], btreemap! {
+        1 => Node::new("1").with_election_priority(10),
+        2 => Node::new("2").with_election_priority(5),
+        3 => Node::new("3"),
+        4 => Node::new("4").with_election_priority(20),
+    })
+    .unwrap()
+}
+
+fn eng(id: u64) -> Engine<u64> {
+    let mut eng = Engine::<u64>::new(id, &RaftState::new(id), EngineConfig::default());
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m1234()));
+    eng
+}
+
+/// Make the engine a candidate of `term`.
+fn candidate(id: u64, term: u64) -> Engine<u64> {
+    let mut eng = eng(id);
+    eng.state.log_ids = LogIdList::new(vec![log_id(1, 1)]);
+    eng.state.vote = Vote::new(term, id);
+    eng.state.server_state = ServerState::Candidate;
+    eng.state.new_leader(&eng.config.commit_quorum);
+    eng
+}
+
+#[test]
+fn test_election_priority() -> anyhow::Result<()> {
+    let eng = eng(1);
+
+    assert_eq!(10, eng.election_priority(&1));
+    assert_eq!(5, eng.election_priority(&2));
+    assert_eq!(0, eng.election_priority(&3), "no priority");
+    assert_eq!(20, eng.election_priority(&4));
+    assert_eq!(0, eng.election_priority(&5), "not in membership");
+
+    Ok(())
+}
+
+#[test]
+fn test_election_priority_rank() -> anyhow::Result<()> {
+    // Learner 4 has the highest priority but it does not elect, thus it does not delay voters.
+    assert_eq!(0, eng(1).election_priority_rank());
+    assert_eq!(1, eng(2).election_priority_rank());
+    assert_eq!(2, eng(3).election_priority_rank());
+    assert_eq!(0, eng(4).election_priority_rank());
+
+    Ok(())
+}
+
+#[test]
+fn test_election_priority_rank_counts_distinct_priorities() -> anyhow::Result<()> {
+    let m = Membership::<u64>::with_nodes(vec![btreeset! {1,2,3}], btreemap! {
+        1 => Node::new("1").with_election_priority(10),
+        2 => Node::new("2").with_election_priority(10),
+        3 => Node::new("3"),
+    })?;
+
+    let mut eng = eng(3);
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m));
+
+    assert_eq!(1, eng.election_priority_rank());
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_vote_req_candidate_yields_to_higher_priority() -> anyhow::Result<()> {
+    let mut eng = candidate(2, 2);
+
+    let resp = eng.handle_vote_req(VoteRequest {
+        vote: Vote::new(2, 1),
+        last_log_id: Some(log_id(1, 1)),
+        pre_vote: false,
+        leader_transfer: false,
+    });
+
+    assert!(!resp.vote_granted, "it has voted for itself in this term");
+    assert_eq!(Vote::new(2, 2), eng.state.vote);
+
+    assert!(eng.state.internal_server_state.is_following());
+    assert_eq!(ServerState::Follower, eng.state.server_state);
+    assert_eq!(
+        vec![
+            Command::InstallElectionTimer { can_be_leader: true },
+            Command::UpdateServerState {
+                server_state: ServerState::Follower
+            },
+        ],
+        eng.commands
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_vote_req_candidate_does_not_yield() -> anyhow::Result<()> {
+    tracing::info!("--- lower priority candidate");
+    {
+        let mut eng = candidate(2, 2);
+
+        eng.handle_vote_req(VoteRequest {
+            vote: Vote::new(2, 3),
+            last_log_id: Some(log_id(1, 1)),
+            pre_vote: false,
+            leader_transfer: false,
+        });
+
+        assert!(eng.state.internal_server_state.is_leading());
+        assert_eq!(ServerState::Candidate, eng.state.server_state);
+        assert_eq!(0, eng.commands.len());
+    }
+
+    tracing::info!("--- higher priority candidate with smaller last log id");
+    {
+        let mut eng = candidate(2, 2);
+        eng.state.log_ids = LogIdList::new(vec![log_id(1, 1), log_id(2, 3)]);
+
+        eng.handle_vote_req(VoteRequest {
+            vote: Vote::new(2, 1),
+            last_log_id: Some(log_id(1, 1)),
+            pre_vote: false,
+            leader_transfer: false,
+        });
+
+        assert!(eng.state.internal_server_state.is_leading());
+        assert_eq!(0, eng.commands.len());
+    }
+
+    tracing::info!("--- higher priority candidate of a smaller term");
+    {
+        let mut eng = candidate(2, 3);
+
+        eng.handle_vote_req(VoteRequest {
+            vote: Vote::new(2, 1),
+            last_log_id: Some(log_id(1, 1)),
+            pre_vote: false,
+            leader_transfer: false,
+        });
+
+        assert!(eng.state.internal_server_state.is_leading());
+        assert_eq!(0, eng.commands.len());
+    }
+
+    tracing::info!("--- a leader does not yield");
+    {
+        let mut eng = candidate(2, 2);
+        eng.state.vote = Vote::new_committed(2, 2);
+        eng.state.server_state = ServerState::Leader;
+
+        eng.handle_vote_req(VoteRequest {
+            vote: Vote::new(2, 1),
+            last_log_id: Some(log_id(1, 1)),
+            pre_vote: false,
+            leader_transfer: false,
+        });
+
+        assert!(eng.state.internal_server_state.is_leading());
+        assert_eq!(0, eng.commands.len());
+    }
+
+    Ok(())
+}
@@ -1,19 +1,33 @@
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::sync::Arc;
+use std::time::Duration;
 
+use maplit::btreeset;
+
+use crate::async_runtime::Instant;
+use crate::config::CommitQuorum;
+use crate::config::SnapshotSource;
 use crate::core::ServerState;
 use crate::engine::Command;
 use crate::entry::RaftEntry;
 use crate::error::InitializeError;
+use crate::error::LogNotApplied;
 use crate::error::NotAMembershipEntry;
 use crate::error::NotAllowed;
 use crate::error::NotInMembers;
+use crate::error::PurgeLogsError;
 use crate::error::RejectVoteRequest;
+use crate::error::ReplicationLagging;
 use crate::internal_server_state::InternalServerState;
 use crate::membership::EffectiveMembership;
 use crate::membership::NodeRole;
+use crate::node::as_default_node;
 use crate::progress::Progress;
+use crate::progress::VecProgress;
+use crate::quorum::CommitQuorumSet;
 use crate::raft::AppendEntriesResponse;
+use crate::raft::TimeoutNowRequest;
 use crate::raft::VoteRequest;
 use crate::raft::VoteResponse;
 use crate::raft_state::RaftState;
@@ -24,7 +38,9 @@ use crate::LogIdOptionExt;
 use crate::Membership;
 use crate::MembershipState;
 use crate::MetricsChangeFlags;
+use crate::Node;
 use crate::NodeId;
+use crate::NodeInfo;
 use crate::Vote;
 
 /// Config for Engine
@@ -40,6 +56,22 @@ pub(crate) struct EngineConfig {
     /// whether to keep applied log that are not included in snapshots.
     /// false by default
     pub(crate) keep_unsnapshoted_log: bool,
+
+    /// The number of logs included in the latest snapshot to keep, if `keep_unsnapshoted_log` is set.
+    /// 0 by default
+    pub(crate) max_in_snapshot_log_to_keep: u64,
+
+    /// Whether to run a pre-vote phase before an election.
+    /// false by default
+    pub(crate) enable_prevote: bool,
+
+    /// Where a lagging target receives a snapshot from.
+    /// The leader by default
+    pub(crate) snapshot_source: SnapshotSource,
+
+    /// How many voters have to acknowledge a log before it is committed.
+    /// A majority by default
+    pub(crate) commit_quorum: CommitQuorum,
 }
 
 impl Default for EngineConfig {
@@ -48,6 +80,10 @@ impl Default for EngineConfig {
             max_applied_log_to_keep: 1000,
             purge_batch_size: 256,
             keep_unsnapshoted_log: false,
+            max_in_snapshot_log_to_keep: 0,
+            enable_prevote: false,
+            snapshot_source: SnapshotSource::Leader,
+            commit_quorum: CommitQuorum::Majority,
         }
     }
 }
@@ -61,9 +97,9 @@ impl Default for EngineConfig {
 /// This structure only contains necessary information to run raft algorithm,
 /// but none of the application specific data.
 /// TODO: make the fields private
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 #[derive(PartialEq, Eq)]
-pub(crate) struct Engine<NID: NodeId> {
+pub(crate) struct Engine<NID: NodeId, N: NodeInfo = Node, I: Instant = tokio::time::Instant> {
     /// TODO:
     #[allow(dead_code)]
     pub(crate) id: NID,
@@ -76,22 +112,36 @@ pub(crate) struct Engine<NID: NodeId> {
     pub(crate) snapshot_last_log_id: Option<LogId<NID>>,
 
     /// The state of this raft node.
-    pub(crate) state: RaftState<NID>,
+    pub(crate) state: RaftState<NID, N, I>,
+
+    /// The nodes that granted the ongoing pre-vote of this node, if it is in a pre-vote phase.
+    pub(crate) pre_vote_granted_by: Option<BTreeSet<NID>>,
+
+    /// Whether the last election started by this node went through a pre-vote phase.
+    pub(crate) last_election_pre_vote: bool,
+
+    /// The vote of the leader this node last accepted an AppendEntries from, and the time it was received.
+    ///
+    /// It is only used by a follower for a read with a freshness bound.
+    pub(crate) leader_contacted: Option<(Vote<NID>, I)>,
 
     /// Tracks what kind of metrics changed
     pub(crate) metrics_flags: MetricsChangeFlags,
 
     /// Command queue that need to be executed by `RaftRuntime`.
-    pub(crate) commands: Vec<Command<NID>>,
+    pub(crate) commands: Vec<Command<NID, N>>,
 }
 
-impl<NID: NodeId> Engine<NID> {
-    pub(crate) fn new(id: NID, init_state: &RaftState<NID>, config: EngineConfig) -> Self {
+impl<NID: NodeId, N: NodeInfo, I: Instant> Engine<NID, N, I> {
+    pub(crate) fn new(id: NID, init_state: &RaftState<NID, N, I>, config: EngineConfig) -> Self {
         Self {
             id,
             config,
             snapshot_last_log_id: None,
             state: init_state.clone(),
+            pre_vote_granted_by: None,
+            last_election_pre_vote: false,
+            leader_contacted: None,
             metrics_flags: MetricsChangeFlags::default(),
             commands: vec![],
         }
@@ -105,7 +155,10 @@ impl<NID: NodeId> Engine<NID> {
     /// Appending the very first log is slightly different from appending log by a leader or follower.
     /// This step is not confined by the consensus protocol and has to be dealt with differently.
     #[tracing::instrument(level = "debug", skip(self, entries))]
-    pub(crate) fn initialize<Ent: RaftEntry<NID>>(&mut self, entries: &mut [Ent]) -> Result<(), InitializeError<NID>> {
+    pub(crate) fn initialize<Ent: RaftEntry<NID, N>>(
+        &mut self,
+        entries: &mut [Ent],
+    ) -> Result<(), InitializeError<NID, N>> {
         let l = entries.len();
         debug_assert_eq!(1, l);
 
@@ -133,20 +186,118 @@ impl<NID: NodeId> Engine<NID> {
     }
 
     /// Start to elect this node as leader
+    ///
+    /// If pre-vote is enabled, it solicits pre-votes first, and the real election starts only when a quorum would
+    /// grant the vote.
+    ///
+    /// A witness never starts an election: it can not be the leader without the log payloads.
     #[tracing::instrument(level = "debug", skip(self))]
     pub(crate) fn elect(&mut self) {
-        self.handle_vote_change(&Vote::new(self.state.vote.term + 1, self.id)).unwrap();
+        if self.is_witness(&self.id) {
+            tracing::debug!("a witness does not elect");
+            return;
+        }
+
+        if self.config.enable_prevote {
+            self.pre_vote();
+        } else {
+            self.set_last_election_pre_vote(false);
+            self.elect_with_new_term(VoteRequest::new);
+        }
+    }
+
+    /// Handle a TimeoutNow request from the leader that is transferring its leadership to this node.
+    ///
+    /// It starts an election at once, without pre-vote, if the request is from the current leader and this node has
+    /// caught up with the leader's log. Returns whether the election is started.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) fn handle_timeout_now(&mut self, req: TimeoutNowRequest<NID>) -> bool {
+        tracing::debug!(req = display(req.summary()), "Engine::handle_timeout_now");
+
+        if req.vote != self.state.vote || !req.vote.committed {
+            tracing::debug!(
+                req = display(req.summary()),
+                my_vote = display(self.state.vote.summary()),
+                "reject TimeoutNow: not from the current leader"
+            );
+            return false;
+        }
+
+        if !self.is_voter() {
+            tracing::debug!(req = display(req.summary()), "reject TimeoutNow: not a voter");
+            return false;
+        }
+
+        if self.is_witness(&self.id) {
+            tracing::debug!(req = display(req.summary()), "reject TimeoutNow: a witness");
+            return false;
+        }
+
+        if self.state.last_log_id() < req.last_log_id {
+            tracing::debug!(
+                req = display(req.summary()),
+                my_last_log_id = display(self.state.last_log_id().summary()),
+                "reject TimeoutNow: log is lagging"
+            );
+            return false;
+        }
+
+        self.pre_vote_granted_by = None;
+        self.set_last_election_pre_vote(false);
+        self.elect_with_new_term(VoteRequest::new_leader_transfer);
+        true
+    }
+
+    /// Ask voters whether they would grant a vote for the next term, without increasing the term of this node.
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn pre_vote(&mut self) {
+        let granted = btreeset! {self.id.clone()};
+
+        // Fast-path: if there is only one node in the cluster.
+
+        if self.state.membership_state.effective.is_quorum(granted.iter()) {
+            self.pre_vote_granted_by = None;
+            self.set_last_election_pre_vote(true);
+            self.elect_with_new_term(VoteRequest::new);
+            return;
+        }
+
+        // Slow-path: send pre-vote request, let a quorum grant it.
+
+        self.pre_vote_granted_by = Some(granted);
+
+        self.push_command(Command::SendVote {
+            vote_req: VoteRequest::new_pre_vote(
+                Vote::new(self.state.vote.term + 1, self.id.clone()),
+                self.state.last_log_id(),
+            ),
+        });
+        self.push_command(Command::InstallElectionTimer { can_be_leader: true });
+    }
+
+    fn set_last_election_pre_vote(&mut self, pre_voted: bool) {
+        if self.last_election_pre_vote != pre_voted {
+            self.last_election_pre_vote = pre_voted;
+            self.metrics_flags.set_cluster_changed();
+        }
+    }
+
+    /// Increase the term and send vote requests built by `new_vote_req` to become the leader.
+    fn elect_with_new_term(&mut self, new_vote_req: fn(Vote<NID>, Option<LogId<NID>>) -> VoteRequest<NID>) {
+        self.handle_vote_change(&Vote::new(self.state.vote.term + 1, self.id.clone())).unwrap();
 
         // Safe unwrap()
         let leader = self.state.internal_server_state.leading_mut().unwrap();
-        leader.grant_vote_by(self.id);
+        leader.grant_vote_by(self.id.clone());
         let quorum_granted = leader.is_vote_granted();
 
         // Fast-path: if there is only one node in the cluster.
 
         if quorum_granted {
             self.state.vote.commit();
-            self.push_command(Command::SaveVote { vote: self.state.vote });
+            self.push_command(Command::SaveVote {
+                vote: self.state.vote.clone(),
+            });
 
             // TODO: For compatibility. remove it. The runtime does not need to know about server state.
             self.set_server_state(ServerState::Leader);
@@ -156,7 +307,7 @@ impl<NID: NodeId> Engine<NID> {
         // Slow-path: send vote request, let a quorum grant it.
 
         self.push_command(Command::SendVote {
-            vote_req: VoteRequest::new(self.state.vote, self.state.last_log_id()),
+            vote_req: new_vote_req(self.state.vote.clone(), self.state.last_log_id()),
         });
 
         // TODO: For compatibility. remove it. The runtime does not need to know about server state.
@@ -185,6 +336,16 @@ impl<NID: NodeId> Engine<NID> {
                 err = display(reject),
                 "reject vote request"
             );
+
+            if self.should_yield_election_to(&req) {
+                tracing::info!(
+                    req = display(req.summary()),
+                    "stop electing: a candidate with higher priority is campaigning"
+                );
+                // The election timer of this node is delayed by its priority rank,
+                // thus the higher priority candidate will elect first in the next round.
+                self.enter_following();
+            }
             false
         } else {
             true
@@ -193,21 +354,169 @@ impl<NID: NodeId> Engine<NID> {
         VoteResponse {
             // Return the updated vote, this way the candidate knows which vote is granted, in case the candidate's vote
             // is changed after sending the vote request.
-            vote: self.state.vote,
+            vote: self.state.vote.clone(),
             vote_granted,
             last_log_id: self.state.last_log_id(),
         }
     }
 
+    /// Whether this candidate should stop its election in favor of the candidate that sent `req`.
+    ///
+    /// It yields only to a candidate of the same term, with a higher election priority and a log at least as
+    /// up-to-date as its own.
+    fn should_yield_election_to(&self, req: &VoteRequest<NID>) -> bool {
+        let my_vote = &self.state.vote;
+
+        if my_vote.committed || !self.state.internal_server_state.is_leading() {
+            return false;
+        }
+
+        if req.vote.term != my_vote.term || req.last_log_id < self.state.last_log_id() {
+            return false;
+        }
+
+        self.election_priority(&req.vote.node_id) > self.election_priority(&self.id)
+    }
+
+    /// The election priority of a node in the effective membership. A node without priority has priority 0.
+    pub(crate) fn election_priority(&self, node_id: &NID) -> u64 {
+        let em = &self.state.membership_state.effective;
+        em.get_node(node_id)
+            .and_then(as_default_node)
+            .and_then(|n| n.election_priority())
+            .unwrap_or_default()
+    }
+
+    /// Whether a node is a witness voter in the effective membership. See
+    /// [`Membership::with_witness()`](`crate::Membership::with_witness`).
+    pub(crate) fn is_witness(&self, node_id: &NID) -> bool {
+        self.state.membership_state.effective.membership.is_witness(node_id)
+    }
+
+    /// The number of distinct election priorities among voters that are higher than that of this node.
+    ///
+    /// This node delays its election by `rank * election_timeout_max`, so that a node with higher priority elects
+    /// first.
+    pub(crate) fn election_priority_rank(&self) -> u64 {
+        let mine = self.election_priority(&self.id);
+
+        let higher = self
+            .state
+            .membership_state
+            .effective
+            .voter_ids()
+            .map(|id| self.election_priority(&id))
+            .filter(|p| *p > mine)
+            .collect::<BTreeSet<_>>();
+
+        higher.len() as u64
+    }
+
+    /// Handle a pre-vote request: tell the candidate whether its vote would be granted.
+    ///
+    /// Unlike a vote request, it does not change the vote of this node.
+    /// A pre-vote is rejected if this node is an established leader, or if it would reject the real vote.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) fn handle_pre_vote_req(&self, req: VoteRequest<NID>) -> VoteResponse<NID> {
+        tracing::debug!(req = display(req.summary()), "Engine::handle_pre_vote_req");
+        tracing::debug!(
+            my_vote = display(self.state.vote.summary()),
+            my_last_log_id = display(self.state.last_log_id().summary()),
+            "Engine::handle_pre_vote_req"
+        );
+
+        // A leader that stepped down keeps its vote, but is not an established leader any more.
+        let vote_granted = if self.is_leader() && self.state.internal_server_state.is_leading() {
+            tracing::debug!(req = display(req.summary()), "reject pre-vote request: I am the leader");
+            false
+        } else if req.vote < self.state.vote {
+            tracing::debug!(
+                req = display(req.summary()),
+                "reject pre-vote request by a greater vote"
+            );
+            false
+        } else if req.last_log_id < self.state.last_log_id() {
+            tracing::debug!(
+                req = display(req.summary()),
+                "reject pre-vote request by a greater last-log-id"
+            );
+            false
+        } else {
+            true
+        };
+
+        VoteResponse {
+            vote: self.state.vote.clone(),
+            vote_granted,
+            last_log_id: self.state.last_log_id(),
+        }
+    }
+
+    /// Handle a pre-vote response. When a quorum granted the pre-vote, the real election starts.
+    #[tracing::instrument(level = "debug", skip(self, resp))]
+    pub(crate) fn handle_pre_vote_resp(&mut self, target: NID, resp: VoteResponse<NID>) {
+        tracing::debug!(
+            resp = display(resp.summary()),
+            target = display(&target),
+            "handle_pre_vote_resp"
+        );
+
+        // If this node is no longer in a pre-vote phase, just ignore the delayed response.
+        let granted_by = match &mut self.pre_vote_granted_by {
+            Some(x) => x,
+            None => return,
+        };
+
+        if resp.vote_granted {
+            granted_by.insert(target);
+
+            if self.state.membership_state.effective.is_quorum(granted_by.iter()) {
+                tracing::debug!("quorum granted pre-vote");
+
+                self.pre_vote_granted_by = None;
+                self.set_last_election_pre_vote(true);
+                self.elect_with_new_term(VoteRequest::new);
+            }
+            return;
+        }
+
+        // pre-vote is rejected: give up this round, the term of this node is not changed.
+
+        self.pre_vote_granted_by = None;
+
+        // If peer's vote is greater than current vote, there is a newer term in the cluster.
+        if resp.vote > self.state.vote {
+            self.state.vote = resp.vote;
+            self.push_command(Command::SaveVote {
+                vote: self.state.vote.clone(),
+            });
+        }
+
+        // Seen a higher log.
+        if resp.last_log_id > self.state.last_log_id() {
+            self.push_command(Command::InstallElectionTimer { can_be_leader: false });
+        } else {
+            self.push_command(Command::InstallElectionTimer { can_be_leader: true });
+        }
+
+        // A candidate whose election timed out may be soliciting pre-votes.
+        if self.state.internal_server_state.is_following() {
+            return;
+        }
+
+        self.state.internal_server_state = InternalServerState::Following;
+        self.set_server_state(ServerState::Follower);
+    }
+
     #[tracing::instrument(level = "debug", skip(self, resp))]
     pub(crate) fn handle_vote_resp(&mut self, target: NID, resp: VoteResponse<NID>) {
         tracing::debug!(
             resp = display(resp.summary()),
-            target = display(target),
+            target = display(&target),
             "handle_vote_resp"
         );
         tracing::debug!(
-            my_vote = display(self.state.vote),
+            my_vote = display(&self.state.vote),
             my_last_log_id = display(self.state.last_log_id().summary()),
             "handle_vote_resp"
         );
@@ -234,7 +543,9 @@ impl<NID: NodeId> Engine<NID> {
                 // Openraft insists doing this because:
                 // - Voting is not in the hot path, thus no performance penalty.
                 // - Leadership won't be lost if a leader restarted quick enough.
-                self.push_command(Command::SaveVote { vote: self.state.vote });
+                self.push_command(Command::SaveVote {
+                    vote: self.state.vote.clone(),
+                });
 
                 self.set_server_state(ServerState::Leader);
             }
@@ -251,7 +562,9 @@ impl<NID: NodeId> Engine<NID> {
         // If peer's vote is greater than current vote, revert to follower state.
         if resp.vote > self.state.vote {
             self.state.vote = resp.vote;
-            self.push_command(Command::SaveVote { vote: self.state.vote });
+            self.push_command(Command::SaveVote {
+                vote: self.state.vote.clone(),
+            });
         }
 
         // Seen a higher log.
@@ -287,7 +600,7 @@ impl<NID: NodeId> Engine<NID> {
     /// TODO(xp): metrics flag needs to be dealt with.
     /// TODO(xp): if vote indicates this node is not the leader, refuse append
     #[tracing::instrument(level = "debug", skip(self, entries))]
-    pub(crate) fn leader_append_entries<'a, Ent: RaftEntry<NID> + 'a>(&mut self, entries: &mut [Ent]) {
+    pub(crate) fn leader_append_entries<'a, Ent: RaftEntry<NID, N> + 'a>(&mut self, entries: &mut [Ent]) {
         let l = entries.len();
         if l == 0 {
             return;
@@ -298,50 +611,18 @@ impl<NID: NodeId> Engine<NID> {
 
         self.push_command(Command::AppendInputEntries { range: 0..l });
 
-        // Fast commit:
-        // If the cluster has only one voter, then an entry will be committed as soon as it is appended.
-        // But if there is a membership log in the middle of the input entries, the condition to commit will change.
-        // Thus we have to deal with entries before and after a membership entry differently:
-        //
-        // When a membership entry is seen, update progress for all former entries.
-        // Then upgrade the quorum set for the Progress.
-        //
-        // E.g., if the input entries are `2..6`, entry 4 changes membership from `a` to `abc`.
-        // Then it will output a LeaderCommit command to commit entries `2,3`.
-        // ```text
-        // 1 2 3 4 5 6
-        // a x x a y y
-        //       b
-        //       c
-        // ```
-        //
-        // If the input entries are `2..6`, entry 4 changes membership from `abc` to `a`.
-        // Then it will output a LeaderCommit command to commit entries `2,3,4,5,6`.
-        // ```text
-        // 1 2 3 4 5 6
-        // a x x a y y
-        // b
-        // c
-        // ```
+        // The leader's own progress is not updated here: the entries are counted in the commit quorum only when the
+        // storage reports they are flushed, with `leader_log_flushed()`. By then the membership in the entries is
+        // effective, and the commit is decided by it, even for the entries before a membership entry.
         for entry in entries.iter() {
-            if let Some(_m) = entry.get_membership() {
-                let log_index = entry.get_log_id().index;
-
-                if log_index > 0 {
-                    if let Some(prev_log_id) = self.state.get_log_id(log_index - 1) {
-                        self.update_progress(self.id, Some(prev_log_id));
-                    }
-                }
-
+            if let Some(m) = entry.get_membership() {
                 // since this entry, the condition to commit has been changed.
-                self.update_effective_membership(entry.get_log_id(), _m);
+                self.update_effective_membership(entry.get_log_id(), m);
             }
         }
-        if let Some(last) = entries.last() {
-            self.update_progress(self.id, Some(*last.get_log_id()));
-        }
 
-        // Still need to replicate to learners, even when it is fast-committed.
+        // Replicate as soon as the entries are appended, without waiting for the local flush: the entries can be
+        // committed by a quorum of followers before the leader flushed them.
         self.push_command(Command::ReplicateInputEntries { range: 0..l });
         self.push_command(Command::MoveInputCursorBy { n: l });
     }
@@ -358,7 +639,7 @@ impl<NID: NodeId> Engine<NID> {
         leader_committed: Option<LogId<NID>>,
     ) -> AppendEntriesResponse<NID>
     where
-        Ent: RaftEntry<NID> + MessageSummary<Ent> + 'a,
+        Ent: RaftEntry<NID, N> + MessageSummary<Ent> + 'a,
     {
         tracing::debug!(
             vote = display(vote),
@@ -368,7 +649,7 @@ impl<NID: NodeId> Engine<NID> {
             "append-entries request"
         );
         tracing::debug!(
-            my_vote = display(self.state.vote),
+            my_vote = display(&self.state.vote),
             my_last_log_id = display(self.state.last_log_id().summary()),
             my_last_applied = display(self.state.last_applied.summary()),
             "local state"
@@ -414,8 +695,40 @@ impl<NID: NodeId> Engine<NID> {
         AppendEntriesResponse::Success
     }
 
+    /// Handle a heartbeat, an AppendEntries carrying only the vote and the committed log id of the leader.
+    ///
+    /// A heartbeat refreshes the leader lease, as any other AppendEntries does, but it does not touch the logs: there
+    /// is no `prev_log_id` to match. The local committed log id is updated only if the last local log is proposed by
+    /// this leader, in which case all the local logs upto it are the same as the leader's.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn handle_heartbeat_req(
+        &mut self,
+        vote: &Vote<NID>,
+        leader_committed: Option<LogId<NID>>,
+    ) -> AppendEntriesResponse<NID> {
+        tracing::debug!(
+            vote = display(vote),
+            leader_committed = display(leader_committed.summary()),
+            "heartbeat request"
+        );
+
+        let res = self.handle_vote_change(vote);
+        if let Err(rejected) = res {
+            return rejected.into();
+        }
+
+        let last = self.state.last_log_id();
+        let from_leader = last.as_ref().map(|x| x.leader_id == vote.leader_id()).unwrap_or(false);
+
+        if from_leader && vote.committed {
+            self.follower_update_committed(std::cmp::min(leader_committed, last));
+        }
+
+        AppendEntriesResponse::Success
+    }
+
     #[tracing::instrument(level = "debug", skip_all)]
-    pub(crate) fn follower_commit_entries<'a, Ent: RaftEntry<NID> + 'a>(
+    pub(crate) fn follower_commit_entries<'a, Ent: RaftEntry<NID, N> + 'a>(
         &mut self,
         leader_committed: Option<LogId<NID>>,
         prev_log_id: Option<LogId<NID>>,
@@ -427,10 +740,15 @@ impl<NID: NodeId> Engine<NID> {
         );
 
         // Committed index can not > last_log_id.index
-        let last = entries.last().map(|x| *x.get_log_id());
+        let last = entries.last().map(|x| x.get_log_id().clone());
         let last = std::cmp::max(last, prev_log_id);
         let committed = std::cmp::min(leader_committed, last);
 
+        self.follower_update_committed(committed);
+    }
+
+    /// Update the committed log id of a follower, which is known to be in the local logs, and apply logs upto it.
+    fn follower_update_committed(&mut self, committed: Option<LogId<NID>>) {
         tracing::debug!(committed = display(committed.summary()), "update committed");
 
         if let Some(prev_committed) = self.state.update_committed(&committed) {
@@ -451,7 +769,11 @@ impl<NID: NodeId> Engine<NID> {
     ///
     /// Membership config changes are also detected and applied here.
     #[tracing::instrument(level = "debug", skip(self, entries))]
-    pub(crate) fn follower_do_append_entries<'a, Ent: RaftEntry<NID> + 'a>(&mut self, entries: &[Ent], since: usize) {
+    pub(crate) fn follower_do_append_entries<'a, Ent: RaftEntry<NID, N> + 'a>(
+        &mut self,
+        entries: &[Ent],
+        since: usize,
+    ) {
         let l = entries.len();
         if since == l {
             return;
@@ -561,6 +883,9 @@ impl<NID: NodeId> Engine<NID> {
     ///
     /// `max_keep` specifies the number of applied logs to keep.
     /// `max_keep==0` means every applied log can be purged.
+    ///
+    /// If `keep_unsnapshoted_log` is set, the last `max_in_snapshot_log_to_keep` logs included in the snapshot are kept
+    /// too.
     #[tracing::instrument(level = "debug", skip_all)]
     pub(crate) fn calc_purge_upto(&mut self) -> Option<LogId<NID>> {
         let st = &self.state;
@@ -571,8 +896,12 @@ impl<NID: NodeId> Engine<NID> {
         let mut purge_end = last_applied.next_index().saturating_sub(max_keep);
 
         if self.config.keep_unsnapshoted_log {
-            let idx = self.snapshot_last_log_id.next_index();
-            tracing::debug!("the very last log included in snapshots: {}", idx);
+            let idx = self.snapshot_last_log_id.next_index().saturating_sub(self.config.max_in_snapshot_log_to_keep);
+            tracing::debug!(
+                max_in_snapshot_log_to_keep = self.config.max_in_snapshot_log_to_keep,
+                "the very last log included in snapshots that can be purged: {}",
+                idx
+            );
             purge_end = idx.min(purge_end);
         }
 
@@ -606,11 +935,117 @@ impl<NID: NodeId> Engine<NID> {
         log_id
     }
 
+    /// Pick a voter for `target` to fetch a snapshot from, instead of receiving the snapshot of this leader.
+    ///
+    /// A voter is a candidate only if [`SnapshotSource::Voters`] is configured and it has replicated `need`, i.e., the
+    /// last log id of the snapshot this leader would send. The one that has replicated the most logs is picked.
+    /// It returns None if this leader should send its own snapshot.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn pick_snapshot_source(&self, target: &NID, need: &LogId<NID>) -> Option<NID> {
+        if self.config.snapshot_source != SnapshotSource::Voters {
+            return None;
+        }
+
+        let leader = self.state.internal_server_state.leading()?;
+
+        let mut source: Option<(&NID, &Option<LogId<NID>>)> = None;
+
+        for (id, matched) in leader.progress.iter() {
+            if id == &self.id || id == target || leader.progress.is_voter(id) != Some(true) {
+                continue;
+            }
+
+            if matched.as_ref() < Some(need) {
+                continue;
+            }
+
+            if source.map_or(true, |(_, m)| matched > m) {
+                source = Some((id, matched));
+            }
+        }
+
+        source.map(|(id, _)| id.clone())
+    }
+
+    /// Calculate the log id up to which to purge, inclusive, for a purge requested by the application.
+    ///
+    /// It never purges a log that is not included in the last snapshot. Unless `force` is set, a leader also keeps
+    /// the logs that a replication target has not yet matched, so that it does not have to fall back to a snapshot.
+    /// It returns None if there is no log to purge.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn calc_trigger_purge_upto(&self, upto: u64, force: bool) -> Option<LogId<NID>> {
+        let st = &self.state;
+
+        let mut purge_end = std::cmp::min(upto.saturating_add(1), self.snapshot_last_log_id.next_index());
+
+        if !force {
+            if let Some(leader) = st.internal_server_state.leading() {
+                for (id, matched) in leader.progress.iter() {
+                    tracing::debug!("replication target {} needs logs since: {}", id, matched.next_index());
+                    purge_end = std::cmp::min(purge_end, matched.next_index());
+                }
+            }
+        }
+
+        tracing::debug!(
+            last_purged_log_id = display(st.last_purged_log_id().summary()),
+            "try purge: (-oo, {})",
+            purge_end
+        );
+
+        if purge_end <= st.last_purged_log_id().next_index() {
+            return None;
+        }
+
+        st.log_ids.get(purge_end - 1)
+    }
+
+    /// Check whether the logs upto `log_id`, inclusive, can be purged as requested by the application, and return the
+    /// log id to purge upto.
+    ///
+    /// The logs are purged by the index of `log_id`. Only applied logs can be purged, and a leader refuses to purge a
+    /// log that a replication target has not yet matched. It returns None if the logs are already purged.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn calc_purge_logs_upto(&self, log_id: &LogId<NID>) -> Result<Option<LogId<NID>>, PurgeLogsError<NID>> {
+        let st = &self.state;
+
+        if Some(log_id.index) > st.last_applied.index() {
+            return Err(LogNotApplied {
+                log_id: log_id.clone(),
+                last_applied: st.last_applied.clone(),
+            }
+            .into());
+        }
+
+        if Some(log_id.index) <= st.last_purged_log_id().index() {
+            return Ok(None);
+        }
+
+        if let Some(leader) = st.internal_server_state.leading() {
+            for (id, matched) in leader.progress.iter() {
+                if id == &self.id {
+                    continue;
+                }
+
+                if matched.index() < Some(log_id.index) {
+                    return Err(ReplicationLagging {
+                        log_id: log_id.clone(),
+                        target: id.clone(),
+                        matched: matched.clone(),
+                    }
+                    .into());
+                }
+            }
+        }
+
+        Ok(st.log_ids.get(log_id.index))
+    }
+
     /// Purge log entries upto `upto`, inclusive.
     #[tracing::instrument(level = "debug", skip(self))]
     pub(crate) fn purge_log(&mut self, upto: LogId<NID>) {
         let st = &mut self.state;
-        let log_id = Some(upto);
+        let log_id = Some(upto.clone());
 
         if log_id <= st.last_purged_log_id() {
             return;
@@ -623,7 +1058,7 @@ impl<NID: NodeId> Engine<NID> {
 
     /// Update membership state with a committed membership config
     #[tracing::instrument(level = "debug", skip_all)]
-    pub(crate) fn update_committed_membership(&mut self, membership: EffectiveMembership<NID>) {
+    pub(crate) fn update_committed_membership(&mut self, membership: EffectiveMembership<NID, N>) {
         tracing::debug!("update committed membership: {}", membership.summary());
 
         let server_state = self.calc_server_state();
@@ -659,14 +1094,14 @@ impl<NID: NodeId> Engine<NID> {
     }
 
     #[tracing::instrument(level = "debug", skip_all)]
-    pub(crate) fn update_effective_membership(&mut self, log_id: &LogId<NID>, m: &Membership<NID>) {
+    pub(crate) fn update_effective_membership(&mut self, log_id: &LogId<NID>, m: &Membership<NID, N>) {
         tracing::debug!("update effective membership: log_id:{} {}", log_id, m.summary());
 
         self.metrics_flags.set_cluster_changed();
 
         let server_state = self.calc_server_state();
 
-        let em = Arc::new(EffectiveMembership::new(Some(*log_id), m.clone()));
+        let em = Arc::new(EffectiveMembership::new(Some(log_id.clone()), m.clone()));
 
         self.state.membership_state.effective = em.clone();
 
@@ -678,29 +1113,32 @@ impl<NID: NodeId> Engine<NID> {
         if let Some(leader) = &mut self.state.internal_server_state.leading_mut() {
             let old_progress = leader.progress.clone();
 
-            let old_repls = old_progress.iter().copied().collect::<BTreeMap<_, _>>();
+            let old_repls = old_progress.iter().cloned().collect::<BTreeMap<_, _>>();
 
             let learner_ids = em.learner_ids().collect::<Vec<_>>();
 
-            leader.progress = old_progress.upgrade_quorum_set(em, &learner_ids);
+            let commit_quorum_set =
+                CommitQuorumSet::new(em.clone(), em.commit_required_ids(&self.config.commit_quorum));
+            leader.progress = old_progress.upgrade_quorum_set(commit_quorum_set, &learner_ids);
+            leader.clock_progress = leader.clock_progress.clone().upgrade_quorum_set(em, &learner_ids);
 
             // If it is leader, update replication to reflect membership change.
 
-            let new_repls = leader.progress.iter().copied().collect::<BTreeMap<_, _>>();
+            let new_repls = leader.progress.iter().cloned().collect::<BTreeMap<_, _>>();
 
             // TODO: test
             let mut add = vec![];
             let mut remove = vec![];
             for (node_id, matched) in new_repls.iter() {
                 if !old_repls.contains_key(node_id) {
-                    add.push((*node_id, *matched));
+                    add.push((node_id.clone(), matched.clone()));
                 }
             }
 
             for (node_id, matched) in old_repls.iter() {
                 // A leader that is removed will be shut down when this membership log is committed.
                 if !new_repls.contains_key(node_id) && node_id != &self.id {
-                    remove.push((*node_id, *matched));
+                    remove.push((node_id.clone(), matched.clone()));
                 }
             }
 
@@ -728,9 +1166,9 @@ impl<NID: NodeId> Engine<NID> {
 
             tracing::debug!(progress = debug(&leader.progress), "leader progress");
 
-            let res = leader.progress.update(&node_id, log_id);
+            let res = leader.progress.update(&node_id, log_id).map(|c| c.clone());
             match res {
-                Ok(c) => *c,
+                Ok(c) => c,
                 Err(_) => {
                     // TODO: leader should not append log if it is no longer in the membership.
                     //       There is a chance this will happen:
@@ -752,7 +1190,7 @@ impl<NID: NodeId> Engine<NID> {
         tracing::debug!(committed = debug(&committed), "committed after updating progress");
 
         // Only when the log id is proposed by current leader, it is committed.
-        if let Some(c) = committed {
+        if let Some(c) = &committed {
             if c.leader_id.term != self.state.vote.term || c.leader_id.node_id != self.state.vote.node_id {
                 return;
             }
@@ -760,16 +1198,127 @@ impl<NID: NodeId> Engine<NID> {
 
         if let Some(prev_committed) = self.state.update_committed(&committed) {
             self.push_command(Command::ReplicateCommitted {
-                committed: self.state.committed,
+                committed: self.state.committed.clone(),
             });
             self.push_command(Command::LeaderCommit {
                 since: prev_committed,
-                upto: self.state.committed.unwrap(),
+                upto: self.state.committed.clone().unwrap(),
             });
             self.purge_applied_log();
         }
     }
 
+    /// Count the leader's own log upto `log_id` in the commit quorum, after the local storage flushed it.
+    ///
+    /// A flush that is already counted, e.g., one reported after the leader is re-established, is ignored.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn leader_log_flushed(&mut self, log_id: Option<LogId<NID>>) {
+        let leader = match self.state.internal_server_state.leading() {
+            None => {
+                return;
+            }
+            Some(x) => x,
+        };
+
+        if leader.progress.is_voter(&self.id).is_some() && leader.progress.get(&self.id) >= &log_id {
+            return;
+        }
+
+        self.update_progress(self.id.clone(), log_id);
+    }
+
+    /// Record that this node accepted an AppendEntries, including a heartbeat, from the leader of `vote` at `time`.
+    ///
+    /// It is ignored if `vote` is not the committed vote of another node that this node has accepted, e.g., the
+    /// request is rejected by a higher vote.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn follower_leader_contacted(&mut self, vote: &Vote<NID>, time: I) {
+        if vote != &self.state.vote || !vote.committed || vote.node_id == self.id {
+            return;
+        }
+
+        if let Some((v, t)) = &self.leader_contacted {
+            // The time must be monotonic: requests from the same leader may be handled out of order.
+            if v == vote && t >= &time {
+                return;
+            }
+        }
+
+        self.leader_contacted = Some((vote.clone(), time));
+    }
+
+    /// The time elapsed since this node last heard from the leader it currently follows, as of `now`.
+    ///
+    /// It returns `None` if this node has not heard from the leader of its current vote, e.g., since it saw a new
+    /// vote.
+    pub(crate) fn leader_contact_elapsed(&self, now: I) -> Option<Duration> {
+        let (vote, time) = self.leader_contacted.as_ref()?;
+
+        if vote != &self.state.vote {
+            return None;
+        }
+
+        Some(now.saturating_duration_since(*time))
+    }
+
+    /// Update the time at which `node_id` acknowledged this leader.
+    ///
+    /// `time` is when the acknowledged request was sent by this leader. A leader always acknowledges itself, thus its
+    /// own time is updated too.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn update_leader_clock(&mut self, node_id: NID, time: I) {
+        let leader = match self.state.internal_server_state.leading_mut() {
+            None => {
+                return;
+            }
+            Some(x) => x,
+        };
+
+        let prev = leader.clock_progress.granted().clone();
+
+        for id in [&self.id, &node_id] {
+            // The time must be monotonic: acknowledgements from different replication tasks may arrive out of order.
+            if leader.clock_progress.is_voter(id).is_some() && leader.clock_progress.get(id) < &Some(time) {
+                let _ = leader.clock_progress.update(id, Some(time));
+            }
+        }
+
+        if leader.clock_progress.granted() != &prev {
+            self.metrics_flags.set_replication_changed();
+        }
+    }
+
+    /// Forget all acknowledgements of this leader, so that the read lease has to be re-established.
+    pub(crate) fn reset_leader_clock(&mut self) {
+        if let Some(leader) = self.state.internal_server_state.leading_mut() {
+            let em = self.state.membership_state.effective.clone();
+            leader.clock_progress = VecProgress::new(em.clone(), em.learner_ids());
+
+            self.metrics_flags.set_replication_changed();
+        }
+    }
+
+    /// Step down because this leader is not acknowledged by a quorum within the max election timeout, with
+    /// check-quorum.
+    ///
+    /// It keeps its vote but leaves the leader state, thus it grants the pre-vote of another candidate, and it may be
+    /// elected again when its own election timeout fires.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn leader_lost_quorum(&mut self) {
+        if !self.state.internal_server_state.is_leading() {
+            return;
+        }
+
+        self.enter_following();
+    }
+
+    /// The latest time at which a quorum acknowledged this leader.
+    ///
+    /// It returns `None` if this node is not a leader, or it has not yet been acknowledged by a quorum.
+    pub(crate) fn leader_quorum_acked(&self) -> Option<I> {
+        self.state.internal_server_state.leading().and_then(|l| *l.clock_progress.granted())
+    }
+
     // --- Draft API ---
 
     // // --- app API ---
@@ -786,14 +1335,14 @@ impl<NID: NodeId> Engine<NID> {
 }
 
 /// Supporting util
-impl<NID: NodeId> Engine<NID> {
+impl<NID: NodeId, N: NodeInfo, I: Instant> Engine<NID, N, I> {
     /// Enter leader state.
     ///
     /// Leader state has two phase: election phase and replication phase, similar to paxos phase-1 and phase-2
     pub(crate) fn enter_leading(&mut self) {
         debug_assert_eq!(self.state.vote.node_id, self.id);
 
-        self.state.new_leader();
+        self.state.new_leader(&self.config.commit_quorum);
         // TODO: install heartbeat timer
     }
 
@@ -810,7 +1359,7 @@ impl<NID: NodeId> Engine<NID> {
 
         let vote = &self.state.vote;
 
-        if vote.committed {
+        if vote.committed && vote.node_id != self.id {
             // There is an active leader.
             // Do not elect for a longer while.
             // TODO: Installing a timer should not be part of the Engine's job.
@@ -819,7 +1368,7 @@ impl<NID: NodeId> Engine<NID> {
             // TODO: remove this when heartbeat log is ready.
             self.push_command(Command::RejectElection {});
         } else {
-            // There is an active candidate.
+            // There is an active candidate, or this leader stepped down and there is no active leader.
             // Do not elect for a short while.
             self.push_command(Command::InstallElectionTimer { can_be_leader: true });
         }
@@ -838,7 +1387,7 @@ impl<NID: NodeId> Engine<NID> {
     }
 
     /// Update effective membership config if encountering a membership config log entry.
-    fn try_update_membership<Ent: RaftEntry<NID>>(&mut self, entry: &Ent) {
+    fn try_update_membership<Ent: RaftEntry<NID, N>>(&mut self, entry: &Ent) {
         if let Some(m) = entry.get_membership() {
             self.update_effective_membership(entry.get_log_id(), m);
         }
@@ -846,7 +1395,7 @@ impl<NID: NodeId> Engine<NID> {
 
     /// Update membership state if membership config entries are found.
     #[allow(dead_code)]
-    fn follower_update_membership<'a, Ent: RaftEntry<NID> + 'a>(
+    fn follower_update_membership<'a, Ent: RaftEntry<NID, N> + 'a>(
         &mut self,
         entries: impl DoubleEndedIterator<Item = &'a Ent>,
     ) {
@@ -880,15 +1429,15 @@ impl<NID: NodeId> Engine<NID> {
     /// when conflicting logs are found.
     ///
     /// See: [Effective-membership](https://datafuselabs.github.io/openraft/effective-membership.html)
-    fn last_two_memberships<'a, Ent: RaftEntry<NID> + 'a>(
+    fn last_two_memberships<'a, Ent: RaftEntry<NID, N> + 'a>(
         entries: impl DoubleEndedIterator<Item = &'a Ent>,
-    ) -> Vec<EffectiveMembership<NID>> {
+    ) -> Vec<EffectiveMembership<NID, N>> {
         let mut memberships = vec![];
 
         // Find the last 2 membership config entries: the committed and the effective.
         for ent in entries.rev() {
             if let Some(m) = ent.get_membership() {
-                memberships.insert(0, EffectiveMembership::new(Some(*ent.get_log_id()), m.clone()));
+                memberships.insert(0, EffectiveMembership::new(Some(ent.get_log_id().clone()), m.clone()));
                 if memberships.len() == 2 {
                     break;
                 }
@@ -901,7 +1450,7 @@ impl<NID: NodeId> Engine<NID> {
     /// Update membership state with the last 2 membership configs found in new log entries
     ///
     /// Return if new membership config is found
-    fn update_membership_state(&mut self, memberships: Vec<EffectiveMembership<NID>>) {
+    fn update_membership_state(&mut self, memberships: Vec<EffectiveMembership<NID, N>>) {
         debug_assert!(self.state.membership_state.effective.log_id < memberships[0].log_id);
 
         let new_mem_state = if memberships.len() == 1 {
@@ -933,7 +1482,7 @@ impl<NID: NodeId> Engine<NID> {
     }
 
     fn set_server_state(&mut self, server_state: ServerState) {
-        tracing::debug!(id = display(self.id), ?server_state, "set_server_state");
+        tracing::debug!(id = display(&self.id), ?server_state, "set_server_state");
 
         // TODO: the caller should be very sure about what server-state to set.
         //       The following condition check is copied from old code,
@@ -954,10 +1503,10 @@ impl<NID: NodeId> Engine<NID> {
 
     /// Check if a raft node is in a state that allows to initialize.
     ///
-    /// It is allowed to initialize only when `last_log_id.is_none()` and `vote==(term=0, node_id=0)`.
+    /// It is allowed to initialize only when `last_log_id.is_none()` and `vote==(term=0, node_id=self.id)`.
     /// See: [Conditions for initialization](https://datafuselabs.github.io/openraft/cluster-formation.html#conditions-for-initialization)
     fn check_initialize(&self) -> Result<(), NotAllowed<NID>> {
-        if self.state.last_log_id().is_none() && self.state.vote == Vote::default() {
+        if self.state.last_log_id().is_none() && self.state.vote == Vote::new(0, self.id.clone()) {
             return Ok(());
         }
 
@@ -965,15 +1514,15 @@ impl<NID: NodeId> Engine<NID> {
 
         Err(NotAllowed {
             last_log_id: self.state.last_log_id(),
-            vote: self.state.vote,
+            vote: self.state.vote.clone(),
         })
     }
 
     /// When initialize, the node that accept initialize request has to be a member of the initial config.
-    fn check_members_contain_me(&self, m: &Membership<NID>) -> Result<(), NotInMembers<NID>> {
+    fn check_members_contain_me(&self, m: &Membership<NID, N>) -> Result<(), NotInMembers<NID, N>> {
         if !m.is_voter(&self.id) {
             let e = NotInMembers {
-                node_id: self.id,
+                node_id: self.id.clone(),
                 membership: m.clone(),
             };
             Err(e)
@@ -1006,7 +1555,7 @@ impl<NID: NodeId> Engine<NID> {
         l
     }
 
-    fn assign_log_ids<'a, Ent: RaftEntry<NID> + 'a>(&mut self, entries: impl Iterator<Item = &'a mut Ent>) {
+    fn assign_log_ids<'a, Ent: RaftEntry<NID, N> + 'a>(&mut self, entries: impl Iterator<Item = &'a mut Ent>) {
         let mut log_id = LogId::new(self.state.vote.leader_id(), self.state.last_log_id().next_index());
         for entry in entries {
             entry.set_log_id(&log_id);
@@ -1027,16 +1576,19 @@ impl<NID: NodeId> Engine<NID> {
         if vote >= &self.state.vote {
             // Ok
         } else {
-            return Err(RejectVoteRequest::ByVote(self.state.vote));
+            return Err(RejectVoteRequest::ByVote(self.state.vote.clone()));
         }
 
         tracing::debug!(%vote, "grant vote" );
 
+        // A message from a legal leader or candidate ends the pre-vote phase of this node.
+        self.pre_vote_granted_by = None;
+
         // Grant the vote
 
         if vote > &self.state.vote {
-            self.state.vote = *vote;
-            self.push_command(Command::SaveVote { vote: *vote });
+            self.state.vote = vote.clone();
+            self.push_command(Command::SaveVote { vote: vote.clone() });
         }
 
         if self.state.vote.node_id == self.id {
@@ -1083,7 +1635,7 @@ impl<NID: NodeId> Engine<NID> {
         self.state.vote.node_id == self.id && self.state.vote.committed
     }
 
-    fn push_command(&mut self, cmd: Command<NID>) {
+    fn push_command(&mut self, cmd: Command<NID, N>) {
         cmd.update_metrics_flags(&mut self.metrics_flags);
         self.commands.push(cmd)
     }
@@ -4,6 +4,7 @@ use maplit::btreeset;
 
 use crate::engine::Command;
 use crate::engine::Engine;
+use crate::engine::EngineConfig;
 use crate::engine::LogIdList;
 use crate::EffectiveMembership;
 use crate::Entry;
@@ -13,6 +14,7 @@ use crate::LogId;
 use crate::Membership;
 use crate::MembershipState;
 use crate::MetricsChangeFlags;
+use crate::RaftState;
 
 crate::declare_raft_types!(
     pub(crate) Foo: D=(), R=(), NodeId=u64
@@ -29,6 +31,7 @@ fn blank(term: u64, index: u64) -> Entry<Foo> {
     Entry {
         log_id: log_id(term, index),
         payload: EntryPayload::<Foo>::Blank,
+        context: None,
     }
 }
 
@@ -41,7 +44,7 @@ fn m23() -> Membership<u64> {
 }
 
 fn eng() -> Engine<u64> {
-    let mut eng = Engine::<u64>::default();
+    let mut eng = Engine::<u64>::new(0, &RaftState::new(0), EngineConfig::default());
     eng.state.committed = Some(log_id(1, 1));
     eng.state.membership_state.committed = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m01()));
     eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m23()));
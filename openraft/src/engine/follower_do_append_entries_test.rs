@@ -5,6 +5,7 @@ use maplit::btreeset;
 use crate::core::ServerState;
 use crate::engine::Command;
 use crate::engine::Engine;
+use crate::engine::EngineConfig;
 use crate::EffectiveMembership;
 use crate::Entry;
 use crate::EntryPayload;
@@ -13,6 +14,7 @@ use crate::LogId;
 use crate::Membership;
 use crate::MembershipState;
 use crate::MetricsChangeFlags;
+use crate::RaftState;
 
 crate::declare_raft_types!(
     pub(crate) Foo: D=(), R=(), NodeId=u64
@@ -29,6 +31,7 @@ fn blank(term: u64, index: u64) -> Entry<Foo> {
     Entry {
         log_id: log_id(term, index),
         payload: EntryPayload::<Foo>::Blank,
+        context: None,
     }
 }
 
@@ -49,10 +52,8 @@ fn m45() -> Membership<u64> {
 }
 
 fn eng() -> Engine<u64> {
-    let mut eng = Engine::<u64> {
-        id: 2, // make it a member
-        ..Default::default()
-    };
+    // make it a member
+    let mut eng = Engine::<u64>::new(2, &RaftState::new(2), EngineConfig::default());
     eng.state.server_state = ServerState::Candidate;
     eng.state.log_ids.append(log_id(1, 1));
     eng.state.log_ids.append(log_id(2, 3));
@@ -164,6 +165,7 @@ fn test_follower_do_append_entries_one_membership_entry() -> anyhow::Result<()>
             Entry {
                 log_id: log_id(3, 5),
                 payload: EntryPayload::<Foo>::Membership(m34()),
+                context: None,
             },
         ],
         3,
@@ -231,19 +233,23 @@ fn test_follower_do_append_entries_three_membership_entries() -> anyhow::Result<
             Entry {
                 log_id: log_id(3, 4),
                 payload: EntryPayload::<Foo>::Membership(m01()),
+                context: None,
             }, // ignored
             blank(3, 4),
             Entry {
                 log_id: log_id(3, 5),
                 payload: EntryPayload::<Foo>::Membership(m01()),
+                context: None,
             },
             Entry {
                 log_id: log_id(4, 6),
                 payload: EntryPayload::<Foo>::Membership(m34()),
+                context: None,
             },
             Entry {
                 log_id: log_id(4, 7),
                 payload: EntryPayload::<Foo>::Membership(m45()),
+                context: None,
             },
         ],
         1,
@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::engine::Engine;
+use crate::engine::EngineConfig;
+use crate::RaftState;
+use crate::Vote;
+
+fn eng() -> Engine<u64> {
+    let mut eng = Engine::<u64>::new(1, &RaftState::new(1), EngineConfig::default());
+    eng.state.vote = Vote::new_committed(2, 2);
+    eng
+}
+
+#[test]
+fn test_follower_leader_contacted() -> anyhow::Result<()> {
+    let t0 = Instant::now();
+    let t1 = t0 + Duration::from_millis(10);
+
+    let mut eng = eng();
+    assert_eq!(None, eng.leader_contact_elapsed(t0), "never contacted");
+
+    eng.follower_leader_contacted(&Vote::new_committed(2, 2), t1);
+    assert_eq!(Some((Vote::new_committed(2, 2), t1)), eng.leader_contacted);
+    assert_eq!(
+        Some(Duration::from_millis(5)),
+        eng.leader_contact_elapsed(t1 + Duration::from_millis(5))
+    );
+    assert_eq!(
+        Some(Duration::from_millis(0)),
+        eng.leader_contact_elapsed(t0),
+        "now before contact"
+    );
+
+    eng.follower_leader_contacted(&Vote::new_committed(2, 2), t0);
+    assert_eq!(
+        Some((Vote::new_committed(2, 2), t1)),
+        eng.leader_contacted,
+        "time does not go back"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_follower_leader_contacted_ignored() -> anyhow::Result<()> {
+    let t0 = Instant::now();
+
+    let mut eng = eng();
+
+    eng.follower_leader_contacted(&Vote::new_committed(1, 2), t0);
+    assert_eq!(None, eng.leader_contacted, "not the vote of this node");
+
+    eng.follower_leader_contacted(&Vote::new(2, 2), t0);
+    assert_eq!(None, eng.leader_contacted, "vote is not committed");
+
+    eng.state.vote = Vote::new_committed(2, 1);
+    eng.follower_leader_contacted(&Vote::new_committed(2, 1), t0);
+    assert_eq!(None, eng.leader_contacted, "this node is the leader");
+
+    Ok(())
+}
+
+#[test]
+fn test_leader_contact_elapsed_vote_changed() -> anyhow::Result<()> {
+    let t0 = Instant::now();
+
+    let mut eng = eng();
+    eng.follower_leader_contacted(&Vote::new_committed(2, 2), t0);
+    assert_eq!(Some(Duration::from_millis(0)), eng.leader_contact_elapsed(t0));
+
+    eng.state.vote = Vote::new(3, 3);
+    assert_eq!(
+        None,
+        eng.leader_contact_elapsed(t0),
+        "the contacted leader is no longer followed"
+    );
+
+    Ok(())
+}
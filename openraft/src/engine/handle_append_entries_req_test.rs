@@ -4,8 +4,11 @@ use maplit::btreeset;
 use pretty_assertions::assert_eq;
 
 use crate::core::ServerState;
+use crate::engine::testing::sid;
+use crate::engine::testing::StringConfig;
 use crate::engine::Command;
 use crate::engine::Engine;
+use crate::engine::EngineConfig;
 use crate::raft::AppendEntriesResponse;
 use crate::EffectiveMembership;
 use crate::Entry;
@@ -15,6 +18,7 @@ use crate::LogId;
 use crate::Membership;
 use crate::MembershipState;
 use crate::MetricsChangeFlags;
+use crate::RaftState;
 use crate::Vote;
 
 crate::declare_raft_types!(
@@ -32,6 +36,7 @@ fn blank(term: u64, index: u64) -> Entry<Foo> {
     Entry {
         log_id: log_id(term, index),
         payload: EntryPayload::<Foo>::Blank,
+        context: None,
     }
 }
 
@@ -48,10 +53,8 @@ fn m34() -> Membership<u64> {
 }
 
 fn eng() -> Engine<u64> {
-    let mut eng = Engine::<u64> {
-        id: 2, // make it a member
-        ..Default::default()
-    };
+    // make it a member
+    let mut eng = Engine::<u64>::new(2, &RaftState::new(2), EngineConfig::default());
     eng.state.last_applied = Some(log_id(0, 0));
     eng.state.vote = Vote::new(2, 1);
     eng.state.server_state = ServerState::Candidate;
@@ -300,6 +303,7 @@ fn test_handle_append_entries_req_entries_conflict() -> anyhow::Result<()> {
         &[blank(1, 2), Entry {
             log_id: log_id(3, 3),
             payload: EntryPayload::Membership(m34()),
+            context: None,
         }],
         Some(log_id(4, 4)),
     );
@@ -361,3 +365,83 @@ fn test_handle_append_entries_req_entries_conflict() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_handle_append_entries_req_string_node_id() -> anyhow::Result<()> {
+    // A node id that is not `Copy` works just like `u64`.
+    // The second entry in entries conflicts and replaces the effective membership, without this node.
+
+    let log_id = |term, index| LogId::<String> {
+        leader_id: LeaderId { term, node_id: sid(1) },
+        index,
+    };
+    let blank = |term, index| Entry::<StringConfig> {
+        log_id: log_id(term, index),
+        payload: EntryPayload::Blank,
+        context: None,
+    };
+    let m = |ids: &[u64]| Membership::<String>::new(vec![ids.iter().map(|x| sid(*x)).collect()], None);
+
+    let mut eng = Engine::<String>::new(sid(2), &RaftState::new(sid(2)), EngineConfig::default());
+    eng.state.last_applied = Some(log_id(0, 0));
+    eng.state.vote = Vote::new(2, sid(1));
+    eng.state.server_state = ServerState::Candidate;
+    eng.state.log_ids.append(log_id(1, 1));
+    eng.state.log_ids.append(log_id(2, 3));
+    eng.state.committed = Some(log_id(0, 0));
+    eng.state.membership_state.committed = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m(&[0, 1])));
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m(&[2, 3])));
+
+    let resp = eng.handle_append_entries_req(
+        &Vote::new_committed(2, sid(1)),
+        Some(log_id(1, 1)),
+        &[blank(1, 2), Entry {
+            log_id: log_id(3, 3),
+            payload: EntryPayload::Membership(m(&[3, 4])),
+            context: None,
+        }],
+        Some(log_id(4, 4)),
+    );
+
+    assert_eq!(AppendEntriesResponse::Success, resp);
+    assert_eq!(&[log_id(1, 1), log_id(3, 3)], eng.state.log_ids.key_log_ids());
+    assert_eq!(Vote::new_committed(2, sid(1)), eng.state.vote);
+    assert_eq!(Some(log_id(3, 3)), eng.state.committed);
+    assert_eq!(
+        MembershipState {
+            committed: Arc::new(EffectiveMembership::new(Some(log_id(3, 3)), m(&[3, 4]))),
+            effective: Arc::new(EffectiveMembership::new(Some(log_id(3, 3)), m(&[3, 4])))
+        },
+        eng.state.membership_state
+    );
+    assert_eq!(ServerState::Learner, eng.state.server_state);
+
+    assert_eq!(
+        vec![
+            Command::SaveVote {
+                vote: Vote::new_committed(2, sid(1))
+            },
+            Command::InstallElectionTimer { can_be_leader: false },
+            Command::RejectElection {},
+            Command::DeleteConflictLog { since: log_id(2, 3) },
+            Command::UpdateMembership {
+                membership: Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m(&[0, 1])))
+            },
+            Command::UpdateServerState {
+                server_state: ServerState::Learner
+            },
+            Command::AppendInputEntries { range: 1..2 },
+            Command::UpdateMembership {
+                membership: Arc::new(EffectiveMembership::new(Some(log_id(3, 3)), m(&[3, 4])))
+            },
+            Command::MoveInputCursorBy { n: 2 },
+            Command::FollowerCommit {
+                since: Some(log_id(0, 0)),
+                upto: log_id(3, 3)
+            },
+        ],
+        eng.commands
+    );
+
+    Ok(())
+}
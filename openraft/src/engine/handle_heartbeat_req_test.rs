@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+use pretty_assertions::assert_eq;
+
+use crate::core::ServerState;
+use crate::engine::Command;
+use crate::engine::Engine;
+use crate::engine::EngineConfig;
+use crate::raft::AppendEntriesResponse;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::RaftState;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn m01() -> Membership<u64> {
+    Membership::<u64>::new(vec![btreeset! {0,1}], None)
+}
+
+fn m23() -> Membership<u64> {
+    Membership::<u64>::new(vec![btreeset! {2,3}], None)
+}
+
+fn eng() -> Engine<u64> {
+    // make it a member
+    let mut eng = Engine::<u64>::new(2, &RaftState::new(2), EngineConfig::default());
+    eng.state.last_applied = Some(log_id(0, 0));
+    eng.state.vote = Vote::new(1, 2);
+    eng.state.server_state = ServerState::Candidate;
+    eng.state.log_ids.append(log_id(1, 1));
+    eng.state.log_ids.append(log_id(2, 3));
+    eng.state.committed = Some(log_id(0, 0));
+    eng.state.membership_state.committed = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m01()));
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m23()));
+    eng.enter_leading();
+    eng
+}
+
+#[test]
+fn test_handle_heartbeat_req_vote_is_rejected() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    let resp = eng.handle_heartbeat_req(&Vote::new(1, 1), Some(log_id(2, 3)));
+
+    assert_eq!(AppendEntriesResponse::HigherVote(Vote::new(1, 2)), resp);
+    assert_eq!(Vote::new(1, 2), eng.state.vote);
+    assert_eq!(Some(log_id(0, 0)), eng.state.committed);
+    assert_eq!(ServerState::Candidate, eng.state.server_state);
+
+    assert_eq!(0, eng.commands.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_heartbeat_req_last_log_from_leader() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    let resp = eng.handle_heartbeat_req(&Vote::new_committed(2, 1), Some(log_id(2, 3)));
+
+    assert_eq!(AppendEntriesResponse::Success, resp);
+    assert_eq!(
+        &[
+            log_id(1, 1), //
+            log_id(2, 3), //
+        ],
+        eng.state.log_ids.key_log_ids(),
+        "logs are not touched"
+    );
+    assert_eq!(Vote::new_committed(2, 1), eng.state.vote);
+    assert_eq!(Some(log_id(2, 3)), eng.state.committed);
+    assert_eq!(ServerState::Follower, eng.state.server_state);
+
+    assert_eq!(
+        vec![
+            Command::SaveVote {
+                vote: Vote::new_committed(2, 1)
+            },
+            Command::InstallElectionTimer { can_be_leader: false },
+            Command::RejectElection {},
+            Command::UpdateServerState {
+                server_state: ServerState::Follower
+            },
+            Command::FollowerCommit {
+                since: Some(log_id(0, 0)),
+                upto: log_id(2, 3)
+            },
+        ],
+        eng.commands
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_heartbeat_req_last_log_not_from_leader() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    // The last log is proposed by a former leader, it may not be in the logs of the new leader.
+    let resp = eng.handle_heartbeat_req(&Vote::new_committed(3, 1), Some(log_id(3, 4)));
+
+    assert_eq!(AppendEntriesResponse::Success, resp);
+    assert_eq!(Vote::new_committed(3, 1), eng.state.vote);
+    assert_eq!(Some(log_id(0, 0)), eng.state.committed);
+    assert_eq!(ServerState::Follower, eng.state.server_state);
+
+    assert_eq!(
+        vec![
+            Command::SaveVote {
+                vote: Vote::new_committed(3, 1)
+            },
+            Command::InstallElectionTimer { can_be_leader: false },
+            Command::RejectElection {},
+            Command::UpdateServerState {
+                server_state: ServerState::Follower
+            },
+        ],
+        eng.commands
+    );
+
+    Ok(())
+}
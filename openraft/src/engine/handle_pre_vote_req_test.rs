@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+use pretty_assertions::assert_eq;
+
+use crate::core::ServerState;
+use crate::engine::Engine;
+use crate::engine::EngineConfig;
+use crate::engine::LogIdList;
+use crate::raft::VoteRequest;
+use crate::raft::VoteResponse;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::RaftState;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn m012() -> Membership<u64> {
+    Membership::<u64>::new(vec![btreeset! {0,1,2}], None)
+}
+
+fn eng() -> Engine<u64> {
+    let mut eng = Engine::<u64>::new(0, &RaftState::new(0), EngineConfig::default());
+    eng.state.vote = Vote::new_committed(2, 1);
+    eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m012()));
+    eng
+}
+
+#[test]
+fn test_handle_pre_vote_req_reject_smaller_vote() -> anyhow::Result<()> {
+    let eng = eng();
+
+    let resp = eng.handle_pre_vote_req(VoteRequest::new_pre_vote(Vote::new(1, 2), Some(log_id(2, 3))));
+
+    assert_eq!(
+        VoteResponse {
+            vote: Vote::new_committed(2, 1),
+            vote_granted: false,
+            last_log_id: Some(log_id(2, 3)),
+        },
+        resp
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_pre_vote_req_reject_smaller_last_log_id() -> anyhow::Result<()> {
+    let eng = eng();
+
+    let resp = eng.handle_pre_vote_req(VoteRequest::new_pre_vote(Vote::new(3, 2), Some(log_id(2, 2))));
+
+    assert_eq!(
+        VoteResponse {
+            vote: Vote::new_committed(2, 1),
+            vote_granted: false,
+            last_log_id: Some(log_id(2, 3)),
+        },
+        resp
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_pre_vote_req_reject_by_leader() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.state.vote = Vote::new_committed(2, 0);
+    eng.state.new_leader(&eng.config.commit_quorum);
+    eng.state.server_state = ServerState::Leader;
+
+    let resp = eng.handle_pre_vote_req(VoteRequest::new_pre_vote(Vote::new(3, 2), Some(log_id(2, 3))));
+
+    assert_eq!(
+        VoteResponse {
+            vote: Vote::new_committed(2, 0),
+            vote_granted: false,
+            last_log_id: Some(log_id(2, 3)),
+        },
+        resp
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_pre_vote_req_granted_without_changing_vote() -> anyhow::Result<()> {
+    let eng = eng();
+
+    let resp = eng.handle_pre_vote_req(VoteRequest::new_pre_vote(Vote::new(3, 2), Some(log_id(2, 3))));
+
+    assert_eq!(
+        VoteResponse {
+            vote: Vote::new_committed(2, 1),
+            vote_granted: true,
+            last_log_id: Some(log_id(2, 3)),
+        },
+        resp
+    );
+
+    assert_eq!(Vote::new_committed(2, 1), eng.state.vote);
+    assert!(eng.state.internal_server_state.is_following());
+    assert_eq!(0, eng.commands.len());
+
+    Ok(())
+}
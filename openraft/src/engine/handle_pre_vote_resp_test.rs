@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+use pretty_assertions::assert_eq;
+
+use crate::core::ServerState;
+use crate::engine::Command;
+use crate::engine::Engine;
+use crate::engine::EngineConfig;
+use crate::engine::LogIdList;
+use crate::raft::VoteRequest;
+use crate::raft::VoteResponse;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::MetricsChangeFlags;
+use crate::RaftState;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn m123() -> Membership<u64> {
+    Membership::<u64>::new(vec![btreeset! {1,2,3}], None)
+}
+
+fn eng() -> Engine<u64> {
+    let mut eng = Engine::<u64>::new(1, &RaftState::new(1), EngineConfig {
+        enable_prevote: true,
+        ..Default::default()
+    });
+    eng.state.vote = Vote::new(2, 1);
+    eng.state.log_ids = LogIdList::new(vec![log_id(2, 2)]);
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m123()));
+    eng
+}
+
+#[test]
+fn test_handle_pre_vote_resp() -> anyhow::Result<()> {
+    tracing::info!("--- not in pre-vote. just ignore");
+    {
+        let mut eng = eng();
+
+        eng.handle_pre_vote_resp(2, VoteResponse {
+            vote: Vote::new(2, 1),
+            vote_granted: true,
+            last_log_id: Some(log_id(2, 2)),
+        });
+
+        assert_eq!(Vote::new(2, 1), eng.state.vote);
+        assert_eq!(None, eng.pre_vote_granted_by);
+        assert_eq!(0, eng.commands.len());
+    }
+
+    tracing::info!("--- quorum granted pre-vote: start a real election");
+    {
+        let mut eng = eng();
+        eng.pre_vote_granted_by = Some(btreeset! {1});
+
+        eng.handle_pre_vote_resp(2, VoteResponse {
+            vote: Vote::new(2, 1),
+            vote_granted: true,
+            last_log_id: Some(log_id(2, 2)),
+        });
+
+        assert_eq!(Vote::new(3, 1), eng.state.vote);
+        assert_eq!(None, eng.pre_vote_granted_by);
+        assert!(eng.last_election_pre_vote);
+        assert_eq!(
+            Some(btreeset! {1},),
+            eng.state.internal_server_state.leading().map(|x| x.vote_granted_by.clone())
+        );
+
+        assert_eq!(ServerState::Candidate, eng.state.server_state);
+        assert_eq!(
+            vec![
+                Command::SaveVote { vote: Vote::new(3, 1) },
+                Command::SendVote {
+                    vote_req: VoteRequest::new(Vote::new(3, 1), Some(log_id(2, 2)))
+                },
+                Command::UpdateServerState {
+                    server_state: ServerState::Candidate
+                },
+                Command::InstallElectionTimer { can_be_leader: true },
+            ],
+            eng.commands
+        );
+    }
+
+    tracing::info!("--- pre-vote rejected by a higher vote: update vote, do not elect");
+    {
+        let mut eng = eng();
+        eng.pre_vote_granted_by = Some(btreeset! {1});
+
+        eng.handle_pre_vote_resp(2, VoteResponse {
+            vote: Vote::new_committed(3, 3),
+            vote_granted: false,
+            last_log_id: Some(log_id(2, 2)),
+        });
+
+        assert_eq!(Vote::new_committed(3, 3), eng.state.vote);
+        assert_eq!(None, eng.pre_vote_granted_by);
+        assert!(!eng.last_election_pre_vote);
+        assert!(eng.state.internal_server_state.is_following());
+
+        assert_eq!(ServerState::Follower, eng.state.server_state);
+        assert_eq!(
+            MetricsChangeFlags {
+                leader: false,
+                other_metrics: true
+            },
+            eng.metrics_flags
+        );
+
+        assert_eq!(
+            vec![
+                Command::SaveVote {
+                    vote: Vote::new_committed(3, 3)
+                },
+                Command::InstallElectionTimer { can_be_leader: true },
+            ],
+            eng.commands
+        );
+    }
+
+    tracing::info!("--- pre-vote rejected by a greater last log id: can not be leader for a while");
+    {
+        let mut eng = eng();
+        eng.pre_vote_granted_by = Some(btreeset! {1});
+
+        eng.handle_pre_vote_resp(2, VoteResponse {
+            vote: Vote::new(2, 1),
+            vote_granted: false,
+            last_log_id: Some(log_id(2, 3)),
+        });
+
+        assert_eq!(Vote::new(2, 1), eng.state.vote);
+        assert_eq!(None, eng.pre_vote_granted_by);
+
+        assert_eq!(
+            vec![Command::InstallElectionTimer { can_be_leader: false },],
+            eng.commands
+        );
+    }
+    Ok(())
+}
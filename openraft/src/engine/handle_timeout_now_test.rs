@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+use pretty_assertions::assert_eq;
+
+use crate::core::ServerState;
+use crate::engine::Command;
+use crate::engine::Engine;
+use crate::engine::EngineConfig;
+use crate::engine::LogIdList;
+use crate::raft::TimeoutNowRequest;
+use crate::raft::VoteRequest;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::RaftState;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn m012() -> Membership<u64> {
+    Membership::<u64>::new(vec![btreeset! {0,1,2}], None)
+}
+
+fn m12() -> Membership<u64> {
+    Membership::<u64>::new(vec![btreeset! {1,2}], None)
+}
+
+fn eng() -> Engine<u64> {
+    let mut eng = Engine::<u64>::new(0, &RaftState::new(0), EngineConfig::default());
+    eng.state.vote = Vote::new_committed(2, 1);
+    eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m012()));
+    eng
+}
+
+#[test]
+fn test_handle_timeout_now_reject_not_current_leader() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    let started = eng.handle_timeout_now(TimeoutNowRequest {
+        vote: Vote::new_committed(1, 1),
+        last_log_id: Some(log_id(2, 3)),
+    });
+
+    assert!(!started);
+    assert_eq!(Vote::new_committed(2, 1), eng.state.vote);
+    assert_eq!(0, eng.commands.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_timeout_now_reject_not_voter() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m12()));
+
+    let started = eng.handle_timeout_now(TimeoutNowRequest {
+        vote: Vote::new_committed(2, 1),
+        last_log_id: Some(log_id(2, 3)),
+    });
+
+    assert!(!started);
+    assert_eq!(Vote::new_committed(2, 1), eng.state.vote);
+    assert_eq!(0, eng.commands.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_timeout_now_reject_lagging_log() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    let started = eng.handle_timeout_now(TimeoutNowRequest {
+        vote: Vote::new_committed(2, 1),
+        last_log_id: Some(log_id(2, 4)),
+    });
+
+    assert!(!started);
+    assert_eq!(Vote::new_committed(2, 1), eng.state.vote);
+    assert_eq!(0, eng.commands.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_timeout_now_start_election() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    let started = eng.handle_timeout_now(TimeoutNowRequest {
+        vote: Vote::new_committed(2, 1),
+        last_log_id: Some(log_id(2, 3)),
+    });
+
+    assert!(started);
+    assert_eq!(Vote::new(3, 0), eng.state.vote);
+    assert_eq!(ServerState::Candidate, eng.state.server_state);
+    assert!(!eng.last_election_pre_vote);
+
+    assert_eq!(
+        vec![
+            Command::SaveVote { vote: Vote::new(3, 0) },
+            Command::SendVote {
+                vote_req: VoteRequest::new_leader_transfer(Vote::new(3, 0), Some(log_id(2, 3)))
+            },
+            Command::UpdateServerState {
+                server_state: ServerState::Candidate
+            },
+            Command::InstallElectionTimer { can_be_leader: true },
+        ],
+        eng.commands
+    );
+
+    Ok(())
+}
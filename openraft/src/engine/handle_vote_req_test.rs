@@ -3,8 +3,10 @@ use std::sync::Arc;
 use maplit::btreeset;
 
 use crate::core::ServerState;
+use crate::engine::testing::sid;
 use crate::engine::Command;
 use crate::engine::Engine;
+use crate::engine::EngineConfig;
 use crate::engine::LogIdList;
 use crate::raft::VoteRequest;
 use crate::raft::VoteResponse;
@@ -13,6 +15,7 @@ use crate::LeaderId;
 use crate::LogId;
 use crate::Membership;
 use crate::MetricsChangeFlags;
+use crate::RaftState;
 use crate::Vote;
 
 fn log_id(term: u64, index: u64) -> LogId<u64> {
@@ -27,11 +30,11 @@ fn m01() -> Membership<u64> {
 }
 
 fn eng() -> Engine<u64> {
-    let mut eng = Engine::<u64>::default();
+    let mut eng = Engine::<u64>::new(0, &RaftState::new(0), EngineConfig::default());
     eng.state.vote = Vote::new(2, 1);
     eng.state.server_state = ServerState::Candidate;
     eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m01()));
-    eng.state.new_leader();
+    eng.state.new_leader(&eng.config.commit_quorum);
     eng
 }
 
@@ -42,6 +45,8 @@ fn test_handle_vote_req_reject_smaller_vote() -> anyhow::Result<()> {
     let resp = eng.handle_vote_req(VoteRequest {
         vote: Vote::new(1, 2),
         last_log_id: None,
+        pre_vote: false,
+        leader_transfer: false,
     });
 
     assert_eq!(
@@ -78,6 +83,8 @@ fn test_handle_vote_req_reject_smaller_last_log_id() -> anyhow::Result<()> {
     let resp = eng.handle_vote_req(VoteRequest {
         vote: Vote::new(3, 2),
         last_log_id: Some(log_id(1, 3)),
+        pre_vote: false,
+        leader_transfer: false,
     });
 
     assert_eq!(
@@ -115,6 +122,8 @@ fn test_handle_vote_req_granted_equal_vote_and_last_log_id() -> anyhow::Result<(
     let resp = eng.handle_vote_req(VoteRequest {
         vote: Vote::new(2, 1),
         last_log_id: Some(log_id(2, 3)),
+        pre_vote: false,
+        leader_transfer: false,
     });
 
     assert_eq!(
@@ -161,6 +170,8 @@ fn test_handle_vote_req_granted_greater_vote() -> anyhow::Result<()> {
     let resp = eng.handle_vote_req(VoteRequest {
         vote: Vote::new(3, 1),
         last_log_id: Some(log_id(2, 3)),
+        pre_vote: false,
+        leader_transfer: false,
     });
 
     assert_eq!(
@@ -215,6 +226,8 @@ fn test_handle_vote_req_granted_follower_learner_does_not_emit_update_server_sta
         eng.handle_vote_req(VoteRequest {
             vote: Vote::new(3, 1),
             last_log_id: Some(log_id(2, 3)),
+            pre_vote: false,
+            leader_transfer: false,
         });
 
         assert_eq!(st, eng.state.server_state);
@@ -240,6 +253,8 @@ fn test_handle_vote_req_granted_follower_learner_does_not_emit_update_server_sta
         eng.handle_vote_req(VoteRequest {
             vote: Vote::new(3, 1),
             last_log_id: Some(log_id(2, 3)),
+            pre_vote: false,
+            leader_transfer: false,
         });
 
         assert_eq!(st, eng.state.server_state);
@@ -254,3 +269,75 @@ fn test_handle_vote_req_granted_follower_learner_does_not_emit_update_server_sta
     }
     Ok(())
 }
+
+#[test]
+fn test_handle_vote_req_string_node_id() -> anyhow::Result<()> {
+    // A node id that is not `Copy` works just like `u64`.
+
+    let log_id = |term, index| LogId::<String> {
+        leader_id: LeaderId { term, node_id: sid(1) },
+        index,
+    };
+
+    let mut eng = Engine::<String>::new(sid(0), &RaftState::new(sid(0)), EngineConfig::default());
+    eng.state.vote = Vote::new(2, sid(1));
+    eng.state.server_state = ServerState::Candidate;
+    eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(
+        Some(log_id(1, 1)),
+        Membership::<String>::new(vec![btreeset! {sid(0), sid(1)}], None),
+    ));
+    eng.state.new_leader(&eng.config.commit_quorum);
+
+    tracing::info!("--- reject a smaller vote");
+    {
+        let resp = eng.handle_vote_req(VoteRequest {
+            vote: Vote::new(1, sid(2)),
+            last_log_id: Some(log_id(2, 3)),
+            pre_vote: false,
+            leader_transfer: false,
+        });
+
+        assert!(!resp.vote_granted);
+        assert_eq!(Vote::new(2, sid(1)), eng.state.vote);
+        assert_eq!(0, eng.commands.len());
+    }
+
+    tracing::info!("--- grant a greater vote");
+    {
+        let resp = eng.handle_vote_req(VoteRequest {
+            vote: Vote::new(3, sid(1)),
+            last_log_id: Some(log_id(2, 3)),
+            pre_vote: false,
+            leader_transfer: false,
+        });
+
+        assert_eq!(
+            VoteResponse {
+                vote: Vote::new(3, sid(1)),
+                vote_granted: true,
+                last_log_id: Some(log_id(2, 3))
+            },
+            resp
+        );
+
+        assert_eq!(Vote::new(3, sid(1)), eng.state.vote);
+        assert!(eng.state.internal_server_state.is_following());
+        assert_eq!(ServerState::Follower, eng.state.server_state);
+
+        assert_eq!(
+            vec![
+                Command::SaveVote {
+                    vote: Vote::new(3, sid(1))
+                },
+                Command::InstallElectionTimer { can_be_leader: true },
+                Command::UpdateServerState {
+                    server_state: ServerState::Follower
+                }
+            ],
+            eng.commands
+        );
+    }
+
+    Ok(())
+}
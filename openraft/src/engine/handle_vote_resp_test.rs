@@ -6,6 +6,7 @@ use pretty_assertions::assert_eq;
 use crate::core::ServerState;
 use crate::engine::Command;
 use crate::engine::Engine;
+use crate::engine::EngineConfig;
 use crate::engine::LogIdList;
 use crate::raft::VoteResponse;
 use crate::EffectiveMembership;
@@ -13,6 +14,7 @@ use crate::LeaderId;
 use crate::LogId;
 use crate::Membership;
 use crate::MetricsChangeFlags;
+use crate::RaftState;
 use crate::Vote;
 
 fn log_id(term: u64, index: u64) -> LogId<u64> {
@@ -31,7 +33,7 @@ fn m1234() -> Membership<u64> {
 }
 
 fn eng() -> Engine<u64> {
-    Engine::<u64>::default()
+    Engine::<u64>::new(1, &RaftState::new(1), EngineConfig::default())
 }
 
 #[test]
@@ -66,10 +68,9 @@ fn test_handle_vote_resp() -> anyhow::Result<()> {
     tracing::info!("--- recv a smaller vote. vote_granted==false always revert this node to follower");
     {
         let mut eng = eng();
-        eng.id = 1;
         eng.state.vote = Vote::new(2, 1);
         eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m12()));
-        eng.state.new_leader();
+        eng.state.new_leader(&eng.config.commit_quorum);
         eng.state.internal_server_state.leading_mut().map(|l| l.vote_granted_by.insert(1));
         eng.state.server_state = ServerState::Candidate;
 
@@ -105,11 +106,10 @@ fn test_handle_vote_resp() -> anyhow::Result<()> {
     tracing::info!("--- seen a higher vote. revert to follower");
     {
         let mut eng = eng();
-        eng.id = 1;
         eng.state.vote = Vote::new(2, 1);
         eng.state.log_ids = LogIdList::new(vec![log_id(3, 3)]);
         eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m12()));
-        eng.state.new_leader();
+        eng.state.new_leader(&eng.config.commit_quorum);
         eng.state.internal_server_state.leading_mut().map(|l| l.vote_granted_by.insert(1));
         eng.state.server_state = ServerState::Candidate;
 
@@ -146,10 +146,9 @@ fn test_handle_vote_resp() -> anyhow::Result<()> {
     tracing::info!("--- equal vote, rejected by higher last_log_id. revert to follower");
     {
         let mut eng = eng();
-        eng.id = 1;
         eng.state.vote = Vote::new(2, 1);
         eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m12()));
-        eng.state.new_leader();
+        eng.state.new_leader(&eng.config.commit_quorum);
         eng.state.internal_server_state.leading_mut().map(|l| l.vote_granted_by.insert(1));
         eng.state.server_state = ServerState::Candidate;
 
@@ -185,10 +184,9 @@ fn test_handle_vote_resp() -> anyhow::Result<()> {
     tracing::info!("--- equal vote, granted, but not constitute a quorum. nothing to do");
     {
         let mut eng = eng();
-        eng.id = 1;
         eng.state.vote = Vote::new(2, 1);
         eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m1234()));
-        eng.state.new_leader();
+        eng.state.new_leader(&eng.config.commit_quorum);
         eng.state.internal_server_state.leading_mut().map(|l| l.vote_granted_by.insert(1));
         eng.state.server_state = ServerState::Candidate;
 
@@ -219,10 +217,9 @@ fn test_handle_vote_resp() -> anyhow::Result<()> {
     tracing::info!("--- equal vote, granted, constitute a quorum. become leader");
     {
         let mut eng = eng();
-        eng.id = 1;
         eng.state.vote = Vote::new(2, 1);
         eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m12()));
-        eng.state.new_leader();
+        eng.state.new_leader(&eng.config.commit_quorum);
         eng.state.internal_server_state.leading_mut().map(|l| l.vote_granted_by.insert(1));
         eng.state.server_state = ServerState::Candidate;
 
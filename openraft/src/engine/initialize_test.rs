@@ -5,6 +5,7 @@ use crate::core::ServerState;
 use crate::engine::testing::Config;
 use crate::engine::Command;
 use crate::engine::Engine;
+use crate::engine::EngineConfig;
 use crate::engine::LogIdList;
 use crate::entry::EntryRef;
 use crate::error::InitializeError;
@@ -17,14 +18,15 @@ use crate::LeaderId;
 use crate::LogId;
 use crate::Membership;
 use crate::MetricsChangeFlags;
+use crate::RaftState;
 use crate::Vote;
 
 #[test]
 fn test_initialize_single_node() -> anyhow::Result<()> {
-    let eng = Engine::<u64>::default;
+    let eng = |id| Engine::<u64>::new(id, &RaftState::new(id), EngineConfig::default());
 
     let log_id0 = LogId {
-        leader_id: LeaderId::new(0, 0),
+        leader_id: LeaderId::new(0, 1),
         index: 0,
     };
 
@@ -35,8 +37,7 @@ fn test_initialize_single_node() -> anyhow::Result<()> {
     tracing::info!("--- ok: init empty node 1 with membership(1,2)");
     tracing::info!("--- expect OK result, check output commands and state changes");
     {
-        let mut eng = eng();
-        eng.id = 1;
+        let mut eng = eng(1);
 
         eng.initialize(&mut entries)?;
 
@@ -94,13 +95,13 @@ fn test_initialize_single_node() -> anyhow::Result<()> {
 
 #[test]
 fn test_initialize() -> anyhow::Result<()> {
-    let eng = Engine::<u64>::default;
+    let eng = |id| Engine::<u64>::new(id, &RaftState::new(id), EngineConfig::default());
 
     let log_id0 = LogId {
-        leader_id: LeaderId::new(0, 0),
+        leader_id: LeaderId::new(0, 1),
         index: 0,
     };
-    let vote0 = Vote::new(0, 0);
+    let vote0 = Vote::new(0, 1);
 
     let m12 = || Membership::<u64>::new(vec![btreeset! {1,2}], None);
     let payload = EntryPayload::<Config>::Membership(m12());
@@ -109,8 +110,7 @@ fn test_initialize() -> anyhow::Result<()> {
     tracing::info!("--- ok: init empty node 1 with membership(1,2)");
     tracing::info!("--- expect OK result, check output commands and state changes");
     {
-        let mut eng = eng();
-        eng.id = 1;
+        let mut eng = eng(1);
 
         eng.initialize(&mut entries)?;
 
@@ -155,9 +155,11 @@ fn test_initialize() -> anyhow::Result<()> {
                             committed: false,
                         },
                         last_log_id: Some(LogId {
-                            leader_id: LeaderId { term: 0, node_id: 0 },
+                            leader_id: LeaderId { term: 0, node_id: 1 },
                             index: 0,
                         },),
+                        pre_vote: false,
+                        leader_transfer: false,
                     },
                 },
                 Command::UpdateServerState {
@@ -171,7 +173,7 @@ fn test_initialize() -> anyhow::Result<()> {
 
     tracing::info!("--- not allowed because of last_log_id");
     {
-        let mut eng = eng();
+        let mut eng = eng(1);
         eng.state.log_ids = LogIdList::new(vec![log_id0]);
 
         assert_eq!(
@@ -185,13 +187,13 @@ fn test_initialize() -> anyhow::Result<()> {
 
     tracing::info!("--- not allowed because of vote");
     {
-        let mut eng = eng();
-        eng.state.vote = Vote::new(0, 1);
+        let mut eng = eng(1);
+        eng.state.vote = Vote::new(0, 2);
 
         assert_eq!(
             Err(InitializeError::NotAllowed(NotAllowed {
                 last_log_id: None,
-                vote: Vote::new(0, 1),
+                vote: Vote::new(0, 2),
             })),
             eng.initialize(&mut entries)
         );
@@ -199,7 +201,7 @@ fn test_initialize() -> anyhow::Result<()> {
 
     tracing::info!("--- node id 0 is not in membership");
     {
-        let mut eng = eng();
+        let mut eng = eng(0);
 
         assert_eq!(
             Err(InitializeError::NotInMembers(NotInMembers {
@@ -212,7 +214,7 @@ fn test_initialize() -> anyhow::Result<()> {
 
     tracing::info!("--- log entry is not a membership entry");
     {
-        let mut eng = eng();
+        let mut eng = eng(1);
 
         let payload = EntryPayload::<Config>::Blank;
         let mut entries = [EntryRef::new(&payload)];
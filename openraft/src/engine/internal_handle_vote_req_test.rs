@@ -5,6 +5,7 @@ use maplit::btreeset;
 use crate::core::ServerState;
 use crate::engine::Command;
 use crate::engine::Engine;
+use crate::engine::EngineConfig;
 use crate::engine::LogIdList;
 use crate::error::RejectVoteRequest;
 use crate::EffectiveMembership;
@@ -12,6 +13,7 @@ use crate::LeaderId;
 use crate::LogId;
 use crate::Membership;
 use crate::MetricsChangeFlags;
+use crate::RaftState;
 use crate::Vote;
 
 fn log_id(term: u64, index: u64) -> LogId<u64> {
@@ -26,11 +28,11 @@ fn m01() -> Membership<u64> {
 }
 
 fn eng() -> Engine<u64> {
-    let mut eng = Engine::<u64>::default();
+    let mut eng = Engine::<u64>::new(0, &RaftState::new(0), EngineConfig::default());
     eng.state.vote = Vote::new(2, 1);
     eng.state.server_state = ServerState::Candidate;
     eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m01()));
-    eng.state.new_leader();
+    eng.state.new_leader(&eng.config.commit_quorum);
     eng
 }
 
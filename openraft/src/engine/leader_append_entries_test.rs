@@ -7,6 +7,7 @@ use maplit::btreeset;
 
 use crate::engine::Command;
 use crate::engine::Engine;
+use crate::engine::EngineConfig;
 use crate::EffectiveMembership;
 use crate::Entry;
 use crate::EntryPayload;
@@ -15,6 +16,7 @@ use crate::LogId;
 use crate::Membership;
 use crate::MembershipState;
 use crate::MetricsChangeFlags;
+use crate::RaftState;
 use crate::Vote;
 
 crate::declare_raft_types!(
@@ -32,6 +34,7 @@ fn blank(term: u64, index: u64) -> Entry<Foo> {
     Entry {
         log_id: log_id(term, index),
         payload: EntryPayload::<Foo>::Blank,
+        context: None,
     }
 }
 
@@ -48,6 +51,10 @@ fn m1_2() -> Membership<u64> {
     Membership::<u64>::new(vec![btreeset! {1}], Some(btreeset! {2}))
 }
 
+fn m123() -> Membership<u64> {
+    Membership::<u64>::new(vec![btreeset! {1,2,3}], None)
+}
+
 fn m13() -> Membership<u64> {
     Membership::<u64>::new(vec![btreeset! {1,3}], None)
 }
@@ -61,10 +68,8 @@ fn m34() -> Membership<u64> {
 }
 
 fn eng() -> Engine<u64> {
-    let mut eng = Engine::<u64> {
-        id: 1, // make it a member
-        ..Default::default()
-    };
+    // make it a member
+    let mut eng = Engine::<u64>::new(1, &RaftState::new(1), EngineConfig::default());
     eng.state.last_applied = Some(log_id(0, 0));
     eng.state.vote = Vote::new_committed(3, 1);
     eng.state.log_ids.append(log_id(1, 1));
@@ -159,10 +164,10 @@ fn test_leader_append_entries_normal() -> anyhow::Result<()> {
 }
 
 #[test]
-fn test_leader_append_entries_fast_commit() -> anyhow::Result<()> {
+fn test_leader_append_entries_commit_when_flushed() -> anyhow::Result<()> {
     let mut eng = eng();
     eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m1()));
-    eng.state.new_leader();
+    eng.state.new_leader(&eng.config.commit_quorum);
 
     // log id will be assigned by eng.
     eng.leader_append_entries(&mut [
@@ -181,6 +186,21 @@ fn test_leader_append_entries_fast_commit() -> anyhow::Result<()> {
         eng.state.log_ids.key_log_ids()
     );
     assert_eq!(Some(LogId::new(LeaderId::new(3, 1), 6)), eng.state.last_log_id());
+
+    // Not committed until the leader flushed the entries, even in a single voter cluster.
+    assert_eq!(None, eng.state.committed);
+    assert_eq!(
+        vec![
+            Command::AppendInputEntries { range: 0..3 },
+            Command::ReplicateInputEntries { range: 0..3 },
+            Command::MoveInputCursorBy { n: 3 },
+        ],
+        eng.commands
+    );
+
+    eng.commands = vec![];
+    eng.leader_log_flushed(Some(log_id(3, 6)));
+
     assert_eq!(
         MembershipState {
             committed: Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m1())),
@@ -200,7 +220,6 @@ fn test_leader_append_entries_fast_commit() -> anyhow::Result<()> {
 
     assert_eq!(
         vec![
-            Command::AppendInputEntries { range: 0..3 },
             Command::ReplicateCommitted {
                 committed: Some(log_id(3, 6))
             },
@@ -208,22 +227,72 @@ fn test_leader_append_entries_fast_commit() -> anyhow::Result<()> {
                 since: None,
                 upto: LogId::new(LeaderId::new(3, 1), 6)
             },
-            Command::ReplicateInputEntries { range: 0..3 },
-            Command::MoveInputCursorBy { n: 3 },
         ],
         eng.commands
     );
 
+    // A flush that is already counted is ignored.
+    eng.commands = vec![];
+    eng.leader_log_flushed(Some(log_id(3, 5)));
+
+    assert_eq!(Some(LogId::new(LeaderId::new(3, 1), 6)), eng.state.committed);
+    assert_eq!(0, eng.commands.len());
+
     Ok(())
 }
 
-/// With membership log, fast-commit upto membership entry that is not a single-node cluster.
-/// Leader is no longer a voter should work.
+/// The entries replicated to a quorum of followers are committed before the leader flushed them.
 #[test]
-fn test_leader_append_entries_fast_commit_upto_membership_entry() -> anyhow::Result<()> {
+fn test_leader_append_entries_committed_by_followers_before_flushed() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m123()));
+    eng.state.new_leader(&eng.config.commit_quorum);
+
+    eng.leader_append_entries(&mut [
+        blank(1, 1), //
+        blank(1, 1),
+        blank(1, 1),
+    ]);
+
+    eng.commands = vec![];
+    eng.update_progress(2, Some(log_id(3, 6)));
+
+    assert_eq!(None, eng.state.committed, "a quorum is not reached by one follower");
+    assert_eq!(0, eng.commands.len());
+
+    eng.update_progress(3, Some(log_id(3, 6)));
+
+    assert_eq!(Some(log_id(3, 6)), eng.state.committed);
+    assert_eq!(
+        vec![
+            Command::ReplicateCommitted {
+                committed: Some(log_id(3, 6))
+            },
+            Command::LeaderCommit {
+                since: None,
+                upto: log_id(3, 6)
+            },
+        ],
+        eng.commands
+    );
+
+    // The flush reported later does not commit again.
+    eng.commands = vec![];
+    eng.leader_log_flushed(Some(log_id(3, 6)));
+
+    assert_eq!(Some(log_id(3, 6)), eng.state.committed);
+    assert_eq!(0, eng.commands.len());
+
+    Ok(())
+}
+
+/// With membership log, the flushed entries are committed by the new membership.
+/// A leader that is no longer a voter can not commit by itself.
+#[test]
+fn test_leader_append_entries_flushed_leader_not_in_membership() -> anyhow::Result<()> {
     let mut eng = eng();
     eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m1()));
-    eng.state.new_leader();
+    eng.state.new_leader(&eng.config.commit_quorum);
 
     // log id will be assigned by eng.
     eng.leader_append_entries(&mut [
@@ -231,9 +300,11 @@ fn test_leader_append_entries_fast_commit_upto_membership_entry() -> anyhow::Res
         Entry {
             log_id: log_id(1, 1),
             payload: EntryPayload::Membership(m34()),
+            context: None,
         },
         blank(1, 1),
     ]);
+    eng.leader_log_flushed(Some(log_id(3, 6)));
 
     assert_eq!(
         &[
@@ -247,8 +318,7 @@ fn test_leader_append_entries_fast_commit_upto_membership_entry() -> anyhow::Res
     assert_eq!(Some(LogId::new(LeaderId::new(3, 1), 6)), eng.state.last_log_id());
     assert_eq!(
         MembershipState {
-            // previous effective become committed.
-            committed: Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m1())),
+            committed: Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m01())),
             // new effective.
             effective: Arc::new(EffectiveMembership::new(
                 Some(LogId::new(LeaderId::new(3, 1), 5)),
@@ -257,7 +327,7 @@ fn test_leader_append_entries_fast_commit_upto_membership_entry() -> anyhow::Res
         },
         eng.state.membership_state
     );
-    assert_eq!(Some(LogId::new(LeaderId::new(3, 1), 4)), eng.state.committed);
+    assert_eq!(None, eng.state.committed);
 
     assert_eq!(
         MetricsChangeFlags {
@@ -270,13 +340,6 @@ fn test_leader_append_entries_fast_commit_upto_membership_entry() -> anyhow::Res
     assert_eq!(
         vec![
             Command::AppendInputEntries { range: 0..3 },
-            Command::ReplicateCommitted {
-                committed: Some(log_id(3, 4))
-            },
-            Command::LeaderCommit {
-                since: None,
-                upto: LogId::new(LeaderId::new(3, 1), 4)
-            },
             Command::UpdateMembership {
                 membership: Arc::new(EffectiveMembership::new(
                     Some(LogId::new(LeaderId::new(3, 1), 5)),
@@ -296,12 +359,12 @@ fn test_leader_append_entries_fast_commit_upto_membership_entry() -> anyhow::Res
     Ok(())
 }
 
-/// With membership log, fast-commit is allowed if no voter change.
+/// With membership log, the flushed entries are committed at once if there is no voter change.
 #[test]
-fn test_leader_append_entries_fast_commit_membership_no_voter_change() -> anyhow::Result<()> {
+fn test_leader_append_entries_flushed_membership_no_voter_change() -> anyhow::Result<()> {
     let mut eng = eng();
     eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m1()));
-    eng.state.new_leader();
+    eng.state.new_leader(&eng.config.commit_quorum);
 
     // log id will be assigned by eng.
     eng.leader_append_entries(&mut [
@@ -309,9 +372,11 @@ fn test_leader_append_entries_fast_commit_membership_no_voter_change() -> anyhow
         Entry {
             log_id: log_id(1, 1),
             payload: EntryPayload::Membership(m1_2()),
+            context: None,
         },
         blank(1, 1),
     ]);
+    eng.leader_log_flushed(Some(log_id(3, 6)));
 
     assert_eq!(
         &[
@@ -349,14 +414,6 @@ fn test_leader_append_entries_fast_commit_membership_no_voter_change() -> anyhow
     assert_eq!(
         vec![
             Command::AppendInputEntries { range: 0..3 },
-            // first commit upto the membership entry(exclusive).
-            Command::ReplicateCommitted {
-                committed: Some(log_id(3, 4))
-            },
-            Command::LeaderCommit {
-                since: None,
-                upto: LogId::new(LeaderId::new(3, 1), 4)
-            },
             Command::UpdateMembership {
                 membership: Arc::new(EffectiveMembership::new(
                     Some(LogId::new(LeaderId::new(3, 1), 5)),
@@ -367,16 +424,16 @@ fn test_leader_append_entries_fast_commit_membership_no_voter_change() -> anyhow
                 remove: vec![],
                 add: vec![(2, None)]
             },
-            // second commit upto the end.
+            Command::ReplicateInputEntries { range: 0..3 },
+            Command::MoveInputCursorBy { n: 3 },
+            // commit upto the end when flushed.
             Command::ReplicateCommitted {
                 committed: Some(log_id(3, 6))
             },
             Command::LeaderCommit {
-                since: Some(LogId::new(LeaderId::new(3, 1), 4)),
+                since: None,
                 upto: LogId::new(LeaderId::new(3, 1), 6)
             },
-            Command::ReplicateInputEntries { range: 0..3 },
-            Command::MoveInputCursorBy { n: 3 },
         ],
         eng.commands
     );
@@ -384,14 +441,12 @@ fn test_leader_append_entries_fast_commit_membership_no_voter_change() -> anyhow
     Ok(())
 }
 
-// TODO(xp): check progress
-
-/// With membership log, fast-commit all if the membership log changes to one voter.
+/// With membership log, the flushed entries are committed at once if the membership log changes to one voter.
 #[test]
-fn test_leader_append_entries_fast_commit_if_membership_voter_change_to_1() -> anyhow::Result<()> {
+fn test_leader_append_entries_flushed_if_membership_voter_change_to_1() -> anyhow::Result<()> {
     let mut eng = eng();
     eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m13()));
-    eng.state.new_leader();
+    eng.state.new_leader(&eng.config.commit_quorum);
 
     // log id will be assigned by eng.
     eng.leader_append_entries(&mut [
@@ -399,9 +454,11 @@ fn test_leader_append_entries_fast_commit_if_membership_voter_change_to_1() -> a
         Entry {
             log_id: log_id(1, 1),
             payload: EntryPayload::Membership(m1_2()),
+            context: None,
         },
         blank(1, 1),
     ]);
+    eng.leader_log_flushed(Some(log_id(3, 6)));
 
     assert_eq!(
         &[
@@ -449,6 +506,8 @@ fn test_leader_append_entries_fast_commit_if_membership_voter_change_to_1() -> a
                 remove: vec![(3, None)],
                 add: vec![(2, None)]
             },
+            Command::ReplicateInputEntries { range: 0..3 },
+            Command::MoveInputCursorBy { n: 3 },
             // It is correct to commit if the membership change ot a one node cluster.
             Command::ReplicateCommitted {
                 committed: Some(log_id(3, 6))
@@ -457,8 +516,6 @@ fn test_leader_append_entries_fast_commit_if_membership_voter_change_to_1() -> a
                 since: None,
                 upto: LogId::new(LeaderId::new(3, 1), 6)
             },
-            Command::ReplicateInputEntries { range: 0..3 },
-            Command::MoveInputCursorBy { n: 3 },
         ],
         eng.commands
     );
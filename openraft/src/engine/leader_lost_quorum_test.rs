@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+use pretty_assertions::assert_eq;
+use tokio::time::Instant;
+
+use crate::core::ServerState;
+use crate::engine::Command;
+use crate::engine::Engine;
+use crate::engine::EngineConfig;
+use crate::engine::LogIdList;
+use crate::raft::VoteRequest;
+use crate::raft::VoteResponse;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::RaftState;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn m012() -> Membership<u64> {
+    Membership::<u64>::new(vec![btreeset! {0,1,2}], None)
+}
+
+/// An established leader node-0.
+fn eng() -> Engine<u64> {
+    let mut eng = Engine::<u64>::new(0, &RaftState::new(0), EngineConfig::default());
+    eng.state.vote = Vote::new_committed(2, 0);
+    eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m012()));
+    eng.state.new_leader(&eng.config.commit_quorum);
+    eng.state.server_state = ServerState::Leader;
+    eng
+}
+
+#[test]
+fn test_leader_lost_quorum_not_leading() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.state.vote = Vote::new_committed(2, 1);
+    eng.enter_following();
+    eng.commands = vec![];
+
+    let eng0 = eng.clone();
+    eng.leader_lost_quorum();
+
+    assert_eq!(eng0, eng, "nothing changed");
+
+    Ok(())
+}
+
+#[test]
+fn test_leader_lost_quorum() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.update_leader_clock(1, Instant::now());
+
+    eng.leader_lost_quorum();
+
+    assert_eq!(Vote::new_committed(2, 0), eng.state.vote, "vote is kept");
+    assert!(eng.state.internal_server_state.is_following());
+    assert_eq!(ServerState::Follower, eng.state.server_state);
+    assert_eq!(None, eng.leader_quorum_acked(), "leader clock is cleared");
+
+    assert_eq!(
+        vec![
+            //
+            Command::InstallElectionTimer { can_be_leader: true },
+            Command::UpdateServerState {
+                server_state: ServerState::Follower
+            },
+        ],
+        eng.commands
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_leader_lost_quorum_then_grant_pre_vote() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    let req = VoteRequest::new_pre_vote(Vote::new(3, 2), Some(log_id(2, 3)));
+
+    let resp = eng.handle_pre_vote_req(req.clone());
+    assert!(!resp.vote_granted, "an established leader rejects pre-vote");
+
+    eng.leader_lost_quorum();
+
+    let resp = eng.handle_pre_vote_req(req);
+    assert_eq!(
+        VoteResponse {
+            vote: Vote::new_committed(2, 0),
+            vote_granted: true,
+            last_log_id: Some(log_id(2, 3)),
+        },
+        resp
+    );
+
+    Ok(())
+}
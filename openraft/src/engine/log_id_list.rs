@@ -1,9 +1,9 @@
 use crate::raft_types::RaftLogId;
+use crate::storage::StorageAccess;
 use crate::storage::StorageHelper;
 use crate::LogId;
 use crate::LogIdOptionExt;
 use crate::NodeId;
-use crate::RaftStorage;
 use crate::RaftTypeConfig;
 use crate::StorageError;
 
@@ -14,12 +14,18 @@ use crate::StorageError;
 ///
 /// If it is not empty, the first one is `last_purged_log_id` and the last one is `last_log_id`.
 /// The last one may have the same leader id as the second last one.
-#[derive(Default, Debug, Clone)]
+#[derive(Debug, Clone)]
 #[derive(PartialEq, Eq)]
 pub struct LogIdList<NID: NodeId> {
     key_log_ids: Vec<LogId<NID>>,
 }
 
+impl<NID: NodeId> Default for LogIdList<NID> {
+    fn default() -> Self {
+        Self { key_log_ids: vec![] }
+    }
+}
+
 impl<NID: NodeId> LogIdList<NID> {
     /// Load all log ids that are the first one proposed by a leader.
     ///
@@ -48,7 +54,7 @@ impl<NID: NodeId> LogIdList<NID> {
     ) -> Result<LogIdList<NID>, StorageError<NID>>
     where
         C: RaftTypeConfig<NodeId = NID>,
-        Sto: RaftStorage<C>,
+        Sto: StorageAccess<C>,
     {
         let mut res = vec![];
 
@@ -62,7 +68,7 @@ impl<NID: NodeId> LogIdList<NID> {
         };
 
         // Recursion stack
-        let mut stack = vec![(first, last)];
+        let mut stack = vec![(first, last.clone())];
 
         loop {
             let (first, last) = match stack.pop() {
@@ -74,7 +80,7 @@ impl<NID: NodeId> LogIdList<NID> {
 
             // Case AA
             if first.leader_id == last.leader_id {
-                if res.last().map(|x| x.leader_id) < Some(first.leader_id) {
+                if res.last().map(|x| &x.leader_id) < Some(&first.leader_id) {
                     res.push(first);
                 }
                 continue;
@@ -82,7 +88,7 @@ impl<NID: NodeId> LogIdList<NID> {
 
             // Two adjacent logs with different leader_id, no need to binary search
             if first.index + 1 == last.index {
-                if res.last().map(|x| x.leader_id) < Some(first.leader_id) {
+                if res.last().map(|x| &x.leader_id) < Some(&first.leader_id) {
                     res.push(first);
                 }
                 res.push(last);
@@ -93,7 +99,7 @@ impl<NID: NodeId> LogIdList<NID> {
 
             if first.leader_id == mid.leader_id {
                 // Case AAC
-                if res.last().map(|x| x.leader_id) < Some(first.leader_id) {
+                if res.last().map(|x| &x.leader_id) < Some(&first.leader_id) {
                     res.push(first);
                 }
                 stack.push((mid, last));
@@ -104,7 +110,7 @@ impl<NID: NodeId> LogIdList<NID> {
                 // Case ABC
                 // first.leader_id < mid_log_id.leader_id < last.leader_id
                 // Deal with (first, mid) then (mid, last)
-                stack.push((mid, last));
+                stack.push((mid.clone(), last));
                 stack.push((first, mid));
             }
         }
@@ -130,14 +136,14 @@ impl<NID: NodeId> LogIdList<NID> {
     pub(crate) fn extend_from_same_leader<'a, LID: RaftLogId<NID> + 'a>(&mut self, new_ids: &[LID]) {
         if let Some(first) = new_ids.first() {
             let first_id = first.get_log_id();
-            self.append(*first_id);
+            self.append(first_id.clone());
 
             if let Some(last) = new_ids.last() {
                 let last_id = last.get_log_id();
                 assert_eq!(last_id.leader_id, first_id.leader_id);
 
                 if last_id != first_id {
-                    self.append(*last_id);
+                    self.append(last_id.clone());
                 }
             }
         }
@@ -146,15 +152,15 @@ impl<NID: NodeId> LogIdList<NID> {
     /// Extends a list of `log_id`.
     #[allow(dead_code)]
     pub(crate) fn extend<'a, LID: RaftLogId<NID> + 'a>(&mut self, new_ids: &[LID]) {
-        let mut prev = self.last().map(|x| x.leader_id);
+        let mut prev = self.last().map(|x| x.leader_id.clone());
 
         for x in new_ids.iter() {
             let log_id = x.get_log_id();
 
-            if prev != Some(log_id.leader_id) {
-                self.append(*log_id);
+            if prev.as_ref() != Some(&log_id.leader_id) {
+                self.append(log_id.clone());
 
-                prev = Some(log_id.leader_id);
+                prev = Some(log_id.leader_id.clone());
             }
         }
 
@@ -162,7 +168,7 @@ impl<NID: NodeId> LogIdList<NID> {
             let log_id = last.get_log_id();
 
             if self.last() != Some(log_id) {
-                self.append(*log_id);
+                self.append(log_id.clone());
             }
         }
     }
@@ -192,9 +198,9 @@ impl<NID: NodeId> LogIdList<NID> {
 
         // l >= 2
 
-        let last = self.key_log_ids[l - 1];
+        let last = &self.key_log_ids[l - 1];
 
-        if self.key_log_ids.get(l - 2).map(|x| x.leader_id) == Some(last.leader_id) {
+        if self.key_log_ids.get(l - 2).map(|x| &x.leader_id) == Some(&last.leader_id) {
             // Replace the **last log id**.
             self.key_log_ids[l - 1] = new_log_id;
             return;
@@ -226,7 +232,7 @@ impl<NID: NodeId> LogIdList<NID> {
         // Add key log id if there is a gap between last.index and at - 1.
         let last = self.key_log_ids.last();
         if let Some(last) = last {
-            let (last_leader_id, last_index) = (last.leader_id, last.index);
+            let (last_leader_id, last_index) = (last.leader_id.clone(), last.index);
             if last_index < at - 1 {
                 self.append(LogId::new(last_leader_id, at - 1));
             }
@@ -241,7 +247,7 @@ impl<NID: NodeId> LogIdList<NID> {
         // When installing  snapshot it may need to purge across the `last_log_id`.
         if upto.index >= last.next_index() {
             debug_assert!(Some(upto) > self.last());
-            self.key_log_ids = vec![*upto];
+            self.key_log_ids = vec![upto.clone()];
             return;
         }
 
@@ -272,12 +278,12 @@ impl<NID: NodeId> LogIdList<NID> {
         let res = self.key_log_ids.binary_search_by(|log_id| log_id.index.cmp(&index));
 
         match res {
-            Ok(i) => Some(LogId::new(self.key_log_ids[i].leader_id, index)),
+            Ok(i) => Some(LogId::new(self.key_log_ids[i].leader_id.clone(), index)),
             Err(i) => {
                 if i == 0 || i == self.key_log_ids.len() {
                     None
                 } else {
-                    Some(LogId::new(self.key_log_ids[i - 1].leader_id, index))
+                    Some(LogId::new(self.key_log_ids[i - 1].leader_id.clone(), index))
                 }
             }
         }
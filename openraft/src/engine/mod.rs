@@ -30,22 +30,33 @@ mod command;
 mod engine_impl;
 mod log_id_list;
 
+#[cfg(test)] mod calc_purge_logs_upto_test;
 #[cfg(test)] mod calc_purge_upto_test;
+#[cfg(test)] mod calc_trigger_purge_upto_test;
 #[cfg(test)] mod elect_test;
+#[cfg(test)] mod election_priority_test;
 #[cfg(test)] mod follower_commit_entries_test;
 #[cfg(test)] mod follower_do_append_entries_test;
+#[cfg(test)] mod follower_leader_contacted_test;
 #[cfg(test)] mod handle_append_entries_req_test;
+#[cfg(test)] mod handle_heartbeat_req_test;
+#[cfg(test)] mod handle_pre_vote_req_test;
+#[cfg(test)] mod handle_pre_vote_resp_test;
+#[cfg(test)] mod handle_timeout_now_test;
 #[cfg(test)] mod handle_vote_req_test;
 #[cfg(test)] mod handle_vote_resp_test;
 #[cfg(test)] mod initialize_test;
 #[cfg(test)] mod internal_handle_vote_req_test;
 #[cfg(test)] mod leader_append_entries_test;
+#[cfg(test)] mod leader_lost_quorum_test;
 #[cfg(test)] mod log_id_list_test;
+#[cfg(test)] mod pick_snapshot_source_test;
 #[cfg(test)] mod purge_log_test;
 #[cfg(test)] mod testing;
 #[cfg(test)] mod truncate_logs_test;
 #[cfg(test)] mod update_committed_membership_test;
 #[cfg(test)] mod update_effective_membership_test;
+#[cfg(test)] mod update_leader_clock_test;
 #[cfg(test)] mod update_progress_test;
 
 pub(crate) use command::Command;
@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+
+use crate::engine::Engine;
+use crate::engine::EngineConfig;
+use crate::internal_server_state::InternalServerState;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::RaftState;
+use crate::SnapshotSource;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn m1234() -> Membership<u64> {
+    Membership::<u64>::new(vec![btreeset! {1,2,3,4}], Some(btreeset! {1,2,3,4,5}))
+}
+
+fn eng(snapshot_source: SnapshotSource) -> Engine<u64> {
+    let mut eng = Engine::<u64>::new(1, &RaftState::new(1), EngineConfig {
+        snapshot_source,
+        ..Default::default()
+    });
+    eng.state.vote = Vote::new_committed(2, 1);
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m1234()));
+    eng.state.new_leader(&eng.config.commit_quorum);
+    eng
+}
+
+#[test]
+fn test_pick_snapshot_source_leader() -> anyhow::Result<()> {
+    let mut eng = eng(SnapshotSource::Leader);
+    eng.update_progress(2, Some(log_id(2, 10)));
+
+    assert_eq!(None, eng.pick_snapshot_source(&4, &log_id(2, 5)));
+
+    Ok(())
+}
+
+#[test]
+fn test_pick_snapshot_source_not_leader() -> anyhow::Result<()> {
+    let mut eng = eng(SnapshotSource::Voters);
+    eng.update_progress(2, Some(log_id(2, 10)));
+    eng.state.internal_server_state = InternalServerState::Following;
+
+    assert_eq!(None, eng.pick_snapshot_source(&4, &log_id(2, 5)));
+
+    Ok(())
+}
+
+#[test]
+fn test_pick_snapshot_source_voters() -> anyhow::Result<()> {
+    let mut eng = eng(SnapshotSource::Voters);
+
+    tracing::info!("--- no voter has the logs in the snapshot");
+    {
+        eng.update_progress(2, Some(log_id(2, 4)));
+        assert_eq!(None, eng.pick_snapshot_source(&4, &log_id(2, 5)));
+    }
+
+    tracing::info!("--- the voter that has replicated the most logs is picked");
+    {
+        eng.update_progress(2, Some(log_id(2, 5)));
+        eng.update_progress(3, Some(log_id(2, 7)));
+        assert_eq!(Some(3), eng.pick_snapshot_source(&4, &log_id(2, 5)));
+    }
+
+    tracing::info!("--- the target itself is not picked");
+    {
+        assert_eq!(Some(2), eng.pick_snapshot_source(&3, &log_id(2, 5)));
+    }
+
+    tracing::info!("--- a learner is not picked");
+    {
+        eng.update_progress(5, Some(log_id(2, 9)));
+        assert_eq!(Some(3), eng.pick_snapshot_source(&4, &log_id(2, 5)));
+    }
+
+    Ok(())
+}
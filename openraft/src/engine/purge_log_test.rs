@@ -1,8 +1,10 @@
 use crate::engine::Command;
 use crate::engine::Engine;
+use crate::engine::EngineConfig;
 use crate::engine::LogIdList;
 use crate::LeaderId;
 use crate::LogId;
+use crate::RaftState;
 
 fn log_id(term: u64, index: u64) -> LogId<u64> {
     LogId::<u64> {
@@ -12,7 +14,7 @@ fn log_id(term: u64, index: u64) -> LogId<u64> {
 }
 
 fn eng() -> Engine<u64> {
-    let mut eng = Engine::<u64>::default();
+    let mut eng = Engine::<u64>::new(0, &RaftState::new(0), EngineConfig::default());
     eng.state.log_ids = LogIdList::new(vec![log_id(2, 2), log_id(4, 4), log_id(4, 6)]);
     eng
 }
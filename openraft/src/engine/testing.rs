@@ -1,14 +1,36 @@
+use crate::RaftTypeConfig;
+
 /// Req for test
 #[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize),
+    archive(check_bytes)
+)]
 pub(crate) struct Req {}
 
 /// Resp for test
 #[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize),
+    archive(check_bytes)
+)]
 pub(crate) struct Resp {}
 
 // Config for test
 crate::declare_raft_types!(
    pub(crate) Config: D = Req, R = Resp, NodeId = u64
 );
+
+// Config for test, with a node id that is not `Copy`
+crate::declare_raft_types!(
+   pub(crate) StringConfig: D = Req, R = Resp, NodeId = String
+);
+
+/// Build a node id of [`StringConfig`] from a number.
+pub(crate) fn sid(id: u64) -> <StringConfig as RaftTypeConfig>::NodeId {
+    id.to_string()
+}
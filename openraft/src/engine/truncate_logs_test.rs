@@ -4,6 +4,7 @@ use maplit::btreeset;
 
 use crate::engine::Command;
 use crate::engine::Engine;
+use crate::engine::EngineConfig;
 use crate::engine::LogIdList;
 use crate::EffectiveMembership;
 use crate::LeaderId;
@@ -11,6 +12,7 @@ use crate::LogId;
 use crate::Membership;
 use crate::MembershipState;
 use crate::MetricsChangeFlags;
+use crate::RaftState;
 use crate::ServerState;
 
 fn log_id(term: u64, index: u64) -> LogId<u64> {
@@ -33,10 +35,7 @@ fn m23() -> Membership<u64> {
 }
 
 fn eng() -> Engine<u64> {
-    let mut eng = Engine::<u64> {
-        id: 2,
-        ..Default::default()
-    };
+    let mut eng = Engine::<u64>::new(2, &RaftState::new(2), EngineConfig::default());
     eng.state.server_state = ServerState::Follower;
     eng.state.log_ids = LogIdList::new(vec![
         log_id(2, 2), //
@@ -5,12 +5,14 @@ use maplit::btreeset;
 use crate::core::ServerState;
 use crate::engine::Command;
 use crate::engine::Engine;
+use crate::engine::EngineConfig;
 use crate::EffectiveMembership;
 use crate::LeaderId;
 use crate::LogId;
 use crate::Membership;
 use crate::MembershipState;
 use crate::MetricsChangeFlags;
+use crate::RaftState;
 
 crate::declare_raft_types!(
     pub(crate) Foo: D=(), R=(), NodeId=u64
@@ -36,10 +38,8 @@ fn m34() -> Membership<u64> {
 }
 
 fn eng() -> Engine<u64> {
-    let mut eng = Engine::<u64> {
-        id: 2, // make it a member
-        ..Default::default()
-    };
+    // make it a member
+    let mut eng = Engine::<u64>::new(2, &RaftState::new(2), EngineConfig::default());
     eng.state.server_state = ServerState::Follower;
     eng.state.membership_state.committed = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m01()));
     eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m23()));
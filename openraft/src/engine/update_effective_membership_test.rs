@@ -3,8 +3,10 @@ use std::sync::Arc;
 use maplit::btreeset;
 
 use crate::core::ServerState;
+use crate::engine::testing::sid;
 use crate::engine::Command;
 use crate::engine::Engine;
+use crate::engine::EngineConfig;
 use crate::progress::Progress;
 use crate::EffectiveMembership;
 use crate::LeaderId;
@@ -12,6 +14,7 @@ use crate::LogId;
 use crate::Membership;
 use crate::MembershipState;
 use crate::MetricsChangeFlags;
+use crate::RaftState;
 use crate::Vote;
 
 crate::declare_raft_types!(
@@ -46,10 +49,8 @@ fn m4_356() -> Membership<u64> {
 }
 
 fn eng() -> Engine<u64> {
-    let mut eng = Engine::<u64> {
-        id: 2, // make it a member
-        ..Default::default()
-    };
+    // make it a member
+    let mut eng = Engine::<u64>::new(2, &RaftState::new(2), EngineConfig::default());
     eng.state.server_state = ServerState::Follower;
     eng.state.membership_state.committed = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m01()));
     eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m23()));
@@ -100,7 +101,7 @@ fn test_update_effective_membership_for_leader() -> anyhow::Result<()> {
     eng.state.server_state = ServerState::Leader;
     // Make it a real leader: voted for itself and vote is committed.
     eng.state.vote = Vote::new_committed(2, 2);
-    eng.state.new_leader();
+    eng.state.new_leader(&eng.config.commit_quorum);
 
     eng.update_effective_membership(&log_id(3, 4), &m34());
 
@@ -157,7 +158,7 @@ fn test_update_effective_membership_update_learner_process() -> anyhow::Result<(
     // Make it a real leader: voted for itself and vote is committed.
     eng.state.vote = Vote::new_committed(2, 2);
     eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m23_45()));
-    eng.state.new_leader();
+    eng.state.new_leader(&eng.config.commit_quorum);
 
     if let Some(l) = &mut eng.state.internal_server_state.leading_mut() {
         assert_eq!(&None, l.progress.get(&4));
@@ -205,3 +206,50 @@ fn test_update_effective_membership_update_learner_process() -> anyhow::Result<(
 
     Ok(())
 }
+
+#[test]
+fn test_update_effective_membership_string_node_id() -> anyhow::Result<()> {
+    // A node id that is not `Copy` works just like `u64`.
+
+    let log_id = |term, index| LogId::<String> {
+        leader_id: LeaderId { term, node_id: sid(1) },
+        index,
+    };
+    let m = |ids: &[u64]| Membership::<String>::new(vec![ids.iter().map(|x| sid(*x)).collect()], None);
+
+    let mut eng = Engine::<String>::new(sid(2), &RaftState::new(sid(2)), EngineConfig::default());
+    eng.state.server_state = ServerState::Leader;
+    eng.state.membership_state.committed = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m(&[0, 1])));
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m(&[2, 3])));
+    eng.state.vote = Vote::new_committed(2, sid(2));
+    eng.state.new_leader(&eng.config.commit_quorum);
+
+    eng.update_effective_membership(&log_id(3, 4), &m(&[3, 4]));
+
+    assert_eq!(
+        MembershipState {
+            committed: Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m(&[0, 1]))),
+            effective: Arc::new(EffectiveMembership::new(Some(log_id(3, 4)), m(&[3, 4])))
+        },
+        eng.state.membership_state
+    );
+    assert_eq!(ServerState::Leader, eng.state.server_state);
+
+    assert_eq!(
+        vec![
+            //
+            Command::UpdateMembership {
+                membership: Arc::new(EffectiveMembership::new(Some(log_id(3, 4)), m(&[3, 4]))),
+            },
+            Command::UpdateReplicationStreams {
+                add: vec![(sid(4), None)],
+                remove: vec![],
+            }
+        ],
+        eng.commands
+    );
+
+    assert!(eng.state.internal_server_state.leading().unwrap().progress.get(&sid(4)).is_none());
+
+    Ok(())
+}
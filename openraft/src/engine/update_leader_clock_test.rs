@@ -0,0 +1,108 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use maplit::btreeset;
+use tokio::time::Instant;
+
+use crate::engine::Engine;
+use crate::engine::EngineConfig;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::MetricsChangeFlags;
+use crate::RaftState;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn m123() -> Membership<u64> {
+    Membership::<u64>::new(vec![btreeset! {1,2,3}], None)
+}
+
+fn eng() -> Engine<u64> {
+    let mut eng = Engine::<u64>::new(1, &RaftState::new(1), EngineConfig::default());
+    eng.state.vote = Vote::new_committed(2, 1);
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m123()));
+    eng
+}
+
+#[test]
+fn test_update_leader_clock_no_leader() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    let eng0 = eng.clone();
+    eng.update_leader_clock(2, Instant::now());
+
+    assert_eq!(eng0, eng, "nothing changed");
+    assert_eq!(None, eng.leader_quorum_acked());
+
+    Ok(())
+}
+
+#[test]
+fn test_update_leader_clock_quorum_acked() -> anyhow::Result<()> {
+    let t0 = Instant::now();
+    let t1 = t0 + Duration::from_millis(10);
+
+    let mut eng = eng();
+    eng.state.new_leader(&eng.config.commit_quorum);
+
+    eng.update_leader_clock(2, t0);
+    assert_eq!(
+        Some(t0),
+        eng.leader_quorum_acked(),
+        "leader itself and node-2 acked at t0"
+    );
+    assert_eq!(
+        MetricsChangeFlags {
+            leader: true,
+            other_metrics: false
+        },
+        eng.metrics_flags
+    );
+
+    eng.metrics_flags.reset();
+    eng.update_leader_clock(3, t1);
+    assert_eq!(
+        Some(t1),
+        eng.leader_quorum_acked(),
+        "leader itself and node-3 acked at t1"
+    );
+    assert!(eng.metrics_flags.leader);
+
+    tracing::info!("--- an out of order acknowledgement does not move the clock backward");
+    {
+        eng.metrics_flags.reset();
+        eng.update_leader_clock(2, t0);
+        assert_eq!(Some(t1), eng.leader_quorum_acked());
+        assert!(!eng.metrics_flags.leader);
+    }
+
+    tracing::info!("--- a node not in the membership is ignored");
+    {
+        eng.update_leader_clock(4, t1 + Duration::from_millis(10));
+        assert_eq!(Some(t1), eng.leader_quorum_acked());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_reset_leader_clock() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.state.new_leader(&eng.config.commit_quorum);
+
+    eng.update_leader_clock(2, Instant::now());
+    assert!(eng.leader_quorum_acked().is_some());
+
+    eng.reset_leader_clock();
+    assert_eq!(None, eng.leader_quorum_acked());
+
+    Ok(())
+}
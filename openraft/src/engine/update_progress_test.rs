@@ -4,11 +4,14 @@ use maplit::btreeset;
 
 use crate::engine::Command;
 use crate::engine::Engine;
+use crate::engine::EngineConfig;
 use crate::engine::LogIdList;
+use crate::CommitQuorum;
 use crate::EffectiveMembership;
 use crate::LeaderId;
 use crate::LogId;
 use crate::Membership;
+use crate::RaftState;
 use crate::Vote;
 
 fn log_id(term: u64, index: u64) -> LogId<u64> {
@@ -27,10 +30,8 @@ fn m123() -> Membership<u64> {
 }
 
 fn eng() -> Engine<u64> {
-    let mut eng = Engine::<u64> {
-        id: 2, // make it a member
-        ..Default::default()
-    };
+    // make it a member
+    let mut eng = Engine::<u64>::new(2, &RaftState::new(2), EngineConfig::default());
     eng.state.vote = Vote::new_committed(2, 1);
     eng.state.membership_state.committed = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m01()));
     eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m123()));
@@ -56,7 +57,7 @@ fn test_update_progress_no_leader() -> anyhow::Result<()> {
 #[test]
 fn test_update_progress_update_leader_progress() -> anyhow::Result<()> {
     let mut eng = eng();
-    eng.state.new_leader();
+    eng.state.new_leader(&eng.config.commit_quorum);
 
     // progress: None, None, (1,2)
     eng.update_progress(3, Some(log_id(1, 2)));
@@ -103,6 +104,103 @@ fn test_update_progress_update_leader_progress() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_update_progress_commit_quorum_all() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.config.commit_quorum = CommitQuorum::All;
+    eng.state.new_leader(&eng.config.commit_quorum);
+
+    // progress: None, (2,1), (2,3); a quorum has (2,1), but not every voter
+    eng.update_progress(2, Some(log_id(2, 1)));
+    eng.update_progress(3, Some(log_id(2, 3)));
+    assert_eq!(None, eng.state.committed);
+    assert_eq!(0, eng.commands.len());
+
+    // progress: (2,4), (2,1), (2,3); committed: (2,1)
+    eng.update_progress(1, Some(log_id(2, 4)));
+    assert_eq!(Some(log_id(2, 1)), eng.state.committed);
+    assert_eq!(
+        vec![
+            Command::ReplicateCommitted {
+                committed: Some(log_id(2, 1))
+            },
+            Command::LeaderCommit {
+                since: None,
+                upto: log_id(2, 1)
+            }
+        ],
+        eng.commands
+    );
+
+    eng.commands = vec![];
+    // progress: (2,4), (2,5), (2,3); committed: (2,3)
+    eng.update_progress(2, Some(log_id(2, 5)));
+    assert_eq!(Some(log_id(2, 3)), eng.state.committed);
+    assert_eq!(
+        vec![
+            Command::ReplicateCommitted {
+                committed: Some(log_id(2, 3))
+            },
+            Command::LeaderCommit {
+                since: Some(log_id(2, 1)),
+                upto: log_id(2, 3)
+            }
+        ],
+        eng.commands
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_update_progress_commit_quorum_all_joint_with_witness() -> anyhow::Result<()> {
+    // Joint config, 4 is a witness, which is not required to commit.
+    let joint_eng = || {
+        let mut eng = eng();
+        eng.config.commit_quorum = CommitQuorum::All;
+
+        let m = Membership::<u64>::new(vec![btreeset! {1,2,3}, btreeset! {2,3,4,5}], None).with_witness(4);
+        eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m));
+        eng.state.new_leader(&eng.config.commit_quorum);
+        eng
+    };
+
+    {
+        let mut eng = joint_eng();
+
+        // progress: 1,2,3: (2,3); 4,5: None; not a quorum of the second config
+        eng.update_progress(1, Some(log_id(2, 3)));
+        eng.update_progress(2, Some(log_id(2, 3)));
+        eng.update_progress(3, Some(log_id(2, 3)));
+        assert_eq!(None, eng.state.committed);
+
+        // progress: 1,2,3,4: (2,3); 5: None; a quorum of every config, but 5 in the second config is required
+        eng.update_progress(4, Some(log_id(2, 3)));
+        assert_eq!(None, eng.state.committed);
+        assert_eq!(0, eng.commands.len());
+
+        // progress: 1,2,3,4: (2,3); 5: (2,1); committed: (2,1)
+        eng.update_progress(5, Some(log_id(2, 1)));
+        assert_eq!(Some(log_id(2, 1)), eng.state.committed);
+    }
+
+    // A lagging witness does not block the commit
+    {
+        let mut eng = joint_eng();
+
+        // progress: 1,2,3,5: (2,3); 4: None; committed: (2,3)
+        eng.update_progress(1, Some(log_id(2, 3)));
+        eng.update_progress(2, Some(log_id(2, 3)));
+        eng.update_progress(3, Some(log_id(2, 3)));
+        assert_eq!(None, eng.state.committed);
+
+        eng.update_progress(5, Some(log_id(2, 3)));
+        assert_eq!(Some(log_id(2, 3)), eng.state.committed);
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_update_progress_purge_upto_committed() -> anyhow::Result<()> {
     let mut eng = eng();
@@ -110,7 +208,7 @@ fn test_update_progress_purge_upto_committed() -> anyhow::Result<()> {
     eng.config.max_applied_log_to_keep = 0;
     eng.config.purge_batch_size = 1;
 
-    eng.state.new_leader();
+    eng.state.new_leader(&eng.config.commit_quorum);
 
     // progress: None, (2,1), (2,3); committed: (2,1)
     eng.update_progress(3, Some(log_id(1, 2)));
@@ -141,7 +239,7 @@ fn test_update_progress_purge_upto_committed_minus_1() -> anyhow::Result<()> {
     eng.config.max_applied_log_to_keep = 1;
     eng.config.purge_batch_size = 1;
 
-    eng.state.new_leader();
+    eng.state.new_leader(&eng.config.commit_quorum);
 
     // progress: None, (2,1), (2,3); committed: (2,1)
     eng.update_progress(3, Some(log_id(1, 2)));
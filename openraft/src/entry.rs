@@ -1,27 +1,35 @@
 use std::fmt::Debug;
+use std::sync::Arc;
 
 use crate::raft_types::RaftLogId;
 use crate::LogId;
 use crate::Membership;
 use crate::MessageSummary;
+use crate::Node;
 use crate::NodeId;
+use crate::NodeInfo;
 use crate::RaftTypeConfig;
 
 /// Defines operations on an entry payload.
-pub trait RaftPayload<NID: NodeId> {
+pub trait RaftPayload<NID: NodeId, N: NodeInfo = Node> {
     /// Return `Some(())` if the entry payload is blank.
     fn is_blank(&self) -> bool;
 
     /// Return `Some(&Membership)` if the entry payload is a membership payload.
-    fn get_membership(&self) -> Option<&Membership<NID>>;
+    fn get_membership(&self) -> Option<&Membership<NID, N>>;
 }
 
 /// Defines operations on an entry.
-pub trait RaftEntry<NID: NodeId>: RaftPayload<NID> + RaftLogId<NID> {}
+pub trait RaftEntry<NID: NodeId, N: NodeInfo = Node>: RaftPayload<NID, N> + RaftLogId<NID> {}
 
 /// Log entry payload variants.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize),
+    archive(check_bytes)
+)]
 pub enum EntryPayload<C: RaftTypeConfig> {
     /// An empty payload committed by a new cluster leader.
     Blank,
@@ -29,7 +37,7 @@ pub enum EntryPayload<C: RaftTypeConfig> {
     Normal(C::D),
 
     /// A change-membership log entry.
-    Membership(Membership<C::NodeId>),
+    Membership(Membership<C::NodeId, C::Node>),
 }
 
 impl<C: RaftTypeConfig> MessageSummary<EntryPayload<C>> for EntryPayload<C> {
@@ -47,27 +55,35 @@ impl<C: RaftTypeConfig> MessageSummary<EntryPayload<C>> for EntryPayload<C> {
 /// A Raft log entry.
 #[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize),
+    archive(check_bytes)
+)]
 pub struct Entry<C: RaftTypeConfig> {
     pub log_id: LogId<C::NodeId>,
 
     /// This entry's payload.
     pub payload: EntryPayload<C>,
+
+    /// Application metadata submitted along with the payload, e.g., a tracing id, see
+    /// [`ClientWriteRequest::with_context()`](`crate::raft::ClientWriteRequest::with_context`).
+    ///
+    /// It is not part of the state machine input: applying an entry must not depend on it. It is replicated to
+    /// followers as is, so that every node can trace applying the entry.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub context: Option<Vec<u8>>,
 }
 
 impl<C: RaftTypeConfig> Debug for Entry<C>
 where C::D: Debug
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Entry").field("log_id", &self.log_id).field("payload", &self.payload).finish()
-    }
-}
-
-impl<C: RaftTypeConfig> Default for Entry<C> {
-    fn default() -> Self {
-        Self {
-            log_id: LogId::default(),
-            payload: EntryPayload::Blank,
-        }
+        f.debug_struct("Entry")
+            .field("log_id", &self.log_id)
+            .field("payload", &self.payload)
+            .field("context", &self.context)
+            .finish()
     }
 }
 
@@ -77,6 +93,22 @@ impl<C: RaftTypeConfig> AsRef<Entry<C>> for Entry<C> {
     }
 }
 
+impl<C: RaftTypeConfig> Entry<C> {
+    /// Whether it is the entry a leader appends when it is established, given the log id of the entry before it.
+    ///
+    /// A leader appends this entry before any other one, thus it is the first entry proposed by its leader, i.e., the
+    /// entry at a term boundary. Its payload is blank, or the data returned by
+    /// [`RaftStorage::leader_established_data()`](`crate::RaftStorage::leader_established_data`). A membership
+    /// entry, such as the one written by `Raft::initialize()`, is never such an entry.
+    pub fn is_leader_established(&self, prev_log_id: Option<&LogId<C::NodeId>>) -> bool {
+        if self.payload.get_membership().is_some() {
+            return false;
+        }
+
+        prev_log_id.map(|p| &p.leader_id) != Some(&self.log_id.leader_id)
+    }
+}
+
 impl<C: RaftTypeConfig> MessageSummary<Entry<C>> for Entry<C> {
     fn summary(&self) -> String {
         format!("{}:{}", self.log_id, self.payload.summary())
@@ -88,8 +120,10 @@ impl<C: RaftTypeConfig> MessageSummary<Entry<C>> for Entry<C> {
 /// This is only used internally, to avoid memory copy for the payload.
 #[derive(Clone)]
 pub(crate) struct EntryRef<'p, C: RaftTypeConfig> {
-    pub log_id: LogId<C::NodeId>,
+    /// It is `None` until a log id is assigned to this entry.
+    pub log_id: Option<LogId<C::NodeId>>,
     pub payload: &'p EntryPayload<C>,
+    pub context: Option<&'p [u8]>,
 }
 
 impl<'p, C: RaftTypeConfig> Debug for EntryRef<'p, C>
@@ -102,24 +136,32 @@ where C::D: Debug
 
 impl<'p, C: RaftTypeConfig> MessageSummary<EntryRef<'p, C>> for EntryRef<'p, C> {
     fn summary(&self) -> String {
-        format!("{}:{}", self.log_id, self.payload.summary())
+        format!("{}:{}", self.log_id.summary(), self.payload.summary())
     }
 }
 
 impl<C: RaftTypeConfig> From<&Entry<C>> for Entry<C> {
     fn from(er: &Entry<C>) -> Self {
         Entry {
-            log_id: er.log_id,
+            log_id: er.log_id.clone(),
             payload: er.payload.clone(),
+            context: er.context.clone(),
         }
     }
 }
 
+impl<C: RaftTypeConfig> From<&Arc<Entry<C>>> for Entry<C> {
+    fn from(ent: &Arc<Entry<C>>) -> Self {
+        ent.as_ref().clone()
+    }
+}
+
 impl<'p, C: RaftTypeConfig> From<&EntryRef<'p, C>> for Entry<C> {
     fn from(er: &EntryRef<'p, C>) -> Self {
         Entry {
-            log_id: er.log_id,
+            log_id: er.get_log_id().clone(),
             payload: er.payload.clone(),
+            context: er.context.map(|c| c.to_vec()),
         }
     }
 }
@@ -127,20 +169,29 @@ impl<'p, C: RaftTypeConfig> From<&EntryRef<'p, C>> for Entry<C> {
 impl<'p, C: RaftTypeConfig> EntryRef<'p, C> {
     pub fn new(payload: &'p EntryPayload<C>) -> Self {
         Self {
-            log_id: Default::default(),
+            log_id: None,
             payload,
+            context: None,
+        }
+    }
+
+    pub fn with_context(payload: &'p EntryPayload<C>, context: Option<&'p [u8]>) -> Self {
+        Self {
+            log_id: None,
+            payload,
+            context,
         }
     }
 }
 
 // impl traits for EntryPayload
 
-impl<C: RaftTypeConfig> RaftPayload<C::NodeId> for EntryPayload<C> {
+impl<C: RaftTypeConfig> RaftPayload<C::NodeId, C::Node> for EntryPayload<C> {
     fn is_blank(&self) -> bool {
         matches!(self, EntryPayload::Blank)
     }
 
-    fn get_membership(&self) -> Option<&Membership<C::NodeId>> {
+    fn get_membership(&self) -> Option<&Membership<C::NodeId, C::Node>> {
         if let EntryPayload::Membership(m) = self {
             Some(m)
         } else {
@@ -151,12 +202,12 @@ impl<C: RaftTypeConfig> RaftPayload<C::NodeId> for EntryPayload<C> {
 
 // impl traits for Entry
 
-impl<C: RaftTypeConfig> RaftPayload<C::NodeId> for Entry<C> {
+impl<C: RaftTypeConfig> RaftPayload<C::NodeId, C::Node> for Entry<C> {
     fn is_blank(&self) -> bool {
         self.payload.is_blank()
     }
 
-    fn get_membership(&self) -> Option<&Membership<C::NodeId>> {
+    fn get_membership(&self) -> Option<&Membership<C::NodeId, C::Node>> {
         self.payload.get_membership()
     }
 }
@@ -167,32 +218,63 @@ impl<C: RaftTypeConfig> RaftLogId<C::NodeId> for Entry<C> {
     }
 
     fn set_log_id(&mut self, log_id: &LogId<C::NodeId>) {
-        self.log_id = *log_id;
+        self.log_id = log_id.clone();
+    }
+}
+
+impl<C: RaftTypeConfig> RaftEntry<C::NodeId, C::Node> for Entry<C> {}
+
+// impl traits for a shared Entry
+
+impl<C: RaftTypeConfig> MessageSummary<Arc<Entry<C>>> for Arc<Entry<C>> {
+    fn summary(&self) -> String {
+        self.as_ref().summary()
+    }
+}
+
+impl<C: RaftTypeConfig> RaftPayload<C::NodeId, C::Node> for Arc<Entry<C>> {
+    fn is_blank(&self) -> bool {
+        self.payload.is_blank()
+    }
+
+    fn get_membership(&self) -> Option<&Membership<C::NodeId, C::Node>> {
+        self.payload.get_membership()
+    }
+}
+
+impl<C: RaftTypeConfig> RaftLogId<C::NodeId> for Arc<Entry<C>> {
+    fn get_log_id(&self) -> &LogId<C::NodeId> {
+        &self.log_id
+    }
+
+    /// Clone the entry if it is shared.
+    fn set_log_id(&mut self, log_id: &LogId<C::NodeId>) {
+        Arc::make_mut(self).log_id = log_id.clone();
     }
 }
 
-impl<C: RaftTypeConfig> RaftEntry<C::NodeId> for Entry<C> {}
+impl<C: RaftTypeConfig> RaftEntry<C::NodeId, C::Node> for Arc<Entry<C>> {}
 
 // impl traits for RefEntry
 
-impl<'p, C: RaftTypeConfig> RaftPayload<C::NodeId> for EntryRef<'p, C> {
+impl<'p, C: RaftTypeConfig> RaftPayload<C::NodeId, C::Node> for EntryRef<'p, C> {
     fn is_blank(&self) -> bool {
         self.payload.is_blank()
     }
 
-    fn get_membership(&self) -> Option<&Membership<C::NodeId>> {
+    fn get_membership(&self) -> Option<&Membership<C::NodeId, C::Node>> {
         self.payload.get_membership()
     }
 }
 
 impl<'p, C: RaftTypeConfig> RaftLogId<C::NodeId> for EntryRef<'p, C> {
     fn get_log_id(&self) -> &LogId<C::NodeId> {
-        &self.log_id
+        self.log_id.as_ref().expect("log id is assigned before being read")
     }
 
     fn set_log_id(&mut self, log_id: &LogId<C::NodeId>) {
-        self.log_id = *log_id;
+        self.log_id = Some(log_id.clone());
     }
 }
 
-impl<'p, C: RaftTypeConfig> RaftEntry<C::NodeId> for EntryRef<'p, C> {}
+impl<'p, C: RaftTypeConfig> RaftEntry<C::NodeId, C::Node> for EntryRef<'p, C> {}
@@ -0,0 +1,44 @@
+#[cfg(feature = "rkyv")]
+#[test]
+fn test_entry_rkyv_round_trip() -> anyhow::Result<()> {
+    use maplit::btreemap;
+    use maplit::btreeset;
+    use rkyv::Deserialize;
+
+    use crate::engine::testing::Config;
+    use crate::Entry;
+    use crate::EntryPayload;
+    use crate::LeaderId;
+    use crate::LogId;
+    use crate::Membership;
+    use crate::Node;
+
+    let membership = Membership::<u64>::with_nodes(vec![btreeset! {1,2}], btreemap! {
+        1 => Node::new("127.0.0.1:21001"),
+        2 => Node::new("127.0.0.1:21002").with_election_priority(5),
+        3 => Node::new("127.0.0.1:21003"),
+    })?;
+
+    let entry = Entry::<Config> {
+        log_id: LogId {
+            leader_id: LeaderId::new(3, 1),
+            index: 5,
+        },
+        payload: EntryPayload::Membership(membership.clone()),
+        context: Some(b"trace-id".to_vec()),
+    };
+
+    let bytes = rkyv::to_bytes::<_, 256>(&entry)?;
+
+    let archived = rkyv::check_archived_root::<Entry<Config>>(&bytes[..]).map_err(|e| anyhow::anyhow!("{}", e))?;
+    let got: Entry<Config> = archived.deserialize(&mut rkyv::Infallible).unwrap();
+
+    assert_eq!(entry.log_id, got.log_id);
+    assert_eq!(entry.context, got.context);
+    match got.payload {
+        EntryPayload::Membership(m) => assert_eq!(membership, m),
+        _ => unreachable!("expect a membership payload"),
+    }
+
+    Ok(())
+}
@@ -2,17 +2,23 @@
 
 use std::collections::BTreeSet;
 use std::error::Error;
+use std::fmt;
 use std::fmt::Debug;
 use std::time::Duration;
 
 use anyerror::AnyError;
 
+use crate::compression::CompressionAlgo;
+use crate::config::ConfigError;
 use crate::raft::AppendEntriesResponse;
+use crate::raft_types::SnapshotId;
 use crate::raft_types::SnapshotSegmentId;
+use crate::AppError;
 use crate::LogId;
 use crate::Membership;
 use crate::Node;
 use crate::NodeId;
+use crate::NodeInfo;
 use crate::RPCTypes;
 use crate::StorageError;
 use crate::Vote;
@@ -61,6 +67,9 @@ where E: TryInto<Fatal<NID>> + Clone
 pub enum AppendEntriesError<NID: NodeId> {
     #[error(transparent)]
     Fatal(#[from] Fatal<NID>),
+
+    #[error(transparent)]
+    Compression(#[from] CompressionError),
 }
 
 // TODO: not used, remove
@@ -78,6 +87,22 @@ pub enum InstallSnapshotError<NID: NodeId> {
     #[error(transparent)]
     SnapshotMismatch(#[from] SnapshotMismatch),
 
+    /// The received data does not match the checksum sent by the leader. The leader should re-send it.
+    #[error(transparent)]
+    ChecksumMismatch(#[from] ChecksumMismatch),
+
+    #[error(transparent)]
+    Fatal(#[from] Fatal<NID>),
+}
+
+/// An error related to a [`Raft::install_local_snapshot()`](`crate::Raft::install_local_snapshot`) request.
+#[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum InstallLocalSnapshotError<NID: NodeId> {
+    /// The snapshot is behind the logs this node has already applied.
+    #[error(transparent)]
+    SnapshotBehind(#[from] SnapshotBehind<NID>),
+
     #[error(transparent)]
     Fatal(#[from] Fatal<NID>),
 }
@@ -85,9 +110,9 @@ pub enum InstallSnapshotError<NID: NodeId> {
 /// An error related to a is_leader request.
 #[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
-pub enum CheckIsLeaderError<NID: NodeId> {
+pub enum CheckIsLeaderError<NID: NodeId, N: NodeInfo = Node> {
     #[error(transparent)]
-    ForwardToLeader(#[from] ForwardToLeader<NID>),
+    ForwardToLeader(#[from] ForwardToLeader<NID, N>),
 
     #[error(transparent)]
     QuorumNotEnough(#[from] QuorumNotEnough<NID>),
@@ -96,18 +121,119 @@ pub enum CheckIsLeaderError<NID: NodeId> {
     Fatal(#[from] Fatal<NID>),
 }
 
+/// An error related to a [`Raft::follower_read()`](`crate::Raft::follower_read`) request.
+#[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum FollowerReadError<NID: NodeId, N: NodeInfo = Node> {
+    /// This node is the leader, or a candidate without a known leader.
+    #[error(transparent)]
+    ForwardToLeader(#[from] ForwardToLeader<NID, N>),
+
+    /// This follower has not heard from the leader within the staleness bound.
+    #[error(transparent)]
+    StaleRead(#[from] StaleRead<NID, N>),
+
+    #[error(transparent)]
+    Fatal(#[from] Fatal<NID>),
+}
+
+/// An error related to a transfer-leadership request.
+#[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum TransferLeaderError<NID: NodeId, N: NodeInfo = Node> {
+    #[error(transparent)]
+    ForwardToLeader(#[from] ForwardToLeader<NID, N>),
+
+    /// The target is not a voter in the effective membership.
+    #[error(transparent)]
+    NotInMembers(#[from] NotInMembers<NID, N>),
+
+    /// The target is a witness, which can not become the leader.
+    #[error(transparent)]
+    IsWitness(#[from] IsWitness<NID>),
+
+    /// The target did not catch up with the leader in time.
+    #[error(transparent)]
+    Timeout(#[from] Timeout<NID>),
+
+    /// The target refused to start an election.
+    #[error(transparent)]
+    Rejected(#[from] TransferLeaderRejected<NID>),
+
+    /// Failed to send the TimeoutNow RPC to the target.
+    #[error(transparent)]
+    RPCError(#[from] RPCError<NID, VoteError<NID>, N>),
+
+    #[error(transparent)]
+    Fatal(#[from] Fatal<NID>),
+}
+
+/// An error related to a [`Raft::trigger_elect()`](`crate::Raft::trigger_elect`) request.
+#[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum TriggerElectError<NID: NodeId, N: NodeInfo = Node> {
+    /// This node is not a voter in the effective membership, e.g., it is a learner.
+    #[error(transparent)]
+    NotInMembers(#[from] NotInMembers<NID, N>),
+
+    /// This node is a witness, which never starts an election.
+    #[error(transparent)]
+    IsWitness(#[from] IsWitness<NID>),
+
+    #[error(transparent)]
+    Fatal(#[from] Fatal<NID>),
+}
+
+/// An error related to a [`Raft::update_config()`](`crate::Raft::update_config`) request.
+#[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
+pub enum UpdateConfigError<NID: NodeId> {
+    /// The updated config does not pass [`Config::validate()`](`crate::Config::validate`).
+    #[error(transparent)]
+    InvalidConfig(#[from] ConfigError),
+
+    #[error(transparent)]
+    Fatal(#[from] Fatal<NID>),
+}
+
+/// An error related to a [`Raft::purge_logs_upto()`](`crate::Raft::purge_logs_upto`) request.
+#[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum PurgeLogsError<NID: NodeId> {
+    /// The logs to purge are not yet applied.
+    #[error(transparent)]
+    NotApplied(#[from] LogNotApplied<NID>),
+
+    /// A replication target of this leader still needs the logs to purge.
+    #[error(transparent)]
+    ReplicationLagging(#[from] ReplicationLagging<NID>),
+
+    #[error(transparent)]
+    Fatal(#[from] Fatal<NID>),
+}
+
 /// An error related to a client write request.
 #[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
 #[derive(PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
-pub enum ClientWriteError<NID: NodeId> {
+pub enum ClientWriteError<NID: NodeId, N: NodeInfo = Node, E: AppError = Infallible> {
     #[error(transparent)]
-    ForwardToLeader(#[from] ForwardToLeader<NID>),
+    ForwardToLeader(#[from] ForwardToLeader<NID, N>),
 
     /// When writing a change-membership entry.
     #[error(transparent)]
     ChangeMembershipError(#[from] ChangeMembershipError<NID>),
 
+    /// The state machine rejected the write with an application defined error.
+    #[error(transparent)]
+    #[try_into(ignore)]
+    AppError(E),
+
+    /// The leader stepped down because it lost contact with a quorum before the write is committed.
+    ///
+    /// The write may still be committed by a new leader.
+    #[error(transparent)]
+    QuorumNotEnough(#[from] QuorumNotEnough<NID>),
+
     #[error(transparent)]
     Fatal(#[from] Fatal<NID>),
 }
@@ -130,25 +256,36 @@ pub enum ChangeMembershipError<NID: NodeId> {
 
     #[error(transparent)]
     MissingNodeInfo(#[from] MissingNodeInfo<NID>),
+
+    #[error(transparent)]
+    QuorumNotEnough(#[from] QuorumNotEnough<NID>),
+
+    /// A witness is asked to become a learner.
+    #[error(transparent)]
+    WitnessToLearner(#[from] WitnessToLearner<NID>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
-pub enum AddLearnerError<NID: NodeId> {
+pub enum AddLearnerError<NID: NodeId, N: NodeInfo = Node> {
     #[error(transparent)]
-    ForwardToLeader(#[from] ForwardToLeader<NID>),
+    ForwardToLeader(#[from] ForwardToLeader<NID, N>),
 
     #[error(transparent)]
     MissingNodeInfo(#[from] MissingNodeInfo<NID>),
 
+    /// The learner is added, but it did not catch up with the leader before a blocking `add_learner()` returns.
+    #[error(transparent)]
+    NotCaughtUp(#[from] LearnerNotCaughtUp<NID>),
+
     #[error(transparent)]
     Fatal(#[from] Fatal<NID>),
 }
 
-impl<NID: NodeId> TryFrom<AddLearnerError<NID>> for ForwardToLeader<NID> {
-    type Error = AddLearnerError<NID>;
+impl<NID: NodeId, N: NodeInfo> TryFrom<AddLearnerError<NID, N>> for ForwardToLeader<NID, N> {
+    type Error = AddLearnerError<NID, N>;
 
-    fn try_from(value: AddLearnerError<NID>) -> Result<Self, Self::Error> {
+    fn try_from(value: AddLearnerError<NID, N>) -> Result<Self, Self::Error> {
         if let AddLearnerError::ForwardToLeader(e) = value {
             return Ok(e);
         }
@@ -159,12 +296,12 @@ impl<NID: NodeId> TryFrom<AddLearnerError<NID>> for ForwardToLeader<NID> {
 /// The set of errors which may take place when initializing a pristine Raft node.
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, derive_more::TryInto)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
-pub enum InitializeError<NID: NodeId> {
+pub enum InitializeError<NID: NodeId, N: NodeInfo = Node> {
     #[error(transparent)]
     NotAllowed(#[from] NotAllowed<NID>),
 
     #[error(transparent)]
-    NotInMembers(#[from] NotInMembers<NID>),
+    NotInMembers(#[from] NotInMembers<NID, N>),
 
     #[error(transparent)]
     NotAMembershipEntry(#[from] NotAMembershipEntry),
@@ -194,19 +331,25 @@ impl<NID: NodeId> From<StorageError<NID>> for InstallSnapshotError<NID> {
         f.into()
     }
 }
-impl<NID: NodeId> From<StorageError<NID>> for CheckIsLeaderError<NID> {
+impl<NID: NodeId> From<StorageError<NID>> for InstallLocalSnapshotError<NID> {
+    fn from(s: StorageError<NID>) -> Self {
+        let f: Fatal<NID> = s.into();
+        f.into()
+    }
+}
+impl<NID: NodeId, N: NodeInfo> From<StorageError<NID>> for CheckIsLeaderError<NID, N> {
     fn from(s: StorageError<NID>) -> Self {
         let f: Fatal<NID> = s.into();
         f.into()
     }
 }
-impl<NID: NodeId> From<StorageError<NID>> for InitializeError<NID> {
+impl<NID: NodeId, N: NodeInfo> From<StorageError<NID>> for InitializeError<NID, N> {
     fn from(s: StorageError<NID>) -> Self {
         let f: Fatal<NID> = s.into();
         f.into()
     }
 }
-impl<NID: NodeId> From<StorageError<NID>> for AddLearnerError<NID> {
+impl<NID: NodeId, N: NodeInfo> From<StorageError<NID>> for AddLearnerError<NID, N> {
     fn from(s: StorageError<NID>) -> Self {
         let f: Fatal<NID> = s.into();
         f.into()
@@ -216,7 +359,7 @@ impl<NID: NodeId> From<StorageError<NID>> for AddLearnerError<NID> {
 /// Error variants related to the Replication.
 #[derive(Debug, thiserror::Error)]
 #[allow(clippy::large_enum_variant)]
-pub enum ReplicationError<NID: NodeId> {
+pub enum ReplicationError<NID: NodeId, N: NodeInfo = Node> {
     #[error(transparent)]
     HigherVote(#[from] HigherVote<NID>),
 
@@ -244,7 +387,27 @@ pub enum ReplicationError<NID: NodeId> {
     Network(#[from] NetworkError),
 
     #[error(transparent)]
-    RemoteError(#[from] RemoteError<NID, AppendEntriesError<NID>>),
+    Unreachable(#[from] Unreachable),
+
+    #[error(transparent)]
+    RemoteError(#[from] RemoteError<NID, AppendEntriesError<NID>, N>),
+}
+
+/// Error occurred when sending a snapshot with [`RaftNetwork::full_snapshot`](`crate::RaftNetwork::full_snapshot`).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum StreamingError<NID: NodeId> {
+    /// Failed to read the snapshot data. Replication to the target is shut down.
+    #[error(transparent)]
+    StorageError(#[from] StorageError<NID>),
+
+    /// Failed to send the snapshot. Openraft sends it again after a heartbeat interval.
+    #[error(transparent)]
+    Network(#[from] NetworkError),
+
+    /// The target can not be reached. Openraft sends the snapshot again after a heartbeat interval.
+    #[error(transparent)]
+    Unreachable(#[from] Unreachable),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
@@ -253,7 +416,7 @@ pub enum ReplicationError<NID: NodeId> {
     derive(serde::Deserialize, serde::Serialize),
     serde(bound = "T:serde::Serialize + for <'d> serde::Deserialize<'d>")
 )]
-pub enum RPCError<NID: NodeId, T: Error> {
+pub enum RPCError<NID: NodeId, T: Error, N: NodeInfo = Node> {
     #[error(transparent)]
     NodeNotFound(#[from] NodeNotFound<NID>),
 
@@ -263,22 +426,37 @@ pub enum RPCError<NID: NodeId, T: Error> {
     #[error(transparent)]
     Network(#[from] NetworkError),
 
+    /// The target node can not be reached, e.g., it is down or the connection is refused.
+    ///
+    /// Unlike a [`NetworkError`], which may be transient, replication to an unreachable target backs off with the
+    /// [`BackoffPolicy`](`crate::network::BackoffPolicy`) returned by
+    /// [`RaftNetwork::backoff()`](`crate::RaftNetwork::backoff`).
     #[error(transparent)]
-    RemoteError(#[from] RemoteError<NID, T>),
+    Unreachable(#[from] Unreachable),
+
+    /// The target rejects the request because it is too large, e.g., it exceeds the message size limit of the
+    /// transport.
+    ///
+    /// Replication sends the logs again in smaller AppendEntries requests.
+    #[error(transparent)]
+    PayloadTooLarge(#[from] PayloadTooLarge),
+
+    #[error(transparent)]
+    RemoteError(#[from] RemoteError<NID, T, N>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[error("error occur on remote peer {target}: {source}")]
-pub struct RemoteError<NID: NodeId, T: std::error::Error> {
+pub struct RemoteError<NID: NodeId, T: std::error::Error, N: NodeInfo = Node> {
     // #[serde(bound = "")]
     #[cfg_attr(feature = "serde", serde(bound = ""))]
     pub target: NID,
-    pub target_node: Option<Node>,
+    pub target_node: Option<N>,
     pub source: T,
 }
 
-impl<NID: NodeId, T: std::error::Error> RemoteError<NID, T> {
+impl<NID: NodeId, T: std::error::Error, N: NodeInfo> RemoteError<NID, T, N> {
     pub fn new(target: NID, e: T) -> Self {
         Self {
             target,
@@ -286,7 +464,7 @@ impl<NID: NodeId, T: std::error::Error> RemoteError<NID, T> {
             source: e,
         }
     }
-    pub fn new_with_node(target: NID, node: Node, e: T) -> Self {
+    pub fn new_with_node(target: NID, node: N, e: T) -> Self {
         Self {
             target,
             target_node: Some(node),
@@ -311,6 +489,24 @@ pub struct CommittedAdvanceTooMany {
     pub target_index: u64,
 }
 
+/// Failed to compress or decompress the log entries in an `AppendEntriesRequest`.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[error("failed to compress or decompress entries with {algo}: {source}")]
+pub struct CompressionError {
+    pub algo: CompressionAlgo,
+    pub source: AnyError,
+}
+
+impl CompressionError {
+    pub fn new<E: Error + 'static>(algo: CompressionAlgo, e: &E) -> Self {
+        Self {
+            algo,
+            source: AnyError::new(e),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 #[error("NetworkError: {source}")]
@@ -327,6 +523,69 @@ impl NetworkError {
     }
 }
 
+/// Error that indicates a node can not be reached, e.g., it is down, and RPCs to it should be backed off.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("Unreachable node: {source}")]
+pub struct Unreachable {
+    #[from]
+    source: AnyError,
+}
+
+impl Unreachable {
+    pub fn new<E: Error + 'static>(e: &E) -> Self {
+        Self {
+            source: AnyError::new(e),
+        }
+    }
+}
+
+/// Error that indicates an RPC payload is too large to send, with a hint of how many entries or bytes an
+/// AppendEntries can carry at most.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("RPC payload too large: {action}, entries_hint: {entries_hint}, bytes_hint: {bytes_hint}")]
+pub struct PayloadTooLarge {
+    action: RPCTypes,
+    entries_hint: u64,
+    bytes_hint: u64,
+}
+
+impl PayloadTooLarge {
+    /// Create an error for an AppendEntries request that carries more than `entries_hint` entries.
+    pub fn new_entries_hint(entries_hint: u64) -> Self {
+        Self {
+            action: RPCTypes::AppendEntries,
+            entries_hint,
+            bytes_hint: u64::MAX,
+        }
+    }
+
+    /// Create an error for an AppendEntries request whose entries are larger than `bytes_hint` bytes, as counted by
+    /// [`RaftTypeConfig::entry_size()`](`crate::RaftTypeConfig::entry_size`).
+    pub fn new_bytes_hint(bytes_hint: u64) -> Self {
+        Self {
+            action: RPCTypes::AppendEntries,
+            entries_hint: u64::MAX,
+            bytes_hint,
+        }
+    }
+
+    pub fn action(&self) -> RPCTypes {
+        self.action.clone()
+    }
+
+    /// The max number of entries the target accepts in one request, or `u64::MAX` if there is no hint.
+    pub fn entries_hint(&self) -> u64 {
+        self.entries_hint
+    }
+
+    /// The max number of bytes of entries the target accepts in one request, or `u64::MAX` if there is no hint.
+    pub fn bytes_hint(&self) -> u64 {
+        self.bytes_hint
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 #[error("timeout after {timeout:?} when {action} {id}->{target}")]
@@ -347,10 +606,42 @@ pub struct LackEntry<NID: NodeId> {
 
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize),
+    archive(check_bytes)
+)]
 #[error("has to forward request to: {leader_id:?}, {leader_node:?}")]
-pub struct ForwardToLeader<NID: NodeId> {
+pub struct ForwardToLeader<NID: NodeId, N: NodeInfo = Node> {
+    /// The id of the leader this node knows of. It is `None` if this node does not know a leader.
     pub leader_id: Option<NID>,
-    pub leader_node: Option<Node>,
+
+    /// The node info of the leader, found in the effective membership, e.g., the address to send the request to.
+    ///
+    /// It is `None` if this node does not know a leader, or the leader is added without node info.
+    pub leader_node: Option<N>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("stale read: last contact with the leader: {elapsed:?} ago, max staleness: {max_staleness:?}, {forward}")]
+pub struct StaleRead<NID: NodeId, N: NodeInfo = Node> {
+    /// The leader to read from instead.
+    pub forward: ForwardToLeader<NID, N>,
+
+    /// The time elapsed since this node last heard from the leader. It is `None` if it has not heard from the leader
+    /// of its current vote.
+    pub elapsed: Option<Duration>,
+
+    pub max_staleness: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("transferring leadership to {target} is rejected, target vote: {target_vote}")]
+pub struct TransferLeaderRejected<NID: NodeId> {
+    pub target: NID,
+    pub target_vote: Vote<NID>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
@@ -361,6 +652,44 @@ pub struct SnapshotMismatch {
     pub got: SnapshotSegmentId,
 }
 
+/// The checksum of a snapshot chunk, or of a whole snapshot if `offset` is `None`, does not match the data.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error(
+    "snapshot checksum mismatch, snapshot: {id}, chunk offset: {offset:?}, expect: {expect:#010x}, got: {got:#010x}"
+)]
+pub struct ChecksumMismatch {
+    pub id: SnapshotId,
+    pub offset: Option<u64>,
+    pub expect: u32,
+    pub got: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("snapshot last log id {last_log_id} is behind the last applied log id {last_applied:?}")]
+pub struct SnapshotBehind<NID: NodeId> {
+    pub last_log_id: LogId<NID>,
+    pub last_applied: Option<LogId<NID>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("can not purge logs upto {log_id}: not applied, last applied: {last_applied:?}")]
+pub struct LogNotApplied<NID: NodeId> {
+    pub log_id: LogId<NID>,
+    pub last_applied: Option<LogId<NID>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("can not purge logs upto {log_id}: replication target {target} still needs them, matched: {matched:?}")]
+pub struct ReplicationLagging<NID: NodeId> {
+    pub log_id: LogId<NID>,
+    pub target: NID,
+    pub matched: Option<LogId<NID>>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 #[error("not enough for a quorum, cluster: {cluster}, got: {got:?}")]
@@ -384,6 +713,47 @@ pub struct LearnerNotFound<NID: NodeId> {
     pub node_id: NID,
 }
 
+/// A learner added by a blocking [`Raft::add_learner()`](`crate::Raft::add_learner`) did not catch up with the
+/// leader, with the progress it reached.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("learner {node_id} did not catch up: {reason}, matched: {matched:?}, leader last log index: {leader_last_log_index:?}")]
+pub struct LearnerNotCaughtUp<NID: NodeId> {
+    pub node_id: NID,
+
+    /// The last log id known to be replicated to the learner.
+    pub matched: Option<LogId<NID>>,
+
+    /// The last log index on the leader when it stopped waiting.
+    pub leader_last_log_index: Option<u64>,
+
+    pub reason: NotCaughtUpReason,
+}
+
+/// Why a blocking [`Raft::add_learner()`](`crate::Raft::add_learner`) stopped waiting for the learner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum NotCaughtUpReason {
+    /// The learner did not catch up within `Config::add_learner_timeout`, e.g., it is unreachable.
+    Timeout,
+
+    /// This node is no longer the leader.
+    LeaderLost,
+
+    /// The learner is removed from the membership.
+    Removed,
+}
+
+impl fmt::Display for NotCaughtUpReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotCaughtUpReason::Timeout => write!(f, "timeout"),
+            NotCaughtUpReason::LeaderLost => write!(f, "leader lost"),
+            NotCaughtUpReason::Removed => write!(f, "learner removed"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 #[error("replication to learner {node_id} is lagging {distance}, matched: {matched:?}, can not add as member")]
@@ -412,9 +782,27 @@ pub struct MissingNodeInfo<NID: NodeId> {
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 #[error("node {node_id} has to be a member. membership:{membership:?}")]
-pub struct NotInMembers<NID: NodeId> {
+pub struct NotInMembers<NID: NodeId, N: NodeInfo = Node> {
+    pub node_id: NID,
+    pub membership: Membership<NID, N>,
+}
+
+/// The node is a witness: it votes and acknowledges logs, but it can not become the leader.
+///
+/// See [`Membership::with_witness()`](`crate::Membership::with_witness`).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("node {node_id} is a witness, it can not become the leader")]
+pub struct IsWitness<NID: NodeId> {
+    pub node_id: NID,
+}
+
+/// A witness does not have the log payloads to serve as a learner: it can only be removed from the cluster.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("node {node_id} is a witness, it can not become a learner")]
+pub struct WitnessToLearner<NID: NodeId> {
     pub node_id: NID,
-    pub membership: Membership<NID>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
@@ -1,8 +1,11 @@
 use std::sync::Arc;
 
+use crate::async_runtime::Instant;
 use crate::leader::Leader;
 use crate::EffectiveMembership;
+use crate::Node;
 use crate::NodeId;
+use crate::NodeInfo;
 
 /// In openraft there are only two state for a server:
 /// Leading(raft leader or raft candidate) and following(raft follower or raft learner):
@@ -16,11 +19,11 @@ use crate::NodeId;
 ///   become leader. A following state that is not a member is just a learner.
 #[derive(Clone, Debug)]
 #[derive(PartialEq, Eq)]
-pub(crate) enum InternalServerState<NID: NodeId> {
+pub(crate) enum InternalServerState<NID: NodeId, N: NodeInfo = Node, I: Instant = tokio::time::Instant> {
     /// Leader or candidate.
     ///
     /// `vote.committed==true` means it is a leader.
-    Leading(Leader<NID, Arc<EffectiveMembership<NID>>>),
+    Leading(Leader<NID, Arc<EffectiveMembership<NID, N>>, I>),
 
     /// Follower or learner.
     ///
@@ -28,21 +31,21 @@ pub(crate) enum InternalServerState<NID: NodeId> {
     Following,
 }
 
-impl<NID: NodeId> Default for InternalServerState<NID> {
+impl<NID: NodeId, N: NodeInfo, I: Instant> Default for InternalServerState<NID, N, I> {
     fn default() -> Self {
         Self::Following
     }
 }
 
-impl<NID: NodeId> InternalServerState<NID> {
-    pub(crate) fn leading(&self) -> Option<&Leader<NID, Arc<EffectiveMembership<NID>>>> {
+impl<NID: NodeId, N: NodeInfo, I: Instant> InternalServerState<NID, N, I> {
+    pub(crate) fn leading(&self) -> Option<&Leader<NID, Arc<EffectiveMembership<NID, N>>, I>> {
         match self {
             InternalServerState::Leading(l) => Some(l),
             InternalServerState::Following => None,
         }
     }
 
-    pub(crate) fn leading_mut(&mut self) -> Option<&mut Leader<NID, Arc<EffectiveMembership<NID>>>> {
+    pub(crate) fn leading_mut(&mut self) -> Option<&mut Leader<NID, Arc<EffectiveMembership<NID, N>>, I>> {
         match self {
             InternalServerState::Leading(l) => Some(l),
             InternalServerState::Following => None,
@@ -1,7 +1,9 @@
 use std::collections::BTreeSet;
 
+use crate::async_runtime::Instant;
 use crate::progress::Progress;
 use crate::progress::VecProgress;
+use crate::quorum::CommitQuorumSet;
 use crate::quorum::QuorumSet;
 use crate::LogId;
 use crate::NodeId;
@@ -21,23 +23,44 @@ use crate::NodeId;
 /// But instead it will be able to upgrade its `leader_id` without losing leadership.
 #[derive(Clone, Debug)]
 #[derive(PartialEq, Eq)]
-pub(crate) struct Leader<NID: NodeId, QS: QuorumSet<NID>> {
+pub(crate) struct Leader<NID: NodeId, QS: QuorumSet<NID>, I: Instant = tokio::time::Instant> {
     /// Which nodes have granted the the vote of this node.
     pub(crate) vote_granted_by: BTreeSet<NID>,
 
     /// Tracks the replication progress and committed index
-    pub(crate) progress: VecProgress<NID, Option<LogId<NID>>, QS>,
+    ///
+    /// A log is committed when it is acknowledged by a quorum that includes every required voter, see
+    /// [`CommitQuorumSet`].
+    pub(crate) progress: VecProgress<NID, Option<LogId<NID>>, CommitQuorumSet<NID, QS>>,
+
+    /// Tracks the latest time at which each node acknowledged this leader.
+    ///
+    /// The time is when the acknowledged request was sent, not when the response was received.
+    /// The granted value is the latest time at which a quorum acknowledged this leader, i.e., the base of a read
+    /// lease.
+    pub(crate) clock_progress: VecProgress<NID, Option<I>, QS>,
 }
 
-impl<NID, QS> Leader<NID, QS>
+impl<NID, QS, I> Leader<NID, QS, I>
 where
     NID: NodeId,
-    QS: QuorumSet<NID> + 'static,
+    QS: QuorumSet<NID> + Clone + 'static,
+    I: Instant,
 {
-    pub(crate) fn new(quorum_set: QS, learner_ids: impl Iterator<Item = NID>) -> Self {
+    /// Create a leader with the voters in `quorum_set`. A log it proposes has to be acknowledged by a quorum that
+    /// also includes every node in `commit_required`.
+    pub(crate) fn new(
+        quorum_set: QS,
+        learner_ids: impl Iterator<Item = NID>,
+        commit_required: impl Iterator<Item = NID>,
+    ) -> Self {
+        let learner_ids = learner_ids.collect::<Vec<_>>();
+        let commit_quorum_set = CommitQuorumSet::new(quorum_set.clone(), commit_required);
+
         Self {
             vote_granted_by: BTreeSet::new(),
-            progress: VecProgress::new(quorum_set, learner_ids),
+            progress: VecProgress::new(commit_quorum_set, learner_ids.iter().cloned()),
+            clock_progress: VecProgress::new(quorum_set, learner_ids.into_iter()),
         }
     }
 
@@ -48,7 +71,7 @@ where
 
     /// Return if a quorum of `membership` has granted it.
     pub(crate) fn is_vote_granted(&self) -> bool {
-        let qs = self.progress.quorum_set();
+        let qs = self.progress.quorum_set().quorum_set();
         qs.is_quorum(self.vote_granted_by.iter())
     }
 }
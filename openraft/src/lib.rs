@@ -16,6 +16,7 @@
 //!   messages.
 
 mod change_members;
+mod compression;
 mod config;
 mod core;
 mod defensive;
@@ -24,14 +25,19 @@ mod membership;
 mod node;
 mod progress;
 mod quorum;
+mod raft_group;
 mod raft_types;
 mod replication;
+mod responder;
+mod rng;
 mod storage_error;
 mod store_ext;
+mod store_transform;
 mod store_wrapper;
 mod summary;
 mod vote;
 
+pub mod async_runtime;
 mod engine;
 pub mod error;
 mod internal_server_state;
@@ -46,17 +52,31 @@ pub mod testing;
 pub mod timer;
 pub mod versioned;
 
+#[cfg(test)] mod async_runtime_test;
+#[cfg(test)] mod entry_test;
+#[cfg(test)] mod node_test;
 #[cfg(test)] mod raft_state_test;
+#[cfg(test)] mod raft_test;
 
 pub use anyerror;
 pub use anyerror::AnyError;
 pub use async_trait;
 pub use metrics::ReplicationTargetMetrics;
 
+pub use crate::async_runtime::AsyncRuntime;
+pub use crate::async_runtime::Instant;
+pub use crate::async_runtime::TokioRuntime;
 pub use crate::change_members::ChangeMembers;
+pub use crate::compression::CompressedEntries;
+pub use crate::compression::CompressionAlgo;
+pub use crate::config::CommitQuorum;
 pub use crate::config::Config;
 pub use crate::config::ConfigError;
+pub use crate::config::ConfigUpdate;
 pub use crate::config::SnapshotPolicy;
+pub use crate::config::SnapshotPolicyContext;
+pub use crate::config::SnapshotPredicate;
+pub use crate::config::SnapshotSource;
 pub use crate::core::ServerState;
 pub use crate::defensive::DefensiveCheck;
 pub use crate::defensive::DefensiveCheckBase;
@@ -66,14 +86,21 @@ pub use crate::entry::RaftPayload;
 pub use crate::membership::EffectiveMembership;
 pub use crate::membership::Membership;
 pub use crate::membership::MembershipState;
+pub use crate::metrics::RaftDataMetrics;
 pub use crate::metrics::RaftMetrics;
+pub use crate::metrics::RaftServerMetrics;
+pub use crate::network::BackoffPolicy;
+pub use crate::network::ExponentialBackoff;
+pub use crate::network::RPCOption;
 pub use crate::network::RPCTypes;
 pub use crate::network::RaftNetwork;
 pub use crate::network::RaftNetworkFactory;
 pub use crate::node::Node;
 pub use crate::node::NodeId;
+pub use crate::node::NodeInfo;
 pub use crate::raft::Raft;
 pub use crate::raft::RaftTypeConfig;
+pub use crate::raft_group::RaftGroupHandle;
 pub use crate::raft_state::RaftState;
 pub use crate::raft_types::LogId;
 pub use crate::raft_types::LogIdOptionExt;
@@ -82,11 +109,17 @@ pub use crate::raft_types::SnapshotId;
 pub use crate::raft_types::SnapshotSegmentId;
 pub use crate::raft_types::StateMachineChanges;
 pub use crate::raft_types::Update;
+pub use crate::responder::OneshotResponder;
+pub use crate::responder::Responder;
+pub use crate::responder::WriteResult;
 pub use crate::storage::RaftLogReader;
+pub use crate::storage::RaftLogStorage;
 pub use crate::storage::RaftSnapshotBuilder;
+pub use crate::storage::RaftStateMachine;
 pub use crate::storage::RaftStorage;
 pub use crate::storage::RaftStorageDebug;
 pub use crate::storage::SnapshotMeta;
+pub use crate::storage::SnapshotSignature;
 pub use crate::storage::StorageHelper;
 pub use crate::storage_error::DefensiveError;
 pub use crate::storage_error::ErrorSubject;
@@ -96,6 +129,9 @@ pub use crate::storage_error::StorageIOError;
 pub use crate::storage_error::ToStorageResult;
 pub use crate::storage_error::Violation;
 pub use crate::store_ext::StoreExt;
+pub use crate::store_transform::PayloadTransform;
+pub use crate::store_transform::TransformLogReader;
+pub use crate::store_transform::TransformStore;
 pub use crate::store_wrapper::Wrapper;
 pub use crate::summary::MessageSummary;
 pub use crate::vote::LeaderId;
@@ -152,3 +188,27 @@ pub trait AppDataResponse: Clone + Send + Sync + 'static {}
 
 #[cfg(not(feature = "serde"))]
 impl<T> AppDataResponse for T where T: Clone + Send + Sync + 'static {}
+
+/// A trait defining an application specific error, returned when the state machine rejects a client write.
+///
+/// An application declares it with [`RaftTypeConfig::AppError`]. If it does not, it defaults to
+/// [`error::Infallible`], i.e., the state machine never rejects a write.
+///
+/// ## Note
+///
+/// The trait is automatically implemented for all types which satisfy its supertraits.
+#[cfg(feature = "serde")]
+pub trait AppError:
+    std::error::Error + Clone + Send + Sync + serde::Serialize + serde::de::DeserializeOwned + 'static
+{
+}
+
+#[cfg(feature = "serde")]
+impl<T> AppError for T where T: std::error::Error + Clone + Send + Sync + serde::Serialize + serde::de::DeserializeOwned + 'static
+{}
+
+#[cfg(not(feature = "serde"))]
+pub trait AppError: std::error::Error + Clone + Send + Sync + 'static {}
+
+#[cfg(not(feature = "serde"))]
+impl<T> AppError for T where T: std::error::Error + Clone + Send + Sync + 'static {}
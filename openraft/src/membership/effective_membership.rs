@@ -1,6 +1,7 @@
 use std::collections::BTreeSet;
 use std::fmt::Debug;
 
+use crate::config::CommitQuorum;
 use crate::entry::RaftEntry;
 use crate::membership::NodeRole;
 use crate::quorum::Joint;
@@ -11,6 +12,7 @@ use crate::Membership;
 use crate::MessageSummary;
 use crate::Node;
 use crate::NodeId;
+use crate::NodeInfo;
 
 /// The currently active membership config.
 ///
@@ -19,13 +21,13 @@ use crate::NodeId;
 /// - and the config.
 ///
 /// An active config is just the last seen config in raft spec.
-#[derive(Clone, Default, Eq)]
+#[derive(Clone, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
-pub struct EffectiveMembership<NID: NodeId> {
+pub struct EffectiveMembership<NID: NodeId, N: NodeInfo = Node> {
     /// The id of the log that applies this membership config
     pub log_id: Option<LogId<NID>>,
 
-    pub membership: Membership<NID>,
+    pub membership: Membership<NID, N>,
 
     /// The quorum set built from `membership`.
     // #[serde(skip_serialize)]
@@ -36,7 +38,7 @@ pub struct EffectiveMembership<NID: NodeId> {
     voter_ids: BTreeSet<NID>,
 }
 
-impl<NID: NodeId> Debug for EffectiveMembership<NID> {
+impl<NID: NodeId, N: NodeInfo> Debug for EffectiveMembership<NID, N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("EffectiveMembership")
             .field("log_id", &self.log_id)
@@ -46,33 +48,49 @@ impl<NID: NodeId> Debug for EffectiveMembership<NID> {
     }
 }
 
-impl<NID: NodeId> PartialEq for EffectiveMembership<NID> {
+impl<NID: NodeId, N: NodeInfo> Default for EffectiveMembership<NID, N> {
+    fn default() -> Self {
+        Self::new(None, Membership::default())
+    }
+}
+
+impl<NID: NodeId, N: NodeInfo> PartialEq for EffectiveMembership<NID, N> {
     fn eq(&self, other: &Self) -> bool {
         self.log_id == other.log_id && self.membership == other.membership && self.voter_ids == other.voter_ids
     }
 }
 
-impl<NID: NodeId, LID: RaftLogId<NID>> From<(&LID, Membership<NID>)> for EffectiveMembership<NID> {
-    fn from(v: (&LID, Membership<NID>)) -> Self {
-        EffectiveMembership::new(Some(*v.0.get_log_id()), v.1)
+impl<NID, N, LID> From<(&LID, Membership<NID, N>)> for EffectiveMembership<NID, N>
+where
+    NID: NodeId,
+    N: NodeInfo,
+    LID: RaftLogId<NID>,
+{
+    fn from(v: (&LID, Membership<NID, N>)) -> Self {
+        EffectiveMembership::new(Some(v.0.get_log_id().clone()), v.1)
     }
 }
 
 /// Build a EffectiveMembership from a membership config entry
-impl<NID: NodeId, Ent: RaftEntry<NID>> From<&Ent> for EffectiveMembership<NID> {
+impl<NID, N, Ent> From<&Ent> for EffectiveMembership<NID, N>
+where
+    NID: NodeId,
+    N: NodeInfo,
+    Ent: RaftEntry<NID, N>,
+{
     fn from(v: &Ent) -> Self {
-        EffectiveMembership::new(Some(*v.get_log_id()), v.get_membership().unwrap().clone())
+        EffectiveMembership::new(Some(v.get_log_id().clone()), v.get_membership().unwrap().clone())
     }
 }
 
-impl<NID: NodeId> EffectiveMembership<NID> {
-    pub fn new(log_id: Option<LogId<NID>>, membership: Membership<NID>) -> Self {
+impl<NID: NodeId, N: NodeInfo> EffectiveMembership<NID, N> {
+    pub fn new(log_id: Option<LogId<NID>>, membership: Membership<NID, N>) -> Self {
         let voter_ids = membership.voter_ids().collect();
 
         let configs = membership.get_joint_config();
         let mut joint = vec![];
         for c in configs {
-            joint.push(c.iter().copied().collect::<Vec<_>>());
+            joint.push(c.iter().cloned().collect::<Vec<_>>());
         }
 
         let quorum_set = Joint::from(joint);
@@ -87,10 +105,12 @@ impl<NID: NodeId> EffectiveMembership<NID> {
 }
 
 /// Membership API
-impl<NID: NodeId> EffectiveMembership<NID> {
-    /// Return if a node is a voter or learner, or not in this membership config at all.
+impl<NID: NodeId, N: NodeInfo> EffectiveMembership<NID, N> {
+    /// Return if a node is a witness, voter or learner, or not in this membership config at all.
     pub(crate) fn get_node_role(&self, nid: &NID) -> Option<NodeRole> {
-        if self.voter_ids.contains(nid) {
+        if self.membership.is_witness(nid) {
+            Some(NodeRole::Witness)
+        } else if self.voter_ids.contains(nid) {
             Some(NodeRole::Voter)
         } else if self.contains(nid) {
             Some(NodeRole::Learner)
@@ -106,7 +126,15 @@ impl<NID: NodeId> EffectiveMembership<NID> {
 
     /// Returns an Iterator of all voter node ids. Learners are not included.
     pub fn voter_ids(&self) -> impl Iterator<Item = NID> + '_ {
-        self.voter_ids.iter().copied()
+        self.voter_ids.iter().cloned()
+    }
+
+    /// Returns the voters a log has to be acknowledged by to be committed with `commit_quorum`, besides a quorum.
+    ///
+    /// With `CommitQuorum::All` they are the voters that store the log payloads: every voter but the witnesses.
+    pub(crate) fn commit_required_ids(&self, commit_quorum: &CommitQuorum) -> impl Iterator<Item = NID> + '_ {
+        let all = commit_quorum == &CommitQuorum::All;
+        self.voter_ids.iter().filter(move |id| all && !self.membership.is_witness(id)).cloned()
     }
 
     /// Returns an Iterator of all learner node ids. Voters are not included.
@@ -121,12 +149,12 @@ impl<NID: NodeId> EffectiveMembership<NID> {
     }
 
     /// Get a the node(either voter or learner) by node id.
-    pub fn get_node(&self, node_id: &NID) -> Option<&Node> {
+    pub fn get_node(&self, node_id: &NID) -> Option<&N> {
         self.membership.get_node(node_id)
     }
 
     /// Returns an Iterator of all nodes(voters and learners).
-    pub fn nodes(&self) -> impl Iterator<Item = (&NID, &Option<Node>)> {
+    pub fn nodes(&self) -> impl Iterator<Item = (&NID, &Option<N>)> {
         self.membership.nodes()
     }
 
@@ -139,14 +167,14 @@ impl<NID: NodeId> EffectiveMembership<NID> {
     }
 }
 
-impl<NID: NodeId> MessageSummary<EffectiveMembership<NID>> for EffectiveMembership<NID> {
+impl<NID: NodeId, N: NodeInfo> MessageSummary<EffectiveMembership<NID, N>> for EffectiveMembership<NID, N> {
     fn summary(&self) -> String {
         format!("{{log_id:{:?} membership:{}}}", self.log_id, self.membership.summary())
     }
 }
 
 /// Implement node-id joint quorum set.
-impl<NID: NodeId> QuorumSet<NID> for EffectiveMembership<NID> {
+impl<NID: NodeId, N: NodeInfo> QuorumSet<NID> for EffectiveMembership<NID, N> {
     type Iter = std::collections::btree_set::IntoIter<NID>;
 
     fn is_quorum<'a, I: Iterator<Item = &'a NID> + Clone>(&self, ids: I) -> bool {
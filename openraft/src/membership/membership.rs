@@ -13,32 +13,33 @@ use crate::quorum::QuorumSet;
 use crate::MessageSummary;
 use crate::Node;
 use crate::NodeId;
+use crate::NodeInfo;
 
 /// Convert other types into the internal data structure for node infos
-pub trait IntoOptionNodes<NID: NodeId> {
-    fn into_option_nodes(self) -> BTreeMap<NID, Option<Node>>;
+pub trait IntoOptionNodes<NID: NodeId, N: NodeInfo = Node> {
+    fn into_option_nodes(self) -> BTreeMap<NID, Option<N>>;
 }
 
-impl<NID: NodeId> IntoOptionNodes<NID> for () {
-    fn into_option_nodes(self) -> BTreeMap<NID, Option<Node>> {
+impl<NID: NodeId, N: NodeInfo> IntoOptionNodes<NID, N> for () {
+    fn into_option_nodes(self) -> BTreeMap<NID, Option<N>> {
         btreemap! {}
     }
 }
 
-impl<NID: NodeId> IntoOptionNodes<NID> for BTreeSet<NID> {
-    fn into_option_nodes(self) -> BTreeMap<NID, Option<Node>> {
+impl<NID: NodeId, N: NodeInfo> IntoOptionNodes<NID, N> for BTreeSet<NID> {
+    fn into_option_nodes(self) -> BTreeMap<NID, Option<N>> {
         self.into_iter().map(|node_id| (node_id, None)).collect()
     }
 }
 
-impl<NID: NodeId> IntoOptionNodes<NID> for BTreeMap<NID, Node> {
-    fn into_option_nodes(self) -> BTreeMap<NID, Option<Node>> {
+impl<NID: NodeId, N: NodeInfo> IntoOptionNodes<NID, N> for BTreeMap<NID, N> {
+    fn into_option_nodes(self) -> BTreeMap<NID, Option<N>> {
         self.into_iter().map(|(node_id, n)| (node_id, Some(n))).collect()
     }
 }
 
-impl<NID: NodeId> IntoOptionNodes<NID> for BTreeMap<NID, Option<Node>> {
-    fn into_option_nodes(self) -> BTreeMap<NID, Option<Node>> {
+impl<NID: NodeId, N: NodeInfo> IntoOptionNodes<NID, N> for BTreeMap<NID, Option<N>> {
+    fn into_option_nodes(self) -> BTreeMap<NID, Option<N>> {
         self
     }
 }
@@ -47,9 +48,14 @@ impl<NID: NodeId> IntoOptionNodes<NID> for BTreeMap<NID, Option<Node>> {
 ///
 /// It could be a joint of one, two or more configs, i.e., a quorum is a node set that is superset of a majority of
 /// every config.
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
-pub struct Membership<NID: NodeId> {
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize),
+    archive(check_bytes)
+)]
+pub struct Membership<NID: NodeId, N: NodeInfo = Node> {
     /// Multi configs of members.
     ///
     /// AKA a joint config in original raft paper.
@@ -59,13 +65,29 @@ pub struct Membership<NID: NodeId> {
     ///
     /// A node-id key that is in `nodes` but is not in `configs` is a **learner**.
     /// The values in this map must all be `Some` or `None`.
-    nodes: BTreeMap<NID, Option<Node>>,
+    nodes: BTreeMap<NID, Option<N>>,
+
+    /// The voters that are **witnesses**, see [`Membership::with_witness()`].
+    ///
+    /// Every witness is a voter in `configs`.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "BTreeSet::is_empty"))]
+    witnesses: BTreeSet<NID>,
 }
 
-impl<NID: NodeId> TryFrom<BTreeMap<NID, Option<Node>>> for Membership<NID> {
+impl<NID: NodeId, N: NodeInfo> Default for Membership<NID, N> {
+    fn default() -> Self {
+        Self {
+            configs: vec![],
+            nodes: BTreeMap::new(),
+            witnesses: BTreeSet::new(),
+        }
+    }
+}
+
+impl<NID: NodeId, N: NodeInfo> TryFrom<BTreeMap<NID, Option<N>>> for Membership<NID, N> {
     type Error = MissingNodeInfo<NID>;
 
-    fn try_from(b: BTreeMap<NID, Option<Node>>) -> Result<Self, Self::Error> {
+    fn try_from(b: BTreeMap<NID, Option<N>>) -> Result<Self, Self::Error> {
         let member_ids = b.keys().cloned().collect::<BTreeSet<NID>>();
 
         let membership = Membership::with_nodes(vec![member_ids], b)?;
@@ -73,7 +95,7 @@ impl<NID: NodeId> TryFrom<BTreeMap<NID, Option<Node>>> for Membership<NID> {
     }
 }
 
-impl<NID: NodeId> MessageSummary<Membership<NID>> for Membership<NID> {
+impl<NID: NodeId, N: NodeInfo> MessageSummary<Membership<NID, N>> for Membership<NID, N> {
     fn summary(&self) -> String {
         let mut res = vec!["members:[".to_string()];
         for (i, c) in self.configs.iter().enumerate() {
@@ -112,11 +134,16 @@ impl<NID: NodeId> MessageSummary<Membership<NID>> for Membership<NID> {
             }
         }
         res.push("]".to_string());
+
+        if !self.witnesses.is_empty() {
+            let witnesses = self.witnesses.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+            res.push(format!(",witnesses:[{}]", witnesses.join(",")));
+        }
         res.join("")
     }
 }
 
-impl<NID: NodeId> Membership<NID> {
+impl<NID: NodeId, N: NodeInfo> Membership<NID, N> {
     /// Create a new Membership of multiple configs(joint) and optionally a set of learner node ids.
     ///
     /// A node id that is in `node_ids` but is not in `configs` is a **learner**.
@@ -132,7 +159,35 @@ impl<NID: NodeId> Membership<NID> {
 
         let nodes = Self::extend_nodes(nodes, &voter_ids.into_option_nodes());
 
-        Membership { configs, nodes }
+        Membership {
+            configs,
+            nodes,
+            witnesses: BTreeSet::new(),
+        }
+    }
+
+    /// Make the voter `node_id` a **witness**: a voter that counts toward the quorum of elections and commits, but does
+    /// not keep the payloads of the logs, nor a state machine with data.
+    ///
+    /// A leader replicates only the log ids and the membership logs to a witness, and a witness never starts an
+    /// election. Thus a witness costs little, but it is no replica of the data: a log committed by a quorum that
+    /// includes witnesses is stored by fewer nodes than a quorum. E.g., in a cluster of two nodes and a witness, a log
+    /// committed by one node and the witness is lost if that node loses its storage. A witness refuses to vote for a
+    /// candidate whose logs are behind its own log ids, thus the cluster stays unavailable instead of electing a
+    /// leader without the committed logs, until the node with them is back.
+    ///
+    /// A witness that is behind the purged logs of the leader still receives a full snapshot.
+    ///
+    /// A witness stays a witness until it is removed from the cluster: it can not be turned into a learner, which
+    /// would serve logs it does not have. A removed witness has to clear its storage before it joins again.
+    ///
+    /// It has no effect if `node_id` is not a voter. In a running cluster, a learner is made a witness with
+    /// [`ChangeMembers::AddWitnesses`](`crate::ChangeMembers::AddWitnesses`).
+    pub fn with_witness(mut self, node_id: NID) -> Self {
+        if self.is_voter(&node_id) {
+            self.witnesses.insert(node_id);
+        }
+        self
     }
 
     /// Create a new Membership of multiple configs and optional node infos.
@@ -144,7 +199,7 @@ impl<NID: NodeId> Membership<NID> {
     ///   ids not in `configs` are learner node ids. In this case, every node id in `configs` has to present in `nodes`
     ///   or an error will be returned.
     pub(crate) fn with_nodes<T>(configs: Vec<BTreeSet<NID>>, nodes: T) -> Result<Self, MissingNodeInfo<NID>>
-    where T: IntoOptionNodes<NID> {
+    where T: IntoOptionNodes<NID, N> {
         let nodes = nodes.into_option_nodes();
 
         for voter_id in configs.as_joint().ids() {
@@ -161,13 +216,23 @@ impl<NID: NodeId> Membership<NID> {
             let first_none = nodes.iter().find(|(_node_id, v)| v.is_none());
             if let Some(first_none) = first_none {
                 return Err(MissingNodeInfo {
-                    node_id: *first_none.0,
+                    node_id: first_none.0.clone(),
                     reason: "is None".to_string(),
                 });
             }
         }
 
-        Ok(Membership { configs, nodes })
+        Ok(Membership {
+            configs,
+            nodes,
+            witnesses: BTreeSet::new(),
+        })
+    }
+
+    /// Keep the witnesses of `self` that are still voters in `m`.
+    fn inherit_witnesses(&self, mut m: Self) -> Self {
+        m.witnesses = self.witnesses.iter().filter(|id| m.is_voter(id)).cloned().collect();
+        m
     }
 
     /// Extends nodes btreemap with another.
@@ -175,16 +240,16 @@ impl<NID: NodeId> Membership<NID> {
     /// Node that present in `old` will **NOT** be replaced because changing the address of a node potentially breaks
     /// consensus guarantee.
     pub(crate) fn extend_nodes(
-        old: BTreeMap<NID, Option<Node>>,
-        new: &BTreeMap<NID, Option<Node>>,
-    ) -> BTreeMap<NID, Option<Node>> {
+        old: BTreeMap<NID, Option<N>>,
+        new: &BTreeMap<NID, Option<N>>,
+    ) -> BTreeMap<NID, Option<N>> {
         let mut res = old;
 
         for (k, v) in new.iter() {
             if res.contains_key(k) {
                 continue;
             }
-            res.insert(*k, v.clone());
+            res.insert(k.clone(), v.clone());
         }
 
         res
@@ -195,7 +260,7 @@ impl<NID: NodeId> Membership<NID> {
         self.configs.len() > 1
     }
 
-    pub(crate) fn add_learner(&self, node_id: NID, node: Option<Node>) -> Result<Self, MissingNodeInfo<NID>> {
+    pub(crate) fn add_learner(&self, node_id: NID, node: Option<N>) -> Result<Self, MissingNodeInfo<NID>> {
         let configs = self.configs.clone();
 
         let nodes = Self::extend_nodes(self.nodes.clone(), &btreemap! {node_id=>node});
@@ -207,11 +272,13 @@ impl<NID: NodeId> Membership<NID> {
 }
 
 /// Membership API
-impl<NID: NodeId> Membership<NID> {
+impl<NID: NodeId, N: NodeInfo> Membership<NID, N> {
     /// Return if a node is a voter or learner, or not in this membership config at all.
     #[allow(dead_code)]
     pub(crate) fn get_node_role(&self, nid: &NID) -> Option<NodeRole> {
-        if self.is_voter(nid) {
+        if self.is_witness(nid) {
+            Some(NodeRole::Witness)
+        } else if self.is_voter(nid) {
             Some(NodeRole::Voter)
         } else if self.contains(nid) {
             Some(NodeRole::Learner)
@@ -230,15 +297,25 @@ impl<NID: NodeId> Membership<NID> {
         false
     }
 
+    /// Check if the given `NodeId` is a witness voter. See [`Membership::with_witness()`].
+    pub fn is_witness(&self, node_id: &NID) -> bool {
+        self.witnesses.contains(node_id)
+    }
+
     /// Returns an Iterator of all voter node ids. Learners are not included.
     pub(crate) fn voter_ids(&self) -> impl Iterator<Item = NID> {
         self.configs.as_joint().ids()
     }
 
+    /// Returns an Iterator of the witness voter ids.
+    pub fn witness_ids(&self) -> impl Iterator<Item = NID> + '_ {
+        self.witnesses.iter().cloned()
+    }
+
     /// Returns an Iterator of all learner node ids. Voters are not included.
     #[allow(dead_code)]
     pub(crate) fn learner_ids(&self) -> impl Iterator<Item = NID> + '_ {
-        self.nodes.keys().filter(|x| !self.is_voter(x)).copied()
+        self.nodes.keys().filter(|x| !self.is_voter(x)).cloned()
     }
 
     /// Returns if a voter or learner exists in this membership.
@@ -247,13 +324,13 @@ impl<NID: NodeId> Membership<NID> {
     }
 
     /// Get a the node(either voter or learner) by node id.
-    pub(crate) fn get_node(&self, node_id: &NID) -> Option<&Node> {
+    pub(crate) fn get_node(&self, node_id: &NID) -> Option<&N> {
         let x = self.nodes.get(node_id)?;
         x.as_ref()
     }
 
     /// Returns an Iterator of all nodes(voters and learners).
-    pub fn nodes(&self) -> impl Iterator<Item = (&NID, &Option<Node>)> {
+    pub fn nodes(&self) -> impl Iterator<Item = (&NID, &Option<N>)> {
         self.nodes.iter()
     }
 
@@ -267,7 +344,7 @@ impl<NID: NodeId> Membership<NID> {
 }
 
 /// Quorum related API
-impl<NID: NodeId> Membership<NID> {
+impl<NID: NodeId, N: NodeInfo> Membership<NID, N> {
     /// Returns the next safe membership to change to while the expected final membership is `goal`.
     ///
     /// E.g.(`cicj` is a joint membership of `ci` and `cj`):
@@ -286,7 +363,7 @@ impl<NID: NodeId> Membership<NID> {
     /// }
     /// ```
     pub(crate) fn next_safe<T>(&self, goal: T, turn_to_learner: bool) -> Result<Self, MissingNodeInfo<NID>>
-    where T: IntoOptionNodes<NID> {
+    where T: IntoOptionNodes<NID, N> {
         let goal = goal.into_option_nodes();
 
         let goal_ids = goal.keys().cloned().collect::<BTreeSet<_>>();
@@ -295,16 +372,50 @@ impl<NID: NodeId> Membership<NID> {
 
         let mut nodes = Self::extend_nodes(self.nodes.clone(), &goal);
 
-        if !turn_to_learner {
-            let old_voter_ids = self.configs.as_joint().ids().collect::<BTreeSet<_>>();
-            let new_voter_ids = config.as_joint().ids().collect::<BTreeSet<_>>();
+        let old_voter_ids = self.configs.as_joint().ids().collect::<BTreeSet<_>>();
+        let new_voter_ids = config.as_joint().ids().collect::<BTreeSet<_>>();
 
-            for node_id in old_voter_ids.difference(&new_voter_ids) {
+        for node_id in old_voter_ids.difference(&new_voter_ids) {
+            // A removed witness is never turned into a learner: it has no log payloads to serve.
+            if !turn_to_learner || self.is_witness(node_id) {
                 nodes.remove(node_id);
             }
-        };
+        }
 
         let m = Membership::with_nodes(config, nodes)?;
+        Ok(self.inherit_witnesses(m))
+    }
+
+    /// Returns the next safe membership to change to, towards exactly the `voters` and `learners`.
+    ///
+    /// A node that is neither in `voters` nor in `learners` is removed, once it is no longer a voter in the joint
+    /// config. Node infos are not changed.
+    pub(crate) fn next_safe_exact(
+        &self,
+        voters: BTreeSet<NID>,
+        learners: &BTreeSet<NID>,
+    ) -> Result<Self, MissingNodeInfo<NID>> {
+        let config = Joint::from(self.configs.clone()).find_coherent(voters).children().clone();
+
+        let voter_ids = config.as_joint().ids().collect::<BTreeSet<_>>();
+
+        let mut nodes = self.nodes.clone();
+        nodes.retain(|node_id, _| {
+            voter_ids.contains(node_id) || (learners.contains(node_id) && !self.is_witness(node_id))
+        });
+
+        let m = Membership::with_nodes(config, nodes)?;
+        Ok(self.inherit_witnesses(m))
+    }
+
+    /// Returns the next safe membership to change to, towards the current voters and `witnesses`, which become witness
+    /// voters.
+    pub(crate) fn next_safe_witnesses(&self, witnesses: &BTreeSet<NID>) -> Result<Self, MissingNodeInfo<NID>> {
+        let last = self.configs.last().cloned().unwrap_or_default();
+        let goal = last.union(witnesses).cloned().collect::<BTreeSet<_>>();
+
+        let mut m = self.next_safe(goal, false)?;
+        m.witnesses.extend(witnesses.iter().filter(|id| m.is_voter(id)).cloned());
         Ok(m)
     }
 }
@@ -1,7 +1,9 @@
 use std::sync::Arc;
 
 use crate::EffectiveMembership;
+use crate::Node;
 use crate::NodeId;
+use crate::NodeInfo;
 
 /// The state of membership configs a raft node needs to know.
 ///
@@ -25,16 +27,25 @@ use crate::NodeId;
 // By (2), a follower only need to revert at most one membership log.
 //
 // Thus a raft node will only need to store at most two recent membership logs.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 #[derive(PartialEq, Eq)]
-pub struct MembershipState<NID: NodeId> {
-    pub committed: Arc<EffectiveMembership<NID>>,
+pub struct MembershipState<NID: NodeId, N: NodeInfo = Node> {
+    pub committed: Arc<EffectiveMembership<NID, N>>,
 
     // Using `Arc` because the effective membership will be copied to RaftMetrics frequently.
-    pub effective: Arc<EffectiveMembership<NID>>,
+    pub effective: Arc<EffectiveMembership<NID, N>>,
 }
 
-impl<NID: NodeId> MembershipState<NID> {
+impl<NID: NodeId, N: NodeInfo> Default for MembershipState<NID, N> {
+    fn default() -> Self {
+        Self {
+            committed: Arc::new(EffectiveMembership::default()),
+            effective: Arc::new(EffectiveMembership::default()),
+        }
+    }
+}
+
+impl<NID: NodeId, N: NodeInfo> MembershipState<NID, N> {
     pub(crate) fn is_voter(&self, id: &NID) -> bool {
         self.effective.membership.is_voter(id)
     }
@@ -279,6 +279,69 @@ fn test_membership_next_safe() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_membership_next_safe_exact() -> anyhow::Result<()> {
+    let m = Membership::<u64>::new(vec![btreeset! {1,2,3}], Some(btreeset! {4,5}));
+
+    // Promote 4, demote 1 to learner, remove 2 and 5.
+    let joint = m.next_safe_exact(btreeset! {3,4}, &btreeset! {1})?;
+    assert_eq!(
+        Membership::<u64>::new(vec![btreeset! {1,2,3}, btreeset! {3,4}], None),
+        joint,
+        "2 is kept until it leaves the joint config, 5 is removed at once"
+    );
+
+    let uniform = joint.next_safe_exact(btreeset! {3,4}, &btreeset! {1})?;
+    assert_eq!(
+        Membership::<u64>::new(vec![btreeset! {3,4}], Some(btreeset! {1})),
+        uniform
+    );
+
+    // Already there
+    assert_eq!(uniform, uniform.next_safe_exact(btreeset! {3,4}, &btreeset! {1})?);
+
+    // A node in both sets is a voter
+    assert_eq!(
+        Membership::<u64>::new(vec![btreeset! {3,4}], None),
+        uniform.next_safe_exact(btreeset! {3,4}, &btreeset! {4})?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_membership_witness() -> anyhow::Result<()> {
+    let m = Membership::<u64>::new(vec![btreeset! {1,2}], Some(btreeset! {3}));
+
+    let m = m.with_witness(3);
+    assert!(!m.is_witness(&3), "a learner is not made a witness");
+    assert_eq!("members:[{1,2}],learners:[3]", m.summary());
+
+    // Add 3 as a witness, through a joint config
+    let joint = m.next_safe_witnesses(&btreeset! {3})?;
+    assert_eq!(
+        Membership::<u64>::new(vec![btreeset! {1,2}, btreeset! {1,2,3}], None).with_witness(3),
+        joint
+    );
+    assert_eq!("members:[{1,2},{1,2,3}],learners:[],witnesses:[3]", joint.summary());
+
+    let uniform = joint.next_safe_witnesses(&btreeset! {3})?;
+    assert_eq!(
+        Membership::<u64>::new(vec![btreeset! {1,2,3}], None).with_witness(3),
+        uniform
+    );
+    assert_eq!(vec![3], uniform.witness_ids().collect::<Vec<_>>());
+
+    // A removed witness is not turned into a learner
+    let joint = uniform.next_safe(btreeset! {1,2}, true)?;
+    assert!(joint.is_witness(&3), "3 is a witness until it leaves the joint config");
+
+    let removed = joint.next_safe(btreeset! {1,2}, true)?;
+    assert_eq!(Membership::<u64>::new(vec![btreeset! {1,2}], None), removed);
+
+    Ok(())
+}
+
 #[test]
 fn test_membership_next_safe_with_nodes() -> anyhow::Result<()> {
     let node = |s: &str| Node {
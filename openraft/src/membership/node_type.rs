@@ -2,5 +2,8 @@
 #[derive(PartialEq, Eq)]
 pub(crate) enum NodeRole {
     Voter,
+    /// A voter that does not keep the log payloads, see
+    /// [`Membership::with_witness()`](`crate::Membership::with_witness`).
+    Witness,
     Learner,
 }
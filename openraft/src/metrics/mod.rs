@@ -14,10 +14,22 @@ mod wait;
 #[cfg(test)] mod replication_metrics_test;
 #[cfg(test)] mod wait_test;
 
+pub use raft_metrics::PromotionStage;
+pub use raft_metrics::RaftDataMetrics;
 pub use raft_metrics::RaftMetrics;
+pub use raft_metrics::RaftServerMetrics;
+pub use raft_metrics::SnapshotBuildMetrics;
+pub use raft_metrics::SnapshotBuildProgress;
+pub use raft_metrics::StepDown;
+pub use raft_metrics::StepDownReason;
+pub use replication_metrics::LogCacheMetrics;
 pub(crate) use replication_metrics::RemoveTarget;
+pub use replication_metrics::ReplicationBackoff;
+pub use replication_metrics::ReplicationLag;
 pub use replication_metrics::ReplicationMetrics;
+pub use replication_metrics::ReplicationProgress;
 pub use replication_metrics::ReplicationTargetMetrics;
+pub use replication_metrics::SnapshotTransmission;
 pub(crate) use replication_metrics::UpdateMatchedLogId;
 pub use wait::Wait;
 pub use wait::WaitError;
@@ -1,18 +1,129 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
+use crate::async_runtime::Instant;
 use crate::core::ServerState;
 use crate::error::Fatal;
 use crate::membership::EffectiveMembership;
+use crate::metrics::LogCacheMetrics;
+use crate::metrics::ReplicationBackoff;
+use crate::metrics::ReplicationLag;
 use crate::metrics::ReplicationMetrics;
+use crate::metrics::ReplicationProgress;
 use crate::summary::MessageSummary;
 use crate::versioned::Versioned;
 use crate::LogId;
+use crate::Node;
 use crate::NodeId;
+use crate::NodeInfo;
+use crate::Vote;
+
+/// The stage of promoting a learner to a voter.
+///
+/// See [`Raft::add_learner_and_promote()`].
+///
+/// [`Raft::add_learner_and_promote()`]: crate::Raft::add_learner_and_promote
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum PromotionStage {
+    /// Waiting for the learner to catch up with the leader, or for a previous membership change to commit.
+    ///
+    /// A learner that falls behind before the joint config is proposed stays in this stage. A learner that falls
+    /// behind before the uniform config is proposed is moved back to this stage, by reverting the joint config.
+    CatchingUp,
+
+    /// A joint config that includes the learner as a voter is proposed.
+    Joint,
+
+    /// The uniform config that includes the learner as a voter is proposed.
+    Uniform,
+}
+
+/// Why a leader stepped down. See [`RaftMetrics::last_step_down`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum StepDownReason {
+    /// It was not acknowledged by a quorum within the max election timeout, with `Config::enable_check_quorum`,
+    /// e.g., it is partitioned away from the others.
+    LostQuorum,
+
+    /// It saw a higher vote, e.g., from a candidate or a leader of a higher term.
+    HigherVote,
+
+    /// It transferred its leadership with [`Raft::transfer_leadership()`](`crate::Raft::transfer_leadership`).
+    Transferred,
+
+    /// It is no longer a voter after a membership change.
+    Removed,
+}
+
+/// A step down of this node from the leader.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct StepDown<NID: NodeId> {
+    /// The vote of this node when it was the leader, which tells the leadership that ended.
+    pub vote: Vote<NID>,
+
+    pub reason: StepDownReason,
+}
+
+/// How far building a snapshot has gone, as reported by the snapshot builder.
+///
+/// See [`RaftSnapshotBuilder::set_progress_reporter()`](`crate::RaftSnapshotBuilder::set_progress_reporter`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SnapshotBuildProgress {
+    /// The amount of work done, in a unit chosen by the builder, e.g., keys or bytes.
+    pub done: u64,
+
+    /// The total amount of work, in the same unit as `done`.
+    pub total: u64,
+}
+
+impl SnapshotBuildProgress {
+    /// The fraction of the work done, in `[0, 1]`, or `None` if the total is 0.
+    pub fn fraction(&self) -> Option<f64> {
+        if self.total == 0 {
+            None
+        } else {
+            Some(self.done.min(self.total) as f64 / self.total as f64)
+        }
+    }
+}
+
+/// The metrics about building snapshots on a Raft node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct SnapshotBuildMetrics<NID: NodeId> {
+    /// Whether a snapshot is being built.
+    pub building: bool,
+
+    /// The id of the last log included in the last built or installed snapshot.
+    pub last_snapshot_log_id: Option<LogId<NID>>,
+
+    /// The latest progress reported by the builder of the snapshot being built.
+    ///
+    /// It is best-effort: it is `None` if the builder does not report any progress, and it is reset when a build
+    /// finishes.
+    pub progress: Option<SnapshotBuildProgress>,
+}
+
+impl<NID: NodeId> Default for SnapshotBuildMetrics<NID> {
+    fn default() -> Self {
+        Self {
+            building: false,
+            last_snapshot_log_id: None,
+            progress: None,
+        }
+    }
+}
 
 /// A set of metrics describing the current state of a Raft node.
+///
+/// `I` is the [`Instant`] type of the [`AsyncRuntime`](`crate::AsyncRuntime`) of the node.
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
-pub struct RaftMetrics<NID: NodeId> {
+pub struct RaftMetrics<NID: NodeId, N: NodeInfo = Node, I: Instant = tokio::time::Instant> {
     pub running_state: Result<(), Fatal<NID>>,
 
     /// The ID of the Raft node.
@@ -34,6 +145,12 @@ pub struct RaftMetrics<NID: NodeId> {
     /// If there is no snapshot, it is (0,0).
     pub snapshot: Option<LogId<NID>>,
 
+    /// The id of the last log that is purged from the log.
+    pub purged: Option<LogId<NID>>,
+
+    /// Whether a snapshot is being built, and how far it has gone.
+    pub snapshot_build: SnapshotBuildMetrics<NID>,
+
     // ---
     // --- cluster ---
     // ---
@@ -44,16 +161,50 @@ pub struct RaftMetrics<NID: NodeId> {
     pub current_leader: Option<NID>,
 
     /// The current membership config of the cluster.
-    pub membership_config: Arc<EffectiveMembership<NID>>,
+    pub membership_config: Arc<EffectiveMembership<NID, N>>,
+
+    /// Whether the last election started by this node went through a pre-vote phase.
+    pub last_election_pre_vote: bool,
+
+    /// The latest time this node stepped down from the leader, and why, since it started.
+    pub last_step_down: Option<StepDown<NID>>,
+
+    /// The node this leader is transferring its leadership to, if a transfer is in progress.
+    pub transferring_leader_to: Option<NID>,
+
+    /// The latest time at which a quorum acknowledged this leader. It is Some() only when this node is leader.
+    ///
+    /// An application can build its own read lease policy on it. See [`Raft::get_read_lease()`].
+    ///
+    /// [`Raft::get_read_lease()`]: crate::Raft::get_read_lease
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub last_quorum_acked: Option<I>,
+
+    /// The learners this leader is promoting to voters, and how far each promotion has gone.
+    pub learner_promotions: BTreeMap<NID, PromotionStage>,
 
     // ---
     // --- replication ---
     // ---
     /// The metrics about the leader. It is Some() only when this node is leader.
     pub replication: Option<Versioned<ReplicationMetrics<NID>>>,
+
+    /// The replication targets this leader is backing off from after consecutive RPC failures.
+    ///
+    /// See [`Config::max_replication_backoff`](`crate::Config::max_replication_backoff`).
+    pub replication_backoff: BTreeMap<NID, ReplicationBackoff>,
+
+    /// The progress of every replication target, reported by the replication tasks of this leader.
+    pub replication_progress: BTreeMap<NID, ReplicationProgress<NID, I>>,
+
+    /// How far every replication target lags behind this leader. It is empty if this node is not a leader.
+    pub replication_lag: BTreeMap<NID, ReplicationLag<NID>>,
+
+    /// How often the replication streams read logs from the in-memory log cache, since this node started.
+    pub log_cache: LogCacheMetrics,
 }
 
-impl<NID: NodeId> MessageSummary<RaftMetrics<NID>> for RaftMetrics<NID> {
+impl<NID: NodeId, N: NodeInfo, I: Instant> MessageSummary<RaftMetrics<NID, N, I>> for RaftMetrics<NID, N, I> {
     fn summary(&self) -> String {
         format!("Metrics{{id:{},{:?}, term:{}, last_log:{:?}, last_applied:{:?}, leader:{:?}, membership:{}, snapshot:{:?}, replication:{}",
                 self.id,
@@ -69,7 +220,7 @@ impl<NID: NodeId> MessageSummary<RaftMetrics<NID>> for RaftMetrics<NID> {
     }
 }
 
-impl<NID: NodeId> RaftMetrics<NID> {
+impl<NID: NodeId, N: NodeInfo, I: Instant> RaftMetrics<NID, N, I> {
     pub fn new_initial(id: NID) -> Self {
         Self {
             running_state: Ok(()),
@@ -80,6 +231,90 @@ impl<NID: NodeId> RaftMetrics<NID> {
             last_applied: None,
             current_leader: None,
             membership_config: Arc::new(EffectiveMembership::default()),
+            last_election_pre_vote: false,
+            last_step_down: None,
+            transferring_leader_to: None,
+            last_quorum_acked: None,
+            learner_promotions: BTreeMap::new(),
+            snapshot: None,
+            purged: None,
+            snapshot_build: SnapshotBuildMetrics::default(),
+            replication: None,
+            replication_backoff: BTreeMap::new(),
+            replication_progress: BTreeMap::new(),
+            replication_lag: BTreeMap::new(),
+            log_cache: LogCacheMetrics::default(),
+        }
+    }
+}
+
+/// The metrics about the server state of a Raft node: its vote, role and the cluster it belongs to.
+///
+/// Unlike [`RaftMetrics`], it does not change when logs are appended or applied, thus watching it on a busy node does
+/// not wake up the watcher for every write. See [`Raft::server_metrics()`].
+///
+/// [`Raft::server_metrics()`]: crate::Raft::server_metrics
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct RaftServerMetrics<NID: NodeId, N: NodeInfo = Node> {
+    /// The ID of the Raft node.
+    pub id: NID,
+
+    /// The vote of the Raft node.
+    pub vote: Vote<NID>,
+
+    /// The state of the Raft node.
+    pub state: ServerState,
+
+    /// The current cluster leader.
+    pub current_leader: Option<NID>,
+
+    /// The current membership config of the cluster.
+    pub membership_config: Arc<EffectiveMembership<NID, N>>,
+
+    /// The latest time this node stepped down from the leader, and why, since it started.
+    pub last_step_down: Option<StepDown<NID>>,
+}
+
+impl<NID: NodeId, N: NodeInfo> RaftServerMetrics<NID, N> {
+    pub fn new_initial(id: NID) -> Self {
+        Self {
+            id: id.clone(),
+            vote: Vote::new(0, id),
+            state: ServerState::Follower,
+            current_leader: None,
+            membership_config: Arc::new(EffectiveMembership::default()),
+            last_step_down: None,
+        }
+    }
+}
+
+/// The metrics about the data of a Raft node: its logs, state machine, snapshot and replication.
+///
+/// See [`Raft::data_metrics()`].
+///
+/// [`Raft::data_metrics()`]: crate::Raft::data_metrics
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct RaftDataMetrics<NID: NodeId> {
+    /// The last log id has been appended to this Raft node's log.
+    pub last_log: Option<LogId<NID>>,
+
+    /// The last log id has been applied to this Raft node's state machine.
+    pub last_applied: Option<LogId<NID>>,
+
+    /// The id of the last log included in snapshot.
+    pub snapshot: Option<LogId<NID>>,
+
+    /// The metrics about the leader. It is Some() only when this node is leader.
+    pub replication: Option<Versioned<ReplicationMetrics<NID>>>,
+}
+
+impl<NID: NodeId> Default for RaftDataMetrics<NID> {
+    fn default() -> Self {
+        Self {
+            last_log: None,
+            last_applied: None,
             snapshot: None,
             replication: None,
         }
@@ -2,7 +2,9 @@ use std::collections::BTreeMap;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::async_runtime::Instant;
 use crate::versioned::Update;
 use crate::versioned::UpdateError;
 use crate::LeaderId;
@@ -11,13 +13,21 @@ use crate::MessageSummary;
 use crate::NodeId;
 
 /// The metrics about the leader. It is Some() only when this node is leader.
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 pub struct ReplicationMetrics<NID: NodeId> {
     /// Replication metrics of all known replication target: voters and learners
     pub replication: BTreeMap<NID, ReplicationTargetMetrics<NID>>,
 }
 
+impl<NID: NodeId> Default for ReplicationMetrics<NID> {
+    fn default() -> Self {
+        Self {
+            replication: BTreeMap::new(),
+        }
+    }
+}
+
 impl<NID: NodeId> MessageSummary<ReplicationMetrics<NID>> for ReplicationMetrics<NID> {
     fn summary(&self) -> String {
         let mut res = vec!["LeaderMetrics{".to_string()];
@@ -54,8 +64,8 @@ impl<NID: NodeId> Update<ReplicationMetrics<NID>> for UpdateMatchedLogId<NID> {
 
     /// To insert a new record always work.
     fn apply_mut(&self, to: &mut ReplicationMetrics<NID>) {
-        to.replication.insert(self.target, ReplicationTargetMetrics {
-            matched_leader_id: self.matched.leader_id,
+        to.replication.insert(self.target.clone(), ReplicationTargetMetrics {
+            matched_leader_id: self.matched.leader_id.clone(),
             matched_index: AtomicU64::new(self.matched.index),
         });
     }
@@ -77,7 +87,7 @@ impl<NID: NodeId> Update<ReplicationMetrics<NID>> for RemoveTarget<NID> {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 pub struct ReplicationTargetMetrics<NID: NodeId> {
     pub(crate) matched_leader_id: LeaderId<NID>,
@@ -87,7 +97,7 @@ pub struct ReplicationTargetMetrics<NID: NodeId> {
 impl<NID: NodeId> Clone for ReplicationTargetMetrics<NID> {
     fn clone(&self) -> Self {
         Self {
-            matched_leader_id: self.matched_leader_id,
+            matched_leader_id: self.matched_leader_id.clone(),
             matched_index: AtomicU64::new(self.matched_index.load(Ordering::Relaxed)),
         }
     }
@@ -113,7 +123,7 @@ impl<NID: NodeId> ReplicationTargetMetrics<NID> {
     pub fn matched(&self) -> LogId<NID> {
         let index = self.matched_index.load(Ordering::Relaxed);
         LogId {
-            leader_id: self.matched_leader_id,
+            leader_id: self.matched_leader_id.clone(),
             index,
         }
     }
@@ -124,3 +134,101 @@ impl<NID: NodeId> MessageSummary<ReplicationTargetMetrics<NID>> for ReplicationT
         format!("{}", self.matched())
     }
 }
+
+/// The backoff state of a replication target that can not be reached.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct ReplicationBackoff {
+    /// The number of consecutive RPC failures to the target.
+    pub failures: u64,
+
+    /// The delay before the next retry, jitter included.
+    pub delay: Duration,
+}
+
+/// The progress of replicating logs or a snapshot to a target, reported by the replication task.
+///
+/// It is updated at most once every [`Config::replication_progress_interval`].
+///
+/// [`Config::replication_progress_interval`]: `crate::Config::replication_progress_interval`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct ReplicationProgress<NID: NodeId, I: Instant = tokio::time::Instant> {
+    /// The last log id known to be replicated to the target.
+    pub matched: Option<LogId<NID>>,
+
+    /// The greatest log index the target may have matched.
+    ///
+    /// The leader is searching for the last matching log in `(matched.index, max_possible_matched_index]`, after the
+    /// target reported a conflict. The search is done when they are equal.
+    pub max_possible_matched_index: Option<u64>,
+
+    /// The snapshot being streamed to the target, if any.
+    pub snapshot: Option<SnapshotTransmission>,
+
+    /// When the latest response from the target is received.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub last_acked: Option<I>,
+
+    /// The backoff state, if the target can not be reached.
+    pub backoff: Option<ReplicationBackoff>,
+}
+
+impl<NID: NodeId, I: Instant> Default for ReplicationProgress<NID, I> {
+    fn default() -> Self {
+        Self {
+            matched: None,
+            max_possible_matched_index: None,
+            snapshot: None,
+            last_acked: None,
+            backoff: None,
+        }
+    }
+}
+
+/// How far a replication target lags behind the leader.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct ReplicationLag<NID: NodeId> {
+    /// The last log id known to be replicated to the target.
+    pub matched: Option<LogId<NID>>,
+
+    /// The number of logs the target lacks: the leader's last log index minus the matched index.
+    pub lag: u64,
+}
+
+/// How often the replication streams of a leader read logs from the in-memory log cache instead of the log storage.
+///
+/// See [`Config::log_cache_max_entries`](`crate::Config::log_cache_max_entries`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct LogCacheMetrics {
+    /// The number of reads served by the cache.
+    pub hits: u64,
+
+    /// The number of reads that fell back to the log storage.
+    pub misses: u64,
+}
+
+impl LogCacheMetrics {
+    /// The ratio of reads served by the cache, or `None` if nothing is read yet.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            None
+        } else {
+            Some(self.hits as f64 / total as f64)
+        }
+    }
+}
+
+/// The progress of streaming a snapshot to a target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SnapshotTransmission {
+    /// The number of bytes that are acknowledged by the target.
+    pub sent: u64,
+
+    /// The size of the snapshot in bytes.
+    pub total: u64,
+}
@@ -1,15 +1,18 @@
 use core::time::Duration;
 use std::collections::BTreeSet;
+use std::marker::PhantomData;
 
-use tokio::sync::watch;
-use tokio::time::Instant;
-
+use crate::async_runtime::watch;
 use crate::core::ServerState;
 use crate::metrics::RaftMetrics;
+use crate::AsyncRuntime;
 use crate::LogId;
 use crate::LogIdOptionExt;
 use crate::MessageSummary;
+use crate::Node;
 use crate::NodeId;
+use crate::NodeInfo;
+use crate::TokioRuntime;
 
 // Error variants related to metrics.
 #[derive(Debug, thiserror::Error)]
@@ -22,17 +25,20 @@ pub enum WaitError {
 }
 
 /// Wait is a wrapper of RaftMetrics channel that impls several utils to wait for metrics to satisfy some condition.
-pub struct Wait<NID: NodeId> {
+///
+/// The timeout is driven by the async runtime `RT`.
+pub struct Wait<NID: NodeId, N: NodeInfo = Node, RT: AsyncRuntime = TokioRuntime> {
     pub timeout: Duration,
-    pub rx: watch::Receiver<RaftMetrics<NID>>,
+    pub rx: watch::Receiver<RaftMetrics<NID, N, RT::Instant>>,
+    pub(crate) marker_rt: PhantomData<RT>,
 }
 
-impl<NID: NodeId> Wait<NID> {
+impl<NID: NodeId, N: NodeInfo, RT: AsyncRuntime> Wait<NID, N, RT> {
     /// Wait for metrics to satisfy some condition or timeout.
     #[tracing::instrument(level = "trace", skip(self, func), fields(msg=%msg.to_string()))]
-    pub async fn metrics<T>(&self, func: T, msg: impl ToString) -> Result<RaftMetrics<NID>, WaitError>
-    where T: Fn(&RaftMetrics<NID>) -> bool + Send {
-        let timeout_at = Instant::now() + self.timeout;
+    pub async fn metrics<T>(&self, func: T, msg: impl ToString) -> Result<RaftMetrics<NID, N, RT::Instant>, WaitError>
+    where T: Fn(&RaftMetrics<NID, N, RT::Instant>) -> bool + Send {
+        let timeout_at = RT::now() + self.timeout;
 
         let mut rx = self.rx.clone();
         loop {
@@ -55,7 +61,7 @@ impl<NID: NodeId> Wait<NID> {
                 return Ok(latest);
             }
 
-            let now = Instant::now();
+            let now = RT::now();
             if now >= timeout_at {
                 return Err(WaitError::Timeout(
                     self.timeout,
@@ -65,7 +71,7 @@ impl<NID: NodeId> Wait<NID> {
 
             let sleep_time = timeout_at - now;
             tracing::debug!(?sleep_time, "wait timeout");
-            let delay = tokio::time::sleep(sleep_time);
+            let delay = RT::sleep(sleep_time);
 
             tokio::select! {
                 _ = delay => {
@@ -96,17 +102,38 @@ impl<NID: NodeId> Wait<NID> {
 
     /// Wait for `current_leader` to become `Some(leader_id)` until timeout.
     #[tracing::instrument(level = "trace", skip(self), fields(msg=msg.to_string().as_str()))]
-    pub async fn current_leader(&self, leader_id: NID, msg: impl ToString) -> Result<RaftMetrics<NID>, WaitError> {
+    pub async fn current_leader(
+        &self,
+        leader_id: NID,
+        msg: impl ToString,
+    ) -> Result<RaftMetrics<NID, N, RT::Instant>, WaitError> {
         self.metrics(
-            |x| x.current_leader == Some(leader_id),
+            |x| x.current_leader.as_ref() == Some(&leader_id),
             &format!("{} .current_leader -> {}", msg.to_string(), leader_id),
         )
         .await
     }
 
+    /// Wait for `current_leader` to become `Some` until timeout.
+    #[tracing::instrument(level = "trace", skip(self), fields(msg=msg.to_string().as_str()))]
+    pub async fn current_leader_is_known(
+        &self,
+        msg: impl ToString,
+    ) -> Result<RaftMetrics<NID, N, RT::Instant>, WaitError> {
+        self.metrics(
+            |x| x.current_leader.is_some(),
+            &format!("{} .current_leader is known", msg.to_string()),
+        )
+        .await
+    }
+
     /// Wait until applied exactly `want_log`(inclusive) logs or timeout.
     #[tracing::instrument(level = "trace", skip(self), fields(msg=msg.to_string().as_str()))]
-    pub async fn log(&self, want_log_index: Option<u64>, msg: impl ToString) -> Result<RaftMetrics<NID>, WaitError> {
+    pub async fn log(
+        &self,
+        want_log_index: Option<u64>,
+        msg: impl ToString,
+    ) -> Result<RaftMetrics<NID, N, RT::Instant>, WaitError> {
         self.metrics(
             |x| x.last_log_index == want_log_index,
             &format!("{} .last_log_index -> {:?}", msg.to_string(), want_log_index),
@@ -122,7 +149,11 @@ impl<NID: NodeId> Wait<NID> {
 
     /// Wait until applied at least `want_log`(inclusive) logs or timeout.
     #[tracing::instrument(level = "trace", skip(self), fields(msg=msg.to_string().as_str()))]
-    pub async fn log_at_least(&self, want_log: Option<u64>, msg: impl ToString) -> Result<RaftMetrics<NID>, WaitError> {
+    pub async fn log_at_least(
+        &self,
+        want_log: Option<u64>,
+        msg: impl ToString,
+    ) -> Result<RaftMetrics<NID, N, RT::Instant>, WaitError> {
         self.metrics(
             |x| x.last_log_index >= want_log,
             &format!("{} .last_log_index >= {:?}", msg.to_string(), want_log),
@@ -136,9 +167,30 @@ impl<NID: NodeId> Wait<NID> {
         .await
     }
 
+    /// Wait until the state machine applied logs upto at least `want_index`(inclusive), or timeout.
+    ///
+    /// Unlike [`log_at_least()`](`Self::log_at_least`), it does not wait for the logs to be appended, thus it also
+    /// returns once the logs are applied by installing a snapshot.
+    #[tracing::instrument(level = "trace", skip(self), fields(msg=msg.to_string().as_str()))]
+    pub async fn applied_index(
+        &self,
+        want_index: u64,
+        msg: impl ToString,
+    ) -> Result<RaftMetrics<NID, N, RT::Instant>, WaitError> {
+        self.metrics(
+            |x| x.last_applied.index() >= Some(want_index),
+            &format!("{} .last_applied.index >= {}", msg.to_string(), want_index),
+        )
+        .await
+    }
+
     /// Wait for `state` to become `want_state` or timeout.
     #[tracing::instrument(level = "trace", skip(self), fields(msg=msg.to_string().as_str()))]
-    pub async fn state(&self, want_state: ServerState, msg: impl ToString) -> Result<RaftMetrics<NID>, WaitError> {
+    pub async fn state(
+        &self,
+        want_state: ServerState,
+        msg: impl ToString,
+    ) -> Result<RaftMetrics<NID, N, RT::Instant>, WaitError> {
         self.metrics(
             |x| x.state == want_state,
             &format!("{} .state -> {:?}", msg.to_string(), want_state),
@@ -152,10 +204,10 @@ impl<NID: NodeId> Wait<NID> {
         &self,
         want_members: BTreeSet<NID>,
         msg: impl ToString,
-    ) -> Result<RaftMetrics<NID>, WaitError> {
+    ) -> Result<RaftMetrics<NID, N, RT::Instant>, WaitError> {
         self.metrics(
             |x| {
-                let got = x.membership_config.nodes().map(|(nid, _)| *nid).collect::<BTreeSet<_>>();
+                let got = x.membership_config.nodes().map(|(nid, _)| nid.clone()).collect::<BTreeSet<_>>();
                 want_members == got
             },
             &format!("{} .members -> {:?}", msg.to_string(), want_members),
@@ -165,11 +217,31 @@ impl<NID: NodeId> Wait<NID> {
 
     /// Wait for `snapshot` to become `want_snapshot` or timeout.
     #[tracing::instrument(level = "trace", skip(self), fields(msg=msg.to_string().as_str()))]
-    pub async fn snapshot(&self, want_snapshot: LogId<NID>, msg: impl ToString) -> Result<RaftMetrics<NID>, WaitError> {
+    pub async fn snapshot(
+        &self,
+        want_snapshot: LogId<NID>,
+        msg: impl ToString,
+    ) -> Result<RaftMetrics<NID, N, RT::Instant>, WaitError> {
         self.metrics(
-            |x| x.snapshot == Some(want_snapshot),
+            |x| x.snapshot.as_ref() == Some(&want_snapshot),
             &format!("{} .snapshot -> {}", msg.to_string(), want_snapshot),
         )
         .await
     }
+
+    /// Wait for `purged` to become at least `want_purged` or timeout.
+    ///
+    /// Logs are purged in batches, thus the last purged log may go past `want_purged`.
+    #[tracing::instrument(level = "trace", skip(self), fields(msg=msg.to_string().as_str()))]
+    pub async fn purged(
+        &self,
+        want_purged: LogId<NID>,
+        msg: impl ToString,
+    ) -> Result<RaftMetrics<NID, N, RT::Instant>, WaitError> {
+        self.metrics(
+            |x| x.purged.as_ref() >= Some(&want_purged),
+            &format!("{} .purged >= {}", msg.to_string(), want_purged),
+        )
+        .await
+    }
 }
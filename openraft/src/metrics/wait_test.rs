@@ -2,26 +2,27 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use maplit::btreeset;
-use tokio::sync::watch;
 use tokio::time::sleep;
 
+use crate::async_runtime::watch;
 use crate::core::ServerState;
 use crate::membership::EffectiveMembership;
 use crate::metrics::Wait;
 use crate::metrics::WaitError;
 use crate::raft_types::LogIdOptionExt;
+use crate::AsyncRuntime;
 use crate::LeaderId;
 use crate::LogId;
 use crate::Membership;
-use crate::NodeId;
 use crate::RaftMetrics;
+use crate::TokioRuntime;
 
 /// Test wait for different state changes
 #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
 async fn test_wait() -> anyhow::Result<()> {
     {
         // wait for leader
-        let (init, w, tx) = init_wait_test::<u64>();
+        let (init, w, tx) = init_wait_test();
 
         let h = tokio::spawn(async move {
             sleep(Duration::from_millis(10)).await;
@@ -35,9 +36,25 @@ async fn test_wait() -> anyhow::Result<()> {
         assert_eq!(Some(3), got.current_leader);
     }
 
+    {
+        // wait for any leader
+        let (init, w, tx) = init_wait_test();
+
+        let h = tokio::spawn(async move {
+            sleep(Duration::from_millis(10)).await;
+            let mut update = init.clone();
+            update.current_leader = Some(2);
+            let rst = tx.send(update);
+            assert!(rst.is_ok());
+        });
+        let got = w.current_leader_is_known("leader").await?;
+        h.await?;
+        assert_eq!(Some(2), got.current_leader);
+    }
+
     {
         // wait for log
-        let (init, w, tx) = init_wait_test::<u64>();
+        let (init, w, tx) = init_wait_test();
 
         let h = tokio::spawn(async move {
             sleep(Duration::from_millis(10)).await;
@@ -63,9 +80,38 @@ async fn test_wait() -> anyhow::Result<()> {
         assert!(got_least4.is_err());
     }
 
+    {
+        // wait for applied index
+        let (init, w, tx) = init_wait_test();
+
+        let h = tokio::spawn(async move {
+            sleep(Duration::from_millis(10)).await;
+            let mut update = init.clone();
+            update.last_applied = Some(LogId::new(LeaderId::new(1, 0), 3));
+            let rst = tx.send(update);
+            assert!(rst.is_ok());
+        });
+        let got = w.applied_index(3, "applied").await?;
+        let got_least2 = w.applied_index(2, "applied").await?;
+        let got_least4 = w.applied_index(4, "applied").await;
+        h.await?;
+
+        assert_eq!(Some(3), got.last_applied.index());
+        assert_eq!(Some(3), got_least2.last_applied.index());
+
+        match got_least4.unwrap_err() {
+            WaitError::Timeout(t, _) => {
+                assert_eq!(Duration::from_millis(100), t);
+            }
+            _ => {
+                panic!("expect WaitError::Timeout");
+            }
+        }
+    }
+
     {
         // wait for state
-        let (init, w, tx) = init_wait_test::<u64>();
+        let (init, w, tx) = init_wait_test();
 
         let h = tokio::spawn(async move {
             sleep(Duration::from_millis(10)).await;
@@ -82,7 +128,7 @@ async fn test_wait() -> anyhow::Result<()> {
 
     {
         // wait for members
-        let (init, w, tx) = init_wait_test::<u64>();
+        let (init, w, tx) = init_wait_test();
 
         let h = tokio::spawn(async move {
             sleep(Duration::from_millis(10)).await;
@@ -105,7 +151,7 @@ async fn test_wait() -> anyhow::Result<()> {
 
     tracing::info!("--- wait for snapshot, Ok");
     {
-        let (init, w, tx) = init_wait_test::<u64>();
+        let (init, w, tx) = init_wait_test();
 
         let h = tokio::spawn(async move {
             sleep(Duration::from_millis(10)).await;
@@ -120,9 +166,31 @@ async fn test_wait() -> anyhow::Result<()> {
         assert_eq!(Some(LogId::new(LeaderId::new(1, 0), 2)), got.snapshot);
     }
 
+    tracing::info!("--- wait for purged, Ok");
+    {
+        let (init, w, tx) = init_wait_test();
+
+        let h = tokio::spawn(async move {
+            sleep(Duration::from_millis(10)).await;
+            let mut update = init.clone();
+            update.purged = Some(LogId::new(LeaderId::new(1, 0), 1));
+            let rst = tx.send(update.clone());
+            assert!(rst.is_ok());
+
+            sleep(Duration::from_millis(10)).await;
+            update.purged = Some(LogId::new(LeaderId::new(1, 0), 3));
+            let rst = tx.send(update);
+            assert!(rst.is_ok());
+        });
+        let got = w.purged(LogId::new(LeaderId::new(1, 0), 2), "purged").await?;
+        h.await?;
+
+        assert_eq!(Some(LogId::new(LeaderId::new(1, 0), 3)), got.purged);
+    }
+
     tracing::info!("--- wait for snapshot, only index matches");
     {
-        let (init, w, tx) = init_wait_test::<u64>();
+        let (init, w, tx) = init_wait_test();
 
         let h = tokio::spawn(async move {
             sleep(Duration::from_millis(10)).await;
@@ -147,7 +215,7 @@ async fn test_wait() -> anyhow::Result<()> {
 
     {
         // timeout
-        let (_init, w, _tx) = init_wait_test::<u64>();
+        let (_init, w, _tx) = init_wait_test();
 
         let h = tokio::spawn(async move {
             sleep(Duration::from_millis(200)).await;
@@ -170,10 +238,10 @@ async fn test_wait() -> anyhow::Result<()> {
 
 /// Build a initial state for testing of Wait:
 /// Returns init metrics, Wait, and the tx to send an updated metrics.
-fn init_wait_test<NID: NodeId>() -> (RaftMetrics<NID>, Wait<NID>, watch::Sender<RaftMetrics<NID>>) {
+fn init_wait_test() -> (RaftMetrics<u64>, Wait<u64>, watch::Sender<RaftMetrics<u64>>) {
     let init = RaftMetrics {
         running_state: Ok(()),
-        id: NID::default(),
+        id: 0,
         state: ServerState::Learner,
         current_term: 0,
         last_log_index: None,
@@ -183,14 +251,26 @@ fn init_wait_test<NID: NodeId>() -> (RaftMetrics<NID>, Wait<NID>, watch::Sender<
             None,
             Membership::new(vec![btreeset! {}], None),
         )),
+        last_election_pre_vote: false,
+        last_step_down: None,
+        transferring_leader_to: None,
+        last_quorum_acked: None,
+        learner_promotions: Default::default(),
 
         snapshot: None,
+        purged: None,
+        snapshot_build: Default::default(),
         replication: None,
+        replication_backoff: Default::default(),
+        replication_progress: Default::default(),
+        replication_lag: Default::default(),
+        log_cache: Default::default(),
     };
-    let (tx, rx) = watch::channel(init.clone());
+    let (tx, rx) = TokioRuntime::watch(init.clone());
     let w = Wait {
         timeout: Duration::from_millis(100),
         rx,
+        marker_rt: Default::default(),
     };
 
     (init, w, tx)
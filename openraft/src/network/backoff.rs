@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+/// Decides how long a replication stream waits before retrying a target that is unreachable.
+///
+/// It is provided by [`RaftNetwork::backoff()`](`crate::RaftNetwork::backoff`), and consulted after every
+/// [`Unreachable`](`crate::error::Unreachable`) error. The count of consecutive failures is reset once an RPC to the
+/// target succeeds.
+pub trait BackoffPolicy: Send + Sync + 'static {
+    /// The delay before the next retry, after `failures` consecutive failures. `failures` starts from 1.
+    fn delay(&self, failures: u64) -> Duration;
+}
+
+/// The default [`BackoffPolicy`]: exponential backoff with jitter.
+///
+/// After the `n`-th consecutive failure, the delay is `base * 2^(n-1)`, capped by `max`, with jitter in its upper
+/// half. The delay is never less than `base`.
+#[derive(Debug, Clone)]
+pub struct ExponentialBackoff {
+    base: Duration,
+    max: Duration,
+}
+
+impl ExponentialBackoff {
+    /// Create an exponential backoff. A `max` that is not greater than `base` retries every `base`.
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            base,
+            max: std::cmp::max(base, max),
+        }
+    }
+}
+
+impl BackoffPolicy for ExponentialBackoff {
+    fn delay(&self, failures: u64) -> Duration {
+        let shift = std::cmp::min(failures.saturating_sub(1), 31) as u32;
+        let delay = std::cmp::min(self.base.saturating_mul(1 << shift), self.max);
+
+        let lower = std::cmp::max(delay / 2, self.base);
+        if lower < delay {
+            crate::rng::gen_range(lower..=delay)
+        } else {
+            delay
+        }
+    }
+}
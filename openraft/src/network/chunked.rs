@@ -0,0 +1,179 @@
+use std::io::SeekFrom;
+use std::time::Duration;
+
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncSeek;
+use tokio::io::AsyncSeekExt;
+
+use crate::error::InstallSnapshotError;
+use crate::error::RPCError;
+use crate::error::StreamingError;
+use crate::network::RPCOption;
+use crate::network::SnapshotStreaming;
+use crate::raft::InstallSnapshotRequest;
+use crate::raft::SnapshotResponse;
+use crate::replication::throttle::Throttle;
+use crate::storage::Snapshot;
+use crate::AsyncRuntime;
+use crate::ErrorSubject;
+use crate::ErrorVerb;
+use crate::NodeId;
+use crate::RaftNetwork;
+use crate::RaftTypeConfig;
+use crate::ToStorageResult;
+use crate::Vote;
+
+/// Streams a snapshot in chunks with [`RaftNetwork::install_snapshot`], for an implementation of
+/// [`RaftNetwork::full_snapshot`] to send a file-like snapshot.
+///
+/// The target writes the chunks into the snapshot data created by its storage, see
+/// [`Raft::install_snapshot`](`crate::Raft::install_snapshot`). It requires [`RaftTypeConfig::SnapshotData`] to be
+/// file-like on both ends.
+pub struct Chunked {}
+
+impl Chunked {
+    /// Send `snapshot` to the target of `net` in chunks.
+    ///
+    /// A chunk that fails is sent again, from the offset the target asks to resume from, if any. It returns when the
+    /// last chunk is accepted, or when the target responds with a greater vote than `vote`.
+    #[tracing::instrument(level = "debug", skip_all, fields(meta = ?snapshot.meta))]
+    pub async fn send_snapshot<C, Net>(
+        net: &mut Net,
+        vote: Vote<C::NodeId>,
+        mut snapshot: Snapshot<C::NodeId, C::SnapshotData, C::Node>,
+        streaming: SnapshotStreaming,
+    ) -> Result<SnapshotResponse<C::NodeId>, StreamingError<C::NodeId>>
+    where
+        C: RaftTypeConfig,
+        C::SnapshotData: AsyncRead + AsyncSeek + Unpin,
+        Net: RaftNetwork<C> + ?Sized,
+    {
+        let err_x = || (ErrorSubject::Snapshot(snapshot.meta.signature()), ErrorVerb::Read);
+
+        let end = snapshot.snapshot.seek(SeekFrom::End(0)).await.sto_res(err_x)?;
+
+        let mut offset = 0;
+
+        streaming.report_progress(0, end);
+
+        let mut buf = Vec::with_capacity(streaming.chunk_size() as usize);
+
+        let mut throttle = streaming.max_bytes_per_sec().map(|rate| Throttle::new(rate, C::AsyncRuntime::now()));
+
+        let snapshot_checksum = if streaming.enable_checksum() {
+            let mut checksum = crc32fast::Hasher::new();
+            snapshot.snapshot.seek(SeekFrom::Start(0)).await.sto_res(err_x)?;
+            loop {
+                let n_read = snapshot.snapshot.read_buf(&mut buf).await.sto_res(err_x)?;
+                if n_read == 0 {
+                    break;
+                }
+                checksum.update(&buf[..n_read]);
+                buf.clear();
+            }
+            Some(checksum.finalize())
+        } else {
+            None
+        };
+
+        loop {
+            // Build the RPC.
+            snapshot.snapshot.seek(SeekFrom::Start(offset)).await.sto_res(err_x)?;
+
+            let n_read = snapshot.snapshot.read_buf(&mut buf).await.sto_res(err_x)?;
+
+            let done = (offset + n_read as u64) == end; // If bytes read == 0, then we're done.
+            let data = Vec::from(&buf[..n_read]);
+            let req = InstallSnapshotRequest {
+                vote: vote.clone(),
+                meta: snapshot.meta.clone(),
+                offset,
+                chunk_checksum: snapshot_checksum.map(|_| crc32fast::hash(&data)),
+                snapshot_checksum: if done { snapshot_checksum } else { None },
+                data,
+                done,
+            };
+            buf.clear();
+
+            if let Some(throttle) = &mut throttle {
+                let delay = throttle.take(n_read as u64, C::AsyncRuntime::now());
+                if delay > Duration::default() {
+                    tracing::debug!("throttle snapshot chunk for {:?}", delay);
+                    C::AsyncRuntime::sleep(delay).await;
+                }
+            }
+
+            // Send the RPC over to the target.
+            tracing::debug!(
+                snapshot_size = req.data.len(),
+                req.offset,
+                end,
+                req.done,
+                "sending snapshot chunk"
+            );
+
+            let option = RPCOption::new(streaming.chunk_timeout());
+            let res = C::AsyncRuntime::timeout(streaming.chunk_timeout(), net.install_snapshot(req, option)).await;
+
+            let res = match res {
+                Ok(outer_res) => match outer_res {
+                    Ok(res) => res,
+                    Err(err) => {
+                        tracing::warn!(error=%err, "error sending InstallSnapshot RPC to target");
+
+                        if let RPCError::RemoteError(remote_err) = &err {
+                            offset = resume_offset(&snapshot.meta.snapshot_id, offset, &remote_err.source);
+                        }
+                        continue;
+                    }
+                },
+                Err(err) => {
+                    tracing::warn!(error=%err, "timeout while sending InstallSnapshot RPC to target");
+                    continue;
+                }
+            };
+
+            // Handle response conditions.
+            if res.vote > vote {
+                return Ok(SnapshotResponse { vote: res.vote });
+            }
+
+            // If we just sent the final chunk of the snapshot, the target has installed it.
+            if done {
+                tracing::debug!(
+                    "done install snapshot: snapshot last_log_id: {}",
+                    snapshot.meta.last_log_id
+                );
+                return Ok(SnapshotResponse { vote: res.vote });
+            }
+
+            // Everything is good, so update offset for sending the next chunk.
+            // The target may have received more bytes, e.g., in a previous attempt to send the same snapshot.
+            offset = match res.next_offset {
+                Some(next) => {
+                    if next != offset + n_read as u64 {
+                        tracing::info!(next, "target asks to resume snapshot from offset");
+                    }
+                    next
+                }
+                None => offset + n_read as u64,
+            };
+
+            streaming.report_progress(offset, end);
+        }
+    }
+}
+
+/// The offset to re-send a snapshot from, after the target rejected the chunk at `offset` with `err`.
+fn resume_offset<NID: NodeId>(snapshot_id: &str, offset: u64, err: &InstallSnapshotError<NID>) -> u64 {
+    match err {
+        // The target is receiving the same snapshot: resume from the bytes it has received.
+        InstallSnapshotError::SnapshotMismatch(e) if e.expect.id == snapshot_id => e.expect.offset,
+        // The target is not receiving this snapshot, e.g., it has restarted.
+        InstallSnapshotError::SnapshotMismatch(_) => 0,
+        // The target dropped the whole snapshot because its checksum does not match.
+        InstallSnapshotError::ChecksumMismatch(e) if e.offset.is_none() => 0,
+        _ => offset,
+    }
+}
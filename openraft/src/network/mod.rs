@@ -0,0 +1,315 @@
+//! The Raft network interface.
+
+mod backoff;
+mod chunked;
+mod snapshot_streaming;
+
+use std::error::Error;
+use std::fmt::Formatter;
+use std::time::Duration;
+
+use anyerror::AnyError;
+use async_trait::async_trait;
+pub use backoff::BackoffPolicy;
+pub use backoff::ExponentialBackoff;
+pub use chunked::Chunked;
+pub use snapshot_streaming::SnapshotStreaming;
+
+use crate::error::AppendEntriesError;
+use crate::error::ClientWriteError;
+use crate::error::Fatal;
+use crate::error::InstallSnapshotError;
+use crate::error::NetworkError;
+use crate::error::RPCError;
+use crate::error::StreamingError;
+use crate::error::VoteError;
+use crate::raft::AppendEntriesRequest;
+use crate::raft::AppendEntriesResponse;
+use crate::raft::ClientWriteResponse;
+use crate::raft::ForwardClientWriteRequest;
+use crate::raft::InstallSnapshotRequest;
+use crate::raft::InstallSnapshotResponse;
+use crate::raft::SnapshotFromRequest;
+use crate::raft::SnapshotFromResponse;
+use crate::raft::SnapshotResponse;
+use crate::raft::TimeoutNowRequest;
+use crate::raft::TimeoutNowResponse;
+use crate::raft::VoteRequest;
+use crate::raft::VoteResponse;
+use crate::storage::Snapshot;
+use crate::Config;
+use crate::NodeId;
+use crate::NodeInfo;
+use crate::RaftTypeConfig;
+use crate::Vote;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum RPCTypes {
+    Vote,
+    AppendEntries,
+    InstallSnapshot,
+    TimeoutNow,
+}
+
+impl std::fmt::Display for RPCTypes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Whether `err` tells the connection to the target is broken, after which
+/// [`RaftNetwork::on_connection_error()`] is called on the client that sent the RPC.
+pub(crate) fn is_connection_error<NID: NodeId, E: Error, N: NodeInfo>(err: &RPCError<NID, E, N>) -> bool {
+    matches!(err, RPCError::Unreachable(_) | RPCError::Network(_))
+}
+
+/// Options for sending an RPC, provided by openraft to [`RaftNetwork`].
+///
+/// Openraft computes a deadline for every RPC by its kind: a heartbeat is expected to return within
+/// [`Config::heartbeat_interval`], an AppendEntries carrying logs within [`Config::append_entries_timeout`], a snapshot
+/// chunk within [`Config::install_snapshot_timeout`] and a vote request within [`Config::election_timeout_min`].
+///
+/// Openraft gives up on an RPC that does not return in `hard_ttl`, except for a vote request. An implementation can
+/// use it to set the transport level timeout.
+///
+/// [`Config::heartbeat_interval`]: crate::Config::heartbeat_interval
+/// [`Config::append_entries_timeout`]: crate::Config::append_entries_timeout
+/// [`Config::install_snapshot_timeout`]: crate::Config::install_snapshot_timeout
+/// [`Config::election_timeout_min`]: crate::Config::election_timeout_min
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RPCOption {
+    hard_ttl: Duration,
+}
+
+impl RPCOption {
+    /// Create an option for an RPC that openraft gives up waiting for after `hard_ttl`.
+    pub fn new(hard_ttl: Duration) -> Self {
+        Self { hard_ttl }
+    }
+
+    /// The duration after which openraft gives up waiting for the response.
+    pub fn hard_ttl(&self) -> Duration {
+        self.hard_ttl
+    }
+
+    /// The suggested duration for the transport to wait for a response, which is 3/4 of `hard_ttl`.
+    ///
+    /// A transport that times out before openraft does is able to report a more accurate error.
+    pub fn soft_ttl(&self) -> Duration {
+        self.hard_ttl * 3 / 4
+    }
+}
+
+/// A trait defining the interface for a Raft network between cluster members.
+///
+/// See the [network chapter of the guide](https://datafuselabs.github.io/openraft/getting-started.html#3-impl-raftnetwork)
+/// for details and discussion on this trait and how to implement it.
+///
+/// Typically, the network implementation as such will be hidden behind a `Box<T>` or `Arc<T>` and
+/// this interface implemented on the `Box<T>` or `Arc<T>`.
+///
+/// A single network instance is used to connect to a single target node. The network instance is
+/// constructed by the [`RaftNetworkFactory`].
+///
+/// Openraft calls [`append_entries`](`RaftNetwork::append_entries`),
+/// [`install_snapshot`](`RaftNetwork::install_snapshot`) and [`vote`](`RaftNetwork::vote`), which receive an
+/// [`RPCOption`] with the deadline of the RPC. By default they ignore the option and call `send_append_entries`,
+/// `send_install_snapshot` and `send_vote`. To migrate, an implementation that needs the deadline overrides these
+/// methods, and implements the `send_*` methods by calling them with an option of its own choice.
+///
+/// The kind of [`RPCError`] an implementation returns decides how replication reacts to it:
+/// - [`RPCError::Unreachable`]: the target is down, replication backs off with the policy from
+///   [`backoff()`](`RaftNetwork::backoff`).
+/// - [`RPCError::Timeout`] and [`RPCError::Network`]: replication retries on the next heartbeat. A timeout is not an
+///   acknowledgement, thus it counts toward check-quorum.
+/// - [`RPCError::PayloadTooLarge`]: the logs are sent again in smaller AppendEntries requests.
+/// - [`RPCError::RemoteError`]: the target received the RPC and returned an error.
+#[async_trait]
+pub trait RaftNetwork<C>: Send + Sync + 'static
+where C: RaftTypeConfig
+{
+    /// Send an AppendEntries RPC to the target Raft node (§5).
+    async fn send_append_entries(
+        &mut self,
+        rpc: AppendEntriesRequest<C>,
+    ) -> Result<AppendEntriesResponse<C::NodeId>, RPCError<C::NodeId, AppendEntriesError<C::NodeId>, C::Node>>;
+
+    /// Send an InstallSnapshot RPC to the target Raft node (§7).
+    async fn send_install_snapshot(
+        &mut self,
+        rpc: InstallSnapshotRequest<C>,
+    ) -> Result<InstallSnapshotResponse<C::NodeId>, RPCError<C::NodeId, InstallSnapshotError<C::NodeId>, C::Node>>;
+
+    /// Send a RequestVote RPC to the target Raft node (§5).
+    async fn send_vote(
+        &mut self,
+        rpc: VoteRequest<C::NodeId>,
+    ) -> Result<VoteResponse<C::NodeId>, RPCError<C::NodeId, VoteError<C::NodeId>, C::Node>>;
+
+    /// The policy to back off replication to the target, after an RPC to it fails with
+    /// [`RPCError::Unreachable`].
+    ///
+    /// The default is an [`ExponentialBackoff`] with jitter, from [`Config::replication_backoff_base`] up to
+    /// [`Config::max_replication_backoff`]. It is called once when a replication stream to the target starts.
+    ///
+    /// [`Config::replication_backoff_base`]: crate::Config::replication_backoff_base
+    /// [`Config::max_replication_backoff`]: crate::Config::max_replication_backoff
+    fn backoff(&self, config: &Config) -> Box<dyn BackoffPolicy> {
+        Box::new(ExponentialBackoff::new(
+            Duration::from_millis(config.replication_backoff_base),
+            Duration::from_millis(config.max_replication_backoff),
+        ))
+    }
+
+    /// Called after an RPC sent with this client fails because the connection to the target is broken, i.e., with
+    /// [`RPCError::Unreachable`] or [`RPCError::Network`], or a snapshot fails to be sent with a network error.
+    ///
+    /// A client is kept after such a failure and is used to send the following RPCs, see [`RaftNetworkFactory`]. An
+    /// implementation whose transport may go stale, e.g., a gRPC channel to a target that restarted, rebuilds it
+    /// here. The default implementation does nothing.
+    fn on_connection_error(&mut self) {}
+
+    /// Send an AppendEntries RPC to the target Raft node, with the deadline in `option`.
+    ///
+    /// The default implementation calls [`send_append_entries`](`RaftNetwork::send_append_entries`).
+    async fn append_entries(
+        &mut self,
+        rpc: AppendEntriesRequest<C>,
+        option: RPCOption,
+    ) -> Result<AppendEntriesResponse<C::NodeId>, RPCError<C::NodeId, AppendEntriesError<C::NodeId>, C::Node>> {
+        let _ = option;
+        self.send_append_entries(rpc).await
+    }
+
+    /// Send an InstallSnapshot RPC carrying a snapshot chunk to the target Raft node, with the deadline in `option`.
+    ///
+    /// The default implementation calls [`send_install_snapshot`](`RaftNetwork::send_install_snapshot`).
+    async fn install_snapshot(
+        &mut self,
+        rpc: InstallSnapshotRequest<C>,
+        option: RPCOption,
+    ) -> Result<InstallSnapshotResponse<C::NodeId>, RPCError<C::NodeId, InstallSnapshotError<C::NodeId>, C::Node>> {
+        let _ = option;
+        self.send_install_snapshot(rpc).await
+    }
+
+    /// Send a snapshot to the target Raft node.
+    ///
+    /// If [`RaftTypeConfig::SnapshotData`] is file-like, it can be streamed in chunks with
+    /// [`install_snapshot`](`RaftNetwork::install_snapshot`) by calling
+    /// `Chunked::send_snapshot(self, vote, snapshot, streaming).await`, see [`Chunked`]. An application that transports
+    /// snapshots by itself, e.g., by sending the checkpoint a snapshot refers to, sends the snapshot as a whole
+    /// instead. The target installs it with [`Raft::install_full_snapshot`], which returns the response to send back.
+    ///
+    /// A snapshot that fails to be sent with a [`StreamingError::Network`] is sent again after a heartbeat interval.
+    ///
+    /// [`Raft::install_full_snapshot`]: crate::Raft::install_full_snapshot
+    async fn full_snapshot(
+        &mut self,
+        vote: Vote<C::NodeId>,
+        snapshot: Snapshot<C::NodeId, C::SnapshotData, C::Node>,
+        streaming: SnapshotStreaming,
+    ) -> Result<SnapshotResponse<C::NodeId>, StreamingError<C::NodeId>>;
+
+    /// Let the target Raft node fetch a snapshot from another node, instead of receiving the snapshot of the leader.
+    ///
+    /// It is only used when [`Config::snapshot_source`] is [`SnapshotSource::Voters`]. The target is expected to fetch
+    /// the snapshot of `rpc.source`, which can be read there with [`Raft::get_snapshot`], and install it with
+    /// [`Raft::install_full_snapshot`] if it includes `rpc.must_include`. Otherwise, the snapshot of the source is
+    /// stale and the target responds with `last_log_id: None`, which lets the leader send its own snapshot.
+    ///
+    /// The default implementation returns a network error, in which case the leader sends its own snapshot too.
+    ///
+    /// [`Config::snapshot_source`]: crate::Config::snapshot_source
+    /// [`SnapshotSource::Voters`]: crate::SnapshotSource::Voters
+    /// [`Raft::get_snapshot`]: crate::Raft::get_snapshot
+    /// [`Raft::install_full_snapshot`]: crate::Raft::install_full_snapshot
+    async fn get_snapshot_from(
+        &mut self,
+        rpc: SnapshotFromRequest<C>,
+        option: RPCOption,
+    ) -> Result<SnapshotFromResponse<C::NodeId>, RPCError<C::NodeId, Fatal<C::NodeId>, C::Node>> {
+        let _ = (rpc, option);
+        Err(NetworkError::new(&AnyError::error("GetSnapshotFrom RPC is not implemented")).into())
+    }
+
+    /// Send a RequestVote RPC to the target Raft node, with the deadline in `option`.
+    ///
+    /// The default implementation calls [`send_vote`](`RaftNetwork::send_vote`).
+    async fn vote(
+        &mut self,
+        rpc: VoteRequest<C::NodeId>,
+        option: RPCOption,
+    ) -> Result<VoteResponse<C::NodeId>, RPCError<C::NodeId, VoteError<C::NodeId>, C::Node>> {
+        let _ = option;
+        self.send_vote(rpc).await
+    }
+
+    /// Send a TimeoutNow RPC to the target Raft node, to let it start an election at once.
+    ///
+    /// It is only used when the leader transfers its leadership to the target, see [`Raft::transfer_leadership`].
+    /// The default implementation returns a network error, i.e., leadership transfer is not supported.
+    ///
+    /// [`Raft::transfer_leadership`]: crate::Raft::transfer_leadership
+    async fn send_timeout_now(
+        &mut self,
+        rpc: TimeoutNowRequest<C::NodeId>,
+    ) -> Result<TimeoutNowResponse<C::NodeId>, RPCError<C::NodeId, VoteError<C::NodeId>, C::Node>> {
+        let _ = rpc;
+        Err(NetworkError::new(&AnyError::error("TimeoutNow RPC is not implemented")).into())
+    }
+
+    /// Forward a client write to the target Raft node, which is believed to be the leader.
+    ///
+    /// It is only used when [`Config::enable_forward_client_write`] is set. The target should handle it with
+    /// [`Raft::forward_client_write`]. The default implementation returns a network error, in which case the caller of
+    /// `Raft::client_write()` receives a `ForwardToLeader` error.
+    ///
+    /// [`Config::enable_forward_client_write`]: crate::Config::enable_forward_client_write
+    /// [`Raft::forward_client_write`]: crate::Raft::forward_client_write
+    async fn forward_client_write(
+        &mut self,
+        rpc: ForwardClientWriteRequest<C>,
+    ) -> Result<ClientWriteResponse<C>, RPCError<C::NodeId, ClientWriteError<C::NodeId, C::Node, C::AppError>, C::Node>>
+    {
+        let _ = rpc;
+        Err(NetworkError::new(&AnyError::error("ForwardClientWrite RPC is not implemented")).into())
+    }
+}
+
+/// A trait defining the interface for a Raft network factory to create connections between cluster members.
+///
+/// See the [network chapter of the guide](https://datafuselabs.github.io/openraft/getting-started.html#3-impl-raftnetwork)
+/// for details and discussion on this trait and how to implement it.
+///
+/// Typically, the network implementation as such will be hidden behind a `Box<T>` or `Arc<T>` and
+/// this interface implemented on the `Box<T>` or `Arc<T>`.
+///
+/// The lifecycle of a client created by [`connect()`](`RaftNetworkFactory::connect`):
+/// - A leader creates [`Config::max_inflight_appends`] clients for every replication stream, and one for the heartbeat
+///   worker of every target, when it is established or a target is added. They are reused for every AppendEntries,
+///   heartbeat and snapshot to the target, until the leader steps down or the target is removed, when they are dropped.
+/// - A client is not recreated when an RPC fails: instead [`RaftNetwork::on_connection_error()`] is called on it, to
+///   let it rebuild its transport.
+/// - A client created for other RPCs, e.g., the vote requests of an election, a TimeoutNow, a forwarded client write,
+///   or the heartbeats confirming a leadership for a linearizable read, is used once and dropped.
+///
+/// Since clients are created often, an implementation may keep a pool of connections in the factory, and let the
+/// clients share them.
+#[async_trait]
+pub trait RaftNetworkFactory<C>: Send + Sync + 'static
+where C: RaftTypeConfig
+{
+    /// Actual type of the network handling a single connection.
+    type Network: RaftNetwork<C>;
+
+    /// Create a new network instance sending RPCs to the target node.
+    ///
+    /// This doesn't have to be a "real" connection, the network instance is just configured to send
+    /// RPCs to the specified target node.
+    ///
+    /// The method is intentionally async to give the implementation a chance to use asynchronous
+    /// sync primitives to serialize access to the common internal object, if needed.
+    async fn connect(&mut self, target: C::NodeId, node: Option<&C::Node>) -> Self::Network;
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::metrics::SnapshotTransmission;
+use crate::Config;
+
+/// How to send a snapshot, provided by openraft to [`RaftNetwork::full_snapshot`](`crate::RaftNetwork::full_snapshot`).
+///
+/// The options are taken from the [`Config`]: [`Chunked`](`crate::network::Chunked`) uses them to stream a snapshot
+/// in chunks, and an application that sends a snapshot by itself may use them too, or ignore them.
+pub struct SnapshotStreaming {
+    chunk_size: u64,
+    chunk_timeout: Duration,
+    max_bytes_per_sec: Option<u64>,
+    enable_checksum: bool,
+
+    /// Reports the bytes sent so far to the replication metrics.
+    report: Box<dyn Fn(SnapshotTransmission) + Send + Sync + 'static>,
+}
+
+impl SnapshotStreaming {
+    pub(crate) fn new<F>(config: &Config, report: F) -> Self
+    where F: Fn(SnapshotTransmission) + Send + Sync + 'static {
+        Self {
+            chunk_size: config.snapshot_max_chunk_size,
+            chunk_timeout: Duration::from_millis(config.install_snapshot_timeout),
+            max_bytes_per_sec: config.install_snapshot_max_bytes_per_sec,
+            enable_checksum: config.enable_snapshot_checksum,
+            report: Box::new(report),
+        }
+    }
+
+    /// The max size in bytes of a chunk, see [`Config::snapshot_max_chunk_size`].
+    pub fn chunk_size(&self) -> u64 {
+        self.chunk_size
+    }
+
+    /// The timeout for sending a chunk, see [`Config::install_snapshot_timeout`].
+    pub fn chunk_timeout(&self) -> Duration {
+        self.chunk_timeout
+    }
+
+    /// The max number of bytes to send per second, see [`Config::install_snapshot_max_bytes_per_sec`].
+    pub fn max_bytes_per_sec(&self) -> Option<u64> {
+        self.max_bytes_per_sec
+    }
+
+    /// Whether to send CRC32 checksums along with the chunks, see [`Config::enable_snapshot_checksum`].
+    pub fn enable_checksum(&self) -> bool {
+        self.enable_checksum
+    }
+
+    /// Report that `sent` out of `total` bytes are acknowledged by the target.
+    ///
+    /// The progress is shown in [`ReplicationProgress::snapshot`](`crate::metrics::ReplicationProgress::snapshot`).
+    pub fn report_progress(&self, sent: u64, total: u64) {
+        (self.report)(SnapshotTransmission { sent, total });
+    }
+}
+
+impl fmt::Debug for SnapshotStreaming {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnapshotStreaming")
+            .field("chunk_size", &self.chunk_size)
+            .field("chunk_timeout", &self.chunk_timeout)
+            .field("max_bytes_per_sec", &self.max_bytes_per_sec)
+            .field("enable_checksum", &self.enable_checksum)
+            .finish()
+    }
+}
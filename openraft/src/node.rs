@@ -1,3 +1,4 @@
+use std::any::Any;
 use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::fmt::Display;
@@ -7,26 +8,12 @@ use std::hash::Hash;
 /// Essential trait bound for node-id, except serde.
 #[doc(hidden)]
 pub trait NodeIdEssential:
-    Sized + Send + Sync + Eq + PartialEq + Ord + PartialOrd + Debug + Display + Hash + Copy + Clone + Default + 'static
+    Sized + Send + Sync + Eq + PartialEq + Ord + PartialOrd + Debug + Display + Hash + Clone + 'static
 {
 }
 
-impl<T> NodeIdEssential for T where T: Sized
-        + Send
-        + Sync
-        + Eq
-        + PartialEq
-        + Ord
-        + PartialOrd
-        + Debug
-        + Display
-        + Hash
-        + Copy
-        + Clone
-        + Default
-        + 'static
-{
-}
+impl<T> NodeIdEssential for T where T: Sized + Send + Sync + Eq + PartialEq + Ord + PartialOrd + Debug + Display + Hash + Clone + 'static
+{}
 
 /// A Raft node's ID.
 ///
@@ -43,7 +30,41 @@ pub trait NodeId: NodeIdEssential {}
 #[cfg(not(feature = "serde"))]
 impl<T> NodeId for T where T: NodeIdEssential {}
 
-/// Additional node information.
+/// Essential trait bound for node info, except serde.
+#[doc(hidden)]
+pub trait NodeInfoEssential:
+    Sized + Send + Sync + Eq + PartialEq + Debug + Display + Clone + Default + 'static
+{
+}
+
+impl<T> NodeInfoEssential for T where T: Sized + Send + Sync + Eq + PartialEq + Debug + Display + Clone + Default + 'static
+{}
+
+/// Additional information about a Raft node, such as its network address.
+///
+/// An application defines its own node info type with [`RaftTypeConfig::Node`](`crate::RaftTypeConfig::Node`).
+/// If it does not, the default [`Node`] is used.
+///
+/// The only attribute openraft reads from a node, the election priority, is stored in [`Node::data`]. A node of an
+/// application defined type has no priority.
+#[cfg(feature = "serde")]
+pub trait NodeInfo: NodeInfoEssential + serde::Serialize + for<'a> serde::Deserialize<'a> {}
+
+#[cfg(feature = "serde")]
+impl<T> NodeInfo for T where T: NodeInfoEssential + serde::Serialize + for<'a> serde::Deserialize<'a> {}
+
+#[cfg(not(feature = "serde"))]
+pub trait NodeInfo: NodeInfoEssential {}
+
+#[cfg(not(feature = "serde"))]
+impl<T> NodeInfo for T where T: NodeInfoEssential {}
+
+/// Returns the node info as the default [`Node`], or `None` if it is of an application defined type.
+pub(crate) fn as_default_node<N: NodeInfo>(node: &N) -> Option<&Node> {
+    (node as &dyn Any).downcast_ref::<Node>()
+}
+
+/// The default node info implementation.
 ///
 /// The most common usage is to store the connecting address of a node.
 /// So that an application does not need an additional store to support its RaftNetwork implementation.
@@ -51,6 +72,11 @@ impl<T> NodeId for T where T: NodeIdEssential {}
 /// An application is also free not to use this storage and implements its own node-id to address mapping.
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize),
+    archive(check_bytes)
+)]
 pub struct Node {
     pub addr: String,
     /// Other User defined data.
@@ -58,12 +84,47 @@ pub struct Node {
 }
 
 impl Node {
+    /// The reserved key in [`Node::data`] to store the election priority.
+    pub const ELECTION_PRIORITY_KEY: &'static str = "openraft.election_priority";
+
     pub fn new(addr: impl ToString) -> Self {
         Self {
             addr: addr.to_string(),
             ..Default::default()
         }
     }
+
+    /// Attach a piece of user defined data to this node, overriding the previous value of the same key.
+    ///
+    /// It returns `Self` so that it can be chained:
+    /// `Node::new("127.0.0.1:21001").with_data("zone", "us-east-1").with_data("rack", "r3")`.
+    pub fn with_data(mut self, key: impl ToString, value: impl ToString) -> Self {
+        self.data.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Get the user defined data by key.
+    pub fn get_data(&self, key: &str) -> Option<&str> {
+        self.data.get(key).map(|x| x.as_str())
+    }
+
+    /// Set the election priority of this node, a node with higher priority is preferred to become the leader.
+    ///
+    /// A voter delays its election, by `k * election_timeout_max` milliseconds, where `k` is the number of distinct
+    /// priorities higher than its own among the voters. A node without priority has priority 0.
+    /// A candidate also stops its election when it receives a vote request from a higher priority candidate whose log
+    /// is at least as up-to-date as its own.
+    ///
+    /// It is stored in [`Node::data`] with key [`Node::ELECTION_PRIORITY_KEY`], and takes effect only if
+    /// [`RaftTypeConfig::Node`](`crate::RaftTypeConfig::Node`) is `Node`.
+    pub fn with_election_priority(self, priority: u64) -> Self {
+        self.with_data(Self::ELECTION_PRIORITY_KEY, priority)
+    }
+
+    /// The election priority of this node. See [`Node::with_election_priority()`].
+    pub fn election_priority(&self) -> Option<u64> {
+        self.get_data(Self::ELECTION_PRIORITY_KEY).and_then(|x| x.parse().ok())
+    }
 }
 
 impl Display for Node {
@@ -0,0 +1,151 @@
+use std::any::TypeId;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::num::NonZeroU64;
+
+use maplit::btreemap;
+use maplit::btreeset;
+
+use crate::node::as_default_node;
+use crate::Membership;
+use crate::MessageSummary;
+use crate::Node;
+use crate::RaftState;
+use crate::RaftTypeConfig;
+use crate::Vote;
+
+/// An application defined node type.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+struct Endpoint {
+    host: String,
+    port: u16,
+}
+
+impl Display for Endpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.host, self.port)
+    }
+}
+
+/// An application defined node id that has no `Default` value.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+struct NonZeroNodeId(NonZeroU64);
+
+impl Display for NonZeroNodeId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+crate::declare_raft_types!(
+    pub(crate) DefaultNodeConfig: D = (), R = (), NodeId = u64
+);
+
+crate::declare_raft_types!(
+    pub(crate) EndpointConfig: D = (), R = (), NodeId = u64, Node = Endpoint
+);
+
+crate::declare_raft_types!(
+    pub(crate) NonZeroNodeIdConfig: D = (), R = (), NodeId = NonZeroNodeId
+);
+
+#[test]
+fn test_node_with_data() -> anyhow::Result<()> {
+    let n = Node::new("127.0.0.1:21001").with_data("zone", "us-east-1").with_data("rack", "r3");
+
+    assert_eq!("127.0.0.1:21001", n.addr);
+    assert_eq!(
+        btreemap! {
+            "rack".to_string() => "r3".to_string(),
+            "zone".to_string() => "us-east-1".to_string(),
+        },
+        n.data
+    );
+
+    assert_eq!(Some("us-east-1"), n.get_data("zone"));
+    assert_eq!(Some("r3"), n.get_data("rack"));
+    assert_eq!(None, n.get_data("foo"));
+
+    Ok(())
+}
+
+#[test]
+fn test_node_with_data_override() -> anyhow::Result<()> {
+    let n = Node::new("127.0.0.1:21001").with_data("zone", "us-east-1").with_data("zone", "us-west-2");
+
+    assert_eq!(1, n.data.len());
+    assert_eq!(Some("us-west-2"), n.get_data("zone"));
+
+    Ok(())
+}
+
+#[test]
+fn test_node_election_priority() -> anyhow::Result<()> {
+    let n = Node::new("127.0.0.1:21001");
+    assert_eq!(None, n.election_priority());
+
+    let n = n.with_election_priority(3);
+    assert_eq!(Some(3), n.election_priority());
+    assert_eq!(Some("3"), n.get_data(Node::ELECTION_PRIORITY_KEY));
+
+    let n = n.with_data(Node::ELECTION_PRIORITY_KEY, "foo");
+    assert_eq!(None, n.election_priority(), "invalid priority is ignored");
+
+    assert_eq!(
+        Some(3),
+        as_default_node(&Node::new("a").with_election_priority(3)).and_then(|n| n.election_priority())
+    );
+    assert_eq!(
+        None,
+        as_default_node(&Endpoint::default()),
+        "no priority for a custom node type"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_declare_raft_types_default_node() -> anyhow::Result<()> {
+    assert_eq!(
+        TypeId::of::<Node>(),
+        TypeId::of::<<DefaultNodeConfig as RaftTypeConfig>::Node>()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_declare_raft_types_with_node() -> anyhow::Result<()> {
+    assert_eq!(
+        TypeId::of::<Endpoint>(),
+        TypeId::of::<<EndpointConfig as RaftTypeConfig>::Node>()
+    );
+
+    let endpoint = Endpoint {
+        host: "127.0.0.1".to_string(),
+        port: 21001,
+    };
+
+    let m = Membership::<u64, <EndpointConfig as RaftTypeConfig>::Node>::with_nodes(
+        vec![btreeset! {1}],
+        btreemap! {1=>endpoint.clone()},
+    )?;
+
+    assert_eq!(Some(&endpoint), m.get_node(&1));
+    assert_eq!("members:[{1:{127.0.0.1:21001}}],learners:[]", m.summary());
+
+    Ok(())
+}
+
+#[test]
+fn test_node_id_without_default() -> anyhow::Result<()> {
+    let id = || NonZeroNodeId(NonZeroU64::new(3).unwrap());
+
+    let st = RaftState::<<NonZeroNodeIdConfig as RaftTypeConfig>::NodeId>::new(id());
+    assert_eq!(Vote::new(0, id()), st.vote);
+    assert_eq!(None, st.last_log_id());
+
+    Ok(())
+}
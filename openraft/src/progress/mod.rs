@@ -96,7 +96,7 @@ pub(crate) struct Stat {
 
 impl<ID, V, QS> VecProgress<ID, V, QS>
 where
-    ID: PartialEq + Copy + Debug + 'static,
+    ID: PartialEq + Clone + Debug + 'static,
     V: PartialOrd + Ord + Default + 'static,
     QS: QuorumSet<ID>,
 {
@@ -151,8 +151,8 @@ where
 
 impl<ID, V, QS> Progress<ID, V, QS> for VecProgress<ID, V, QS>
 where
-    ID: PartialEq + Debug + Copy + 'static,
-    V: PartialOrd + Ord + Copy + Default + 'static,
+    ID: PartialEq + Debug + Clone + 'static,
+    V: PartialOrd + Ord + Clone + Default + 'static,
     QS: QuorumSet<ID> + 'static,
 {
     /// Update one of the scalar value and re-calculate the committed value.
@@ -198,15 +198,14 @@ where
         };
 
         let elt = &mut self.vector[index];
-        let prev = elt.1;
 
-        if prev == value {
+        if elt.1 == value {
             return Ok(&self.granted);
         }
 
-        debug_assert!(value > prev);
+        debug_assert!(value > elt.1);
 
-        elt.1 = value;
+        let prev = std::mem::replace(&mut elt.1, value);
 
         // Learner does not grant a value.
         // And it won't be moved up to adjust the order.
@@ -214,7 +213,7 @@ where
             return Ok(&self.granted);
         }
 
-        if prev <= self.granted && self.granted < value {
+        if prev <= self.granted && self.granted < self.vector[index].1 {
             let new_index = self.move_up(index);
 
             // From high to low, find the max value that has constituted a quorum.
@@ -230,7 +229,7 @@ where
                 self.stat.is_quorum_count += 1;
 
                 if self.quorum_set.is_quorum(it) {
-                    self.granted = self.vector[i].1;
+                    self.granted = self.vector[i].1.clone();
                     break;
                 }
             }
@@ -257,12 +256,12 @@ where
     }
 
     fn upgrade_quorum_set(self, quorum_set: QS, leaner_ids: &[ID]) -> Self {
-        let mut new_prog = Self::new(quorum_set, leaner_ids.iter().copied());
+        let mut new_prog = Self::new(quorum_set, leaner_ids.iter().cloned());
 
         new_prog.stat = self.stat.clone();
 
         for (id, v) in self.iter() {
-            let _ = new_prog.update(id, *v);
+            let _ = new_prog.update(id, v.clone());
         }
         new_prog
     }
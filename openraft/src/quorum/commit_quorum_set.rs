@@ -0,0 +1,55 @@
+use crate::quorum::QuorumSet;
+
+/// A quorum set that a log has to be acknowledged by to be committed.
+///
+/// A quorum of it is a quorum of the wrapped quorum set that also includes every id in `required`.
+/// With [`CommitQuorum::All`](`crate::CommitQuorum::All`), `required` are the voters that store the log payloads, i.e.,
+/// every voter in every config of a joint membership, except the witnesses. With `CommitQuorum::Majority` it is empty.
+#[derive(Clone, Debug)]
+#[derive(PartialEq, Eq)]
+pub(crate) struct CommitQuorumSet<ID, QS>
+where
+    ID: 'static,
+    QS: QuorumSet<ID>,
+{
+    quorum_set: QS,
+    required: Vec<ID>,
+}
+
+impl<ID, QS> CommitQuorumSet<ID, QS>
+where
+    ID: 'static,
+    QS: QuorumSet<ID>,
+{
+    pub(crate) fn new(quorum_set: QS, required: impl Iterator<Item = ID>) -> Self {
+        Self {
+            quorum_set,
+            required: required.collect(),
+        }
+    }
+
+    /// The wrapped quorum set, without the required ids.
+    pub(crate) fn quorum_set(&self) -> &QS {
+        &self.quorum_set
+    }
+}
+
+impl<ID, QS> QuorumSet<ID> for CommitQuorumSet<ID, QS>
+where
+    ID: PartialEq + 'static,
+    QS: QuorumSet<ID>,
+{
+    type Iter = QS::Iter;
+
+    fn is_quorum<'a, I: Iterator<Item = &'a ID> + Clone>(&self, ids: I) -> bool {
+        if !self.quorum_set.is_quorum(ids.clone()) {
+            return false;
+        }
+
+        self.required.iter().all(|r| ids.clone().any(|id| id == r))
+    }
+
+    fn ids(&self) -> Self::Iter {
+        self.quorum_set.ids()
+    }
+}
@@ -4,6 +4,7 @@
 
 mod coherent;
 mod coherent_impl;
+mod commit_quorum_set;
 mod joint;
 mod joint_impl;
 mod quorum_set;
@@ -18,6 +19,7 @@ mod bench;
 
 pub(crate) use coherent::Coherent;
 pub(crate) use coherent::FindCoherent;
+pub(crate) use commit_quorum_set::CommitQuorumSet;
 pub(crate) use joint::AsJoint;
 pub(crate) use joint::Joint;
 pub(crate) use quorum_set::QuorumSet;
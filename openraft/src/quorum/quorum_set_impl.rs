@@ -4,7 +4,7 @@ use crate::quorum::quorum_set::QuorumSet;
 
 /// Impl a simple majority quorum set
 impl<ID> QuorumSet<ID> for BTreeSet<ID>
-where ID: PartialOrd + Ord + Copy + 'static
+where ID: PartialOrd + Ord + Clone + 'static
 {
     type Iter = std::collections::btree_set::IntoIter<ID>;
 
@@ -29,7 +29,7 @@ where ID: PartialOrd + Ord + Copy + 'static
 
 /// Impl a simple majority quorum set
 impl<ID> QuorumSet<ID> for Vec<ID>
-where ID: PartialOrd + Ord + Copy + 'static
+where ID: PartialOrd + Ord + Clone + 'static
 {
     type Iter = std::collections::btree_set::IntoIter<ID>;
 
@@ -54,7 +54,7 @@ where ID: PartialOrd + Ord + Copy + 'static
 
 /// Impl a simple majority quorum set
 impl<ID> QuorumSet<ID> for &[ID]
-where ID: PartialOrd + Ord + Copy + 'static
+where ID: PartialOrd + Ord + Clone + 'static
 {
     type Iter = std::collections::btree_set::IntoIter<ID>;
 
@@ -73,6 +73,6 @@ where ID: PartialOrd + Ord + Copy + 'static
     }
 
     fn ids(&self) -> Self::Iter {
-        BTreeSet::from_iter(self.iter().copied()).into_iter()
+        BTreeSet::from_iter(self.iter().cloned()).into_iter()
     }
 }
@@ -1,6 +1,7 @@
 use maplit::btreeset;
 
 use crate::quorum::AsJoint;
+use crate::quorum::CommitQuorumSet;
 use crate::quorum::Joint;
 use crate::quorum::QuorumSet;
 
@@ -102,6 +103,41 @@ fn test_joint_quorum_set_impl() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_commit_quorum_set_impl() -> anyhow::Result<()> {
+    // Nothing required is a plain majority
+    {
+        let qs = CommitQuorumSet::new(vec![1, 2, 3], [].into_iter());
+
+        assert!(!qs.is_quorum([1].iter()));
+        assert!(qs.is_quorum([1, 2].iter()));
+        assert!(qs.is_quorum([2, 3].iter()));
+    }
+
+    // Every required id has to be in a quorum, 3 is not required, e.g., a witness
+    {
+        let qs = CommitQuorumSet::new(vec![1, 2, 3], [1, 2].into_iter());
+
+        assert!(!qs.is_quorum([1].iter()));
+        assert!(!qs.is_quorum([1, 3].iter()));
+        assert!(!qs.is_quorum([2, 3].iter()));
+        assert!(qs.is_quorum([1, 2].iter()));
+        assert!(qs.is_quorum([1, 2, 3].iter()));
+    }
+
+    // A quorum of a joint config with required ids from every config
+    {
+        let m123_345 = vec![btreeset! {1,2,3}, btreeset! {3,4,5}];
+        let qs = CommitQuorumSet::new(m123_345.as_joint(), [1, 2, 3, 4, 5].into_iter());
+
+        assert!(!qs.is_quorum([1, 2, 3, 4].iter()));
+        assert!(qs.is_quorum([1, 2, 3, 4, 5].iter()));
+        assert_eq!(btreeset! {1,2,3,4,5}, qs.ids().collect());
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_ids() -> anyhow::Result<()> {
     {
@@ -6,16 +6,25 @@ use std::fmt::Display;
 use std::sync::Arc;
 use std::time::Duration;
 
-use tokio::sync::mpsc;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use maplit::btreeset;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncSeek;
+use tokio::io::AsyncWrite;
 use tokio::sync::oneshot;
-use tokio::sync::watch;
 use tokio::sync::Mutex;
-use tokio::task::JoinError;
-use tokio::task::JoinHandle;
 use tracing::Level;
 
+use crate::async_runtime::broadcast;
+use crate::async_runtime::mpsc;
+use crate::async_runtime::watch;
+use crate::async_runtime::InstantOf;
+use crate::async_runtime::JoinHandle;
 use crate::config::Config;
+use crate::config::ConfigUpdate;
 use crate::core::replication_lag;
+use crate::core::ChunkWriter;
 use crate::core::Expectation;
 use crate::core::RaftCore;
 use crate::core::SnapshotUpdate;
@@ -24,27 +33,50 @@ use crate::error::AddLearnerError;
 use crate::error::AppendEntriesError;
 use crate::error::CheckIsLeaderError;
 use crate::error::ClientWriteError;
+use crate::error::CompressionError;
 use crate::error::Fatal;
+use crate::error::FollowerReadError;
+use crate::error::ForwardToLeader;
 use crate::error::InitializeError;
+use crate::error::InstallLocalSnapshotError;
 use crate::error::InstallSnapshotError;
+use crate::error::LearnerNotCaughtUp;
+use crate::error::NotCaughtUpReason;
+use crate::error::PurgeLogsError;
+use crate::error::TransferLeaderError;
+use crate::error::TriggerElectError;
+use crate::error::UpdateConfigError;
 use crate::error::VoteError;
 use crate::membership::IntoOptionNodes;
+use crate::metrics::RaftDataMetrics;
 use crate::metrics::RaftMetrics;
+use crate::metrics::RaftServerMetrics;
+use crate::metrics::ReplicationBackoff;
+use crate::metrics::ReplicationProgress;
 use crate::metrics::Wait;
+use crate::metrics::WaitError;
+use crate::raft_types::LogIdOptionExt;
+use crate::raft_types::LogIndexOptionExt;
 use crate::storage::Snapshot;
 use crate::AppData;
 use crate::AppDataResponse;
+use crate::AppError;
+use crate::AsyncRuntime;
 use crate::ChangeMembers;
+use crate::CompressedEntries;
 use crate::Entry;
 use crate::EntryPayload;
 use crate::LogId;
 use crate::Membership;
 use crate::MessageSummary;
-use crate::Node;
 use crate::NodeId;
+use crate::NodeInfo;
+use crate::RaftLogStorage;
 use crate::RaftNetworkFactory;
 use crate::RaftState;
-use crate::RaftStorage;
+use crate::RaftStateMachine;
+use crate::Responder;
+use crate::ServerState;
 use crate::SnapshotMeta;
 use crate::Vote;
 
@@ -78,6 +110,72 @@ pub trait RaftTypeConfig:
 
     /// A Raft node's ID.
     type NodeId: NodeId;
+
+    /// Raft application level node data, such as the network address.
+    ///
+    /// When declaring types with [`declare_raft_types!`], it defaults to [`Node`](`crate::Node`) if not specified.
+    ///
+    /// The election priority set with [`Node::with_election_priority()`](`crate::Node::with_election_priority`) is
+    /// read only from the built-in `Node`: with an application defined type, every node has priority 0, i.e., the
+    /// priority has no effect.
+    type Node: NodeInfo;
+
+    /// Application specific error, returned by the state machine to reject a client write.
+    ///
+    /// When declaring types with [`declare_raft_types!`], it defaults to [`Infallible`](`crate::error::Infallible`)
+    /// if not specified.
+    type AppError: AppError;
+
+    /// The async runtime to spawn tasks and to drive timers.
+    ///
+    /// When declaring types with [`declare_raft_types!`], it defaults to [`TokioRuntime`](`crate::TokioRuntime`) if
+    /// not specified.
+    type AsyncRuntime: AsyncRuntime;
+
+    /// Delivers the result of a write submitted with [`Raft::client_write_with_responder()`].
+    ///
+    /// When declaring types with [`declare_raft_types!`], it defaults to
+    /// [`OneshotResponder`](`crate::OneshotResponder`) if not specified.
+    type Responder: Responder<Self>;
+
+    /// The data of a snapshot, built by the storage and installed on a node that lags behind.
+    ///
+    /// It can be any type, e.g., a handle to a checkpoint directory, which the application sends by itself, see
+    /// [`RaftNetwork::full_snapshot`](`crate::RaftNetwork::full_snapshot`). If it is a file-like object, i.e.,
+    /// `AsyncRead + AsyncWrite + AsyncSeek + Unpin`, openraft streams it in chunks with
+    /// [`Chunked`](`crate::network::Chunked`) and [`Raft::install_snapshot`].
+    ///
+    /// When declaring types with [`declare_raft_types!`], it defaults to `std::io::Cursor<Vec<u8>>` if not
+    /// specified.
+    type SnapshotData: Send + Sync + 'static;
+
+    /// The size in bytes of an application data, counted by [`SnapshotPolicy::SinceLastBytes`].
+    ///
+    /// The default is the size of `D` itself, which does not include the data it owns on the heap, such as the
+    /// content of a `Vec`. To use `SinceLastBytes` with such data, implement `RaftTypeConfig` without
+    /// [`declare_raft_types!`] and override this method.
+    ///
+    /// [`SnapshotPolicy::SinceLastBytes`]: `crate::SnapshotPolicy::SinceLastBytes`
+    fn data_size(data: &Self::D) -> u64 {
+        std::mem::size_of_val(data) as u64
+    }
+
+    /// The size in bytes of a log entry, counted by [`Config::max_payload_bytes`] and the log cache of a leader.
+    ///
+    /// The default is the size of [`Entry`] itself, its context, and [`data_size()`](`Self::data_size`) of a normal
+    /// entry. An application that knows the encoded size of its entries, e.g., to keep an AppendEntries below the
+    /// message size limit of its transport, overrides this method.
+    ///
+    /// [`Config::max_payload_bytes`]: `crate::Config::max_payload_bytes`
+    fn entry_size(entry: &Entry<Self>) -> u64
+    where Self: Sized {
+        let data_size = match &entry.payload {
+            EntryPayload::Normal(data) => Self::data_size(data),
+            _ => 0,
+        };
+        let context_size = entry.context.as_ref().map(|c| c.len() as u64).unwrap_or_default();
+        std::mem::size_of::<Entry<Self>>() as u64 + data_size + context_size
+    }
 }
 
 /// Define types for a Raft type configuration.
@@ -95,43 +193,98 @@ pub trait RaftTypeConfig:
 ///    pub Config: D = ClientRequest, R = ClientResponse, NodeId = MemNodeId
 /// );
 /// ```
+///
+/// An associated type that is not specified uses its default:
+/// - `Node`: [`Node`](`crate::Node`).
+/// - `AppError`: [`Infallible`](`crate::error::Infallible`).
+/// - `AsyncRuntime`: [`TokioRuntime`](`crate::TokioRuntime`).
+/// - `Responder`: [`OneshotResponder`](`crate::OneshotResponder`).
+/// - `SnapshotData`: `std::io::Cursor<Vec<u8>>`.
+///
+/// E.g., to use an application defined node type and an application error:
+/// ```ignore
+/// openraft::declare_raft_types!(
+///    pub Config: D = ClientRequest, R = ClientResponse, NodeId = MemNodeId, Node = MyNode, AppError = MyError
+/// );
+/// ```
 #[macro_export]
 macro_rules! declare_raft_types {
-    ( $(#[$outer:meta])* $visibility:vis $id:ident: $($(#[$inner:meta])* $type_id:ident = $type:ty),+ ) => {
+    // `Node` is specified: clear the default.
+    (@types $id:ident, [$($node:ty)?], [$($app_err:ty)?], [$($rt:ty)?], [$($resp:ty)?], [$($sd:ty)?], [$($acc:tt)*] $(#[$inner:meta])* Node = $type:ty $(, $($rest:tt)*)?) => {
+        $crate::declare_raft_types!(@types $id, [], [$($app_err)?], [$($rt)?], [$($resp)?], [$($sd)?], [$($acc)* $(#[$inner])* type Node = $type;] $($($rest)*)?);
+    };
+
+    // `AppError` is specified: clear the default.
+    (@types $id:ident, [$($node:ty)?], [$($app_err:ty)?], [$($rt:ty)?], [$($resp:ty)?], [$($sd:ty)?], [$($acc:tt)*] $(#[$inner:meta])* AppError = $type:ty $(, $($rest:tt)*)?) => {
+        $crate::declare_raft_types!(@types $id, [$($node)?], [], [$($rt)?], [$($resp)?], [$($sd)?], [$($acc)* $(#[$inner])* type AppError = $type;] $($($rest)*)?);
+    };
+
+    // `AsyncRuntime` is specified: clear the default.
+    (@types $id:ident, [$($node:ty)?], [$($app_err:ty)?], [$($rt:ty)?], [$($resp:ty)?], [$($sd:ty)?], [$($acc:tt)*] $(#[$inner:meta])* AsyncRuntime = $type:ty $(, $($rest:tt)*)?) => {
+        $crate::declare_raft_types!(@types $id, [$($node)?], [$($app_err)?], [], [$($resp)?], [$($sd)?], [$($acc)* $(#[$inner])* type AsyncRuntime = $type;] $($($rest)*)?);
+    };
+
+    // `Responder` is specified: clear the default.
+    (@types $id:ident, [$($node:ty)?], [$($app_err:ty)?], [$($rt:ty)?], [$($resp:ty)?], [$($sd:ty)?], [$($acc:tt)*] $(#[$inner:meta])* Responder = $type:ty $(, $($rest:tt)*)?) => {
+        $crate::declare_raft_types!(@types $id, [$($node)?], [$($app_err)?], [$($rt)?], [], [$($sd)?], [$($acc)* $(#[$inner])* type Responder = $type;] $($($rest)*)?);
+    };
+
+    // `SnapshotData` is specified: clear the default.
+    (@types $id:ident, [$($node:ty)?], [$($app_err:ty)?], [$($rt:ty)?], [$($resp:ty)?], [$($sd:ty)?], [$($acc:tt)*] $(#[$inner:meta])* SnapshotData = $type:ty $(, $($rest:tt)*)?) => {
+        $crate::declare_raft_types!(@types $id, [$($node)?], [$($app_err)?], [$($rt)?], [$($resp)?], [], [$($acc)* $(#[$inner])* type SnapshotData = $type;] $($($rest)*)?);
+    };
+
+    (@types $id:ident, [$($node:ty)?], [$($app_err:ty)?], [$($rt:ty)?], [$($resp:ty)?], [$($sd:ty)?], [$($acc:tt)*] $(#[$inner:meta])* $type_id:ident = $type:ty $(, $($rest:tt)*)?) => {
+        $crate::declare_raft_types!(@types $id, [$($node)?], [$($app_err)?], [$($rt)?], [$($resp)?], [$($sd)?], [$($acc)* $(#[$inner])* type $type_id = $type;] $($($rest)*)?);
+    };
+
+    // All types are consumed: emit the impl, with defaults for those not specified.
+    (@types $id:ident, [$($node:ty)?], [$($app_err:ty)?], [$($rt:ty)?], [$($resp:ty)?], [$($sd:ty)?], [$($acc:tt)*]) => {
+        impl $crate::RaftTypeConfig for $id {
+            $($acc)*
+
+            $(type Node = $node;)?
+            $(type AppError = $app_err;)?
+            $(type AsyncRuntime = $rt;)?
+            $(type Responder = $resp;)?
+            $(type SnapshotData = $sd;)?
+        }
+    };
+
+    ( $(#[$outer:meta])* $visibility:vis $id:ident: $($rest:tt)+ ) => {
         $(#[$outer])*
         #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd)]
         #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
         $visibility struct $id {}
 
-        impl $crate::RaftTypeConfig for $id {
-            $(
-                $(#[$inner])*
-                type $type_id = $type;
-            )+
-        }
+        $crate::declare_raft_types!(@types $id, [$crate::Node], [$crate::error::Infallible], [$crate::TokioRuntime], [$crate::OneshotResponder<$id>], [::std::io::Cursor<Vec<u8>>], [] $($rest)+);
     };
 }
 
 /// The running state of RaftCore
-enum CoreState<NID: NodeId> {
+enum CoreState<C: RaftTypeConfig> {
     /// The RaftCore task is still running.
-    Running(JoinHandle<Result<(), Fatal<NID>>>),
+    Running(JoinHandle<Result<(), Fatal<C::NodeId>>, <C::AsyncRuntime as AsyncRuntime>::JoinError>),
 
     /// The RaftCore task has finished. The return value of the task is stored.
-    Done(Result<(), Fatal<NID>>),
+    Done(Result<(), Fatal<C::NodeId>>),
 }
 
-struct RaftInner<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> {
+struct RaftInner<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>> {
     id: C::NodeId,
     config: Arc<Config>,
-    tx_api: mpsc::UnboundedSender<RaftMsg<C, N, S>>,
-    rx_metrics: watch::Receiver<RaftMetrics<C::NodeId>>,
+    tx_api: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
+    rx_metrics: watch::Receiver<RaftMetrics<C::NodeId, C::Node, InstantOf<C>>>,
+    rx_server_metrics: watch::Receiver<RaftServerMetrics<C::NodeId, C::Node>>,
+    rx_data_metrics: watch::Receiver<RaftDataMetrics<C::NodeId>>,
+    tx_applied: broadcast::Sender<AppliedEvent<C>>,
     // TODO(xp): it does not need to be a async mutex.
     #[allow(clippy::type_complexity)]
     tx_shutdown: Mutex<Option<oneshot::Sender<()>>>,
     marker_n: std::marker::PhantomData<N>,
-    marker_s: std::marker::PhantomData<S>,
-    core_state: Mutex<CoreState<C::NodeId>>,
+    marker_ls: std::marker::PhantomData<LS>,
+    marker_sm: std::marker::PhantomData<SM>,
+    core_state: Mutex<CoreState<C>>,
 }
 
 /// The Raft API.
@@ -155,11 +308,11 @@ struct RaftInner<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>>
 /// is shutting down (potentially for data safety reasons due to a storage error), and the `shutdown`
 /// method should be called on this type to await the shutdown of the node. If the parent
 /// application needs to shutdown the Raft node for any reason, calling `shutdown` will do the trick.
-pub struct Raft<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> {
-    inner: Arc<RaftInner<C, N, S>>,
+pub struct Raft<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>> {
+    inner: Arc<RaftInner<C, N, LS, SM>>,
 }
 
-impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N, S> {
+impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>> Raft<C, N, LS, SM> {
     /// Create and spawn a new Raft task.
     ///
     /// ### `id`
@@ -175,25 +328,38 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     /// An implementation of the `RaftNetworkFactory` trait which will be used by Raft for sending RPCs to
     /// peer nodes within the cluster. See the docs on the `RaftNetworkFactory` trait for more details.
     ///
-    /// ### `storage`
-    /// An implementation of the `RaftStorage` trait which will be used by Raft for data storage.
-    /// See the docs on the `RaftStorage` trait for more details.
-    #[tracing::instrument(level="debug", skip(config, network, storage), fields(cluster=%config.cluster_name))]
-    pub fn new(id: C::NodeId, config: Arc<Config>, network: N, storage: S) -> Self {
-        let (tx_api, rx_api) = mpsc::unbounded_channel();
-        let (tx_metrics, rx_metrics) = watch::channel(RaftMetrics::new_initial(id));
+    /// ### `log_store`
+    /// An implementation of the [`RaftLogStorage`] trait which will be used by Raft to store the vote and the logs.
+    ///
+    /// ### `state_machine`
+    /// An implementation of the [`RaftStateMachine`] trait which will be used by Raft to apply logs and to build and
+    /// install snapshots.
+    ///
+    /// An existing `RaftStorage` implementation provides both with
+    /// [`Adaptor::new()`](`crate::storage::Adaptor::new`).
+    #[tracing::instrument(level="debug", skip(config, network, log_store, state_machine), fields(cluster=%config.cluster_name))]
+    pub fn new(id: C::NodeId, config: Arc<Config>, network: N, log_store: LS, state_machine: SM) -> Self {
+        let (tx_api, rx_api) = C::AsyncRuntime::mpsc_unbounded();
+        let (tx_metrics, rx_metrics) = C::AsyncRuntime::watch(RaftMetrics::new_initial(id.clone()));
+        let (tx_server_metrics, rx_server_metrics) = C::AsyncRuntime::watch(RaftServerMetrics::new_initial(id.clone()));
+        let (tx_data_metrics, rx_data_metrics) = C::AsyncRuntime::watch(RaftDataMetrics::default());
         let (tx_shutdown, rx_shutdown) = oneshot::channel();
+        let (tx_applied, _) = C::AsyncRuntime::broadcast(std::cmp::max(config.applied_event_buffer_size, 1) as usize);
 
-        let _tick_handle = Tick::spawn(Duration::from_millis(config.heartbeat_interval * 3 / 2), tx_api.clone());
+        let _tick_handle = Tick::spawn(Duration::from_millis(config.effective_tick_interval()), tx_api.clone());
 
         let core_handle = RaftCore::spawn(
-            id,
+            id.clone(),
             config.clone(),
             network,
-            storage,
+            log_store,
+            state_machine,
             tx_api.clone(),
             rx_api,
             tx_metrics,
+            tx_server_metrics,
+            tx_data_metrics,
+            tx_applied.clone(),
             rx_shutdown,
         );
 
@@ -202,9 +368,13 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
             config,
             tx_api,
             rx_metrics,
+            rx_server_metrics,
+            rx_data_metrics,
+            tx_applied,
             tx_shutdown: Mutex::new(Some(tx_shutdown)),
             marker_n: std::marker::PhantomData,
-            marker_s: std::marker::PhantomData,
+            marker_ls: std::marker::PhantomData,
+            marker_sm: std::marker::PhantomData,
             core_state: Mutex::new(CoreState::Running(core_handle)),
         };
         Self { inner: Arc::new(inner) }
@@ -215,12 +385,19 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     /// These RPCs are sent by the cluster leader to replicate log entries (§5.3), and are also
     /// used as heartbeats (§5.2).
     #[tracing::instrument(level = "debug", skip(self, rpc))]
+    ///
+    /// A heartbeat, see [`AppendEntriesRequest::is_heartbeat`], only refreshes the leader lease and the committed log
+    /// id, without matching the logs.
+    ///
+    /// Entries compressed by the leader are decompressed before being stored.
     pub async fn append_entries(
         &self,
-        rpc: AppendEntriesRequest<C>,
+        mut rpc: AppendEntriesRequest<C>,
     ) -> Result<AppendEntriesResponse<C::NodeId>, AppendEntriesError<C::NodeId>> {
         tracing::debug!(rpc = display(rpc.summary()), "Raft::append_entries");
 
+        rpc.decompress_entries()?;
+
         let (tx, rx) = oneshot::channel();
         self.call_core(RaftMsg::AppendEntries { rpc, tx }, rx).await
     }
@@ -236,19 +413,104 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         self.call_core(RaftMsg::RequestVote { rpc, tx }, rx).await
     }
 
+    /// Submit a TimeoutNow RPC to this Raft node.
+    ///
+    /// These RPCs are sent by the cluster leader when it transfers its leadership to this node.
+    /// This node starts an election at once if the request is from its current leader and it has caught up with the
+    /// leader's log.
+    #[tracing::instrument(level = "debug", skip(self, rpc))]
+    pub async fn timeout_now(
+        &self,
+        rpc: TimeoutNowRequest<C::NodeId>,
+    ) -> Result<TimeoutNowResponse<C::NodeId>, VoteError<C::NodeId>> {
+        tracing::debug!(rpc = display(rpc.summary()), "Raft::timeout_now()");
+
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::TimeoutNow { rpc, tx }, rx).await
+    }
+
     /// Submit an InstallSnapshot RPC to this Raft node.
     ///
     /// These RPCs are sent by the cluster leader in order to bring a new node or a slow node up-to-speed
-    /// with the leader (§7).
+    /// with the leader (§7), see [`Chunked`](`crate::network::Chunked`).
+    ///
+    /// The chunks are written into the snapshot data created by the storage, thus it requires the snapshot data to be
+    /// file-like. Otherwise, a snapshot is installed with [`Raft::install_full_snapshot`].
     #[tracing::instrument(level = "debug", skip(self, rpc))]
     pub async fn install_snapshot(
         &self,
         rpc: InstallSnapshotRequest<C>,
-    ) -> Result<InstallSnapshotResponse<C::NodeId>, InstallSnapshotError<C::NodeId>> {
+    ) -> Result<InstallSnapshotResponse<C::NodeId>, InstallSnapshotError<C::NodeId>>
+    where
+        C::SnapshotData: AsyncRead + AsyncWrite + AsyncSeek + Unpin,
+    {
         tracing::debug!(rpc = display(rpc.summary()), "Raft::install_snapshot()");
 
         let (tx, rx) = oneshot::channel();
-        self.call_core(RaftMsg::InstallSnapshot { rpc, tx }, rx).await
+        let writer = ChunkWriter::new();
+        self.call_core(RaftMsg::InstallSnapshot { rpc, writer, tx }, rx).await
+    }
+
+    /// Get the current snapshot stored on this node, if there is one.
+    ///
+    /// It returns the snapshot data in the storage, e.g., for a target to fetch a snapshot from this node, see
+    /// [`RaftNetwork::get_snapshot_from`](`crate::RaftNetwork::get_snapshot_from`), or for a backup tool to copy the
+    /// latest snapshot elsewhere.
+    ///
+    /// It does not build a snapshot: it returns `None` if none has been built or installed yet, see
+    /// [`trigger_snapshot()`](`Raft::trigger_snapshot`). The returned handle is read without `RaftCore`: if
+    /// [`RaftStateMachine::get_current_snapshot()`](`crate::storage::RaftStateMachine::get_current_snapshot`) returns
+    /// a reader independent of the storage, holding it delays neither a new snapshot build nor a snapshot install.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_snapshot(
+        &self,
+    ) -> Result<Option<Snapshot<C::NodeId, C::SnapshotData, C::Node>>, Fatal<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::GetSnapshot { tx }, rx).await
+    }
+
+    /// Create a handle from the storage to write the data of a snapshot into, which is then installed with
+    /// [`Raft::install_full_snapshot`].
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn begin_receiving_snapshot(&self) -> Result<Box<C::SnapshotData>, Fatal<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::BeginReceivingSnapshot { tx }, rx).await
+    }
+
+    /// Install a snapshot that is completely received by the application, e.g., fetched from an object storage.
+    ///
+    /// It is the counterpart of `Raft::install_snapshot()` for a snapshot that is not sent in chunks: the snapshot is
+    /// installed if `vote` is not less than the vote of this node, in the same way as the last chunk of a streamed
+    /// snapshot. A snapshot that is not newer than the last applied log id is not installed.
+    ///
+    /// `snapshot.snapshot` is usually created with [`Raft::begin_receiving_snapshot`]. The returned response has to be
+    /// sent back to the leader, see [`RaftNetwork::full_snapshot`](`crate::RaftNetwork::full_snapshot`).
+    #[tracing::instrument(level = "debug", skip(self, snapshot), fields(meta = ?snapshot.meta))]
+    pub async fn install_full_snapshot(
+        &self,
+        vote: Vote<C::NodeId>,
+        snapshot: Snapshot<C::NodeId, C::SnapshotData, C::Node>,
+    ) -> Result<SnapshotResponse<C::NodeId>, Fatal<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::InstallFullSnapshot { vote, snapshot, tx }, rx).await
+    }
+
+    /// Install a snapshot that is provided locally, e.g., restored from a backup, without involving a leader.
+    ///
+    /// Unlike [`Raft::install_full_snapshot`], there is no vote to check: the snapshot is installed into the state
+    /// machine and the last applied log id and the membership config are updated in accordance with it, the same way
+    /// as a snapshot received from a leader. The vote of this node is not changed.
+    ///
+    /// It returns [`SnapshotBehind`](`crate::error::SnapshotBehind`) if the snapshot is behind the last applied log
+    /// id, and does nothing if the snapshot is at the last applied log id.
+    #[tracing::instrument(level = "debug", skip(self, data), fields(meta = ?meta))]
+    pub async fn install_local_snapshot(
+        &self,
+        meta: SnapshotMeta<C::NodeId, C::Node>,
+        data: Box<C::SnapshotData>,
+    ) -> Result<(), InstallLocalSnapshotError<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::InstallLocalSnapshot { meta, data, tx }, rx).await
     }
 
     /// Get the ID of the current leader from this Raft node.
@@ -258,7 +520,114 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     /// reads. This method is perfect for making decisions on where to route client requests.
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn current_leader(&self) -> Option<C::NodeId> {
-        self.metrics().borrow().current_leader
+        self.metrics().borrow().current_leader.clone()
+    }
+
+    /// Get the ID and the node info of the current leader from this Raft node.
+    ///
+    /// Like [`current_leader()`](`Raft::current_leader`), it reads the latest metrics and never waits for `RaftCore`.
+    /// The node info is looked up in the effective membership in the same metrics. It is `None` if the leader is
+    /// added without node info.
+    ///
+    /// It returns `None` if no leader is known, e.g., during an election.
+    pub fn current_leader_node(&self) -> Option<(C::NodeId, Option<C::Node>)> {
+        let m = self.inner.rx_metrics.borrow();
+        let leader_id = m.current_leader.clone()?;
+        let node = m.membership_config.get_node(&leader_id).cloned();
+        Some((leader_id, node))
+    }
+
+    /// Get a summary of the current state of this Raft node: the vote, logs and leader.
+    ///
+    /// It is read from `RaftCore` and is cheap to clone: unlike [`metrics()`](`Raft::metrics`), it does not contain
+    /// the membership config or the replication state, and it also contains the committed log id.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn current_state(&self) -> Result<RaftStateSummary<C::NodeId>, Fatal<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::GetStateSummary { tx }, rx).await
+    }
+
+    /// Update the timing knobs of the config of this node, without restarting it.
+    ///
+    /// The fields set in `update` replace those of the config this node currently runs with, or of the config updated
+    /// by a previous call that is not yet applied. The updated config is validated with the same rules as
+    /// [`Config::validate()`], and an invalid one is rejected with `UpdateConfigError::InvalidConfig`.
+    ///
+    /// It returns once the updated config is accepted. `RaftCore` applies it at the next tick: a leader passes it to
+    /// its replication streams and restarts its heartbeats with the new interval, and a new election timeout is used
+    /// from the next time the election timer is reset. Use [`config()`](`Raft::config`) to confirm it is applied.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn update_config(&self, update: ConfigUpdate) -> Result<(), UpdateConfigError<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::UpdateConfig { update, tx }, rx).await
+    }
+
+    /// Get the config this node currently runs with, including the updates applied by
+    /// [`update_config()`](`Raft::update_config`).
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn config(&self) -> Result<Arc<Config>, Fatal<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::GetConfig { tx }, rx).await
+    }
+
+    /// Ask this node to start building a snapshot at once, regardless of the snapshot policy.
+    ///
+    /// It returns once the build is scheduled, without waiting for it to complete: watch for the snapshot with
+    /// [`wait()`](`Raft::wait`) or [`metrics()`](`Raft::metrics`). It works on any node, a follower or learner builds
+    /// its own local snapshot.
+    ///
+    /// If a snapshot is already being built or received, it does nothing and returns
+    /// [`TriggerSnapshotResult::InProgress`].
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn trigger_snapshot(&self) -> Result<TriggerSnapshotResult, Fatal<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::TriggerSnapshot { tx }, rx).await
+    }
+
+    /// Ask this node to purge its logs up to index `upto`, inclusive, regardless of the purge policy.
+    ///
+    /// A log that is not included in the last snapshot is never purged, thus the logs are purged up to
+    /// `min(upto, snapshot.last_log_id.index)`. Unless `force` is set, a leader also keeps the logs that a
+    /// replication target has not yet matched. With `force`, such a target is sent a snapshot instead.
+    ///
+    /// It returns the last log id purged by this call, or `None` if there is nothing to purge. The new purged log id is
+    /// also reported in [`RaftMetrics::purged`] and can be waited for with [`Wait::purged()`].
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn trigger_purge_log(
+        &self,
+        upto: u64,
+        force: bool,
+    ) -> Result<Option<LogId<C::NodeId>>, Fatal<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::TriggerPurgeLog { upto, force, tx }, rx).await
+    }
+
+    /// Purge the logs of this node up to `log_id`, inclusive, e.g., after the application built a snapshot by itself.
+    ///
+    /// Unlike [`trigger_purge_log()`](`Raft::trigger_purge_log`), it does not silently purge less than asked: it
+    /// returns [`PurgeLogsError::NotApplied`] if `log_id` is not yet applied, and on a leader,
+    /// [`PurgeLogsError::ReplicationLagging`] if a replication target has not yet matched `log_id`, i.e., it still
+    /// needs the logs to catch up without a snapshot. Purging logs that are already purged does nothing.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn purge_logs_upto(&self, log_id: LogId<C::NodeId>) -> Result<(), PurgeLogsError<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::PurgeLogsUpto { log_id, tx }, rx).await
+    }
+
+    /// Ask this node to start an election at once, instead of waiting for its election timeout.
+    ///
+    /// If pre-vote is enabled, the node runs a pre-vote round first, just like an election started by a timeout. It
+    /// returns once the election is started, without waiting for it to complete: watch for the result with
+    /// [`wait()`](`Raft::wait`) or [`metrics()`](`Raft::metrics`).
+    ///
+    /// It returns [`TriggerElectError::NotInMembers`] if this node is not a voter, e.g., it is a learner, or
+    /// [`TriggerElectError::IsWitness`] if this node is a witness.
+    ///
+    /// See [`Config::disable_auto_elect`] to leave elections entirely to this method.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn trigger_elect(&self) -> Result<(), TriggerElectError<C::NodeId, C::Node>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::TriggerElect { tx }, rx).await
     }
 
     /// Check to ensure this node is still the cluster leader, in order to guard against stale reads (§8).
@@ -266,9 +635,117 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     /// The actual read operation itself is up to the application, this method just ensures that
     /// the read will not be stale.
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn is_leader(&self) -> Result<(), CheckIsLeaderError<C::NodeId>> {
+    pub async fn is_leader(&self) -> Result<(), CheckIsLeaderError<C::NodeId, C::Node>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::CheckIsLeaderRequest { tx }, rx).await?;
+        Ok(())
+    }
+
+    /// Ensure a read on the state machine is linearizable, with the read-index protocol.
+    ///
+    /// It confirms this node is still the leader with a round of heartbeats, captures the committed log id as the
+    /// read log id, then waits for the state machine to apply up to it.
+    /// The returned log id is the one the application can safely read at. No log is written to the raft log.
+    ///
+    /// A `CheckIsLeaderError` is returned if this node is not a leader or leadership is lost during the round.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn ensure_linearizable(&self) -> Result<LogId<C::NodeId>, CheckIsLeaderError<C::NodeId, C::Node>> {
+        let (tx, rx) = oneshot::channel();
+        let read_log_id = self.call_core(RaftMsg::CheckIsLeaderRequest { tx }, rx).await?;
+
+        let mut rx = self.metrics();
+        loop {
+            if rx.borrow().last_applied.index() >= Some(read_log_id.index) {
+                return Ok(read_log_id);
+            }
+
+            if rx.changed().await.is_err() {
+                let fatal = self.get_core_stopped_error("waiting for state machine to apply read log id", None).await;
+                return Err(fatal.into());
+            }
+        }
+    }
+
+    /// Get the read lease of this leader: the instant until which it may serve linearizable reads locally.
+    ///
+    /// The lease is `last_quorum_acked + election_timeout_min - clock_drift_bound`, where `last_quorum_acked` is the
+    /// latest time at which a quorum acknowledged this leader. Before the lease expires, no other node can be
+    /// elected, thus a read does not need a round of heartbeats as [`Raft::ensure_linearizable`] does.
+    /// The read must still observe the state machine applied up to the committed log id of this leader.
+    /// The lease is invalidated at once when this node sees a higher vote.
+    /// If `clock_drift_bound` is not less than `election_timeout_min`, there is no lease: the returned instant is
+    /// `last_quorum_acked`, which has already passed.
+    ///
+    /// A `CheckIsLeaderError::ForwardToLeader` is returned if this node is not a leader, or it is transferring its
+    /// leadership. A `CheckIsLeaderError::QuorumNotEnough` is returned if this leader has not yet been acknowledged by
+    /// a quorum, or it has not yet committed a log in its term.
+    /// The time at which a quorum acknowledged this leader is also reported in [`RaftMetrics::last_quorum_acked`].
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_read_lease(&self) -> Result<InstantOf<C>, CheckIsLeaderError<C::NodeId, C::Node>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::GetReadLease { tx }, rx).await
+    }
+
+    /// Returns `true` if this node is a leader whose read lease has not yet expired.
+    ///
+    /// It is read from `RaftCore`, not from the metrics, and is the same check as
+    /// [`get_read_lease()`](`Raft::get_read_lease`) without the lease instant: it is `false` if this node is not a
+    /// leader, is a candidate, is transferring its leadership, has not yet been acknowledged by a quorum or committed a
+    /// log in its term, or its lease has expired because it is not acknowledged by a quorum within
+    /// `election_timeout_min - clock_drift_bound`. It is also `false` if `RaftCore` has stopped.
+    ///
+    /// A `true` only tells no other leader could be elected at the moment it is checked: by the time the caller acts on
+    /// it, the lease may have expired. Use [`ensure_linearizable()`](`Raft::ensure_linearizable`) to guard a read.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn has_leader_lease(&self) -> bool {
+        match self.get_read_lease().await {
+            Ok(lease) => C::AsyncRuntime::now() < lease,
+            Err(_) => false,
+        }
+    }
+
+    /// Get the log id a follower has applied to its state machine, for a read with a bounded staleness.
+    ///
+    /// The read is served only if this follower heard from the leader, by an AppendEntries or a heartbeat, no longer
+    /// than `max_staleness` ago, thus the state machine misses at most the logs committed within about
+    /// `max_staleness`. The read is not linearizable: use [`ensure_linearizable()`](`Raft::ensure_linearizable`) on
+    /// the leader for that. A learner can serve such reads too.
+    ///
+    /// A `FollowerReadError::StaleRead` is returned if this node has not heard from the leader within `max_staleness`,
+    /// and the read should be sent to the leader in it. A `FollowerReadError::ForwardToLeader` is returned if this
+    /// node is not following a leader, e.g., it is the leader or a candidate.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn follower_read(
+        &self,
+        max_staleness: Duration,
+    ) -> Result<Option<LogId<C::NodeId>>, FollowerReadError<C::NodeId, C::Node>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::FollowerRead { max_staleness, tx }, rx).await
+    }
+
+    /// Get the ID of the leader this node currently knows of.
+    ///
+    /// Unlike [`current_leader()`](`Raft::current_leader`), it is read from `RaftCore` rather than from the metrics,
+    /// which may lag behind. It returns `None` if no leader is known, e.g., during an election, or `RaftCore` has
+    /// stopped. The leader it returns may have been deposed without this node knowing: use
+    /// [`has_leader_lease()`](`Raft::has_leader_lease`) on this node to check whether it is a leader.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn leader_id(&self) -> Option<C::NodeId> {
+        self.current_state().await.ok()?.current_leader
+    }
+
+    /// Transfer the leadership of this node to the `target` node.
+    ///
+    /// The leader stops accepting client writes, waits for `target` to catch up with its log, then sends it a
+    /// TimeoutNow RPC to let it start an election at once. It returns when `target` has started the election.
+    ///
+    /// If `target` is not a voter, is a witness, or it does not catch up within [`Config::transfer_leader_timeout`], an
+    /// error is returned and this node resumes accepting writes. The transfer in progress is reported in
+    /// [`RaftMetrics::transferring_leader_to`].
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn transfer_leadership(&self, target: C::NodeId) -> Result<(), TransferLeaderError<C::NodeId, C::Node>> {
         let (tx, rx) = oneshot::channel();
-        self.call_core(RaftMsg::CheckIsLeaderRequest { tx }, rx).await
+        self.call_core(RaftMsg::TransferLeader { target, tx }, rx).await
     }
 
     /// Submit a mutating client request to Raft to update the state of the system (§5.1).
@@ -283,18 +760,204 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     /// numbers to every command. Then, the state machine should track the latest serial number
     /// processed for each client, along with the associated response. If it receives a command whose
     /// serial number has already been executed, it responds immediately without re-executing the
-    /// request (§8). The `RaftStorage::apply_entry_to_state_machine` method is the perfect place
+    /// request (§8). The `RaftStateMachine::apply_to_state_machine` method is the perfect place
     /// to implement this.
     ///
     /// These are application specific requirements, and must be implemented by the application which is
     /// being built on top of Raft.
+    ///
+    /// If the state machine rejects the request with an application error, it is returned as
+    /// `ClientWriteError::AppError`.
+    ///
+    /// If this node is not the leader, it returns `ClientWriteError::ForwardToLeader`, unless
+    /// [`Config::enable_forward_client_write`] is set: then the request is forwarded to the known leader and the
+    /// leader's result is returned.
     #[tracing::instrument(level = "debug", skip(self, rpc))]
     pub async fn client_write(
         &self,
         rpc: ClientWriteRequest<C>,
-    ) -> Result<ClientWriteResponse<C>, ClientWriteError<C::NodeId>> {
+    ) -> Result<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>> {
         let (tx, rx) = oneshot::channel();
-        self.call_core(RaftMsg::ClientWriteRequest { rpc, tx }, rx).await
+        self.call_core(RaftMsg::ClientWriteRequest { rpc, tx, forward: true }, rx).await
+    }
+
+    /// Submit a mutating client request to this node only: fail at once if it is not the leader.
+    ///
+    /// It is the same as [`client_write()`](`Raft::client_write`) on a leader. On a non-leader it returns
+    /// `ClientWriteError::ForwardToLeader` with the leader known to this node, and never forwards the request, even if
+    /// [`Config::enable_forward_client_write`] is set. This is for a client that routes requests to the leader by
+    /// itself.
+    ///
+    /// If the latest metrics tell this node is not the leader, the request is rejected without being queued in
+    /// `RaftCore`, with the leader in the metrics. Otherwise `RaftCore` decides, and it rejects the request with the
+    /// leader it knows if it is no longer the leader.
+    #[tracing::instrument(level = "debug", skip(self, rpc))]
+    pub async fn client_write_to(
+        &self,
+        rpc: ClientWriteRequest<C>,
+    ) -> Result<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>> {
+        {
+            let m = self.inner.rx_metrics.borrow();
+            if m.state != ServerState::Leader {
+                let leader_id = m.current_leader.clone();
+                let leader_node = leader_id.as_ref().and_then(|id| m.membership_config.get_node(id).cloned());
+                return Err(ForwardToLeader { leader_id, leader_node }.into());
+            }
+        }
+
+        let (tx, rx) = oneshot::channel();
+        self.call_core(
+            RaftMsg::ClientWriteRequest {
+                rpc,
+                tx,
+                forward: false,
+            },
+            rx,
+        )
+        .await
+    }
+
+    /// Submit a client write forwarded by another Raft node with `RaftNetwork::forward_client_write()`.
+    ///
+    /// It is handled the same way as [`client_write()`](`Raft::client_write`), except that a non-leader forwards it
+    /// again only if it has not yet been forwarded `Config::max_forward_client_write_hops` times.
+    #[tracing::instrument(level = "debug", skip(self, rpc))]
+    pub async fn forward_client_write(
+        &self,
+        rpc: ForwardClientWriteRequest<C>,
+    ) -> Result<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>> {
+        tracing::debug!(rpc = display(rpc.summary()), "Raft::forward_client_write()");
+
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::ForwardClientWrite { rpc, tx }, rx).await
+    }
+
+    /// Submit a mutating client request to Raft, and deliver the result to `responder` instead of returning it.
+    ///
+    /// This is the lower-level form of [`client_write()`](`Raft::client_write`): the application decides how the
+    /// result is delivered, e.g., written directly to a network response stream, by providing its own
+    /// [`Responder`] as [`RaftTypeConfig::Responder`].
+    ///
+    /// It returns once the request is sent to `RaftCore`. `responder` receives the same result `client_write()`
+    /// would return, including `ClientWriteError::ForwardToLeader` if this node is not the leader. If `RaftCore` has
+    /// stopped, `Fatal` is returned and `responder` is dropped.
+    #[tracing::instrument(level = "debug", skip(self, rpc, responder))]
+    pub async fn client_write_with_responder(
+        &self,
+        rpc: ClientWriteRequest<C>,
+        responder: C::Responder,
+    ) -> Result<(), Fatal<C::NodeId>> {
+        let send_res = self.inner.tx_api.send(RaftMsg::ClientWriteWithResponder { rpc, responder });
+        if send_res.is_err() {
+            let fatal = self.get_core_stopped_error("sending tx to RaftCore", None).await;
+            return Err(fatal);
+        }
+        Ok(())
+    }
+
+    /// Submit a mutating client request to Raft without waiting for it to be applied.
+    ///
+    /// It returns as soon as the request is appended to the leader's log. No response is sent back when it is
+    /// committed or applied, thus the caller does not know whether it is eventually committed: if the leader crashes or
+    /// loses leadership, an accepted entry may be lost, as with an unanswered `client_write`.
+    ///
+    /// If this node is not the leader, it fails at once with `ClientWriteError::ForwardToLeader`.
+    #[tracing::instrument(level = "debug", skip(self, rpc))]
+    pub async fn client_write_ff(
+        &self,
+        rpc: ClientWriteRequest<C>,
+    ) -> Result<(), ClientWriteError<C::NodeId, C::Node, C::AppError>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::ClientWriteFFRequest { rpc, tx }, rx).await
+    }
+
+    /// Submit a batch of mutating client requests to Raft, and wait for all of them to be applied.
+    ///
+    /// The requests are appended as consecutive log entries in the order they are given, in a single pass through
+    /// `RaftCore`. The responses are returned in the same order.
+    ///
+    /// If any of the requests fails, the error of the first failed one is returned:
+    /// - The entries before it have been applied.
+    /// - If it is `ClientWriteError::AppError`, only this entry is rejected by the state machine, the entries after it
+    ///   are still applied.
+    /// - If this node is not the leader, or it loses leadership before the entry is committed, every entry that is not
+    ///   yet committed fails with `ClientWriteError::ForwardToLeader`. These entries may still be committed by the next
+    ///   leader, as with `client_write`.
+    #[tracing::instrument(level = "debug", skip(self, rpcs), fields(n = rpcs.len()))]
+    pub async fn client_write_many(
+        &self,
+        rpcs: Vec<ClientWriteRequest<C>>,
+    ) -> Result<Vec<ClientWriteResponse<C>>, ClientWriteError<C::NodeId, C::Node, C::AppError>> {
+        let results = self.write_many(rpcs).await?;
+        results.into_iter().collect()
+    }
+
+    /// Submit a batch of application data to Raft, and wait for all of them to be committed and applied.
+    ///
+    /// The data are appended as consecutive log entries in the order they are given, with a single storage append on
+    /// the leader. The leader applies them in the same order, and the responses are returned in the same order.
+    ///
+    /// A batch either succeeds or fails as a whole:
+    /// - If this node is not the leader, or it loses leadership before every entry of the batch is committed,
+    ///   `ClientWriteError::ForwardToLeader` is returned for the whole batch, even if some of the entries have been
+    ///   committed. The entries that are not committed may still be committed by the next leader, as with
+    ///   `client_write`.
+    /// - Otherwise, every entry is committed. If the state machine rejects any of them, the first
+    ///   `ClientWriteError::AppError` is returned. The other entries are still applied.
+    #[tracing::instrument(level = "debug", skip(self, data), fields(n = data.len()))]
+    pub async fn client_write_batch(
+        &self,
+        data: Vec<C::D>,
+    ) -> Result<Vec<ClientWriteResponse<C>>, ClientWriteError<C::NodeId, C::Node, C::AppError>> {
+        let rpcs = data.into_iter().map(|d| ClientWriteRequest::new(EntryPayload::Normal(d))).collect();
+        let results = self.write_many(rpcs).await?;
+
+        let forward = results.iter().find_map(|r| match r {
+            Err(ClientWriteError::ForwardToLeader(e)) => Some(e.clone()),
+            _ => None,
+        });
+        if let Some(e) = forward {
+            return Err(e.into());
+        }
+
+        results.into_iter().collect()
+    }
+
+    /// Send a batch of writes to `RaftCore` and wait for the result of every one of them.
+    async fn write_many(
+        &self,
+        rpcs: Vec<ClientWriteRequest<C>>,
+    ) -> Result<Vec<Result<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>>>, Fatal<C::NodeId>>
+    {
+        if rpcs.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let (txs, rxs): (Vec<_>, Vec<_>) = rpcs.iter().map(|_| oneshot::channel()).unzip();
+
+        let send_res = self.inner.tx_api.send(RaftMsg::ClientWriteManyRequest { rpcs, txs });
+        if send_res.is_err() {
+            let fatal = self.get_core_stopped_error("sending tx to RaftCore", None).await;
+            return Err(fatal);
+        }
+
+        let n = rxs.len();
+        let mut results = Vec::with_capacity(n);
+
+        // Wait for every response, so that the caller sees the result of every entry.
+        for rx in rxs {
+            match rx.await {
+                Ok(res) => results.push(res),
+                Err(_) => {
+                    // RaftCore has stopped: no more response will be received.
+                    let fatal = self.get_core_stopped_error("receiving rx from RaftCore", None).await;
+                    results.resize_with(n, || Err(fatal.clone().into()));
+                    break;
+                }
+            }
+        }
+
+        Ok(results)
     }
 
     /// Initialize a pristine Raft node with the given config.
@@ -318,12 +981,42 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     /// More than one node performing `initialize()` with the same config is safe,
     /// with different config will result in split brain condition.
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn initialize<T>(&self, members: T) -> Result<(), InitializeError<C::NodeId>>
-    where T: IntoOptionNodes<C::NodeId> + Debug {
+    pub async fn initialize<T>(&self, members: T) -> Result<(), InitializeError<C::NodeId, C::Node>>
+    where T: IntoOptionNodes<C::NodeId, C::Node> + Debug {
         let (tx, rx) = oneshot::channel();
         self.call_core(
             RaftMsg::Initialize {
                 members: members.into_option_nodes(),
+                learners: BTreeMap::new(),
+                tx,
+            },
+            rx,
+        )
+        .await
+    }
+
+    /// Initialize a pristine Raft node with the given voters and learners.
+    ///
+    /// It is the same as [`Raft::initialize()`], except that `learners` are added to the first membership config
+    /// log too, thus the leader replicates logs to them without having to call [`Raft::add_learner()`] after the
+    /// cluster is formed. A node id in both `members` and `learners` is a voter.
+    ///
+    /// It returns `InitializeError::NotAllowed` if this node already has any log, as `initialize()` does.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn initialize_with_learners<T, L>(
+        &self,
+        members: T,
+        learners: L,
+    ) -> Result<(), InitializeError<C::NodeId, C::Node>>
+    where
+        T: IntoOptionNodes<C::NodeId, C::Node> + Debug,
+        L: IntoOptionNodes<C::NodeId, C::Node> + Debug,
+    {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(
+            RaftMsg::Initialize {
+                members: members.into_option_nodes(),
+                learners: learners.into_option_nodes(),
                 tx,
             },
             rx,
@@ -337,8 +1030,12 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     /// - Setup replication from leader to it.
     ///
     /// If blocking is true, this function blocks until the leader believes the logs on the new node is up to date,
-    /// i.e., ready to join the cluster, as a voter, by calling `change_membership`.
-    /// When finished, it returns the last log id on the new node, in a `RaftResponse::LogId`.
+    /// i.e., it lags behind by no more than `Config::replication_lag_threshold` and is ready to join the cluster, as a
+    /// voter, by calling `change_membership`.
+    /// When finished, it returns the last log id on the new node, in `AddLearnerResponse::matched`.
+    /// If the new node does not catch up within `Config::add_learner_timeout`, e.g., it is unreachable, or this node
+    /// is no longer the leader, it returns `AddLearnerError::NotCaughtUp` with the progress reached so far. The
+    /// learner stays in the membership in either case.
     ///
     /// If blocking is false, this function returns at once as successfully setting up the replication.
     ///
@@ -347,15 +1044,24 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     /// The caller can attach additional info `node` to this node id.
     /// A `node` can be used to store the network address of a node. Thus an application does not need another store for
     /// mapping node-id to ip-addr when implementing the RaftNetwork.
-    #[tracing::instrument(level = "debug", skip(self, id), fields(target=display(id)))]
+    #[tracing::instrument(level = "debug", skip(self, id), fields(target=display(&id)))]
     pub async fn add_learner(
         &self,
         id: C::NodeId,
-        node: Option<Node>,
+        node: Option<C::Node>,
         blocking: bool,
-    ) -> Result<AddLearnerResponse<C::NodeId>, AddLearnerError<C::NodeId>> {
+    ) -> Result<AddLearnerResponse<C::NodeId>, AddLearnerError<C::NodeId, C::Node>> {
         let (tx, rx) = oneshot::channel();
-        let resp = self.call_core(RaftMsg::AddLearner { id, node, tx }, rx).await?;
+        let resp = self
+            .call_core(
+                RaftMsg::AddLearner {
+                    id: id.clone(),
+                    node,
+                    tx,
+                },
+                rx,
+            )
+            .await?;
 
         if !blocking {
             return Ok(resp);
@@ -368,21 +1074,18 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         // Otherwise, blocks until the replication to the new learner becomes up to date.
 
         // The log id of the membership that contains the added learner.
-        let membership_log_id = resp.membership_log_id;
+        let membership_log_id = resp.membership_log_id.clone();
 
-        let res0 = Arc::new(std::sync::Mutex::new(resp));
-        let res = res0.clone();
+        let progress0 = Arc::new(std::sync::Mutex::new(LearnerCatchUp::default()));
+        let progress = progress0.clone();
 
+        let timeout = Duration::from_millis(self.inner.config.add_learner_timeout);
         let wait_res = self
-            .wait(None)
+            .wait(Some(timeout))
             .metrics(
-                |metrics| match self.check_replication_upto_date(metrics, id, membership_log_id) {
-                    Ok(matched) => {
-                        res.lock().unwrap().matched = matched;
-                        true
-                    }
-                    // keep waiting
-                    Err(_) => false,
+                |metrics| {
+                    let mut p = progress.lock().unwrap();
+                    self.check_replication_upto_date(metrics, id.clone(), membership_log_id.clone(), &mut p)
                 },
                 "wait new learner to become line-rate",
             )
@@ -390,37 +1093,81 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
 
         tracing::info!(wait_res = debug(&wait_res), "waiting for replication to new learner");
 
-        let r = {
-            let x = res0.lock().unwrap();
-            x.clone()
+        let p = progress0.lock().unwrap().clone();
+
+        let reason = match wait_res {
+            Ok(_) => p.stopped,
+            Err(WaitError::Timeout(_, _)) => Some(NotCaughtUpReason::Timeout),
+            Err(WaitError::ShuttingDown) => return Err(Fatal::Stopped.into()),
         };
-        Ok(r)
+
+        if let Some(reason) = reason {
+            return Err(LearnerNotCaughtUp {
+                node_id: id,
+                matched: p.matched,
+                leader_last_log_index: p.leader_last_log_index,
+                reason,
+            }
+            .into());
+        }
+
+        Ok(AddLearnerResponse {
+            matched: p.matched,
+            ..resp
+        })
+    }
+
+    /// Add a new learner raft node and promote it to a voter once it catches up with the leader.
+    ///
+    /// The leader proposes the membership change when the learner lags behind by no more than
+    /// `Config::replication_lag_threshold`, and has stayed so for `Config::learner_promotion_rounds` heartbeat
+    /// intervals. If the learner falls behind again before the joint config is proposed, the leader waits for it to
+    /// catch up again instead of promoting it; if it falls behind before the uniform config is proposed, the leader
+    /// reverts the joint config, keeping it a learner, then waits for it to catch up again.
+    /// The promotion goes through the same joint consensus as `change_membership`. Its stage is reported in
+    /// `RaftMetrics::learner_promotions`.
+    ///
+    /// It returns the response of the uniform config log that makes the learner a voter.
+    /// Other membership changes proposed during the promotion may fail with `ChangeMembershipError::InProgress`.
+    ///
+    /// See `Config::auto_promote_learner` to promote every learner added by `add_learner`.
+    #[tracing::instrument(level = "debug", skip(self, id), fields(target=display(&id)))]
+    pub async fn add_learner_and_promote(
+        &self,
+        id: C::NodeId,
+        node: Option<C::Node>,
+    ) -> Result<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::AddLearnerAndPromote { id, node, tx }, rx).await
     }
 
-    /// Returns Ok() with the latest known matched log id if it should quit waiting: leader change, node removed, or
-    /// replication becomes upto date.
+    /// Returns true if it should quit waiting: replication becomes upto date, leader change or node removed.
     ///
-    /// Returns Err() if it should keep waiting.
+    /// The latest known matched log id is recorded in `progress`, as is the reason if it quits before the learner
+    /// catches up.
     fn check_replication_upto_date(
         &self,
-        metrics: &RaftMetrics<C::NodeId>,
+        metrics: &RaftMetrics<C::NodeId, C::Node, InstantOf<C>>,
         node_id: C::NodeId,
         membership_log_id: Option<LogId<C::NodeId>>,
-    ) -> Result<Option<LogId<C::NodeId>>, ()> {
+        progress: &mut LearnerCatchUp<C::NodeId>,
+    ) -> bool {
         if metrics.membership_config.log_id < membership_log_id {
             // Waiting for the latest metrics to report.
-            return Err(());
+            return false;
         }
 
         if !metrics.membership_config.membership.contains(&node_id) {
             // This learner has been removed.
-            return Ok(None);
+            progress.stopped = Some(NotCaughtUpReason::Removed);
+            return true;
         }
 
         let repl = match &metrics.replication {
             None => {
                 // This node is no longer a leader.
-                return Ok(None);
+                progress.stopped = Some(NotCaughtUpReason::LeaderLost);
+                return true;
             }
             Some(x) => x,
         };
@@ -429,22 +1176,20 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         let target_metrics = match replication_metrics.get(&node_id) {
             None => {
                 // Maybe replication is not reported yet. Keep waiting.
-                return Err(());
+                return false;
             }
             Some(x) => x,
         };
 
         let matched = target_metrics.matched();
 
-        let distance = replication_lag(&Some(matched.index), &metrics.last_log_index);
+        progress.matched = Some(matched.clone());
+        progress.leader_last_log_index = metrics.last_log_index;
 
-        if distance <= self.inner.config.replication_lag_threshold {
-            // replication became up to date.
-            return Ok(Some(matched));
-        }
+        let distance = replication_lag(&Some(matched.index), &metrics.last_log_index);
 
-        // Not up to date, keep waiting.
-        Err(())
+        // Quit waiting if replication became up to date, otherwise keep waiting.
+        distance <= self.inner.config.replication_lag_threshold
     }
 
     /// Propose a cluster configuration change.
@@ -468,6 +1213,13 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     ///    - Otherwise if `turn_to_learner` is false, then the new membership is {"members":{3,4,5}, "learners":{}}, in
     ///      which the members not exists in the new membership just be removed from the cluster.
     ///
+    /// The learners, e.g., the voters turned into learners, are removed with `ChangeMembers::RemoveLearners`, which
+    /// does not change the voters.
+    ///
+    /// With `ChangeMembers::Exact`, the voters and the learners are both changed in one call, and `turn_to_learner` is
+    /// ignored: e.g., from {"members":{1,2,3}, "learners":{4}}, `Exact{voters:{1,2,4}, learners:{3}}` promotes 4 and
+    /// demotes 3 through a single joint config.
+    ///
     /// If it loses leadership or crashed before committing the second **uniform** config log, the cluster is left in
     /// the **joint** config.
     #[tracing::instrument(level = "info", skip_all)]
@@ -476,7 +1228,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         members: impl Into<ChangeMembers<C::NodeId>>,
         allow_lagging: bool,
         turn_to_learner: bool,
-    ) -> Result<ClientWriteResponse<C>, ClientWriteError<C::NodeId>> {
+    ) -> Result<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>> {
         let changes: ChangeMembers<C::NodeId> = members.into();
 
         tracing::info!(
@@ -491,7 +1243,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         } else {
             match &changes {
                 // Removing voters will never be blocked by replication.
-                ChangeMembers::Remove(_) => None,
+                ChangeMembers::Remove(_) | ChangeMembers::RemoveLearners(_) => None,
                 _ => Some(Expectation::AtLineRate),
             }
         };
@@ -513,7 +1265,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
 
         tracing::debug!("res of first step: {:?}", res.summary());
 
-        let (log_id, joint) = (res.log_id, res.membership.clone().unwrap());
+        let (log_id, joint) = (res.log_id.clone(), res.membership.clone().unwrap());
 
         if !joint.is_in_joint_consensus() {
             return Ok(res);
@@ -540,9 +1292,87 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         Ok(res)
     }
 
+    /// Remove this node, which has to be the leader, from the cluster, and hand its leadership over.
+    ///
+    /// It removes this node from the voters with `change_membership()`, without keeping it as a learner. Once the
+    /// membership that removes it is committed, the leader sends TimeoutNow to the most up-to-date remaining voter,
+    /// so that the cluster elects a new leader at once instead of after an election timeout. Then it stops
+    /// replicating and steps down to a learner. If the successor does not catch up within
+    /// `Config::transfer_leader_timeout`, or rejects, the leader steps down without it.
+    ///
+    /// It returns the response of committing the last membership entry, after this node is no longer the leader.
+    ///
+    /// On a follower it fails with `ForwardToLeader`: the leader has to be asked to remove this node with
+    /// `change_membership()`.
+    #[tracing::instrument(level = "info", skip_all)]
+    pub async fn leave(&self) -> Result<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>> {
+        let id = self.inner.id.clone();
+
+        let res = self.change_membership(ChangeMembers::Remove(btreeset! {id}), false, false).await?;
+
+        let timeout = Duration::from_millis(self.inner.config.transfer_leader_timeout);
+        loop {
+            let wait_res = self
+                .wait(Some(timeout))
+                .metrics(|m| m.state != ServerState::Leader, "removed leader steps down")
+                .await;
+
+            match wait_res {
+                Ok(_) => break,
+                Err(WaitError::Timeout(_, _)) => {
+                    tracing::info!("leave: removed leader has not yet stepped down, keep waiting");
+                }
+                Err(WaitError::ShuttingDown) => return Err(Fatal::Stopped.into()),
+            }
+        }
+
+        tracing::info!("leave: this node is removed from the cluster: {}", res.summary());
+
+        Ok(res)
+    }
+
+    /// Check if a `change_membership()` with the same arguments would be accepted, without changing anything.
+    ///
+    /// It returns the membership config the change would end up with, i.e., the uniform config if the change goes
+    /// through a joint config. Otherwise it returns the error `change_membership()` would return, e.g.,
+    /// `ChangeMembershipError::InProgress` if a previous membership change is not yet committed, or `ForwardToLeader`
+    /// if this node is not the leader.
+    ///
+    /// The result is only a hint: the cluster may change before a following `change_membership()` is called.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn change_membership_dry_run(
+        &self,
+        members: impl Into<ChangeMembers<C::NodeId>>,
+        allow_lagging: bool,
+        turn_to_learner: bool,
+    ) -> Result<Membership<C::NodeId, C::Node>, ClientWriteError<C::NodeId, C::Node, C::AppError>> {
+        let changes: ChangeMembers<C::NodeId> = members.into();
+
+        let when = if allow_lagging {
+            None
+        } else {
+            match &changes {
+                ChangeMembers::Remove(_) | ChangeMembers::RemoveLearners(_) => None,
+                _ => Some(Expectation::AtLineRate),
+            }
+        };
+
+        let (tx, rx) = oneshot::channel();
+        self.call_core(
+            RaftMsg::ChangeMembershipDryRun {
+                changes,
+                when,
+                turn_to_learner,
+                tx,
+            },
+            rx,
+        )
+        .await
+    }
+
     /// Invoke RaftCore by sending a RaftMsg and blocks waiting for response.
     #[tracing::instrument(level = "debug", skip(self, mes, rx))]
-    pub(crate) async fn call_core<T, E>(&self, mes: RaftMsg<C, N, S>, rx: RaftRespRx<T, E>) -> Result<T, E>
+    pub(crate) async fn call_core<T, E>(&self, mes: RaftMsg<C, N, LS, SM>, rx: RaftRespRx<T, E>) -> Result<T, E>
     where E: From<Fatal<C::NodeId>> {
         let sum = if tracing::enabled!(Level::DEBUG) {
             None
@@ -606,7 +1436,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
 
                 let core_task_res = match res {
                     Err(err) => {
-                        if err.is_panic() {
+                        if C::AsyncRuntime::is_panic(&err) {
                             Err(Fatal::Panicked)
                         } else {
                             Err(Fatal::Stopped)
@@ -635,15 +1465,87 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     ///
     /// If the API channel is already closed (Raft is in shutdown), then the request functor is
     /// destroyed right away and not called at all.
-    pub fn external_request<F: FnOnce(&RaftState<C::NodeId>, &mut S, &mut N) + Send + 'static>(&self, req: F) {
+    pub fn external_request<
+        F: FnOnce(&RaftState<C::NodeId, C::Node, InstantOf<C>>, &mut LS, &mut N) + Send + 'static,
+    >(
+        &self,
+        req: F,
+    ) {
         let _ignore_error = self.inner.tx_api.send(RaftMsg::ExternalRequest { req: Box::new(req) });
     }
 
     /// Get a handle to the metrics channel.
-    pub fn metrics(&self) -> watch::Receiver<RaftMetrics<C::NodeId>> {
+    pub fn metrics(&self) -> watch::Receiver<RaftMetrics<C::NodeId, C::Node, InstantOf<C>>> {
         self.inner.rx_metrics.clone()
     }
 
+    /// Get a handle to the server metrics channel: vote, server state, leader and membership.
+    ///
+    /// It is updated only when one of these fields changes, thus a watcher of leadership changes is not woken up by
+    /// every applied log, as it would be with [`metrics()`](`Raft::metrics`).
+    pub fn server_metrics(&self) -> watch::Receiver<RaftServerMetrics<C::NodeId, C::Node>> {
+        self.inner.rx_server_metrics.clone()
+    }
+
+    /// Get a handle to the data metrics channel: last log, last applied, snapshot and replication.
+    pub fn data_metrics(&self) -> watch::Receiver<RaftDataMetrics<C::NodeId>> {
+        self.inner.rx_data_metrics.clone()
+    }
+
+    /// Subscribe to the entries applied to the state machine on this node.
+    ///
+    /// An [`AppliedEvent`] is sent after every entry is applied, including blank and membership entries, in log order.
+    /// When a snapshot is installed, `AppliedEvent::Snapshot` is sent instead of the entries it includes.
+    /// Only events after this call are received.
+    ///
+    /// `RaftCore` never waits for a subscriber: at most [`Config::applied_event_buffer_size`] events are buffered
+    /// for a subscriber. When a subscriber falls behind more than that, the oldest events are dropped and the next
+    /// `recv()` returns `RecvError::Lagged` with the number of events dropped.
+    pub fn subscribe_applied(&self) -> broadcast::Receiver<AppliedEvent<C>> {
+        self.inner.tx_applied.subscribe()
+    }
+
+    /// Subscribe to a projection of the metrics.
+    ///
+    /// The returned stream yields the current value of `f(metrics)` at once, and then yields a new value only when
+    /// it differs from the last yielded one. Changes to metrics fields that do not affect the projection do not wake
+    /// up the stream consumer. E.g., to watch leader changes:
+    ///
+    /// ```ignore
+    /// let mut leaders = raft.metrics_filtered(|m| m.current_leader);
+    /// while let Some(leader) = leaders.next().await {
+    ///     // reconcile with the new leader
+    /// }
+    /// ```
+    ///
+    /// The stream ends when the raft node is shut down.
+    pub fn metrics_filtered<T, F>(&self, f: F) -> BoxStream<'static, T>
+    where
+        T: PartialEq + Clone + Send + 'static,
+        F: Fn(&RaftMetrics<C::NodeId, C::Node, InstantOf<C>>) -> T + Send + 'static,
+    {
+        let rx = self.metrics();
+        let last = f(&rx.borrow());
+
+        futures::stream::unfold((rx, f, last, true), |(mut rx, f, last, first)| async move {
+            if first {
+                return Some((last.clone(), (rx, f, last, false)));
+            }
+
+            loop {
+                if rx.changed().await.is_err() {
+                    return None;
+                }
+
+                let curr = f(&rx.borrow());
+                if curr != last {
+                    return Some((curr.clone(), (rx, f, curr, false)));
+                }
+            }
+        })
+        .boxed()
+    }
+
     /// Get a handle to wait for the metrics to satisfy some condition.
     ///
     /// ```ignore
@@ -661,7 +1563,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     /// // wait for raft state to become a follower
     /// r.wait(None).state(State::Follower, "state").await?;
     /// ```
-    pub fn wait(&self, timeout: Option<Duration>) -> Wait<C::NodeId> {
+    pub fn wait(&self, timeout: Option<Duration>) -> Wait<C::NodeId, C::Node, C::AsyncRuntime> {
         let timeout = match timeout {
             Some(t) => t,
             None => Duration::from_millis(500),
@@ -669,11 +1571,12 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         Wait {
             timeout,
             rx: self.inner.rx_metrics.clone(),
+            marker_rt: Default::default(),
         }
     }
 
     /// Shutdown this Raft node.
-    pub async fn shutdown(&self) -> Result<(), JoinError> {
+    pub async fn shutdown(&self) -> Result<(), <C::AsyncRuntime as AsyncRuntime>::JoinError> {
         if let Some(tx) = self.inner.tx_shutdown.lock().await.take() {
             // A failure to send means the RaftCore is already shutdown. Continue to check the task return value.
             let send_res = tx.send(());
@@ -686,9 +1589,44 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         //           to let the caller know the return value of RaftCore task.
         Ok(())
     }
+
+    /// Shutdown this Raft node after the logs that are already accepted are applied.
+    ///
+    /// It stops accepting client writes at once: a write submitted after this call fails with `Fatal::Stopped`.
+    /// A leader keeps replicating its logs until every log is committed and applied, then it shuts down.
+    /// A non-leader shuts down at once, since it can not commit logs by itself.
+    ///
+    /// A log is committed only after the storage reports it is flushed, see
+    /// [`RaftLogStorage::append()`](`crate::storage::RaftLogStorage::append`), thus there is nothing to flush.
+    ///
+    /// If the logs are not all applied within [`Config::graceful_shutdown_timeout`], it shuts down anyway. The
+    /// number of logs left unapplied is returned in the [`ShutdownReport`].
+    pub async fn shutdown_gracefully(&self) -> Result<ShutdownReport, <C::AsyncRuntime as AsyncRuntime>::JoinError> {
+        let (tx, rx) = oneshot::channel();
+
+        let send_res = self.inner.tx_api.send(RaftMsg::Drain { tx });
+        let report = match send_res {
+            Ok(_) => rx.await.ok(),
+            Err(_) => None,
+        };
+
+        // If RaftCore quits before responding, report from the last metrics.
+        let report = report.unwrap_or_else(|| {
+            let m = self.inner.rx_metrics.borrow();
+            ShutdownReport {
+                unapplied: m.last_log_index.next_index().saturating_sub(m.last_applied.next_index()),
+            }
+        });
+
+        self.shutdown().await?;
+
+        Ok(report)
+    }
 }
 
-impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Clone for Raft<C, N, S> {
+impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>> Clone
+    for Raft<C, N, LS, SM>
+{
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
@@ -709,8 +1647,96 @@ pub struct AddLearnerResponse<NID: NodeId> {
     pub matched: Option<LogId<NID>>,
 }
 
+/// The progress of a learner that a blocking [`Raft::add_learner()`] is waiting for.
+#[derive(Debug, Clone)]
+struct LearnerCatchUp<NID: NodeId> {
+    matched: Option<LogId<NID>>,
+    leader_last_log_index: Option<u64>,
+
+    /// The reason to quit waiting before the learner catches up.
+    stopped: Option<NotCaughtUpReason>,
+}
+
+impl<NID: NodeId> Default for LearnerCatchUp<NID> {
+    fn default() -> Self {
+        Self {
+            matched: None,
+            leader_last_log_index: None,
+            stopped: None,
+        }
+    }
+}
+
+/// An event sent to the subscribers of [`Raft::subscribe_applied()`].
+#[derive(Debug, Clone)]
+pub enum AppliedEvent<C: RaftTypeConfig> {
+    /// A log entry is applied to the state machine.
+    Entry {
+        log_id: LogId<C::NodeId>,
+
+        /// The payload of the entry, which tells the kind of it: blank, normal or membership.
+        payload: EntryPayload<C>,
+
+        /// Whether it is the entry a leader appends when it is established, see [`Entry::is_leader_established()`].
+        leader_established: bool,
+    },
+
+    /// A snapshot is installed to the state machine. The entries it includes are not sent.
+    Snapshot {
+        /// The last log id included in the snapshot.
+        last_log_id: LogId<C::NodeId>,
+    },
+}
+
+/// The result of [`Raft::trigger_snapshot()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum TriggerSnapshotResult {
+    /// Building a snapshot is started.
+    Scheduled,
+
+    /// Nothing is done, because a snapshot is already being built, or being received from the leader.
+    InProgress,
+
+    /// Nothing is done, because no log is applied since the last snapshot.
+    NothingToSnapshot,
+}
+
+/// The result of [`Raft::current_state()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct RaftStateSummary<NID: NodeId> {
+    /// The current term, i.e., the term of `vote`.
+    pub term: u64,
+
+    /// The vote this node has granted or received.
+    pub vote: Vote<NID>,
+
+    /// The id of the last log in the local log.
+    pub last_log_id: Option<LogId<NID>>,
+
+    /// The id of the last log known to be committed.
+    pub committed: Option<LogId<NID>>,
+
+    /// The id of the last log applied to the state machine.
+    pub last_applied: Option<LogId<NID>>,
+
+    /// The current leader known by this node.
+    pub current_leader: Option<NID>,
+}
+
+/// The result of [`Raft::shutdown_gracefully()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct ShutdownReport {
+    /// The number of logs that are not applied when this node shuts down.
+    ///
+    /// It is `0` if the logs are all applied before the deadline.
+    pub unapplied: u64,
+}
+
 /// A message coming from the Raft API.
-pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> {
+pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>> {
     AppendEntries {
         rpc: AppendEntriesRequest<C>,
         tx: RaftRespTx<AppendEntriesResponse<C::NodeId>, AppendEntriesError<C::NodeId>>,
@@ -723,38 +1749,127 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
         target: C::NodeId,
         resp: VoteResponse<C::NodeId>,
 
-        /// Which ServerState sent this message. It is also the requested vote.
-        vote: Vote<C::NodeId>,
+        /// Which ServerState sent this message. It is also the requested vote.
+        vote: Vote<C::NodeId>,
+    },
+    PreVoteResponse {
+        target: C::NodeId,
+        resp: VoteResponse<C::NodeId>,
+
+        /// The vote of the node when it sent the pre-vote request.
+        vote: Vote<C::NodeId>,
+    },
+    InstallSnapshot {
+        rpc: InstallSnapshotRequest<C>,
+        writer: ChunkWriter<C>,
+        tx: RaftRespTx<InstallSnapshotResponse<C::NodeId>, InstallSnapshotError<C::NodeId>>,
+    },
+
+    GetSnapshot {
+        tx: RaftRespTx<Option<Snapshot<C::NodeId, C::SnapshotData, C::Node>>, Fatal<C::NodeId>>,
+    },
+
+    BeginReceivingSnapshot {
+        tx: RaftRespTx<Box<C::SnapshotData>, Fatal<C::NodeId>>,
+    },
+
+    InstallFullSnapshot {
+        vote: Vote<C::NodeId>,
+        snapshot: Snapshot<C::NodeId, C::SnapshotData, C::Node>,
+        tx: RaftRespTx<SnapshotResponse<C::NodeId>, Fatal<C::NodeId>>,
+    },
+
+    InstallLocalSnapshot {
+        meta: SnapshotMeta<C::NodeId, C::Node>,
+        data: Box<C::SnapshotData>,
+        tx: RaftRespTx<(), InstallLocalSnapshotError<C::NodeId>>,
+    },
+
+    SnapshotUpdate {
+        update: SnapshotUpdate<C::NodeId>,
+    },
+
+    ClientWriteRequest {
+        rpc: ClientWriteRequest<C>,
+        tx: RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>>,
+
+        /// Whether a non-leader forwards it to the leader, if `Config::enable_forward_client_write` is set.
+        forward: bool,
+    },
+    /// A write forwarded by another node.
+    ForwardClientWrite {
+        rpc: ForwardClientWriteRequest<C>,
+        tx: RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>>,
+    },
+    /// A write whose result is delivered to an application defined responder.
+    ClientWriteWithResponder {
+        rpc: ClientWriteRequest<C>,
+        responder: C::Responder,
+    },
+    /// A fire-and-forget write: `tx` is responded once the entry is appended to the log.
+    ClientWriteFFRequest {
+        rpc: ClientWriteRequest<C>,
+        tx: RaftRespTx<(), ClientWriteError<C::NodeId, C::Node, C::AppError>>,
+    },
+    ClientWriteManyRequest {
+        rpcs: Vec<ClientWriteRequest<C>>,
+
+        /// One channel for every request in `rpcs`, in the same order.
+        txs: Vec<RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>>>,
+    },
+    CheckIsLeaderRequest {
+        tx: RaftRespTx<LogId<C::NodeId>, CheckIsLeaderError<C::NodeId, C::Node>>,
+    },
+
+    GetReadLease {
+        tx: RaftRespTx<InstantOf<C>, CheckIsLeaderError<C::NodeId, C::Node>>,
     },
-    InstallSnapshot {
-        rpc: InstallSnapshotRequest<C>,
-        tx: RaftRespTx<InstallSnapshotResponse<C::NodeId>, InstallSnapshotError<C::NodeId>>,
+
+    FollowerRead {
+        max_staleness: Duration,
+        tx: RaftRespTx<Option<LogId<C::NodeId>>, FollowerReadError<C::NodeId, C::Node>>,
     },
 
-    SnapshotUpdate {
-        update: SnapshotUpdate<C::NodeId>,
+    TimeoutNow {
+        rpc: TimeoutNowRequest<C::NodeId>,
+        tx: RaftRespTx<TimeoutNowResponse<C::NodeId>, VoteError<C::NodeId>>,
     },
 
-    ClientWriteRequest {
-        rpc: ClientWriteRequest<C>,
-        tx: RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId>>,
+    TransferLeader {
+        target: C::NodeId,
+        tx: RaftRespTx<(), TransferLeaderError<C::NodeId, C::Node>>,
     },
-    CheckIsLeaderRequest {
-        tx: RaftRespTx<(), CheckIsLeaderError<C::NodeId>>,
+
+    /// The result of sending TimeoutNow RPC to the target of an ongoing leadership transfer.
+    TimeoutNowResult {
+        target: C::NodeId,
+        result: Result<TimeoutNowResponse<C::NodeId>, TransferLeaderError<C::NodeId, C::Node>>,
+
+        /// Which ServerState sent this message
+        vote: Vote<C::NodeId>,
     },
 
     Initialize {
-        members: BTreeMap<C::NodeId, Option<Node>>,
-        tx: RaftRespTx<(), InitializeError<C::NodeId>>,
+        members: BTreeMap<C::NodeId, Option<C::Node>>,
+        learners: BTreeMap<C::NodeId, Option<C::Node>>,
+        tx: RaftRespTx<(), InitializeError<C::NodeId, C::Node>>,
     },
     /// Request raft core to setup a new replication to a learner.
     AddLearner {
         id: C::NodeId,
 
-        node: Option<Node>,
+        node: Option<C::Node>,
 
         /// Send the log id when the replication becomes line-rate.
-        tx: RaftRespTx<AddLearnerResponse<C::NodeId>, AddLearnerError<C::NodeId>>,
+        tx: RaftRespTx<AddLearnerResponse<C::NodeId>, AddLearnerError<C::NodeId, C::Node>>,
+    },
+    AddLearnerAndPromote {
+        id: C::NodeId,
+
+        node: Option<C::Node>,
+
+        /// Send the response of the uniform config log that makes the learner a voter.
+        tx: RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>>,
     },
     ChangeMembership {
         changes: ChangeMembers<C::NodeId>,
@@ -767,12 +1882,57 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
         /// will be turned into learners, otherwise they will be removed.
         turn_to_learner: bool,
 
-        tx: RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId>>,
+        tx: RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>>,
+    },
+
+    /// Check a change-membership without writing any log.
+    ChangeMembershipDryRun {
+        changes: ChangeMembers<C::NodeId>,
+        when: Option<Expectation>,
+        turn_to_learner: bool,
+        tx: RaftRespTx<Membership<C::NodeId, C::Node>, ClientWriteError<C::NodeId, C::Node, C::AppError>>,
     },
 
     ExternalRequest {
         #[allow(clippy::type_complexity)]
-        req: Box<dyn FnOnce(&RaftState<C::NodeId>, &mut S, &mut N) + Send + 'static>,
+        req: Box<dyn FnOnce(&RaftState<C::NodeId, C::Node, InstantOf<C>>, &mut LS, &mut N) + Send + 'static>,
+    },
+
+    TriggerSnapshot {
+        tx: RaftRespTx<TriggerSnapshotResult, Fatal<C::NodeId>>,
+    },
+
+    TriggerElect {
+        tx: RaftRespTx<(), TriggerElectError<C::NodeId, C::Node>>,
+    },
+
+    TriggerPurgeLog {
+        upto: u64,
+        force: bool,
+        tx: RaftRespTx<Option<LogId<C::NodeId>>, Fatal<C::NodeId>>,
+    },
+
+    PurgeLogsUpto {
+        log_id: LogId<C::NodeId>,
+        tx: RaftRespTx<(), PurgeLogsError<C::NodeId>>,
+    },
+
+    GetStateSummary {
+        tx: RaftRespTx<RaftStateSummary<C::NodeId>, Fatal<C::NodeId>>,
+    },
+
+    UpdateConfig {
+        update: ConfigUpdate,
+        tx: RaftRespTx<(), UpdateConfigError<C::NodeId>>,
+    },
+
+    GetConfig {
+        tx: RaftRespTx<Arc<Config>, Fatal<C::NodeId>>,
+    },
+
+    /// Start to shut down gracefully.
+    Drain {
+        tx: oneshot::Sender<ShutdownReport>,
     },
 
     /// A tick event to wake up RaftCore to check timeout etc.
@@ -795,6 +1955,55 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
         vote: Vote<C::NodeId>,
     },
 
+    /// A replication target acknowledged the leader with a request sent at `sending_time`.
+    /// Sent by a replication task `ReplicationCore`.
+    ReplicationAcked {
+        /// The ID of the target node that acknowledged the leader.
+        target: C::NodeId,
+
+        /// When the acknowledged request was sent.
+        sending_time: InstantOf<C>,
+
+        /// Which ServerState sent this message
+        vote: Vote<C::NodeId>,
+    },
+
+    /// The local storage flushed the logs upto `log_id`, or failed to.
+    /// Sent by the [`LogFlushed`](`crate::storage::LogFlushed`) callback of a leader's append.
+    LogFlushed {
+        log_id: Option<LogId<C::NodeId>>,
+
+        result: Result<(), std::io::Error>,
+
+        /// Which ServerState sent this message
+        vote: Vote<C::NodeId>,
+    },
+
+    /// The backoff state of a replication target changed.
+    /// Sent by a replication task `ReplicationCore`.
+    UpdateReplicationBackoff {
+        /// The ID of the target node.
+        target: C::NodeId,
+
+        /// The backoff state, or `None` if the target became reachable.
+        backoff: Option<ReplicationBackoff>,
+
+        /// Which ServerState sent this message
+        vote: Vote<C::NodeId>,
+    },
+
+    /// The progress of a replication target changed.
+    /// Sent by a replication task `ReplicationCore`, at a bounded rate.
+    UpdateReplicationProgress {
+        /// The ID of the target node.
+        target: C::NodeId,
+
+        progress: ReplicationProgress<C::NodeId, InstantOf<C>>,
+
+        /// Which ServerState sent this message
+        vote: Vote<C::NodeId>,
+    },
+
     /// An event indicating that the Raft node needs to revert to follower state.
     /// Sent by a replication task `ReplicationCore`.
     // TODO: rename it
@@ -811,6 +2020,22 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
 
     /// An event from a replication stream requesting snapshot info.
     /// Sent by a replication task `ReplicationCore`.
+    /// Pick a voter for a replication target to fetch a snapshot from, see [`SnapshotSource`].
+    ///
+    /// [`SnapshotSource`]: crate::SnapshotSource
+    PickSnapshotSource {
+        target: C::NodeId,
+
+        /// The last log id of the snapshot the leader would send.
+        need: LogId<C::NodeId>,
+
+        /// The response channel for delivering the picked voter and its node info.
+        tx: oneshot::Sender<Option<(C::NodeId, Option<C::Node>)>>,
+
+        /// Which ServerState sent this message
+        vote: Vote<C::NodeId>,
+    },
+
     NeedsSnapshot {
         target: C::NodeId,
 
@@ -818,7 +2043,7 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
         must_include: Option<LogId<C::NodeId>>,
 
         /// The response channel for delivering the snapshot data.
-        tx: oneshot::Sender<Snapshot<C::NodeId, S::SnapshotData>>,
+        tx: oneshot::Sender<Snapshot<C::NodeId, C::SnapshotData, C::Node>>,
 
         /// Which ServerState sent this message
         vote: Vote<C::NodeId>,
@@ -829,11 +2054,12 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
     ReplicationFatal,
 }
 
-impl<C, N, S> MessageSummary<RaftMsg<C, N, S>> for RaftMsg<C, N, S>
+impl<C, N, LS, SM> MessageSummary<RaftMsg<C, N, LS, SM>> for RaftMsg<C, N, LS, SM>
 where
     C: RaftTypeConfig,
     N: RaftNetworkFactory<C>,
-    S: RaftStorage<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
 {
     fn summary(&self) -> String {
         match self {
@@ -846,22 +2072,69 @@ where
             RaftMsg::VoteResponse { target, resp, vote } => {
                 format!("VoteResponse: from: {}: {}, res-vote: {}", target, resp.summary(), vote)
             }
+            RaftMsg::PreVoteResponse { target, resp, vote } => {
+                format!(
+                    "PreVoteResponse: from: {}: {}, res-vote: {}",
+                    target,
+                    resp.summary(),
+                    vote
+                )
+            }
             RaftMsg::InstallSnapshot { rpc, .. } => {
                 format!("InstallSnapshot: {}", rpc.summary())
             }
+            RaftMsg::GetSnapshot { .. } => "GetSnapshot".to_string(),
+            RaftMsg::BeginReceivingSnapshot { .. } => "BeginReceivingSnapshot".to_string(),
+            RaftMsg::InstallFullSnapshot { vote, snapshot, .. } => {
+                format!("InstallFullSnapshot: vote: {}, meta: {:?}", vote, snapshot.meta)
+            }
+            RaftMsg::InstallLocalSnapshot { meta, .. } => {
+                format!("InstallLocalSnapshot: meta: {:?}", meta)
+            }
             RaftMsg::SnapshotUpdate { update } => {
                 format!("SnapshotUpdate: {:?}", update)
             }
             RaftMsg::ClientWriteRequest { rpc, .. } => {
                 format!("ClientWriteRequest: {}", rpc.summary())
             }
+            RaftMsg::ForwardClientWrite { rpc, .. } => {
+                format!("ForwardClientWrite: {}", rpc.summary())
+            }
+            RaftMsg::ClientWriteWithResponder { rpc, .. } => {
+                format!("ClientWriteWithResponder: {}", rpc.summary())
+            }
+            RaftMsg::ClientWriteFFRequest { rpc, .. } => {
+                format!("ClientWriteFFRequest: {}", rpc.summary())
+            }
+            RaftMsg::ClientWriteManyRequest { rpcs, .. } => {
+                format!("ClientWriteManyRequest: n: {}", rpcs.len())
+            }
             RaftMsg::CheckIsLeaderRequest { .. } => "CheckIsLeaderRequest".to_string(),
-            RaftMsg::Initialize { members, .. } => {
-                format!("Initialize: {:?}", members)
+            RaftMsg::GetReadLease { .. } => "GetReadLease".to_string(),
+            RaftMsg::FollowerRead { max_staleness, .. } => {
+                format!("FollowerRead: max_staleness: {:?}", max_staleness)
+            }
+            RaftMsg::TimeoutNow { rpc, .. } => {
+                format!("TimeoutNow: {}", rpc.summary())
+            }
+            RaftMsg::TransferLeader { target, .. } => {
+                format!("TransferLeader: {}", target)
+            }
+            RaftMsg::TimeoutNowResult { target, result, vote } => {
+                format!(
+                    "TimeoutNowResult: target: {}, result: {:?}, vote: {}",
+                    target, result, vote
+                )
+            }
+            RaftMsg::Initialize { members, learners, .. } => {
+                format!("Initialize: {:?}, learners: {:?}", members, learners)
             }
             RaftMsg::AddLearner { id, node, .. } => {
                 format!("AddLearner: id: {}, node: {:?}", id, node)
             }
+            RaftMsg::AddLearnerAndPromote { id, node, .. } => {
+                format!("AddLearnerAndPromote: id: {}, node: {:?}", id, node)
+            }
             RaftMsg::ChangeMembership {
                 changes: members,
                 when,
@@ -873,7 +2146,32 @@ where
                     members, when, turn_to_learner,
                 )
             }
+            RaftMsg::ChangeMembershipDryRun {
+                changes: members,
+                when,
+                turn_to_learner,
+                ..
+            } => {
+                format!(
+                    "ChangeMembershipDryRun: members: {:?}, when: {:?}, turn_to_learner: {}",
+                    members, when, turn_to_learner,
+                )
+            }
             RaftMsg::ExternalRequest { .. } => "External Request".to_string(),
+            RaftMsg::TriggerSnapshot { .. } => "TriggerSnapshot".to_string(),
+            RaftMsg::TriggerElect { .. } => "TriggerElect".to_string(),
+            RaftMsg::TriggerPurgeLog { upto, force, .. } => {
+                format!("TriggerPurgeLog: upto: {}, force: {}", upto, force)
+            }
+            RaftMsg::PurgeLogsUpto { log_id, .. } => {
+                format!("PurgeLogsUpto: {}", log_id)
+            }
+            RaftMsg::GetStateSummary { .. } => "GetStateSummary".to_string(),
+            RaftMsg::UpdateConfig { update, .. } => {
+                format!("UpdateConfig: {:?}", update)
+            }
+            RaftMsg::GetConfig { .. } => "GetConfig".to_string(),
+            RaftMsg::Drain { .. } => "Drain".to_string(),
             RaftMsg::Tick { i } => {
                 format!("Tick {}", i)
             }
@@ -887,6 +2185,46 @@ where
                     target, result, vote
                 )
             }
+            RaftMsg::ReplicationAcked {
+                ref target,
+                ref sending_time,
+                ref vote,
+            } => {
+                format!(
+                    "ReplicationAcked: target: {}, sending_time: {:?}, server_state_vote: {}",
+                    target, sending_time, vote
+                )
+            }
+            RaftMsg::LogFlushed {
+                ref log_id,
+                ref result,
+                ref vote,
+            } => {
+                format!(
+                    "LogFlushed: log_id: {:?}, result: {:?}, server_state_vote: {}",
+                    log_id, result, vote
+                )
+            }
+            RaftMsg::UpdateReplicationBackoff {
+                ref target,
+                ref backoff,
+                ref vote,
+            } => {
+                format!(
+                    "UpdateReplicationBackoff: target: {}, backoff: {:?}, server_state_vote: {}",
+                    target, backoff, vote
+                )
+            }
+            RaftMsg::UpdateReplicationProgress {
+                ref target,
+                ref progress,
+                ref vote,
+            } => {
+                format!(
+                    "UpdateReplicationProgress: target: {}, progress: {:?}, server_state_vote: {}",
+                    target, progress, vote
+                )
+            }
             RaftMsg::RevertToFollower {
                 ref target,
                 ref new_vote,
@@ -908,6 +2246,17 @@ where
                     target, must_include, vote
                 )
             }
+            RaftMsg::PickSnapshotSource {
+                ref target,
+                ref need,
+                ref vote,
+                ..
+            } => {
+                format!(
+                    "PickSnapshotSource: target: {}, need: {}, server_state_vote: {}",
+                    target, need, vote
+                )
+            }
             RaftMsg::ReplicationFatal => "ReplicationFatal".to_string(),
         }
     }
@@ -926,19 +2275,66 @@ pub struct AppendEntriesRequest<C: RaftTypeConfig> {
     ///
     /// This may be empty when the leader is sending heartbeats. Entries
     /// are batched for efficiency.
-    pub entries: Vec<Entry<C>>,
+    ///
+    /// The entries are shared with the leader's log cache and the other replication streams, thus sending the same
+    /// entries to several followers does not copy their payloads.
+    pub entries: Vec<Arc<Entry<C>>>,
 
     /// The leader's committed log id.
     pub leader_commit: Option<LogId<C::NodeId>>,
+
+    /// The new log entries compressed with [`Config::replication_compression`], in which case `entries` is empty.
+    ///
+    /// They are decompressed by [`Raft::append_entries`] before being stored.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub compressed_entries: Option<CompressedEntries>,
+
+    /// The node this leader is transferring its leadership to, if it is announcing a leadership transfer.
+    ///
+    /// A follower that has accepted this announcement grants the leader-transfer vote request of this node for the
+    /// next term, even when the lease of the current leader has not yet expired.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub leader_transfer_to: Option<C::NodeId>,
+}
+
+impl<C: RaftTypeConfig> AppendEntriesRequest<C> {
+    /// Build a heartbeat, which carries only the vote and the committed log id of the leader.
+    pub fn new_heartbeat(vote: Vote<C::NodeId>, leader_commit: Option<LogId<C::NodeId>>) -> Self {
+        Self {
+            vote,
+            prev_log_id: None,
+            entries: vec![],
+            leader_commit,
+            compressed_entries: None,
+            leader_transfer_to: None,
+        }
+    }
+
+    /// Whether it is a heartbeat: it carries no logs and no `prev_log_id` to match.
+    ///
+    /// A follower handles a heartbeat as a refresh of the leader lease, without touching its logs.
+    pub fn is_heartbeat(&self) -> bool {
+        self.prev_log_id.is_none() && self.entries.is_empty() && self.compressed_entries.is_none()
+    }
+
+    /// Move the entries in `compressed_entries`, if any, back to `entries`.
+    pub(crate) fn decompress_entries(&mut self) -> Result<(), CompressionError> {
+        if let Some(compressed) = self.compressed_entries.take() {
+            self.entries = compressed.decompress()?.into_iter().map(Arc::new).collect();
+        }
+        Ok(())
+    }
 }
 
 impl<C: RaftTypeConfig> Clone for AppendEntriesRequest<C> {
     fn clone(&self) -> Self {
         Self {
-            vote: self.vote,
-            prev_log_id: self.prev_log_id,
+            vote: self.vote.clone(),
+            prev_log_id: self.prev_log_id.clone(),
             entries: self.entries.clone(),
-            leader_commit: self.leader_commit,
+            leader_commit: self.leader_commit.clone(),
+            compressed_entries: self.compressed_entries.clone(),
+            leader_transfer_to: self.leader_transfer_to.clone(),
         }
     }
 }
@@ -952,6 +2348,8 @@ where C::D: Debug
             .field("prev_log_id", &self.prev_log_id)
             .field("entries", &self.entries)
             .field("leader_commit", &self.leader_commit)
+            .field("compressed_entries", &self.compressed_entries)
+            .field("leader_transfer_to", &self.leader_transfer_to)
             .finish()
     }
 }
@@ -959,11 +2357,24 @@ where C::D: Debug
 impl<C: RaftTypeConfig> MessageSummary<AppendEntriesRequest<C>> for AppendEntriesRequest<C> {
     fn summary(&self) -> String {
         format!(
-            "vote={}, prev_log_id={}, leader_commit={}, entries={}",
+            "vote={}, prev_log_id={}, leader_commit={}, entries={}{}{}",
             self.vote,
             self.prev_log_id.summary(),
             self.leader_commit.summary(),
-            self.entries.as_slice().summary()
+            self.entries.as_slice().summary(),
+            match &self.compressed_entries {
+                None => "".to_string(),
+                Some(c) => format!(
+                    ", compressed: {} entries in {} bytes with {}",
+                    c.count,
+                    c.data.len(),
+                    c.algo
+                ),
+            },
+            match &self.leader_transfer_to {
+                None => "".to_string(),
+                Some(target) => format!(", leader_transfer_to: {}", target),
+            }
         )
     }
 }
@@ -1004,17 +2415,60 @@ impl<NID: NodeId> MessageSummary<AppendEntriesResponse<NID>> for AppendEntriesRe
 pub struct VoteRequest<NID: NodeId> {
     pub vote: Vote<NID>,
     pub last_log_id: Option<LogId<NID>>,
+
+    /// Whether it is a pre-vote request.
+    ///
+    /// A pre-vote only asks whether the voter would grant `vote`.
+    /// The voter does not update or persist its own vote when handling it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pre_vote: bool,
+
+    /// Whether the election is started because the leader is transferring its leadership to the candidate.
+    ///
+    /// A voter grants such a vote even if it has received a heartbeat from the leader recently, but only if the
+    /// leader has announced the transfer to the candidate with [`AppendEntriesRequest::leader_transfer_to`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub leader_transfer: bool,
 }
 
 impl<NID: NodeId> MessageSummary<VoteRequest<NID>> for VoteRequest<NID> {
     fn summary(&self) -> String {
-        format!("{}, last_log:{:?}", self.vote, self.last_log_id.map(|x| x.to_string()))
+        format!(
+            "{}, last_log:{:?}{}{}",
+            self.vote,
+            self.last_log_id.as_ref().map(|x| x.to_string()),
+            if self.pre_vote { ", pre_vote" } else { "" },
+            if self.leader_transfer { ", leader_transfer" } else { "" }
+        )
     }
 }
 
 impl<NID: NodeId> VoteRequest<NID> {
     pub fn new(vote: Vote<NID>, last_log_id: Option<LogId<NID>>) -> Self {
-        Self { vote, last_log_id }
+        Self {
+            vote,
+            last_log_id,
+            pre_vote: false,
+            leader_transfer: false,
+        }
+    }
+
+    pub fn new_pre_vote(vote: Vote<NID>, last_log_id: Option<LogId<NID>>) -> Self {
+        Self {
+            vote,
+            last_log_id,
+            pre_vote: true,
+            leader_transfer: false,
+        }
+    }
+
+    pub fn new_leader_transfer(vote: Vote<NID>, last_log_id: Option<LogId<NID>>) -> Self {
+        Self {
+            vote,
+            last_log_id,
+            pre_vote: false,
+            leader_transfer: true,
+        }
     }
 }
 
@@ -1039,11 +2493,49 @@ impl<NID: NodeId> MessageSummary<VoteResponse<NID>> for VoteResponse<NID> {
             "{{granted:{}, {}, last_log:{:?}}}",
             self.vote_granted,
             self.vote,
-            self.last_log_id.map(|x| x.to_string())
+            self.last_log_id.as_ref().map(|x| x.to_string())
+        )
+    }
+}
+
+/// An RPC sent by a leader to let the target start an election at once, when transferring leadership to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct TimeoutNowRequest<NID: NodeId> {
+    /// The vote of the leader.
+    pub vote: Vote<NID>,
+
+    /// The last log id of the leader. The target has to have caught up to it.
+    pub last_log_id: Option<LogId<NID>>,
+}
+
+impl<NID: NodeId> MessageSummary<TimeoutNowRequest<NID>> for TimeoutNowRequest<NID> {
+    fn summary(&self) -> String {
+        format!(
+            "{}, last_log:{:?}",
+            self.vote,
+            self.last_log_id.as_ref().map(|x| x.to_string())
         )
     }
 }
 
+/// The response to a `TimeoutNowRequest`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct TimeoutNowResponse<NID: NodeId> {
+    /// The vote of the target after handling the request.
+    pub vote: Vote<NID>,
+
+    /// Whether the target has started an election.
+    pub election_started: bool,
+}
+
+impl<NID: NodeId> MessageSummary<TimeoutNowResponse<NID>> for TimeoutNowResponse<NID> {
+    fn summary(&self) -> String {
+        format!("{{election_started:{}, {}}}", self.election_started, self.vote)
+    }
+}
+
 /// An RPC sent by the Raft leader to send chunks of a snapshot to a follower (§7).
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
@@ -1051,7 +2543,7 @@ pub struct InstallSnapshotRequest<C: RaftTypeConfig> {
     pub vote: Vote<C::NodeId>,
 
     /// Metadata of a snapshot: snapshot_id, last_log_ed membership etc.
-    pub meta: SnapshotMeta<C::NodeId>,
+    pub meta: SnapshotMeta<C::NodeId, C::Node>,
 
     /// The byte offset where this chunk of data is positioned in the snapshot file.
     pub offset: u64,
@@ -1060,6 +2552,15 @@ pub struct InstallSnapshotRequest<C: RaftTypeConfig> {
 
     /// Will be `true` if this is the last chunk in the snapshot.
     pub done: bool,
+
+    /// The CRC32 checksum of `data`, verified before it is written, if checksums are enabled.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub chunk_checksum: Option<u32>,
+
+    /// The CRC32 checksum of the whole snapshot, sent with the last chunk and verified before the snapshot is
+    /// installed, if checksums are enabled.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub snapshot_checksum: Option<u32>,
 }
 
 impl<C: RaftTypeConfig> MessageSummary<InstallSnapshotRequest<C>> for InstallSnapshotRequest<C> {
@@ -1080,6 +2581,60 @@ impl<C: RaftTypeConfig> MessageSummary<InstallSnapshotRequest<C>> for InstallSna
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 pub struct InstallSnapshotResponse<NID: NodeId> {
     pub vote: Vote<NID>,
+
+    /// The offset of the next chunk the target expects, or `None` if the snapshot is installed or rejected.
+    ///
+    /// The leader sends the next chunk from this offset, which allows it to skip the bytes already received, e.g.,
+    /// when it starts over streaming the same snapshot.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub next_offset: Option<u64>,
+}
+
+/// An RPC sent by the Raft leader to let a target fetch a snapshot from another node, instead of from the leader.
+///
+/// See [`RaftNetwork::get_snapshot_from`](`crate::RaftNetwork::get_snapshot_from`).
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct SnapshotFromRequest<C: RaftTypeConfig> {
+    pub vote: Vote<C::NodeId>,
+
+    /// The node to fetch the snapshot from.
+    pub source: C::NodeId,
+
+    /// The node info of `source` in the membership config, if there is one.
+    pub source_node: Option<C::Node>,
+
+    /// The log id the fetched snapshot has to include.
+    pub must_include: LogId<C::NodeId>,
+}
+
+impl<C: RaftTypeConfig> MessageSummary<SnapshotFromRequest<C>> for SnapshotFromRequest<C> {
+    fn summary(&self) -> String {
+        format!(
+            "vote={}, source={}, must_include={}",
+            self.vote, self.source, self.must_include
+        )
+    }
+}
+
+/// The response to a `SnapshotFromRequest`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct SnapshotFromResponse<NID: NodeId> {
+    pub vote: Vote<NID>,
+
+    /// The last log id of the snapshot the target has installed.
+    ///
+    /// It is `None` if the snapshot of the source does not include `must_include`, in which case the leader sends its
+    /// own snapshot.
+    pub last_log_id: Option<LogId<NID>>,
+}
+
+/// The response to a snapshot that is sent as a whole instead of in chunks, see [`Raft::install_full_snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct SnapshotResponse<NID: NodeId> {
+    pub vote: Vote<NID>,
 }
 
 //////////////////////////////////////////////////////////////////////////////////////////////////
@@ -1092,13 +2647,20 @@ pub struct InstallSnapshotResponse<NID: NodeId> {
 pub struct ClientWriteRequest<C: RaftTypeConfig> {
     /// The application specific contents of this client request.
     pub(crate) payload: EntryPayload<C>,
+
+    /// Application metadata that rides along with the payload, see [`Entry::context`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) context: Option<Vec<u8>>,
 }
 
 impl<C: RaftTypeConfig> Debug for ClientWriteRequest<C>
 where C::D: Debug
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("ClientWriteRequest").field("payload", &self.payload).finish()
+        f.debug_struct("ClientWriteRequest")
+            .field("payload", &self.payload)
+            .field("context", &self.context)
+            .finish()
     }
 }
 
@@ -1110,7 +2672,50 @@ impl<C: RaftTypeConfig> MessageSummary<ClientWriteRequest<C>> for ClientWriteReq
 
 impl<C: RaftTypeConfig> ClientWriteRequest<C> {
     pub fn new(entry: EntryPayload<C>) -> Self {
-        Self { payload: entry }
+        Self {
+            payload: entry,
+            context: None,
+        }
+    }
+
+    /// Attach application metadata to the request, e.g., a correlation id for distributed tracing.
+    ///
+    /// The context is stored in the log entry as [`Entry::context`], replicated to every follower, and passed to
+    /// [`RaftStateMachine::apply_to_state_machine()`](`crate::storage::RaftStateMachine::apply_to_state_machine`)
+    /// along with the entry. It is application metadata, not part of the state machine input: applying an entry
+    /// must produce the same result whatever its context is.
+    pub fn with_context(mut self, context: impl Into<Vec<u8>>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+}
+
+/// An RPC sent by a non-leader to forward a client write to the leader.
+///
+/// See [`Config::enable_forward_client_write`](`crate::Config::enable_forward_client_write`).
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct ForwardClientWriteRequest<C: RaftTypeConfig> {
+    /// The client write to forward.
+    pub rpc: ClientWriteRequest<C>,
+
+    /// The number of times the write has been forwarded, including this one.
+    pub hops: u64,
+}
+
+impl<C: RaftTypeConfig> Debug for ForwardClientWriteRequest<C>
+where C::D: Debug
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ForwardClientWriteRequest")
+            .field("rpc", &self.rpc)
+            .field("hops", &self.hops)
+            .finish()
+    }
+}
+
+impl<C: RaftTypeConfig> MessageSummary<ForwardClientWriteRequest<C>> for ForwardClientWriteRequest<C> {
+    fn summary(&self) -> String {
+        format!("hops:{}, {}", self.hops, self.rpc.summary())
     }
 }
 
@@ -1128,7 +2733,7 @@ pub struct ClientWriteResponse<C: RaftTypeConfig> {
     pub data: C::R,
 
     /// If the log entry is a change-membership entry.
-    pub membership: Option<Membership<C::NodeId>>,
+    pub membership: Option<Membership<C::NodeId, C::Node>>,
 }
 
 impl<C: RaftTypeConfig> Debug for ClientWriteResponse<C>
@@ -0,0 +1,98 @@
+//! Run multiple Raft groups in one process.
+
+use std::fmt::Debug;
+use std::ops::Deref;
+
+use crate::Raft;
+use crate::RaftLogStorage;
+use crate::RaftNetworkFactory;
+use crate::RaftStateMachine;
+use crate::RaftTypeConfig;
+
+/// A [`Raft`] that belongs to the Raft group `group_id`.
+///
+/// A process that runs dozens of Raft groups, e.g., one per shard, creates a `Raft` for each of them, usually with a
+/// storage of every group backed by a single shared storage engine, scoping the keys of each group by the group id.
+/// Openraft itself has no notion of groups: every `Raft` is independent of the others, and sees only the
+/// storage it is created with. See the "Storage isolation" section of [`RaftStorage`](`crate::RaftStorage`) for what a
+/// shared storage must guarantee.
+///
+/// This handle dereferences to the `Raft`, and carries the group id for the application to route requests by.
+pub struct RaftGroupHandle<G, C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
+    group_id: G,
+    raft: Raft<C, N, LS, SM>,
+}
+
+impl<G, C, N, LS, SM> RaftGroupHandle<G, C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
+    pub fn new(group_id: G, raft: Raft<C, N, LS, SM>) -> Self {
+        Self { group_id, raft }
+    }
+
+    /// The id of the group this Raft belongs to.
+    pub fn group_id(&self) -> &G {
+        &self.group_id
+    }
+
+    pub fn raft(&self) -> &Raft<C, N, LS, SM> {
+        &self.raft
+    }
+
+    pub fn into_inner(self) -> (G, Raft<C, N, LS, SM>) {
+        (self.group_id, self.raft)
+    }
+}
+
+impl<G, C, N, LS, SM> Deref for RaftGroupHandle<G, C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
+    type Target = Raft<C, N, LS, SM>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.raft
+    }
+}
+
+impl<G, C, N, LS, SM> Clone for RaftGroupHandle<G, C, N, LS, SM>
+where
+    G: Clone,
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            group_id: self.group_id.clone(),
+            raft: self.raft.clone(),
+        }
+    }
+}
+
+impl<G, C, N, LS, SM> Debug for RaftGroupHandle<G, C, N, LS, SM>
+where
+    G: Debug,
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RaftGroupHandle").field("group_id", &self.group_id).finish()
+    }
+}
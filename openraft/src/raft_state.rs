@@ -1,3 +1,5 @@
+use crate::async_runtime::Instant;
+use crate::config::CommitQuorum;
 use crate::engine::LogIdList;
 use crate::internal_server_state::InternalServerState;
 use crate::leader::Leader;
@@ -5,13 +7,18 @@ use crate::raft_types::RaftLogId;
 use crate::LogId;
 use crate::LogIdOptionExt;
 use crate::MembershipState;
+use crate::Node;
 use crate::NodeId;
+use crate::NodeInfo;
 use crate::ServerState;
 use crate::Vote;
 
 /// A struct used to represent the raft state which a Raft node needs.
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
-pub struct RaftState<NID: NodeId> {
+///
+/// `I` is the [`Instant`] type of the [`AsyncRuntime`](`crate::AsyncRuntime`), with which a leader tracks when it
+/// is acknowledged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RaftState<NID: NodeId, N: NodeInfo = Node, I: Instant = tokio::time::Instant> {
     /// The vote state of this node.
     pub vote: Vote<NID>,
 
@@ -22,13 +29,13 @@ pub struct RaftState<NID: NodeId> {
     pub log_ids: LogIdList<NID>,
 
     /// The latest cluster membership configuration found, in log or in state machine.
-    pub membership_state: MembershipState<NID>,
+    pub membership_state: MembershipState<NID, N>,
 
     // --
     // -- volatile fields: they are not persisted.
     // --
     /// The internal server state used by Engine.
-    pub(crate) internal_server_state: InternalServerState<NID>,
+    pub(crate) internal_server_state: InternalServerState<NID, N, I>,
 
     /// The log id of the last known committed entry.
     ///
@@ -42,9 +49,28 @@ pub struct RaftState<NID: NodeId> {
     pub server_state: ServerState,
 }
 
-impl<NID> RaftState<NID>
-where NID: NodeId
+impl<NID, N, I> RaftState<NID, N, I>
+where
+    NID: NodeId,
+    N: NodeInfo,
+    I: Instant,
 {
+    /// Create the state of a node `id` that has not yet stored anything.
+    ///
+    /// The initial `vote` is the minimal vote this node can have: `(term=0, node_id=id)`.
+    /// See: [Conditions for initialization](https://datafuselabs.github.io/openraft/cluster-formation.html#conditions-for-initialization)
+    pub fn new(id: NID) -> Self {
+        Self {
+            vote: Vote::new(0, id),
+            last_applied: None,
+            log_ids: LogIdList::default(),
+            membership_state: MembershipState::default(),
+            internal_server_state: InternalServerState::default(),
+            committed: None,
+            server_state: ServerState::default(),
+        }
+    }
+
     /// Append a list of `log_id`.
     ///
     /// The log ids in the input has to be continuous.
@@ -71,7 +97,7 @@ where NID: NodeId
     #[allow(dead_code)]
     pub(crate) fn has_log_id(&self, log_id: &LogId<NID>) -> bool {
         if log_id.index < self.committed.next_index() {
-            debug_assert!(Some(*log_id) <= self.committed);
+            debug_assert!(Some(log_id.clone()) <= self.committed);
             return true;
         }
 
@@ -100,9 +126,12 @@ where NID: NodeId
 
     /// Create a new Leader, when raft enters candidate state.
     /// In openraft, Leader and Candidate shares the same state.
-    pub(crate) fn new_leader(&mut self) {
+    ///
+    /// The logs it proposes are committed by `commit_quorum`.
+    pub(crate) fn new_leader(&mut self, commit_quorum: &CommitQuorum) {
         let em = &self.membership_state.effective;
-        self.internal_server_state = InternalServerState::Leading(Leader::new(em.clone(), em.learner_ids()));
+        let leader = Leader::new(em.clone(), em.learner_ids(), em.commit_required_ids(commit_quorum));
+        self.internal_server_state = InternalServerState::Leading(leader);
     }
 
     /// Return true if the currently effective membership is committed.
@@ -114,9 +143,9 @@ where NID: NodeId
     /// If updated, it returns the previous value in a `Some()`.
     pub(crate) fn update_committed(&mut self, committed: &Option<LogId<NID>>) -> Option<Option<LogId<NID>>> {
         if committed > &self.committed {
-            let prev = self.committed;
+            let prev = self.committed.clone();
 
-            self.committed = *committed;
+            self.committed = committed.clone();
 
             // TODO(xp): use a vec to store committed and effective membership.
             if self.committed >= self.membership_state.effective.log_id {
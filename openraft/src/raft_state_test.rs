@@ -23,7 +23,7 @@ fn m12() -> Membership<u64> {
 
 #[test]
 fn test_raft_state_has_log_id_empty() -> anyhow::Result<()> {
-    let rs = RaftState::default();
+    let rs = RaftState::new(1);
 
     assert!(!rs.has_log_id(&log_id(0, 0)));
 
@@ -34,7 +34,7 @@ fn test_raft_state_has_log_id_empty() -> anyhow::Result<()> {
 fn test_raft_state_has_log_id_committed_gets_true() -> anyhow::Result<()> {
     let rs = RaftState {
         committed: Some(log_id(2, 1)),
-        ..Default::default()
+        ..RaftState::new(1)
     };
 
     assert!(rs.has_log_id(&log_id(0, 0)));
@@ -49,7 +49,7 @@ fn test_raft_state_has_log_id_in_log_id_list() -> anyhow::Result<()> {
     let rs = RaftState {
         committed: Some(log_id(2, 1)),
         log_ids: LogIdList::new(vec![log_id(1, 2), log_id(3, 4)]),
-        ..Default::default()
+        ..RaftState::new(1)
     };
 
     assert!(rs.has_log_id(&log_id(0, 0)));
@@ -68,20 +68,20 @@ fn test_raft_state_has_log_id_in_log_id_list() -> anyhow::Result<()> {
 fn test_raft_state_last_log_id() -> anyhow::Result<()> {
     let rs = RaftState::<u64> {
         log_ids: LogIdList::new(vec![]),
-        ..Default::default()
+        ..RaftState::new(1)
     };
 
     assert_eq!(None, rs.last_log_id());
 
     let rs = RaftState {
         log_ids: LogIdList::new(vec![log_id(1, 2)]),
-        ..Default::default()
+        ..RaftState::new(1)
     };
     assert_eq!(Some(log_id(1, 2)), rs.last_log_id());
 
     let rs = RaftState {
         log_ids: LogIdList::new(vec![log_id(1, 2), log_id(3, 4)]),
-        ..Default::default()
+        ..RaftState::new(1)
     };
     assert_eq!(Some(log_id(3, 4)), rs.last_log_id());
 
@@ -92,20 +92,20 @@ fn test_raft_state_last_log_id() -> anyhow::Result<()> {
 fn test_raft_state_last_purged_log_id() -> anyhow::Result<()> {
     let rs = RaftState::<u64> {
         log_ids: LogIdList::new(vec![]),
-        ..Default::default()
+        ..RaftState::new(1)
     };
 
     assert_eq!(None, rs.last_purged_log_id());
 
     let rs = RaftState {
         log_ids: LogIdList::new(vec![log_id(1, 2)]),
-        ..Default::default()
+        ..RaftState::new(1)
     };
     assert_eq!(Some(log_id(1, 2)), rs.last_purged_log_id());
 
     let rs = RaftState {
         log_ids: LogIdList::new(vec![log_id(1, 2), log_id(3, 4)]),
-        ..Default::default()
+        ..RaftState::new(1)
     };
     assert_eq!(Some(log_id(1, 2)), rs.last_purged_log_id());
 
@@ -121,7 +121,7 @@ fn test_raft_state_is_membership_committed() -> anyhow::Result<()> {
             committed: Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m12())),
             effective: Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m12())),
         },
-        ..Default::default()
+        ..RaftState::new(1)
     };
 
     assert!(
@@ -135,7 +135,7 @@ fn test_raft_state_is_membership_committed() -> anyhow::Result<()> {
             committed: Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m12())),
             effective: Arc::new(EffectiveMembership::new(Some(log_id(2, 2)), m12())),
         },
-        ..Default::default()
+        ..RaftState::new(1)
     };
 
     assert!(
@@ -149,7 +149,7 @@ fn test_raft_state_is_membership_committed() -> anyhow::Result<()> {
             committed: Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m12())),
             effective: Arc::new(EffectiveMembership::new(Some(log_id(3, 3)), m12())),
         },
-        ..Default::default()
+        ..RaftState::new(1)
     };
 
     assert!(!rs.is_membership_committed(), "rs.committed < effective.log_id");
@@ -0,0 +1,86 @@
+use std::any::TypeId;
+
+use crate::error::ClientWriteError;
+use crate::error::Infallible;
+use crate::RaftTypeConfig;
+
+/// An application defined error to reject a client write.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[error("duplicated request: {0}")]
+struct DuplicatedRequest(u64);
+
+crate::declare_raft_types!(
+    pub(crate) DefaultAppErrorConfig: D = (), R = (), NodeId = u64
+);
+
+crate::declare_raft_types!(
+    pub(crate) AppErrorConfig: D = (), R = (), AppError = DuplicatedRequest, NodeId = u64
+);
+
+#[test]
+fn test_declare_raft_types_default_app_error() -> anyhow::Result<()> {
+    assert_eq!(
+        TypeId::of::<Infallible>(),
+        TypeId::of::<<DefaultAppErrorConfig as RaftTypeConfig>::AppError>()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_declare_raft_types_with_app_error() -> anyhow::Result<()> {
+    assert_eq!(
+        TypeId::of::<DuplicatedRequest>(),
+        TypeId::of::<<AppErrorConfig as RaftTypeConfig>::AppError>()
+    );
+
+    let err = ClientWriteError::<u64, crate::Node, <AppErrorConfig as RaftTypeConfig>::AppError>::AppError(
+        DuplicatedRequest(3),
+    );
+    assert_eq!("duplicated request: 3", err.to_string());
+
+    Ok(())
+}
+
+crate::declare_raft_types!(
+    pub(crate) SnapshotDataConfig: D = (), R = (), NodeId = u64, SnapshotData = tokio::fs::File
+);
+
+#[test]
+fn test_declare_raft_types_default_snapshot_data() -> anyhow::Result<()> {
+    assert_eq!(
+        TypeId::of::<std::io::Cursor<Vec<u8>>>(),
+        TypeId::of::<<DefaultAppErrorConfig as RaftTypeConfig>::SnapshotData>()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_declare_raft_types_with_snapshot_data() -> anyhow::Result<()> {
+    assert_eq!(
+        TypeId::of::<tokio::fs::File>(),
+        TypeId::of::<<SnapshotDataConfig as RaftTypeConfig>::SnapshotData>()
+    );
+
+    Ok(())
+}
+
+/// A snapshot data that is not file-like, e.g., the path to a checkpoint directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CheckpointDir(std::path::PathBuf);
+
+crate::declare_raft_types!(
+    pub(crate) CheckpointConfig: D = (), R = (), NodeId = u64, SnapshotData = CheckpointDir
+);
+
+#[test]
+fn test_declare_raft_types_with_generic_snapshot_data() -> anyhow::Result<()> {
+    assert_eq!(
+        TypeId::of::<CheckpointDir>(),
+        TypeId::of::<<CheckpointConfig as RaftTypeConfig>::SnapshotData>()
+    );
+
+    Ok(())
+}
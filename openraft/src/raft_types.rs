@@ -8,8 +8,13 @@ use crate::RaftTypeConfig;
 
 /// The identity of a raft log.
 /// A term, node_id and an index identifies an log globally.
-#[derive(Debug, Default, Copy, Clone, PartialOrd, Ord, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize),
+    archive(check_bytes)
+)]
 pub struct LogId<NID: NodeId> {
     pub leader_id: LeaderId<NID>,
     pub index: u64,
@@ -27,7 +32,7 @@ impl<NID: NodeId> RaftLogId<NID> for LogId<NID> {
     }
 
     fn set_log_id(&mut self, log_id: &LogId<NID>) {
-        *self = *log_id
+        *self = log_id.clone()
     }
 }
 
@@ -53,19 +58,12 @@ impl<NID: NodeId> LogId<NID> {
         if leader_id.term == 0 || index == 0 {
             assert_eq!(
                 leader_id.term, 0,
-                "zero-th log entry must be (0,0,0), but {} {}",
+                "zero-th log entry must be (0,*,0), but {} {}",
                 leader_id, index
             );
-            assert_eq!(
-                leader_id.node_id,
-                NID::default(),
-                "zero-th log entry must be (0,0,0), but {} {}",
-                leader_id,
-                index
-            );
             assert_eq!(
                 index, 0,
-                "zero-th log entry must be (0,0,0), but {} {}",
+                "zero-th log entry must be (0,*,0), but {} {}",
                 leader_id, index
             );
         }
@@ -80,7 +78,7 @@ pub trait LogIdOptionExt {
 
 impl<NID: NodeId> LogIdOptionExt for Option<LogId<NID>> {
     fn index(&self) -> Option<u64> {
-        self.map(|x| x.index)
+        self.as_ref().map(|x| x.index)
     }
 
     fn next_index(&self) -> u64 {
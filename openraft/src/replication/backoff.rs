@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use crate::metrics::ReplicationBackoff;
+use crate::network::BackoffPolicy;
+
+/// Tracks the consecutive failures to reach a replication target, and the delay before the next retry, which is
+/// decided by a [`BackoffPolicy`].
+pub(crate) struct Backoff {
+    policy: Box<dyn BackoffPolicy>,
+
+    /// The number of consecutive failures.
+    failures: u64,
+
+    /// The delay returned by the last `on_failure()`.
+    delay: Duration,
+}
+
+impl Backoff {
+    pub(crate) fn new(policy: Box<dyn BackoffPolicy>) -> Self {
+        Self {
+            policy,
+            failures: 0,
+            delay: Duration::default(),
+        }
+    }
+
+    /// Record a failed RPC and return the delay before the next retry.
+    pub(crate) fn on_failure(&mut self) -> Duration {
+        self.failures += 1;
+        self.delay = self.policy.delay(self.failures);
+        self.delay
+    }
+
+    /// Record a successful RPC. It returns `true` if it was backing off.
+    pub(crate) fn on_success(&mut self) -> bool {
+        let was_backing_off = self.failures > 0;
+        self.failures = 0;
+        self.delay = Duration::default();
+        was_backing_off
+    }
+
+    /// The backoff state to report in metrics. It is `None` if the last RPC succeeded.
+    pub(crate) fn metrics(&self) -> Option<ReplicationBackoff> {
+        if self.failures == 0 {
+            return None;
+        }
+
+        Some(ReplicationBackoff {
+            failures: self.failures,
+            delay: self.delay,
+        })
+    }
+}
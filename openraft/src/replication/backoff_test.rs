@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use crate::metrics::ReplicationBackoff;
+use crate::network::BackoffPolicy;
+use crate::network::ExponentialBackoff;
+use crate::replication::backoff::Backoff;
+
+fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+}
+
+fn exponential(base: Duration, max: Duration) -> Backoff {
+    Backoff::new(Box::new(ExponentialBackoff::new(base, max)))
+}
+
+#[test]
+fn test_backoff_grows_up_to_max() -> anyhow::Result<()> {
+    let mut b = exponential(ms(50), ms(500));
+    assert_eq!(None, b.metrics());
+
+    // 50
+    assert_eq!(ms(50), b.on_failure());
+
+    // 100, 200, 400, with jitter in the upper half.
+    for want in [100, 200, 400] {
+        let d = b.on_failure();
+        assert!(ms(want / 2) <= d && d <= ms(want), "want: {}ms, got: {:?}", want, d);
+    }
+
+    // capped by max
+    for _ in 0..100 {
+        let d = b.on_failure();
+        assert!(ms(250) <= d && d <= ms(500), "got: {:?}", d);
+    }
+
+    let m = b.metrics().unwrap();
+    assert_eq!(104, m.failures);
+
+    Ok(())
+}
+
+#[test]
+fn test_backoff_reset_on_success() -> anyhow::Result<()> {
+    let mut b = exponential(ms(50), ms(500));
+
+    assert_eq!(false, b.on_success());
+
+    let d = b.on_failure();
+    assert_eq!(Some(ReplicationBackoff { failures: 1, delay: d }), b.metrics());
+
+    assert_eq!(true, b.on_success());
+    assert_eq!(None, b.metrics());
+
+    assert_eq!(ms(50), b.on_failure(), "starts over from base");
+
+    Ok(())
+}
+
+#[test]
+fn test_backoff_disabled_if_max_le_base() -> anyhow::Result<()> {
+    let mut b = exponential(ms(50), ms(0));
+
+    for _ in 0..10 {
+        assert_eq!(ms(50), b.on_failure());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_backoff_custom_policy() -> anyhow::Result<()> {
+    /// Wait 10ms more after every failure.
+    struct Linear;
+
+    impl BackoffPolicy for Linear {
+        fn delay(&self, failures: u64) -> Duration {
+            ms(10 * failures)
+        }
+    }
+
+    let mut b = Backoff::new(Box::new(Linear));
+
+    assert_eq!(ms(10), b.on_failure());
+    assert_eq!(ms(20), b.on_failure());
+    assert_eq!(
+        Some(ReplicationBackoff {
+            failures: 2,
+            delay: ms(20)
+        }),
+        b.metrics()
+    );
+
+    b.on_success();
+    assert_eq!(ms(10), b.on_failure());
+
+    Ok(())
+}
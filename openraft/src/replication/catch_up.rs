@@ -0,0 +1,17 @@
+use crate::raft_types::LogIndexOptionExt;
+
+/// Whether a target should be sent a snapshot instead of catching up with logs, because it may lack at least
+/// `gap` logs that the leader has already purged.
+///
+/// `matched` is the greatest log index known to be on the target, `last_purged` is the index of the last log purged by
+/// the leader. A `gap` of `0` disables it: the target is then sent a snapshot only when a log it needs is found to be
+/// purged.
+pub(crate) fn prefer_snapshot(matched: Option<u64>, last_purged: Option<u64>, gap: u64) -> bool {
+    if gap == 0 {
+        return false;
+    }
+
+    // The logs in `[matched + 1, last_purged]` are needed by the target but can not be sent.
+    let lacking = last_purged.next_index().saturating_sub(matched.next_index());
+    lacking >= gap
+}
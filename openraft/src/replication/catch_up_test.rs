@@ -0,0 +1,34 @@
+use crate::replication::catch_up::prefer_snapshot;
+
+#[test]
+fn test_prefer_snapshot_disabled() -> anyhow::Result<()> {
+    assert!(!prefer_snapshot(None, Some(100), 0));
+    assert!(!prefer_snapshot(Some(3), Some(100), 0));
+    Ok(())
+}
+
+#[test]
+fn test_prefer_snapshot_nothing_purged() -> anyhow::Result<()> {
+    assert!(!prefer_snapshot(None, None, 1));
+    assert!(!prefer_snapshot(Some(3), None, 1));
+    Ok(())
+}
+
+#[test]
+fn test_prefer_snapshot_boundary() -> anyhow::Result<()> {
+    // The next log the target needs, 6, is not purged.
+    assert!(!prefer_snapshot(Some(5), Some(5), 1));
+    assert!(!prefer_snapshot(Some(6), Some(5), 1));
+
+    // Log 5 is needed but purged.
+    assert!(prefer_snapshot(Some(4), Some(5), 1));
+
+    // Logs 3, 4, 5 are needed but purged.
+    assert!(!prefer_snapshot(Some(2), Some(5), 4));
+    assert!(prefer_snapshot(Some(2), Some(5), 3));
+
+    // An empty target lacks logs 0 through 5.
+    assert!(!prefer_snapshot(None, Some(5), 7));
+    assert!(prefer_snapshot(None, Some(5), 6));
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use crate::metrics::LogCacheMetrics;
+use crate::Entry;
+use crate::LogId;
+use crate::RaftTypeConfig;
+
+/// The recent logs appended by a leader, shared by every replication stream of it.
+///
+/// Streams read the cached entries as `Arc`s, thus the payload of a log is not copied for every target it is sent
+/// to. The cached logs are always consecutive: a log is evicted once every target has replicated it, or the oldest
+/// logs are evicted when the cache holds more than `max_entries` logs or `max_bytes` bytes.
+pub(crate) struct LogCache<C: RaftTypeConfig> {
+    inner: Mutex<Inner<C>>,
+
+    /// The number of reads served by the cache.
+    hits: AtomicU64,
+
+    /// The number of reads that are not served by the cache.
+    misses: AtomicU64,
+}
+
+struct Inner<C: RaftTypeConfig> {
+    entries: BTreeMap<u64, Arc<Entry<C>>>,
+
+    /// The total size of the cached entries, in bytes.
+    size: u64,
+
+    max_entries: u64,
+
+    max_bytes: u64,
+}
+
+impl<C: RaftTypeConfig> LogCache<C> {
+    pub(crate) fn new(max_entries: u64, max_bytes: u64) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                entries: BTreeMap::new(),
+                size: 0,
+                max_entries,
+                max_bytes,
+            }),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Add the logs a leader just appended.
+    ///
+    /// The cached logs at or after the first appended one are replaced, to keep the cached logs consecutive.
+    pub(crate) fn append(&self, entries: impl IntoIterator<Item = Arc<Entry<C>>>) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.max_entries == 0 || inner.max_bytes == 0 {
+            return;
+        }
+
+        for ent in entries {
+            let index = ent.log_id.index;
+
+            if inner.entries.range(index..).next().is_some() {
+                let removed = inner.entries.split_off(&index);
+                inner.size -= removed.values().map(|e| entry_size(e)).sum::<u64>();
+            }
+
+            inner.size += entry_size(&ent);
+            inner.entries.insert(index, ent);
+        }
+
+        while inner.size > inner.max_bytes || inner.entries.len() as u64 > inner.max_entries {
+            let first = match inner.entries.keys().next().copied() {
+                Some(x) => x,
+                None => break,
+            };
+            let ent = inner.entries.remove(&first).unwrap();
+            inner.size -= entry_size(&ent);
+        }
+    }
+
+    /// Get the logs in `range`, or `None` if any of them is not cached.
+    ///
+    /// Every call is counted as a hit or a miss.
+    pub(crate) fn get_range(&self, range: Range<u64>) -> Option<Vec<Arc<Entry<C>>>> {
+        let inner = self.inner.lock().unwrap();
+
+        let want = (range.end - range.start) as usize;
+        let entries = inner.entries.range(range).map(|(_, ent)| ent.clone()).collect::<Vec<_>>();
+
+        if entries.len() == want {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            Some(entries)
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            None
+        }
+    }
+
+    /// Get the log id at `index`, if it is cached.
+    pub(crate) fn get_log_id(&self, index: u64) -> Option<LogId<C::NodeId>> {
+        let inner = self.inner.lock().unwrap();
+        inner.entries.get(&index).map(|ent| ent.log_id.clone())
+    }
+
+    /// Evict logs upto `index`, inclusive.
+    pub(crate) fn evict_upto(&self, index: u64) {
+        let mut inner = self.inner.lock().unwrap();
+
+        let kept = inner.entries.split_off(&(index + 1));
+        let evicted = std::mem::replace(&mut inner.entries, kept);
+        inner.size -= evicted.values().map(|e| entry_size(e)).sum::<u64>();
+    }
+
+    /// Evict logs since `index`, inclusive, e.g., when they are deleted from the log storage.
+    pub(crate) fn truncate(&self, index: u64) {
+        let mut inner = self.inner.lock().unwrap();
+
+        let removed = inner.entries.split_off(&index);
+        inner.size -= removed.values().map(|e| entry_size(e)).sum::<u64>();
+    }
+
+    /// Evict every log.
+    pub(crate) fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.entries.clear();
+        inner.size = 0;
+    }
+
+    pub(crate) fn metrics(&self) -> LogCacheMetrics {
+        LogCacheMetrics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// The number of cached logs and their total size in bytes.
+    #[allow(dead_code)]
+    pub(crate) fn stat(&self) -> (usize, u64) {
+        let inner = self.inner.lock().unwrap();
+        (inner.entries.len(), inner.size)
+    }
+}
+
+/// The size of an entry counted against `max_bytes`.
+fn entry_size<C: RaftTypeConfig>(ent: &Entry<C>) -> u64 {
+    C::entry_size(ent)
+}
@@ -0,0 +1,132 @@
+use std::sync::Arc;
+
+use crate::metrics::LogCacheMetrics;
+use crate::replication::log_cache::LogCache;
+use crate::Entry;
+use crate::EntryPayload;
+use crate::LeaderId;
+use crate::LogId;
+
+crate::declare_raft_types!(
+   pub(crate) Config: D = u64, R = (), NodeId = u64
+);
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::new(LeaderId::new(term, 0), index)
+}
+
+fn ent(term: u64, index: u64) -> Arc<Entry<Config>> {
+    Arc::new(Entry {
+        log_id: log_id(term, index),
+        payload: EntryPayload::Normal(index),
+        context: None,
+    })
+}
+
+fn ent_size() -> u64 {
+    (std::mem::size_of::<Entry<Config>>() + std::mem::size_of::<u64>()) as u64
+}
+
+fn indexes(entries: Option<Vec<Arc<Entry<Config>>>>) -> Option<Vec<u64>> {
+    entries.map(|x| x.iter().map(|e| e.log_id.index).collect())
+}
+
+#[test]
+fn test_log_cache_get_range() -> anyhow::Result<()> {
+    let c = LogCache::<Config>::new(1000, 1024 * 1024);
+    c.append((3..6).map(|i| ent(1, i)));
+
+    assert_eq!(Some(vec![3, 4, 5]), indexes(c.get_range(3..6)));
+    assert_eq!(Some(vec![4]), indexes(c.get_range(4..5)));
+
+    // Not every log in the range is cached.
+    assert_eq!(None, indexes(c.get_range(2..5)));
+    assert_eq!(None, indexes(c.get_range(4..7)));
+
+    assert_eq!(Some(log_id(1, 3)), c.get_log_id(3));
+    assert_eq!(None, c.get_log_id(2));
+
+    // The entries are shared, not copied.
+    let a = c.get_range(3..4).unwrap();
+    let b = c.get_range(3..4).unwrap();
+    assert!(Arc::ptr_eq(&a[0], &b[0]));
+
+    assert_eq!(LogCacheMetrics { hits: 4, misses: 2 }, c.metrics());
+
+    Ok(())
+}
+
+#[test]
+fn test_log_cache_append_replaces_conflicting() -> anyhow::Result<()> {
+    let c = LogCache::<Config>::new(1000, 1024 * 1024);
+    c.append((3..6).map(|i| ent(1, i)));
+    c.append(vec![ent(2, 4)]);
+
+    assert_eq!(Some(log_id(2, 4)), c.get_log_id(4));
+    assert_eq!(None, c.get_log_id(5));
+    assert_eq!((2, ent_size() * 2), c.stat());
+
+    Ok(())
+}
+
+#[test]
+fn test_log_cache_evict_upto() -> anyhow::Result<()> {
+    let c = LogCache::<Config>::new(1000, 1024 * 1024);
+    c.append((3..6).map(|i| ent(1, i)));
+
+    c.evict_upto(1);
+    assert_eq!((3, ent_size() * 3), c.stat());
+
+    c.evict_upto(4);
+    assert_eq!((1, ent_size()), c.stat());
+    assert_eq!(Some(vec![5]), indexes(c.get_range(5..6)));
+
+    c.evict_upto(10);
+    assert_eq!((0, 0), c.stat());
+
+    Ok(())
+}
+
+#[test]
+fn test_log_cache_truncate() -> anyhow::Result<()> {
+    let c = LogCache::<Config>::new(1000, 1024 * 1024);
+    c.append((3..6).map(|i| ent(1, i)));
+
+    c.truncate(4);
+    assert_eq!((1, ent_size()), c.stat());
+    assert_eq!(None, indexes(c.get_range(3..5)));
+
+    c.append((3..6).map(|i| ent(1, i)));
+    c.clear();
+    assert_eq!((0, 0), c.stat());
+
+    Ok(())
+}
+
+#[test]
+fn test_log_cache_capacity() -> anyhow::Result<()> {
+    // The oldest logs are evicted when the cache holds too many bytes.
+    let c = LogCache::<Config>::new(1000, ent_size() * 2);
+    c.append((3..6).map(|i| ent(1, i)));
+
+    assert_eq!((2, ent_size() * 2), c.stat());
+    assert_eq!(None, c.get_log_id(3));
+    assert_eq!(Some(vec![4, 5]), indexes(c.get_range(4..6)));
+
+    // Or too many logs.
+    let c = LogCache::<Config>::new(1, 1024 * 1024);
+    c.append((3..6).map(|i| ent(1, i)));
+
+    assert_eq!((1, ent_size()), c.stat());
+    assert_eq!(Some(vec![5]), indexes(c.get_range(5..6)));
+
+    // A cache with capacity 0 is disabled.
+    for (max_entries, max_bytes) in [(0, 1024 * 1024), (1000, 0)] {
+        let c = LogCache::<Config>::new(max_entries, max_bytes);
+        c.append((3..6).map(|i| ent(1, i)));
+
+        assert_eq!((0, 0), c.stat());
+    }
+
+    Ok(())
+}
@@ -1,66 +1,95 @@
 //! Replication stream.
 
-use std::io::SeekFrom;
+pub(crate) mod backoff;
+mod catch_up;
+pub(crate) mod log_cache;
+pub(crate) mod throttle;
+
+#[cfg(test)] mod backoff_test;
+#[cfg(test)] mod catch_up_test;
+#[cfg(test)] mod log_cache_test;
+#[cfg(test)] mod throttle_test;
+
+use std::collections::VecDeque;
+use std::ops::Range;
 use std::sync::Arc;
+use std::time::Duration;
 
+use futures::future::BoxFuture;
 use futures::future::FutureExt;
-use tokio::io::AsyncReadExt;
-use tokio::io::AsyncSeekExt;
-use tokio::sync::mpsc;
+use futures::stream::FuturesOrdered;
+use futures::StreamExt;
 use tokio::sync::oneshot;
-use tokio::task::JoinHandle;
-use tokio::time::interval;
-use tokio::time::timeout;
-use tokio::time::Duration;
-use tokio::time::Interval;
 use tracing_futures::Instrument;
 
+use crate::async_runtime::mpsc;
+use crate::async_runtime::Instant;
+use crate::async_runtime::InstantOf;
+use crate::async_runtime::JoinHandle;
 use crate::config::Config;
-use crate::config::SnapshotPolicy;
+use crate::config::SnapshotSource;
 use crate::error::AppendEntriesError;
 use crate::error::CommittedAdvanceTooMany;
 use crate::error::HigherVote;
 use crate::error::LackEntry;
+use crate::error::NetworkError;
+use crate::error::PayloadTooLarge;
 use crate::error::RPCError;
 use crate::error::ReplicationError;
+use crate::error::StreamingError;
 use crate::error::Timeout;
+use crate::metrics::ReplicationProgress;
+use crate::metrics::SnapshotTransmission;
+use crate::network::is_connection_error;
+use crate::network::RPCOption;
+use crate::network::SnapshotStreaming;
 use crate::raft::AppendEntriesRequest;
 use crate::raft::AppendEntriesResponse;
-use crate::raft::InstallSnapshotRequest;
 use crate::raft::RaftMsg;
+use crate::raft::SnapshotFromRequest;
 use crate::raft_types::LogIdOptionExt;
 use crate::raft_types::LogIndexOptionExt;
+use crate::replication::backoff::Backoff;
+use crate::replication::catch_up;
+use crate::replication::log_cache::LogCache;
 use crate::storage::RaftLogReader;
 use crate::storage::Snapshot;
-use crate::ErrorSubject;
-use crate::ErrorVerb;
+use crate::AsyncRuntime;
+use crate::Entry;
+use crate::EntryPayload;
 use crate::LogId;
 use crate::MessageSummary;
-use crate::Node;
 use crate::NodeId;
+use crate::NodeInfo;
 use crate::RPCTypes;
+use crate::RaftLogStorage;
 use crate::RaftNetwork;
 use crate::RaftNetworkFactory;
-use crate::RaftStorage;
+use crate::RaftStateMachine;
 use crate::RaftTypeConfig;
-use crate::ToStorageResult;
+use crate::SnapshotMeta;
 use crate::Vote;
 
 /// The handle to a spawned replication stream.
-pub(crate) struct ReplicationStream<NID: NodeId> {
+pub(crate) struct ReplicationStream<C: RaftTypeConfig> {
     /// The spawn handle the `ReplicationCore` task.
-    pub handle: JoinHandle<()>,
+    pub handle: JoinHandle<(), <C::AsyncRuntime as AsyncRuntime>::JoinError>,
 
     /// The channel used for communicating with the replication task.
-    pub repl_tx: mpsc::UnboundedSender<UpdateReplication<NID>>,
+    pub repl_tx: mpsc::UnboundedSender<UpdateReplication<C::NodeId>>,
 }
 
 /// A task responsible for sending replication events to a target follower in the Raft cluster.
 ///
-/// NOTE: we do not stack replication requests to targets because this could result in
-/// out-of-order delivery. We always buffer until we receive a success response, then send the
-/// next payload from the buffer.
-pub(crate) struct ReplicationCore<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> {
+/// By default a replication request is sent only after the previous one is acknowledged. With
+/// [`Config::max_inflight_appends`] greater than 1, requests are pipelined once the target matches the leader's
+/// logs, and a request that is delivered out of order is rejected and sent again.
+pub(crate) struct ReplicationCore<
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+> {
     /// The ID of the target Raft node which replication events are to be sent to.
     target: C::NodeId,
 
@@ -69,16 +98,32 @@ pub(crate) struct ReplicationCore<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S
 
     /// A channel for sending events to the Raft node.
     #[allow(clippy::type_complexity)]
-    raft_core_tx: mpsc::UnboundedSender<RaftMsg<C, N, S>>,
+    raft_core_tx: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
 
     /// A channel for receiving events from the Raft node.
     repl_rx: mpsc::UnboundedReceiver<UpdateReplication<C::NodeId>>,
 
-    /// The `RaftNetwork` interface.
-    network: N::Network,
+    /// The `RaftNetwork` connections to the target, one for every AppendEntries that may be outstanding.
+    ///
+    /// The connections used by pipelined requests are taken out, and are put back when the responses are received.
+    /// Other RPCs are sent with the first one.
+    networks: Vec<N::Network>,
+
+    /// The `RaftLogReader` of a `RaftLogStorage` interface.
+    log_reader: LS::LogReader,
+
+    /// The recent logs of the leader, shared with the other replication streams.
+    ///
+    /// Logs are read from `log_reader` only if they are not cached.
+    log_cache: Arc<LogCache<C>>,
+
+    /// The consecutive logs read from `log_reader` ahead of those sent, upto `Config::replication_prefetch_entries`.
+    ///
+    /// It keeps the last sent log too, which is the `prev_log_id` of the next request.
+    prefetched: VecDeque<Arc<Entry<C>>>,
 
-    /// The `RaftLogReader` of a `RaftStorage` interface.
-    log_reader: S::LogReader,
+    /// Whether the target is a witness, which is sent only the log ids and the membership logs.
+    witness: bool,
 
     /// The Raft's runtime config.
     config: Arc<Config>,
@@ -104,42 +149,74 @@ pub(crate) struct ReplicationCore<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S
     max_possible_matched_index: Option<u64>,
 
     /// The heartbeat interval for ensuring that heartbeats are always delivered in a timely fashion.
-    heartbeat: Interval,
+    heartbeat_interval: Duration,
+
+    /// When to send the next heartbeat.
+    next_heartbeat: InstantOf<C>,
+
+    /// The backoff for retrying when the target can not be reached.
+    backoff: Backoff,
+
+    /// When to retry if it is backing off.
+    retry_at: InstantOf<C>,
+
+    /// The max number of entries and bytes in an AppendEntries, lowered from `max_payload_entries` and
+    /// `max_payload_bytes` after the target rejects a payload as too large.
+    payload_limit: Option<PayloadLimit>,
 
     /// The timeout for sending snapshot segment.
     install_snapshot_timeout: Duration,
 
     /// if or not need to replicate log entries or states, e.g., `commit_index` etc.
     need_to_replicate: bool,
+
+    /// The target rejected a probe, and the matching log is not found yet.
+    ///
+    /// A probe carries no logs in this state.
+    probing: bool,
+
+    /// When the latest response from the target is received.
+    last_acked: Option<InstantOf<C>>,
+
+    /// The latest progress reported to RaftCore, and when it is reported.
+    reported_progress: Option<(ReplicationProgress<C::NodeId, InstantOf<C>>, InstantOf<C>)>,
 }
 
-impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> ReplicationCore<C, N, S> {
+impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>>
+    ReplicationCore<C, N, LS, SM>
+{
     /// Spawn a new replication task for the target node.
-    #[tracing::instrument(level = "trace", skip(config, network, log_reader, raft_core_tx))]
+    #[tracing::instrument(level = "trace", skip(config, networks, log_reader, log_cache, raft_core_tx))]
     #[allow(clippy::type_complexity)]
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn spawn(
         target: C::NodeId,
-        target_node: Option<Node>,
+        target_node: Option<C::Node>,
+        witness: bool,
         vote: Vote<C::NodeId>,
         config: Arc<Config>,
         last_log: Option<LogId<C::NodeId>>,
         committed: Option<LogId<C::NodeId>>,
-        network: N::Network,
-        log_reader: S::LogReader,
-        raft_core_tx: mpsc::UnboundedSender<RaftMsg<C, N, S>>,
+        networks: Vec<N::Network>,
+        log_reader: LS::LogReader,
+        log_cache: Arc<LogCache<C>>,
+        raft_core_tx: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
         span: tracing::Span,
-    ) -> ReplicationStream<C::NodeId> {
+    ) -> ReplicationStream<C> {
         // other component to ReplicationStream
-        let (repl_tx, repl_rx) = mpsc::unbounded_channel();
+        let (repl_tx, repl_rx) = C::AsyncRuntime::mpsc_unbounded();
         let heartbeat_timeout = Duration::from_millis(config.heartbeat_interval);
         let install_snapshot_timeout = Duration::from_millis(config.install_snapshot_timeout);
+        let backoff = Backoff::new(networks[0].backoff(&config));
 
         let this = Self {
             target,
             vote,
-            network,
+            networks,
             log_reader,
+            log_cache,
+            prefetched: VecDeque::new(),
+            witness,
             config,
             target_repl_state: TargetReplState::LineRate,
             committed,
@@ -147,24 +224,31 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
             max_possible_matched_index: last_log.index(),
             raft_core_tx,
             repl_rx,
-            heartbeat: interval(heartbeat_timeout),
+            heartbeat_interval: heartbeat_timeout,
+            next_heartbeat: C::AsyncRuntime::now(),
+            backoff,
+            retry_at: C::AsyncRuntime::now(),
+            payload_limit: None,
             install_snapshot_timeout,
             need_to_replicate: true,
+            probing: false,
+            last_acked: None,
+            reported_progress: None,
         };
 
-        let handle = tokio::spawn(this.main().instrument(span));
+        let handle = C::AsyncRuntime::spawn(this.main().instrument(span));
 
         ReplicationStream { handle, repl_tx }
     }
 
-    #[tracing::instrument(level="debug", skip(self), fields(vote=%self.vote, target=display(self.target), cluster=%self.config.cluster_name))]
+    #[tracing::instrument(level="debug", skip(self), fields(vote=%self.vote, target=display(&self.target), cluster=%self.config.cluster_name))]
     async fn main(mut self) {
         loop {
             // If it returns Ok(), always go back to LineRate state.
             let res = match &self.target_repl_state {
                 TargetReplState::LineRate => self.line_rate_loop().await,
                 TargetReplState::Snapshotting { must_include } => {
-                    let must = *must_include;
+                    let must = must_include.clone();
                     self.replicate_snapshot(must).await
                 }
                 TargetReplState::Shutdown => return,
@@ -186,9 +270,9 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
                 }
                 ReplicationError::HigherVote(h) => {
                     let _ = self.raft_core_tx.send(RaftMsg::RevertToFollower {
-                        target: self.target,
+                        target: self.target.clone(),
                         new_vote: h.higher,
-                        vote: self.vote,
+                        vote: self.vote.clone(),
                     });
                     return;
                 }
@@ -214,6 +298,9 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
                 ReplicationError::Network { .. } => {
                     // nothing to do
                 }
+                ReplicationError::Unreachable { .. } => {
+                    // nothing to do
+                }
                 ReplicationError::RemoteError(remote_err) => {
                     tracing::error!(%remote_err, "remote peer error");
                     match remote_err.source {
@@ -221,6 +308,10 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
                             tracing::error!(%fatal, target=%remote_err.target, "remote fatal error, close replication");
                             return;
                         }
+                        AppendEntriesError::Compression(e) => {
+                            // The target can not decompress: it is likely built without feature `compression`.
+                            tracing::error!(error=%e, target=%remote_err.target, "remote failed to decompress entries");
+                        }
                     }
                 }
             };
@@ -231,22 +322,141 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
     ///
     /// This request will timeout if no response is received within the
     /// configured heartbeat interval.
-    #[tracing::instrument(level = "debug", skip(self))]
-    async fn send_append_entries(&mut self) -> Result<(), ReplicationError<C::NodeId>> {
+    #[tracing::instrument(
+        name = "raft.replicate",
+        level = "debug",
+        skip(self),
+        fields(
+            node_id = display(&self.vote.node_id),
+            target = display(&self.target),
+            term = self.vote.term,
+            log_index = tracing::field::Empty
+        )
+    )]
+    async fn send_append_entries(&mut self) -> Result<(), ReplicationError<C::NodeId, C::Node>> {
         // find the mid position aligning to 8
         let diff = self.max_possible_matched_index.next_index() - self.matched.next_index();
         let offset = diff / 16 * 8;
 
-        let mut prev_index = self.matched.index().add(offset);
+        let prev_index = self.matched.index().add(offset);
+
+        let (payload, last_purged) = self.load_append_entries(prev_index).await?;
+        let (payload, sent) = self.new_inflight_append(payload, last_purged, false);
+        tracing::Span::current().record("log_index", &sent.matched.index());
+
+        let option = RPCOption::new(sent.timeout);
+        let res = C::AsyncRuntime::timeout(sent.timeout, self.networks[0].append_entries(payload, option)).await;
 
-        let (prev_log_id, logs, has_more_logs) = loop {
+        if let Ok(Err(err)) = &res {
+            if is_connection_error(err) {
+                self.networks[0].on_connection_error();
+            }
+        }
+
+        self.handle_append_entries_result(sent, res)
+    }
+
+    /// Send AppendEntries RPCs to the target without waiting for the previous one to be acknowledged, with at most
+    /// `max_inflight_appends` of them outstanding.
+    ///
+    /// Every request starts from the last log of the previous one, and responses are handled in the order the
+    /// requests are sent. If a request fails or is rejected, the outstanding requests are discarded, and the next
+    /// call starts over from the confirmed `matched`.
+    async fn send_append_entries_pipelined(&mut self) -> Result<(), ReplicationError<C::NodeId, C::Node>> {
+        let mut inflight = FuturesOrdered::new();
+
+        let res = self.run_pipeline(&mut inflight).await;
+
+        // Wait for the outstanding requests to take back their connections. Their responses are discarded.
+        while let Some((network, _sent, _res)) = inflight.next().await {
+            self.networks.push(network);
+        }
+
+        res
+    }
+
+    #[allow(clippy::type_complexity)]
+    async fn run_pipeline(
+        &mut self,
+        inflight: &mut FuturesOrdered<
+            BoxFuture<
+                'static,
+                (
+                    N::Network,
+                    InflightAppend<C::NodeId, InstantOf<C>>,
+                    AppendEntriesResult<C>,
+                ),
+            >,
+        >,
+    ) -> Result<(), ReplicationError<C::NodeId, C::Node>> {
+        let mut prev_index = self.matched.index();
+        let mut has_more_logs = true;
+
+        loop {
+            while has_more_logs && !self.networks.is_empty() {
+                let (payload, last_purged) = self.load_append_entries(prev_index).await?;
+                has_more_logs = self.need_to_replicate;
+
+                let speculative = payload.prev_log_id.index() > self.matched.index();
+                let (payload, sent) = self.new_inflight_append(payload, last_purged, speculative);
+                prev_index = sent.matched.index();
+
+                tracing::debug!(
+                    payload = display(payload.summary()),
+                    inflight = inflight.len() + 1,
+                    "pipeline AppendEntries"
+                );
+
+                let mut network = self.networks.pop().unwrap();
+                inflight.push_back(
+                    async move {
+                        let option = RPCOption::new(sent.timeout);
+                        let res = C::AsyncRuntime::timeout(sent.timeout, network.append_entries(payload, option)).await;
+                        (network, sent, res)
+                    }
+                    .boxed(),
+                );
+            }
+
+            let (mut network, sent, res) = match inflight.next().await {
+                Some(x) => x,
+                None => return Ok(()),
+            };
+            if let Ok(Err(err)) = &res {
+                if is_connection_error(err) {
+                    network.on_connection_error();
+                }
+            }
+            self.networks.push(network);
+
+            let expected = sent.matched.clone();
+            self.handle_append_entries_result(sent, res)?;
+            self.report_progress();
+
+            if self.probing || self.matched < expected {
+                // The request is rejected: the logs sent after it are discarded and have to be sent again.
+                self.need_to_replicate = true;
+                return Ok(());
+            }
+        }
+    }
+
+    /// Load the logs after `prev_index` for an AppendEntries request, and set `need_to_replicate` if there are more
+    /// logs to send.
+    ///
+    /// It returns the request and the last purged log id.
+    async fn load_append_entries(
+        &mut self,
+        mut prev_index: Option<u64>,
+    ) -> Result<(AppendEntriesRequest<C>, Option<LogId<C::NodeId>>), ReplicationError<C::NodeId, C::Node>> {
+        let (prev_log_id, logs, has_more_logs, last_purged) = loop {
             // TODO(xp): test heartbeat when all logs are removed.
 
             let log_state = self.log_reader.get_log_state().await?;
 
             let last_purged = log_state.last_purged_log_id;
 
-            self.check_consecutive(last_purged)?;
+            self.check_consecutive(last_purged.clone())?;
 
             if prev_index < last_purged.index() {
                 prev_index = last_purged.index();
@@ -254,7 +464,13 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
 
             let last_log_index = log_state.last_log_id.next_index();
             let start = prev_index.next_index();
-            let end = std::cmp::min(start + self.config.max_payload_entries, last_log_index);
+            let end = if self.probing && prev_index != self.matched.index() {
+                // Do not send logs the target may not be able to accept, until the matching log is found.
+                start
+            } else {
+                let (max_entries, _) = self.payload_limit();
+                std::cmp::min(start + max_entries, last_log_index)
+            };
 
             tracing::debug!(
                 ?self.matched,
@@ -268,24 +484,34 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
             assert!(end >= prev_index.next_index());
 
             let prev_log_id = if prev_index == last_purged.index() {
-                last_purged
+                last_purged.clone()
             } else if let Some(prev_i) = prev_index {
-                let first = self.log_reader.try_get_log_entry(prev_i).await?;
-                match first {
-                    Some(f) => Some(f.log_id),
-                    None => {
-                        tracing::info!("can not load first entry: at {:?}, retry loading logs", prev_index);
-                        continue;
-                    }
+                let cached = self.log_cache.get_log_id(prev_i).or_else(|| self.prefetched_log_id(prev_i));
+                let first = match cached {
+                    Some(log_id) => Some(log_id),
+                    None => self.log_reader.try_get_log_entry(prev_i).await?.map(|ent| ent.log_id),
+                };
+                if first.is_none() {
+                    tracing::info!("can not load first entry: at {:?}, retry loading logs", prev_index);
+                    continue;
                 }
+                first
             } else {
                 None
             };
 
             let logs = if start == end {
                 vec![]
+            } else if let Some(logs) = self.log_cache.get_range(start..end) {
+                logs
+            } else if let Some(logs) = self.get_prefetched(start..end) {
+                logs
             } else {
-                let logs = self.log_reader.try_get_log_entries(start..end).await?;
+                // Read ahead the logs for the following requests, at most `replication_prefetch_entries`.
+                let read_end = std::cmp::min(start + self.config.replication_prefetch_entries, last_log_index);
+                let read_end = std::cmp::max(read_end, end);
+
+                let logs = self.log_reader.try_get_log_entries(start..read_end).await?;
                 if !logs.is_empty() && logs[0].log_id.index > prev_log_id.next_index() {
                     // There is still chance the first log is removed.
                     // log entry is just deleted after fetching first_log_id.
@@ -293,38 +519,103 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
                     continue;
                 }
 
+                let mut logs = logs.into_iter().map(Arc::new).collect::<Vec<_>>();
+                if read_end > end {
+                    self.prefetched = logs.iter().cloned().collect();
+                    logs.truncate((end - start) as usize);
+                }
                 logs
             };
 
-            break (prev_log_id, logs, end < last_log_index);
+            let logs = if self.witness { strip_payloads(logs) } else { logs };
+
+            // Send fewer logs if they exceed the bytes limit.
+            let (_, max_bytes) = self.payload_limit();
+            let mut logs = logs;
+            let n = entries_within_bytes(&logs, max_bytes);
+            let end = if n < logs.len() {
+                logs.truncate(n);
+                start + n as u64
+            } else {
+                end
+            };
+
+            break (prev_log_id, logs, end < last_log_index, last_purged);
         };
 
         // set the need_to_replicate flag if there is more
         self.need_to_replicate = has_more_logs;
-        let conflict = prev_log_id;
-        let matched = if logs.is_empty() {
-            prev_log_id
-        } else {
-            Some(logs[logs.len() - 1].log_id)
-        };
 
         // Build the heartbeat frame to be sent to the follower.
         let payload = AppendEntriesRequest {
-            vote: self.vote,
+            vote: self.vote.clone(),
             prev_log_id,
-            leader_commit: self.committed,
+            leader_commit: self.committed.clone(),
             entries: logs,
+            compressed_entries: None,
+            leader_transfer_to: None,
+        };
+
+        Ok((payload, last_purged))
+    }
+
+    /// Compress the payload and record what a response to it means, right before it is sent.
+    ///
+    /// A request is `speculative` if it is pipelined before the target confirms its `prev_log_id`.
+    fn new_inflight_append(
+        &self,
+        payload: AppendEntriesRequest<C>,
+        last_purged: Option<LogId<C::NodeId>>,
+        speculative: bool,
+    ) -> (AppendEntriesRequest<C>, InflightAppend<C::NodeId, InstantOf<C>>) {
+        let conflict = payload.prev_log_id.clone();
+        let matched = if payload.entries.is_empty() {
+            payload.prev_log_id.clone()
+        } else {
+            Some(payload.entries[payload.entries.len() - 1].log_id.clone())
         };
 
-        // Send the payload.
+        let the_timeout = self.append_entries_timeout(&payload);
+        let entries = payload.entries.len() as u64;
+        let bytes = payload.entries.iter().map(|ent| C::entry_size(ent)).sum();
+        let payload = self.compress_entries(payload);
+
         tracing::debug!(
             payload=%payload.summary(),
             "start sending append_entries, timeout: {:?}",
-            self.config.heartbeat_interval
+            the_timeout
         );
 
-        let the_timeout = Duration::from_millis(self.config.heartbeat_interval);
-        let res = timeout(the_timeout, self.network.send_append_entries(payload)).await;
+        let sent = InflightAppend {
+            conflict,
+            matched,
+            entries,
+            bytes,
+            last_purged,
+            speculative,
+            timeout: the_timeout,
+            sending_time: C::AsyncRuntime::now(),
+        };
+
+        (payload, sent)
+    }
+
+    /// Handle the result of an AppendEntries RPC.
+    fn handle_append_entries_result(
+        &mut self,
+        sent: InflightAppend<C::NodeId, InstantOf<C>>,
+        res: AppendEntriesResult<C>,
+    ) -> Result<(), ReplicationError<C::NodeId, C::Node>> {
+        let InflightAppend {
+            conflict,
+            matched,
+            entries,
+            bytes,
+            last_purged,
+            speculative,
+            timeout: the_timeout,
+            sending_time,
+        } = sent;
 
         let append_resp = match res {
             Ok(append_res) => match append_res {
@@ -333,22 +624,42 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
                     tracing::warn!(error=%err, "error sending AppendEntries RPC to target");
                     let repl_err = match err {
                         RPCError::NodeNotFound(e) => ReplicationError::NodeNotFound(e),
+                        RPCError::PayloadTooLarge(e) if entries > 1 => {
+                            self.shrink_payload_limit(entries, bytes, &e);
+                            // Send the same logs again in smaller requests.
+                            self.need_to_replicate = true;
+                            return Ok(());
+                        }
+                        RPCError::PayloadTooLarge(e) => {
+                            // A single log can not be split. Retry it later as if the network failed.
+                            ReplicationError::Network(NetworkError::new(&e))
+                        }
+                        // A timeout is not an acknowledgement, thus it is counted toward check-quorum, while the
+                        // stream keeps replicating.
                         RPCError::Timeout(e) => {
                             let _ = self.raft_core_tx.send(RaftMsg::UpdateReplicationMatched {
-                                target: self.target,
+                                target: self.target.clone(),
                                 result: Err(e.to_string()),
-                                vote: self.vote,
+                                vote: self.vote.clone(),
                             });
                             ReplicationError::Timeout(e)
                         }
                         RPCError::Network(e) => {
                             let _ = self.raft_core_tx.send(RaftMsg::UpdateReplicationMatched {
-                                target: self.target,
+                                target: self.target.clone(),
                                 result: Err(e.to_string()),
-                                vote: self.vote,
+                                vote: self.vote.clone(),
                             });
                             ReplicationError::Network(e)
                         }
+                        RPCError::Unreachable(e) => {
+                            let _ = self.raft_core_tx.send(RaftMsg::UpdateReplicationMatched {
+                                target: self.target.clone(),
+                                result: Err(e.to_string()),
+                                vote: self.vote.clone(),
+                            });
+                            ReplicationError::Unreachable(e)
+                        }
                         RPCError::RemoteError(e) => ReplicationError::RemoteError(e),
                     };
                     return Err(repl_err);
@@ -358,15 +669,15 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
                 tracing::warn!(error=%timeout_err, "timeout while sending AppendEntries RPC to target");
 
                 let _ = self.raft_core_tx.send(RaftMsg::UpdateReplicationMatched {
-                    target: self.target,
+                    target: self.target.clone(),
                     result: Err(timeout_err.to_string()),
-                    vote: self.vote,
+                    vote: self.vote.clone(),
                 });
 
                 return Err(ReplicationError::Timeout(Timeout {
                     action: RPCTypes::AppendEntries,
-                    id: self.vote.node_id,
-                    target: self.target,
+                    id: self.vote.node_id.clone(),
+                    target: self.target.clone(),
                     timeout: the_timeout,
                 }));
             }
@@ -374,9 +685,18 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
 
         tracing::debug!("append_entries resp: {:?}", append_resp);
 
+        self.last_acked = Some(C::AsyncRuntime::now());
+
         match append_resp {
             AppendEntriesResponse::Success => {
+                self.report_acked(sending_time);
+                if entries > 0 {
+                    self.grow_payload_limit();
+                }
                 self.update_matched(matched);
+                if self.matched.index() == self.max_possible_matched_index {
+                    self.probing = false;
+                }
                 Ok(())
             }
             AppendEntriesResponse::HigherVote(vote) => {
@@ -385,25 +705,163 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
 
                 Err(ReplicationError::HigherVote(HigherVote {
                     higher: vote,
-                    mine: self.vote,
+                    mine: self.vote.clone(),
                 }))
             }
+            AppendEntriesResponse::Conflict if speculative => {
+                // The target may just not have received an earlier request yet. Instead of searching for the
+                // matching log, the logs after the confirmed `matched` are sent again.
+                tracing::debug!(
+                    conflict = debug(&conflict),
+                    matched = debug(&self.matched),
+                    "pipelined AppendEntries is rejected"
+                );
+                self.report_acked(sending_time);
+                Ok(())
+            }
             AppendEntriesResponse::Conflict => {
                 debug_assert!(conflict.is_some(), "prev_log_id=None never conflict");
                 let conflict = conflict.unwrap();
 
+                // A conflict response still means the target accepted the leader's vote.
+                self.report_acked(sending_time);
+
                 // Continue to find the matching log id on follower.
                 self.max_possible_matched_index = if conflict.index == 0 {
                     None
                 } else {
                     Some(conflict.index - 1)
                 };
+                self.probing = true;
+
+                // Switch to snapshot at once, instead of probing a target that can not catch up with logs.
+                self.check_consecutive(last_purged.clone())?;
+                if catch_up::prefer_snapshot(
+                    self.matched.index(),
+                    last_purged.index(),
+                    self.config.snapshot_catch_up_gap,
+                ) {
+                    tracing::info!(
+                        matched = debug(&self.matched),
+                        last_purged = debug(&last_purged),
+                        "target lags far behind, replicate snapshot"
+                    );
+                    return Err(ReplicationError::LackEntry(LackEntry {
+                        index: self.matched.index(),
+                        last_purged_log_id: last_purged,
+                    }));
+                }
 
                 Ok(())
             }
         }
     }
 
+    /// Wait until `until`, sending heartbeats to the target meanwhile, e.g., while a snapshot chunk is throttled.
+    async fn wait_with_heartbeat(&mut self, until: InstantOf<C>) -> Result<(), ReplicationError<C::NodeId, C::Node>> {
+        loop {
+            let now = C::AsyncRuntime::now();
+            if now >= until {
+                return Ok(());
+            }
+
+            if now >= self.next_heartbeat {
+                self.reset_heartbeat();
+                self.send_heartbeat().await?;
+                continue;
+            }
+
+            C::AsyncRuntime::sleep_until(std::cmp::min(until, self.next_heartbeat)).await;
+        }
+    }
+
+    /// Send an AppendEntries RPC without logs, to keep the target from starting an election.
+    ///
+    /// `prev_log_id` is the matched log id, which the target always accepts. A failed heartbeat is only logged.
+    async fn send_heartbeat(&mut self) -> Result<(), ReplicationError<C::NodeId, C::Node>> {
+        let payload = AppendEntriesRequest {
+            vote: self.vote.clone(),
+            prev_log_id: self.matched.clone(),
+            leader_commit: self.committed.clone(),
+            entries: vec![],
+            compressed_entries: None,
+            leader_transfer_to: None,
+        };
+
+        let option = RPCOption::new(self.heartbeat_interval);
+        let sending_time = C::AsyncRuntime::now();
+        let res = C::AsyncRuntime::timeout(
+            self.heartbeat_interval,
+            self.networks[0].append_entries(payload, option),
+        )
+        .await;
+
+        match res {
+            Ok(Ok(AppendEntriesResponse::HigherVote(vote))) => Err(ReplicationError::HigherVote(HigherVote {
+                higher: vote,
+                mine: self.vote.clone(),
+            })),
+            Ok(Ok(_)) => {
+                self.last_acked = Some(C::AsyncRuntime::now());
+                self.report_acked(sending_time);
+                self.report_progress();
+                Ok(())
+            }
+            Ok(Err(err)) => {
+                tracing::warn!(error=%err, "error sending heartbeat to target");
+                if is_connection_error(&err) {
+                    self.networks[0].on_connection_error();
+                }
+                Ok(())
+            }
+            Err(err) => {
+                tracing::warn!(error=%err, "timeout while sending heartbeat to target");
+                Ok(())
+            }
+        }
+    }
+
+    /// A heartbeat should return within `heartbeat_interval`, while an AppendEntries carrying logs is allowed more
+    /// time.
+    /// Compress the entries in `payload` with [`Config::replication_compression`], if it is set.
+    ///
+    /// If compressing fails, the entries are sent uncompressed.
+    fn compress_entries(&self, payload: AppendEntriesRequest<C>) -> AppendEntriesRequest<C> {
+        #[cfg(feature = "compression")]
+        {
+            let mut payload = payload;
+
+            if let Some(algo) = self.config.replication_compression {
+                if !payload.entries.is_empty() {
+                    match crate::CompressedEntries::compress(algo, &payload.entries) {
+                        Ok(compressed) => {
+                            payload.entries = vec![];
+                            payload.compressed_entries = Some(compressed);
+                        }
+                        Err(e) => {
+                            tracing::warn!(error=%e, "failed to compress entries, send them uncompressed");
+                        }
+                    }
+                }
+            }
+
+            payload
+        }
+
+        #[cfg(not(feature = "compression"))]
+        {
+            payload
+        }
+    }
+
+    fn append_entries_timeout(&self, payload: &AppendEntriesRequest<C>) -> Duration {
+        if payload.entries.is_empty() {
+            self.heartbeat_interval
+        } else {
+            Duration::from_millis(self.config.append_entries_timeout)
+        }
+    }
+
     /// max_possible_matched_index is the least index for `prev_log_id` to form a consecutive log sequence
     #[tracing::instrument(level = "trace", skip_all)]
     fn check_consecutive(&self, last_purged: Option<LogId<C::NodeId>>) -> Result<(), LackEntry<C::NodeId>> {
@@ -445,34 +903,218 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
             tracing::debug!(target=%self.target, matched=?self.matched, "matched updated");
 
             let _ = self.raft_core_tx.send(RaftMsg::UpdateReplicationMatched {
-                target: self.target,
+                target: self.target.clone(),
                 // `self.matched < new_matched` implies new_matched can not be None.
                 // Thus unwrap is safe.
-                result: Ok(self.matched.unwrap()),
-                vote: self.vote,
+                result: Ok(self.matched.clone().unwrap()),
+                vote: self.vote.clone(),
             });
         }
     }
 
+    /// Report to RaftCore that the target acknowledged the leader with a request sent at `sending_time`,
+    /// which extends the leader's read lease.
+    #[tracing::instrument(level = "trace", skip(self))]
+    fn report_acked(&self, sending_time: InstantOf<C>) {
+        let _ = self.raft_core_tx.send(RaftMsg::ReplicationAcked {
+            target: self.target.clone(),
+            sending_time,
+            vote: self.vote.clone(),
+        });
+    }
+
     /// Perform a check to see if this replication stream is lagging behind far enough that a
     /// snapshot is warranted.
     #[tracing::instrument(level = "trace", skip(self))]
     pub(self) fn needs_snapshot(&self) -> bool {
-        match &self.config.snapshot_policy {
-            SnapshotPolicy::LogsSinceLast(threshold) => {
+        match self.config.snapshot_policy.logs_since_last_threshold() {
+            Some(threshold) => {
                 let c = self.committed.next_index();
                 let m = self.matched.next_index();
 
-                let needs_snap = c.saturating_sub(m) >= *threshold;
+                let needs_snap = c.saturating_sub(m) >= threshold;
 
                 tracing::trace!("snapshot needed: {}", needs_snap);
                 needs_snap
             }
+            // A snapshot is sent only when the logs to replicate are purged.
+            None => false,
+        }
+    }
+
+    /// Schedule the next heartbeat one heartbeat interval from now.
+    fn reset_heartbeat(&mut self) {
+        self.next_heartbeat = C::AsyncRuntime::now() + self.heartbeat_interval;
+    }
+
+    /// Get the logs in `range` from the prefetched logs, or `None` if any of them is not prefetched.
+    ///
+    /// The prefetched logs before the one at `range.start - 1` are dropped, since they will not be sent again unless
+    /// the target rejects a request, in which case they are read again.
+    fn get_prefetched(&mut self, range: Range<u64>) -> Option<Vec<Arc<Entry<C>>>> {
+        while let Some(first) = self.prefetched.front() {
+            if first.log_id.index + 1 >= range.start {
+                break;
+            }
+            self.prefetched.pop_front();
+        }
+
+        let first = self.prefetched.front()?.log_id.index;
+        if range.start < first || range.end > first + self.prefetched.len() as u64 {
+            return None;
+        }
+
+        let skip = (range.start - first) as usize;
+        let n = (range.end - range.start) as usize;
+        Some(self.prefetched.iter().skip(skip).take(n).cloned().collect())
+    }
+
+    /// Get the log id at `index` from the prefetched logs.
+    fn prefetched_log_id(&self, index: u64) -> Option<LogId<C::NodeId>> {
+        let first = self.prefetched.front()?.log_id.index;
+        if index < first {
+            return None;
+        }
+        self.prefetched.get((index - first) as usize).map(|ent| ent.log_id.clone())
+    }
+
+    /// The max number of entries and bytes of the logs in an AppendEntries.
+    fn payload_limit(&self) -> (u64, u64) {
+        let max_bytes = self.config.max_payload_bytes.unwrap_or(u64::MAX);
+        match &self.payload_limit {
+            Some(l) => (l.entries, std::cmp::min(l.bytes, max_bytes)),
+            None => (self.config.max_payload_entries, max_bytes),
+        }
+    }
+
+    /// Lower the limit of an AppendEntries after a request of `sent_entries` entries and `sent_bytes` bytes is too
+    /// large.
+    ///
+    /// The target's hints are used if they help, otherwise the number of entries is halved.
+    fn shrink_payload_limit(&mut self, sent_entries: u64, sent_bytes: u64, err: &PayloadTooLarge) {
+        let (mut entries, mut bytes) = self.payload_limit();
+
+        if err.entries_hint() < sent_entries || err.bytes_hint() < sent_bytes {
+            entries = std::cmp::min(entries, err.entries_hint());
+            bytes = std::cmp::min(bytes, err.bytes_hint());
+        } else {
+            entries = sent_entries / 2;
+        }
+        let entries = std::cmp::max(entries, 1);
+
+        tracing::info!(
+            sent_entries,
+            sent_bytes,
+            entries,
+            bytes,
+            "payload too large for target={}, send fewer entries",
+            self.target
+        );
+
+        self.payload_limit = Some(PayloadLimit {
+            entries,
+            bytes,
+            accepted: 0,
+        });
+    }
+
+    /// Count an accepted request carrying logs, and double the lowered limit after every
+    /// [`PayloadLimit::GROW_AFTER`] of them, until it reaches the configured maximum.
+    fn grow_payload_limit(&mut self) {
+        let l = match &mut self.payload_limit {
+            Some(l) => l,
+            None => return,
+        };
+
+        l.accepted += 1;
+        if l.accepted < PayloadLimit::GROW_AFTER {
+            return;
+        }
+
+        l.entries = l.entries.saturating_mul(2);
+        l.bytes = l.bytes.saturating_mul(2);
+        l.accepted = 0;
+
+        let max_bytes = self.config.max_payload_bytes.unwrap_or(u64::MAX);
+        if l.entries >= self.config.max_payload_entries && l.bytes >= max_bytes {
+            self.payload_limit = None;
+        }
+    }
+
+    /// Whether it is waiting to retry the target after an RPC to it failed.
+    fn is_waiting_to_retry(&self) -> bool {
+        C::AsyncRuntime::now() < self.retry_at
+    }
+
+    /// Retry on the next heartbeat after an RPC to the target fails with an error that may be transient.
+    fn retry_on_heartbeat(&mut self) {
+        self.retry_at = C::AsyncRuntime::now() + self.heartbeat_interval;
+        self.next_heartbeat = self.retry_at;
+    }
+
+    /// Delay the next retry with the backoff policy after the target is found unreachable.
+    fn back_off(&mut self) {
+        let delay = self.backoff.on_failure();
+        self.retry_at = C::AsyncRuntime::now() + delay;
+        // Do not wake up for heartbeat before it is time to retry.
+        self.next_heartbeat = self.retry_at;
+
+        tracing::debug!(delay = debug(delay), "back off replication to target={}", self.target);
+        self.report_backoff();
+    }
+
+    /// Stop backing off once an RPC to the target succeeds.
+    fn reset_backoff(&mut self) {
+        if self.backoff.on_success() {
+            tracing::info!("target={} is reachable, stop backing off", self.target);
+            self.report_backoff();
+        }
+    }
+
+    fn report_backoff(&self) {
+        let _ = self.raft_core_tx.send(RaftMsg::UpdateReplicationBackoff {
+            target: self.target.clone(),
+            backoff: self.backoff.metrics(),
+            vote: self.vote.clone(),
+        });
+    }
+
+    /// Report the replication progress to RaftCore, if it changed.
+    ///
+    /// A change is held back if the previous report is sent less than `replication_progress_interval` ago. It will be
+    /// reported by a later call, e.g., on the next heartbeat.
+    fn report_progress(&mut self) {
+        let progress = ReplicationProgress {
+            matched: self.matched.clone(),
+            max_possible_matched_index: self.max_possible_matched_index,
+            // The progress of sending a snapshot is reported by `SnapshotStreaming`.
+            snapshot: None,
+            last_acked: self.last_acked,
+            backoff: self.backoff.metrics(),
+        };
+
+        let now = C::AsyncRuntime::now();
+
+        if let Some((prev, reported_at)) = &self.reported_progress {
+            if prev == &progress {
+                return;
+            }
+            if now < *reported_at + Duration::from_millis(self.config.replication_progress_interval) {
+                return;
+            }
         }
+
+        let _ = self.raft_core_tx.send(RaftMsg::UpdateReplicationProgress {
+            target: self.target.clone(),
+            progress: progress.clone(),
+            vote: self.vote.clone(),
+        });
+
+        self.reported_progress = Some((progress, now));
     }
 
     #[tracing::instrument(level = "trace", skip(self))]
-    pub async fn try_drain_raft_rx(&mut self) -> Result<(), ReplicationError<C::NodeId>> {
+    pub async fn try_drain_raft_rx(&mut self) -> Result<(), ReplicationError<C::NodeId, C::Node>> {
         tracing::debug!("try_drain_raft_rx");
 
         for _i in 0..self.config.max_payload_entries {
@@ -511,6 +1153,16 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
         if event.last_log_id.index() > self.matched.index() {
             self.need_to_replicate = true;
         }
+
+        if let Some(config) = event.config {
+            self.heartbeat_interval = Duration::from_millis(config.heartbeat_interval);
+            self.install_snapshot_timeout = Duration::from_millis(config.install_snapshot_timeout);
+            self.config = config;
+        }
+
+        if let Some(witness) = event.witness {
+            self.witness = witness;
+        }
     }
 }
 
@@ -529,6 +1181,95 @@ enum TargetReplState<NID: NodeId> {
     Shutdown,
 }
 
+/// The result of an AppendEntries RPC, or a timeout.
+#[allow(clippy::type_complexity)]
+type AppendEntriesResult<C> = Result<
+    Result<
+        AppendEntriesResponse<<C as RaftTypeConfig>::NodeId>,
+        RPCError<
+            <C as RaftTypeConfig>::NodeId,
+            AppendEntriesError<<C as RaftTypeConfig>::NodeId>,
+            <C as RaftTypeConfig>::Node,
+        >,
+    >,
+    <<C as RaftTypeConfig>::AsyncRuntime as AsyncRuntime>::TimeoutError,
+>;
+
+/// An AppendEntries RPC that is sent to the target, and what a response to it means.
+struct InflightAppend<NID: NodeId, I: Instant> {
+    /// The `prev_log_id` of the request, which the target does not have if it responds with a conflict.
+    conflict: Option<LogId<NID>>,
+
+    /// The last log id in the request, which the target matches if it accepts the request.
+    matched: Option<LogId<NID>>,
+
+    /// The last purged log id when the logs of the request are loaded.
+    last_purged: Option<LogId<NID>>,
+
+    /// The number of entries in the request.
+    entries: u64,
+
+    /// The size of the entries in the request, by `RaftTypeConfig::entry_size()`.
+    bytes: u64,
+
+    /// The request is pipelined before the target confirms its `prev_log_id`.
+    ///
+    /// Thus a conflict may be caused by an earlier request that is not received yet, rather than a log the target
+    /// lacks.
+    speculative: bool,
+
+    timeout: Duration,
+
+    sending_time: I,
+}
+
+/// A lowered max number of entries and bytes of an AppendEntries, which grows back after requests are accepted.
+#[derive(Debug, Clone)]
+struct PayloadLimit {
+    entries: u64,
+
+    bytes: u64,
+
+    /// The number of accepted requests since the limit is lowered or grown.
+    accepted: u64,
+}
+
+impl PayloadLimit {
+    const GROW_AFTER: u64 = 16;
+}
+
+/// The number of the leading entries whose total size is within `max_bytes`, but at least one.
+fn entries_within_bytes<C: RaftTypeConfig>(entries: &[Arc<Entry<C>>], max_bytes: u64) -> usize {
+    let mut size = 0u64;
+    for (i, ent) in entries.iter().enumerate() {
+        size = size.saturating_add(C::entry_size(ent));
+        if size > max_bytes {
+            return std::cmp::max(i, 1);
+        }
+    }
+    entries.len()
+}
+
+/// Replace the payloads of normal entries with blank ones, for a witness target.
+///
+/// Membership entries are kept: a witness has to know the membership to vote and to acknowledge logs.
+fn strip_payloads<C: RaftTypeConfig>(entries: Vec<Arc<Entry<C>>>) -> Vec<Arc<Entry<C>>> {
+    entries
+        .into_iter()
+        .map(|ent| {
+            if matches!(ent.payload, EntryPayload::Normal(_)) {
+                Arc::new(Entry {
+                    log_id: ent.log_id.clone(),
+                    payload: EntryPayload::Blank,
+                    context: None,
+                })
+            } else {
+                ent
+            }
+        })
+        .collect()
+}
+
 /// An event from the RaftCore in leader state to replication stream.
 pub(crate) struct UpdateReplication<NID: NodeId> {
     /// The new entry which needs to be replicated.
@@ -538,6 +1279,12 @@ pub(crate) struct UpdateReplication<NID: NodeId> {
 
     /// The index of the highest log entry which is known to be committed in the cluster.
     pub(crate) committed: Option<LogId<NID>>,
+
+    /// The config updated by `Raft::update_config()`, which replaces the config of this stream.
+    pub(crate) config: Option<Arc<Config>>,
+
+    /// Whether the target is a witness in the updated membership.
+    pub(crate) witness: Option<bool>,
 }
 
 impl<NID: NodeId> MessageSummary<UpdateReplication<NID>> for UpdateReplication<NID> {
@@ -549,60 +1296,89 @@ impl<NID: NodeId> MessageSummary<UpdateReplication<NID>> for UpdateReplication<N
     }
 }
 
-impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> ReplicationCore<C, N, S> {
+impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, LS: RaftLogStorage<C>, SM: RaftStateMachine<C>>
+    ReplicationCore<C, N, LS, SM>
+{
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn line_rate_loop(&mut self) -> Result<(), ReplicationError<C::NodeId>> {
+    pub async fn line_rate_loop(&mut self) -> Result<(), ReplicationError<C::NodeId, C::Node>> {
         loop {
-            loop {
-                tracing::debug!(
-                    "current matched: {:?} max_possible_matched_index: {:?}",
-                    self.matched,
-                    self.max_possible_matched_index
-                );
+            // A failed target is not retried until the retry delay elapses, even if there are new logs.
+            if !self.is_waiting_to_retry() {
+                loop {
+                    tracing::debug!(
+                        "current matched: {:?} max_possible_matched_index: {:?}",
+                        self.matched,
+                        self.max_possible_matched_index
+                    );
+
+                    // Requests are pipelined only when the target confirmed the logs before the next one to send.
+                    // While searching for the matching log, one request is sent at a time.
+                    let pipelined = self.config.max_inflight_appends > 1
+                        && !self.probing
+                        && self.matched.index() == self.max_possible_matched_index;
+
+                    let res = if pipelined {
+                        self.send_append_entries_pipelined().await
+                    } else {
+                        self.send_append_entries().await
+                    };
+                    tracing::debug!(target = display(&self.target), res = debug(&res), "replication res",);
 
-                let res = self.send_append_entries().await;
-                tracing::debug!(target = display(self.target), res = debug(&res), "replication res",);
+                    self.report_progress();
 
-                if let Err(err) = res {
-                    tracing::error!(error=%err, "error replication to target={}", self.target);
+                    if let Err(err) = res {
+                        tracing::error!(error=%err, "error replication to target={}", self.target);
 
-                    // For transport error, just keep retrying.
-                    match err {
-                        ReplicationError::Timeout { .. } => {
-                            break;
-                        }
-                        ReplicationError::Network { .. } => {
-                            break;
-                        }
-                        _ => {
-                            return Err(err);
+                        // For transport error, keep retrying: an unreachable target after a backoff delay, other
+                        // errors on the next heartbeat.
+                        match err {
+                            ReplicationError::Unreachable { .. } => {
+                                self.back_off();
+                                break;
+                            }
+                            ReplicationError::Timeout { .. } => {
+                                self.retry_on_heartbeat();
+                                break;
+                            }
+                            ReplicationError::Network { .. } => {
+                                self.retry_on_heartbeat();
+                                break;
+                            }
+                            _ => {
+                                return Err(err);
+                            }
                         }
                     }
-                }
 
-                if self.matched.index() == self.max_possible_matched_index {
-                    break;
+                    self.reset_backoff();
+
+                    if self.matched.index() == self.max_possible_matched_index {
+                        break;
+                    }
                 }
-            }
 
-            if self.needs_snapshot() {
-                return Err(ReplicationError::CommittedAdvanceTooMany(CommittedAdvanceTooMany {
-                    // TODO(xp) fill them
-                    committed_index: 0,
-                    target_index: 0,
-                }));
-            }
+                if self.needs_snapshot() {
+                    return Err(ReplicationError::CommittedAdvanceTooMany(CommittedAdvanceTooMany {
+                        // TODO(xp) fill them
+                        committed_index: 0,
+                        target_index: 0,
+                    }));
+                }
 
-            // Check raft channel to ensure we are staying up-to-date
-            self.try_drain_raft_rx().await?;
-            if self.need_to_replicate {
-                // if there is more log, continue to send_append_entries
-                continue;
+                // Check raft channel to ensure we are staying up-to-date
+                self.try_drain_raft_rx().await?;
+                if self.need_to_replicate && !self.is_waiting_to_retry() {
+                    // if there is more log, continue to send_append_entries
+                    continue;
+                }
             }
 
             tokio::select! {
-                _ = self.heartbeat.tick() => {
+                _ = C::AsyncRuntime::sleep_until(self.next_heartbeat) => {
                     tracing::debug!("heartbeat triggered");
+                    self.reset_heartbeat();
+                    // A progress held back by the rate limit is reported now.
+                    self.report_progress();
                     // continue
                 }
 
@@ -626,11 +1402,23 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
     pub async fn replicate_snapshot(
         &mut self,
         snapshot_must_include: Option<LogId<C::NodeId>>,
-    ) -> Result<(), ReplicationError<C::NodeId>> {
-        let snapshot = self.wait_for_snapshot(snapshot_must_include).await?;
-        self.stream_snapshot(snapshot).await?;
+    ) -> Result<(), ReplicationError<C::NodeId, C::Node>> {
+        loop {
+            let snapshot = self.wait_for_snapshot(snapshot_must_include.clone()).await?;
 
-        Ok(())
+            if self.config.snapshot_source == SnapshotSource::Voters
+                && self.send_snapshot_from_peer(&snapshot.meta).await?
+            {
+                return Ok(());
+            }
+
+            if self.send_snapshot(snapshot).await? {
+                return Ok(());
+            }
+
+            self.wait_with_heartbeat(C::AsyncRuntime::now() + self.heartbeat_interval).await?;
+            self.try_drain_raft_rx().await?;
+        }
     }
 
     /// Wait for a response from the storage layer for the current snapshot.
@@ -641,7 +1429,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
     async fn wait_for_snapshot(
         &mut self,
         snapshot_must_include: Option<LogId<C::NodeId>>,
-    ) -> Result<Snapshot<C::NodeId, S::SnapshotData>, ReplicationError<C::NodeId>> {
+    ) -> Result<Snapshot<C::NodeId, C::SnapshotData, C::Node>, ReplicationError<C::NodeId, C::Node>> {
         // Ask raft core for a snapshot.
         // - If raft core has a ready snapshot, it sends back through tx.
         // - Otherwise raft core starts a new task taking snapshot, and **close** `tx` when finished. Thus there has to
@@ -654,10 +1442,10 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
             // TODO(xp): handle sending error. If channel is closed, quite replication by returning
             // ReplicationError::Closed.
             let _ = self.raft_core_tx.send(RaftMsg::NeedsSnapshot {
-                target: self.target,
-                must_include: snapshot_must_include,
+                target: self.target.clone(),
+                must_include: snapshot_must_include.clone(),
                 tx,
-                vote: self.vote,
+                vote: self.vote.clone(),
             });
 
             let mut waiting_for_snapshot = true;
@@ -666,9 +1454,12 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
             //           heartbeat, new-log, or snapshot is ready.
             while waiting_for_snapshot {
                 tokio::select! {
-                    _ = self.heartbeat.tick() => {
+                    _ = C::AsyncRuntime::sleep_until(self.next_heartbeat) => {
+                        self.reset_heartbeat();
+
                         // TODO(xp): just heartbeat:
                         let res = self.send_append_entries().await;
+                        self.report_progress();
                         match res {
                             Ok(_) => {
                                 //
@@ -713,86 +1504,207 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
         }
     }
 
-    #[tracing::instrument(level = "trace", skip(self, snapshot))]
-    async fn stream_snapshot(
+    /// Let the target fetch a snapshot from a voter picked by the leader, with [`RaftNetwork::get_snapshot_from`].
+    ///
+    /// It returns `false` if there is no such voter, or the target fails to fetch a snapshot that includes the last
+    /// log id of the leader's snapshot, in which case the leader's snapshot has to be sent.
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn send_snapshot_from_peer(
         &mut self,
-        mut snapshot: Snapshot<C::NodeId, S::SnapshotData>,
-    ) -> Result<(), ReplicationError<C::NodeId>> {
-        let err_x = || (ErrorSubject::Snapshot(snapshot.meta.clone()), ErrorVerb::Read);
+        meta: &SnapshotMeta<C::NodeId, C::Node>,
+    ) -> Result<bool, ReplicationError<C::NodeId, C::Node>> {
+        let (tx, rx) = oneshot::channel();
+
+        let _ = self.raft_core_tx.send(RaftMsg::PickSnapshotSource {
+            target: self.target.clone(),
+            need: meta.last_log_id.clone(),
+            tx,
+            vote: self.vote.clone(),
+        });
+
+        // The channel is closed if this node is no longer the leader that started this replication.
+        let (source, source_node) = match rx.await {
+            Ok(Some(x)) => x,
+            Ok(None) | Err(_) => return Ok(false),
+        };
 
-        let end = snapshot.snapshot.seek(SeekFrom::End(0)).await.sto_res(err_x)?;
+        let req = SnapshotFromRequest {
+            vote: self.vote.clone(),
+            source: source.clone(),
+            source_node,
+            must_include: meta.last_log_id.clone(),
+        };
+        tracing::debug!(req = display(req.summary()), "let target fetch snapshot from a voter");
 
-        let mut offset = 0;
+        let option = RPCOption::new(self.install_snapshot_timeout);
+        let res = C::AsyncRuntime::timeout(
+            self.install_snapshot_timeout,
+            self.networks[0].get_snapshot_from(req, option),
+        )
+        .await;
+
+        let resp = match res {
+            Ok(Ok(resp)) => resp,
+            Ok(Err(err)) => {
+                tracing::warn!(error=%err, source=display(&source), "error letting target fetch snapshot from a voter");
+                if is_connection_error(&err) {
+                    self.networks[0].on_connection_error();
+                }
+                return Ok(false);
+            }
+            Err(err) => {
+                tracing::warn!(error=%err, source=display(&source), "timeout letting target fetch snapshot from a voter");
+                return Ok(false);
+            }
+        };
 
-        let mut buf = Vec::with_capacity(self.config.snapshot_max_chunk_size as usize);
+        self.last_acked = Some(C::AsyncRuntime::now());
 
-        loop {
-            // Build the RPC.
-            snapshot.snapshot.seek(SeekFrom::Start(offset)).await.sto_res(err_x)?;
-
-            let n_read = snapshot.snapshot.read_buf(&mut buf).await.sto_res(err_x)?;
-
-            let done = (offset + n_read as u64) == end; // If bytes read == 0, then we're done.
-            let req = InstallSnapshotRequest {
-                vote: self.vote,
-                meta: snapshot.meta.clone(),
-                offset,
-                data: Vec::from(&buf[..n_read]),
-                done,
-            };
-            buf.clear();
+        if resp.vote > self.vote {
+            return Err(ReplicationError::HigherVote(HigherVote {
+                higher: resp.vote,
+                mine: self.vote.clone(),
+            }));
+        }
 
-            // Send the RPC over to the target.
-            tracing::debug!(
-                snapshot_size = req.data.len(),
-                req.offset,
-                end,
-                req.done,
-                "sending snapshot chunk"
-            );
+        match resp.last_log_id {
+            Some(last_log_id) if last_log_id >= meta.last_log_id => {
+                tracing::debug!(
+                    "done fetching snapshot from {}: snapshot last_log_id: {}, matched: {:?}",
+                    source,
+                    last_log_id,
+                    self.matched,
+                );
 
-            let res = timeout(self.install_snapshot_timeout, self.network.send_install_snapshot(req)).await;
+                self.update_matched(Some(last_log_id));
+                self.report_progress();
+                Ok(true)
+            }
+            last_log_id => {
+                tracing::info!(
+                    source = display(&source),
+                    last_log_id = debug(&last_log_id),
+                    "snapshot of the source is stale, send the snapshot of the leader"
+                );
+                Ok(false)
+            }
+        }
+    }
 
-            let res = match res {
-                Ok(outer_res) => match outer_res {
-                    Ok(res) => res,
-                    Err(err) => {
-                        tracing::warn!(error=%err, "error sending InstallSnapshot RPC to target");
-                        continue;
+    /// Send the snapshot to the target with [`RaftNetwork::full_snapshot`].
+    ///
+    /// Events from RaftCore are merged after the snapshot is sent, and replication quits at once if RaftCore closes
+    /// the channel. It returns `false` if the snapshot fails to be sent, in which case it has to be sent again.
+    #[tracing::instrument(
+        name = "raft.snapshot",
+        level = "debug",
+        skip(self, snapshot),
+        fields(
+            node_id = display(&self.vote.node_id),
+            target = display(&self.target),
+            term = self.vote.term,
+            log_index = snapshot.meta.last_log_id.index
+        )
+    )]
+    async fn send_snapshot(
+        &mut self,
+        snapshot: Snapshot<C::NodeId, C::SnapshotData, C::Node>,
+    ) -> Result<bool, ReplicationError<C::NodeId, C::Node>> {
+        let meta = snapshot.meta.clone();
+        let streaming = SnapshotStreaming::new(&self.config, self.snapshot_progress_reporter());
+
+        let mut events = vec![];
+
+        let res = {
+            let fut = self.networks[0].full_snapshot(self.vote.clone(), snapshot, streaming);
+            tokio::pin!(fut);
+
+            loop {
+                tokio::select! {
+                    res = &mut fut => break res,
+
+                    event_opt = self.repl_rx.recv() => {
+                        match event_opt {
+                            Some(event) => events.push(event),
+                            None => {
+                                tracing::info!("repl_rx is closed, stop sending snapshot");
+                                return Err(ReplicationError::Closed);
+                            }
+                        }
                     }
-                },
-                Err(err) => {
-                    tracing::warn!(error=%err, "timeout while sending InstallSnapshot RPC to target");
-                    continue;
                 }
-            };
+            }
+        };
 
-            // Handle response conditions.
-            if res.vote > self.vote {
-                return Err(ReplicationError::HigherVote(HigherVote {
-                    higher: res.vote,
-                    mine: self.vote,
-                }));
+        for event in events {
+            self.process_raft_event(event);
+        }
+
+        // The progress of the transmission is reported by `SnapshotStreaming`: force a report of the current state.
+        self.reported_progress = None;
+
+        let resp = match res {
+            Ok(resp) => resp,
+            Err(StreamingError::StorageError(e)) => return Err(ReplicationError::StorageError(e)),
+            Err(StreamingError::Network(e)) => {
+                tracing::warn!(error=%e, "error sending snapshot to target");
+                self.networks[0].on_connection_error();
+                self.report_progress();
+                return Ok(false);
+            }
+            Err(StreamingError::Unreachable(e)) => {
+                tracing::warn!(error=%e, "target is unreachable when sending snapshot");
+                self.networks[0].on_connection_error();
+                self.report_progress();
+                return Ok(false);
             }
+        };
 
-            // If we just sent the final chunk of the snapshot, then transition to lagging state.
-            if done {
-                tracing::debug!(
-                    "done install snapshot: snapshot last_log_id: {}, matched: {:?}",
-                    snapshot.meta.last_log_id,
-                    self.matched,
-                );
+        self.last_acked = Some(C::AsyncRuntime::now());
 
-                self.update_matched(Some(snapshot.meta.last_log_id));
+        if resp.vote > self.vote {
+            return Err(ReplicationError::HigherVote(HigherVote {
+                higher: resp.vote,
+                mine: self.vote.clone(),
+            }));
+        }
 
-                return Ok(());
-            }
+        tracing::debug!(
+            "done sending snapshot: snapshot last_log_id: {}, matched: {:?}",
+            meta.last_log_id,
+            self.matched,
+        );
 
-            // Everything is good, so update offset for sending the next chunk.
-            offset += n_read as u64;
+        self.update_matched(Some(meta.last_log_id));
+        self.report_progress();
 
-            // Check raft channel to ensure we are staying up-to-date, then loop.
-            self.try_drain_raft_rx().await?;
+        Ok(true)
+    }
+
+    /// Build a function that reports the progress of sending a snapshot to RaftCore.
+    ///
+    /// The other fields of the progress are those when the snapshot starts to be sent.
+    fn snapshot_progress_reporter(&self) -> impl Fn(SnapshotTransmission) + Send + Sync + 'static {
+        let tx = self.raft_core_tx.clone();
+        let target = self.target.clone();
+        let vote = self.vote.clone();
+        let progress = ReplicationProgress {
+            matched: self.matched.clone(),
+            max_possible_matched_index: self.max_possible_matched_index,
+            snapshot: None,
+            last_acked: self.last_acked,
+            backoff: self.backoff.metrics(),
+        };
+
+        move |transmission| {
+            let _ = tx.send(RaftMsg::UpdateReplicationProgress {
+                target: target.clone(),
+                progress: ReplicationProgress {
+                    snapshot: Some(transmission),
+                    ..progress.clone()
+                },
+                vote: vote.clone(),
+            });
         }
     }
 }
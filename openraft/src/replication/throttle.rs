@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use crate::async_runtime::Instant;
+
+const NANOS_PER_SEC: i128 = 1_000_000_000;
+
+/// A token bucket that limits the number of bytes sent per second, e.g., when streaming snapshot chunks.
+///
+/// Up to one second worth of bytes can be sent at once. Tokens are counted in byte-nanoseconds to avoid rounding.
+#[derive(Debug, Clone)]
+pub(crate) struct Throttle<I: Instant> {
+    bytes_per_sec: i128,
+
+    /// Available tokens, in byte-nanoseconds. It goes negative if more bytes are taken than available.
+    tokens: i128,
+
+    updated_at: I,
+}
+
+impl<I: Instant> Throttle<I> {
+    pub(crate) fn new(bytes_per_sec: u64, now: I) -> Self {
+        let bytes_per_sec = bytes_per_sec as i128;
+        Self {
+            bytes_per_sec,
+            tokens: bytes_per_sec * NANOS_PER_SEC,
+            updated_at: now,
+        }
+    }
+
+    /// Take `n` bytes from the bucket and return how long to wait before sending them.
+    pub(crate) fn take(&mut self, n: u64, now: I) -> Duration {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_nanos() as i128;
+        if now > self.updated_at {
+            self.updated_at = now;
+        }
+
+        let capacity = self.bytes_per_sec * NANOS_PER_SEC;
+        self.tokens = std::cmp::min(self.tokens + elapsed * self.bytes_per_sec, capacity);
+        self.tokens -= n as i128 * NANOS_PER_SEC;
+
+        if self.tokens >= 0 {
+            return Duration::default();
+        }
+
+        let deficit = -self.tokens;
+        let nanos = (deficit + self.bytes_per_sec - 1) / self.bytes_per_sec;
+        Duration::from_nanos(nanos as u64)
+    }
+}
@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::replication::throttle::Throttle;
+
+fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+}
+
+#[test]
+fn test_throttle() -> anyhow::Result<()> {
+    let t0 = Instant::now();
+    let mut t = Throttle::new(100, t0);
+
+    // Up to one second worth of bytes is sent at once.
+    assert_eq!(ms(0), t.take(50, t0));
+    assert_eq!(ms(500), t.take(100, t0));
+
+    // The deficit is paid back after waiting.
+    assert_eq!(ms(100), t.take(10, t0 + ms(500)));
+
+    // The bucket does not store more than one second worth of bytes.
+    assert_eq!(ms(0), t.take(100, t0 + ms(10_000)));
+    assert_eq!(ms(10), t.take(1, t0 + ms(10_000)));
+
+    Ok(())
+}
+
+#[test]
+fn test_throttle_large_chunk() -> anyhow::Result<()> {
+    let t0 = Instant::now();
+    let mut t = Throttle::new(100, t0);
+
+    // A chunk larger than the bucket is sent after waiting for the bytes exceeding one second.
+    assert_eq!(ms(2_000), t.take(300, t0));
+    assert_eq!(ms(1_000), t.take(100, t0 + ms(2_000)));
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+//! Deliver the result of a client write.
+
+use tokio::sync::oneshot;
+
+use crate::error::ClientWriteError;
+use crate::raft::ClientWriteResponse;
+use crate::raft::RaftRespTx;
+use crate::RaftTypeConfig;
+
+/// The result of a client write.
+pub type WriteResult<C> = Result<
+    ClientWriteResponse<C>,
+    ClientWriteError<<C as RaftTypeConfig>::NodeId, <C as RaftTypeConfig>::Node, <C as RaftTypeConfig>::AppError>,
+>;
+
+/// Delivers the result of a client write submitted with
+/// [`Raft::client_write_with_responder()`](`crate::Raft::client_write_with_responder`).
+///
+/// `RaftCore` calls [`send()`](`Responder::send`) when the entry is applied to the state machine, or when the write
+/// fails, e.g., this node loses leadership before the entry is committed. A responder is dropped without being called
+/// if `Raft` shuts down.
+///
+/// `send()` is called inside the `RaftCore` loop, thus it should not block.
+pub trait Responder<C: RaftTypeConfig>: Send + 'static {
+    /// Deliver the result of a client write.
+    fn send(self, result: WriteResult<C>);
+}
+
+/// The default [`Responder`]: it sends the result through a oneshot channel.
+pub struct OneshotResponder<C: RaftTypeConfig> {
+    tx: oneshot::Sender<WriteResult<C>>,
+}
+
+impl<C: RaftTypeConfig> OneshotResponder<C> {
+    /// Create a responder and the receiver to receive the result from.
+    pub fn new() -> (Self, oneshot::Receiver<WriteResult<C>>) {
+        let (tx, rx) = oneshot::channel();
+        (Self { tx }, rx)
+    }
+}
+
+impl<C: RaftTypeConfig> Responder<C> for OneshotResponder<C> {
+    fn send(self, result: WriteResult<C>) {
+        let res = self.tx.send(result);
+        tracing::debug!("OneshotResponder sends result, is error: {}", res.is_err());
+    }
+}
+
+/// Where `RaftCore` delivers the result of a client write to.
+pub(crate) enum ClientResponder<C: RaftTypeConfig> {
+    /// A channel that a `Raft` API is waiting on.
+    Channel(RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>>),
+
+    /// A responder provided by the application.
+    App(C::Responder),
+}
+
+impl<C: RaftTypeConfig> ClientResponder<C> {
+    pub(crate) fn send(self, result: WriteResult<C>) {
+        match self {
+            ClientResponder::Channel(tx) => {
+                let res = tx.send(result);
+                tracing::debug!("send client response through tx, send_res is error: {}", res.is_err());
+            }
+            ClientResponder::App(responder) => responder.send(result),
+        }
+    }
+}
+
+impl<C: RaftTypeConfig> From<RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>>>
+    for ClientResponder<C>
+{
+    fn from(tx: RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node, C::AppError>>) -> Self {
+        ClientResponder::Channel(tx)
+    }
+}
@@ -0,0 +1,25 @@
+//! The randomness openraft draws from, e.g., for election timeouts and backoff jitter.
+
+use rand::distributions::uniform::SampleRange;
+use rand::distributions::uniform::SampleUniform;
+
+/// Pick a random value in `range`.
+///
+/// It draws from the thread local, OS seeded RNG, or, with feature `manual-clock`, from
+/// [`SeededRng`](`crate::testing::SeededRng`) once a test seeds it.
+pub(crate) fn gen_range<T, R>(range: R) -> T
+where
+    T: SampleUniform,
+    R: SampleRange<T>,
+{
+    #[cfg(feature = "manual-clock")]
+    {
+        crate::testing::SeededRng::gen_range(range)
+    }
+
+    #[cfg(not(feature = "manual-clock"))]
+    {
+        use rand::Rng;
+        rand::thread_rng().gen_range(range)
+    }
+}
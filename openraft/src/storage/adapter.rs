@@ -0,0 +1,195 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use crate::membership::EffectiveMembership;
+use crate::raft_types::StateMachineChanges;
+use crate::storage::LogFlushed;
+use crate::storage::LogState;
+use crate::storage::RaftLogReader;
+use crate::storage::RaftLogStorage;
+use crate::storage::RaftStateMachine;
+use crate::storage::Snapshot;
+use crate::storage::SnapshotMeta;
+use crate::Entry;
+use crate::LeaderId;
+use crate::LogId;
+use crate::RaftStorage;
+use crate::RaftTypeConfig;
+use crate::StorageError;
+use crate::Vote;
+
+/// Splits a [`RaftStorage`] into a [`RaftLogStorage`] and a [`RaftStateMachine`].
+///
+/// Both halves share the same storage, thus they still wait for each other: an application that wants the log IO and
+/// applying to proceed concurrently implements the two traits on different objects instead.
+///
+/// ```ignore
+/// let (log_store, state_machine) = Adaptor::new(store);
+/// let raft = Raft::new(id, config, network, log_store, state_machine);
+/// ```
+pub struct Adaptor<C, S>
+where
+    C: RaftTypeConfig,
+    S: RaftStorage<C>,
+{
+    storage: Arc<RwLock<S>>,
+    _p: PhantomData<C>,
+}
+
+impl<C, S> Clone for Adaptor<C, S>
+where
+    C: RaftTypeConfig,
+    S: RaftStorage<C>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            storage: self.storage.clone(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<C, S> Adaptor<C, S>
+where
+    C: RaftTypeConfig,
+    S: RaftStorage<C>,
+{
+    /// Wrap `store` and return the log store and the state machine built upon it.
+    pub fn new(store: S) -> (Self, Self) {
+        let a = Self {
+            storage: Arc::new(RwLock::new(store)),
+            _p: PhantomData,
+        };
+        (a.clone(), a)
+    }
+}
+
+#[async_trait]
+impl<C, S> RaftLogReader<C> for Adaptor<C, S>
+where
+    C: RaftTypeConfig,
+    S: RaftStorage<C>,
+{
+    async fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>> {
+        self.storage.write().await.get_log_state().await
+    }
+
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+        self.storage.write().await.try_get_log_entries(range).await
+    }
+}
+
+#[async_trait]
+impl<C, S> RaftLogStorage<C> for Adaptor<C, S>
+where
+    C: RaftTypeConfig,
+    S: RaftStorage<C>,
+{
+    type LogReader = S::LogReader;
+
+    async fn save_vote(&mut self, vote: &Vote<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.storage.write().await.save_vote(vote).await
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>> {
+        self.storage.write().await.read_vote().await
+    }
+
+    async fn save_committed(&mut self, committed: Option<LogId<C::NodeId>>) -> Result<(), StorageError<C::NodeId>> {
+        self.storage.write().await.save_committed(committed).await
+    }
+
+    async fn read_committed(&mut self) -> Result<Option<LogId<C::NodeId>>, StorageError<C::NodeId>> {
+        self.storage.write().await.read_committed().await
+    }
+
+    async fn get_log_reader(&mut self) -> Self::LogReader {
+        self.storage.write().await.get_log_reader().await
+    }
+
+    async fn append(&mut self, entries: &[&Entry<C>], callback: LogFlushed<C>) -> Result<(), StorageError<C::NodeId>> {
+        // A `RaftStorage` persists the logs before `append_to_log()` returns.
+        self.storage.write().await.append_to_log(entries).await?;
+        callback.log_io_completed(Ok(()));
+        Ok(())
+    }
+
+    async fn append_with_vote(
+        &mut self,
+        vote: &Vote<C::NodeId>,
+        entries: &[&Entry<C>],
+        callback: LogFlushed<C>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        self.storage.write().await.append_to_log_with_vote(vote, entries).await?;
+        callback.log_io_completed(Ok(()));
+        Ok(())
+    }
+
+    async fn delete_conflict_logs_since(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.storage.write().await.delete_conflict_logs_since(log_id).await
+    }
+
+    async fn purge_logs_upto(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.storage.write().await.purge_logs_upto(log_id).await
+    }
+}
+
+#[async_trait]
+impl<C, S> RaftStateMachine<C> for Adaptor<C, S>
+where
+    C: RaftTypeConfig,
+    S: RaftStorage<C>,
+{
+    type SnapshotBuilder = S::SnapshotBuilder;
+
+    async fn last_applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<C::NodeId>>, EffectiveMembership<C::NodeId, C::Node>), StorageError<C::NodeId>> {
+        self.storage.write().await.last_applied_state().await
+    }
+
+    async fn apply_to_state_machine(&mut self, entries: &[&Entry<C>]) -> Result<Vec<C::R>, StorageError<C::NodeId>> {
+        self.storage.write().await.apply_to_state_machine(entries).await
+    }
+
+    async fn try_apply_to_state_machine(
+        &mut self,
+        entries: &[&Entry<C>],
+    ) -> Result<Vec<Result<C::R, C::AppError>>, StorageError<C::NodeId>> {
+        self.storage.write().await.try_apply_to_state_machine(entries).await
+    }
+
+    async fn leader_established_data(&mut self, leader_id: &LeaderId<C::NodeId>) -> Option<C::D> {
+        self.storage.write().await.leader_established_data(leader_id).await
+    }
+
+    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder {
+        self.storage.write().await.get_snapshot_builder().await
+    }
+
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<C::SnapshotData>, StorageError<C::NodeId>> {
+        self.storage.write().await.begin_receiving_snapshot().await
+    }
+
+    async fn install_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<C::NodeId, C::Node>,
+        snapshot: Box<C::SnapshotData>,
+    ) -> Result<StateMachineChanges<C>, StorageError<C::NodeId>> {
+        self.storage.write().await.install_snapshot(meta, snapshot).await
+    }
+
+    async fn get_current_snapshot(
+        &mut self,
+    ) -> Result<Option<Snapshot<C::NodeId, C::SnapshotData, C::Node>>, StorageError<C::NodeId>> {
+        self.storage.write().await.get_current_snapshot().await
+    }
+}
@@ -0,0 +1,237 @@
+//! A state machine that hands the committed logs to the application instead of applying them.
+
+use anyerror::AnyError;
+use async_trait::async_trait;
+use tokio::sync::oneshot;
+
+use crate::async_runtime::mpsc;
+use crate::membership::EffectiveMembership;
+use crate::raft_types::StateMachineChanges;
+use crate::storage::RaftSnapshotBuilder;
+use crate::storage::RaftStateMachine;
+use crate::storage::Snapshot;
+use crate::storage::SnapshotMeta;
+use crate::AsyncRuntime;
+use crate::Entry;
+use crate::ErrorSubject;
+use crate::ErrorVerb;
+use crate::LogId;
+use crate::RaftTypeConfig;
+use crate::StorageError;
+use crate::StorageIOError;
+
+/// The part of a state machine other than applying logs, which a [`ChannelStateMachine`] delegates to.
+///
+/// The methods have the same meaning as the ones of [`RaftStateMachine`] with the same names.
+#[async_trait]
+pub trait PulledStateMachine<C>: Send + Sync + 'static
+where C: RaftTypeConfig
+{
+    /// Snapshot builder type.
+    type SnapshotBuilder: RaftSnapshotBuilder<C, C::SnapshotData>;
+
+    /// Returns the last log id the application acknowledged, and the membership of the last acknowledged membership
+    /// log.
+    async fn last_applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<C::NodeId>>, EffectiveMembership<C::NodeId, C::Node>), StorageError<C::NodeId>>;
+
+    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder;
+
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<C::SnapshotData>, StorageError<C::NodeId>>;
+
+    async fn install_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<C::NodeId, C::Node>,
+        snapshot: Box<C::SnapshotData>,
+    ) -> Result<StateMachineChanges<C>, StorageError<C::NodeId>>;
+
+    async fn get_current_snapshot(
+        &mut self,
+    ) -> Result<Option<Snapshot<C::NodeId, C::SnapshotData, C::Node>>, StorageError<C::NodeId>>;
+}
+
+/// A committed log delivered by a [`ChannelStateMachine`], to be applied and acknowledged by the application.
+pub struct ApplyEntry<C: RaftTypeConfig> {
+    /// The log to apply.
+    pub entry: Entry<C>,
+
+    tx: oneshot::Sender<Result<C::R, C::AppError>>,
+}
+
+impl<C: RaftTypeConfig> ApplyEntry<C> {
+    /// The id of the log to apply.
+    pub fn log_id(&self) -> &LogId<C::NodeId> {
+        &self.entry.log_id
+    }
+
+    /// Acknowledge that the log is applied, with the response to the client that proposed it.
+    ///
+    /// Every log has to be acknowledged, including blank and membership logs: dropping an `ApplyEntry` without
+    /// acknowledging it is a fatal storage error.
+    pub fn ack(self, response: Result<C::R, C::AppError>) {
+        let _ = self.tx.send(response);
+    }
+}
+
+/// The receiving end of a [`ChannelStateMachine`], from which the application pulls the committed logs in order.
+pub struct ApplyReceiver<C: RaftTypeConfig> {
+    rx: mpsc::Receiver<ApplyEntry<C>>,
+}
+
+impl<C: RaftTypeConfig> ApplyReceiver<C> {
+    /// Receive the next committed log, or `None` if the `ChannelStateMachine` is dropped.
+    pub async fn recv(&mut self) -> Option<ApplyEntry<C>> {
+        self.rx.recv().await
+    }
+
+    /// Receive the next committed log, blocking the current thread, e.g., a thread owned by a foreign runtime.
+    ///
+    /// It panics if called within an asynchronous context.
+    pub fn blocking_recv(&mut self) -> Option<ApplyEntry<C>> {
+        self.rx.blocking_recv()
+    }
+}
+
+/// A [`RaftStateMachine`] that sends the committed logs to the application through a bounded channel, instead of
+/// applying them by itself.
+///
+/// The application drains the [`ApplyReceiver`] and acknowledges every [`ApplyEntry`] once it is applied. Snapshots
+/// and the last applied state are served by the [`PulledStateMachine`] it is created with.
+///
+/// ### Backpressure
+///
+/// The channel holds at most `capacity` logs. `apply_to_state_machine()` waits for room in the channel when the
+/// application falls behind, and it returns only when every log of the batch is acknowledged. `RaftCore` does not
+/// proceed meanwhile, thus a slow application slows down the node instead of letting committed logs pile up.
+///
+/// ### Crash recovery
+///
+/// A log the application has not acknowledged is not applied as far as openraft is concerned. After a restart,
+/// openraft delivers again every committed log after the one returned by
+/// [`PulledStateMachine::last_applied_state()`], thus the application should persist the last acknowledged log id,
+/// along with the last membership log it acknowledged, atomically with the effect of applying it.
+pub struct ChannelStateMachine<C, S>
+where
+    C: RaftTypeConfig,
+    S: PulledStateMachine<C>,
+{
+    inner: S,
+    tx: mpsc::Sender<ApplyEntry<C>>,
+}
+
+impl<C, S> ChannelStateMachine<C, S>
+where
+    C: RaftTypeConfig,
+    S: PulledStateMachine<C>,
+{
+    /// Create a state machine delegating to `inner`, and the receiver to pull at most `capacity` pending logs from.
+    pub fn new(inner: S, capacity: usize) -> (Self, ApplyReceiver<C>) {
+        let (tx, rx) = C::AsyncRuntime::mpsc(capacity);
+        (Self { inner, tx }, ApplyReceiver { rx })
+    }
+}
+
+fn apply_error<C: RaftTypeConfig>(log_id: &LogId<C::NodeId>, msg: &str) -> StorageError<C::NodeId> {
+    StorageError::IO {
+        source: StorageIOError::new(
+            ErrorSubject::Apply(log_id.clone()),
+            ErrorVerb::Write,
+            AnyError::error(msg),
+        ),
+    }
+}
+
+#[async_trait]
+impl<C, S> RaftStateMachine<C> for ChannelStateMachine<C, S>
+where
+    C: RaftTypeConfig,
+    S: PulledStateMachine<C>,
+{
+    type SnapshotBuilder = S::SnapshotBuilder;
+
+    async fn last_applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<C::NodeId>>, EffectiveMembership<C::NodeId, C::Node>), StorageError<C::NodeId>> {
+        self.inner.last_applied_state().await
+    }
+
+    /// A log rejected by the application with [`ApplyEntry::ack()`] fails with a storage error: openraft applies the
+    /// logs with [`try_apply_to_state_machine()`](`Self::try_apply_to_state_machine`), which delivers the rejection to
+    /// the client instead.
+    async fn apply_to_state_machine(&mut self, entries: &[&Entry<C>]) -> Result<Vec<C::R>, StorageError<C::NodeId>> {
+        let results = self.try_apply_to_state_machine(entries).await?;
+
+        let mut res = Vec::with_capacity(results.len());
+        for (entry, r) in entries.iter().zip(results) {
+            match r {
+                Ok(resp) => res.push(resp),
+                Err(_app_err) => {
+                    return Err(apply_error::<C>(
+                        &entry.log_id,
+                        "ApplyEntry is rejected by the application",
+                    ))
+                }
+            }
+        }
+
+        Ok(res)
+    }
+
+    async fn try_apply_to_state_machine(
+        &mut self,
+        entries: &[&Entry<C>],
+    ) -> Result<Vec<Result<C::R, C::AppError>>, StorageError<C::NodeId>> {
+        let mut pending = Vec::with_capacity(entries.len());
+
+        for entry in entries {
+            let (tx, rx) = oneshot::channel();
+            let apply = ApplyEntry {
+                entry: (*entry).clone(),
+                tx,
+            };
+
+            if self.tx.send(apply).await.is_err() {
+                return Err(apply_error::<C>(&entry.log_id, "ApplyReceiver is dropped"));
+            }
+            pending.push((entry.log_id.clone(), rx));
+        }
+
+        let mut res = Vec::with_capacity(pending.len());
+        for (log_id, rx) in pending {
+            match rx.await {
+                Ok(r) => res.push(r),
+                Err(_) => {
+                    return Err(apply_error::<C>(
+                        &log_id,
+                        "ApplyEntry is dropped without being acknowledged",
+                    ))
+                }
+            }
+        }
+
+        Ok(res)
+    }
+
+    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder {
+        self.inner.get_snapshot_builder().await
+    }
+
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<C::SnapshotData>, StorageError<C::NodeId>> {
+        self.inner.begin_receiving_snapshot().await
+    }
+
+    async fn install_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<C::NodeId, C::Node>,
+        snapshot: Box<C::SnapshotData>,
+    ) -> Result<StateMachineChanges<C>, StorageError<C::NodeId>> {
+        self.inner.install_snapshot(meta, snapshot).await
+    }
+
+    async fn get_current_snapshot(
+        &mut self,
+    ) -> Result<Option<Snapshot<C::NodeId, C::SnapshotData, C::Node>>, StorageError<C::NodeId>> {
+        self.inner.get_current_snapshot().await
+    }
+}
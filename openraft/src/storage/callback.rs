@@ -0,0 +1,80 @@
+//! Callbacks for the storage to report the completion of an IO.
+
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+use crate::metrics::SnapshotBuildProgress;
+use crate::LogId;
+use crate::RaftTypeConfig;
+
+/// Reports that the logs passed to [`RaftLogStorage::append()`](`crate::storage::RaftLogStorage::append`) are durably
+/// flushed.
+///
+/// A storage may call [`LogFlushed::log_io_completed()`] before `append()` returns, or later, e.g., when the batch of
+/// logs it belongs to is synced to disk. A leader counts its own log in the quorum only when the callback is called,
+/// thus an entry is never committed before it is flushed on the leader.
+pub struct LogFlushed<C: RaftTypeConfig> {
+    last_log_id: Option<LogId<C::NodeId>>,
+    callback: Box<dyn FnOnce(Option<LogId<C::NodeId>>, Result<(), io::Error>) + Send>,
+}
+
+impl<C: RaftTypeConfig> LogFlushed<C> {
+    pub(crate) fn new<F>(last_log_id: Option<LogId<C::NodeId>>, callback: F) -> Self
+    where F: FnOnce(Option<LogId<C::NodeId>>, Result<(), io::Error>) + Send + 'static {
+        Self {
+            last_log_id,
+            callback: Box::new(callback),
+        }
+    }
+
+    /// The id of the last log to flush.
+    pub fn last_log_id(&self) -> Option<&LogId<C::NodeId>> {
+        self.last_log_id.as_ref()
+    }
+
+    /// Report the result of flushing the logs: `Ok(())` if they are persisted, or the error that failed the flush.
+    ///
+    /// An error is fatal: it shuts down the `Raft` node.
+    pub fn log_io_completed(self, result: Result<(), io::Error>) {
+        (self.callback)(self.last_log_id, result)
+    }
+}
+
+impl<C: RaftTypeConfig> fmt::Debug for LogFlushed<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogFlushed").field("last_log_id", &self.last_log_id).finish()
+    }
+}
+
+/// Reports how far building a snapshot has gone, to expose it in
+/// [`RaftMetrics::snapshot_build`](`crate::RaftMetrics::snapshot_build`).
+///
+/// It is passed to a snapshot builder with
+/// [`RaftSnapshotBuilder::set_progress_reporter()`](`crate::RaftSnapshotBuilder::set_progress_reporter`) before a build
+/// starts. The progress is best-effort: a report may be delivered after the build finishes, in which case it is
+/// ignored.
+#[derive(Clone)]
+pub struct SnapshotProgressReporter {
+    callback: Arc<dyn Fn(SnapshotBuildProgress) + Send + Sync>,
+}
+
+impl SnapshotProgressReporter {
+    pub(crate) fn new<F>(callback: F) -> Self
+    where F: Fn(SnapshotBuildProgress) + Send + Sync + 'static {
+        Self {
+            callback: Arc::new(callback),
+        }
+    }
+
+    /// Report that `done` out of `total` work of building the snapshot is done.
+    pub fn report(&self, done: u64, total: u64) {
+        (self.callback)(SnapshotBuildProgress { done, total })
+    }
+}
+
+impl fmt::Debug for SnapshotProgressReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnapshotProgressReporter").finish()
+    }
+}
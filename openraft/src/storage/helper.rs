@@ -1,9 +1,19 @@
+use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::ops::RangeBounds;
 use std::sync::Arc;
 
+use async_trait::async_trait;
+
+use crate::async_runtime::InstantOf;
 use crate::engine::LogIdList;
 use crate::internal_server_state::InternalServerState;
+use crate::storage::LogState;
+use crate::storage::RaftLogReader;
+use crate::storage::RaftLogStorage;
+use crate::storage::RaftStateMachine;
 use crate::EffectiveMembership;
+use crate::Entry;
 use crate::EntryPayload;
 use crate::LogId;
 use crate::LogIdOptionExt;
@@ -12,12 +22,121 @@ use crate::RaftState;
 use crate::RaftStorage;
 use crate::RaftTypeConfig;
 use crate::StorageError;
+use crate::Vote;
+
+/// The storage operations [`StorageHelper`] reads the state of a node with.
+///
+/// It is implemented for every [`RaftStorage`], and for a log store and a state machine borrowed together, thus
+/// `StorageHelper` works with both forms of storage.
+#[async_trait]
+pub trait StorageAccess<C: RaftTypeConfig>: Send {
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>>;
+
+    async fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>>;
+
+    async fn get_log_entries<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>>;
+
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>>;
+
+    async fn purge_logs_upto(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>>;
+
+    async fn last_applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<C::NodeId>>, EffectiveMembership<C::NodeId, C::Node>), StorageError<C::NodeId>>;
+}
+
+#[async_trait]
+impl<C: RaftTypeConfig, S: RaftStorage<C>> StorageAccess<C> for S {
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>> {
+        RaftStorage::read_vote(self).await
+    }
+
+    async fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>> {
+        RaftLogReader::get_log_state(self).await
+    }
+
+    async fn get_log_entries<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+        RaftLogReader::get_log_entries(self, range).await
+    }
+
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+        RaftLogReader::try_get_log_entries(self, range).await
+    }
+
+    async fn purge_logs_upto(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        RaftStorage::purge_logs_upto(self, log_id).await
+    }
+
+    async fn last_applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<C::NodeId>>, EffectiveMembership<C::NodeId, C::Node>), StorageError<C::NodeId>> {
+        RaftStorage::last_applied_state(self).await
+    }
+}
+
+/// A log store and a state machine borrowed together, to be accessed by a [`StorageHelper`].
+pub(crate) struct SplitStorage<'a, LS, SM> {
+    pub(crate) log_store: &'a mut LS,
+    pub(crate) state_machine: &'a mut SM,
+}
+
+#[async_trait]
+impl<'a, C, LS, SM> StorageAccess<C> for SplitStorage<'a, LS, SM>
+where
+    C: RaftTypeConfig,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>> {
+        self.log_store.read_vote().await
+    }
+
+    async fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>> {
+        self.log_store.get_log_state().await
+    }
+
+    async fn get_log_entries<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+        self.log_store.get_log_entries(range).await
+    }
+
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+        self.log_store.try_get_log_entries(range).await
+    }
+
+    async fn purge_logs_upto(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.log_store.purge_logs_upto(log_id).await
+    }
+
+    async fn last_applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<C::NodeId>>, EffectiveMembership<C::NodeId, C::Node>), StorageError<C::NodeId>> {
+        self.state_machine.last_applied_state().await
+    }
+}
 
 /// StorageHelper provides additional methods to access a RaftStorage implementation.
 pub struct StorageHelper<'a, C, Sto>
 where
     C: RaftTypeConfig,
-    Sto: RaftStorage<C>,
+    Sto: StorageAccess<C>,
 {
     pub(crate) sto: &'a mut Sto,
     _p: PhantomData<C>,
@@ -26,7 +145,7 @@ where
 impl<'a, C, Sto> StorageHelper<'a, C, Sto>
 where
     C: RaftTypeConfig,
-    Sto: RaftStorage<C>,
+    Sto: StorageAccess<C>,
 {
     pub fn new(sto: &'a mut Sto) -> Self {
         Self {
@@ -39,7 +158,12 @@ where
     ///
     /// When the Raft node is first started, it will call this interface to fetch the last known state from stable
     /// storage.
-    pub async fn get_initial_state(&mut self) -> Result<RaftState<C::NodeId>, StorageError<C::NodeId>> {
+    ///
+    /// `id` is the id of this Raft node. It is used to build the initial vote if there is no vote in storage.
+    pub async fn get_initial_state(
+        &mut self,
+        id: C::NodeId,
+    ) -> Result<RaftState<C::NodeId, C::Node, InstantOf<C>>, StorageError<C::NodeId>> {
         let vote = self.sto.read_vote().await?;
         let st = self.sto.get_log_state().await?;
         let mut last_purged_log_id = st.last_purged_log_id;
@@ -49,9 +173,9 @@ where
 
         // Clean up dirty state: snapshot is installed but logs are not cleaned.
         if last_log_id < last_applied {
-            self.sto.purge_logs_upto(last_applied.unwrap()).await?;
-            last_log_id = last_applied;
-            last_purged_log_id = last_applied;
+            self.sto.purge_logs_upto(last_applied.clone().unwrap()).await?;
+            last_log_id = last_applied.clone();
+            last_purged_log_id = last_applied.clone();
         }
 
         let log_ids = LogIdList::load_log_ids(last_purged_log_id, last_log_id, self).await?;
@@ -60,7 +184,7 @@ where
             last_applied,
             // The initial value for `vote` is the minimal possible value.
             // See: [Conditions for initialization](https://datafuselabs.github.io/openraft/cluster-formation.html#conditions-for-initialization)
-            vote: vote.unwrap_or_default(),
+            vote: vote.unwrap_or_else(|| Vote::new(0, id)),
             log_ids,
             membership_state: mem_state,
 
@@ -81,7 +205,7 @@ where
 
         let entries = self.sto.get_log_entries(log_index..=log_index).await?;
 
-        Ok(entries[0].log_id)
+        Ok(entries[0].log_id.clone())
     }
 
     /// Returns the last 2 membership config found in log or state machine.
@@ -100,7 +224,7 @@ where
     /// a follower only need to revert at most one membership log.
     ///
     /// Thus a raft node will only need to store at most two recent membership logs.
-    pub async fn get_membership(&mut self) -> Result<MembershipState<C::NodeId>, StorageError<C::NodeId>> {
+    pub async fn get_membership(&mut self) -> Result<MembershipState<C::NodeId, C::Node>, StorageError<C::NodeId>> {
         let (_, sm_mem) = self.sto.last_applied_state().await?;
 
         let sm_mem_next_index = sm_mem.log_id.next_index();
@@ -138,7 +262,7 @@ where
     pub async fn last_membership_in_log(
         &mut self,
         since_index: u64,
-    ) -> Result<Vec<EffectiveMembership<C::NodeId>>, StorageError<C::NodeId>> {
+    ) -> Result<Vec<EffectiveMembership<C::NodeId, C::Node>>, StorageError<C::NodeId>> {
         let st = self.sto.get_log_state().await?;
 
         let mut end = st.last_log_id.next_index();
@@ -153,7 +277,7 @@ where
 
             for ent in entries.iter().rev() {
                 if let EntryPayload::Membership(ref mem) = ent.payload {
-                    let em = EffectiveMembership::new(Some(ent.log_id), mem.clone());
+                    let em = EffectiveMembership::new(Some(ent.log_id.clone()), mem.clone());
                     res.insert(0, em);
                     if res.len() == 2 {
                         return Ok(res);
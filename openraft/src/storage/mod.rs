@@ -1,49 +1,93 @@
 //! The Raft storage interface and data types.
 
+mod adapter;
+mod apply_channel;
+mod callback;
 mod helper;
+mod v2;
+
 use std::fmt::Debug;
 use std::ops::RangeBounds;
 
+pub use adapter::Adaptor;
+pub use apply_channel::ApplyEntry;
+pub use apply_channel::ApplyReceiver;
+pub use apply_channel::ChannelStateMachine;
+pub use apply_channel::PulledStateMachine;
 use async_trait::async_trait;
+pub use callback::LogFlushed;
+pub use callback::SnapshotProgressReporter;
+pub(crate) use helper::SplitStorage;
+pub(crate) use helper::StorageAccess;
 pub use helper::StorageHelper;
-use tokio::io::AsyncRead;
-use tokio::io::AsyncSeek;
-use tokio::io::AsyncWrite;
+pub use v2::RaftLogStorage;
+pub use v2::RaftStateMachine;
 
 use crate::defensive::check_range_matches_entries;
 use crate::membership::EffectiveMembership;
 use crate::raft_types::SnapshotId;
 use crate::raft_types::StateMachineChanges;
 use crate::Entry;
+use crate::LeaderId;
 use crate::LogId;
+use crate::Node;
 use crate::NodeId;
+use crate::NodeInfo;
 use crate::RaftTypeConfig;
 use crate::StorageError;
 use crate::Vote;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
-pub struct SnapshotMeta<NID: NodeId> {
+pub struct SnapshotMeta<NID: NodeId, N: NodeInfo = Node> {
     // Log entries upto which this snapshot includes, inclusive.
     pub last_log_id: LogId<NID>,
 
     // The last applied membership config.
-    pub last_membership: EffectiveMembership<NID>,
+    pub last_membership: EffectiveMembership<NID, N>,
 
     /// To identify a snapshot when transferring.
     /// Caveat: even when two snapshot is built with the same `last_log_id`, they still could be different in bytes.
     pub snapshot_id: SnapshotId,
 }
 
+impl<NID: NodeId, N: NodeInfo> SnapshotMeta<NID, N> {
+    /// Returns the signature that identifies this snapshot.
+    pub fn signature(&self) -> SnapshotSignature<NID> {
+        SnapshotSignature {
+            last_log_id: self.last_log_id.clone(),
+            last_membership_log_id: self.last_membership.log_id.clone(),
+            snapshot_id: self.snapshot_id.clone(),
+        }
+    }
+}
+
+/// The identity of a snapshot, without the membership config.
+///
+/// It is used to identify a snapshot in an error, e.g., in [`ErrorSubject::Snapshot`](`crate::ErrorSubject::Snapshot`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct SnapshotSignature<NID: NodeId> {
+    /// Log entries upto which this snapshot includes, inclusive.
+    pub last_log_id: LogId<NID>,
+
+    /// The id of the log that applies the last membership config in this snapshot.
+    pub last_membership_log_id: Option<LogId<NID>>,
+
+    /// To identify a snapshot when transferring.
+    pub snapshot_id: SnapshotId,
+}
+
 /// The data associated with the current snapshot.
 #[derive(Debug)]
-pub struct Snapshot<NID, S>
+pub struct Snapshot<NID, S, N = Node>
 where
     NID: NodeId,
-    S: AsyncRead + AsyncSeek + Send + Unpin + 'static,
+    N: NodeInfo,
+    S: Send + 'static,
 {
     /// metadata of a snapshot
-    pub meta: SnapshotMeta<NID>,
+    pub meta: SnapshotMeta<NID, N>,
 
     /// A read handle to the associated snapshot.
     pub snapshot: Box<S>,
@@ -119,6 +163,11 @@ where C: RaftTypeConfig
 ///
 /// This interface is accessed read-only from snapshot building task.
 ///
+/// The task runs apart from `RaftCore`, which keeps applying logs with [`RaftStorage::apply_to_state_machine`] while
+/// a snapshot is being built. Thus the builder should not block applying for long, e.g., by holding a lock on the
+/// state machine until the snapshot is built: it may build the snapshot from a copy or a point-in-time view of the
+/// state machine instead. `RaftCore` learns of the snapshot only when the building is finished.
+///
 /// Typically, the snapshot implementation as such will be hidden behind a reference type like
 /// `Arc<T>` or `Box<T>` and this interface implemented on the reference type. It can be
 /// co-implemented with [`RaftStorage`] interface on the same cloneable object, if the underlying
@@ -127,21 +176,26 @@ where C: RaftTypeConfig
 pub trait RaftSnapshotBuilder<C, SD>: Send + Sync + 'static
 where
     C: RaftTypeConfig,
-    SD: AsyncRead + AsyncWrite + AsyncSeek + Send + Sync + Unpin + 'static,
+    SD: Send + Sync + 'static,
 {
     /// Build snapshot
     ///
     /// A snapshot has to contain information about exactly all logs up to the last applied.
+    /// Logs applied after the building starts may or may not be included, as long as `meta.last_log_id` is the last
+    /// log included.
     ///
     /// Building snapshot can be done by:
     /// - Performing log compaction, e.g. merge log entries that operates on the same key, like a LSM-tree does,
     /// - or by fetching a snapshot from the state machine.
-    async fn build_snapshot(&mut self) -> Result<Snapshot<C::NodeId, SD>, StorageError<C::NodeId>>;
+    async fn build_snapshot(&mut self) -> Result<Snapshot<C::NodeId, SD, C::Node>, StorageError<C::NodeId>>;
 
-    // NOTES:
-    // This interface is geared toward small file-based snapshots. However, not all snapshots can
-    // be easily represented as a file. Probably a more generic interface will be needed to address
-    // also other needs.
+    /// Set the reporter through which the following [`build_snapshot()`](`Self::build_snapshot`) reports its
+    /// progress.
+    ///
+    /// It is called before every build. The default implementation ignores it, thus no progress is reported.
+    fn set_progress_reporter(&mut self, reporter: SnapshotProgressReporter) {
+        let _ = reporter;
+    }
 }
 
 /// A trait defining the interface for a Raft storage system.
@@ -156,21 +210,33 @@ where
 /// storage, except concurrency with snapshot builder and log reader, both created by this API.
 /// The implementation of the API has to cope with (infrequent) concurrent access from these two
 /// components.
+///
+/// A `Raft` does not take a `RaftStorage` directly: it takes a [`RaftLogStorage`] and a [`RaftStateMachine`], which
+/// a `RaftStorage` is split into with [`Adaptor`].
+///
+/// ### Storage isolation
+///
+/// Several Raft groups in one process may share a storage engine, e.g., one RocksDB instance, with a `RaftStorage`
+/// for each group that scopes its keys by a group id. Openraft treats every `RaftStorage` as the only storage of its
+/// `Raft`, thus an implementation must guarantee:
+/// - The vote, logs, last purged log id, state machine, and snapshots of a group are never read or written through the
+///   storage of another group: e.g., `purge_logs_upto()` or `delete_conflict_logs_since()` of one group deletes only
+///   the logs of this group, and `get_log_state()` reports only the logs of this group.
+/// - The durability guarantees hold per group: `save_vote()` and `append_to_log()` of one group are persisted when they
+///   return, no matter what other groups are doing.
+///
+/// See [`RaftGroupHandle`](`crate::RaftGroupHandle`).
 #[async_trait]
 pub trait RaftStorage<C>: RaftLogReader<C> + Send + Sync + 'static
 where C: RaftTypeConfig
 {
-    /// The storage engine's associated type used for exposing a snapshot for reading & writing.
-    ///
-    /// See the [storage chapter of the guide](https://datafuselabs.github.io/openraft/getting-started.html#implement-raftstorage)
-    /// for details on where and how this is used.
-    type SnapshotData: AsyncRead + AsyncWrite + AsyncSeek + Send + Sync + Unpin + 'static;
-
     /// Log reader type.
     type LogReader: RaftLogReader<C>;
 
     /// Snapshot builder type.
-    type SnapshotBuilder: RaftSnapshotBuilder<C, Self::SnapshotData>;
+    ///
+    /// The type of the snapshot data is [`RaftTypeConfig::SnapshotData`].
+    type SnapshotBuilder: RaftSnapshotBuilder<C, C::SnapshotData>;
 
     // --- Vote
 
@@ -178,6 +244,24 @@ where C: RaftTypeConfig
 
     async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>>;
 
+    /// Save the log id of the last committed log.
+    ///
+    /// It is called when the committed log id advances. A node restarted with a saved committed log id applies the
+    /// logs upto it at once, instead of waiting for a leader to tell it the committed log id again. It does not have
+    /// to be flushed before returning: a lost committed log id only makes a restarted node apply fewer logs at once.
+    ///
+    /// The default implementation does not save it.
+    async fn save_committed(&mut self, committed: Option<LogId<C::NodeId>>) -> Result<(), StorageError<C::NodeId>> {
+        let _ = committed;
+        Ok(())
+    }
+
+    /// Return the last committed log id saved by [`save_committed()`](`Self::save_committed`), or `None` if it
+    /// is not saved.
+    async fn read_committed(&mut self) -> Result<Option<LogId<C::NodeId>>, StorageError<C::NodeId>> {
+        Ok(None)
+    }
+
     // --- Log
 
     /// Get the log reader.
@@ -192,6 +276,27 @@ where C: RaftTypeConfig
     /// determine its location to be written in the log.
     async fn append_to_log(&mut self, entries: &[&Entry<C>]) -> Result<(), StorageError<C::NodeId>>;
 
+    /// Save the vote and append a payload of entries to the log, e.g., in one transaction, to persist both with one
+    /// sync.
+    ///
+    /// Openraft calls it instead of `save_vote()` followed by `append_to_log()` when a vote change and an append are
+    /// caused by the same event, e.g., a follower receives the first AppendEntries from a new leader. The default
+    /// implementation calls the two methods in that order.
+    ///
+    /// An implementation that overrides it must uphold the guarantees of the two calls:
+    /// - Both the vote and the entries are persisted when it returns.
+    /// - The entries are never persisted without the vote: if it fails, either nothing is persisted, or only the vote
+    ///   is. A node that has the logs of a leader but not its vote may grant a vote to another candidate of the same
+    ///   term after restarting.
+    async fn append_to_log_with_vote(
+        &mut self,
+        vote: &Vote<C::NodeId>,
+        entries: &[&Entry<C>],
+    ) -> Result<(), StorageError<C::NodeId>> {
+        self.save_vote(vote).await?;
+        self.append_to_log(entries).await
+    }
+
     /// Delete conflict log entries since `log_id`, inclusive.
     async fn delete_conflict_logs_since(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>>;
 
@@ -205,7 +310,7 @@ where C: RaftTypeConfig
     // NOTE: This can be made into sync, provided all state machines will use atomic read or the like.
     async fn last_applied_state(
         &mut self,
-    ) -> Result<(Option<LogId<C::NodeId>>, EffectiveMembership<C::NodeId>), StorageError<C::NodeId>>;
+    ) -> Result<(Option<LogId<C::NodeId>>, EffectiveMembership<C::NodeId, C::Node>), StorageError<C::NodeId>>;
 
     /// Apply the given payload of entries to the state machine.
     ///
@@ -221,6 +326,12 @@ where C: RaftTypeConfig
     /// - Store the last applied log id.
     /// - Deal with the EntryPayload::Normal() log, which is business logic log.
     /// - Deal with EntryPayload::Membership, store the membership config.
+    ///
+    /// The entry a leader appends when it is established, i.e., at a term boundary, is told apart with
+    /// [`Entry::is_leader_established()`], given the log id applied before it.
+    ///
+    /// It returns one response for every entry, in the same order as `entries`.
+    /// To reject a client write, implement [`try_apply_to_state_machine()`](`Self::try_apply_to_state_machine`) too.
     // TODO The reply should happen asynchronously, somehow. Make this method synchronous and
     // instead of using the result, pass a channel where to post the completion. The Raft core can
     // then collect completions on this channel and update the client with the result once all
@@ -228,10 +339,43 @@ where C: RaftTypeConfig
     // operation pipelining w/o the need to wait for the completion of each operation inline.
     async fn apply_to_state_machine(&mut self, entries: &[&Entry<C>]) -> Result<Vec<C::R>, StorageError<C::NodeId>>;
 
+    /// Apply the given entries to the state machine, and let the application reject some of them.
+    ///
+    /// It returns one result for every entry, in the same order as `entries`.
+    /// An application rejects a client write by returning `Err(C::AppError)` for its entry. The rejected entry is still
+    /// regarded as applied and the error is delivered to the client that proposed it, as
+    /// [`ClientWriteError::AppError`](`crate::error::ClientWriteError::AppError`).
+    ///
+    /// Openraft applies entries with this method. The default implementation calls
+    /// [`apply_to_state_machine()`](`Self::apply_to_state_machine`) and rejects nothing. An application that declares
+    /// [`RaftTypeConfig::AppError`] overrides it to reject writes.
+    async fn try_apply_to_state_machine(
+        &mut self,
+        entries: &[&Entry<C>],
+    ) -> Result<Vec<Result<C::R, C::AppError>>, StorageError<C::NodeId>> {
+        let responses = self.apply_to_state_machine(entries).await?;
+        Ok(responses.into_iter().map(Ok).collect())
+    }
+
+    /// Return the application data of the entry a leader appends when it is established, or `None` to append a blank
+    /// entry, which is the default.
+    ///
+    /// A new leader appends an entry before any other one, to commit the entries of the previous leaders. An
+    /// application piggybacks its own data on it, e.g., to fence the reads served by a stale leader. The data is
+    /// applied as a normal entry, and [`Entry::is_leader_established()`] tells such an entry apart in
+    /// [`apply_to_state_machine()`](`Self::apply_to_state_machine`).
+    async fn leader_established_data(&mut self, leader_id: &LeaderId<C::NodeId>) -> Option<C::D> {
+        let _ = leader_id;
+        None
+    }
+
     // --- Snapshot
 
     /// Get the snapshot builder for the state machine.
     ///
+    /// The builder is moved into a task that builds a snapshot while logs are applied to this storage, see
+    /// [`RaftSnapshotBuilder`].
+    ///
     /// The method is intentionally async to give the implementation a chance to use asynchronous
     /// sync primitives to serialize access to the common internal object, if needed.
     async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder;
@@ -243,7 +387,7 @@ where C: RaftTypeConfig
     /// ### implementation guide
     /// See the [storage chapter of the guide](https://datafuselabs.github.io/openraft/storage.html)
     /// for details on log compaction / snapshotting.
-    async fn begin_receiving_snapshot(&mut self) -> Result<Box<Self::SnapshotData>, StorageError<C::NodeId>>;
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<C::SnapshotData>, StorageError<C::NodeId>>;
 
     /// Install a snapshot which has finished streaming from the cluster leader.
     ///
@@ -253,8 +397,8 @@ where C: RaftTypeConfig
     /// A snapshot created from an earlier call to `begin_receiving_snapshot` which provided the snapshot.
     async fn install_snapshot(
         &mut self,
-        meta: &SnapshotMeta<C::NodeId>,
-        snapshot: Box<Self::SnapshotData>,
+        meta: &SnapshotMeta<C::NodeId, C::Node>,
+        snapshot: Box<C::SnapshotData>,
     ) -> Result<StateMachineChanges<C>, StorageError<C::NodeId>>;
 
     /// Get a readable handle to the current snapshot, along with its metadata.
@@ -270,7 +414,7 @@ where C: RaftTypeConfig
     /// of the snapshot, which should be decoded for creating this method's response data.
     async fn get_current_snapshot(
         &mut self,
-    ) -> Result<Option<Snapshot<C::NodeId, Self::SnapshotData>>, StorageError<C::NodeId>>;
+    ) -> Result<Option<Snapshot<C::NodeId, C::SnapshotData, C::Node>>, StorageError<C::NodeId>>;
 }
 
 /// APIs for debugging a store.
@@ -0,0 +1,165 @@
+//! The storage interface split into a log store and a state machine.
+
+use async_trait::async_trait;
+
+use crate::membership::EffectiveMembership;
+use crate::raft_types::StateMachineChanges;
+use crate::storage::LogFlushed;
+use crate::storage::RaftLogReader;
+use crate::storage::RaftSnapshotBuilder;
+use crate::storage::Snapshot;
+use crate::storage::SnapshotMeta;
+use crate::Entry;
+use crate::LeaderId;
+use crate::LogId;
+use crate::RaftTypeConfig;
+use crate::StorageError;
+use crate::Vote;
+
+/// The log half of the storage: the vote and the logs.
+///
+/// `RaftCore` holds it apart from the [`RaftStateMachine`], thus the log and the state machine can be backed by
+/// different systems, e.g., a local write-ahead log and a remote key-value service, without locking one for the other.
+/// The methods have the same meaning as the ones of [`RaftStorage`](`crate::RaftStorage`) with the same names.
+///
+/// An existing [`RaftStorage`](`crate::RaftStorage`) implementation is split into the two halves with
+/// [`Adaptor`](`crate::storage::Adaptor`).
+#[async_trait]
+pub trait RaftLogStorage<C>: RaftLogReader<C> + Send + Sync + 'static
+where C: RaftTypeConfig
+{
+    /// Log reader type.
+    type LogReader: RaftLogReader<C>;
+
+    // --- Vote
+
+    async fn save_vote(&mut self, vote: &Vote<C::NodeId>) -> Result<(), StorageError<C::NodeId>>;
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>>;
+
+    /// Save the log id of the last committed log.
+    ///
+    /// See [`RaftStorage::save_committed()`](`crate::RaftStorage::save_committed`).
+    ///
+    /// The default implementation does not save it.
+    async fn save_committed(&mut self, committed: Option<LogId<C::NodeId>>) -> Result<(), StorageError<C::NodeId>> {
+        let _ = committed;
+        Ok(())
+    }
+
+    /// Return the last committed log id saved by [`save_committed()`](`Self::save_committed`), or `None` if it
+    /// is not saved.
+    async fn read_committed(&mut self) -> Result<Option<LogId<C::NodeId>>, StorageError<C::NodeId>> {
+        Ok(None)
+    }
+
+    // --- Log
+
+    /// Get the log reader, which is used by replication streams.
+    async fn get_log_reader(&mut self) -> Self::LogReader;
+
+    /// Append a payload of entries to the log, and call `callback` once they are durably flushed.
+    ///
+    /// Though the entries will always be presented in order, each entry's index should be used to
+    /// determine its location to be written in the log.
+    ///
+    /// It may return as soon as the entries are buffered, so that the storage can flush several batches with one sync,
+    /// i.e., group commit. The entries must be readable once it returns, and `callback` must be called in the order
+    /// the batches are appended.
+    ///
+    /// A leader counts its own log in the commit quorum only when `callback` is called. A follower or learner waits
+    /// for `callback` before responding to the leader, thus a delayed callback delays its reply.
+    ///
+    /// A leader replicates the entries as soon as `append()` returns, while they are being flushed, thus returning
+    /// before the sync keeps the local sync latency out of the commit latency: the entries are committed once a
+    /// quorum has them flushed, which may be a quorum of the followers only.
+    async fn append(&mut self, entries: &[&Entry<C>], callback: LogFlushed<C>) -> Result<(), StorageError<C::NodeId>>;
+
+    /// Save the vote and append a payload of entries to the log, e.g., in one transaction, to persist both with one
+    /// sync. `callback` is called once both are durably flushed.
+    ///
+    /// Openraft calls it instead of `save_vote()` followed by `append()` when a vote change and an append are caused by
+    /// the same event, e.g., a follower receives the first AppendEntries from a new leader. The default implementation
+    /// calls the two methods in that order.
+    ///
+    /// An implementation that overrides it must uphold the guarantees of the two calls:
+    /// - The vote is persisted no later than the entries: `callback` must not be called before the vote is durable, and
+    ///   the entries are never persisted without the vote. A node that has the logs of a leader but not its vote may
+    ///   grant a vote to another candidate of the same term after restarting.
+    /// - The entries are readable once it returns, as with `append()`.
+    async fn append_with_vote(
+        &mut self,
+        vote: &Vote<C::NodeId>,
+        entries: &[&Entry<C>],
+        callback: LogFlushed<C>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        self.save_vote(vote).await?;
+        self.append(entries, callback).await
+    }
+
+    /// Delete conflict log entries since `log_id`, inclusive.
+    async fn delete_conflict_logs_since(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>>;
+
+    /// Delete applied log entries upto `log_id`, inclusive.
+    async fn purge_logs_upto(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>>;
+}
+
+/// The state machine half of the storage: applying logs, and building and installing snapshots.
+///
+/// See [`RaftLogStorage`] for the other half.
+#[async_trait]
+pub trait RaftStateMachine<C>: Send + Sync + 'static
+where C: RaftTypeConfig
+{
+    /// Snapshot builder type.
+    type SnapshotBuilder: RaftSnapshotBuilder<C, C::SnapshotData>;
+
+    /// Returns the last applied log id which is recorded in state machine, and the last applied membership log id and
+    /// membership config.
+    async fn last_applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<C::NodeId>>, EffectiveMembership<C::NodeId, C::Node>), StorageError<C::NodeId>>;
+
+    /// Apply the given payload of entries to the state machine, and return one response for every entry.
+    ///
+    /// See [`RaftStorage::apply_to_state_machine`](`crate::RaftStorage::apply_to_state_machine`).
+    async fn apply_to_state_machine(&mut self, entries: &[&Entry<C>]) -> Result<Vec<C::R>, StorageError<C::NodeId>>;
+
+    /// Apply the given entries to the state machine, and let the application reject some of them.
+    ///
+    /// See [`RaftStorage::try_apply_to_state_machine`](`crate::RaftStorage::try_apply_to_state_machine`).
+    async fn try_apply_to_state_machine(
+        &mut self,
+        entries: &[&Entry<C>],
+    ) -> Result<Vec<Result<C::R, C::AppError>>, StorageError<C::NodeId>> {
+        let responses = self.apply_to_state_machine(entries).await?;
+        Ok(responses.into_iter().map(Ok).collect())
+    }
+
+    /// Return the application data of the entry a leader appends when it is established, or `None` to append a blank
+    /// entry, which is the default.
+    ///
+    /// See [`RaftStorage::leader_established_data`](`crate::RaftStorage::leader_established_data`).
+    async fn leader_established_data(&mut self, leader_id: &LeaderId<C::NodeId>) -> Option<C::D> {
+        let _ = leader_id;
+        None
+    }
+
+    /// Get the snapshot builder for the state machine, see [`RaftSnapshotBuilder`].
+    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder;
+
+    /// Create a new blank snapshot, returning a writable handle to the snapshot object.
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<C::SnapshotData>, StorageError<C::NodeId>>;
+
+    /// Install a snapshot which has finished streaming from the cluster leader.
+    async fn install_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<C::NodeId, C::Node>,
+        snapshot: Box<C::SnapshotData>,
+    ) -> Result<StateMachineChanges<C>, StorageError<C::NodeId>>;
+
+    /// Get a readable handle to the current snapshot, along with its metadata.
+    async fn get_current_snapshot(
+        &mut self,
+    ) -> Result<Option<Snapshot<C::NodeId, C::SnapshotData, C::Node>>, StorageError<C::NodeId>>;
+}
@@ -5,7 +5,7 @@ use anyerror::AnyError;
 
 use crate::LogId;
 use crate::NodeId;
-use crate::SnapshotMeta;
+use crate::SnapshotSignature;
 use crate::Vote;
 
 /// Convert error to StorageError::IO();
@@ -86,7 +86,7 @@ pub enum ErrorSubject<NID: NodeId> {
     StateMachine,
 
     /// Error happened when operating snapshot.
-    Snapshot(SnapshotMeta<NID>),
+    Snapshot(SnapshotSignature<NID>),
 
     None,
 }
@@ -153,6 +153,12 @@ pub enum Violation<NID: NodeId> {
 }
 
 /// A storage error could be either a defensive check error or an error occurred when doing the actual io operation.
+///
+/// An error is either transient or not. A [`StorageError::Transient`] error, e.g., the disk is full, means the
+/// operation had no effect and may succeed if it is retried: `RaftCore` retries it up to
+/// [`Config::storage_retry_limit`](`crate::Config::storage_retry_limit`) times before giving up.
+/// Every other error, e.g., a checksum mismatch, is treated as corruption: `RaftCore` shuts down at once with
+/// [`Fatal::StorageError`](`crate::error::Fatal::StorageError`).
 #[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 pub enum StorageError<NID: NodeId> {
@@ -171,6 +177,13 @@ pub enum StorageError<NID: NodeId> {
         #[cfg_attr(feature = "bt", backtrace)]
         source: StorageIOError<NID>,
     },
+
+    /// An error raised by io operation, that had no effect and can be retried.
+    #[error(transparent)]
+    Transient {
+        #[cfg_attr(feature = "bt", backtrace)]
+        source: StorageIOError<NID>,
+    },
 }
 
 impl<NID: NodeId> StorageError<NID> {
@@ -188,10 +201,21 @@ impl<NID: NodeId> StorageError<NID> {
         }
     }
 
+    /// Returns `true` if it is a [`StorageError::Transient`] error, and the operation can be retried.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, StorageError::Transient { .. })
+    }
+
     pub fn from_io_error(subject: ErrorSubject<NID>, verb: ErrorVerb, io_error: std::io::Error) -> Self {
         let sto_io_err = StorageIOError::new(subject, verb, AnyError::new(&io_error));
         StorageError::IO { source: sto_io_err }
     }
+
+    /// Build a [`StorageError::Transient`] error, for an io error after which the operation can be retried.
+    pub fn from_transient_io_error(subject: ErrorSubject<NID>, verb: ErrorVerb, io_error: std::io::Error) -> Self {
+        let sto_io_err = StorageIOError::new(subject, verb, AnyError::new(&io_error));
+        StorageError::Transient { source: sto_io_err }
+    }
 }
 
 /// Error that occurs when operating the store.
@@ -12,9 +12,11 @@ use crate::storage::LogState;
 use crate::storage::RaftLogReader;
 use crate::storage::RaftSnapshotBuilder;
 use crate::storage::Snapshot;
+use crate::storage::SnapshotProgressReporter;
 use crate::summary::MessageSummary;
 use crate::DefensiveCheck;
 use crate::Entry;
+use crate::LeaderId;
 use crate::LogId;
 use crate::RaftStorage;
 use crate::RaftStorageDebug;
@@ -54,6 +56,11 @@ impl<C: RaftTypeConfig, T: RaftStorage<C>> StoreExt<C, T> {
             c: PhantomData,
         }
     }
+
+    /// Return the store it is backed by.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
 }
 
 impl<C: RaftTypeConfig, T: RaftStorage<C>> Wrapper<C, T> for StoreExt<C, T>
@@ -104,8 +111,6 @@ where
     T: RaftStorage<C>,
     C: RaftTypeConfig,
 {
-    type SnapshotData = T::SnapshotData;
-
     type LogReader = LogReaderExt<C, T>;
 
     type SnapshotBuilder = SnapshotBuilderExt<C, T>;
@@ -121,22 +126,32 @@ where
         self.inner().read_vote().await
     }
 
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn save_committed(&mut self, committed: Option<LogId<C::NodeId>>) -> Result<(), StorageError<C::NodeId>> {
+        self.inner().save_committed(committed).await
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn read_committed(&mut self) -> Result<Option<LogId<C::NodeId>>, StorageError<C::NodeId>> {
+        self.inner().read_committed().await
+    }
+
     #[tracing::instrument(level = "trace", skip(self))]
     async fn last_applied_state(
         &mut self,
-    ) -> Result<(Option<LogId<C::NodeId>>, EffectiveMembership<C::NodeId>), StorageError<C::NodeId>> {
+    ) -> Result<(Option<LogId<C::NodeId>>, EffectiveMembership<C::NodeId, C::Node>), StorageError<C::NodeId>> {
         self.inner().last_applied_state().await
     }
 
     #[tracing::instrument(level = "trace", skip(self))]
     async fn delete_conflict_logs_since(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
-        self.defensive_delete_conflict_gt_last_applied(log_id).await?;
+        self.defensive_delete_conflict_gt_last_applied(log_id.clone()).await?;
         self.inner().delete_conflict_logs_since(log_id).await
     }
 
     #[tracing::instrument(level = "trace", skip(self))]
     async fn purge_logs_upto(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
-        self.defensive_purge_applied_le_last_applied(log_id).await?;
+        self.defensive_purge_applied_le_last_applied(log_id.clone()).await?;
         self.inner().purge_logs_upto(log_id).await
     }
 
@@ -150,6 +165,21 @@ where
         self.inner().append_to_log(entries).await
     }
 
+    #[tracing::instrument(level = "trace", skip(self, entries), fields(entries=%entries.summary()))]
+    async fn append_to_log_with_vote(
+        &mut self,
+        vote: &Vote<C::NodeId>,
+        entries: &[&Entry<C>],
+    ) -> Result<(), StorageError<C::NodeId>> {
+        self.defensive_incremental_vote(vote).await?;
+        self.defensive_nonempty_input(entries).await?;
+        self.defensive_consecutive_input(entries).await?;
+        self.defensive_append_log_index_is_last_plus_one(entries).await?;
+        self.defensive_append_log_id_gt_last(entries).await?;
+
+        self.inner().append_to_log_with_vote(vote, entries).await
+    }
+
     #[tracing::instrument(level = "trace", skip(self, entries), fields(entries=%entries.summary()))]
     async fn apply_to_state_machine(&mut self, entries: &[&Entry<C>]) -> Result<Vec<C::R>, StorageError<C::NodeId>> {
         self.defensive_nonempty_input(entries).await?;
@@ -159,16 +189,32 @@ where
         self.inner().apply_to_state_machine(entries).await
     }
 
+    #[tracing::instrument(level = "trace", skip(self, entries), fields(entries=%entries.summary()))]
+    async fn try_apply_to_state_machine(
+        &mut self,
+        entries: &[&Entry<C>],
+    ) -> Result<Vec<Result<C::R, C::AppError>>, StorageError<C::NodeId>> {
+        self.defensive_nonempty_input(entries).await?;
+        self.defensive_apply_index_is_last_applied_plus_one(entries).await?;
+        self.defensive_apply_log_id_gt_last(entries).await?;
+
+        self.inner().try_apply_to_state_machine(entries).await
+    }
+
+    async fn leader_established_data(&mut self, leader_id: &LeaderId<C::NodeId>) -> Option<C::D> {
+        self.inner().leader_established_data(leader_id).await
+    }
+
     #[tracing::instrument(level = "trace", skip(self))]
-    async fn begin_receiving_snapshot(&mut self) -> Result<Box<Self::SnapshotData>, StorageError<C::NodeId>> {
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<C::SnapshotData>, StorageError<C::NodeId>> {
         self.inner().begin_receiving_snapshot().await
     }
 
     #[tracing::instrument(level = "trace", skip(self, snapshot))]
     async fn install_snapshot(
         &mut self,
-        meta: &SnapshotMeta<C::NodeId>,
-        snapshot: Box<Self::SnapshotData>,
+        meta: &SnapshotMeta<C::NodeId, C::Node>,
+        snapshot: Box<C::SnapshotData>,
     ) -> Result<StateMachineChanges<C>, StorageError<C::NodeId>> {
         self.inner().install_snapshot(meta, snapshot).await
     }
@@ -176,7 +222,7 @@ where
     #[tracing::instrument(level = "trace", skip(self))]
     async fn get_current_snapshot(
         &mut self,
-    ) -> Result<Option<Snapshot<C::NodeId, Self::SnapshotData>>, StorageError<C::NodeId>> {
+    ) -> Result<Option<Snapshot<C::NodeId, C::SnapshotData, C::Node>>, StorageError<C::NodeId>> {
         self.inner().get_current_snapshot().await
     }
 
@@ -219,11 +265,17 @@ pub struct SnapshotBuilderExt<C: RaftTypeConfig, T: RaftStorage<C>> {
 }
 
 #[async_trait]
-impl<C: RaftTypeConfig, T: RaftStorage<C>> RaftSnapshotBuilder<C, T::SnapshotData> for SnapshotBuilderExt<C, T> {
+impl<C: RaftTypeConfig, T: RaftStorage<C>> RaftSnapshotBuilder<C, C::SnapshotData> for SnapshotBuilderExt<C, T> {
     #[tracing::instrument(level = "trace", skip(self))]
-    async fn build_snapshot(&mut self) -> Result<Snapshot<C::NodeId, T::SnapshotData>, StorageError<C::NodeId>> {
+    async fn build_snapshot(
+        &mut self,
+    ) -> Result<Snapshot<C::NodeId, C::SnapshotData, C::Node>, StorageError<C::NodeId>> {
         self.inner.build_snapshot().await
     }
+
+    fn set_progress_reporter(&mut self, reporter: SnapshotProgressReporter) {
+        self.inner.set_progress_reporter(reporter)
+    }
 }
 
 /// Extended log reader backed by another impl.
@@ -0,0 +1,284 @@
+use std::fmt::Debug;
+use std::ops::RangeBounds;
+
+use anyerror::AnyError;
+
+use crate::async_trait::async_trait;
+use crate::membership::EffectiveMembership;
+use crate::storage::LogState;
+use crate::storage::RaftLogReader;
+use crate::storage::Snapshot;
+use crate::Entry;
+use crate::EntryPayload;
+use crate::ErrorSubject;
+use crate::ErrorVerb;
+use crate::LeaderId;
+use crate::LogId;
+use crate::RaftStorage;
+use crate::RaftStorageDebug;
+use crate::RaftTypeConfig;
+use crate::SnapshotMeta;
+use crate::StateMachineChanges;
+use crate::StorageError;
+use crate::StorageIOError;
+use crate::Vote;
+use crate::Wrapper;
+
+/// Rewrites the application data of a log entry when it is stored, e.g., to encrypt it at rest.
+///
+/// `decode()` must be the inverse of `encode()`. See [`TransformStore`].
+pub trait PayloadTransform<C: RaftTypeConfig>: Send + Sync + 'static {
+    /// Rewrite the application data of an entry right before it is appended to the log store.
+    fn encode(&self, data: &C::D) -> Result<C::D, AnyError>;
+
+    /// Restore the application data of an entry right after it is read from the log store.
+    fn decode(&self, data: &C::D) -> Result<C::D, AnyError>;
+}
+
+/// A store that transforms the application data of every log entry stored in another store.
+///
+/// The data of an [`EntryPayload::Normal`] entry is encoded by a [`PayloadTransform`] before it is appended to the
+/// inner store, and is decoded when it is read back, i.e., when it is read to be replicated or applied. Thus the
+/// inner store only sees the encoded data, while raft replicates and applies the original data. The log id of an
+/// entry is never changed.
+///
+/// Blank and membership entries are not transformed: raft reads the membership config from the log.
+/// The state machine and snapshots are not transformed either.
+///
+/// Every node decodes the entries it replicates and encodes the entries it receives, thus every node must use the
+/// same transform to hold the same bytes at rest.
+pub struct TransformStore<C: RaftTypeConfig, S: RaftStorage<C>, T: PayloadTransform<C>> {
+    inner: S,
+    transform: std::sync::Arc<T>,
+    c: std::marker::PhantomData<C>,
+}
+
+impl<C: RaftTypeConfig, S: RaftStorage<C>, T: PayloadTransform<C>> TransformStore<C, S, T> {
+    /// Create a store that transforms the entries stored in `inner`.
+    pub fn new(inner: S, transform: T) -> Self {
+        Self {
+            inner,
+            transform: std::sync::Arc::new(transform),
+            c: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<C: RaftTypeConfig, S: RaftStorage<C> + Clone, T: PayloadTransform<C>> Clone for TransformStore<C, S, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            transform: self.transform.clone(),
+            c: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<C: RaftTypeConfig, S: RaftStorage<C> + Default, T: PayloadTransform<C> + Default> Default
+    for TransformStore<C, S, T>
+{
+    fn default() -> Self {
+        Self::new(S::default(), T::default())
+    }
+}
+
+impl<C: RaftTypeConfig, S: RaftStorage<C>, T: PayloadTransform<C>> Wrapper<C, S> for TransformStore<C, S, T> {
+    fn inner(&mut self) -> &mut S {
+        &mut self.inner
+    }
+}
+
+fn encode_entries<C: RaftTypeConfig, T: PayloadTransform<C>>(
+    transform: &T,
+    entries: &[&Entry<C>],
+) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+    let mut res = Vec::with_capacity(entries.len());
+
+    for ent in entries {
+        let payload = match &ent.payload {
+            EntryPayload::Normal(data) => {
+                let encoded = transform
+                    .encode(data)
+                    .map_err(|e| StorageIOError::new(ErrorSubject::Log(ent.log_id.clone()), ErrorVerb::Write, e))?;
+                EntryPayload::Normal(encoded)
+            }
+            p => p.clone(),
+        };
+
+        res.push(Entry {
+            log_id: ent.log_id.clone(),
+            payload,
+            context: ent.context.clone(),
+        });
+    }
+
+    Ok(res)
+}
+
+fn decode_entries<C: RaftTypeConfig, T: PayloadTransform<C>>(
+    transform: &T,
+    entries: Vec<Entry<C>>,
+) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+    let mut res = Vec::with_capacity(entries.len());
+
+    for mut ent in entries {
+        if let EntryPayload::Normal(data) = &ent.payload {
+            let decoded = transform
+                .decode(data)
+                .map_err(|e| StorageIOError::new(ErrorSubject::Log(ent.log_id.clone()), ErrorVerb::Read, e))?;
+            ent.payload = EntryPayload::Normal(decoded);
+        }
+
+        res.push(ent);
+    }
+
+    Ok(res)
+}
+
+#[async_trait]
+impl<C, S, T, SM> RaftStorageDebug<SM> for TransformStore<C, S, T>
+where
+    C: RaftTypeConfig,
+    S: RaftStorage<C> + RaftStorageDebug<SM>,
+    T: PayloadTransform<C>,
+{
+    async fn get_state_machine(&mut self) -> SM {
+        self.inner.get_state_machine().await
+    }
+}
+
+#[async_trait]
+impl<C: RaftTypeConfig, S: RaftStorage<C>, T: PayloadTransform<C>> RaftStorage<C> for TransformStore<C, S, T> {
+    type LogReader = TransformLogReader<C, S, T>;
+
+    type SnapshotBuilder = S::SnapshotBuilder;
+
+    async fn save_vote(&mut self, vote: &Vote<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.inner.save_vote(vote).await
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>> {
+        self.inner.read_vote().await
+    }
+
+    async fn save_committed(&mut self, committed: Option<LogId<C::NodeId>>) -> Result<(), StorageError<C::NodeId>> {
+        self.inner.save_committed(committed).await
+    }
+
+    async fn read_committed(&mut self) -> Result<Option<LogId<C::NodeId>>, StorageError<C::NodeId>> {
+        self.inner.read_committed().await
+    }
+
+    async fn get_log_reader(&mut self) -> Self::LogReader {
+        TransformLogReader {
+            inner: self.inner.get_log_reader().await,
+            transform: self.transform.clone(),
+        }
+    }
+
+    #[tracing::instrument(level = "trace", skip(self, entries))]
+    async fn append_to_log(&mut self, entries: &[&Entry<C>]) -> Result<(), StorageError<C::NodeId>> {
+        let encoded = encode_entries(self.transform.as_ref(), entries)?;
+        let entry_refs = encoded.iter().collect::<Vec<_>>();
+        self.inner.append_to_log(&entry_refs).await
+    }
+
+    #[tracing::instrument(level = "trace", skip(self, entries))]
+    async fn append_to_log_with_vote(
+        &mut self,
+        vote: &Vote<C::NodeId>,
+        entries: &[&Entry<C>],
+    ) -> Result<(), StorageError<C::NodeId>> {
+        let encoded = encode_entries(self.transform.as_ref(), entries)?;
+        let entry_refs = encoded.iter().collect::<Vec<_>>();
+        self.inner.append_to_log_with_vote(vote, &entry_refs).await
+    }
+
+    async fn delete_conflict_logs_since(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.inner.delete_conflict_logs_since(log_id).await
+    }
+
+    async fn purge_logs_upto(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.inner.purge_logs_upto(log_id).await
+    }
+
+    async fn last_applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<C::NodeId>>, EffectiveMembership<C::NodeId, C::Node>), StorageError<C::NodeId>> {
+        self.inner.last_applied_state().await
+    }
+
+    async fn apply_to_state_machine(&mut self, entries: &[&Entry<C>]) -> Result<Vec<C::R>, StorageError<C::NodeId>> {
+        // Entries to apply are already decoded or have never been encoded.
+        self.inner.apply_to_state_machine(entries).await
+    }
+
+    async fn try_apply_to_state_machine(
+        &mut self,
+        entries: &[&Entry<C>],
+    ) -> Result<Vec<Result<C::R, C::AppError>>, StorageError<C::NodeId>> {
+        self.inner.try_apply_to_state_machine(entries).await
+    }
+
+    async fn leader_established_data(&mut self, leader_id: &LeaderId<C::NodeId>) -> Option<C::D> {
+        self.inner.leader_established_data(leader_id).await
+    }
+
+    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder {
+        self.inner.get_snapshot_builder().await
+    }
+
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<C::SnapshotData>, StorageError<C::NodeId>> {
+        self.inner.begin_receiving_snapshot().await
+    }
+
+    async fn install_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<C::NodeId, C::Node>,
+        snapshot: Box<C::SnapshotData>,
+    ) -> Result<StateMachineChanges<C>, StorageError<C::NodeId>> {
+        self.inner.install_snapshot(meta, snapshot).await
+    }
+
+    async fn get_current_snapshot(
+        &mut self,
+    ) -> Result<Option<Snapshot<C::NodeId, C::SnapshotData, C::Node>>, StorageError<C::NodeId>> {
+        self.inner.get_current_snapshot().await
+    }
+}
+
+#[async_trait]
+impl<C: RaftTypeConfig, S: RaftStorage<C>, T: PayloadTransform<C>> RaftLogReader<C> for TransformStore<C, S, T> {
+    async fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>> {
+        self.inner.get_log_state().await
+    }
+
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+        let entries = self.inner.try_get_log_entries(range).await?;
+        decode_entries(self.transform.as_ref(), entries)
+    }
+}
+
+/// A log reader that decodes the entries read from the log reader of the inner store of a [`TransformStore`].
+pub struct TransformLogReader<C: RaftTypeConfig, S: RaftStorage<C>, T: PayloadTransform<C>> {
+    inner: S::LogReader,
+    transform: std::sync::Arc<T>,
+}
+
+#[async_trait]
+impl<C: RaftTypeConfig, S: RaftStorage<C>, T: PayloadTransform<C>> RaftLogReader<C> for TransformLogReader<C, S, T> {
+    async fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>> {
+        self.inner.get_log_state().await
+    }
+
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+        let entries = self.inner.try_get_log_entries(range).await?;
+        decode_entries(self.transform.as_ref(), entries)
+    }
+}
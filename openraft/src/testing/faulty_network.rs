@@ -0,0 +1,523 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyerror::AnyError;
+use async_trait::async_trait;
+
+use crate::error::AppendEntriesError;
+use crate::error::ClientWriteError;
+use crate::error::Fatal;
+use crate::error::InstallSnapshotError;
+use crate::error::NetworkError;
+use crate::error::RPCError;
+use crate::error::StreamingError;
+use crate::error::Timeout;
+use crate::error::Unreachable;
+use crate::error::VoteError;
+use crate::network::BackoffPolicy;
+use crate::network::SnapshotStreaming;
+use crate::raft::AppendEntriesRequest;
+use crate::raft::AppendEntriesResponse;
+use crate::raft::ClientWriteResponse;
+use crate::raft::ForwardClientWriteRequest;
+use crate::raft::InstallSnapshotRequest;
+use crate::raft::InstallSnapshotResponse;
+use crate::raft::SnapshotFromRequest;
+use crate::raft::SnapshotFromResponse;
+use crate::raft::SnapshotResponse;
+use crate::raft::TimeoutNowRequest;
+use crate::raft::TimeoutNowResponse;
+use crate::raft::VoteRequest;
+use crate::raft::VoteResponse;
+use crate::rng;
+use crate::storage::Snapshot;
+use crate::AsyncRuntime;
+use crate::Config;
+use crate::RPCOption;
+use crate::RPCTypes;
+use crate::RaftNetwork;
+use crate::RaftNetworkFactory;
+use crate::RaftTypeConfig;
+use crate::Vote;
+
+/// The faults of the RPCs sent from one node to another, i.e., of a one-way link.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinkFaults {
+    /// The probability, in `[0, 1]`, that an RPC is dropped. The sender sees a dropped RPC time out.
+    pub drop_rate: f64,
+
+    /// The delay before an RPC is delivered.
+    pub latency: Duration,
+
+    /// The upper bound of a random delay added to `latency`.
+    pub jitter: Duration,
+
+    /// The target can not be reached: every RPC fails with [`RPCError::Unreachable`].
+    pub partitioned: bool,
+
+    /// Deliver every Vote, AppendEntries and InstallSnapshot RPC twice. The sender receives the response to the
+    /// second one.
+    pub duplicate: bool,
+}
+
+/// What to do with an RPC, as decided by a hook set with [`NetworkFaults::set_rpc_hook()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RPCFault {
+    /// Deliver it, subject to the faults of the link.
+    Deliver,
+
+    /// Drop it: the sender sees it time out.
+    Drop,
+
+    /// Fail it with [`RPCError::Unreachable`].
+    Unreachable,
+
+    /// Fail it with [`RPCError::Network`] carrying the message.
+    Reject(String),
+}
+
+/// Decides the fault of an RPC by `(from, to, kind)`.
+pub type RPCHook<NID> = Arc<dyn Fn(&NID, &NID, RPCTypes) -> RPCFault + Send + Sync>;
+
+/// Modifies an AppendEntries RPC sent `from` a node `to` another before it is delivered, e.g., to corrupt it.
+pub type AppendEntriesHook<C> = Arc<
+    dyn Fn(&<C as RaftTypeConfig>::NodeId, &<C as RaftTypeConfig>::NodeId, &mut AppendEntriesRequest<C>) + Send + Sync,
+>;
+
+/// The faults of a network, shared by the [`FaultyNetwork`] of every node, and adjusted by a test at runtime.
+///
+/// A link without faults set delivers every RPC at once. A cloned `NetworkFaults` controls the same network.
+pub struct NetworkFaults<C: RaftTypeConfig> {
+    state: Arc<Mutex<FaultsState<C>>>,
+
+    /// The number of RPCs dropped.
+    dropped: Arc<AtomicU64>,
+
+    /// The number of RPCs delivered twice.
+    duplicated: Arc<AtomicU64>,
+}
+
+struct FaultsState<C: RaftTypeConfig> {
+    links: BTreeMap<(C::NodeId, C::NodeId), LinkFaults>,
+
+    /// Nodes that can neither send nor receive RPCs.
+    isolated: BTreeSet<C::NodeId>,
+
+    rpc_hook: Option<RPCHook<C::NodeId>>,
+
+    append_entries_hook: Option<AppendEntriesHook<C>>,
+}
+
+impl<C: RaftTypeConfig> Default for NetworkFaults<C> {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(FaultsState {
+                links: BTreeMap::new(),
+                isolated: BTreeSet::new(),
+                rpc_hook: None,
+                append_entries_hook: None,
+            })),
+            dropped: Default::default(),
+            duplicated: Default::default(),
+        }
+    }
+}
+
+impl<C: RaftTypeConfig> Clone for NetworkFaults<C> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            dropped: self.dropped.clone(),
+            duplicated: self.duplicated.clone(),
+        }
+    }
+}
+
+impl<C: RaftTypeConfig> NetworkFaults<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The faults of the link `from` a node `to` another.
+    pub fn link(&self, from: &C::NodeId, to: &C::NodeId) -> LinkFaults {
+        let st = self.state.lock().unwrap();
+        st.links.get(&(from.clone(), to.clone())).cloned().unwrap_or_default()
+    }
+
+    /// Set the faults of the link `from` a node `to` another.
+    pub fn set_link(&self, from: C::NodeId, to: C::NodeId, faults: LinkFaults) {
+        let mut st = self.state.lock().unwrap();
+        st.links.insert((from, to), faults);
+    }
+
+    /// Change the faults of the link `from` a node `to` another with `f`.
+    pub fn update_link(&self, from: C::NodeId, to: C::NodeId, f: impl FnOnce(&mut LinkFaults)) {
+        let mut st = self.state.lock().unwrap();
+        f(st.links.entry((from, to)).or_default());
+    }
+
+    /// Drop RPCs `from` a node `to` another with probability `drop_rate`.
+    pub fn set_drop_rate(&self, from: C::NodeId, to: C::NodeId, drop_rate: f64) {
+        self.update_link(from, to, |l| l.drop_rate = drop_rate);
+    }
+
+    /// Delay RPCs `from` a node `to` another by `latency`, plus a random delay upto `jitter`.
+    pub fn set_latency(&self, from: C::NodeId, to: C::NodeId, latency: Duration, jitter: Duration) {
+        self.update_link(from, to, |l| {
+            l.latency = latency;
+            l.jitter = jitter;
+        });
+    }
+
+    /// Deliver RPCs `from` a node `to` another twice, or not.
+    pub fn set_duplicate(&self, from: C::NodeId, to: C::NodeId, duplicate: bool) {
+        self.update_link(from, to, |l| l.duplicate = duplicate);
+    }
+
+    /// Stop RPCs `from` a node `to` another, while the other way is not affected.
+    pub fn partition(&self, from: C::NodeId, to: C::NodeId) {
+        self.update_link(from, to, |l| l.partitioned = true);
+    }
+
+    /// Stop every RPC sent from or to `node`.
+    pub fn isolate(&self, node: C::NodeId) {
+        let mut st = self.state.lock().unwrap();
+        st.isolated.insert(node);
+    }
+
+    /// Restore the RPCs `from` a node `to` another stopped by [`partition()`](`Self::partition`).
+    pub fn heal(&self, from: C::NodeId, to: C::NodeId) {
+        self.update_link(from, to, |l| l.partitioned = false);
+    }
+
+    /// Restore the RPCs from and to `node` stopped by [`isolate()`](`Self::isolate`).
+    pub fn restore(&self, node: &C::NodeId) {
+        let mut st = self.state.lock().unwrap();
+        st.isolated.remove(node);
+    }
+
+    /// Remove every fault, including the hooks.
+    pub fn heal_all(&self) {
+        let mut st = self.state.lock().unwrap();
+        st.links.clear();
+        st.isolated.clear();
+        st.rpc_hook = None;
+        st.append_entries_hook = None;
+    }
+
+    /// Decide the fault of every RPC by its kind with `hook`, before the faults of the link apply. `None` removes it.
+    pub fn set_rpc_hook(&self, hook: Option<RPCHook<C::NodeId>>) {
+        let mut st = self.state.lock().unwrap();
+        st.rpc_hook = hook;
+    }
+
+    /// Modify every AppendEntries RPC with `hook` before it is delivered. `None` removes it.
+    pub fn set_append_entries_hook(&self, hook: Option<AppendEntriesHook<C>>) {
+        let mut st = self.state.lock().unwrap();
+        st.append_entries_hook = hook;
+    }
+
+    /// The number of RPCs dropped so far.
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// The number of RPCs delivered twice so far.
+    pub fn duplicated_count(&self) -> u64 {
+        self.duplicated.load(Ordering::Relaxed)
+    }
+
+    /// Decide what happens to an RPC of `kind` `from` a node `to` another.
+    fn verdict(&self, from: &C::NodeId, to: &C::NodeId, kind: RPCTypes) -> Verdict {
+        let (link, rpc_hook) = {
+            let st = self.state.lock().unwrap();
+            if st.isolated.contains(from) || st.isolated.contains(to) {
+                return Verdict::Fail(RPCFault::Unreachable);
+            }
+            let link = st.links.get(&(from.clone(), to.clone())).cloned().unwrap_or_default();
+            (link, st.rpc_hook.clone())
+        };
+
+        if link.partitioned {
+            return Verdict::Fail(RPCFault::Unreachable);
+        }
+
+        // The hook is called without holding the lock, thus it can adjust the faults.
+        if let Some(hook) = rpc_hook {
+            let fault = hook(from, to, kind);
+            if fault != RPCFault::Deliver {
+                return Verdict::Fail(fault);
+            }
+        }
+
+        if link.drop_rate > 0.0 && rng::gen_range(0.0..1.0) < link.drop_rate {
+            return Verdict::Fail(RPCFault::Drop);
+        }
+
+        let mut delay = link.latency;
+        if !link.jitter.is_zero() {
+            delay += Duration::from_micros(rng::gen_range(0..=link.jitter.as_micros() as u64));
+        }
+
+        Verdict::Deliver {
+            delay,
+            duplicate: link.duplicate,
+        }
+    }
+
+    fn corrupt_append_entries(&self, from: &C::NodeId, to: &C::NodeId, rpc: &mut AppendEntriesRequest<C>) {
+        let hook = self.state.lock().unwrap().append_entries_hook.clone();
+        if let Some(hook) = hook {
+            hook(from, to, rpc);
+        }
+    }
+}
+
+enum Verdict {
+    Deliver { delay: Duration, duplicate: bool },
+    Fail(RPCFault),
+}
+
+/// A [`RaftNetworkFactory`] that injects the faults in [`NetworkFaults`] into the RPCs sent by the clients of
+/// another factory.
+///
+/// Every node is given its own `FaultyNetwork`, which knows the sending node, and all of them share one
+/// `NetworkFaults` that the test adjusts at runtime, e.g.:
+///
+/// ```ignore
+/// let faults = NetworkFaults::new();
+/// let network = FaultyNetwork::new(id, MyNetwork::new(), faults.clone());
+/// let raft = Raft::new(id, config, network, log_store, state_machine).await?;
+///
+/// faults.isolate(2);
+/// // ...
+/// faults.heal_all();
+/// ```
+///
+/// The faults apply to every RPC, including the snapshot sent as a whole with `full_snapshot()`.
+pub struct FaultyNetwork<C: RaftTypeConfig, N: RaftNetworkFactory<C>> {
+    id: C::NodeId,
+    inner: N,
+    faults: NetworkFaults<C>,
+}
+
+impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>> FaultyNetwork<C, N> {
+    /// Wrap the network factory `inner` of node `id`.
+    pub fn new(id: C::NodeId, inner: N, faults: NetworkFaults<C>) -> Self {
+        Self { id, inner, faults }
+    }
+
+    /// The faults injected into this network.
+    pub fn faults(&self) -> &NetworkFaults<C> {
+        &self.faults
+    }
+}
+
+#[async_trait]
+impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>> RaftNetworkFactory<C> for FaultyNetwork<C, N> {
+    type Network = FaultyConnection<C, N::Network>;
+
+    async fn connect(&mut self, target: C::NodeId, node: Option<&C::Node>) -> Self::Network {
+        let inner = self.inner.connect(target.clone(), node).await;
+
+        FaultyConnection {
+            id: self.id.clone(),
+            target,
+            inner,
+            faults: self.faults.clone(),
+        }
+    }
+}
+
+/// A client created by [`FaultyNetwork`], sending RPCs to `target` with the inner client.
+pub struct FaultyConnection<C: RaftTypeConfig, T: RaftNetwork<C>> {
+    id: C::NodeId,
+    target: C::NodeId,
+    inner: T,
+    faults: NetworkFaults<C>,
+}
+
+impl<C: RaftTypeConfig, T: RaftNetwork<C>> FaultyConnection<C, T> {
+    /// Apply the faults to an RPC of `kind`, and return whether to deliver it twice if it is delivered.
+    ///
+    /// A dropped RPC returns after `ttl`, as if the sender waited for it in vain.
+    async fn check<E: Error>(&self, kind: RPCTypes, ttl: Duration) -> Result<bool, RPCError<C::NodeId, E, C::Node>> {
+        let fault = match self.faults.verdict(&self.id, &self.target, kind.clone()) {
+            Verdict::Deliver { delay, duplicate } => {
+                if !delay.is_zero() {
+                    C::AsyncRuntime::sleep(delay).await;
+                }
+                return Ok(duplicate);
+            }
+            Verdict::Fail(fault) => fault,
+        };
+
+        let msg = format!("injected fault {:?}: {} -> {} {}", fault, self.id, self.target, kind);
+        let err = match fault {
+            RPCFault::Deliver => unreachable!("delivered RPCs are returned above"),
+            RPCFault::Drop => {
+                self.faults.dropped.fetch_add(1, Ordering::Relaxed);
+                if !ttl.is_zero() {
+                    C::AsyncRuntime::sleep(ttl).await;
+                }
+                RPCError::Timeout(Timeout {
+                    action: kind,
+                    id: self.id.clone(),
+                    target: self.target.clone(),
+                    timeout: ttl,
+                })
+            }
+            RPCFault::Unreachable => Unreachable::new(&AnyError::error(msg)).into(),
+            RPCFault::Reject(reason) => NetworkError::new(&AnyError::error(format!("{}: {}", msg, reason))).into(),
+        };
+
+        Err(err)
+    }
+
+    fn count_duplicate(&self, duplicate: bool) -> bool {
+        if duplicate {
+            self.faults.duplicated.fetch_add(1, Ordering::Relaxed);
+        }
+        duplicate
+    }
+}
+
+#[async_trait]
+impl<C: RaftTypeConfig, T: RaftNetwork<C>> RaftNetwork<C> for FaultyConnection<C, T> {
+    async fn send_append_entries(
+        &mut self,
+        mut rpc: AppendEntriesRequest<C>,
+    ) -> Result<AppendEntriesResponse<C::NodeId>, RPCError<C::NodeId, AppendEntriesError<C::NodeId>, C::Node>> {
+        let duplicate = self.check::<AppendEntriesError<C::NodeId>>(RPCTypes::AppendEntries, Duration::ZERO).await?;
+        self.faults.corrupt_append_entries(&self.id, &self.target, &mut rpc);
+
+        if self.count_duplicate(duplicate) {
+            let _ = self.inner.send_append_entries(rpc.clone()).await;
+        }
+        self.inner.send_append_entries(rpc).await
+    }
+
+    async fn send_install_snapshot(
+        &mut self,
+        rpc: InstallSnapshotRequest<C>,
+    ) -> Result<InstallSnapshotResponse<C::NodeId>, RPCError<C::NodeId, InstallSnapshotError<C::NodeId>, C::Node>> {
+        let duplicate =
+            self.check::<InstallSnapshotError<C::NodeId>>(RPCTypes::InstallSnapshot, Duration::ZERO).await?;
+
+        if self.count_duplicate(duplicate) {
+            let _ = self.inner.send_install_snapshot(rpc.clone()).await;
+        }
+        self.inner.send_install_snapshot(rpc).await
+    }
+
+    async fn send_vote(
+        &mut self,
+        rpc: VoteRequest<C::NodeId>,
+    ) -> Result<VoteResponse<C::NodeId>, RPCError<C::NodeId, VoteError<C::NodeId>, C::Node>> {
+        let duplicate = self.check::<VoteError<C::NodeId>>(RPCTypes::Vote, Duration::ZERO).await?;
+
+        if self.count_duplicate(duplicate) {
+            let _ = self.inner.send_vote(rpc.clone()).await;
+        }
+        self.inner.send_vote(rpc).await
+    }
+
+    fn backoff(&self, config: &Config) -> Box<dyn BackoffPolicy> {
+        self.inner.backoff(config)
+    }
+
+    fn on_connection_error(&mut self) {
+        self.inner.on_connection_error()
+    }
+
+    async fn append_entries(
+        &mut self,
+        mut rpc: AppendEntriesRequest<C>,
+        option: RPCOption,
+    ) -> Result<AppendEntriesResponse<C::NodeId>, RPCError<C::NodeId, AppendEntriesError<C::NodeId>, C::Node>> {
+        let duplicate = self.check::<AppendEntriesError<C::NodeId>>(RPCTypes::AppendEntries, option.hard_ttl()).await?;
+        self.faults.corrupt_append_entries(&self.id, &self.target, &mut rpc);
+
+        if self.count_duplicate(duplicate) {
+            let _ = self.inner.append_entries(rpc.clone(), option.clone()).await;
+        }
+        self.inner.append_entries(rpc, option).await
+    }
+
+    async fn install_snapshot(
+        &mut self,
+        rpc: InstallSnapshotRequest<C>,
+        option: RPCOption,
+    ) -> Result<InstallSnapshotResponse<C::NodeId>, RPCError<C::NodeId, InstallSnapshotError<C::NodeId>, C::Node>> {
+        let duplicate =
+            self.check::<InstallSnapshotError<C::NodeId>>(RPCTypes::InstallSnapshot, option.hard_ttl()).await?;
+
+        if self.count_duplicate(duplicate) {
+            let _ = self.inner.install_snapshot(rpc.clone(), option.clone()).await;
+        }
+        self.inner.install_snapshot(rpc, option).await
+    }
+
+    async fn full_snapshot(
+        &mut self,
+        vote: Vote<C::NodeId>,
+        snapshot: Snapshot<C::NodeId, C::SnapshotData, C::Node>,
+        streaming: SnapshotStreaming,
+    ) -> Result<SnapshotResponse<C::NodeId>, StreamingError<C::NodeId>> {
+        if let Err(e) = self.check::<Fatal<C::NodeId>>(RPCTypes::InstallSnapshot, Duration::ZERO).await {
+            let err = match e {
+                RPCError::Unreachable(e) => StreamingError::Unreachable(e),
+                other => StreamingError::Network(NetworkError::new(&other)),
+            };
+            return Err(err);
+        }
+
+        self.inner.full_snapshot(vote, snapshot, streaming).await
+    }
+
+    async fn get_snapshot_from(
+        &mut self,
+        rpc: SnapshotFromRequest<C>,
+        option: RPCOption,
+    ) -> Result<SnapshotFromResponse<C::NodeId>, RPCError<C::NodeId, Fatal<C::NodeId>, C::Node>> {
+        self.check::<Fatal<C::NodeId>>(RPCTypes::InstallSnapshot, option.hard_ttl()).await?;
+        self.inner.get_snapshot_from(rpc, option).await
+    }
+
+    async fn vote(
+        &mut self,
+        rpc: VoteRequest<C::NodeId>,
+        option: RPCOption,
+    ) -> Result<VoteResponse<C::NodeId>, RPCError<C::NodeId, VoteError<C::NodeId>, C::Node>> {
+        let duplicate = self.check::<VoteError<C::NodeId>>(RPCTypes::Vote, option.hard_ttl()).await?;
+
+        if self.count_duplicate(duplicate) {
+            let _ = self.inner.vote(rpc.clone(), option.clone()).await;
+        }
+        self.inner.vote(rpc, option).await
+    }
+
+    async fn send_timeout_now(
+        &mut self,
+        rpc: TimeoutNowRequest<C::NodeId>,
+    ) -> Result<TimeoutNowResponse<C::NodeId>, RPCError<C::NodeId, VoteError<C::NodeId>, C::Node>> {
+        self.check::<VoteError<C::NodeId>>(RPCTypes::TimeoutNow, Duration::ZERO).await?;
+        self.inner.send_timeout_now(rpc).await
+    }
+
+    async fn forward_client_write(
+        &mut self,
+        rpc: ForwardClientWriteRequest<C>,
+    ) -> Result<ClientWriteResponse<C>, RPCError<C::NodeId, ClientWriteError<C::NodeId, C::Node, C::AppError>, C::Node>>
+    {
+        self.check::<ClientWriteError<C::NodeId, C::Node, C::AppError>>(RPCTypes::AppendEntries, Duration::ZERO)
+            .await?;
+        self.inner.forward_client_write(rpc).await
+    }
+}
@@ -0,0 +1,154 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use tokio::time::Instant;
+
+use crate::async_runtime::watch;
+use crate::AsyncRuntime;
+use crate::TokioRuntime;
+
+lazy_static::lazy_static! {
+    static ref CLOCK: Clock = Clock::new();
+}
+
+/// A clock for tests, that moves forward only when it is told to once it is paused.
+///
+/// With feature `manual-clock`, [`TokioRuntime`](`crate::TokioRuntime`) reads the time from this clock in
+/// [`AsyncRuntime::now()`](`crate::AsyncRuntime::now`), and `sleep()`, `sleep_until()` and `timeout()` wake up by it.
+/// Thus the election timeout, heartbeats, the leader lease and the time an RPC may take all follow it.
+///
+/// Until it is paused, the clock goes with the wall clock. Once paused, time stops for every Raft node in the process,
+/// and it advances only with [`ManualClock::advance()`], e.g., to fire an election timeout at a precise simulated
+/// instant.
+///
+/// There is only one clock in a process: a test that pauses it should run in its own test binary.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ManualClock;
+
+impl ManualClock {
+    /// The current simulated time.
+    pub fn now() -> Instant {
+        CLOCK.state.lock().unwrap().now()
+    }
+
+    /// Stop the clock at the current time. From now on, it advances only with [`ManualClock::advance()`].
+    pub fn pause() {
+        {
+            let mut st = CLOCK.state.lock().unwrap();
+            st.paused = Some(st.now());
+        }
+        CLOCK.notify();
+    }
+
+    /// Let the clock go with the wall clock again, from the current simulated time.
+    ///
+    /// If the wall clock is ahead of the simulated time, the clock jumps forward to it.
+    pub fn resume() {
+        {
+            let mut st = CLOCK.state.lock().unwrap();
+            if let Some(t) = st.paused.take() {
+                st.offset = t.saturating_duration_since(Instant::now());
+            }
+        }
+        CLOCK.notify();
+    }
+
+    /// Returns `true` if the clock is paused.
+    pub fn is_paused() -> bool {
+        CLOCK.state.lock().unwrap().paused.is_some()
+    }
+
+    /// Move the clock forward by `duration`, and wake up the sleeps that reach their deadline.
+    pub fn advance(duration: Duration) {
+        {
+            let mut st = CLOCK.state.lock().unwrap();
+            match &mut st.paused {
+                Some(t) => *t += duration,
+                None => st.offset += duration,
+            }
+        }
+        CLOCK.notify();
+    }
+
+    /// Wait until the clock reaches `deadline`.
+    ///
+    /// While the clock goes with the wall clock, it waits with `wall_sleep_until`, which sleeps by the wall clock of
+    /// the runtime until the given instant.
+    pub(crate) async fn sleep_until(deadline: Instant, wall_sleep_until: fn(Instant) -> BoxFuture<'static, ()>) {
+        let mut changed = CLOCK.rx.clone();
+
+        loop {
+            // The wall clock time to wake up at, or `None` to only wait for the clock to change.
+            let wake_up_at = {
+                let st = CLOCK.state.lock().unwrap();
+                if st.now() >= deadline {
+                    return;
+                }
+                match st.paused {
+                    Some(_) => None,
+                    None => Some(deadline - st.offset),
+                }
+            };
+
+            match wake_up_at {
+                None => {
+                    let _ = changed.changed().await;
+                }
+                Some(at) => {
+                    let _ = futures::future::select(wall_sleep_until(at), Box::pin(changed.changed())).await;
+                }
+            }
+        }
+    }
+}
+
+/// The error returned by [`TokioRuntime::timeout()`](`crate::AsyncRuntime::timeout`) when the deadline on the
+/// [`ManualClock`] has elapsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("deadline has elapsed")]
+pub struct Elapsed;
+
+struct Clock {
+    state: Mutex<ClockState>,
+
+    /// Notifies the sleeping tasks that the clock is changed.
+    tx: watch::Sender<()>,
+    rx: watch::Receiver<()>,
+}
+
+impl Clock {
+    fn new() -> Self {
+        let (tx, rx) = TokioRuntime::watch(());
+        Self {
+            state: Mutex::new(ClockState {
+                paused: None,
+                offset: Duration::default(),
+            }),
+            tx,
+            rx,
+        }
+    }
+
+    fn notify(&self) {
+        // It never fails: `self.rx` is always kept.
+        let _ = self.tx.send(());
+    }
+}
+
+struct ClockState {
+    /// The time the clock stops at, if it is paused.
+    paused: Option<Instant>,
+
+    /// How far the clock is ahead of the wall clock, when it is not paused.
+    offset: Duration,
+}
+
+impl ClockState {
+    fn now(&self) -> Instant {
+        match self.paused {
+            Some(t) => t,
+            None => Instant::now() + self.offset,
+        }
+    }
+}
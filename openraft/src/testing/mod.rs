@@ -1,6 +1,19 @@
+mod faulty_network;
+#[cfg(feature = "manual-clock")] mod manual_clock;
+#[cfg(feature = "manual-clock")] mod seeded_rng;
 mod store_builder;
 mod suite;
 
+pub use faulty_network::AppendEntriesHook;
+pub use faulty_network::FaultyConnection;
+pub use faulty_network::FaultyNetwork;
+pub use faulty_network::LinkFaults;
+pub use faulty_network::NetworkFaults;
+pub use faulty_network::RPCFault;
+pub use faulty_network::RPCHook;
+#[cfg(feature = "manual-clock")] pub use manual_clock::Elapsed;
+#[cfg(feature = "manual-clock")] pub use manual_clock::ManualClock;
+#[cfg(feature = "manual-clock")] pub use seeded_rng::SeededRng;
 pub use store_builder::DefensiveStoreBuilder;
 pub use store_builder::StoreBuilder;
 pub use suite::Suite;
@@ -0,0 +1,48 @@
+use std::sync::Mutex;
+
+use rand::distributions::uniform::SampleRange;
+use rand::distributions::uniform::SampleUniform;
+use rand::rngs::StdRng;
+use rand::thread_rng;
+use rand::Rng;
+use rand::SeedableRng;
+
+lazy_static::lazy_static! {
+    static ref RNG: Mutex<Option<StdRng>> = Mutex::new(None);
+}
+
+/// A random number generator for tests, that replays the same sequence for the same seed.
+///
+/// With feature `manual-clock`, the election timeouts and the backoff jitter are drawn from it. Until it is seeded,
+/// it draws from the thread local, OS seeded RNG, like a build without the feature does.
+///
+/// Together with [`ManualClock`](`crate::testing::ManualClock`) it makes the timing of a test repeatable. There is
+/// only one generator in a process, shared by every Raft node, thus the sequence a single node sees also depends on
+/// the order in which the nodes draw from it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SeededRng;
+
+impl SeededRng {
+    /// Draw from a generator seeded with `seed` from now on.
+    pub fn seed(seed: u64) {
+        *RNG.lock().unwrap() = Some(StdRng::seed_from_u64(seed));
+    }
+
+    /// Draw from the thread local, OS seeded RNG again.
+    pub fn reset() {
+        *RNG.lock().unwrap() = None;
+    }
+
+    /// Pick a random value in `range`.
+    pub fn gen_range<T, R>(range: R) -> T
+    where
+        T: SampleUniform,
+        R: SampleRange<T>,
+    {
+        let mut rng = RNG.lock().unwrap();
+        match rng.as_mut() {
+            Some(rng) => rng.gen_range(range),
+            None => thread_rng().gen_range(range),
+        }
+    }
+}
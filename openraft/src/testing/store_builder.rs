@@ -23,6 +23,14 @@ where
     where
         Res: Future<Output = Result<Ret, StorageError<C::NodeId>>> + Send,
         Fun: Fn(S) -> Res + Sync + Send;
+
+    /// Restart `store`: drop it and open a store on the data it persisted, as if the process crashed and restarted.
+    ///
+    /// The suite checks with it what a store has to keep across restarts. The default returns `store` itself, which
+    /// suits only a store that keeps nothing on disk: a persistent store should override it to reopen its data.
+    async fn restart(&self, store: S) -> Result<S, StorageError<C::NodeId>> {
+        Ok(store)
+    }
 }
 
 /// A builder for testing [`StoreExt`].
@@ -63,4 +71,11 @@ where
             })
             .await
     }
+
+    async fn restart(&self, store: StoreExt<C, BaseStore>) -> Result<StoreExt<C, BaseStore>, StorageError<C::NodeId>> {
+        let base_store = self.base_builder.restart(store.into_inner()).await?;
+        let sto_ext = StoreExt::new(base_store);
+        sto_ext.set_defensive(true);
+        Ok(sto_ext)
+    }
 }
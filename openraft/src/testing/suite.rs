@@ -13,6 +13,7 @@ use crate::testing::DefensiveStoreBuilder;
 use crate::testing::StoreBuilder;
 use crate::AppData;
 use crate::AppDataResponse;
+use crate::AsyncRuntime;
 use crate::DefensiveError;
 use crate::Entry;
 use crate::EntryPayload;
@@ -42,7 +43,17 @@ macro_rules! btreeset {
 
 /// Test suite to ensure a `RaftStore` impl works as expected.
 ///
+/// Every check panics with a message of the invariant the store violates. The checks of what a store keeps across
+/// restarts use [`StoreBuilder::restart()`], which a persistent store should implement.
+///
 /// Usage:
+///
+/// ```ignore
+/// #[test]
+/// pub fn test_my_store() -> Result<(), StorageError<MyNodeId>> {
+///     Suite::test_all(MyStoreBuilder {})
+/// }
+/// ```
 pub struct Suite<C, S, B>
 where
     C: RaftTypeConfig,
@@ -107,6 +118,14 @@ where
         run_fut(builder.run_test(Self::delete_logs_since_0))?;
         run_fut(builder.run_test(Self::append_to_log))?;
         run_fut(builder.run_test(Self::snapshot_meta))?;
+        run_fut(builder.run_test(Self::build_snapshot_while_applying))?;
+
+        run_fut(Self::vote_survives_restart(builder))?;
+        run_fut(Self::committed_survives_restart(builder))?;
+        run_fut(Self::logs_survive_restart(builder))?;
+        run_fut(Self::all_logs_purged_survive_restart(builder))?;
+        run_fut(Self::snapshot_round_trip(builder))?;
+        run_fut(Self::reapply_after_restart(builder))?;
 
         // run_fut(Suite::apply_single(builder))?;
         // run_fut(Suite::apply_multi(builder))?;
@@ -132,10 +151,12 @@ where
                     &Entry {
                         log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 1),
                         payload: EntryPayload::Blank,
+                        context: None,
                     },
                     &Entry {
                         log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 2),
                         payload: EntryPayload::Membership(Membership::new(vec![btreeset! {3,4,5}], None)),
+                        context: None,
                     },
                 ])
                 .await?;
@@ -151,6 +172,7 @@ where
                 .append_to_log(&[&Entry {
                     log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 1),
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2,3}], None)),
+                    context: None,
                 }])
                 .await?;
 
@@ -175,14 +197,17 @@ where
                     &Entry {
                         log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 2),
                         payload: EntryPayload::Blank,
+                        context: None,
                     },
                     &Entry {
                         log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 3),
                         payload: EntryPayload::Membership(Membership::new(vec![btreeset! {7,8,9}], None)),
+                        context: None,
                     },
                     &Entry {
                         log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 4),
                         payload: EntryPayload::Blank,
+                        context: None,
                     },
                 ])
                 .await?;
@@ -209,6 +234,7 @@ where
                 .append_to_log(&[&Entry {
                     log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 5),
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {10,11}], None)),
+                    context: None,
                 }])
                 .await?;
 
@@ -234,10 +260,12 @@ where
                     &Entry {
                         log_id: log_id(1, 1),
                         payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2,3}], None)),
+                        context: None,
                     },
                     &Entry {
                         log_id: log_id(1, 2),
                         payload: EntryPayload::Membership(Membership::new(vec![btreeset! {3,4,5}], None)),
+                        context: None,
                     },
                 ])
                 .await?;
@@ -250,6 +278,7 @@ where
                 .append_to_log(&[&Entry {
                     log_id: log_id(1, 100),
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {5,6,7}], None)),
+                    context: None,
                 }])
                 .await?;
 
@@ -283,6 +312,7 @@ where
                 .append_to_log(&[&Entry {
                     log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 1),
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2,3}], None)),
+                    context: None,
                 }])
                 .await?;
 
@@ -306,10 +336,12 @@ where
                     &Entry {
                         log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 1),
                         payload: EntryPayload::Blank,
+                        context: None,
                     },
                     &Entry {
                         log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 2),
                         payload: EntryPayload::Membership(Membership::new(vec![btreeset! {3,4,5}], None)),
+                        context: None,
                     },
                 ])
                 .await?;
@@ -332,6 +364,7 @@ where
                 .append_to_log(&[&Entry {
                     log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 1),
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2,3}], None)),
+                    context: None,
                 }])
                 .await?;
 
@@ -354,10 +387,12 @@ where
                     &Entry {
                         log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 2),
                         payload: EntryPayload::Blank,
+                        context: None,
                     },
                     &Entry {
                         log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 3),
                         payload: EntryPayload::Membership(Membership::new(vec![btreeset! {7,8,9}], None)),
+                        context: None,
                     },
                 ])
                 .await?;
@@ -381,10 +416,12 @@ where
                     &Entry {
                         log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 4),
                         payload: EntryPayload::Blank,
+                        context: None,
                     },
                     &Entry {
                         log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 5),
                         payload: EntryPayload::Membership(Membership::new(vec![btreeset! {10,11}], None)),
+                        context: None,
                     },
                 ])
                 .await?;
@@ -405,8 +442,8 @@ where
     }
 
     pub async fn get_initial_state_without_init(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        let initial = StorageHelper::new(&mut store).get_initial_state().await?;
-        assert_eq!(RaftState::default(), initial, "uninitialized state");
+        let initial = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await?;
+        assert_eq!(RaftState::new(NODE_ID.into()), initial, "uninitialized state");
         Ok(())
     }
 
@@ -417,6 +454,7 @@ where
             .append_to_log(&[&blank(0, 0), &blank(1, 1), &Entry {
                 log_id: LogId::new(LeaderId::new(3, NODE_ID.into()), 2),
                 payload: EntryPayload::Blank,
+                context: None,
             }])
             .await?;
 
@@ -424,10 +462,11 @@ where
             .apply_to_state_machine(&[&Entry {
                 log_id: LogId::new(LeaderId::new(3, NODE_ID.into()), 1),
                 payload: EntryPayload::Blank,
+                context: None,
             }])
             .await?;
 
-        let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+        let initial = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await?;
 
         assert_eq!(
             initial.last_log_id(),
@@ -465,15 +504,17 @@ where
                     &Entry {
                         log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 1),
                         payload: EntryPayload::Blank,
+                        context: None,
                     },
                     &Entry {
                         log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 2),
                         payload: EntryPayload::Membership(Membership::new(vec![btreeset! {3,4,5}], None)),
+                        context: None,
                     },
                 ])
                 .await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await?;
 
             assert_eq!(
                 Membership::new(vec![btreeset! {3,4,5}], None),
@@ -487,10 +528,11 @@ where
                 .append_to_log(&[&Entry {
                     log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 1),
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2,3}], None)),
+                    context: None,
                 }])
                 .await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await?;
 
             assert_eq!(
                 Membership::new(vec![btreeset! {3,4,5}], None),
@@ -504,10 +546,11 @@ where
                 .append_to_log(&[&Entry {
                     log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 3),
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2,3}], None)),
+                    context: None,
                 }])
                 .await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await?;
 
             assert_eq!(
                 Membership::new(vec![btreeset! {1,2,3}], None),
@@ -525,6 +568,7 @@ where
             .append_to_log(&[&blank(0, 0), &Entry {
                 log_id: LogId::new(LeaderId::new(2, NODE_ID.into()), 1),
                 payload: EntryPayload::Blank,
+                context: None,
             }])
             .await?;
 
@@ -533,15 +577,17 @@ where
                 &Entry {
                     log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 1),
                     payload: EntryPayload::Blank,
+                    context: None,
                 },
                 &Entry {
                     log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 2),
                     payload: EntryPayload::Blank,
+                    context: None,
                 },
             ])
             .await?;
 
-        let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+        let initial = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await?;
 
         assert_eq!(
             initial.last_log_id(),
@@ -558,7 +604,7 @@ where
 
         store.apply_to_state_machine(&[&blank(3, 1)]).await?;
 
-        let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+        let initial = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await?;
 
         assert_eq!(
             initial.last_log_id(),
@@ -584,7 +630,7 @@ where
 
         tracing::info!("--- empty store, expect []");
         {
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await?;
             assert_eq!(Vec::<LogId<C::NodeId>>::new(), initial.log_ids.key_log_ids());
         }
 
@@ -592,7 +638,7 @@ where
         {
             store.append_to_log(&[&blank(0, 0)]).await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await?;
             assert_eq!(vec![log_id(0, 0, 0)], initial.log_ids.key_log_ids());
         }
 
@@ -600,7 +646,7 @@ where
         {
             store.append_to_log(&[&blank(1, 1), &blank(1, 2), &blank(2, 3)]).await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await?;
             assert_eq!(
                 vec![log_id(0, 0, 0), log_id(1, 0, 1), log_id(2, 0, 3)],
                 initial.log_ids.key_log_ids()
@@ -613,7 +659,7 @@ where
         {
             store.append_to_log(&[&blank(2, 4), &blank(3, 5), &blank(3, 6)]).await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await?;
             assert_eq!(
                 vec![
                     log_id(0, 0, 0),
@@ -632,7 +678,7 @@ where
         {
             store.purge_logs_upto(log_id(0, 0, 0)).await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await?;
             assert_eq!(
                 vec![
                     log_id(0, 0, 0),
@@ -649,7 +695,7 @@ where
         {
             store.purge_logs_upto(log_id(1, 0, 1)).await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await?;
             assert_eq!(
                 vec![log_id(1, 0, 1), log_id(2, 0, 3), log_id(3, 0, 5), log_id(3, 0, 6)],
                 initial.log_ids.key_log_ids()
@@ -660,7 +706,7 @@ where
         {
             store.purge_logs_upto(log_id(1, 0, 2)).await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await?;
             assert_eq!(
                 vec![log_id(1, 0, 2), log_id(2, 0, 3), log_id(3, 0, 5), log_id(3, 0, 6)],
                 initial.log_ids.key_log_ids()
@@ -671,7 +717,7 @@ where
         {
             store.purge_logs_upto(log_id(2, 0, 3)).await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await?;
             assert_eq!(
                 vec![log_id(2, 0, 3), log_id(3, 0, 5), log_id(3, 0, 6)],
                 initial.log_ids.key_log_ids()
@@ -682,7 +728,7 @@ where
         {
             store.purge_logs_upto(log_id(3, 0, 6)).await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await?;
             assert_eq!(vec![log_id(3, 0, 6)], initial.log_ids.key_log_ids());
         }
 
@@ -735,7 +781,7 @@ where
     pub async fn try_get_log_entry(mut store: S) -> Result<(), StorageError<C::NodeId>> {
         Self::feed_10_logs_vote_self(&mut store).await?;
 
-        store.purge_logs_upto(LogId::new(LeaderId::new(0, C::NodeId::default()), 0)).await?;
+        store.purge_logs_upto(LogId::new(LeaderId::new(0, NODE_ID.into()), 0)).await?;
 
         let ent = store.try_get_log_entry(3).await?;
         assert_eq!(
@@ -780,7 +826,7 @@ where
 
             let st = store.get_log_state().await?;
             assert_eq!(
-                Some(LogId::new(LeaderId::new(0, C::NodeId::default()), 0)),
+                Some(LogId::new(LeaderId::new(0, NODE_ID.into()), 0)),
                 st.last_purged_log_id
             );
             assert_eq!(Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 2)), st.last_log_id);
@@ -851,6 +897,7 @@ where
                 .apply_to_state_machine(&[&Entry {
                     log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 3),
                     payload: EntryPayload::Blank,
+                    context: None,
                 }])
                 .await?;
             let log_id = store.get_log_state().await?.last_log_id;
@@ -879,6 +926,7 @@ where
                 .apply_to_state_machine(&[&Entry {
                     log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 3),
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2}], None)),
+                    context: None,
                 }])
                 .await?;
 
@@ -899,6 +947,7 @@ where
                 .apply_to_state_machine(&[&Entry {
                     log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 5),
                     payload: EntryPayload::Blank,
+                    context: None,
                 }])
                 .await?;
 
@@ -1047,6 +1096,7 @@ where
                     &Entry {
                         log_id: log_id(0, 0),
                         payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2}], None)),
+                        context: None,
                     },
                 ])
                 .await?;
@@ -1071,6 +1121,7 @@ where
                     &Entry {
                         log_id: log_id(2, 2),
                         payload: EntryPayload::Membership(Membership::new(vec![btreeset! {3,4}], None)),
+                        context: None,
                     },
                 ])
                 .await?;
@@ -1089,6 +1140,283 @@ where
         Ok(())
     }
 
+    pub async fn build_snapshot_while_applying(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        store
+            .apply_to_state_machine(&[
+                //
+                &Entry {
+                    log_id: log_id(0, 0),
+                    payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2}], None)),
+                    context: None,
+                },
+            ])
+            .await?;
+
+        tracing::info!("--- build a snapshot in another task, while applying logs");
+        {
+            let mut b = store.get_snapshot_builder().await;
+            let building = C::AsyncRuntime::spawn(async move { b.build_snapshot().await });
+
+            for i in 1..=10 {
+                store.apply_to_state_machine(&[&blank(1, i)]).await?;
+            }
+
+            let snap = building.await.expect("snapshot building task panicked")?;
+            let meta = snap.meta;
+            assert!(
+                meta.last_log_id >= log_id(0, 0) && meta.last_log_id <= log_id(1, 10),
+                "snapshot includes the logs applied before it is built, got: {}",
+                meta.last_log_id
+            );
+            assert_eq!(Some(log_id(0, 0)), meta.last_membership.log_id);
+        }
+
+        tracing::info!("--- a snapshot built afterwards includes all applied logs");
+        {
+            let mut b = store.get_snapshot_builder().await;
+            let snap = b.build_snapshot().await?;
+            assert_eq!(log_id(1, 10), snap.meta.last_log_id);
+        }
+
+        Ok(())
+    }
+
+    pub async fn vote_survives_restart(builder: &B) -> Result<(), StorageError<C::NodeId>> {
+        builder
+            .run_test(|mut store| async move {
+                let vote = Vote::new_committed(100, NODE_ID.into());
+                store.save_vote(&vote).await?;
+
+                let mut store = builder.restart(store).await?;
+
+                assert_eq!(
+                    Some(vote),
+                    store.read_vote().await?,
+                    "a vote must be persisted when save_vote() returns, and be read back after a restart"
+                );
+                Ok(())
+            })
+            .await
+    }
+
+    pub async fn committed_survives_restart(builder: &B) -> Result<(), StorageError<C::NodeId>> {
+        builder
+            .run_test(|mut store| async move {
+                Self::feed_10_logs_vote_self(&mut store).await?;
+                store.save_committed(Some(log_id(1, 5))).await?;
+
+                let mut store = builder.restart(store).await?;
+
+                let got = store.read_committed().await?;
+                assert!(
+                    got.is_none() || got == Some(log_id(1, 5)),
+                    "read_committed() must return the log id given to the latest save_committed(), or None if a store \
+                     does not save it, got: {:?}",
+                    got
+                );
+                Ok(())
+            })
+            .await
+    }
+
+    pub async fn logs_survive_restart(builder: &B) -> Result<(), StorageError<C::NodeId>> {
+        builder
+            .run_test(|mut store| async move {
+                Self::feed_10_logs_vote_self(&mut store).await?;
+
+                store.delete_conflict_logs_since(log_id(1, 6)).await?;
+                store.append_to_log(&[&blank(2, 6), &blank(2, 7)]).await?;
+                store.purge_logs_upto(log_id(1, 3)).await?;
+
+                let mut store = builder.restart(store).await?;
+
+                let st = store.get_log_state().await?;
+                assert_eq!(
+                    Some(log_id(1, 3)),
+                    st.last_purged_log_id,
+                    "last_purged_log_id must be the log id given to the latest purge_logs_upto(), after a restart"
+                );
+                assert_eq!(
+                    Some(log_id(2, 7)),
+                    st.last_log_id,
+                    "last_log_id must be the last appended log, not a log deleted by delete_conflict_logs_since()"
+                );
+
+                let logs = store.try_get_log_entries(0..20).await?;
+                assert_eq!(
+                    vec![log_id(1, 4), log_id(1, 5), log_id(2, 6), log_id(2, 7)],
+                    logs.iter().map(|ent| ent.log_id.clone()).collect::<Vec<_>>(),
+                    "the logs after the purged ones are kept in order, and the deleted ones are replaced by those \
+                     appended after them"
+                );
+                Ok(())
+            })
+            .await
+    }
+
+    pub async fn all_logs_purged_survive_restart(builder: &B) -> Result<(), StorageError<C::NodeId>> {
+        builder
+            .run_test(|mut store| async move {
+                Self::feed_10_logs_vote_self(&mut store).await?;
+                store.purge_logs_upto(log_id(1, 10)).await?;
+
+                let mut store = builder.restart(store).await?;
+
+                let st = store.get_log_state().await?;
+                assert_eq!(
+                    Some(log_id(1, 10)),
+                    st.last_purged_log_id,
+                    "last_purged_log_id must be kept when every log is purged"
+                );
+                assert_eq!(
+                    Some(log_id(1, 10)),
+                    st.last_log_id,
+                    "last_log_id must be last_purged_log_id when every log is purged"
+                );
+
+                let logs = store.try_get_log_entries(0..20).await?;
+                assert!(logs.is_empty(), "purged logs must not be returned, got: {:?}", logs);
+
+                store.append_to_log(&[&blank(2, 11)]).await?;
+
+                let st = store.get_log_state().await?;
+                assert_eq!(
+                    Some(log_id(1, 10)),
+                    st.last_purged_log_id,
+                    "appending a log must not change last_purged_log_id"
+                );
+                assert_eq!(
+                    Some(log_id(2, 11)),
+                    st.last_log_id,
+                    "a log appended after every log is purged must be the last log"
+                );
+                Ok(())
+            })
+            .await
+    }
+
+    pub async fn snapshot_round_trip(builder: &B) -> Result<(), StorageError<C::NodeId>> {
+        let membership = Membership::new(vec![btreeset! {1,2}], None);
+
+        tracing::info!("--- build a snapshot");
+        let snap = builder
+            .run_test(|mut store| {
+                let membership = membership.clone();
+                async move {
+                    store
+                        .apply_to_state_machine(&[
+                            &Entry {
+                                log_id: log_id(1, 1),
+                                payload: EntryPayload::Membership(membership),
+                                context: None,
+                            },
+                            &blank(1, 2),
+                            &blank(1, 3),
+                        ])
+                        .await?;
+
+                    let mut b = store.get_snapshot_builder().await;
+                    b.build_snapshot().await
+                }
+            })
+            .await?;
+
+        let meta = snap.meta.clone();
+        assert_eq!(
+            log_id(1, 3),
+            meta.last_log_id,
+            "a snapshot must include every log applied before it is built"
+        );
+
+        tracing::info!("--- install the snapshot on another store");
+        let snap = std::sync::Mutex::new(Some(snap));
+        builder
+            .run_test(|mut store| {
+                let snap = snap.lock().unwrap().take().expect("the test runs once");
+                let membership = membership.clone();
+                async move {
+                    let meta = snap.meta.clone();
+                    store.install_snapshot(&meta, snap.snapshot).await?;
+
+                    let mut store = builder.restart(store).await?;
+
+                    let (applied, last_membership) = store.last_applied_state().await?;
+                    assert_eq!(
+                        Some(log_id(1, 3)),
+                        applied,
+                        "last applied log id must be that of the installed snapshot"
+                    );
+                    assert_eq!(
+                        Some(log_id(1, 1)),
+                        last_membership.log_id,
+                        "last membership must be that of the installed snapshot"
+                    );
+                    assert_eq!(
+                        membership, last_membership.membership,
+                        "last membership must be that of the installed snapshot"
+                    );
+
+                    let current = store.get_current_snapshot().await?;
+                    assert_eq!(
+                        Some(meta),
+                        current.map(|s| s.meta),
+                        "the installed snapshot must be returned by get_current_snapshot(), after a restart"
+                    );
+                    Ok(())
+                }
+            })
+            .await
+    }
+
+    pub async fn reapply_after_restart(builder: &B) -> Result<(), StorageError<C::NodeId>> {
+        builder
+            .run_test(|mut store| async move {
+                let membership = Membership::new(vec![btreeset! {1,2}], None);
+                let entries = vec![
+                    Entry {
+                        log_id: log_id(1, 1),
+                        payload: EntryPayload::Membership(membership.clone()),
+                        context: None,
+                    },
+                    blank(1, 2),
+                    blank(1, 3),
+                    blank(1, 4),
+                ];
+
+                store.apply_to_state_machine(&entries.iter().collect::<Vec<_>>()).await?;
+
+                // A crash before the last applied log id is saved makes openraft apply the logs again.
+                let mut store = builder.restart(store).await?;
+                store.apply_to_state_machine(&entries[2..].iter().collect::<Vec<_>>()).await?;
+
+                let (applied, last_membership) = store.last_applied_state().await?;
+                assert_eq!(
+                    Some(log_id(1, 4)),
+                    applied,
+                    "applying logs again must leave the last applied log id at the last of them"
+                );
+                assert_eq!(
+                    Some(log_id(1, 1)),
+                    last_membership.log_id,
+                    "applying logs again must not change the last membership"
+                );
+
+                let mut b = store.get_snapshot_builder().await;
+                let snap = b.build_snapshot().await?;
+                assert_eq!(
+                    log_id(1, 4),
+                    snap.meta.last_log_id,
+                    "a snapshot built after applying logs again must include them once"
+                );
+                assert_eq!(
+                    membership, snap.meta.last_membership.membership,
+                    "a snapshot built after applying logs again must have the last membership"
+                );
+                Ok(())
+            })
+            .await
+    }
+
     // pub async fn apply_single(mut store: S) -> Result<(), StorageError<C::NodeId>> {
 
     //
@@ -1210,6 +1538,7 @@ where
             sto.append_to_log(&[&Entry {
                 log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), i),
                 payload: EntryPayload::Blank,
+                context: None,
             }])
             .await?;
         }
@@ -1270,12 +1599,14 @@ where
                 .append_to_log(&[&blank(0, 0), &blank(1, 1), &blank(1, 2), &Entry {
                     log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 3),
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2,3}], None)),
+                    context: None,
                 }])
                 .await?;
             store
                 .apply_to_state_machine(&[&blank(0, 0), &blank(2, 1), &Entry {
                     log_id: LogId::new(LeaderId::new(2, NODE_ID.into()), 2),
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {3,4,5}], None)),
+                    context: None,
                 }])
                 .await?;
 
@@ -1320,6 +1651,7 @@ where
                 .append_to_log(&[&blank(0, 0), &blank(1, 1), &blank(1, 2), &Entry {
                     log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 3),
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2,3}], None)),
+                    context: None,
                 }])
                 .await?;
 
@@ -1327,10 +1659,11 @@ where
                 .apply_to_state_machine(&[&blank(0, 0), &blank(2, 1), &Entry {
                     log_id: LogId::new(LeaderId::new(2, NODE_ID.into()), 2),
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {3,4,5}], None)),
+                    context: None,
                 }])
                 .await?;
 
-            let state = StorageHelper::new(&mut store).get_initial_state().await;
+            let state = StorageHelper::new(&mut store).get_initial_state(NODE_ID.into()).await;
             let e = state.unwrap_err().into_defensive().unwrap();
 
             assert!(matches!(e, DefensiveError {
@@ -1450,7 +1783,7 @@ where
 
         store.apply_to_state_machine(&[&blank(0, 0)]).await?;
 
-        store.purge_logs_upto(LogId::new(LeaderId::new(0, C::NodeId::default()), 0)).await?;
+        store.purge_logs_upto(LogId::new(LeaderId::new(0, NODE_ID.into()), 0)).await?;
 
         store.get_log_entries(..).await?;
         store.get_log_entries(5..).await?;
@@ -1524,10 +1857,12 @@ where
                 &Entry {
                     log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 1),
                     payload: EntryPayload::Blank,
+                    context: None,
                 },
                 &Entry {
                     log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 3),
                     payload: EntryPayload::Blank,
+                    context: None,
                 },
             ])
             .await;
@@ -1805,6 +2140,7 @@ where C::NodeId: From<u64> {
     Entry {
         log_id: LogId::new(LeaderId::new(term, NODE_ID.into()), index),
         payload: EntryPayload::Blank,
+        context: None,
     }
 }
 
@@ -5,14 +5,11 @@ use std::time::Duration;
 
 use futures::future::select;
 use futures::future::Either;
-use tokio::sync::oneshot;
-use tokio::sync::oneshot::Receiver;
-use tokio::sync::oneshot::Sender;
-use tokio::time::sleep_until;
-use tokio::time::Instant;
 use tracing::trace_span;
 use tracing::Instrument;
 
+use crate::AsyncRuntime;
+
 pub(crate) trait RaftTimer {
     /// Create a new instance that will call `callback` after `timeout`.
     fn new<F: FnOnce() + Send + 'static>(callback: F, timeout: Duration) -> Self;
@@ -27,33 +24,35 @@ pub(crate) trait RaftTimer {
 /// `callback` is guaranteed to be called at most once.
 ///
 /// The deadline can be updated to a higher value then the old deadline won't trigger the `callback`.
-pub(crate) struct Timeout {
+///
+/// The sleep-notify task is spawned on, and sleeps by the clock of, the runtime `RT`.
+pub(crate) struct Timeout<RT: AsyncRuntime> {
     /// A guard to notify the inner-task to quit when it is dropped.
     // tx is not explicitly used.
     #[allow(dead_code)]
-    tx: Sender<()>,
+    tx: RT::OneshotSender,
 
     /// Shared state for running the sleep-notify task.
-    inner: Arc<TimeoutInner>,
+    inner: Arc<TimeoutInner<RT>>,
 }
 
-pub(crate) struct TimeoutInner {
+pub(crate) struct TimeoutInner<RT: AsyncRuntime> {
     /// The time when this Timeout is created.
     ///
     /// The `relative_deadline` stores timeout deadline relative to `init` in micro second.
     /// Thus a `u64` is enough for it to run for years.
-    init: Instant,
+    init: RT::Instant,
 
     /// The micro seconds since `init` after which the callback will be triggered.
     relative_deadline: AtomicU64,
 }
 
-impl RaftTimer for Timeout {
+impl<RT: AsyncRuntime> RaftTimer for Timeout<RT> {
     fn new<F: FnOnce() + Send + 'static>(callback: F, timeout: Duration) -> Self {
-        let (tx, rx) = oneshot::channel();
+        let (tx, rx) = RT::oneshot();
 
         let inner = TimeoutInner {
-            init: Instant::now(),
+            init: RT::now(),
             relative_deadline: AtomicU64::new(timeout.as_micros() as u64),
         };
 
@@ -64,13 +63,13 @@ impl RaftTimer for Timeout {
             inner: inner.clone(),
         };
 
-        tokio::spawn(inner.sleep_loop(rx, callback).instrument(trace_span!("timeout-loop").or_current()));
+        let _ = RT::spawn(inner.sleep_loop(rx, callback).instrument(trace_span!("timeout-loop").or_current()));
 
         t
     }
 
     fn update_timeout(&self, timeout: Duration) {
-        let since_init = Instant::now() + timeout - self.inner.init;
+        let since_init = RT::now() + timeout - self.inner.init;
 
         let new_at = since_init.as_micros() as u64;
 
@@ -78,11 +77,15 @@ impl RaftTimer for Timeout {
     }
 }
 
-impl TimeoutInner {
+impl<RT: AsyncRuntime> TimeoutInner<RT> {
     /// Sleep until the deadline and send callback if the deadline is not changed.
     /// Otherwise, sleep again.
     #[tracing::instrument(level = "debug", skip_all)]
-    pub(crate) async fn sleep_loop<F: FnOnce() + Send + 'static>(self: Arc<Self>, rx: Receiver<()>, callback: F) {
+    pub(crate) async fn sleep_loop<F: FnOnce() + Send + 'static>(
+        self: Arc<Self>,
+        rx: RT::OneshotReceiver,
+        callback: F,
+    ) {
         let mut wake_up_at = None;
 
         let mut rx = rx;
@@ -101,7 +104,7 @@ impl TimeoutInner {
 
             let deadline = self.init + Duration::from_micros(curr_deadline);
 
-            let either = select(Box::pin(sleep_until(deadline)), rx).await;
+            let either = select(RT::sleep_until(deadline), rx).await;
             rx = match either {
                 Either::Left((_sleep_res, rx)) => {
                     tracing::debug!("sleep returned, continue to check if deadline changed");
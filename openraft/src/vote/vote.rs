@@ -5,7 +5,7 @@ use crate::MessageSummary;
 use crate::NodeId;
 
 /// `Vote` represent the privilege of a node.
-#[derive(Debug, Clone, Copy, Default, PartialOrd, Ord, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 pub struct Vote<NID: NodeId> {
     pub term: u64,
@@ -51,7 +51,7 @@ impl<NID: NodeId> Vote<NID> {
     }
 
     pub fn leader_id(&self) -> LeaderId<NID> {
-        LeaderId::new(self.term, self.node_id)
+        LeaderId::new(self.term, self.node_id.clone())
     }
 
     /// Get the leader node id.
@@ -59,7 +59,7 @@ impl<NID: NodeId> Vote<NID> {
     /// Only when a committed vote is seen(granted by a quorum) the voted node id is a valid leader.
     pub fn leader(&self) -> Option<NID> {
         if self.committed {
-            Some(self.node_id)
+            Some(self.node_id.clone())
         } else {
             None
         }
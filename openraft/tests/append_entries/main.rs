@@ -11,7 +11,19 @@ mod t10_conflict_with_empty_entries;
 mod t20_append_conflicts;
 mod t30_append_inconsistent_log;
 mod t40_append_updates_membership;
+mod t45_save_vote_with_logs;
 mod t50_append_entries_with_bigger_term;
 mod t50_replication_1_voter_to_isolated_learner;
 mod t60_large_heartbeat;
+mod t70_replication_backoff;
+mod t72_replication_rpc_errors;
+mod t74_payload_bytes_limit;
+mod t75_pipelined_replication;
+mod t76_heartbeat_while_replicating;
+mod t78_reconnect_on_connection_error;
+mod t79_faulty_network;
+#[cfg(feature = "compression")] mod t80_append_compressed_entries;
+mod t81_replication_prefetch;
+mod t82_commit_quorum;
+mod t85_leader_crash_before_log_flushed;
 mod t90_issue_216_stale_last_log_id;
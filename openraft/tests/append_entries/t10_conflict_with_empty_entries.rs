@@ -49,6 +49,8 @@ async fn conflict_with_empty_entries() -> Result<()> {
         prev_log_id: Some(LogId::new(LeaderId::new(1, 0), 5)),
         entries: vec![],
         leader_commit: Some(LogId::new(LeaderId::new(1, 0), 5)),
+        compressed_entries: None,
+        leader_transfer_to: None,
     };
 
     let resp = router.connect(0, None).await.send_append_entries(rpc).await?;
@@ -60,15 +62,22 @@ async fn conflict_with_empty_entries() -> Result<()> {
     let rpc = AppendEntriesRequest::<memstore::Config> {
         vote: Vote::new_committed(1, 1),
         prev_log_id: None,
-        entries: vec![blank(0, 0), blank(1, 1), Entry {
-            log_id: LogId::new(LeaderId::new(1, 0), 2),
-            payload: EntryPayload::Normal(ClientRequest {
-                client: "foo".to_string(),
-                serial: 1,
-                status: "bar".to_string(),
+        entries: vec![
+            Arc::new(blank(0, 0)),
+            Arc::new(blank(1, 1)),
+            Arc::new(Entry {
+                log_id: LogId::new(LeaderId::new(1, 0), 2),
+                payload: EntryPayload::Normal(ClientRequest {
+                    client: "foo".to_string(),
+                    serial: 1,
+                    status: "bar".to_string(),
+                }),
+                context: None,
             }),
-        }],
+        ],
         leader_commit: Some(LogId::new(LeaderId::new(1, 0), 5)),
+        compressed_entries: None,
+        leader_transfer_to: None,
     };
 
     let resp = router.connect(0, None).await.send_append_entries(rpc).await?;
@@ -82,6 +91,8 @@ async fn conflict_with_empty_entries() -> Result<()> {
         prev_log_id: Some(LogId::new(LeaderId::new(1, 0), 3)),
         entries: vec![],
         leader_commit: Some(LogId::new(LeaderId::new(1, 0), 5)),
+        compressed_entries: None,
+        leader_transfer_to: None,
     };
 
     let resp = router.connect(0, None).await.send_append_entries(rpc).await?;
@@ -43,6 +43,8 @@ async fn append_conflicts() -> Result<()> {
         prev_log_id: None,
         entries: vec![],
         leader_commit: Some(LogId::new(LeaderId::new(1, 0), 2)),
+        compressed_entries: None,
+        leader_transfer_to: None,
     };
 
     let resp = r0.append_entries(req.clone()).await?;
@@ -55,8 +57,10 @@ async fn append_conflicts() -> Result<()> {
     let req = AppendEntriesRequest {
         vote: Vote::new_committed(1, 0),
         prev_log_id: None,
-        entries: vec![blank(0, 0)],
+        entries: vec![Arc::new(blank(0, 0))],
         leader_commit: Some(LogId::new(LeaderId::new(1, 0), 2)),
+        compressed_entries: None,
+        leader_transfer_to: None,
     };
 
     let resp = r0.append_entries(req.clone()).await?;
@@ -70,6 +74,8 @@ async fn append_conflicts() -> Result<()> {
         prev_log_id: Some(LogId::new(LeaderId::new(0, 0), 0)),
         entries: vec![],
         leader_commit: Some(LogId::new(LeaderId::new(1, 0), 2)),
+        compressed_entries: None,
+        leader_transfer_to: None,
     };
 
     let resp = r0.append_entries(req.clone()).await?;
@@ -83,9 +89,16 @@ async fn append_conflicts() -> Result<()> {
     let req = AppendEntriesRequest {
         vote: Vote::new_committed(1, 0),
         prev_log_id: Some(LogId::new(LeaderId::new(0, 0), 0)),
-        entries: vec![blank(1, 1), blank(1, 2), blank(1, 3), blank(1, 4)],
+        entries: vec![
+            Arc::new(blank(1, 1)),
+            Arc::new(blank(1, 2)),
+            Arc::new(blank(1, 3)),
+            Arc::new(blank(1, 4)),
+        ],
         // this set the last_applied to 2
         leader_commit: Some(LogId::new(LeaderId::new(1, 0), 2)),
+        compressed_entries: None,
+        leader_transfer_to: None,
     };
 
     let resp = r0.append_entries(req.clone()).await?;
@@ -108,8 +121,10 @@ async fn append_conflicts() -> Result<()> {
     let req = AppendEntriesRequest {
         vote: Vote::new_committed(1, 0),
         prev_log_id: Some(LogId::new(LeaderId::new(1, 0), 1)),
-        entries: vec![blank(1, 2)],
+        entries: vec![Arc::new(blank(1, 2))],
         leader_commit: Some(LogId::new(LeaderId::new(1, 0), 2)),
+        compressed_entries: None,
+        leader_transfer_to: None,
     };
 
     let resp = r0.append_entries(req).await?;
@@ -123,9 +138,11 @@ async fn append_conflicts() -> Result<()> {
     let req = AppendEntriesRequest {
         vote: Vote::new_committed(1, 0),
         prev_log_id: Some(LogId::new(LeaderId::new(1, 0), 2)),
-        entries: vec![blank(2, 3)],
+        entries: vec![Arc::new(blank(2, 3))],
         // this set the last_applied to 2
         leader_commit: Some(LogId::new(LeaderId::new(1, 0), 2)),
+        compressed_entries: None,
+        leader_transfer_to: None,
     };
 
     let resp = r0.append_entries(req).await?;
@@ -140,6 +157,8 @@ async fn append_conflicts() -> Result<()> {
         prev_log_id: Some(LogId::new(LeaderId::new(1, 0), 2000)),
         entries: vec![],
         leader_commit: Some(LogId::new(LeaderId::new(1, 0), 2)),
+        compressed_entries: None,
+        leader_transfer_to: None,
     };
 
     let resp = r0.append_entries(req).await?;
@@ -155,6 +174,8 @@ async fn append_conflicts() -> Result<()> {
         prev_log_id: Some(LogId::new(LeaderId::new(3, 0), 3)),
         entries: vec![],
         leader_commit: Some(LogId::new(LeaderId::new(1, 0), 2)),
+        compressed_entries: None,
+        leader_transfer_to: None,
     };
 
     let resp = r0.append_entries(req).await?;
@@ -168,8 +189,10 @@ async fn append_conflicts() -> Result<()> {
     let req = AppendEntriesRequest {
         vote: Vote::new_committed(1, 0),
         prev_log_id: Some(LogId::new(LeaderId::new(1, 0), 2)),
-        entries: vec![blank(2, 3), blank(2, 4), blank(2, 5)],
+        entries: vec![Arc::new(blank(2, 3)), Arc::new(blank(2, 4)), Arc::new(blank(2, 5))],
         leader_commit: Some(LogId::new(LeaderId::new(1, 0), 2)),
+        compressed_entries: None,
+        leader_transfer_to: None,
     };
 
     let resp = r0.append_entries(req).await?;
@@ -183,8 +206,10 @@ async fn append_conflicts() -> Result<()> {
     let req = AppendEntriesRequest {
         vote: Vote::new_committed(1, 0),
         prev_log_id: Some(LogId::new(LeaderId::new(2, 0), 3)),
-        entries: vec![blank(3, 4)],
+        entries: vec![Arc::new(blank(3, 4))],
         leader_commit: Some(LogId::new(LeaderId::new(1, 0), 2)),
+        compressed_entries: None,
+        leader_transfer_to: None,
     };
 
     let resp = r0.append_entries(req).await?;
@@ -201,6 +226,8 @@ async fn append_conflicts() -> Result<()> {
         prev_log_id: Some(LogId::new(LeaderId::new(1, 0), 200)),
         entries: vec![],
         leader_commit: Some(LogId::new(LeaderId::new(1, 0), 2)),
+        compressed_entries: None,
+        leader_transfer_to: None,
     };
 
     let resp = r0.append_entries(req).await?;
@@ -55,12 +55,14 @@ async fn append_inconsistent_log() -> Result<()> {
         sto0.append_to_log(&[&Entry {
             log_id: LogId::new(LeaderId::new(2, 0), i),
             payload: EntryPayload::Blank,
+            context: None,
         }])
         .await?;
 
         sto2.append_to_log(&[&Entry {
             log_id: LogId::new(LeaderId::new(3, 0), i),
             payload: EntryPayload::Blank,
+            context: None,
         }])
         .await?;
     }
@@ -42,20 +42,24 @@ async fn append_updates_membership() -> Result<()> {
             vote: Vote::new_committed(1, 0),
             prev_log_id: None,
             entries: vec![
-                blank(0, 0),
-                blank(1, 1),
-                Entry {
+                Arc::new(blank(0, 0)),
+                Arc::new(blank(1, 1)),
+                Arc::new(Entry {
                     log_id: LogId::new(LeaderId::new(1, 0), 2),
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2}], None)),
-                },
-                blank(1, 3),
-                Entry {
+                    context: None,
+                }),
+                Arc::new(blank(1, 3)),
+                Arc::new(Entry {
                     log_id: LogId::new(LeaderId::new(1, 0), 4),
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2,3,4}], None)),
-                },
-                blank(1, 5),
+                    context: None,
+                }),
+                Arc::new(blank(1, 5)),
             ],
             leader_commit: Some(LogId::new(LeaderId::new(0, 0), 0)),
+            compressed_entries: None,
+            leader_transfer_to: None,
         };
 
         let resp = r0.append_entries(req.clone()).await?;
@@ -70,8 +74,10 @@ async fn append_updates_membership() -> Result<()> {
         let req = AppendEntriesRequest {
             vote: Vote::new_committed(1, 0),
             prev_log_id: Some(LogId::new(LeaderId::new(1, 0), 2)),
-            entries: vec![blank(2, 3)],
+            entries: vec![Arc::new(blank(2, 3))],
             leader_commit: Some(LogId::new(LeaderId::new(0, 0), 0)),
+            compressed_entries: None,
+            leader_transfer_to: None,
         };
 
         let resp = r0.append_entries(req.clone()).await?;
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use openraft::raft::AppendEntriesRequest;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftLogReader;
+use openraft::RaftNetwork;
+use openraft::RaftNetworkFactory;
+use openraft::RaftStorage;
+use openraft::Vote;
+use openraft::Wrapper;
+
+use crate::fixtures::blank;
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A follower saves the vote of a new leader along with the logs it sends, with one storage call.
+///
+/// What does this test do?
+///
+/// - brings a 1 Learner node online.
+/// - send an AppendEntries with a new vote and some logs to it.
+/// - asserts that the vote and the logs are saved with one `append_to_log_with_vote()` call.
+/// - send more logs with the same vote, asserts that they are appended on their own.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn save_vote_with_logs() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    router.new_raft_node(0);
+
+    tracing::info!("--- a new vote is saved along with the logs");
+    {
+        let rpc = AppendEntriesRequest::<memstore::Config> {
+            vote: Vote::new_committed(1, 1),
+            prev_log_id: None,
+            entries: vec![Arc::new(blank(0, 0)), Arc::new(blank(1, 1))],
+            leader_commit: None,
+            compressed_entries: None,
+            leader_transfer_to: None,
+        };
+
+        let resp = router.connect(0, None).await.send_append_entries(rpc).await?;
+        assert!(resp.is_success());
+
+        let mut sto = router.get_storage_handle(&0)?;
+        assert_eq!(1, sto.inner().appends_with_vote());
+        assert_eq!(Some(Vote::new_committed(1, 1)), sto.read_vote().await?);
+        assert_eq!(2, sto.try_get_log_entries(..).await?.len());
+    }
+
+    tracing::info!("--- logs with the same vote are appended on their own");
+    {
+        let rpc = AppendEntriesRequest::<memstore::Config> {
+            vote: Vote::new_committed(1, 1),
+            prev_log_id: Some(LogId::new(LeaderId::new(1, 0), 1)),
+            entries: vec![Arc::new(blank(1, 2))],
+            leader_commit: None,
+            compressed_entries: None,
+            leader_transfer_to: None,
+        };
+
+        let resp = router.connect(0, None).await.send_append_entries(rpc).await?;
+        assert!(resp.is_success());
+
+        let mut sto = router.get_storage_handle(&0)?;
+        assert_eq!(1, sto.inner().appends_with_vote());
+        assert_eq!(3, sto.try_get_log_entries(..).await?.len());
+    }
+
+    Ok(())
+}
@@ -34,6 +34,8 @@ async fn append_entries_with_bigger_term() -> Result<()> {
         prev_log_id: Some(LogId::new(LeaderId::new(1, 0), log_index)),
         entries: vec![],
         leader_commit: Some(LogId::new(LeaderId::new(1, 0), log_index)),
+        compressed_entries: None,
+        leader_transfer_to: None,
     };
 
     let resp = router.connect(0, None).await.send_append_entries(req).await?;
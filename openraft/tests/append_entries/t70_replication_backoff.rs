@@ -0,0 +1,84 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The leader backs off from a target that can not be reached, and stops backing off once it is reachable.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters, isolate node-2.
+/// - assert the leader backs off from node-2 with a growing delay, but not from the healthy node-1.
+/// - write some logs, they are committed by node-0 and node-1.
+/// - restore node-2, assert the backoff is reset and node-2 catches up.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn replication_backoff() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            heartbeat_interval: 50,
+            max_replication_backoff: 1_000,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- isolate node-2, the leader backs off from it");
+    {
+        router.isolate_node(2);
+
+        let m0 = router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.replication_backoff.get(&2).map(|b| b.failures >= 4).unwrap_or(false),
+                "backing off from node-2",
+            )
+            .await?;
+
+        let b = m0.replication_backoff.get(&2).unwrap();
+        assert!(
+            b.delay >= Duration::from_millis(200),
+            "delay doubles after every failure: {:?}",
+            b
+        );
+        assert!(b.delay <= Duration::from_millis(1_000), "delay is capped: {:?}", b);
+        assert!(
+            !m0.replication_backoff.contains_key(&1),
+            "healthy node-1 is not affected"
+        );
+    }
+
+    tracing::info!("--- write logs, committed by the healthy nodes");
+    {
+        router.client_request_many(0, "0", 10).await?;
+        log_index += 10;
+
+        router
+            .wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "committed without node-2")
+            .await?;
+    }
+
+    tracing::info!("--- restore node-2, the backoff is reset");
+    {
+        router.restore_node(2);
+
+        router.wait_for_log(&btreeset! {2}, Some(log_index), timeout(), "node-2 catches up").await?;
+        router
+            .wait(&0, timeout())
+            .metrics(|m| m.replication_backoff.is_empty(), "stop backing off from node-2")
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(5_000))
+}
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::InjectedRPCError;
+use crate::fixtures::RaftRouter;
+
+/// Replication reacts to every kind of RPC error differently.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters with check-quorum enabled.
+/// - node-2 rejects AppendEntries with more than 3 entries as too large: it still catches up with smaller requests.
+/// - node-2 is unreachable: the leader backs off from it.
+/// - RPCs to node-2 time out: the leader does not back off, and node-2 catches up once the timeouts stop.
+/// - RPCs to both followers time out: the leader is not acknowledged by a quorum and steps down.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn replication_rpc_errors() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            heartbeat_interval: 50,
+            max_payload_entries: 10,
+            enable_check_quorum: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- node-2 rejects large payloads, it catches up with smaller requests");
+    {
+        router.set_rpc_error(
+            2,
+            Some(InjectedRPCError::PayloadTooLarge {
+                max_entries: 3,
+                max_bytes: u64::MAX,
+            }),
+        );
+
+        router.isolate_node(2);
+        log_index += router.client_request_many(0, "0", 20).await?;
+        router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "write logs").await?;
+        router.restore_node(2);
+
+        router.wait_for_log(&btreeset! {2}, Some(log_index), timeout(), "node-2 catches up").await?;
+
+        router.set_rpc_error(2, None);
+        router
+            .wait(&0, timeout())
+            .metrics(|m| m.replication_backoff.is_empty(), "no backoff from node-2")
+            .await?;
+    }
+
+    tracing::info!("--- node-2 is unreachable, the leader backs off from it");
+    {
+        router.set_rpc_error(2, Some(InjectedRPCError::Unreachable));
+
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.replication_backoff.get(&2).map(|b| b.failures >= 2).unwrap_or(false),
+                "backing off from node-2",
+            )
+            .await?;
+
+        router.set_rpc_error(2, None);
+        router
+            .wait(&0, timeout())
+            .metrics(|m| m.replication_backoff.is_empty(), "stop backing off from node-2")
+            .await?;
+    }
+
+    tracing::info!("--- RPCs to node-2 time out, the leader keeps replicating without backing off");
+    {
+        router.set_rpc_error(2, Some(InjectedRPCError::Timeout));
+
+        log_index += router.client_request_many(0, "0", 5).await?;
+        router
+            .wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "committed without node-2")
+            .await?;
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let m0 = router.get_metrics(&0)?;
+        assert!(
+            !m0.replication_backoff.contains_key(&2),
+            "timeout does not back off: {:?}",
+            m0.replication_backoff
+        );
+        assert_eq!(ServerState::Leader, m0.state, "a quorum still acknowledges the leader");
+
+        router.set_rpc_error(2, None);
+        router.wait_for_log(&btreeset! {2}, Some(log_index), timeout(), "node-2 catches up").await?;
+    }
+
+    tracing::info!("--- RPCs to both followers time out, the leader steps down");
+    {
+        router.set_rpc_error(1, Some(InjectedRPCError::Timeout));
+        router.set_rpc_error(2, Some(InjectedRPCError::Timeout));
+
+        router.wait(&0, timeout()).metrics(|m| m.state != ServerState::Leader, "node-0 steps down").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(5_000))
+}
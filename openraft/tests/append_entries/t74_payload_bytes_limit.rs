@@ -0,0 +1,101 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::Config;
+use openraft::Entry;
+use openraft::EntryPayload;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftTypeConfig;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::InjectedRPCError;
+use crate::fixtures::RaftRouter;
+
+/// Replication limits the bytes of the logs in an AppendEntries, by `Config::max_payload_bytes` and by the hint of a
+/// target that rejects a payload as too large.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters, with `max_payload_bytes` of 3 logs.
+/// - let node-2 reject payloads larger than 3 logs.
+/// - isolate node-2 and write some logs, restore node-2, assert it catches up without any payload rejected.
+/// - let node-2 reject payloads larger than 2 logs with a bytes hint.
+/// - isolate node-2 and write some logs, restore node-2, assert it catches up with smaller requests.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn payload_bytes_limit() -> Result<()> {
+    let log_size = entry_size();
+
+    let config = Arc::new(
+        Config {
+            max_payload_bytes: Some(log_size * 3),
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- payloads are kept within max_payload_bytes");
+    {
+        router.set_rpc_error(
+            2,
+            Some(InjectedRPCError::PayloadTooLarge {
+                max_entries: u64::MAX,
+                max_bytes: log_size * 3,
+            }),
+        );
+
+        router.isolate_node(2);
+        log_index += router.client_request_many(0, "0", 20).await?;
+        router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "write logs").await?;
+        router.restore_node(2);
+
+        router.wait_for_log(&btreeset! {2}, Some(log_index), timeout(), "node-2 catches up").await?;
+        assert_eq!(
+            0,
+            router.payload_too_large_count(),
+            "no payload exceeds max_payload_bytes"
+        );
+    }
+
+    tracing::info!("--- node-2 rejects payloads larger than 2 logs, it catches up with smaller requests");
+    {
+        router.set_rpc_error(
+            2,
+            Some(InjectedRPCError::PayloadTooLarge {
+                max_entries: u64::MAX,
+                max_bytes: log_size * 2,
+            }),
+        );
+
+        router.isolate_node(2);
+        log_index += router.client_request_many(0, "0", 20).await?;
+        router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "write logs").await?;
+        router.restore_node(2);
+
+        router.wait_for_log(&btreeset! {2}, Some(log_index), timeout(), "node-2 catches up").await?;
+        assert!(router.payload_too_large_count() > 0, "large payloads are rejected");
+    }
+
+    Ok(())
+}
+
+/// The size of a log written by `client_request_many()`.
+fn entry_size() -> u64 {
+    let ent = Entry::<memstore::Config> {
+        log_id: LogId::new(LeaderId::new(1, 0), 1),
+        payload: EntryPayload::Normal(ClientRequest::make_request("0", 1)),
+        context: None,
+    };
+    <memstore::Config as RaftTypeConfig>::entry_size(&ent)
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(5_000))
+}
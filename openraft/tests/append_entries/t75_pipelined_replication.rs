@@ -0,0 +1,81 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use tokio::time::Instant;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Pipelined AppendEntries replicate logs faster over a network with a long round trip time.
+///
+/// What does this test do?
+///
+/// - bring up a single-node cluster with a small `max_payload_entries`, and write some logs.
+/// - add a learner over a network with a 20 ms latency, with `max_inflight_appends` of 1 and then of 8.
+/// - assert the learner catches up much faster with pipelined AppendEntries.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn pipelined_replication() -> Result<()> {
+    let one_inflight = learner_catch_up_time(1).await?;
+    let pipelined = learner_catch_up_time(8).await?;
+
+    tracing::info!(
+        one_inflight = debug(one_inflight),
+        pipelined = debug(pipelined),
+        "time for a learner to catch up"
+    );
+
+    assert!(
+        pipelined * 2 < one_inflight,
+        "pipelined: {:?}, one inflight: {:?}",
+        pipelined,
+        one_inflight
+    );
+
+    Ok(())
+}
+
+/// Write logs on a single-node cluster, and measure how long it takes a new learner to replicate them.
+async fn learner_catch_up_time(max_inflight_appends: u64) -> Result<Duration> {
+    let config = Arc::new(
+        Config {
+            max_payload_entries: 2,
+            max_inflight_appends,
+            append_entries_timeout: 500,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!(max_inflight_appends, "--- write logs");
+    {
+        log_index += router.client_request_many(0, "0", 100).await?;
+        router.wait_for_log(&btreeset! {0}, Some(log_index), timeout(), "write logs").await?;
+    }
+
+    tracing::info!(
+        max_inflight_appends,
+        "--- add a learner over a network with a long latency"
+    );
+    let start = Instant::now();
+    {
+        router.network_send_latency(20);
+
+        router.new_raft_node(1);
+        router.add_learner(0, 1).await?;
+        log_index += 1;
+
+        router.wait_for_log(&btreeset! {0, 1}, Some(log_index), timeout(), "learner catches up").await?;
+    }
+
+    Ok(start.elapsed())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(10_000))
+}
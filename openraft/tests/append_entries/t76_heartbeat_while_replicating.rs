@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Heartbeats are sent on their own timer, thus followers busy receiving logs do not start an election.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters with check-quorum enabled.
+/// - delay every AppendEntries that carries logs for longer than the election timeout, but not the heartbeats.
+/// - write some logs, assert they are committed.
+/// - assert no follower starts an election meanwhile, and the leader is not stepped down by check-quorum.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn heartbeat_while_replicating() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            append_entries_timeout: 5_000,
+            enable_check_quorum: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let term = router.get_metrics(&0)?.current_term;
+
+    tracing::info!("--- replicating logs takes longer than the election timeout");
+    {
+        router.network_logs_delay(config.election_timeout_max * 3);
+
+        log_index += router.client_request_many(0, "0", 3).await?;
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "logs are replicated").await?;
+    }
+
+    tracing::info!("--- the leader is kept by heartbeats");
+    {
+        for id in [0, 1, 2] {
+            let m = router.get_metrics(&id)?;
+            assert_eq!(term, m.current_term, "node-{} does not elect", id);
+            assert_eq!(Some(0), m.current_leader, "node-{}", id);
+        }
+
+        let m0 = router.get_metrics(&0)?;
+        assert_eq!(
+            ServerState::Leader,
+            m0.state,
+            "acknowledged by a quorum with heartbeats"
+        );
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(10_000))
+}
@@ -0,0 +1,95 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use tokio::time::sleep;
+use tokio::time::Instant;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::InjectedRPCError;
+use crate::fixtures::RaftRouter;
+
+/// A client of a target is kept after a connection error, and `RaftNetwork::on_connection_error()` is called on it to
+/// let it rebuild its transport.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 2 voters and a learner, node-2.
+/// - RPCs to node-2 time out: assert no client to it rebuilds its connection.
+/// - node-2 is unreachable, then RPCs to it fail with a network error: assert the clients rebuild their connections.
+/// - restore node-2: assert it catches up, no client to it is created by the leader, and the rebuilding stops.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn reconnect_on_connection_error() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            heartbeat_interval: 50,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1}, btreeset! {2}).await?;
+
+    let connects = router.connect_count(&2);
+    assert_eq!(0, router.reconnect_count(&2));
+
+    tracing::info!("--- a timeout is not a connection error");
+    {
+        router.set_rpc_error(2, Some(InjectedRPCError::Timeout));
+        sleep(Duration::from_millis(300)).await;
+        assert_eq!(0, router.reconnect_count(&2));
+    }
+
+    tracing::info!("--- node-2 is unreachable, the clients rebuild their connections");
+    {
+        router.set_rpc_error(2, Some(InjectedRPCError::Unreachable));
+        wait_for_reconnects(&router, 2).await?;
+    }
+
+    tracing::info!("--- RPCs to node-2 fail with a network error, the clients rebuild their connections");
+    {
+        let n = router.reconnect_count(&2);
+        router.set_rpc_error(2, Some(InjectedRPCError::Network));
+        wait_for_reconnects(&router, n + 2).await?;
+    }
+
+    tracing::info!("--- restore node-2, it catches up with the same clients");
+    {
+        router.set_rpc_error(2, None);
+
+        log_index += router.client_request_many(0, "0", 10).await?;
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "node-2 catches up").await?;
+
+        assert_eq!(connects, router.connect_count(&2), "no client to node-2 is created");
+
+        let n = router.reconnect_count(&2);
+        sleep(Duration::from_millis(300)).await;
+        assert_eq!(n, router.reconnect_count(&2), "no more connection errors");
+    }
+
+    Ok(())
+}
+
+/// Wait until the clients to node-2 rebuild their connections at least `n` times.
+async fn wait_for_reconnects(router: &RaftRouter, n: u64) -> Result<()> {
+    let deadline = Instant::now() + timeout().unwrap();
+
+    while router.reconnect_count(&2) < n {
+        if Instant::now() > deadline {
+            anyhow::bail!(
+                "timeout waiting for {} reconnects, got: {}",
+                n,
+                router.reconnect_count(&2)
+            );
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
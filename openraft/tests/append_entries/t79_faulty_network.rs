@@ -0,0 +1,142 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::testing::RPCFault;
+use openraft::testing::RPCHook;
+use openraft::Config;
+use openraft::RPCTypes;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// An isolated leader is replaced, and catches up with the new leader once the network heals.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters.
+/// - isolate the leader with `NetworkFaults::isolate()`, assert another node becomes the leader, and write logs to it.
+/// - heal the network, assert the old leader becomes a follower of the new leader and receives the logs.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn leader_isolated_then_rejoins() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            heartbeat_interval: 50,
+            election_timeout_min: 300,
+            election_timeout_max: 600,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- isolate the leader, another node is elected");
+    let leader = {
+        router.faults().isolate(0);
+
+        router
+            .wait(&1, timeout())
+            .metrics(
+                |m| m.current_leader.is_some() && m.current_leader != Some(0),
+                "node-1 sees a new leader",
+            )
+            .await?;
+
+        let leader = router.get_metrics(&1)?.current_leader.unwrap();
+        router.wait(&leader, timeout()).state(ServerState::Leader, "new leader").await?;
+        leader
+    };
+
+    tracing::info!("--- write logs to the new leader");
+    let log_index = {
+        let mut log_index = router.get_metrics(&leader)?.last_log_index.unwrap();
+        log_index += router.client_request_many(leader, "0", 10).await?;
+        router.wait_for_log(&btreeset! {1,2}, Some(log_index), timeout(), "written without node-0").await?;
+        log_index
+    };
+
+    tracing::info!("--- heal the network, node-0 follows the new leader");
+    {
+        router.faults().heal_all();
+
+        router
+            .wait(&0, timeout())
+            .metrics(|m| m.current_leader == Some(leader), "node-0 follows the new leader")
+            .await?;
+        router.wait_for_log(&btreeset! {0}, Some(log_index), timeout(), "node-0 catches up").await?;
+    }
+
+    Ok(())
+}
+
+/// Logs are replicated through lossy, slow and duplicating links, and a follower that rejects AppendEntries catches up
+/// once it accepts them again.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters.
+/// - let the link from the leader to node-1 drop some RPCs and add latency, and the link to node-2 duplicate RPCs.
+///   Write logs, assert every node receives them.
+/// - reject every AppendEntries sent to node-2 with an RPC hook, write logs, assert they are committed by node-0 and
+///   node-1, then remove the hook and assert node-2 catches up.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn lossy_and_duplicating_links() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            heartbeat_interval: 50,
+            // Long enough that node-2 does not start an election while it rejects heartbeats.
+            election_timeout_min: 3_000,
+            election_timeout_max: 4_000,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- replicate through lossy, slow and duplicating links");
+    {
+        let faults = router.faults();
+        faults.set_drop_rate(0, 1, 0.2);
+        faults.set_latency(0, 1, Duration::from_millis(5), Duration::from_millis(5));
+        faults.set_duplicate(0, 2, true);
+
+        log_index += router.client_request_many(0, "0", 20).await?;
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "replicated").await?;
+
+        assert!(faults.duplicated_count() > 0, "RPCs to node-2 are duplicated");
+        faults.heal_all();
+    }
+
+    tracing::info!("--- node-2 rejects AppendEntries, then accepts them again");
+    {
+        let hook: RPCHook<u64> = Arc::new(|_from: &u64, to: &u64, kind: RPCTypes| {
+            if *to == 2 && kind == RPCTypes::AppendEntries {
+                RPCFault::Reject("rejected by test".to_string())
+            } else {
+                RPCFault::Deliver
+            }
+        });
+        router.faults().set_rpc_hook(Some(hook));
+
+        log_index += router.client_request_many(0, "0", 10).await?;
+        router
+            .wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "committed without node-2")
+            .await?;
+        assert!(router.get_metrics(&2)?.last_log_index < Some(log_index));
+
+        router.faults().set_rpc_hook(None);
+        router.wait_for_log(&btreeset! {2}, Some(log_index), timeout(), "node-2 catches up").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(5_000))
+}
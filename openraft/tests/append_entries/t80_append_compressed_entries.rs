@@ -0,0 +1,53 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::CompressionAlgo;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Replicate logs compressed with `Config::replication_compression`.
+///
+/// What does this test do?
+///
+/// - for each algorithm, create a cluster of 1 voter and 1 learner that compress replicated entries.
+/// - write many logs to the leader, assert the learner receives and applies all of them.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn append_compressed_entries() -> Result<()> {
+    for algo in [CompressionAlgo::Lz4, CompressionAlgo::Zstd] {
+        tracing::info!("--- replicate with {}", algo);
+
+        let config = Arc::new(
+            Config {
+                replication_compression: Some(algo),
+                max_payload_entries: 10,
+                ..Default::default()
+            }
+            .validate()?,
+        );
+        let mut router = RaftRouter::new(config.clone());
+
+        let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {1}).await?;
+
+        router.client_request_many(0, "foo", 50).await?;
+        log_index += 50;
+
+        router
+            .wait_for_log(
+                &btreeset! {0,1},
+                Some(log_index),
+                timeout(),
+                &format!("learner applies logs compressed with {}", algo),
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::Wrapper;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A replication stream reads `replication_prefetch_entries` logs from the storage in one call, and sends them in
+/// several AppendEntries.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters, with the log cache disabled, 10 logs per AppendEntries and 1000 logs prefetched.
+/// - isolate node-2 and write 500 logs.
+/// - restore node-2, assert it catches up with far fewer storage reads than AppendEntries.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn replication_prefetch() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            max_payload_entries: 10,
+            replication_prefetch_entries: 1000,
+            // Keep the storage reads for heartbeats few.
+            heartbeat_interval: 200,
+            election_timeout_min: 1_000,
+            election_timeout_max: 2_000,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::builder(config.clone()).log_cache_max_entries(0).build();
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- isolate node-2 and write logs");
+    {
+        router.isolate_node(2);
+        log_index += router.client_request_many(0, "0", 500).await?;
+        router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "write logs").await?;
+    }
+
+    tracing::info!("--- restore node-2, it catches up with few storage reads");
+    {
+        let mut sto0 = router.get_storage_handle(&0)?;
+        let reads_before = sto0.inner().log_reads();
+
+        router.restore_node(2);
+        router.wait_for_log(&btreeset! {2}, Some(log_index), timeout(), "node-2 catches up").await?;
+
+        let reads = sto0.inner().log_reads() - reads_before;
+        tracing::info!("storage reads of the leader during catching up: {}", reads);
+
+        assert!(
+            reads < 500 / 10,
+            "fewer reads than the 50 AppendEntries sent, got: {}",
+            reads
+        );
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(5_000))
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::raft::ClientWriteRequest;
+use openraft::CommitQuorum;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft::LogIdOptionExt;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With the default `CommitQuorum::Majority`, a log is committed without a partitioned voter.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters and isolate node-2.
+/// - write a log, assert it is committed by node-0 and node-1.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn commit_quorum_majority() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    router.isolate_node(2);
+
+    log_index += router.client_request_many(0, "0", 1).await?;
+    router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "committed by a majority").await?;
+
+    Ok(())
+}
+
+/// With `CommitQuorum::All`, a log is committed only when every voter acknowledges it.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters with `CommitQuorum::All`, and isolate node-2.
+/// - write a log, assert it is not committed by node-0 and node-1 alone.
+/// - restore node-2, assert the log is committed and the write returns.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn commit_quorum_all() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            commit_quorum: CommitQuorum::All,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- isolate node-2, a log is not committed without it");
+    let writing = {
+        router.isolate_node(2);
+
+        let n0 = router.get_raft_handle(&0)?;
+        let req = ClientWriteRequest::new(EntryPayload::Normal(ClientRequest::make_request("0", 0)));
+        let writing = tokio::spawn(async move { n0.client_write(req).await });
+        log_index += 1;
+
+        router
+            .wait(&1, timeout())
+            .metrics(|m| m.last_log_index == Some(log_index), "node-1 receives the log")
+            .await?;
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        for id in [0, 1] {
+            let m = router.get_metrics(&id)?;
+            assert_eq!(
+                Some(log_index - 1),
+                m.last_applied.index(),
+                "node-{} does not commit",
+                id
+            );
+        }
+
+        writing
+    };
+
+    tracing::info!("--- restore node-2, the log is committed");
+    {
+        router.restore_node(2);
+
+        tokio::time::timeout(Duration::from_millis(3_000), writing).await???;
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "committed by all").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyerror::AnyError;
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::Config as MemConfig;
+use memstore::FlushMode;
+use memstore::IntoMemClientRequest;
+use memstore::MemNodeId;
+use memstore::MemStore;
+use openraft::async_trait::async_trait;
+use openraft::error::AppendEntriesError;
+use openraft::error::InstallSnapshotError;
+use openraft::error::NetworkError;
+use openraft::error::RPCError;
+use openraft::error::RemoteError;
+use openraft::error::StreamingError;
+use openraft::error::VoteError;
+use openraft::network::Chunked;
+use openraft::network::SnapshotStreaming;
+use openraft::raft::AppendEntriesRequest;
+use openraft::raft::AppendEntriesResponse;
+use openraft::raft::InstallSnapshotRequest;
+use openraft::raft::InstallSnapshotResponse;
+use openraft::raft::SnapshotResponse;
+use openraft::raft::VoteRequest;
+use openraft::raft::VoteResponse;
+use openraft::storage::Adaptor;
+use openraft::storage::RaftLogReader;
+use openraft::storage::Snapshot;
+use openraft::Config;
+use openraft::Node;
+use openraft::Raft;
+use openraft::RaftNetwork;
+use openraft::RaftNetworkFactory;
+use openraft::RaftStorageDebug;
+use openraft::ServerState;
+use openraft::Vote;
+
+use crate::fixtures::init_default_ut_tracing;
+
+/// A raft node whose log store is `MemStore` itself, thus the flush of its logs can be delayed.
+type FlushRaft = Raft<MemConfig, FlushRouter, Arc<MemStore>, Adaptor<MemConfig, Arc<MemStore>>>;
+
+/// Routes RPCs between `FlushRaft` nodes.
+#[derive(Clone, Default)]
+struct FlushRouter {
+    nodes: Arc<Mutex<BTreeMap<MemNodeId, FlushRaft>>>,
+}
+
+impl FlushRouter {
+    fn new_node(&self, id: MemNodeId, config: Arc<Config>, sto: Arc<MemStore>) -> FlushRaft {
+        let (_, state_machine) = Adaptor::new(sto.clone());
+        let raft = Raft::new(id, config, self.clone(), sto, state_machine);
+        self.nodes.lock().unwrap().insert(id, raft.clone());
+        raft
+    }
+
+    fn remove_node(&self, id: MemNodeId) -> Option<FlushRaft> {
+        self.nodes.lock().unwrap().remove(&id)
+    }
+
+    fn get(&self, id: &MemNodeId) -> std::result::Result<FlushRaft, NetworkError> {
+        let nodes = self.nodes.lock().unwrap();
+        let node =
+            nodes.get(id).ok_or_else(|| NetworkError::new(&AnyError::error(format!("node {} not found", id))))?;
+        Ok(node.clone())
+    }
+}
+
+#[async_trait]
+impl RaftNetworkFactory<MemConfig> for FlushRouter {
+    type Network = FlushNetwork;
+
+    async fn connect(&mut self, target: MemNodeId, _node: Option<&Node>) -> Self::Network {
+        FlushNetwork {
+            target,
+            router: self.clone(),
+        }
+    }
+}
+
+struct FlushNetwork {
+    target: MemNodeId,
+    router: FlushRouter,
+}
+
+#[async_trait]
+impl RaftNetwork<MemConfig> for FlushNetwork {
+    async fn send_append_entries(
+        &mut self,
+        rpc: AppendEntriesRequest<MemConfig>,
+    ) -> std::result::Result<AppendEntriesResponse<MemNodeId>, RPCError<MemNodeId, AppendEntriesError<MemNodeId>>> {
+        let node = self.router.get(&self.target)?;
+        let resp = node.append_entries(rpc).await.map_err(|e| RemoteError::new(self.target, e))?;
+        Ok(resp)
+    }
+
+    async fn send_install_snapshot(
+        &mut self,
+        rpc: InstallSnapshotRequest<MemConfig>,
+    ) -> std::result::Result<InstallSnapshotResponse<MemNodeId>, RPCError<MemNodeId, InstallSnapshotError<MemNodeId>>>
+    {
+        let node = self.router.get(&self.target)?;
+        let resp = node.install_snapshot(rpc).await.map_err(|e| RemoteError::new(self.target, e))?;
+        Ok(resp)
+    }
+
+    async fn full_snapshot(
+        &mut self,
+        vote: Vote<MemNodeId>,
+        snapshot: Snapshot<MemNodeId, Cursor<Vec<u8>>>,
+        streaming: SnapshotStreaming,
+    ) -> std::result::Result<SnapshotResponse<MemNodeId>, StreamingError<MemNodeId>> {
+        Chunked::send_snapshot(self, vote, snapshot, streaming).await
+    }
+
+    async fn send_vote(
+        &mut self,
+        rpc: VoteRequest<MemNodeId>,
+    ) -> std::result::Result<VoteResponse<MemNodeId>, RPCError<MemNodeId, VoteError<MemNodeId>>> {
+        let node = self.router.get(&self.target)?;
+        let resp = node.vote(rpc).await.map_err(|e| RemoteError::new(self.target, e))?;
+        Ok(resp)
+    }
+}
+
+/// The leader replicates logs while flushing them, and the followers commit them even if the leader crashes before
+/// the flush.
+///
+/// What does this test do?
+///
+/// - bring up a 3-node cluster, in which the leader delays reporting that its logs are flushed.
+/// - write a log, assert it is committed by the followers before the leader flushed it.
+/// - crash the leader, losing the log that it has not flushed.
+/// - assert a new leader is elected with the log, and the restarted node catches up.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn leader_crash_before_log_flushed() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let router = FlushRouter::default();
+
+    let sto0 = MemStore::new_async().await;
+    let sto1 = MemStore::new_async().await;
+    let sto2 = MemStore::new_async().await;
+
+    let n0 = router.new_node(0, config.clone(), sto0.clone());
+    router.new_node(1, config.clone(), sto1.clone());
+    router.new_node(2, config.clone(), sto2.clone());
+
+    let mut log_index = 0;
+
+    tracing::info!("--- initialize a 3-node cluster");
+    {
+        n0.initialize(btreeset! {0,1,2}).await?;
+        log_index += 1;
+
+        n0.wait(timeout()).state(ServerState::Leader, "node 0 becomes leader").await?;
+        for id in [0, 1, 2] {
+            router.get(&id)?.wait(timeout()).log(Some(log_index), "the blank log is applied").await?;
+        }
+    }
+
+    tracing::info!("--- a write is committed by the followers before the leader flushed it");
+    {
+        sto0.set_flush_mode(FlushMode::Delayed);
+
+        let resp = n0.client_write(ClientRequest::make_request("foo", 1)).await?;
+        log_index += 1;
+        assert_eq!(log_index, resp.log_id.index);
+
+        for id in [1, 2] {
+            router.get(&id)?.wait(timeout()).log(Some(log_index), "the write is applied").await?;
+        }
+    }
+
+    tracing::info!("--- the leader crashes before flushing, and loses the log");
+    {
+        n0.shutdown().await?;
+        router.remove_node(0);
+
+        assert_eq!(1, sto0.discard_unflushed_logs().await);
+
+        let logs = sto0.clone().try_get_log_entries(..).await?;
+        assert_eq!(Some(log_index - 1), logs.last().map(|ent| ent.log_id.index));
+    }
+
+    tracing::info!("--- a new leader is elected with the committed log");
+    {
+        // The blank log of the new leader.
+        log_index += 1;
+
+        for id in [1, 2] {
+            router
+                .get(&id)?
+                .wait(timeout())
+                .log_at_least(Some(log_index), "the new leader commits a blank log")
+                .await?;
+        }
+
+        for mut sto in [sto1.clone(), sto2.clone()] {
+            let sm = sto.get_state_machine().await;
+            assert_eq!(Some(&"request-1".to_string()), sm.client_status.get("foo"));
+        }
+    }
+
+    tracing::info!("--- restart the crashed node, it catches up");
+    {
+        sto0.set_flush_mode(FlushMode::Immediate);
+        let n0 = router.new_node(0, config.clone(), sto0.clone());
+
+        n0.wait(timeout()).log_at_least(Some(log_index), "node 0 catches up").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
@@ -5,7 +5,11 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::raft::ClientWriteRequest;
 use openraft::Config;
+use openraft::EntryPayload;
 use tokio::runtime::Builder;
 
 use crate::fixtures::RaftRouter;
@@ -13,6 +17,8 @@ use crate::fixtures::RaftRouter;
 struct BenchConfig {
     pub worker_threads: usize,
     pub n_operations: usize,
+    /// Number of requests sent with one `client_write_many()` call. `1` sends every request with `client_write()`.
+    pub batch_size: usize,
     pub members: BTreeSet<u64>,
 }
 
@@ -20,8 +26,8 @@ impl Display for BenchConfig {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "worker: {}, n: {}, raft_members: {:?}",
-            self.worker_threads, self.n_operations, self.members
+            "worker: {}, n: {}, batch: {}, raft_members: {:?}",
+            self.worker_threads, self.n_operations, self.batch_size, self.members
         )
     }
 }
@@ -32,6 +38,7 @@ fn bench_cluster_of_1() -> anyhow::Result<()> {
     bench_with_config(&BenchConfig {
         worker_threads: 8,
         n_operations: 100_000,
+        batch_size: 1,
         members: btreeset! {0},
     })?;
     Ok(())
@@ -43,6 +50,19 @@ fn bench_cluster_of_3() -> anyhow::Result<()> {
     bench_with_config(&BenchConfig {
         worker_threads: 8,
         n_operations: 100_000,
+        batch_size: 1,
+        members: btreeset! {0,1,2},
+    })?;
+    Ok(())
+}
+
+#[test]
+#[ignore]
+fn bench_cluster_of_3_batch_100() -> anyhow::Result<()> {
+    bench_with_config(&BenchConfig {
+        worker_threads: 8,
+        n_operations: 100_000,
+        batch_size: 100,
         members: btreeset! {0,1,2},
     })?;
     Ok(())
@@ -54,6 +74,7 @@ fn bench_cluster_of_5() -> anyhow::Result<()> {
     bench_with_config(&BenchConfig {
         worker_threads: 8,
         n_operations: 100_000,
+        batch_size: 1,
         members: btreeset! {0,1,2,3,4},
     })?;
     Ok(())
@@ -72,7 +93,7 @@ fn bench_with_config(bench_config: &BenchConfig) -> anyhow::Result<()> {
     Ok(output)
 }
 
-/// Benchmark client_write.
+/// Benchmark client_write, or client_write_many if `batch_size` is greater than 1.
 ///
 /// Cluster config:
 /// - Log: in-memory BTree
@@ -94,8 +115,25 @@ async fn do_bench(bench_config: &BenchConfig) -> anyhow::Result<()> {
 
     let now = Instant::now();
 
-    for i in 0..n {
-        router.client_request(0, "foo", i as u64).await?;
+    if bench_config.batch_size <= 1 {
+        for i in 0..n {
+            router.client_request(0, "foo", i as u64).await?;
+        }
+    } else {
+        let leader = router.get_raft_handle(&0)?;
+
+        let mut i = 0;
+        while i < n {
+            let end = std::cmp::min(i + bench_config.batch_size, n);
+            let rpcs = (i..end)
+                .map(|serial| {
+                    ClientWriteRequest::new(EntryPayload::Normal(ClientRequest::make_request("foo", serial as u64)))
+                })
+                .collect();
+
+            leader.client_write_many(rpcs).await?;
+            i = end;
+        }
     }
 
     let elapsed = now.elapsed();
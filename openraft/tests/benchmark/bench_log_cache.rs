@@ -0,0 +1,74 @@
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use maplit::btreeset;
+use openraft::Config;
+use openraft::Wrapper;
+use tokio::runtime::Builder;
+
+use crate::fixtures::RaftRouter;
+
+#[test]
+#[ignore]
+fn bench_line_rate_without_log_cache() -> anyhow::Result<()> {
+    bench_line_rate(0, 10_000)
+}
+
+#[test]
+#[ignore]
+fn bench_line_rate_with_log_cache() -> anyhow::Result<()> {
+    bench_line_rate(4096, 10_000)
+}
+
+fn bench_line_rate(cache_entries: u64, n_logs: usize) -> anyhow::Result<()> {
+    let rt = Builder::new_multi_thread()
+        .worker_threads(8)
+        .enable_all()
+        .thread_name("bench-log-cache")
+        .thread_stack_size(3 * 1024 * 1024)
+        .build()?;
+
+    rt.block_on(do_bench(cache_entries, n_logs))
+}
+
+/// Benchmark the storage reads of a leader replicating `n_logs` logs to followers that keep up with it.
+///
+/// With the log cache, the just appended logs are replicated without reading them back from the storage.
+async fn do_bench(cache_entries: u64, n_logs: usize) -> anyhow::Result<()> {
+    let config = Arc::new(
+        Config {
+            election_timeout_min: 200,
+            election_timeout_max: 2000,
+            max_payload_entries: 64,
+            purge_batch_size: 1024,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::builder(config.clone()).log_cache_max_entries(cache_entries).build();
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let mut sto0 = router.get_storage_handle(&0)?;
+    let reads_before = sto0.inner().log_reads();
+
+    let now = Instant::now();
+
+    log_index += router.client_request_many(0, "foo", n_logs).await?;
+    router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "replicate logs").await?;
+
+    let elapsed = now.elapsed();
+    let reads = sto0.inner().log_reads() - reads_before;
+    let cache = router.get_metrics(&0)?.log_cache;
+
+    println!(
+        "log cache: {}, logs: {}: time: {:?}, storage reads: {}, cache: {:?}",
+        cache_entries, n_logs, elapsed, reads, cache,
+    );
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_secs(60))
+}
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use maplit::btreeset;
+use openraft::Config;
+use openraft::Wrapper;
+use tokio::runtime::Builder;
+
+use crate::fixtures::RaftRouter;
+
+#[test]
+#[ignore]
+fn bench_catch_up_without_prefetch() -> anyhow::Result<()> {
+    bench_catch_up(0, 10_000)
+}
+
+#[test]
+#[ignore]
+fn bench_catch_up_with_prefetch() -> anyhow::Result<()> {
+    bench_catch_up(4096, 10_000)
+}
+
+fn bench_catch_up(prefetch_entries: u64, n_logs: usize) -> anyhow::Result<()> {
+    let rt = Builder::new_multi_thread()
+        .worker_threads(8)
+        .enable_all()
+        .thread_name("bench-prefetch")
+        .thread_stack_size(3 * 1024 * 1024)
+        .build()?;
+
+    rt.block_on(do_bench(prefetch_entries, n_logs))
+}
+
+/// Benchmark the storage reads of a leader catching up a follower that is `n_logs` logs behind.
+///
+/// The log cache is disabled thus every log is read from the storage.
+async fn do_bench(prefetch_entries: u64, n_logs: usize) -> anyhow::Result<()> {
+    let config = Arc::new(
+        Config {
+            election_timeout_min: 200,
+            election_timeout_max: 2000,
+            max_payload_entries: 64,
+            replication_prefetch_entries: prefetch_entries,
+            purge_batch_size: 1024,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::builder(config.clone()).log_cache_max_entries(0).build();
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    router.isolate_node(2);
+    log_index += router.client_request_many(0, "foo", n_logs).await?;
+    router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "write logs").await?;
+
+    let mut sto0 = router.get_storage_handle(&0)?;
+    let reads_before = sto0.inner().log_reads();
+
+    let now = Instant::now();
+
+    router.restore_node(2);
+    router.wait_for_log(&btreeset! {2}, Some(log_index), timeout(), "node-2 catches up").await?;
+
+    let elapsed = now.elapsed();
+    let reads = sto0.inner().log_reads() - reads_before;
+
+    println!(
+        "prefetch: {}, behind: {}, payload: {}: time: {:?}, storage reads: {}",
+        prefetch_entries, n_logs, config.max_payload_entries, elapsed, reads,
+    );
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_secs(60))
+}
@@ -5,3 +5,5 @@
 mod fixtures;
 
 mod bench_cluster;
+mod bench_log_cache;
+mod bench_replication_prefetch;
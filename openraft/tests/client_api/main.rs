@@ -8,5 +8,20 @@ mod fixtures;
 // The later tests may depend on the earlier ones.
 
 mod t10_client_writes;
+mod t11_client_write_many;
+mod t12_client_write_ff;
+mod t13_client_write_with_responder;
+mod t14_forward_client_write;
+mod t15_client_write_batch;
+mod t16_forward_to_leader_node;
+mod t17_transient_storage_error;
+mod t18_delayed_log_flush;
+mod t19_client_write_context;
 mod t20_client_reads;
+mod t21_ensure_linearizable;
+mod t22_read_lease;
+mod t23_leader_lease_and_id;
+mod t24_follower_read;
+mod t25_client_write_to;
+mod t26_client_write_app_error;
 mod t50_lagging_network_write;
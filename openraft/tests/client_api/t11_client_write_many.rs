@@ -0,0 +1,109 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::error::ClientWriteError;
+use openraft::raft::ClientWriteRequest;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft::RaftStorageDebug;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Write a batch of requests with `client_write_many`.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster.
+/// - write a batch to the leader, assert the responses are in the same order as the requests and the logs are
+///   consecutive.
+/// - write a batch to a follower, assert every entry fails with `ForwardToLeader`.
+/// - isolate the leader and write a batch to it, let another node become the leader, then restore the old leader.
+///   Assert the uncommitted batch fails with `ForwardToLeader`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn client_write_many() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_prevote: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- write a batch to the leader");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let resps = n0.client_write_many(batch("0", 0..100)).await?;
+
+        assert_eq!(100, resps.len());
+        for (i, resp) in resps.iter().enumerate() {
+            assert_eq!(log_index + 1 + i as u64, resp.log_id.index);
+        }
+        log_index += 100;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "batch is replicated").await?;
+
+        let sm = router.get_storage_handle(&1)?.get_state_machine().await;
+        assert_eq!(
+            Some(&(99, Some("request-98".to_string()))),
+            sm.client_serial_responses.get("0")
+        );
+    }
+
+    tracing::info!("--- write a batch to a follower");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let res = n1.client_write_many(batch("1", 0..10)).await;
+        assert!(
+            matches!(&res, Err(ClientWriteError::ForwardToLeader(e)) if e.leader_id == Some(0)),
+            "got: {:?}",
+            res
+        );
+    }
+
+    tracing::info!("--- the leader loses leadership before the batch is committed");
+    {
+        router.isolate_node(0);
+
+        let n0 = router.get_raft_handle(&0)?;
+        let pending = tokio::spawn(async move { n0.client_write_many(batch("2", 0..10)).await });
+
+        router
+            .wait(&1, timeout())
+            .metrics(
+                |m| m.current_leader.is_some() && m.current_leader != Some(0),
+                "new leader",
+            )
+            .await?;
+
+        router.restore_node(0);
+        router.wait(&0, timeout()).state(ServerState::Follower, "node-0 steps down").await?;
+
+        let res = tokio::time::timeout(Duration::from_millis(1_000), pending).await??;
+        assert!(
+            matches!(&res, Err(ClientWriteError::ForwardToLeader(_))),
+            "got: {:?}",
+            res
+        );
+    }
+
+    Ok(())
+}
+
+fn batch(client_id: &str, serials: std::ops::Range<u64>) -> Vec<ClientWriteRequest<memstore::Config>> {
+    serials
+        .map(|serial| ClientWriteRequest::new(EntryPayload::Normal(ClientRequest::make_request(client_id, serial))))
+        .collect()
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::error::ClientWriteError;
+use openraft::raft::ClientWriteRequest;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft::RaftStorageDebug;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Write requests with `client_write_ff`, without waiting for them to be applied.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster.
+/// - write a series of requests to the leader with `client_write_ff`, assert they are all replicated and applied.
+/// - write to a follower, assert it fails with `ForwardToLeader`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn client_write_ff() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- write to the leader");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        for serial in 0..100 {
+            n0.client_write_ff(request("0", serial)).await?;
+        }
+        log_index += 100;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "ff writes are applied").await?;
+
+        let sm = router.get_storage_handle(&1)?.get_state_machine().await;
+        assert_eq!(
+            Some(&(99, Some("request-98".to_string()))),
+            sm.client_serial_responses.get("0")
+        );
+    }
+
+    tracing::info!("--- write to a follower");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let res = n1.client_write_ff(request("1", 0)).await;
+        assert!(
+            matches!(&res, Err(ClientWriteError::ForwardToLeader(e)) if e.leader_id == Some(0)),
+            "got: {:?}",
+            res
+        );
+
+        let m1 = router.get_metrics(&1)?;
+        assert_eq!(Some(log_index), m1.last_log_index, "nothing is appended");
+    }
+
+    Ok(())
+}
+
+fn request(client_id: &str, serial: u64) -> ClientWriteRequest<memstore::Config> {
+    ClientWriteRequest::new(EntryPayload::Normal(ClientRequest::make_request(client_id, serial)))
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
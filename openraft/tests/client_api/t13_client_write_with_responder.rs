@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::error::ClientWriteError;
+use openraft::raft::ClientWriteRequest;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft::OneshotResponder;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Deliver the result of a write to a `Responder`.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster.
+/// - write to the leader with a `OneshotResponder`, assert the responder receives the applied result.
+/// - write to a follower, assert the responder receives `ForwardToLeader`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn client_write_with_responder() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- write to the leader");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+
+        let (responder, rx) = OneshotResponder::new();
+        n0.client_write_with_responder(request("0", 1), responder).await?;
+
+        let resp = tokio::time::timeout(Duration::from_millis(1_000), rx).await???;
+        log_index += 1;
+
+        assert_eq!(log_index, resp.log_id.index);
+        assert!(resp.membership.is_none());
+    }
+
+    tracing::info!("--- write to a follower");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+
+        let (responder, rx) = OneshotResponder::new();
+        n1.client_write_with_responder(request("0", 2), responder).await?;
+
+        let res = tokio::time::timeout(Duration::from_millis(1_000), rx).await??;
+        assert!(
+            matches!(&res, Err(ClientWriteError::ForwardToLeader(e)) if e.leader_id == Some(0)),
+            "got: {:?}",
+            res
+        );
+    }
+
+    Ok(())
+}
+
+fn request(client_id: &str, serial: u64) -> ClientWriteRequest<memstore::Config> {
+    ClientWriteRequest::new(EntryPayload::Normal(ClientRequest::make_request(client_id, serial)))
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::error::ClientWriteError;
+use openraft::raft::ClientWriteRequest;
+use openraft::raft::ForwardClientWriteRequest;
+use openraft::Config;
+use openraft::EntryPayload;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A follower forwards a client write to the leader if `enable_forward_client_write` is set.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster with forwarding enabled.
+/// - write to a follower, assert the leader's response is returned and the log is replicated to every node.
+/// - forward a write that has reached the max hops to a follower, assert it returns `ForwardToLeader`.
+/// - isolate the leader, write to a follower, assert it returns `ForwardToLeader`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn forward_client_write() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_forward_client_write: true,
+            max_forward_client_write_hops: 2,
+            // Keep the followers from electing a new leader when the leader is isolated.
+            election_timeout_min: 5_000,
+            election_timeout_max: 6_000,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- write to a follower, it is forwarded to the leader");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let resp = n1.client_write(request("0", 1)).await?;
+        log_index += 1;
+
+        assert_eq!(log_index, resp.log_id.index);
+        assert_eq!(0, resp.log_id.leader_id.node_id);
+
+        router
+            .wait_for_log(
+                &btreeset! {0,1,2},
+                Some(log_index),
+                timeout(),
+                "forwarded write is replicated",
+            )
+            .await?;
+    }
+
+    tracing::info!("--- a write that has been forwarded too many times is not forwarded again");
+    {
+        let n2 = router.get_raft_handle(&2)?;
+        let res = n2
+            .forward_client_write(ForwardClientWriteRequest {
+                rpc: request("0", 2),
+                hops: 2,
+            })
+            .await;
+
+        assert!(
+            matches!(&res, Err(ClientWriteError::ForwardToLeader(e)) if e.leader_id == Some(0)),
+            "got: {:?}",
+            res
+        );
+    }
+
+    tracing::info!("--- isolate the leader, forwarding fails and ForwardToLeader is returned");
+    {
+        router.isolate_node(0);
+
+        let n1 = router.get_raft_handle(&1)?;
+        let res = n1.client_write(request("0", 3)).await;
+
+        assert!(
+            matches!(&res, Err(ClientWriteError::ForwardToLeader(e)) if e.leader_id == Some(0)),
+            "got: {:?}",
+            res
+        );
+    }
+
+    Ok(())
+}
+
+fn request(client_id: &str, serial: u64) -> ClientWriteRequest<memstore::Config> {
+    ClientWriteRequest::new(EntryPayload::Normal(ClientRequest::make_request(client_id, serial)))
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::error::ClientWriteError;
+use openraft::Config;
+use openraft::RaftStorageDebug;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Write a batch of application data with `client_write_batch`.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster.
+/// - write an empty batch, assert it returns no response.
+/// - write a batch to the leader, assert the responses are in order and every entry is applied in order.
+/// - write a batch to a follower, assert the whole batch fails with `ForwardToLeader`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn client_write_batch() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- write an empty batch");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let resps = n0.client_write_batch(vec![]).await?;
+        assert!(resps.is_empty());
+    }
+
+    tracing::info!("--- write a batch to the leader");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let resps = n0.client_write_batch(batch("0", 0..50)).await?;
+
+        assert_eq!(50, resps.len());
+        for (i, resp) in resps.iter().enumerate() {
+            assert_eq!(log_index + 1 + i as u64, resp.log_id.index);
+        }
+        log_index += 50;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "batch is replicated").await?;
+
+        let sm = router.get_storage_handle(&2)?.get_state_machine().await;
+        assert_eq!(
+            Some(&(49, Some("request-48".to_string()))),
+            sm.client_serial_responses.get("0")
+        );
+    }
+
+    tracing::info!("--- write a batch to a follower");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let res = n1.client_write_batch(batch("1", 0..10)).await;
+        assert!(
+            matches!(&res, Err(ClientWriteError::ForwardToLeader(e)) if e.leader_id == Some(0)),
+            "got: {:?}",
+            res
+        );
+    }
+
+    Ok(())
+}
+
+fn batch(client_id: &str, serials: std::ops::Range<u64>) -> Vec<ClientRequest> {
+    serials.map(|serial| ClientRequest::make_request(client_id, serial)).collect()
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreemap;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::error::ClientWriteError;
+use openraft::raft::ClientWriteRequest;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft::Node;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// `ForwardToLeader` carries the node info of the leader, found in the membership.
+///
+/// What does this test do?
+///
+/// - create a node that is not initialized, write to it, assert it knows no leader.
+/// - initialize a 3-node cluster with node info, write to a follower, assert `ForwardToLeader` contains the leader's
+///   node.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn forward_to_leader_node() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    for id in [0, 1, 2] {
+        router.new_raft_node(id);
+    }
+
+    tracing::info!("--- no leader is known before initialization");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let res = n0.client_write(request("0", 1)).await;
+
+        assert!(
+            matches!(&res, Err(ClientWriteError::ForwardToLeader(e)) if e.leader_id.is_none() && e.leader_node.is_none()),
+            "got: {:?}",
+            res
+        );
+    }
+
+    tracing::info!("--- initialize the cluster with node info");
+    let leader = {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.initialize(btreemap! {
+            0 => Node::new("addr-0"),
+            1 => Node::new("addr-1"),
+            2 => Node::new("addr-2"),
+        })
+        .await?;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(1), timeout(), "init").await?;
+        let m = router.wait(&0, timeout()).metrics(|m| m.current_leader.is_some(), "a leader is elected").await?;
+        m.current_leader.unwrap()
+    };
+
+    tracing::info!("--- write to a follower, ForwardToLeader contains the leader's node");
+    {
+        let follower = (leader + 1) % 3;
+        router.wait(&follower, timeout()).current_leader(leader, "follower knows the leader").await?;
+
+        let n = router.get_raft_handle(&follower)?;
+        let res = n.client_write(request("0", 2)).await;
+
+        let want = Node::new(format!("addr-{}", leader));
+        assert!(
+            matches!(&res, Err(ClientWriteError::ForwardToLeader(e)) if e.leader_id == Some(leader) && e.leader_node == Some(want.clone())),
+            "got: {:?}",
+            res
+        );
+    }
+
+    Ok(())
+}
+
+fn request(client_id: &str, serial: u64) -> ClientWriteRequest<memstore::Config> {
+    ClientWriteRequest::new(EntryPayload::Normal(ClientRequest::make_request(client_id, serial)))
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
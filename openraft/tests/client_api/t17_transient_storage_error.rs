@@ -0,0 +1,62 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::Wrapper;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A storage operation that fails with a transient error is retried.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster.
+/// - let the leader's store fail to append twice with a transient error, write to the leader, assert the write
+///   completes and the leader keeps running.
+/// - let it fail more times than the retry limit, assert the leader shuts down with a fatal error.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn transient_storage_error() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            storage_retry_limit: 3,
+            storage_retry_interval: 10,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let mut sto0 = router.get_storage_handle(&0)?;
+
+    tracing::info!("--- append fails twice, then succeeds");
+    {
+        sto0.inner().fail_appends_transiently(2);
+
+        log_index += router.client_request_many(0, "0", 1).await?;
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "write is retried").await?;
+
+        let m = router.get_metrics(&0)?;
+        assert!(m.running_state.is_ok(), "the leader keeps running");
+    }
+
+    tracing::info!("--- append fails more times than the retry limit");
+    {
+        sto0.inner().fail_appends_transiently(10);
+
+        let res = router.client_request_many(0, "1", 1).await;
+        assert!(res.is_err(), "got: {:?}", res);
+
+        router.wait(&0, timeout()).metrics(|m| m.running_state.is_err(), "the leader shuts down").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::FlushMode;
+use memstore::IntoMemClientRequest;
+use memstore::MemStore;
+use openraft::storage::Adaptor;
+use openraft::Config;
+use openraft::LogIdOptionExt;
+use openraft::Raft;
+use openraft::ServerState;
+use tokio::time::sleep;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A log is not committed before the leader's storage reports it is flushed.
+///
+/// What does this test do?
+///
+/// - create a single node cluster with a log store that reports the flush of an append only when told to.
+/// - write a log, assert it is appended but not committed while the flush is pending.
+/// - flush the log, assert the write completes and the log is applied.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn delayed_log_flush() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let router = RaftRouter::new(config.clone());
+
+    let sto = MemStore::new_async().await;
+    let (_, state_machine) = Adaptor::new(sto.clone());
+    let raft = Raft::new(0, config.clone(), router.clone(), sto.clone(), state_machine);
+
+    tracing::info!("--- initialize a single node cluster");
+    let mut log_index = 0;
+    {
+        raft.initialize(btreeset! {0}).await?;
+        log_index += 1;
+
+        raft.wait(timeout()).state(ServerState::Leader, "node 0 becomes leader").await?;
+        raft.wait(timeout()).log(Some(log_index), "the blank log is applied").await?;
+    }
+
+    tracing::info!("--- a write is not committed until the log is flushed");
+    {
+        sto.set_flush_mode(FlushMode::Delayed);
+
+        let r = raft.clone();
+        let write = tokio::spawn(async move { r.client_write(ClientRequest::make_request("foo", 1)).await });
+        log_index += 1;
+
+        raft.wait(timeout()).metrics(|m| m.last_log_index == Some(log_index), "the log is appended").await?;
+
+        sleep(Duration::from_millis(500)).await;
+
+        let m = raft.metrics().borrow().clone();
+        assert_eq!(
+            Some(log_index - 1),
+            m.last_applied.index(),
+            "not committed before flushed"
+        );
+
+        assert_eq!(1, sto.flush_logs());
+
+        let resp = write.await??;
+        assert_eq!(log_index, resp.log_id.index);
+
+        raft.wait(timeout()).log(Some(log_index), "the log is applied").await?;
+    }
+
+    raft.shutdown().await?;
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::raft::ClientWriteRequest;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft::RaftLogReader;
+use openraft::RaftStorageDebug;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The context of a client write is stored in the log entry and replicated to every node.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster.
+/// - write a request with a context and one without to the leader.
+/// - assert every node stores the context with the entry, and applies the request as usual.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn client_write_context() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- write with and without a context");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+
+        let resp = n0.client_write(request("0", 1).with_context("trace-1")).await?;
+        log_index += 1;
+        assert_eq!(log_index, resp.log_id.index);
+
+        n0.client_write(request("0", 2)).await?;
+        log_index += 1;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "writes are applied").await?;
+    }
+
+    tracing::info!("--- every node stores the context with the entry");
+    {
+        for id in [0, 1, 2] {
+            let mut sto = router.get_storage_handle(&id)?;
+
+            let logs = sto.try_get_log_entries(log_index - 1..=log_index).await?;
+            assert_eq!(Some(b"trace-1".to_vec()), logs[0].context, "node {}", id);
+            assert_eq!(None, logs[1].context, "node {}", id);
+
+            let sm = sto.get_state_machine().await;
+            assert_eq!(
+                Some(&(2, Some("request-1".to_string()))),
+                sm.client_serial_responses.get("0"),
+                "node {}",
+                id
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn request(client_id: &str, serial: u64) -> ClientWriteRequest<memstore::Config> {
+    ClientWriteRequest::new(EntryPayload::Normal(ClientRequest::make_request(client_id, serial)))
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::CheckIsLeaderError;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::LogIdOptionExt;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Linearizable read with the read-index protocol.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster and write some data to it.
+/// - call ensure_linearizable on the leader, assert it returns the last written log id and the state machine has
+///   applied it.
+/// - call ensure_linearizable on the followers, assert failure.
+/// - isolate both followers, assert ensure_linearizable on the leader fails.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn ensure_linearizable() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+    router.new_raft_node(0);
+    router.new_raft_node(1);
+    router.new_raft_node(2);
+
+    let mut log_index = 0;
+
+    router.wait_for_log(&btreeset![0, 1, 2], None, None, "empty node").await?;
+    router.wait_for_state(&btreeset![0, 1, 2], ServerState::Learner, None, "empty node").await?;
+    router.assert_pristine_cluster();
+
+    tracing::info!("--- initializing cluster");
+    router.initialize_from_single_node(0).await?;
+    log_index += 1;
+
+    router.wait_for_log(&btreeset![0, 1, 2], Some(log_index), None, "init leader").await?;
+    router.assert_stable_cluster(Some(1), Some(log_index));
+
+    let leader = router.leader().expect("leader not found");
+    assert_eq!(leader, 0, "expected leader to be node 0, got {}", leader);
+
+    tracing::info!("--- write some data, then ensure_linearizable returns the last committed log id");
+
+    router.client_request_many(leader, "0", 10).await?;
+    log_index += 10;
+
+    let read_log_id = router.ensure_linearizable(leader).await?;
+    assert_eq!(LogId::new(LeaderId::new(1, 0), log_index), read_log_id);
+
+    let metrics = router.get_metrics(&leader)?;
+    assert!(metrics.last_applied.index() >= Some(read_log_id.index));
+
+    tracing::info!("--- ensure_linearizable on followers fails with ForwardToLeader");
+
+    for id in [1, 2] {
+        let rst = router.ensure_linearizable(id).await;
+        assert!(
+            matches!(rst, Err(CheckIsLeaderError::ForwardToLeader(_))),
+            "expected ForwardToLeader from node {}, got {:?}",
+            id,
+            rst
+        );
+    }
+
+    tracing::info!("--- isolate node 1 and 2 then ensure_linearizable should fail");
+
+    router.isolate_node(1);
+    router.isolate_node(2);
+    let rst = router.ensure_linearizable(leader).await;
+    tracing::debug!(?rst, "ensure_linearizable with majority down");
+
+    assert!(rst.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::CheckIsLeaderError;
+use openraft::Config;
+use tokio::time::sleep;
+use tokio::time::Instant;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Linearizable read with a lease based on the time a quorum acknowledged the leader.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster.
+/// - assert the leader has a read lease that is not yet expired, and reports `last_quorum_acked` in metrics.
+/// - assert get_read_lease on the followers fails with forward-to-leader info.
+/// - isolate both followers, assert the lease of the leader expires.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn read_lease() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            election_timeout_min: 500,
+            election_timeout_max: 1000,
+            heartbeat_interval: 50,
+            clock_drift_bound: 50,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let _log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let leader = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- the leader has a valid read lease");
+    {
+        // Let heartbeats be acknowledged.
+        sleep(Duration::from_millis(200)).await;
+
+        let lease = leader.get_read_lease().await?;
+        let now = Instant::now();
+        assert!(lease > now, "lease: {:?} now: {:?}", lease, now);
+        assert!(lease <= now + Duration::from_millis(450));
+
+        let acked = router.get_metrics(&0)?.last_quorum_acked;
+        assert!(acked.is_some());
+        assert!(acked.unwrap() <= now);
+    }
+
+    tracing::info!("--- get_read_lease on followers fails with ForwardToLeader");
+    {
+        for id in [1, 2] {
+            let rst = router.get_raft_handle(&id)?.get_read_lease().await;
+            assert!(
+                matches!(&rst, Err(CheckIsLeaderError::ForwardToLeader(f)) if f.leader_id == Some(0)),
+                "expected ForwardToLeader from node {}, got {:?}",
+                id,
+                rst
+            );
+            assert_eq!(None, router.get_metrics(&id)?.last_quorum_acked);
+        }
+    }
+
+    tracing::info!("--- isolate node 1 and 2, the lease expires");
+    {
+        router.isolate_node(1);
+        router.isolate_node(2);
+
+        sleep(Duration::from_millis(500)).await;
+
+        let lease = leader.get_read_lease().await?;
+        assert!(lease <= Instant::now(), "lease should have expired");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use tokio::time::sleep;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Check leadership with `Raft::has_leader_lease()` and find the leader with `Raft::leader_id()`.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster.
+/// - assert only the leader has a leader lease, and every node knows the leader.
+/// - isolate both followers, assert the leader loses its lease but still knows itself as the leader.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn leader_lease_and_id() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            election_timeout_min: 500,
+            election_timeout_max: 1000,
+            heartbeat_interval: 50,
+            clock_drift_bound: 50,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let _log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- only the leader has a lease, every node knows the leader");
+    {
+        // Let heartbeats be acknowledged.
+        sleep(Duration::from_millis(200)).await;
+
+        for id in [0, 1, 2] {
+            let n = router.get_raft_handle(&id)?;
+            assert_eq!(id == 0, n.has_leader_lease().await, "node-{}", id);
+            assert_eq!(Some(0), n.leader_id().await, "node-{}", id);
+        }
+    }
+
+    tracing::info!("--- isolate node 1 and 2, the lease expires");
+    {
+        router.isolate_node(1);
+        router.isolate_node(2);
+
+        sleep(Duration::from_millis(500)).await;
+
+        let leader = router.get_raft_handle(&0)?;
+        assert!(!leader.has_leader_lease().await, "lease should have expired");
+        assert_eq!(Some(0), leader.leader_id().await);
+    }
+
+    Ok(())
+}
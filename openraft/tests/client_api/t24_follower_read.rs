@@ -0,0 +1,99 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::FollowerReadError;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use tokio::time::sleep;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A follower serves reads of its applied log id only if it heard from the leader within a staleness bound.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster.
+/// - assert follower_read on the leader fails with forward-to-leader info.
+/// - assert follower_read on a follower returns its applied log id.
+/// - isolate the follower, assert follower_read fails with a stale-read error beyond the bound, and succeeds within it.
+/// - restore the follower, assert follower_read succeeds again.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn follower_read() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            election_timeout_min: 2000,
+            election_timeout_max: 3000,
+            heartbeat_interval: 50,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    log_index += router.client_request_many(0, "0", 5).await?;
+    router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "write logs").await?;
+
+    let applied = Some(LogId::new(LeaderId::new(1, 0), log_index));
+
+    tracing::info!("--- follower_read on the leader fails with ForwardToLeader");
+    {
+        let rst = router.get_raft_handle(&0)?.follower_read(Duration::from_secs(10)).await;
+        assert!(
+            matches!(&rst, Err(FollowerReadError::ForwardToLeader(f)) if f.leader_id == Some(0)),
+            "got {:?}",
+            rst
+        );
+    }
+
+    tracing::info!("--- follower_read on a follower returns its applied log id");
+    {
+        // Let a heartbeat be received.
+        sleep(Duration::from_millis(200)).await;
+
+        let got = router.get_raft_handle(&1)?.follower_read(Duration::from_millis(200)).await?;
+        assert_eq!(applied, got);
+    }
+
+    tracing::info!("--- isolate node-1, follower_read is served only within the staleness bound");
+    {
+        router.isolate_node(1);
+        sleep(Duration::from_millis(500)).await;
+
+        let n1 = router.get_raft_handle(&1)?;
+
+        let rst = n1.follower_read(Duration::from_millis(200)).await;
+        match rst {
+            Err(FollowerReadError::StaleRead(stale)) => {
+                assert_eq!(Some(0), stale.forward.leader_id);
+                assert_eq!(Duration::from_millis(200), stale.max_staleness);
+                let elapsed = stale.elapsed.unwrap();
+                assert!(elapsed > Duration::from_millis(200), "elapsed: {:?}", elapsed);
+            }
+            _ => panic!("expected StaleRead, got {:?}", rst),
+        }
+
+        let got = n1.follower_read(Duration::from_millis(1500)).await?;
+        assert_eq!(applied, got, "within a larger bound");
+    }
+
+    tracing::info!("--- restore node-1, follower_read is served again");
+    {
+        router.restore_node(1);
+        sleep(Duration::from_millis(200)).await;
+
+        let got = router.get_raft_handle(&1)?.follower_read(Duration::from_millis(200)).await?;
+        assert_eq!(applied, got);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
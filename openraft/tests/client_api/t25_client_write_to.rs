@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::error::ClientWriteError;
+use openraft::raft::ClientWriteRequest;
+use openraft::Config;
+use openraft::EntryPayload;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// `client_write_to()` writes on the leader, and returns `ForwardToLeader` at once on a non-leader, even if
+/// forwarding is enabled.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster with forwarding enabled.
+/// - write to the leader with `client_write_to()`, assert it is committed.
+/// - write to a follower with `client_write_to()`, assert it returns `ForwardToLeader` with the leader and nothing is
+///   written.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn client_write_to() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_forward_client_write: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- write to the leader");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let resp = n0.client_write_to(request("0", 1)).await?;
+        log_index += 1;
+
+        assert_eq!(log_index, resp.log_id.index);
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "write to leader").await?;
+    }
+
+    tracing::info!("--- write to a follower, it is rejected without being forwarded");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let res = n1.client_write_to(request("0", 2)).await;
+
+        match res {
+            Err(ClientWriteError::ForwardToLeader(e)) => {
+                assert_eq!(Some(0), e.leader_id);
+            }
+            _ => panic!("expect ForwardToLeader, got: {:?}", res),
+        }
+
+        // Give a forwarded write, if there were any, the time to be replicated.
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        for id in [0, 1, 2] {
+            let m = router.get_raft_handle(&id)?.metrics().borrow().clone();
+            assert_eq!(Some(log_index), m.last_log_index, "node-{} writes nothing", id);
+        }
+    }
+
+    Ok(())
+}
+
+fn request(client_id: &str, serial: u64) -> ClientWriteRequest<memstore::Config> {
+    ClientWriteRequest::new(EntryPayload::Normal(ClientRequest::make_request(client_id, serial)))
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use memstore::RejectedRequest;
+use openraft::error::ClientWriteError;
+use openraft::raft::ClientWriteRequest;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft::RaftStorageDebug;
+use openraft::Wrapper;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A write rejected by the state machine fails with `ClientWriteError::AppError`.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster, let every state machine reject the requests of client "bad".
+/// - write a request of client "bad", assert it fails with the `AppError` returned by the state machine.
+/// - assert the rejected log is still applied, without changing the state machine.
+/// - assert a request of another client still succeeds.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn client_write_app_error() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    for id in [0, 1, 2] {
+        router.get_storage_handle(&id)?.inner().set_rejected_client(Some("bad".to_string()));
+    }
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- a rejected write fails with AppError");
+    {
+        let req = ClientRequest::make_request("bad", 1);
+        let res = n0.client_write(ClientWriteRequest::new(EntryPayload::Normal(req))).await;
+        log_index += 1;
+
+        let want = RejectedRequest {
+            client: "bad".to_string(),
+            serial: 1,
+        };
+        assert!(
+            matches!(&res, Err(ClientWriteError::AppError(e)) if e == &want),
+            "got: {:?}",
+            res
+        );
+
+        router
+            .wait_for_log(
+                &btreeset! {0,1,2},
+                Some(log_index),
+                timeout(),
+                "the rejected log is applied",
+            )
+            .await?;
+
+        let sm = router.get_storage_handle(&0)?.get_state_machine().await;
+        assert_eq!(
+            None,
+            sm.client_serial_responses.get("bad"),
+            "state machine is not changed"
+        );
+    }
+
+    tracing::info!("--- a write of another client succeeds");
+    {
+        let req = ClientRequest::make_request("good", 1);
+        let resp = n0.client_write(ClientWriteRequest::new(EntryPayload::Normal(req))).await?;
+        log_index += 1;
+
+        assert_eq!(log_index, resp.log_id.index);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -8,3 +8,13 @@ mod fixtures;
 // The later tests may depend on the earlier ones.
 
 mod t10_elect_compare_last_log;
+mod t20_pre_vote;
+mod t30_transfer_leadership;
+mod t31_transfer_vote_within_lease;
+mod t40_check_quorum;
+mod t50_election_priority;
+mod t60_trigger_elect;
+mod t70_simultaneous_start;
+mod t80_tick_interval;
+mod t90_witness;
+mod t95_step_down_reason;
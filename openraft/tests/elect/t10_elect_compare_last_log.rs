@@ -46,6 +46,7 @@ async fn elect_compare_last_log() -> Result<()> {
             &Entry {
                 log_id: LogId::new(LeaderId::new(2, 0), 1),
                 payload: EntryPayload::Membership(Membership::new(vec![btreeset! {0,1}], None)),
+                context: None,
             },
         ])
         .await?;
@@ -65,6 +66,7 @@ async fn elect_compare_last_log() -> Result<()> {
             &Entry {
                 log_id: LogId::new(LeaderId::new(1, 0), 1),
                 payload: EntryPayload::Membership(Membership::new(vec![btreeset! {0,1}], None)),
+                context: None,
             },
             &blank(1, 2),
         ])
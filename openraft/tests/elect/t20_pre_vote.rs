@@ -0,0 +1,56 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A partitioned node does not increase its term with pre-vote, which is enabled by default,
+/// and it does not disrupt the leader when it rejoins.
+///
+/// - Bring up a cluster of 3 voters with the default config.
+/// - Isolate a follower for several election timeouts, its term should not change.
+/// - Restore the follower, the leader should stay the same.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn pre_vote() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let m0 = router.get_metrics(&0)?;
+    assert!(m0.last_election_pre_vote, "the leader is elected with pre-vote");
+    let term = m0.current_term;
+
+    tracing::info!("--- isolate node 2, it should not increase its term");
+    {
+        router.isolate_node(2);
+        tokio::time::sleep(Duration::from_millis(1_000)).await;
+
+        let m2 = router.get_metrics(&2)?;
+        assert_eq!(term, m2.current_term, "isolated node 2 does not start a real election");
+        assert_eq!(ServerState::Follower, m2.state);
+    }
+
+    tracing::info!("--- restore node 2, the leader should not change");
+    {
+        router.restore_node(2);
+        tokio::time::sleep(Duration::from_millis(1_000)).await;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), None, "logs are not changed").await?;
+
+        let m0 = router.get_metrics(&0)?;
+        assert_eq!(ServerState::Leader, m0.state);
+        assert_eq!(term, m0.current_term);
+
+        let m2 = router.get_metrics(&2)?;
+        assert_eq!(term, m2.current_term, "node 2 rejoins without increasing the term");
+        assert_eq!(Some(0), m2.current_leader);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::ClientWriteError;
+use openraft::error::TransferLeaderError;
+use openraft::raft::ClientWriteRequest;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft::ServerState;
+use tokio::time::Instant;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Transfer leadership to a follower.
+///
+/// - Bring up a cluster of 3 voters and 1 learner.
+/// - Transferring leadership to the learner fails.
+/// - Transferring leadership to a lagging follower times out after `transfer_leader_timeout`, then the leader accepts
+///   writes again.
+/// - Transferring leadership to a follower makes it the new leader.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn transfer_leadership() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_prevote: true,
+            transfer_leader_timeout: 500,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {3}).await?;
+
+    let leader = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- can not transfer leadership to a learner");
+    {
+        let res = leader.transfer_leadership(3).await;
+        assert!(
+            matches!(res, Err(TransferLeaderError::NotInMembers(_))),
+            "got: {:?}",
+            res
+        );
+    }
+
+    tracing::info!("--- transfer to a lagging follower: timeout and resume writes");
+    {
+        router.isolate_node(2);
+        router.client_request_many(0, "0", 1).await?;
+        log_index += 1;
+
+        let start = Instant::now();
+        let res = leader.transfer_leadership(2).await;
+        assert!(matches!(res, Err(TransferLeaderError::Timeout(_))), "got: {:?}", res);
+        assert!(start.elapsed() >= Duration::from_millis(500));
+
+        assert_eq!(None, router.get_metrics(&0)?.transferring_leader_to);
+
+        router.client_request_many(0, "0", 1).await?;
+        log_index += 1;
+
+        router.restore_node(2);
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), None, "node 2 catches up").await?;
+    }
+
+    tracing::info!("--- transfer leadership to node 1");
+    {
+        leader.transfer_leadership(1).await?;
+
+        router
+            .wait(&1, Some(Duration::from_millis(2_000)))
+            .state(ServerState::Leader, "node 1 becomes leader")
+            .await?;
+
+        router
+            .wait(&0, Some(Duration::from_millis(2_000)))
+            .current_leader(1, "node 0 sees node 1 as leader")
+            .await?;
+
+        let res = leader.client_write(ClientWriteRequest::new(EntryPayload::Blank)).await;
+        assert!(
+            matches!(&res, Err(ClientWriteError::ForwardToLeader(f)) if f.leader_id == Some(1)),
+            "got: {:?}",
+            res
+        );
+    }
+
+    Ok(())
+}
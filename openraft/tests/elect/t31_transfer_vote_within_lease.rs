@@ -0,0 +1,66 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::raft::VoteRequest;
+use openraft::Config;
+use openraft::ServerState;
+use openraft::Vote;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A follower grants a leader-transfer vote request within the leader lease only if its leader has announced the
+/// transfer to the candidate.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters with a long election timeout, thus the lease of the leader is always valid.
+/// - send an unsolicited leader-transfer vote request of node-2 to node-1, assert it is rejected.
+/// - transfer leadership from node-0 to node-2, assert node-2 becomes the leader well before the lease expires.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn transfer_vote_within_lease() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            election_timeout_min: 3_000,
+            election_timeout_max: 4_000,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let _log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- an unsolicited leader-transfer vote request is rejected within the lease");
+    {
+        let m1 = router.get_metrics(&1)?;
+        let last_log_id = router.get_metrics(&2)?.last_applied;
+
+        let n1 = router.get_raft_handle(&1)?;
+        let resp = n1
+            .vote(VoteRequest::new_leader_transfer(
+                Vote::new(m1.vote.term + 1, 2),
+                last_log_id,
+            ))
+            .await?;
+
+        assert!(!resp.vote_granted, "node-0 did not announce a transfer to node-2");
+        assert_eq!(m1.vote, router.get_metrics(&1)?.vote, "node-1 keeps the vote of node-0");
+    }
+
+    tracing::info!("--- the transfer announced by the leader is granted within the lease");
+    {
+        router.get_raft_handle(&0)?.transfer_leadership(2).await?;
+
+        router.wait(&2, timeout()).state(ServerState::Leader, "node-2 becomes the leader").await?;
+    }
+
+    Ok(())
+}
+
+/// Much shorter than the election timeout: the votes are granted within the lease of node-0.
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,62 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::ClientWriteError;
+use openraft::raft::ClientWriteRequest;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A leader that loses contact with a quorum steps down when check-quorum is enabled.
+///
+/// - Bring up a cluster of 3 voters with check-quorum enabled.
+/// - Isolate the leader and write to it: the write can not be committed.
+/// - The leader steps down within a few election timeouts and the pending write fails with `QuorumNotEnough`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn check_quorum() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_check_quorum: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let _log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- a connected leader does not step down");
+    {
+        tokio::time::sleep(Duration::from_millis(1_000)).await;
+        let m0 = router.get_metrics(&0)?;
+        assert_eq!(ServerState::Leader, m0.state);
+        assert_eq!(Some(0), m0.current_leader);
+    }
+
+    tracing::info!("--- isolate the leader, it steps down and fails pending writes");
+    {
+        router.isolate_node(0);
+
+        let n0 = router.get_raft_handle(&0)?;
+        let pending = tokio::spawn(async move { n0.client_write(ClientWriteRequest::new(EntryPayload::Blank)).await });
+
+        router
+            .wait(&0, Some(Duration::from_millis(2_000)))
+            .metrics(|m| m.state != ServerState::Leader, "node 0 steps down")
+            .await?;
+
+        let res = tokio::time::timeout(Duration::from_millis(1_000), pending).await??;
+        assert!(
+            matches!(res, Err(ClientWriteError::QuorumNotEnough(_))),
+            "got: {:?}",
+            res
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreemap;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::Node;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// When the leader is lost, the voter with the highest election priority becomes the new leader.
+///
+/// - Bring up a cluster of 3 voters with priorities: node-0: 30, node-1: 20, node-2: 10.
+/// - Isolate the leader node-0, node-1 should be elected.
+/// - Restore node-0 and isolate node-1, node-0 should be elected again.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn election_priority() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_prevote: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster with election priorities");
+    {
+        for id in [0, 1, 2] {
+            router.new_raft_node(id);
+        }
+
+        let n0 = router.get_raft_handle(&0)?;
+        n0.initialize(btreemap! {
+            0 => Node::new("").with_election_priority(30),
+            1 => Node::new("").with_election_priority(20),
+            2 => Node::new("").with_election_priority(10),
+        })
+        .await?;
+
+        router.wait(&0, timeout()).state(ServerState::Leader, "node-0 is leader").await?;
+        router.wait_for_log(&btreeset! {0,1,2}, Some(1), timeout(), "init").await?;
+    }
+
+    tracing::info!("--- isolate node-0, node-1 has the highest priority among the others");
+    {
+        router.isolate_node(0);
+
+        router.wait(&1, timeout()).state(ServerState::Leader, "node-1 is elected").await?;
+        router.wait(&2, timeout()).current_leader(1, "node-2 follows node-1").await?;
+    }
+
+    tracing::info!("--- restore node-0 and isolate node-1, node-0 is elected");
+    {
+        router.restore_node(0);
+        router.wait(&0, timeout()).current_leader(1, "node-0 follows node-1").await?;
+
+        router.isolate_node(1);
+
+        router.wait(&0, timeout()).state(ServerState::Leader, "node-0 is elected").await?;
+        router.wait(&2, timeout()).current_leader(0, "node-2 follows node-0").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
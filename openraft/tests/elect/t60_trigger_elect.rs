@@ -0,0 +1,74 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::TriggerElectError;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With automatic elections disabled, an election is started only by `Raft::trigger_elect()`.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters and 1 learner with automatic elections disabled.
+/// - isolate the leader node-0, assert no other node starts an election after the election timeout.
+/// - trigger an election on node-1, assert it becomes the leader.
+/// - trigger an election on the learner, assert it returns `NotInMembers`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn trigger_elect() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_prevote: true,
+            disable_auto_elect: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let _log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {3}).await?;
+
+    tracing::info!("--- isolate node-0, no election is started automatically");
+    {
+        router.isolate_node(0);
+
+        tokio::time::sleep(Duration::from_millis(config.election_timeout_max * 3)).await;
+
+        for id in [1, 2, 3] {
+            let m = router.get_metrics(&id)?;
+            assert_ne!(ServerState::Candidate, m.state, "node-{} does not elect", id);
+            assert_ne!(ServerState::Leader, m.state, "node-{} does not elect", id);
+        }
+    }
+
+    tracing::info!("--- trigger an election on node-1");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        n1.trigger_elect().await?;
+
+        router.wait(&1, timeout()).state(ServerState::Leader, "node-1 is elected").await?;
+        router.wait(&2, timeout()).current_leader(1, "node-2 follows node-1").await?;
+    }
+
+    tracing::info!("--- a learner can not be elected");
+    {
+        let n3 = router.get_raft_handle(&3)?;
+        let res = n3.trigger_elect().await;
+
+        assert!(
+            matches!(&res, Err(TriggerElectError::NotInMembers(e)) if e.node_id == 3),
+            "got: {:?}",
+            res
+        );
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Nodes that start at the same time converge to one leader within a bounded number of election rounds, with
+/// `election_timeout_jitter` spreading their election timeouts.
+///
+/// What does this test do?
+///
+/// - bring up 5 uninitialized nodes with election timeout jitter.
+/// - initialize every node at the same time: every node starts an election at once and the vote is split.
+/// - assert every node agrees on one leader, and that the leader is elected within a bounded number of terms.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn simultaneous_start() -> Result<()> {
+    // Every failed round of election increments the term by at least 1.
+    let max_rounds = 10;
+
+    let config = Arc::new(
+        Config {
+            election_timeout_jitter: 200,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let members = btreeset! {0,1,2,3,4};
+    for id in members.iter() {
+        router.new_raft_node(*id);
+    }
+
+    tracing::info!("--- initialize every node at the same time");
+    {
+        let mut handles = vec![];
+        for id in members.iter() {
+            let n = router.get_raft_handle(id)?;
+            let members = members.clone();
+            handles.push(tokio::spawn(async move { n.initialize(members).await }));
+        }
+
+        for h in handles {
+            h.await??;
+        }
+    }
+
+    tracing::info!("--- every node agrees on one leader within bounded rounds");
+    {
+        let m = router.wait(&0, timeout()).metrics(|m| m.current_leader.is_some(), "a leader is elected").await?;
+        let leader = m.current_leader.unwrap();
+
+        for id in members.iter() {
+            router
+                .wait(id, timeout())
+                .metrics(|m| m.current_leader == Some(leader), "node knows the leader")
+                .await?;
+        }
+
+        let m = router.get_metrics(&leader)?;
+        assert_eq!(ServerState::Leader, m.state);
+        assert!(
+            m.current_term <= max_rounds,
+            "converged within {} rounds, term: {}",
+            max_rounds,
+            m.current_term
+        );
+
+        let leaders = router.latest_metrics().into_iter().filter(|m| m.state == ServerState::Leader).count();
+        assert_eq!(1, leaders, "only one leader");
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(10_000))
+}
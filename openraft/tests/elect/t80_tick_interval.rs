@@ -0,0 +1,56 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A fast tick with short election timeouts still elects a leader, i.e., all time-driven behavior follows
+/// `Config::tick_interval`.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters with a tick of 5 ms and an election timeout of 4 to 8 ticks.
+/// - isolate the leader, assert another node becomes the leader in a few election timeouts.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn tick_interval() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            tick_interval: Some(5),
+            heartbeat_interval: 5,
+            election_timeout_min: 20,
+            election_timeout_max: 40,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- isolate the leader, another node is elected");
+    {
+        router.isolate_node(0);
+
+        router
+            .wait(&1, timeout())
+            .metrics(
+                |m| m.current_leader.is_some() && m.current_leader != Some(0),
+                "node-1 sees a new leader",
+            )
+            .await?;
+
+        let leader = router.get_metrics(&1)?.current_leader.unwrap();
+        router.wait(&leader, timeout()).state(ServerState::Leader, "new leader").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,150 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreemap;
+use maplit::btreeset;
+use openraft::error::ChangeMembershipError;
+use openraft::error::ClientWriteError;
+use openraft::error::TransferLeaderError;
+use openraft::error::TriggerElectError;
+use openraft::ChangeMembers;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft::Node;
+use openraft::RaftLogReader;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A witness votes and acknowledges logs, but it does not store the payloads and never becomes the leader.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 2 voters, add node-2 as a learner and turn it into a witness.
+/// - write some logs, assert node-2 stores none of their payloads.
+/// - isolate node-1, assert logs are still committed by node-0 and the witness.
+/// - assert the witness refuses `trigger_elect()` and is refused as a leadership transfer target.
+/// - assert the witness can not be turned into a learner.
+/// - isolate node-0, assert node-1 is elected with the vote of the witness.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn witness() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster with a witness");
+    let mut log_index = {
+        for id in [0, 1, 2] {
+            router.new_raft_node(id);
+        }
+
+        let n0 = router.get_raft_handle(&0)?;
+        n0.initialize(btreemap! {
+            0 => Node::new(""),
+            1 => Node::new(""),
+        })
+        .await?;
+
+        router.wait(&0, timeout()).state(ServerState::Leader, "node-0 is leader").await?;
+
+        n0.add_learner(2, Some(Node::new("")), true).await?;
+        n0.change_membership(ChangeMembers::AddWitnesses(btreeset! {2}), false, false).await?;
+
+        let m = router.get_metrics(&0)?;
+        let membership = &m.membership_config.membership;
+        assert_eq!(vec![btreeset! {0,1,2}], membership.get_joint_config().clone());
+        assert!(membership.is_witness(&2));
+        assert!(!membership.is_witness(&1));
+
+        m.last_log_index
+    };
+
+    tracing::info!("--- write logs, the witness does not store the payloads");
+    {
+        log_index = Some(log_index.unwrap_or_default() + router.client_request_many(0, "0", 10).await?);
+        router.wait_for_log(&btreeset! {0,1,2}, log_index, timeout(), "write logs").await?;
+
+        let mut sto1 = router.get_storage_handle(&1)?;
+        let logs = sto1.try_get_log_entries(..).await?;
+        let n = logs.iter().filter(|ent| matches!(ent.payload, EntryPayload::Normal(_))).count();
+        assert_eq!(10, n, "node-1 stores the payloads");
+
+        let mut sto2 = router.get_storage_handle(&2)?;
+        let logs = sto2.try_get_log_entries(..).await?;
+        assert_eq!(
+            log_index,
+            logs.last().map(|ent| ent.log_id.index),
+            "the witness has all log ids"
+        );
+        let n = logs.iter().filter(|ent| matches!(ent.payload, EntryPayload::Normal(_))).count();
+        assert_eq!(0, n, "the witness stores no payload");
+    }
+
+    tracing::info!("--- isolate node-1, logs are committed by node-0 and the witness");
+    {
+        router.isolate_node(1);
+
+        log_index = Some(log_index.unwrap() + router.client_request_many(0, "0", 5).await?);
+        router.wait_for_log(&btreeset! {0,2}, log_index, timeout(), "committed without node-1").await?;
+
+        router.restore_node(1);
+        router.wait_for_log(&btreeset! {1}, log_index, timeout(), "node-1 catches up").await?;
+    }
+
+    tracing::info!("--- the witness can not be elected");
+    {
+        let n2 = router.get_raft_handle(&2)?;
+        let res = n2.trigger_elect().await;
+        assert!(
+            matches!(&res, Err(TriggerElectError::IsWitness(e)) if e.node_id == 2),
+            "got: {:?}",
+            res
+        );
+
+        let n0 = router.get_raft_handle(&0)?;
+        let res = n0.transfer_leadership(2).await;
+        assert!(
+            matches!(&res, Err(TransferLeaderError::IsWitness(e)) if e.node_id == 2),
+            "got: {:?}",
+            res
+        );
+    }
+
+    tracing::info!("--- the witness can not be turned into a learner");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let res = n0
+            .change_membership(
+                ChangeMembers::Exact {
+                    voters: btreeset! {0,1},
+                    learners: btreeset! {2},
+                },
+                false,
+                false,
+            )
+            .await;
+        assert!(
+            matches!(&res, Err(ClientWriteError::ChangeMembershipError(ChangeMembershipError::WitnessToLearner(e))) if e.node_id == 2),
+            "got: {:?}",
+            res
+        );
+    }
+
+    tracing::info!("--- isolate node-0, node-1 is elected with the vote of the witness");
+    {
+        router.isolate_node(0);
+
+        router.wait(&1, timeout()).state(ServerState::Leader, "node-1 is elected").await?;
+        router.wait(&2, timeout()).current_leader(1, "the witness follows node-1").await?;
+
+        let m = router.get_metrics(&2)?;
+        assert_eq!(ServerState::Follower, m.state, "the witness stays a follower");
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
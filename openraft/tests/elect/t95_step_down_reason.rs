@@ -0,0 +1,121 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::metrics::StepDownReason;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A leader that loses the quorum reports `LostQuorum` in `RaftMetrics::last_step_down`.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters with check-quorum enabled.
+/// - isolate leader node-0, assert it steps down with `LostQuorum` and the vote it had as a leader.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn step_down_lost_quorum() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_check_quorum: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let _log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let m0 = router.get_metrics(&0)?;
+    assert_eq!(None, m0.last_step_down, "a leader never stepped down");
+
+    tracing::info!("--- isolate the leader, it steps down because of losing the quorum");
+    {
+        router.isolate_node(0);
+
+        router.wait(&0, timeout()).metrics(|m| m.last_step_down.is_some(), "node-0 steps down").await?;
+
+        let step_down = router.get_metrics(&0)?.last_step_down.unwrap();
+        assert_eq!(StepDownReason::LostQuorum, step_down.reason);
+        assert_eq!(m0.vote, step_down.vote, "the vote of the leader that steps down");
+    }
+
+    Ok(())
+}
+
+/// A leader deposed by a higher vote reports `HigherVote`, and one that transfers its leadership reports
+/// `Transferred`.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters, without check-quorum.
+/// - isolate leader node-0 until another leader is elected, then restore it, assert it steps down with `HigherVote`.
+/// - transfer the leadership of the new leader, assert it steps down with `Transferred`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn step_down_higher_vote_and_transfer() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    let _log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- isolate leader node-0 until another leader is elected");
+    let leader = {
+        let vote = router.get_metrics(&0)?.vote;
+
+        router.isolate_node(0);
+        let new_leader = wait_for_leader(&router, &btreeset! {1,2}).await?;
+
+        router.restore_node(0);
+        router
+            .wait(&0, timeout())
+            .metrics(|m| m.last_step_down.is_some(), "node-0 sees the higher vote")
+            .await?;
+
+        let step_down = router.get_metrics(&0)?.last_step_down.unwrap();
+        assert_eq!(StepDownReason::HigherVote, step_down.reason);
+        assert_eq!(vote, step_down.vote);
+
+        new_leader
+    };
+
+    tracing::info!("--- transfer leadership from node-{}", leader);
+    {
+        let target = [0, 1, 2].into_iter().find(|id| *id != leader).unwrap();
+
+        router.get_raft_handle(&leader)?.transfer_leadership(target).await?;
+
+        router
+            .wait(&leader, timeout())
+            .metrics(|m| m.last_step_down.is_some(), "the old leader steps down")
+            .await?;
+
+        let step_down = router.get_metrics(&leader)?.last_step_down.unwrap();
+        assert_eq!(StepDownReason::Transferred, step_down.reason);
+    }
+
+    Ok(())
+}
+
+/// Wait until one of `candidates` becomes the leader and return it.
+async fn wait_for_leader(router: &RaftRouter, candidates: &BTreeSet<u64>) -> Result<u64> {
+    let deadline = tokio::time::Instant::now() + timeout().unwrap();
+    loop {
+        for id in candidates {
+            if router.get_metrics(id)?.state == ServerState::Leader {
+                return Ok(*id);
+            }
+        }
+        if tokio::time::Instant::now() > deadline {
+            anyhow::bail!("no leader is elected among {:?}", candidates);
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(5_000))
+}
@@ -5,11 +5,14 @@
 #[cfg(feature = "bt")] use std::backtrace::Backtrace;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::env;
+use std::error::Error;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::panic::PanicInfo;
+use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -25,28 +28,48 @@ use maplit::btreeset;
 use memstore::Config as MemConfig;
 use memstore::IntoMemClientRequest;
 use memstore::MemStore;
+use openraft::async_runtime::InstantOf;
 use openraft::async_trait::async_trait;
 use openraft::error::AddLearnerError;
 use openraft::error::AppendEntriesError;
 use openraft::error::CheckIsLeaderError;
 use openraft::error::ClientWriteError;
+use openraft::error::Fatal;
 use openraft::error::InstallSnapshotError;
 use openraft::error::NetworkError;
 use openraft::error::NodeNotFound;
+use openraft::error::PayloadTooLarge;
 use openraft::error::RPCError;
 use openraft::error::RemoteError;
+use openraft::error::StreamingError;
+use openraft::error::Timeout;
+use openraft::error::Unreachable;
 use openraft::error::VoteError;
 use openraft::metrics::Wait;
+use openraft::network::Chunked;
+use openraft::network::SnapshotStreaming;
 use openraft::raft::AddLearnerResponse;
 use openraft::raft::AppendEntriesRequest;
 use openraft::raft::AppendEntriesResponse;
 use openraft::raft::ClientWriteRequest;
+use openraft::raft::ClientWriteResponse;
+use openraft::raft::ForwardClientWriteRequest;
 use openraft::raft::InstallSnapshotRequest;
 use openraft::raft::InstallSnapshotResponse;
+use openraft::raft::SnapshotFromRequest;
+use openraft::raft::SnapshotFromResponse;
+use openraft::raft::SnapshotResponse;
+use openraft::raft::TimeoutNowRequest;
+use openraft::raft::TimeoutNowResponse;
 use openraft::raft::VoteRequest;
 use openraft::raft::VoteResponse;
+use openraft::storage::Adaptor;
 use openraft::storage::RaftLogReader;
 use openraft::storage::RaftStorage;
+use openraft::storage::Snapshot;
+use openraft::testing::FaultyNetwork;
+#[cfg(feature = "manual-clock")] use openraft::testing::ManualClock;
+use openraft::testing::NetworkFaults;
 use openraft::Config;
 use openraft::DefensiveCheckBase;
 use openraft::Entry;
@@ -54,7 +77,8 @@ use openraft::EntryPayload;
 use openraft::LeaderId;
 use openraft::LogId;
 use openraft::LogIdOptionExt;
-use openraft::Node;
+use openraft::RPCOption;
+use openraft::RPCTypes;
 use openraft::Raft;
 use openraft::RaftMetrics;
 use openraft::RaftNetwork;
@@ -63,8 +87,12 @@ use openraft::RaftState;
 use openraft::RaftTypeConfig;
 use openraft::ServerState;
 use openraft::StoreExt;
+use openraft::Vote;
 #[allow(unused_imports)] use pretty_assertions::assert_eq;
 #[allow(unused_imports)] use pretty_assertions::assert_ne;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncSeek;
+use tokio::io::AsyncWrite;
 use tracing_appender::non_blocking::WorkerGuard;
 
 use crate::fixtures::logging::init_file_logging;
@@ -74,7 +102,12 @@ pub mod logging;
 pub type StoreWithDefensive<C = MemConfig, S = Arc<MemStore>> = StoreExt<C, S>;
 
 /// A concrete Raft type used during testing.
-pub type MemRaft<C = MemConfig, S = Arc<MemStore>> = Raft<C, TypedRaftRouter<C, S>, StoreWithDefensive<C, S>>;
+pub type MemRaft<C = MemConfig, S = Arc<MemStore>> = Raft<
+    C,
+    FaultyNetwork<C, TypedRaftRouter<C, S>>,
+    Adaptor<C, StoreWithDefensive<C, S>>,
+    Adaptor<C, StoreWithDefensive<C, S>>,
+>;
 
 pub fn init_default_ut_tracing() {
     static START: Once = Once::new();
@@ -136,6 +169,7 @@ pub struct TypedRaftRouter<C: RaftTypeConfig = memstore::Config, S: RaftStorage<
 where
     C::D: Debug + IntoMemClientRequest<C::D>,
     C::R: Debug,
+    C::SnapshotData: AsyncRead + AsyncWrite + AsyncSeek + Unpin,
     S: Default + Clone,
 {
     /// The Raft runtime config which all nodes are using.
@@ -149,6 +183,49 @@ where
     /// To emulate network delay for sending, in milliseconds.
     /// 0 means no delay.
     send_delay: Arc<AtomicU64>,
+
+    /// To emulate a link with a long round trip time, a constant delay for sending, in milliseconds.
+    /// It is added to `send_delay`. 0 means no delay.
+    send_latency: Arc<AtomicU64>,
+
+    /// To emulate a target busy receiving a large batch of logs, a delay for sending an AppendEntries that carries
+    /// logs, in milliseconds. 0 means no delay.
+    logs_delay: Arc<AtomicU64>,
+
+    /// Whether to send a snapshot as a whole with `RaftNetwork::full_snapshot()`, instead of streaming it in chunks.
+    full_snapshot: Arc<AtomicBool>,
+
+    /// The errors to return for RPCs sent to a node.
+    rpc_errors: Arc<Mutex<HashMap<C::NodeId, InjectedRPCError>>>,
+
+    /// The number of AppendEntries rejected with an injected `PayloadTooLarge`.
+    payload_too_large: Arc<AtomicU64>,
+
+    /// The number of clients created for every target.
+    connects: Arc<Mutex<HashMap<C::NodeId, u64>>>,
+
+    /// The number of times a client to every target rebuilds its connection, after a connection error.
+    reconnects: Arc<Mutex<HashMap<C::NodeId, u64>>>,
+
+    /// The faults injected into the RPCs between nodes, by the `FaultyNetwork` every node sends RPCs with.
+    faults: NetworkFaults<C>,
+}
+
+/// An error the router returns for RPCs sent to a node, to emulate a faulty network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InjectedRPCError {
+    /// Return `RPCError::Unreachable` for every RPC.
+    Unreachable,
+
+    /// Return `RPCError::Timeout` for every RPC.
+    Timeout,
+
+    /// Return `RPCError::Network` for every RPC.
+    Network,
+
+    /// Return `RPCError::PayloadTooLarge` for an AppendEntries that carries more than `max_entries` entries, or
+    /// more than `max_bytes` bytes of entries, with a hint of the exceeded limit.
+    PayloadTooLarge { max_entries: u64, max_bytes: u64 },
 }
 
 /// Default `RaftRouter` for memstore.
@@ -157,6 +234,7 @@ pub type RaftRouter = TypedRaftRouter<memstore::Config, Arc<MemStore>>;
 pub struct Builder<C: RaftTypeConfig, S: RaftStorage<C>> {
     config: Arc<Config>,
     send_delay: u64,
+    log_cache_max_entries: u64,
     _phantom: PhantomData<(C, S)>,
 }
 
@@ -164,6 +242,7 @@ impl<C: RaftTypeConfig, S: RaftStorage<C>> Builder<C, S>
 where
     C::D: Debug + IntoMemClientRequest<C::D>,
     C::R: Debug,
+    C::SnapshotData: AsyncRead + AsyncWrite + AsyncSeek + Unpin,
     S: Default + Clone,
 {
     pub fn send_delay(mut self, ms: u64) -> Self {
@@ -171,12 +250,37 @@ where
         self
     }
 
+    /// The log cache size every node uses if `config` does not enable the log cache, `0` to keep it disabled.
+    ///
+    /// The log cache is disabled by default in `Config`, while tests run with it unless told not to.
+    pub fn log_cache_max_entries(mut self, n: u64) -> Self {
+        self.log_cache_max_entries = n;
+        self
+    }
+
     pub fn build(self) -> TypedRaftRouter<C, S> {
+        let config = if self.config.log_cache_max_entries == 0 {
+            Arc::new(Config {
+                log_cache_max_entries: self.log_cache_max_entries,
+                ..self.config.as_ref().clone()
+            })
+        } else {
+            self.config
+        };
+
         TypedRaftRouter {
-            config: self.config,
+            config,
             routing_table: Default::default(),
             isolated_nodes: Default::default(),
             send_delay: Arc::new(AtomicU64::new(self.send_delay)),
+            send_latency: Default::default(),
+            logs_delay: Default::default(),
+            full_snapshot: Default::default(),
+            rpc_errors: Default::default(),
+            payload_too_large: Default::default(),
+            connects: Default::default(),
+            reconnects: Default::default(),
+            faults: Default::default(),
         }
     }
 }
@@ -185,6 +289,7 @@ impl<C: RaftTypeConfig, S: RaftStorage<C>> Clone for TypedRaftRouter<C, S>
 where
     C::D: Debug + IntoMemClientRequest<C::D>,
     C::R: Debug,
+    C::SnapshotData: AsyncRead + AsyncWrite + AsyncSeek + Unpin,
     S: Default + Clone,
 {
     fn clone(&self) -> Self {
@@ -193,6 +298,14 @@ where
             routing_table: self.routing_table.clone(),
             isolated_nodes: self.isolated_nodes.clone(),
             send_delay: self.send_delay.clone(),
+            send_latency: self.send_latency.clone(),
+            logs_delay: self.logs_delay.clone(),
+            full_snapshot: self.full_snapshot.clone(),
+            rpc_errors: self.rpc_errors.clone(),
+            payload_too_large: self.payload_too_large.clone(),
+            connects: self.connects.clone(),
+            reconnects: self.reconnects.clone(),
+            faults: self.faults.clone(),
         }
     }
 }
@@ -201,12 +314,14 @@ impl<C: RaftTypeConfig, S: RaftStorage<C>> TypedRaftRouter<C, S>
 where
     C::D: Debug + IntoMemClientRequest<C::D>,
     C::R: Debug,
+    C::SnapshotData: AsyncRead + AsyncWrite + AsyncSeek + Unpin,
     S: Default + Clone,
 {
     pub fn builder(config: Arc<Config>) -> Builder<C, S> {
         Builder {
             config,
             send_delay: 0,
+            log_cache_max_entries: 4096,
             _phantom: PhantomData,
         }
     }
@@ -220,19 +335,68 @@ where
         self.send_delay.store(ms, Ordering::Relaxed);
     }
 
+    /// Delay every RPC for exactly `ms` milliseconds, in addition to the random `send_delay`.
+    pub fn network_send_latency(&mut self, ms: u64) {
+        self.send_latency.store(ms, Ordering::Relaxed);
+    }
+
+    /// Delay every AppendEntries that carries logs for `ms` milliseconds, while heartbeats are not delayed.
+    pub fn network_logs_delay(&mut self, ms: u64) {
+        self.logs_delay.store(ms, Ordering::Relaxed);
+    }
+
+    /// Return `err` for the RPCs sent to `target`, or stop injecting errors if it is `None`.
+    pub fn set_rpc_error(&self, target: C::NodeId, err: Option<InjectedRPCError>) {
+        let mut rpc_errors = self.rpc_errors.lock().unwrap();
+        match err {
+            Some(err) => {
+                rpc_errors.insert(target, err);
+            }
+            None => {
+                rpc_errors.remove(&target);
+            }
+        }
+    }
+
+    /// The number of AppendEntries rejected with an injected `PayloadTooLarge` error.
+    pub fn payload_too_large_count(&self) -> u64 {
+        self.payload_too_large.load(Ordering::Relaxed)
+    }
+
+    /// The number of clients created by `RaftNetworkFactory::connect()` to `target`.
+    pub fn connect_count(&self, target: &C::NodeId) -> u64 {
+        self.connects.lock().unwrap().get(target).copied().unwrap_or_default()
+    }
+
+    /// The number of times a client to `target` rebuilds its connection in `RaftNetwork::on_connection_error()`.
+    pub fn reconnect_count(&self, target: &C::NodeId) -> u64 {
+        self.reconnects.lock().unwrap().get(target).copied().unwrap_or_default()
+    }
+
+    /// Send snapshots as a whole, by installing the snapshot of the leader on the target with
+    /// `Raft::install_full_snapshot()`.
+    pub fn enable_full_snapshot(&self, enabled: bool) {
+        self.full_snapshot.store(enabled, Ordering::Relaxed);
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     async fn rand_send_delay(&self) {
+        let send_latency = self.send_latency.load(Ordering::Relaxed);
         let send_delay = self.send_delay.load(Ordering::Relaxed);
-        if send_delay == 0 {
+        if send_latency == 0 && send_delay == 0 {
             return;
         }
 
-        let r = rand::random::<u64>() % send_delay;
-        let timeout = Duration::from_millis(r);
+        let r = if send_delay == 0 {
+            0
+        } else {
+            rand::random::<u64>() % send_delay
+        };
+        let timeout = Duration::from_millis(send_latency + r);
         tokio::time::sleep(timeout).await;
     }
 
-    /// Create a cluster: 0 is the initial leader, others are voters and learners
+    /// Create a cluster: the smallest id in `node_ids` is the initial leader, others are voters and learners
     /// NOTE: it create a single node cluster first, then change it to a multi-voter cluster.
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn new_nodes_from_single(
@@ -240,24 +404,23 @@ where
         node_ids: BTreeSet<C::NodeId>,
         learners: BTreeSet<C::NodeId>,
     ) -> anyhow::Result<u64> {
-        let leader_id = C::NodeId::default();
-        assert!(node_ids.contains(&leader_id));
+        let leader_id = node_ids.iter().next().cloned().expect("node_ids must not be empty");
 
-        self.new_raft_node(leader_id);
+        self.new_raft_node(leader_id.clone());
 
         tracing::info!("--- wait for init node to ready");
 
-        self.wait_for_log(&btreeset![leader_id], None, timeout(), "empty").await?;
-        self.wait_for_state(&btreeset![leader_id], ServerState::Learner, timeout(), "empty").await?;
+        self.wait_for_log(&btreeset![leader_id.clone()], None, timeout(), "empty").await?;
+        self.wait_for_state(&btreeset![leader_id.clone()], ServerState::Learner, timeout(), "empty").await?;
 
-        tracing::info!("--- initializing single node cluster: {}", 0);
+        tracing::info!("--- initializing single node cluster: {}", leader_id);
 
-        self.initialize_from_single_node(leader_id).await?;
+        self.initialize_from_single_node(leader_id.clone()).await?;
         let mut log_index = 1; // log 0: initial membership log; log 1: leader initial log
 
         tracing::info!("--- wait for init node to become leader");
 
-        self.wait_for_log(&btreeset![leader_id], Some(log_index), timeout(), "init").await?;
+        self.wait_for_log(&btreeset![leader_id.clone()], Some(log_index), timeout(), "init").await?;
         self.assert_stable_cluster(Some(1), Some(log_index));
 
         for id in node_ids.iter() {
@@ -266,8 +429,8 @@ where
             }
             tracing::info!("--- add voter: {}", id);
 
-            self.new_raft_node(*id);
-            self.add_learner(leader_id, *id).await?;
+            self.new_raft_node(id.clone());
+            self.add_learner(leader_id.clone(), id.clone()).await?;
             log_index += 1;
         }
         self.wait_for_log(
@@ -281,7 +444,7 @@ where
         if node_ids.len() > 1 {
             tracing::info!("--- change membership to setup voters: {:?}", node_ids);
 
-            let node = self.get_raft_handle(&C::NodeId::default())?;
+            let node = self.get_raft_handle(&leader_id)?;
             node.change_membership(node_ids.clone(), true, false).await?;
             log_index += 2;
 
@@ -296,8 +459,8 @@ where
 
         for id in learners.clone() {
             tracing::info!("--- add learner: {}", id);
-            self.new_raft_node(id);
-            self.add_learner(C::NodeId::default(), id).await?;
+            self.new_raft_node(id.clone());
+            self.add_learner(leader_id.clone(), id).await?;
             log_index += 1;
         }
         self.wait_for_log(
@@ -345,7 +508,9 @@ where
 
     #[tracing::instrument(level = "debug", skip(self, sto))]
     pub fn new_raft_node_with_sto(&mut self, id: C::NodeId, sto: StoreWithDefensive<C, S>) {
-        let node = Raft::new(id, self.config.clone(), self.clone(), sto.clone());
+        let (log_store, state_machine) = Adaptor::new(sto.clone());
+        let network = FaultyNetwork::new(id.clone(), self.clone(), self.faults.clone());
+        let node = Raft::new(id.clone(), self.config.clone(), network, log_store, state_machine);
         let mut rt = self.routing_table.lock().unwrap();
         rt.insert(id, (node, sto));
     }
@@ -367,7 +532,7 @@ where
 
     /// Initialize all nodes based on the config in the routing table.
     pub async fn initialize_from_single_node(&self, node_id: C::NodeId) -> Result<()> {
-        tracing::info!({ node_id = display(node_id) }, "initializing cluster from single node");
+        tracing::info!({ node_id = display(&node_id) }, "initializing cluster from single node");
         let members: BTreeSet<C::NodeId> = {
             let rt = self.routing_table.lock().unwrap();
             rt.keys().cloned().collect()
@@ -378,6 +543,32 @@ where
         Ok(())
     }
 
+    /// Move the `ManualClock` forward by `duration`, pausing it first if it is not yet paused.
+    ///
+    /// The clock advances in steps of 10 ms, and the nodes get a little wall clock time to act on every step, thus the
+    /// timers between two steps fire in order.
+    #[cfg(feature = "manual-clock")]
+    pub async fn advance_time(&self, duration: Duration) {
+        if !ManualClock::is_paused() {
+            ManualClock::pause();
+        }
+
+        let step = Duration::from_millis(10);
+        let mut advanced = Duration::default();
+
+        while advanced < duration {
+            let d = std::cmp::min(step, duration - advanced);
+            ManualClock::advance(d);
+            advanced += d;
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    }
+
+    /// The faults injected into the RPCs between nodes, adjustable at runtime.
+    pub fn faults(&self) -> &NetworkFaults<C> {
+        &self.faults
+    }
+
     /// Isolate the network of the specified node.
     #[tracing::instrument(level = "debug", skip(self))]
     pub fn isolate_node(&self, id: C::NodeId) {
@@ -385,7 +576,7 @@ where
     }
 
     /// Get a payload of the latest metrics from each node in the cluster.
-    pub fn latest_metrics(&self) -> Vec<RaftMetrics<C::NodeId>> {
+    pub fn latest_metrics(&self) -> Vec<RaftMetrics<C::NodeId, C::Node, InstantOf<C>>> {
         let rt = self.routing_table.lock().unwrap();
         let mut metrics = vec![];
         for node in rt.values() {
@@ -394,7 +585,7 @@ where
         metrics
     }
 
-    pub fn get_metrics(&self, node_id: &C::NodeId) -> Result<RaftMetrics<C::NodeId>> {
+    pub fn get_metrics(&self, node_id: &C::NodeId) -> Result<RaftMetrics<C::NodeId, C::Node, InstantOf<C>>> {
         let node = self.get_raft_handle(node_id)?;
         let metrics = node.metrics().borrow().clone();
         Ok(metrics)
@@ -404,7 +595,7 @@ where
     pub fn get_raft_handle(&self, node_id: &C::NodeId) -> std::result::Result<MemRaft<C, S>, NodeNotFound<C::NodeId>> {
         let rt = self.routing_table.lock().unwrap();
         let raft_and_sto = rt.get(node_id).ok_or_else(|| NodeNotFound {
-            node_id: *node_id,
+            node_id: node_id.clone(),
             source: AnyError::error(""),
         })?;
         let r = raft_and_sto.clone().0;
@@ -426,16 +617,16 @@ where
         func: T,
         timeout: Option<Duration>,
         msg: &str,
-    ) -> Result<RaftMetrics<C::NodeId>>
+    ) -> Result<RaftMetrics<C::NodeId, C::Node, InstantOf<C>>>
     where
-        T: Fn(&RaftMetrics<C::NodeId>) -> bool + Send,
+        T: Fn(&RaftMetrics<C::NodeId, C::Node, InstantOf<C>>) -> bool + Send,
     {
         let wait = self.wait(node_id, timeout);
         let rst = wait.metrics(func, format!("node-{} {}", node_id, msg)).await?;
         Ok(rst)
     }
 
-    pub fn wait(&self, node_id: &C::NodeId, timeout: Option<Duration>) -> Wait<C::NodeId> {
+    pub fn wait(&self, node_id: &C::NodeId, timeout: Option<Duration>) -> Wait<C::NodeId, C::Node, C::AsyncRuntime> {
         let node = {
             let rt = self.routing_table.lock().unwrap();
             rt.get(node_id).expect("target node not found in routing table").clone().0
@@ -503,7 +694,7 @@ where
         msg: &str,
     ) -> Result<()> {
         for i in node_ids.iter() {
-            self.wait(i, timeout).snapshot(want, msg).await?;
+            self.wait(i, timeout).snapshot(want.clone(), msg).await?;
         }
         Ok(())
     }
@@ -516,7 +707,7 @@ where
         };
 
         self.latest_metrics().into_iter().find_map(|node| {
-            if node.current_leader == Some(node.id) {
+            if node.current_leader.as_ref() == Some(&node.id) {
                 if isolated.contains(&node.id) {
                     None
                 } else {
@@ -540,13 +731,13 @@ where
         &self,
         leader: C::NodeId,
         target: C::NodeId,
-    ) -> Result<AddLearnerResponse<C::NodeId>, AddLearnerError<C::NodeId>> {
+    ) -> Result<AddLearnerResponse<C::NodeId>, AddLearnerError<C::NodeId, C::Node>> {
         let node = self.get_raft_handle(&leader).unwrap();
         node.add_learner(target, None, true).await
     }
 
     /// Send a is_leader request to the target node.
-    pub async fn is_leader(&self, target: C::NodeId) -> Result<(), CheckIsLeaderError<C::NodeId>> {
+    pub async fn is_leader(&self, target: C::NodeId) -> Result<(), CheckIsLeaderError<C::NodeId, C::Node>> {
         let node = {
             let rt = self.routing_table.lock().unwrap();
             rt.get(&target).unwrap_or_else(|| panic!("node with ID {} does not exist", target)).clone()
@@ -554,16 +745,28 @@ where
         node.0.is_leader().await
     }
 
+    /// Send a ensure_linearizable request to the target node.
+    pub async fn ensure_linearizable(
+        &self,
+        target: C::NodeId,
+    ) -> Result<LogId<C::NodeId>, CheckIsLeaderError<C::NodeId, C::Node>> {
+        let node = {
+            let rt = self.routing_table.lock().unwrap();
+            rt.get(&target).unwrap_or_else(|| panic!("node with ID {} does not exist", target)).clone()
+        };
+        node.0.ensure_linearizable().await
+    }
+
     /// Send a client request to the target node, causing test failure on error.
     pub async fn client_request(
         &self,
         mut target: C::NodeId,
         client_id: &str,
         serial: u64,
-    ) -> Result<(), ClientWriteError<C::NodeId>> {
+    ) -> Result<(), ClientWriteError<C::NodeId, C::Node, C::AppError>> {
         for ith in 0..3 {
             let req = <C::D as IntoMemClientRequest<C::D>>::make_request(client_id, serial);
-            if let Err(err) = self.send_client_request(target, req).await {
+            if let Err(err) = self.send_client_request(target.clone(), req).await {
                 tracing::error!({error=%err}, "error from client request");
 
                 #[allow(clippy::single_match)]
@@ -574,8 +777,8 @@ where
                             ith,
                             e.leader_id
                         );
-                        if let Some(l) = e.leader_id {
-                            target = l;
+                        if let Some(l) = &e.leader_id {
+                            target = l.clone();
                             continue;
                         }
                     }
@@ -595,7 +798,9 @@ where
 
     /// Send external request to the particular node.
     pub fn external_request<
-        F: FnOnce(&RaftState<C::NodeId>, &mut StoreExt<C, S>, &mut TypedRaftRouter<C, S>) + Send + 'static,
+        F: FnOnce(&RaftState<C::NodeId, C::Node, InstantOf<C>>, &mut StoreExt<C, S>, &mut TypedRaftRouter<C, S>)
+            + Send
+            + 'static,
     >(
         &self,
         target: C::NodeId,
@@ -621,9 +826,9 @@ where
         target: C::NodeId,
         client_id: &str,
         count: usize,
-    ) -> Result<u64, ClientWriteError<C::NodeId>> {
+    ) -> Result<u64, ClientWriteError<C::NodeId, C::Node, C::AppError>> {
         for idx in 0..count {
-            self.client_request(target, client_id, idx as u64).await?;
+            self.client_request(target.clone(), client_id, idx as u64).await?;
         }
 
         Ok(count as u64)
@@ -633,7 +838,7 @@ where
         &self,
         target: C::NodeId,
         req: C::D,
-    ) -> std::result::Result<C::R, ClientWriteError<C::NodeId>> {
+    ) -> std::result::Result<C::R, ClientWriteError<C::NodeId, C::Node, C::AppError>> {
         let node = {
             let rt = self.routing_table.lock().unwrap();
             rt.get(&target)
@@ -734,12 +939,12 @@ where
         } else {
             leader.last_log_index
         };
-        let all_nodes = nodes.iter().map(|node| node.id).collect::<Vec<_>>();
+        let all_nodes = nodes.iter().map(|node| node.id.clone()).collect::<Vec<_>>();
 
         for node in non_isolated_nodes.iter() {
             assert_eq!(
                 node.current_leader,
-                Some(leader.id),
+                Some(leader.id.clone()),
                 "node {} has leader {:?}, expected {}",
                 node.id,
                 node.current_leader,
@@ -849,7 +1054,7 @@ where
 
         assert_eq!(
             &last_applied,
-            &Some(expect_sm_last_applied_log),
+            &Some(expect_sm_last_applied_log.clone()),
             "expected node {} to have state machine last_applied_log {}, got {:?}",
             id,
             expect_sm_last_applied_log,
@@ -882,8 +1087,8 @@ where
                 &id,
                 expect_term,
                 expect_last_log,
-                expect_voted_for,
-                expect_sm_last_applied_log,
+                expect_voted_for.clone(),
+                expect_sm_last_applied_log.clone(),
                 &expect_snapshot,
             )
             .await?;
@@ -893,16 +1098,56 @@ where
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
-    pub fn check_reachable(&self, id: C::NodeId, target: C::NodeId) -> std::result::Result<(), NetworkError> {
+    pub fn check_reachable(&self, id: C::NodeId, target: C::NodeId) -> std::result::Result<(), Unreachable> {
         let isolated = self.isolated_nodes.lock().unwrap();
 
         if isolated.contains(&target) || isolated.contains(&id) {
-            let network_err = NetworkError::new(&AnyError::error(format!("isolated:{} -> {}", id, target)));
-            return Err(network_err);
+            let unreachable = Unreachable::new(&AnyError::error(format!("isolated:{} -> {}", id, target)));
+            return Err(unreachable);
         }
 
         Ok(())
     }
+
+    /// Return the error injected for an RPC from `id` to `target`, which carries `entries`.
+    pub fn check_rpc_error<E: Error>(
+        &self,
+        action: RPCTypes,
+        id: C::NodeId,
+        target: C::NodeId,
+        entries: &[Arc<Entry<C>>],
+    ) -> std::result::Result<(), RPCError<C::NodeId, E, C::Node>> {
+        let err = match self.rpc_errors.lock().unwrap().get(&target) {
+            Some(err) => err.clone(),
+            None => return Ok(()),
+        };
+
+        let msg = format!("injected:{} -> {}", id, target);
+        match err {
+            InjectedRPCError::Unreachable => Err(Unreachable::new(&AnyError::error(msg)).into()),
+            InjectedRPCError::Timeout => Err(RPCError::Timeout(Timeout {
+                action,
+                id,
+                target,
+                timeout: Duration::from_millis(0),
+            })),
+            InjectedRPCError::Network => Err(NetworkError::new(&AnyError::error(msg)).into()),
+            InjectedRPCError::PayloadTooLarge { max_entries, max_bytes } => {
+                let bytes = entries.iter().map(|ent| C::entry_size(ent)).sum::<u64>();
+                let err = if action != RPCTypes::AppendEntries {
+                    return Ok(());
+                } else if entries.len() as u64 > max_entries {
+                    PayloadTooLarge::new_entries_hint(max_entries)
+                } else if bytes > max_bytes {
+                    PayloadTooLarge::new_bytes_hint(max_bytes)
+                } else {
+                    return Ok(());
+                };
+                self.payload_too_large.fetch_add(1, Ordering::Relaxed);
+                Err(err.into())
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -910,11 +1155,14 @@ impl<C: RaftTypeConfig, S: RaftStorage<C>> RaftNetworkFactory<C> for TypedRaftRo
 where
     C::D: Debug + IntoMemClientRequest<C::D>,
     C::R: Debug,
+    C::SnapshotData: AsyncRead + AsyncWrite + AsyncSeek + Unpin,
     S: Default + Clone,
 {
     type Network = RaftRouterNetwork<C, S>;
 
-    async fn connect(&mut self, target: C::NodeId, _node: Option<&Node>) -> Self::Network {
+    async fn connect(&mut self, target: C::NodeId, _node: Option<&C::Node>) -> Self::Network {
+        *self.connects.lock().unwrap().entry(target.clone()).or_default() += 1;
+
         RaftRouterNetwork {
             target,
             owner: self.clone(),
@@ -926,35 +1174,98 @@ pub struct RaftRouterNetwork<C: RaftTypeConfig, S: RaftStorage<C>>
 where
     C::D: Debug + IntoMemClientRequest<C::D>,
     C::R: Debug,
+    C::SnapshotData: AsyncRead + AsyncWrite + AsyncSeek + Unpin,
     S: Default + Clone,
 {
     target: C::NodeId,
     owner: TypedRaftRouter<C, S>,
 }
 
+impl<C: RaftTypeConfig, S: RaftStorage<C>> RaftRouterNetwork<C, S>
+where
+    C::D: Debug + IntoMemClientRequest<C::D>,
+    C::R: Debug,
+    C::SnapshotData: AsyncRead + AsyncWrite + AsyncSeek + Unpin,
+    S: Default + Clone,
+{
+    /// Read the current snapshot of a node.
+    async fn get_snapshot_of(
+        &self,
+        node_id: &C::NodeId,
+    ) -> std::result::Result<
+        Option<Snapshot<C::NodeId, C::SnapshotData, C::Node>>,
+        RPCError<C::NodeId, Fatal<C::NodeId>, C::Node>,
+    > {
+        let node = self.owner.get_raft_handle(node_id)?;
+        let snapshot = node.get_snapshot().await.map_err(|e| RemoteError::new(node_id.clone(), e))?;
+        Ok(snapshot)
+    }
+
+    /// Copy a snapshot to the target and install it with `Raft::install_full_snapshot()`.
+    async fn install_copy(
+        &self,
+        vote: Vote<C::NodeId>,
+        mut snapshot: Snapshot<C::NodeId, C::SnapshotData, C::Node>,
+    ) -> std::result::Result<SnapshotResponse<C::NodeId>, RPCError<C::NodeId, Fatal<C::NodeId>, C::Node>> {
+        let node = self.owner.get_raft_handle(&self.target)?;
+
+        let mut data = node.begin_receiving_snapshot().await.map_err(|e| RemoteError::new(self.target.clone(), e))?;
+        tokio::io::copy(&mut snapshot.snapshot, &mut data).await.map_err(|e| NetworkError::new(&e))?;
+
+        let resp = node
+            .install_full_snapshot(vote, Snapshot {
+                meta: snapshot.meta,
+                snapshot: data,
+            })
+            .await;
+        let resp = resp.map_err(|e| RemoteError::new(self.target.clone(), e))?;
+        Ok(resp)
+    }
+}
+
 #[async_trait]
 impl<C: RaftTypeConfig, S: RaftStorage<C>> RaftNetwork<C> for RaftRouterNetwork<C, S>
 where
     C::D: Debug + IntoMemClientRequest<C::D>,
     C::R: Debug,
+    C::SnapshotData: AsyncRead + AsyncWrite + AsyncSeek + Unpin,
     S: Default + Clone,
 {
+    /// Emulate rebuilding the connection to the target, by counting it.
+    fn on_connection_error(&mut self) {
+        *self.owner.reconnects.lock().unwrap().entry(self.target.clone()).or_default() += 1;
+    }
+
     /// Send an AppendEntries RPC to the target Raft node (§5).
     async fn send_append_entries(
         &mut self,
         rpc: AppendEntriesRequest<C>,
-    ) -> std::result::Result<AppendEntriesResponse<C::NodeId>, RPCError<C::NodeId, AppendEntriesError<C::NodeId>>> {
+    ) -> std::result::Result<
+        AppendEntriesResponse<C::NodeId>,
+        RPCError<C::NodeId, AppendEntriesError<C::NodeId>, C::Node>,
+    > {
         tracing::debug!("append_entries to id={} {:?}", self.target, rpc);
         self.owner.rand_send_delay().await;
 
-        self.owner.check_reachable(rpc.vote.node_id, self.target)?;
+        let logs_delay = self.owner.logs_delay.load(Ordering::Relaxed);
+        if logs_delay > 0 && !rpc.entries.is_empty() {
+            tokio::time::sleep(Duration::from_millis(logs_delay)).await;
+        }
+
+        self.owner.check_reachable(rpc.vote.node_id.clone(), self.target.clone())?;
+        self.owner.check_rpc_error(
+            RPCTypes::AppendEntries,
+            rpc.vote.node_id.clone(),
+            self.target.clone(),
+            &rpc.entries,
+        )?;
 
         let node = self.owner.get_raft_handle(&self.target)?;
 
         let resp = node.append_entries(rpc).await;
 
         tracing::debug!("append_entries: recv resp from id={} {:?}", self.target, resp);
-        let resp = resp.map_err(|e| RemoteError::new(self.target, e))?;
+        let resp = resp.map_err(|e| RemoteError::new(self.target.clone(), e))?;
         Ok(resp)
     }
 
@@ -962,32 +1273,128 @@ where
     async fn send_install_snapshot(
         &mut self,
         rpc: InstallSnapshotRequest<C>,
-    ) -> std::result::Result<InstallSnapshotResponse<C::NodeId>, RPCError<C::NodeId, InstallSnapshotError<C::NodeId>>>
-    {
+    ) -> std::result::Result<
+        InstallSnapshotResponse<C::NodeId>,
+        RPCError<C::NodeId, InstallSnapshotError<C::NodeId>, C::Node>,
+    > {
         self.owner.rand_send_delay().await;
 
-        self.owner.check_reachable(rpc.vote.node_id, self.target)?;
+        self.owner.check_reachable(rpc.vote.node_id.clone(), self.target.clone())?;
+        self.owner.check_rpc_error(
+            RPCTypes::InstallSnapshot,
+            rpc.vote.node_id.clone(),
+            self.target.clone(),
+            &[],
+        )?;
 
         let node = self.owner.get_raft_handle(&self.target)?;
 
         let resp = node.install_snapshot(rpc).await;
-        let resp = resp.map_err(|e| RemoteError::new(self.target, e))?;
+        let resp = resp.map_err(|e| RemoteError::new(self.target.clone(), e))?;
+        Ok(resp)
+    }
+
+    /// Install the snapshot on the target as a whole if full snapshot is enabled, otherwise stream it in chunks.
+    async fn full_snapshot(
+        &mut self,
+        vote: Vote<C::NodeId>,
+        snapshot: Snapshot<C::NodeId, C::SnapshotData, C::Node>,
+        streaming: SnapshotStreaming,
+    ) -> std::result::Result<SnapshotResponse<C::NodeId>, StreamingError<C::NodeId>> {
+        if !self.owner.full_snapshot.load(Ordering::Relaxed) {
+            return Chunked::send_snapshot(self, vote, snapshot, streaming).await;
+        }
+
+        self.owner.rand_send_delay().await;
+
+        self.owner.check_reachable(vote.node_id.clone(), self.target.clone())?;
+
+        let node = self.owner.get_raft_handle(&self.target).map_err(|e| NetworkError::new(&e))?;
+        let resp = node.install_full_snapshot(vote, snapshot).await.map_err(|e| NetworkError::new(&e))?;
         Ok(resp)
     }
 
+    /// Copy the snapshot of the source to the target, if it includes the required log id.
+    async fn get_snapshot_from(
+        &mut self,
+        rpc: SnapshotFromRequest<C>,
+        _option: RPCOption,
+    ) -> std::result::Result<SnapshotFromResponse<C::NodeId>, RPCError<C::NodeId, Fatal<C::NodeId>, C::Node>> {
+        self.owner.rand_send_delay().await;
+
+        self.owner.check_reachable(rpc.vote.node_id.clone(), self.target.clone())?;
+        self.owner.check_reachable(self.target.clone(), rpc.source.clone())?;
+
+        let snapshot = match self.get_snapshot_of(&rpc.source).await? {
+            Some(s) if s.meta.last_log_id >= rpc.must_include => s,
+            _ => {
+                let node = self.owner.get_raft_handle(&self.target)?;
+                let vote = node.metrics().borrow().vote.clone();
+                return Ok(SnapshotFromResponse {
+                    vote,
+                    last_log_id: None,
+                });
+            }
+        };
+
+        let last_log_id = snapshot.meta.last_log_id.clone();
+        let resp = self.install_copy(rpc.vote, snapshot).await?;
+        Ok(SnapshotFromResponse {
+            vote: resp.vote,
+            last_log_id: Some(last_log_id),
+        })
+    }
+
     /// Send a RequestVote RPC to the target Raft node (§5).
     async fn send_vote(
         &mut self,
         rpc: VoteRequest<C::NodeId>,
-    ) -> std::result::Result<VoteResponse<C::NodeId>, RPCError<C::NodeId, VoteError<C::NodeId>>> {
+    ) -> std::result::Result<VoteResponse<C::NodeId>, RPCError<C::NodeId, VoteError<C::NodeId>, C::Node>> {
         self.owner.rand_send_delay().await;
 
-        self.owner.check_reachable(rpc.vote.node_id, self.target)?;
+        self.owner.check_reachable(rpc.vote.node_id.clone(), self.target.clone())?;
+        self.owner.check_rpc_error(RPCTypes::Vote, rpc.vote.node_id.clone(), self.target.clone(), &[])?;
 
         let node = self.owner.get_raft_handle(&self.target)?;
 
         let resp = node.vote(rpc).await;
-        let resp = resp.map_err(|e| RemoteError::new(self.target, e))?;
+        let resp = resp.map_err(|e| RemoteError::new(self.target.clone(), e))?;
+        Ok(resp)
+    }
+
+    /// Send a TimeoutNow RPC to the target Raft node.
+    async fn send_timeout_now(
+        &mut self,
+        rpc: TimeoutNowRequest<C::NodeId>,
+    ) -> std::result::Result<TimeoutNowResponse<C::NodeId>, RPCError<C::NodeId, VoteError<C::NodeId>, C::Node>> {
+        self.owner.rand_send_delay().await;
+
+        self.owner.check_reachable(rpc.vote.node_id.clone(), self.target.clone())?;
+
+        let node = self.owner.get_raft_handle(&self.target)?;
+
+        let resp = node.timeout_now(rpc).await;
+        let resp = resp.map_err(|e| RemoteError::new(self.target.clone(), e))?;
+        Ok(resp)
+    }
+
+    /// Forward a client write to the target Raft node.
+    async fn forward_client_write(
+        &mut self,
+        rpc: ForwardClientWriteRequest<C>,
+    ) -> std::result::Result<
+        ClientWriteResponse<C>,
+        RPCError<C::NodeId, ClientWriteError<C::NodeId, C::Node, C::AppError>, C::Node>,
+    > {
+        self.owner.rand_send_delay().await;
+
+        // The sender is not known, only check the target.
+        self.owner.check_reachable(self.target.clone(), self.target.clone())?;
+
+        let node = self.owner.get_raft_handle(&self.target)?;
+
+        let resp = node.forward_client_write(rpc).await;
+        let resp = resp.map_err(|e| RemoteError::new(self.target.clone(), e))?;
         Ok(resp)
     }
 }
@@ -1019,5 +1426,6 @@ where C::NodeId: From<u64> {
     Entry {
         log_id: LogId::new(LeaderId::new(term, 0.into()), index),
         payload: EntryPayload::Blank,
+        context: None,
     }
 }
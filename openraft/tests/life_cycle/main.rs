@@ -9,3 +9,7 @@ mod fixtures;
 
 mod t20_initialization;
 mod t20_shutdown;
+mod t21_initialize_with_learners;
+mod t30_shutdown_gracefully;
+mod t40_update_config;
+mod t50_restart_with_saved_committed;
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use maplit::btreeset;
+use openraft::error::InitializeError;
+use openraft::Config;
+use openraft::EffectiveMembership;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::Membership;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Initialize a cluster with voters and learners in the first membership config.
+///
+/// What does this test do?
+///
+/// - brings 4 pristine nodes online.
+/// - initializes node-0 with voters {0,1,2} and learner {3}.
+/// - asserts every node has the effective membership with both the voters and the learner.
+/// - asserts node-3 receives the logs as a learner, without calling `add_learner()`.
+/// - asserts initializing the node again is not allowed.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn initialize_with_learners() -> anyhow::Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+    for id in [0, 1, 2, 3] {
+        router.new_raft_node(id);
+    }
+
+    let mut log_index = 0;
+
+    tracing::info!("--- initialize with voters and a learner");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.initialize_with_learners(btreeset! {0,1,2}, btreeset! {3}).await?;
+
+        // The membership log is at index 0, the blank log of the leader is at index 1.
+        log_index += 1;
+
+        router.wait_for_log(&btreeset![0, 1, 2, 3], Some(log_index), timeout(), "init").await?;
+        router.wait(&3, timeout()).state(ServerState::Learner, "node-3 is a learner").await?;
+    }
+
+    tracing::info!("--- check membership state");
+    for node_id in [0, 1, 2, 3] {
+        router.external_request(node_id, move |s, _sto, _net| {
+            let want = EffectiveMembership::new(
+                Some(LogId::new(LeaderId::new(0, 0), 0)),
+                Membership::new(vec![btreeset! {0,1,2}], Some(btreeset! {3})),
+            );
+            assert_eq!(
+                s.membership_state.effective,
+                Arc::new(want),
+                "node-{}: effective membership",
+                node_id
+            );
+        });
+    }
+
+    tracing::info!("--- initialize again, not allowed");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let res = n0.initialize_with_learners(btreeset! {0,1,2}, btreeset! {3}).await;
+
+        match res {
+            Err(InitializeError::NotAllowed(_)) => {}
+            _ => {
+                panic!("expect InitializeError::NotAllowed, got: {:?}", res);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1000))
+}
@@ -0,0 +1,141 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::error::ClientWriteError;
+use openraft::error::Fatal;
+use openraft::raft::ClientWriteRequest;
+use openraft::Config;
+use openraft::EntryPayload;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A graceful shutdown waits for the accepted logs to be applied, and rejects new writes.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters, isolate node-1 and node-2.
+/// - write to the leader, the write can not be committed.
+/// - shutdown the leader gracefully, assert a new write is rejected with `Fatal::Stopped`.
+/// - restore node-1 and node-2, assert the pending write is applied and the shutdown reports no unapplied log.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn shutdown_gracefully() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            graceful_shutdown_timeout: 5_000,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- isolate followers, write to the leader");
+    let pending = {
+        router.isolate_node(1);
+        router.isolate_node(2);
+
+        let n0 = router.get_raft_handle(&0)?;
+        let pending = tokio::spawn(async move { n0.client_write(request("0", 1)).await });
+
+        router
+            .wait(&0, timeout())
+            .metrics(|m| m.last_log_index == Some(log_index + 1), "write is appended")
+            .await?;
+        pending
+    };
+
+    tracing::info!("--- shutdown gracefully, new writes are rejected");
+    let shutdown = {
+        let n0 = router.get_raft_handle(&0)?;
+        let shutdown = tokio::spawn(async move { n0.shutdown_gracefully().await });
+
+        // Wait for the shutdown to start.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let n0 = router.get_raft_handle(&0)?;
+        let res = n0.client_write(request("0", 2)).await;
+        assert_eq!(Err(ClientWriteError::Fatal(Fatal::Stopped)), res.map(|_| ()));
+
+        shutdown
+    };
+
+    tracing::info!("--- restore followers, the pending write is applied before shutdown");
+    {
+        router.restore_node(1);
+        router.restore_node(2);
+
+        let resp = tokio::time::timeout(Duration::from_millis(5_000), pending).await???;
+        assert_eq!(log_index + 1, resp.log_id.index);
+
+        let report = tokio::time::timeout(Duration::from_millis(5_000), shutdown).await???;
+        assert_eq!(0, report.unapplied);
+    }
+
+    Ok(())
+}
+
+/// A graceful shutdown gives up when the deadline is reached, and reports the unapplied logs.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters, isolate node-1 and node-2.
+/// - write to the leader, the write can not be committed.
+/// - shutdown the leader gracefully, assert it shuts down after the deadline and reports 1 unapplied log.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn shutdown_gracefully_timeout() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            graceful_shutdown_timeout: 500,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- isolate followers, write to the leader");
+    let pending = {
+        router.isolate_node(1);
+        router.isolate_node(2);
+
+        let n0 = router.get_raft_handle(&0)?;
+        let pending = tokio::spawn(async move { n0.client_write(request("0", 1)).await });
+
+        router
+            .wait(&0, timeout())
+            .metrics(|m| m.last_log_index == Some(log_index + 1), "write is appended")
+            .await?;
+        pending
+    };
+
+    tracing::info!("--- shutdown gracefully, it times out");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let report = tokio::time::timeout(Duration::from_millis(3_000), n0.shutdown_gracefully()).await??;
+        assert_eq!(1, report.unapplied);
+
+        let res = tokio::time::timeout(Duration::from_millis(1_000), pending).await??;
+        assert!(
+            matches!(&res, Err(ClientWriteError::ForwardToLeader(_))),
+            "the uncommitted write is answered on shutdown, got: {:?}",
+            res
+        );
+    }
+
+    Ok(())
+}
+
+fn request(client_id: &str, serial: u64) -> ClientWriteRequest<memstore::Config> {
+    ClientWriteRequest::new(EntryPayload::Normal(ClientRequest::make_request(client_id, serial)))
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
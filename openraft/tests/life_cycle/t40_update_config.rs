@@ -0,0 +1,134 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::UpdateConfigError;
+use openraft::Config;
+use openraft::ConfigError;
+use openraft::ConfigUpdate;
+use openraft::ServerState;
+use openraft::SnapshotPolicy;
+use tokio::time::sleep;
+use tokio::time::Instant;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::MemRaft;
+use crate::fixtures::RaftRouter;
+
+/// The timing knobs of the config are updated on running nodes, without restarting them.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters.
+/// - assert an invalid update is rejected, and the config is not changed.
+/// - update the heartbeat interval, election timeouts, max payload entries and snapshot policy on every node, assert
+///   the effective config reflects the update after the next tick.
+/// - assert the leader keeps its leadership with the new timing, and a lagging follower catches up.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn update_config() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            heartbeat_interval: 50,
+            election_timeout_min: 500,
+            election_timeout_max: 1000,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- an invalid update is rejected");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let res = n0
+            .update_config(ConfigUpdate {
+                election_timeout_min: Some(1000),
+                ..Default::default()
+            })
+            .await;
+
+        assert!(
+            matches!(
+                res,
+                Err(UpdateConfigError::InvalidConfig(ConfigError::ElectionTimeout {
+                    min: 1000,
+                    max: 1000
+                }))
+            ),
+            "got: {:?}",
+            res
+        );
+
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(500, n0.config().await?.election_timeout_min, "not changed");
+    }
+
+    tracing::info!("--- update the timing knobs on every node");
+    {
+        for id in [0, 1, 2] {
+            let n = router.get_raft_handle(&id)?;
+            n.update_config(ConfigUpdate {
+                heartbeat_interval: Some(100),
+                election_timeout_min: Some(1000),
+                election_timeout_max: Some(2000),
+                max_payload_entries: Some(2),
+                snapshot_policy: Some(SnapshotPolicy::LogsSinceLast(10_000)),
+            })
+            .await?;
+        }
+
+        for id in [0, 1, 2] {
+            let n = router.get_raft_handle(&id)?;
+            let c = wait_for_config(&n, |c| c.heartbeat_interval == 100).await?;
+
+            assert_eq!(1000, c.election_timeout_min);
+            assert_eq!(2000, c.election_timeout_max);
+            assert_eq!(2, c.max_payload_entries);
+            assert_eq!(SnapshotPolicy::LogsSinceLast(10_000), c.snapshot_policy);
+            assert_eq!(config.cluster_name, c.cluster_name, "other fields are not changed");
+        }
+    }
+
+    tracing::info!("--- the leader keeps its leadership, a lagging follower catches up");
+    {
+        let term = router.get_metrics(&0)?.current_term;
+
+        router.isolate_node(2);
+        log_index += router.client_request_many(0, "0", 10).await?;
+        router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "write logs").await?;
+        router.restore_node(2);
+        router.wait_for_log(&btreeset! {2}, Some(log_index), timeout(), "node-2 catches up").await?;
+
+        sleep(Duration::from_millis(2_000)).await;
+
+        let m0 = router.get_metrics(&0)?;
+        assert_eq!(ServerState::Leader, m0.state);
+        assert_eq!(term, m0.current_term, "no election");
+    }
+
+    Ok(())
+}
+
+/// Poll the effective config of a node until it satisfies `f`.
+async fn wait_for_config(raft: &MemRaft, f: impl Fn(&Config) -> bool) -> Result<Arc<Config>> {
+    let deadline = Instant::now() + timeout().unwrap();
+
+    loop {
+        let c = raft.config().await?;
+        if f(&c) {
+            return Ok(c);
+        }
+
+        if Instant::now() > deadline {
+            anyhow::bail!("timeout waiting for config, got: {:?}", c);
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
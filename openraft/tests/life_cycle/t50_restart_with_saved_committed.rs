@@ -0,0 +1,116 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LogIdOptionExt;
+use openraft::ServerState;
+use openraft::Wrapper;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A restarted node whose store does not save the committed log id applies logs only after a leader commits them.
+///
+/// What does this test do?
+///
+/// - bring up a single node cluster with a long election timeout, write some logs.
+/// - restart the node with its state machine cleared.
+/// - assert no log is applied before the node is elected again.
+/// - assert every log is applied once it is elected.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn restart_without_saved_committed() -> Result<()> {
+    let config = config()?;
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    log_index += router.client_request_many(0, "0", 10).await?;
+    router.wait(&0, timeout()).log(Some(log_index), "write logs").await?;
+
+    restart(&mut router).await?;
+
+    tracing::info!("--- no log is applied before the node is elected");
+    {
+        tokio::time::sleep(Duration::from_millis(1_000)).await;
+
+        let m = router.get_raft_handle(&0)?.metrics().borrow().clone();
+        assert_ne!(ServerState::Leader, m.state);
+        assert_eq!(None, m.last_applied, "the committed log id is unknown");
+    }
+
+    tracing::info!("--- every log is applied after the node is elected");
+    {
+        // The new leader appends a blank log.
+        log_index += 1;
+        router
+            .wait(&0, Some(Duration::from_millis(10_000)))
+            .log(Some(log_index), "applied after election")
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// A restarted node applies the logs upto the committed log id saved by its store, without waiting for a leader.
+///
+/// What does this test do?
+///
+/// - bring up a single node cluster with a long election timeout, let the store save the committed log id, write some
+///   logs.
+/// - restart the node with its state machine cleared.
+/// - assert every log is applied before the node is elected again.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn restart_with_saved_committed() -> Result<()> {
+    let config = config()?;
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    {
+        let mut sto0 = router.get_storage_handle(&0)?;
+        sto0.inner().set_save_committed(true);
+    }
+
+    log_index += router.client_request_many(0, "0", 10).await?;
+    router.wait(&0, timeout()).log(Some(log_index), "write logs").await?;
+
+    restart(&mut router).await?;
+
+    tracing::info!("--- logs upto the saved committed log id are applied at once");
+    {
+        let m = router.wait(&0, timeout()).log(Some(log_index), "applied at startup").await?;
+        assert_ne!(ServerState::Leader, m.state, "applied without being elected");
+        assert_eq!(Some(log_index), m.last_applied.index());
+    }
+
+    Ok(())
+}
+
+/// Shutdown node-0 and start it again on the same store, with the state machine cleared.
+async fn restart(router: &mut RaftRouter) -> Result<()> {
+    let (node0, mut sto0) = router.remove_node(0).unwrap();
+    node0.shutdown().await?;
+
+    sto0.inner().clear_state_machine().await;
+    router.new_raft_node_with_sto(0, sto0);
+
+    Ok(())
+}
+
+fn config() -> Result<Arc<Config>> {
+    let config = Config {
+        // Keep the restarted node from being elected during the test.
+        election_timeout_min: 3_000,
+        election_timeout_max: 4_000,
+        ..Default::default()
+    }
+    .validate()?;
+
+    Ok(Arc::new(config))
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
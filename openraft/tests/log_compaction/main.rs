@@ -5,3 +5,5 @@
 mod fixtures;
 
 mod t10_compaction;
+mod t20_trigger_purge_log;
+mod t30_purge_logs_upto;
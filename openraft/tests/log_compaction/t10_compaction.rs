@@ -96,6 +96,7 @@ async fn compaction() -> Result<()> {
     sto1.append_to_log(&[&blank(0, 0), &Entry {
         log_id: LogId::new(LeaderId::new(1, 0), 1),
         payload: EntryPayload::Membership(Membership::new(vec![btreeset! {0}], None)),
+        context: None,
     }])
     .await?;
 
@@ -143,6 +144,8 @@ async fn compaction() -> Result<()> {
                 prev_log_id: Some(LogId::new(LeaderId::new(1, 0), 2)),
                 entries: vec![],
                 leader_commit: Some(LogId::new(LeaderId::new(0, 0), 0)),
+                compressed_entries: None,
+                leader_transfer_to: None,
             })
             .await?;
 
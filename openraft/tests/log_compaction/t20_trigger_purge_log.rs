@@ -0,0 +1,94 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Purge logs on demand with `Raft::trigger_purge_log()`.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters, with a snapshot policy that never triggers a snapshot.
+/// - isolate node-2 and write some logs.
+/// - purge before a snapshot is built, assert nothing is purged.
+/// - build a snapshot on the leader, purge, assert the logs node-2 has not matched are kept.
+/// - purge with `force`, assert the logs are purged up to the snapshot and reported in metrics.
+/// - restore node-2, assert it catches up.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn trigger_purge_log() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(10_000),
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+    let matched_by_2 = LogId::new(LeaderId::new(1, 0), log_index);
+
+    tracing::info!("--- isolate node-2 and write logs");
+    {
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.replication_lag.get(&2).map(|l| l.matched == Some(matched_by_2)).unwrap_or(false),
+                "the leader knows node-2 matched every log",
+            )
+            .await?;
+
+        router.isolate_node(2);
+
+        log_index += router.client_request_many(0, "0", 10).await?;
+        router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "write logs").await?;
+    }
+
+    let n0 = router.get_raft_handle(&0)?;
+    let want = LogId::new(LeaderId::new(1, 0), log_index);
+
+    tracing::info!("--- no snapshot, nothing to purge");
+    {
+        let res = n0.trigger_purge_log(log_index, true).await?;
+        assert_eq!(None, res);
+    }
+
+    tracing::info!("--- build a snapshot, purge keeps the logs node-2 needs");
+    {
+        n0.trigger_snapshot().await?;
+        router.wait(&0, timeout()).snapshot(want, "leader built a snapshot").await?;
+
+        let res = n0.trigger_purge_log(log_index, false).await?;
+        assert_eq!(Some(matched_by_2), res);
+
+        let res = n0.trigger_purge_log(log_index, false).await?;
+        assert_eq!(None, res, "nothing more to purge");
+    }
+
+    tracing::info!("--- purge with force, up to the snapshot");
+    {
+        let res = n0.trigger_purge_log(u64::MAX, true).await?;
+        assert_eq!(Some(want), res);
+
+        router.wait(&0, timeout()).purged(want, "purged up to the snapshot").await?;
+    }
+
+    tracing::info!("--- restore node-2, it catches up");
+    {
+        router.restore_node(2);
+        router.wait_for_log(&btreeset! {2}, Some(log_index), timeout(), "node-2 catches up").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::PurgeLogsError;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Purge logs manually with `Raft::purge_logs_upto()`, which refuses to purge logs that are still needed.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters, with a snapshot policy that never triggers a snapshot.
+/// - isolate node-2 and write some logs.
+/// - assert purging logs that are not applied fails with `NotApplied`.
+/// - assert purging logs node-2 has not matched fails with `ReplicationLagging` on the leader.
+/// - purge the logs node-2 has matched on the leader, and every applied log on a follower.
+/// - restore node-2, assert it catches up with the logs the leader kept.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn purge_logs_upto() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(10_000),
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+    let matched_by_2 = LogId::new(LeaderId::new(1, 0), log_index);
+
+    tracing::info!("--- isolate node-2 and write logs");
+    {
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.replication_lag.get(&2).map(|l| l.matched == Some(matched_by_2)).unwrap_or(false),
+                "the leader knows node-2 matched every log",
+            )
+            .await?;
+
+        router.isolate_node(2);
+
+        log_index += router.client_request_many(0, "0", 10).await?;
+        router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "write logs").await?;
+    }
+
+    let n0 = router.get_raft_handle(&0)?;
+    let last = LogId::new(LeaderId::new(1, 0), log_index);
+
+    tracing::info!("--- purging logs that are not applied is refused");
+    {
+        let res = n0.purge_logs_upto(LogId::new(LeaderId::new(1, 0), log_index + 1)).await;
+        match res {
+            Err(PurgeLogsError::NotApplied(e)) => {
+                assert_eq!(Some(last), e.last_applied);
+            }
+            _ => {
+                panic!("expect PurgeLogsError::NotApplied, got: {:?}", res);
+            }
+        }
+    }
+
+    tracing::info!("--- purging logs that node-2 still needs is refused");
+    {
+        let res = n0.purge_logs_upto(last).await;
+        match res {
+            Err(PurgeLogsError::ReplicationLagging(e)) => {
+                assert_eq!(2, e.target);
+                assert_eq!(Some(matched_by_2), e.matched);
+            }
+            _ => {
+                panic!("expect PurgeLogsError::ReplicationLagging, got: {:?}", res);
+            }
+        }
+
+        let m = router.get_metrics(&0)?;
+        assert_eq!(None, m.purged, "nothing is purged");
+    }
+
+    tracing::info!("--- purge the logs that node-2 has matched");
+    {
+        n0.purge_logs_upto(matched_by_2).await?;
+        router.wait(&0, timeout()).purged(matched_by_2, "leader purged logs").await?;
+
+        // Purging again does nothing.
+        n0.purge_logs_upto(matched_by_2).await?;
+    }
+
+    tracing::info!("--- a follower purges every applied log");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        n1.purge_logs_upto(last).await?;
+        router.wait(&1, timeout()).purged(last, "follower purged logs").await?;
+    }
+
+    tracing::info!("--- restore node-2, it catches up");
+    {
+        router.restore_node(2);
+        router.wait_for_log(&btreeset! {2}, Some(log_index), timeout(), "node-2 catches up").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
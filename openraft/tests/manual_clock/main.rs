@@ -0,0 +1,16 @@
+#![cfg(feature = "manual-clock")]
+#![cfg_attr(feature = "bt", feature(backtrace))]
+
+#[macro_use]
+#[path = "../fixtures/mod.rs"]
+mod fixtures;
+
+// The clock is shared by every test in a process, thus these tests are kept in their own binary, and every test holds
+// `CLOCK_LOCK` so that they do not run at the same time.
+
+lazy_static::lazy_static! {
+    static ref CLOCK_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+mod t10_elect_at_simulated_instant;
+mod t20_seeded_rng;
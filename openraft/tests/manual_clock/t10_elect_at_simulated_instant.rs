@@ -0,0 +1,86 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::testing::ManualClock;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Drive an election timeout with `ManualClock` instead of waiting for it on the wall clock.
+///
+/// What does this test do?
+///
+/// - build a stable 3-node cluster, isolate the leader and pause the clock.
+/// - advance the clock to short of the election timeout, assert no follower starts an election.
+/// - advance the clock step by step, assert a follower is elected once the election timeout is reached.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn elect_at_simulated_instant() -> Result<()> {
+    let _clock = crate::CLOCK_LOCK.lock().await;
+
+    let config = Arc::new(
+        Config {
+            heartbeat_interval: 50,
+            election_timeout_min: 3_000,
+            election_timeout_max: 4_000,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- isolate the leader and stop the clock");
+
+    router.isolate_node(0);
+    ManualClock::pause();
+    let paused_at = ManualClock::now();
+
+    tracing::info!("--- no election before the election timeout");
+    {
+        // A follower may have received the last heartbeat one heartbeat interval before the clock is paused.
+        let before_timeout = config.election_timeout_min - 2 * config.heartbeat_interval;
+        router.advance_time(Duration::from_millis(before_timeout)).await;
+
+        for id in [1, 2] {
+            let m = router.get_raft_handle(&id)?.metrics().borrow().clone();
+            assert_eq!(1, m.current_term, "node-{} does not elect before the timeout", id);
+            assert_eq!(ServerState::Follower, m.state);
+        }
+    }
+
+    tracing::info!("--- a follower is elected after the election timeout");
+    {
+        let give_up_at = paused_at + Duration::from_millis(config.election_timeout_max * 10);
+
+        let leader = loop {
+            let leader = [1, 2].into_iter().find(|id| {
+                let m = router.get_raft_handle(id).unwrap().metrics().borrow().clone();
+                m.state == ServerState::Leader
+            });
+            if let Some(l) = leader {
+                break l;
+            }
+
+            assert!(ManualClock::now() < give_up_at, "no leader is elected");
+            router.advance_time(Duration::from_millis(10)).await;
+        };
+
+        let elapsed = ManualClock::now() - paused_at;
+        tracing::info!("node-{} is elected after {:?} of simulated time", leader, elapsed);
+
+        assert!(
+            elapsed >= Duration::from_millis(config.election_timeout_min - config.heartbeat_interval),
+            "elected after: {:?}",
+            elapsed
+        );
+    }
+
+    ManualClock::resume();
+
+    Ok(())
+}
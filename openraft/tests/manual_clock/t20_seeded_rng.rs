@@ -0,0 +1,76 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::testing::ManualClock;
+use openraft::testing::SeededRng;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Replay the election timeouts from a seed, and elect a leader in simulated time.
+///
+/// What does this test do?
+///
+/// - seed `SeededRng` twice with the same seed, assert the same election timeouts are drawn.
+/// - build a 3-node cluster, isolate the leader, and advance the paused clock until a follower is elected, within a
+///   couple of seconds of simulated time but much less wall clock time.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn seeded_rng() -> Result<()> {
+    let _clock = crate::CLOCK_LOCK.lock().await;
+
+    let config = Arc::new(
+        Config {
+            heartbeat_interval: 50,
+            election_timeout_min: 1_000,
+            election_timeout_max: 2_000,
+            election_timeout_jitter: 500,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    tracing::info!("--- the same seed replays the same election timeouts");
+    {
+        SeededRng::seed(42);
+        let first = (0..10).map(|_| config.new_rand_election_timeout()).collect::<Vec<_>>();
+
+        SeededRng::seed(42);
+        let second = (0..10).map(|_| config.new_rand_election_timeout()).collect::<Vec<_>>();
+
+        assert_eq!(first, second);
+
+        SeededRng::seed(43);
+        let third = (0..10).map(|_| config.new_rand_election_timeout()).collect::<Vec<_>>();
+        assert_ne!(first, third, "another seed draws another sequence");
+    }
+
+    let mut router = RaftRouter::new(config.clone());
+    router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- isolate the leader, a follower is elected in simulated time");
+    {
+        router.isolate_node(0);
+
+        let wall_clock = std::time::Instant::now();
+        let max_timeout = config.election_timeout_max + config.election_timeout_jitter;
+
+        router.advance_time(Duration::from_millis(max_timeout * 3)).await;
+
+        let leader = [1, 2].into_iter().find(|id| {
+            let m = router.get_raft_handle(id).unwrap().metrics().borrow().clone();
+            m.state == ServerState::Leader
+        });
+        assert!(leader.is_some(), "a follower is elected");
+
+        tracing::info!("elected after {:?} of wall clock time", wall_clock.elapsed());
+    }
+
+    ManualClock::resume();
+    SeededRng::reset();
+
+    Ok(())
+}
@@ -13,11 +13,16 @@ mod t10_add_learner;
 mod t12_concurrent_write_and_add_learner;
 mod t15_add_remove_follower;
 mod t16_change_membership_cases;
+mod t17_change_membership_exact;
+mod t18_change_membership_dry_run;
 mod t20_change_membership;
 mod t25_elect_with_new_config;
 mod t30_commit_joint_config;
 mod t30_step_down;
+mod t31_leave;
 mod t40_removed_follower;
 mod t45_remove_unreachable_follower;
+mod t46_guard_membership_quorum;
+mod t50_auto_promote_learner;
 mod t99_issue_471_adding_learner_uses_uninit_leader_id;
 mod t99_new_leader_auto_commit_uniform_config;
@@ -5,12 +5,16 @@ use std::time::Duration;
 use anyhow::Result;
 use maplit::btreeset;
 use memstore::IntoMemClientRequest;
+use openraft::storage::Adaptor;
 use openraft::Config;
 use openraft::LogIdOptionExt;
 use openraft::Raft;
 use openraft::RaftStorage;
 use openraft::RaftTypeConfig;
 use openraft::ServerState;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncSeek;
+use tokio::io::AsyncWrite;
 use tokio::time::sleep;
 
 use crate::fixtures::init_default_ut_tracing;
@@ -67,7 +71,8 @@ async fn learner_restart() -> Result<()> {
     node1.shutdown().await?;
 
     // restart node-1, assert the state as expected.
-    let restarted = Raft::new(1, config.clone(), router.clone(), sto1);
+    let (log_store, state_machine) = Adaptor::new(sto1);
+    let restarted = Raft::new(1, config.clone(), router.clone(), log_store, state_machine);
     sleep(Duration::from_secs(2)).await;
     assert_node_state(1, &restarted, 1, log_index, ServerState::Learner);
 
@@ -83,6 +88,7 @@ fn assert_node_state<C: RaftTypeConfig, S: RaftStorage<C>>(
 ) where
     C::D: Debug + IntoMemClientRequest<C::D>,
     C::R: Debug,
+    C::SnapshotData: AsyncRead + AsyncWrite + AsyncSeek + Unpin,
     S: Default + Clone,
 {
     let m = node.metrics().borrow().clone();
@@ -4,9 +4,12 @@ use std::time::Duration;
 
 use anyhow::Result;
 use maplit::btreeset;
+use openraft::error::AddLearnerError;
+use openraft::error::NotCaughtUpReason;
 use openraft::Config;
 use openraft::LeaderId;
 use openraft::LogId;
+use openraft::LogIdOptionExt;
 use openraft::Membership;
 use openraft::RaftLogReader;
 use openraft::StorageHelper;
@@ -116,6 +119,62 @@ async fn add_learner_non_blocking() -> Result<()> {
     Ok(())
 }
 
+/// A blocking `add_learner()` returns `NotCaughtUp` with the progress reached, if the learner does not catch up
+/// within `Config::add_learner_timeout`.
+///
+/// - Isolate a new node-1 and add it as a learner, expect `NotCaughtUp` by timeout.
+/// - Restore node-1 and re-add it, expect it to return once node-1 catches up.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn add_learner_not_caught_up() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            replication_lag_threshold: 0,
+            add_learner_timeout: 500,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    log_index += router.client_request_many(0, "learner_add", 10).await?;
+
+    tracing::info!("--- add an unreachable learner, it does not catch up");
+    {
+        router.new_raft_node(1);
+        router.isolate_node(1);
+
+        let res = router.add_learner(0, 1).await;
+        log_index += 1;
+
+        let err = match res {
+            Err(AddLearnerError::NotCaughtUp(e)) => e,
+            _ => panic!("expect NotCaughtUp, got: {:?}", res),
+        };
+        assert_eq!(1, err.node_id);
+        assert_eq!(NotCaughtUpReason::Timeout, err.reason);
+        assert!(err.matched.index() < Some(log_index), "matched: {:?}", err.matched);
+        assert_eq!(Some(log_index), err.leader_last_log_index);
+
+        let m = router.get_metrics(&0)?;
+        assert!(
+            m.membership_config.membership.nodes().any(|(id, _)| *id == 1),
+            "node-1 stays in the membership as a learner"
+        );
+    }
+
+    tracing::info!("--- restore node-1, re-adding it blocks until it catches up");
+    {
+        router.restore_node(1);
+
+        let res = router.add_learner(0, 1).await?;
+        assert_eq!(Some(LogId::new(LeaderId::new(1, 0), log_index)), res.matched);
+    }
+
+    Ok(())
+}
+
 /// add a learner, then shutdown the leader to make leader transferred,
 /// check after new leader come, the learner can receive new log.
 #[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
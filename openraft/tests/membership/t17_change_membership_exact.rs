@@ -0,0 +1,121 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::ChangeMembershipError;
+use openraft::error::ClientWriteError;
+use openraft::ChangeMembers;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Change to an exact set of voters and learners with a single `change_membership` call.
+///
+/// What does this test do?
+///
+/// - bring a cluster of voters {0,1,2} and learners {3,4}.
+/// - change to voters {0,3,4} and learners {1}, assert 3 and 4 are promoted, 1 is demoted and 2 is removed.
+/// - assert an empty voter set or an unknown node is rejected.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn change_membership_exact() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {3,4}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- promote 3,4, demote 1 and remove 2 at once");
+    {
+        let resp = n0
+            .change_membership(
+                ChangeMembers::Exact {
+                    voters: btreeset! {0,3,4},
+                    learners: btreeset! {1},
+                },
+                false,
+                false,
+            )
+            .await?;
+        // joint config, uniform config
+        log_index += 2;
+        assert_eq!(log_index, resp.log_id.index);
+
+        let membership = resp.membership.unwrap();
+        assert_eq!(&vec![btreeset! {0,3,4}], membership.get_joint_config());
+        assert_eq!(
+            btreeset! {0,1,3,4},
+            membership.nodes().map(|(id, _)| *id).collect::<BTreeSet<_>>()
+        );
+
+        router
+            .wait_for_log(
+                &btreeset! {0,1,3,4},
+                Some(log_index),
+                timeout(),
+                "change to exact membership",
+            )
+            .await?;
+
+        let m0 = router.get_metrics(&0)?;
+        assert_eq!(
+            btreeset! {0,3,4},
+            m0.membership_config.voter_ids().collect::<BTreeSet<_>>()
+        );
+    }
+
+    tracing::info!("--- an empty voter set is rejected");
+    {
+        let res = n0
+            .change_membership(
+                ChangeMembers::Exact {
+                    voters: btreeset! {},
+                    learners: btreeset! {0,1,3,4},
+                },
+                false,
+                false,
+            )
+            .await;
+
+        assert!(
+            matches!(
+                res,
+                Err(ClientWriteError::ChangeMembershipError(
+                    ChangeMembershipError::EmptyMembership(_)
+                ))
+            ),
+            "got: {:?}",
+            res
+        );
+    }
+
+    tracing::info!("--- a node that is not in the cluster is rejected");
+    {
+        let res = n0
+            .change_membership(
+                ChangeMembers::Exact {
+                    voters: btreeset! {0,3,4},
+                    learners: btreeset! {1,2},
+                },
+                false,
+                false,
+            )
+            .await;
+
+        match res {
+            Err(ClientWriteError::ChangeMembershipError(ChangeMembershipError::LearnerNotFound(e))) => {
+                assert_eq!(2, e.node_id);
+            }
+            _ => panic!("expect LearnerNotFound, got: {:?}", res),
+        }
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
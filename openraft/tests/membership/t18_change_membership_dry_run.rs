@@ -0,0 +1,114 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::ChangeMembershipError;
+use openraft::error::ClientWriteError;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// `change_membership_dry_run()` reports the result of a change-membership without writing any log.
+///
+/// What does this test do?
+///
+/// - bring a cluster of voter {0} and learners {1,2}.
+/// - assert a valid change returns the resulting membership and no log is written.
+/// - assert an empty voter set, an unknown node and a call to a non-leader are rejected.
+/// - start a change that can not be committed, assert a dry run returns `InProgress`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn change_membership_dry_run() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {1,2}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- a valid change returns the resulting membership");
+    {
+        let membership = n0.change_membership_dry_run(btreeset! {0,1,2}, false, false).await?;
+        assert_eq!(&vec![btreeset! {0,1,2}], membership.get_joint_config());
+
+        let membership = n0.change_membership_dry_run(btreeset! {1,2}, false, true).await?;
+        assert_eq!(&vec![btreeset! {1,2}], membership.get_joint_config());
+        assert!(
+            membership.nodes().any(|(id, _)| *id == 0),
+            "node-0 turns into a learner"
+        );
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let m0 = router.get_metrics(&0)?;
+        assert_eq!(Some(log_index), m0.last_log_index, "no log is written");
+        assert_eq!(btreeset! {0}, m0.membership_config.voter_ids().collect::<BTreeSet<_>>());
+    }
+
+    tracing::info!("--- invalid changes are rejected");
+    {
+        let res = n0.change_membership_dry_run(btreeset! {}, false, false).await;
+        assert!(
+            matches!(
+                res,
+                Err(ClientWriteError::ChangeMembershipError(
+                    ChangeMembershipError::EmptyMembership(_)
+                ))
+            ),
+            "got: {:?}",
+            res
+        );
+
+        let res = n0.change_membership_dry_run(btreeset! {0,3}, false, false).await;
+        match res {
+            Err(ClientWriteError::ChangeMembershipError(ChangeMembershipError::LearnerNotFound(e))) => {
+                assert_eq!(3, e.node_id);
+            }
+            _ => panic!("expect LearnerNotFound, got: {:?}", res),
+        }
+
+        let n1 = router.get_raft_handle(&1)?;
+        let res = n1.change_membership_dry_run(btreeset! {0,1}, false, false).await;
+        assert!(
+            matches!(&res, Err(ClientWriteError::ForwardToLeader(e)) if e.leader_id == Some(0)),
+            "got: {:?}",
+            res
+        );
+    }
+
+    tracing::info!("--- a change in progress fails a dry run");
+    {
+        router.isolate_node(1);
+        router.isolate_node(2);
+
+        let n = n0.clone();
+        let _changing = tokio::spawn(async move { n.change_membership(btreeset! {0,1,2}, true, false).await });
+
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.membership_config.membership.is_in_joint_consensus(),
+                "the joint config is proposed but not committed",
+            )
+            .await?;
+
+        let res = n0.change_membership_dry_run(btreeset! {0}, false, false).await;
+        assert!(
+            matches!(
+                res,
+                Err(ClientWriteError::ChangeMembershipError(
+                    ChangeMembershipError::InProgress(_)
+                ))
+            ),
+            "got: {:?}",
+            res
+        );
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -1,13 +1,17 @@
+use std::collections::BTreeSet;
 use std::convert::TryInto;
 use std::sync::Arc;
 use std::time::Duration;
 
+use maplit::btreemap;
 use maplit::btreeset;
 use memstore::MemNodeId;
 use openraft::error::ChangeMembershipError;
 use openraft::error::ClientWriteError;
+use openraft::ChangeMembers;
 use openraft::Config;
 use openraft::LogIdOptionExt;
+use openraft::Node;
 use openraft::RaftLogReader;
 use openraft::ServerState;
 
@@ -132,6 +136,40 @@ async fn change_without_adding_learner() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// A node not added as a learner is reported by its id, even if the nodes in the cluster have node infos.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn change_without_adding_learner_with_node_infos() -> anyhow::Result<()> {
+    let config = Arc::new(Config { ..Default::default() }.validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    router.new_raft_node(0);
+    router.new_raft_node(1);
+    let leader = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- initialize node-0 with node info, add node-1 as learner");
+    {
+        leader.initialize(btreemap! {0 => Node::new("addr-0")}).await?;
+        router.wait(&0, timeout()).state(ServerState::Leader, "node-0 is leader").await?;
+
+        leader.add_learner(1, Some(Node::new("addr-1")), true).await?;
+    }
+
+    tracing::info!("--- change membership with node-2, which is not added");
+    {
+        let res = leader.change_membership(btreeset! {0,1,2}, true, false).await;
+        match res {
+            Err(ClientWriteError::ChangeMembershipError(ChangeMembershipError::LearnerNotFound(err))) => {
+                assert_eq!(2, err.node_id);
+            }
+            _ => {
+                unreachable!("expect LearnerNotFound, got: {:?}", res)
+            }
+        }
+    }
+
+    Ok(())
+}
+
 #[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
 async fn change_with_lagging_learner_non_blocking() -> anyhow::Result<()> {
     // Add a learner into membership config, expect error NonVoterIsLagging.
@@ -244,6 +282,57 @@ async fn change_with_turn_removed_voter_to_learner() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// A voter turned into a learner keeps receiving logs, until it is removed with `ChangeMembers::RemoveLearners`.
+///
+/// - Turn voter node-2 into a learner.
+/// - Remove learners {1,2}: node-2 is removed, voter node-1 is not changed.
+/// - Assert node-2 no longer receives logs.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn change_with_remove_learners() -> anyhow::Result<()> {
+    let config = Arc::new(Config { ..Default::default() }.validate()?);
+    let mut router = RaftRouter::new(config.clone());
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- turn voter node-2 into a learner");
+    {
+        let node = router.get_raft_handle(&0)?;
+        node.change_membership(btreeset![0, 1], true, true).await?;
+        log_index += 2;
+
+        router.wait_for_log(&btreeset![0, 1, 2], Some(log_index), timeout(), "node-2 receives logs").await?;
+    }
+
+    tracing::info!("--- remove learners, a voter in the set is not changed");
+    {
+        let node = router.get_raft_handle(&0)?;
+        let res = node.change_membership(ChangeMembers::RemoveLearners(btreeset! {1,2}), false, false).await?;
+        log_index += 1;
+
+        let membership = res.membership.unwrap();
+        assert_eq!(&vec![btreeset! {0,1}], membership.get_joint_config());
+        assert_eq!(
+            btreeset! {0,1},
+            membership.nodes().map(|(id, _)| *id).collect::<BTreeSet<_>>(),
+            "node-2 is removed"
+        );
+
+        router.wait_for_log(&btreeset![0, 1], Some(log_index), timeout(), "remove learners").await?;
+    }
+
+    tracing::info!("--- node-2 no longer receives logs");
+    {
+        router.client_request_many(0, "foo", 1).await?;
+        log_index += 1;
+        router.wait_for_log(&btreeset![0, 1], Some(log_index), timeout(), "write a log").await?;
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let m2 = router.get_metrics(&2)?;
+        assert!(m2.last_log_index < Some(log_index), "node-2 does not receive the log");
+    }
+
+    Ok(())
+}
+
 fn timeout() -> Option<Duration> {
     Some(Duration::from_millis(1000))
 }
@@ -0,0 +1,88 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::ClientWriteError;
+use openraft::metrics::StepDownReason;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A leader leaves the cluster with `Raft::leave()` and hands its leadership over without an election timeout.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters with a long election timeout.
+/// - `leave()` on a follower fails with `ForwardToLeader`.
+/// - `leave()` on leader node-0 returns after it stepped down.
+/// - assert another node becomes the leader well before an election timeout, with a membership without node-0.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn leave() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            election_timeout_min: 3_000,
+            election_timeout_max: 4_000,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let _log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- leave() on a follower is forwarded to the leader");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let res = n1.leave().await;
+        assert!(
+            matches!(&res, Err(ClientWriteError::ForwardToLeader(e)) if e.leader_id == Some(0)),
+            "got: {:?}",
+            res
+        );
+    }
+
+    tracing::info!("--- the leader leaves the cluster");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let res = n0.leave().await?;
+
+        let membership = res.membership.unwrap();
+        assert_eq!(vec![btreeset! {1,2}], membership.get_joint_config().clone());
+        assert!(!membership.nodes().any(|(id, _)| *id == 0), "node-0 is removed");
+
+        let m0 = router.get_metrics(&0)?;
+        assert_eq!(ServerState::Learner, m0.state);
+        assert_eq!(
+            Some(StepDownReason::Removed),
+            m0.last_step_down.map(|s| s.reason),
+            "node-0 stepped down because it is removed"
+        );
+    }
+
+    tracing::info!("--- a new leader is elected at once");
+    {
+        let m1 = router
+            .wait(&1, timeout())
+            .metrics(
+                |m| m.current_leader == Some(1) || m.current_leader == Some(2),
+                "node-1 sees a new leader",
+            )
+            .await?;
+
+        let leader = m1.current_leader.unwrap();
+        router.wait(&leader, timeout()).state(ServerState::Leader, "the new leader").await?;
+
+        let m = router.get_metrics(&leader)?;
+        assert_eq!(vec![vec![1, 2]], m.membership_config.get_joint_config().clone());
+    }
+
+    Ok(())
+}
+
+/// Much shorter than the election timeout: the new leader is started by TimeoutNow.
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::MemNodeId;
+use openraft::error::ChangeMembershipError;
+use openraft::Config;
+use tokio::time::sleep;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With `Config::guard_membership_quorum`, a membership change that leaves no live quorum is refused.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster with the guard enabled.
+/// - isolate node 2 and wait for its last acknowledgement to expire.
+/// - assert removing node 1 fails with `QuorumNotEnough`: only 0 of the voters {0,2} is live.
+/// - assert removing the offline node 2 is allowed: the voters {0,1} are both live.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn guard_membership_quorum() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            election_timeout_min: 500,
+            election_timeout_max: 1000,
+            heartbeat_interval: 50,
+            guard_membership_quorum: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- isolate node 2");
+    {
+        router.isolate_node(2);
+        sleep(Duration::from_millis(1_500)).await;
+    }
+
+    let leader = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- removing node 1 is refused");
+    {
+        let res = leader.change_membership(btreeset! {0,2}, false, false).await;
+        let err: ChangeMembershipError<MemNodeId> = res.unwrap_err().try_into().unwrap();
+
+        match err {
+            ChangeMembershipError::QuorumNotEnough(e) => {
+                assert_eq!(btreeset! {0,1}, e.got);
+            }
+            _ => {
+                panic!("expect ChangeMembershipError::QuorumNotEnough, got: {:?}", err);
+            }
+        }
+    }
+
+    tracing::info!("--- removing node 2 is allowed");
+    {
+        leader.change_membership(btreeset! {0,1}, false, false).await?;
+        log_index += 2;
+
+        router.wait_for_log(&btreeset![0, 1], Some(log_index), timeout(), "removed node 2").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1000))
+}
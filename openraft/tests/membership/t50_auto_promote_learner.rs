@@ -0,0 +1,167 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreemap;
+use maplit::btreeset;
+use openraft::metrics::PromotionStage;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// `add_learner_and_promote` adds a learner and makes it a voter once it catches up.
+///
+/// What does this test do?
+///
+/// - bring up a single node cluster and isolate a new node-1 before adding it.
+/// - call `add_learner_and_promote(1)`, assert node-1 stays a learner in the `CatchingUp` stage while it can not
+///   replicate.
+/// - restore node-1, assert the call returns the uniform config in which node-1 is a voter.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn add_learner_and_promote() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            replication_lag_threshold: 0,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    router.client_request_many(0, "0", 10).await?;
+    log_index += 10;
+
+    router.new_raft_node(1);
+    router.isolate_node(1);
+
+    let n0 = router.get_raft_handle(&0)?;
+    let promoting = tokio::spawn(async move { n0.add_learner_and_promote(1, None).await });
+
+    tracing::info!("--- a learner that can not catch up is not promoted");
+    {
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.learner_promotions == btreemap! {1 => PromotionStage::CatchingUp},
+                "node-1 is catching up",
+            )
+            .await?;
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        let m0 = router.get_metrics(&0)?;
+        assert_eq!(btreemap! {1 => PromotionStage::CatchingUp}, m0.learner_promotions);
+        assert_eq!(btreeset! {0}, m0.membership_config.voter_ids().collect::<BTreeSet<_>>());
+        assert!(
+            m0.membership_config.membership.nodes().any(|(id, _)| *id == 1),
+            "node-1 is added as a learner"
+        );
+    }
+
+    tracing::info!("--- restore node-1, it is promoted once it catches up");
+    {
+        router.restore_node(1);
+
+        let resp = tokio::time::timeout(Duration::from_millis(3_000), promoting).await???;
+        let membership = resp.membership.unwrap();
+        assert_eq!(vec![btreeset! {0, 1}], membership.get_joint_config().clone());
+
+        // add learner, joint config, uniform config
+        log_index += 3;
+        assert_eq!(log_index, resp.log_id.index);
+
+        router.wait_for_log(&btreeset! {0, 1}, Some(log_index), timeout(), "node-1 is promoted").await?;
+        router.wait(&0, timeout()).metrics(|m| m.learner_promotions.is_empty(), "promotion is done").await?;
+    }
+
+    Ok(())
+}
+
+/// With `Config::auto_promote_learner`, every learner added by `add_learner` is promoted.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn auto_promote_learner() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            auto_promote_learner: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let _log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    for id in [1, 2] {
+        router.new_raft_node(id);
+        router.add_learner(0, id).await?;
+
+        let m0 = router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.membership_config.voter_ids().any(|x| x == id) && m.learner_promotions.is_empty(),
+                format!("node-{} is promoted", id),
+            )
+            .await?;
+        assert!(!m0.membership_config.membership.is_in_joint_consensus());
+    }
+
+    let m0 = router.get_metrics(&0)?;
+    assert_eq!(
+        btreeset! {0, 1, 2},
+        m0.membership_config.voter_ids().collect::<BTreeSet<_>>()
+    );
+
+    Ok(())
+}
+
+/// With `Config::learner_promotion_rounds`, a caught up learner is promoted only after it stays caught up for that
+/// many heartbeat intervals.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn learner_promotion_rounds() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            heartbeat_interval: 50,
+            learner_promotion_rounds: 20,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    router.new_raft_node(1);
+    let n0 = router.get_raft_handle(&0)?;
+    let started = tokio::time::Instant::now();
+    let promoting = tokio::spawn(async move { n0.add_learner_and_promote(1, None).await });
+
+    tracing::info!("--- node-1 catches up, but it is not promoted at once");
+    {
+        router.wait_for_log(&btreeset! {0, 1}, Some(log_index + 1), timeout(), "node-1 catches up").await?;
+
+        let m0 = router.get_metrics(&0)?;
+        assert_eq!(btreemap! {1 => PromotionStage::CatchingUp}, m0.learner_promotions);
+    }
+
+    tracing::info!("--- node-1 is promoted after it stays caught up");
+    {
+        let resp = tokio::time::timeout(Duration::from_millis(3_000), promoting).await???;
+        assert!(
+            started.elapsed() >= Duration::from_millis(50 * 20),
+            "promoted after 20 heartbeat intervals"
+        );
+
+        let membership = resp.membership.unwrap();
+        assert_eq!(vec![btreeset! {0, 1}], membership.get_joint_config().clone());
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
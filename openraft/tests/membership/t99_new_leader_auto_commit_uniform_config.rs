@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use maplit::btreeset;
+use openraft::storage::Adaptor;
 use openraft::Config;
 use openraft::Entry;
 use openraft::EntryPayload;
@@ -40,6 +41,7 @@ async fn new_leader_auto_commit_uniform_config() -> Result<()> {
                 vec![btreeset! {0}, btreeset! {0,1,2}],
                 Some(btreeset! {}),
             )),
+            context: None,
         }])
         .await?;
     }
@@ -53,7 +55,8 @@ async fn new_leader_auto_commit_uniform_config() -> Result<()> {
     router.new_raft_node(1);
     router.new_raft_node(2);
 
-    let node = Raft::new(0, config.clone(), router.clone(), sto.clone());
+    let (log_store, state_machine) = Adaptor::new(sto.clone());
+    let node = Raft::new(0, config.clone(), router.clone(), log_store, state_machine);
 
     let _ = node;
 
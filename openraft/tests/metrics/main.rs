@@ -11,3 +11,11 @@ mod t10_current_leader;
 mod t20_metrics_state_machine_consistency;
 mod t30_leader_metrics;
 mod t40_metrics_wait;
+mod t42_wait_applied_index;
+mod t50_metrics_filtered;
+mod t60_replication_progress;
+mod t62_replication_lag;
+mod t64_log_cache;
+mod t66_snapshot_build_metrics;
+mod t70_server_and_data_metrics;
+mod t80_current_state;
@@ -1,8 +1,11 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
+use maplit::btreemap;
 use maplit::btreeset;
 use openraft::Config;
+use openraft::Node;
 
 use crate::fixtures::init_default_ut_tracing;
 use crate::fixtures::RaftRouter;
@@ -32,3 +35,61 @@ async fn current_leader() -> Result<()> {
 
     Ok(())
 }
+
+/// Get the current leader along with its node info.
+///
+/// What does this test do?
+///
+/// - initialize a 3-node cluster with node info.
+/// - wait for every node to know the leader, assert `current_leader_node` returns the leader and its node.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn current_leader_node() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    for id in [0, 1, 2] {
+        router.new_raft_node(id);
+    }
+
+    tracing::info!("--- no leader before initialization");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        assert_eq!(None, n0.current_leader_node());
+    }
+
+    tracing::info!("--- initialize the cluster with node info");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.initialize(btreemap! {
+            0 => Node::new("addr-0"),
+            1 => Node::new("addr-1"),
+            2 => Node::new("addr-2"),
+        })
+        .await?;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(1), timeout(), "init").await?;
+    }
+
+    tracing::info!("--- every node returns the leader and its node");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let m = n0.wait(timeout()).current_leader_is_known("leader is elected").await?;
+        let leader = m.current_leader.unwrap();
+
+        for id in [0, 1, 2] {
+            let n = router.get_raft_handle(&id)?;
+            n.wait(timeout()).current_leader(leader, "knows the leader").await?;
+
+            assert_eq!(
+                Some((leader, Some(Node::new(format!("addr-{}", leader))))),
+                n.current_leader_node()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
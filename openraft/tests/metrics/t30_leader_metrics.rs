@@ -169,6 +169,8 @@ async fn leader_metrics() -> Result<()> {
             .send_vote(VoteRequest {
                 vote: Vote::new(100, 100),
                 last_log_id: Some(LogId::new(LeaderId::new(10, 0), 100)),
+                pre_vote: false,
+                leader_transfer: false,
             })
             .await?;
 
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::metrics::WaitError;
+use openraft::raft::ClientWriteRequest;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft::LogIdOptionExt;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Wait for a follower to apply a log with `Wait::applied_index()`.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster.
+/// - write a log to the leader, wait for every follower to apply it.
+/// - isolate node-2 and write another log, assert waiting on node-2 times out.
+/// - restore node-2, assert the wait returns once node-2 applies the log.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn wait_applied_index() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- followers apply a just committed log");
+    {
+        let resp = n0.client_write(request("0", 1)).await?;
+        let index = resp.log_id.index;
+
+        for id in [1, 2] {
+            let m = router.wait(&id, timeout()).applied_index(index, "follower applied the log").await?;
+            assert!(m.last_applied.index() >= Some(index), "node {}", id);
+        }
+    }
+
+    tracing::info!("--- an isolated follower does not apply a new log");
+    let index = {
+        router.isolate_node(2);
+
+        let resp = n0.client_write(request("0", 2)).await?;
+        let index = resp.log_id.index;
+
+        let res = router
+            .wait(&2, Some(Duration::from_millis(500)))
+            .applied_index(index, "isolated follower does not apply the log")
+            .await;
+
+        match res {
+            Err(WaitError::Timeout(_, _)) => {
+                // ok
+            }
+            _ => {
+                panic!("expect WaitError::Timeout, got: {:?}", res);
+            }
+        }
+
+        index
+    };
+
+    tracing::info!("--- the follower applies the log once it is restored");
+    {
+        router.restore_node(2);
+
+        let m = router.wait(&2, timeout()).applied_index(index, "restored follower applied the log").await?;
+        assert!(m.last_applied.index() >= Some(index));
+    }
+
+    Ok(())
+}
+
+fn request(client_id: &str, serial: u64) -> ClientWriteRequest<memstore::Config> {
+    ClientWriteRequest::new(EntryPayload::Normal(ClientRequest::make_request(client_id, serial)))
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The leader reports the replication progress of every target in metrics.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters.
+/// - write some logs.
+/// - assert the leader reports every follower has matched the last log, has acknowledged, and is not backing off.
+/// - isolate a follower, assert the leader reports it is backing off.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn replication_progress() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            replication_progress_interval: 20,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- write logs, the progress of every follower is reported");
+    {
+        log_index += router.client_request_many(0, "0", 10).await?;
+
+        let want = Some(LogId::new(LeaderId::new(1, 0), log_index));
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| {
+                    [1, 2].iter().all(|id| match m.replication_progress.get(id) {
+                        Some(p) => {
+                            p.matched == want
+                                && p.max_possible_matched_index == Some(log_index)
+                                && p.snapshot.is_none()
+                                && p.last_acked.is_some()
+                                && p.backoff.is_none()
+                        }
+                        None => false,
+                    })
+                },
+                "followers matched the last log",
+            )
+            .await?;
+    }
+
+    tracing::info!("--- isolate node-2, the leader reports it is backing off");
+    {
+        router.isolate_node(2);
+
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.replication_progress.get(&2).map(|p| p.backoff.is_some()).unwrap_or(false),
+                "node-2 is backing off",
+            )
+            .await?;
+
+        let metrics = router.get_metrics(&0)?;
+        let p1 = metrics.replication_progress.get(&1).unwrap();
+        assert!(p1.backoff.is_none(), "node-1 is reachable");
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
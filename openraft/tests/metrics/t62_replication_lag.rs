@@ -0,0 +1,101 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The leader reports how far every replication target lags behind it in metrics.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters and 1 learner.
+/// - isolate node-2 and write some logs, assert the leader reports node-2 lags behind by the number of logs written.
+/// - restore node-2, assert its lag goes to zero once it catches up.
+/// - assert the lag is empty on non-leaders.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn replication_lag() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {3}).await?;
+
+    tracing::info!("--- every target has caught up");
+    {
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| {
+                    m.replication_lag.len() == 3
+                        && m.replication_lag
+                            .values()
+                            .all(|l| l.lag == 0 && l.matched.as_ref().map(|x| x.index) == Some(log_index))
+                },
+                "every target has zero lag",
+            )
+            .await?;
+
+        let metrics = router.get_metrics(&0)?;
+        assert!(
+            !metrics.replication_lag.contains_key(&0),
+            "the leader itself is not a target"
+        );
+    }
+
+    tracing::info!("--- isolate node-2, it lags behind");
+    {
+        router.isolate_node(2);
+
+        let n = 10;
+        log_index += router.client_request_many(0, "0", n).await?;
+
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.replication_lag.get(&2).map(|l| l.lag >= n as u64).unwrap_or(false),
+                "node-2 lags behind",
+            )
+            .await?;
+
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.replication_lag.get(&1).map(|l| l.lag == 0).unwrap_or(false),
+                "node-1 has caught up",
+            )
+            .await?;
+    }
+
+    tracing::info!("--- restore node-2, its lag goes to zero");
+    {
+        router.restore_node(2);
+
+        let want = Some(LogId::new(LeaderId::new(1, 0), log_index));
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.replication_lag.get(&2).map(|l| l.lag == 0 && l.matched == want).unwrap_or(false),
+                "node-2 has caught up",
+            )
+            .await?;
+    }
+
+    tracing::info!("--- non-leaders report no lag");
+    {
+        for id in [1, 2, 3] {
+            let metrics = router.get_metrics(&id)?;
+            assert!(metrics.replication_lag.is_empty(), "node-{} is not a leader", id);
+        }
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
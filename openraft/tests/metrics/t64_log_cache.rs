@@ -0,0 +1,74 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The replication streams read recent logs from the leader's log cache, and older logs from the log storage.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters, with a log cache of 5 logs.
+/// - write some logs, assert the leader reports they are read from the cache.
+/// - isolate node-2 and write more logs than the cache holds.
+/// - restore node-2, assert it catches up by reading the evicted logs from the log storage.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn log_cache() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            log_cache_max_entries: 5,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- recent logs are read from the cache");
+    {
+        let before = router.get_metrics(&0)?.log_cache;
+
+        log_index += router.client_request_many(0, "0", 3).await?;
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "write logs").await?;
+
+        router
+            .wait(&0, timeout())
+            .metrics(|m| m.log_cache.hits > before.hits, "logs are read from the cache")
+            .await?;
+    }
+
+    tracing::info!("--- isolate node-2, write more logs than the cache holds");
+    {
+        router.isolate_node(2);
+
+        log_index += router.client_request_many(0, "0", 20).await?;
+        router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "write logs").await?;
+    }
+
+    tracing::info!("--- restore node-2, it catches up with logs read from the log storage");
+    {
+        let before = router.get_metrics(&0)?.log_cache;
+
+        router.restore_node(2);
+        router.wait_for_log(&btreeset! {2}, Some(log_index), timeout(), "node-2 catches up").await?;
+
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.log_cache.misses > before.misses,
+                "evicted logs are read from the log storage",
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
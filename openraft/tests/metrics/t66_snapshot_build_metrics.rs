@@ -0,0 +1,89 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::metrics::SnapshotBuildProgress;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::SnapshotPolicy;
+use openraft::Wrapper;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A snapshot build and the progress reported by the builder are exposed in `RaftMetrics::snapshot_build`.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 2 voters, with a snapshot policy that never triggers a snapshot.
+/// - let the snapshot builder of node-1 take 4 steps and report the progress after every step.
+/// - trigger a snapshot on node-1, assert the metrics report the build in progress and the reported steps.
+/// - assert the metrics report the built snapshot and no progress once the build finishes.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_build_metrics() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(10_000),
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1}, btreeset! {}).await?;
+
+    log_index += router.client_request_many(0, "0", 10).await?;
+    router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "write logs").await?;
+
+    let want = LogId::new(LeaderId::new(1, 0), log_index);
+
+    tracing::info!("--- no snapshot is being built");
+    {
+        let m = router.get_metrics(&1)?;
+        assert!(!m.snapshot_build.building);
+        assert_eq!(None, m.snapshot_build.last_snapshot_log_id);
+        assert_eq!(None, m.snapshot_build.progress);
+    }
+
+    tracing::info!("--- build a snapshot on node-1, the progress is reported");
+    {
+        let sto1 = router.get_storage_handle(&1)?;
+        sto1.inner().set_snapshot_build_steps(4, Duration::from_millis(200));
+
+        router.get_raft_handle(&1)?.trigger_snapshot().await?;
+
+        // The last step is followed by the end of the build at once, thus it may not be seen.
+        for done in 1..=3 {
+            let m = router
+                .wait(&1, timeout())
+                .metrics(
+                    |m| m.snapshot_build.progress.map(|p| p.done) == Some(done),
+                    format!("progress {}/4", done),
+                )
+                .await?;
+
+            assert!(m.snapshot_build.building);
+            assert_eq!(None, m.snapshot_build.last_snapshot_log_id);
+            assert_eq!(
+                Some(SnapshotBuildProgress { done, total: 4 }),
+                m.snapshot_build.progress
+            );
+            assert_eq!(Some(done as f64 / 4.0), m.snapshot_build.progress.unwrap().fraction());
+        }
+
+        router.wait(&1, timeout()).snapshot(want, "node-1 built a snapshot").await?;
+        router.wait(&1, timeout()).metrics(|m| !m.snapshot_build.building, "build finished").await?;
+
+        let m = router.get_metrics(&1)?;
+        assert_eq!(Some(want), m.snapshot_build.last_snapshot_log_id);
+        assert_eq!(None, m.snapshot_build.progress, "progress is reset");
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
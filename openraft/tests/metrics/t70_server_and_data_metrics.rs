@@ -0,0 +1,72 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::ServerState;
+use openraft::Vote;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The server metrics are not updated by writes, while the data metrics are.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters.
+/// - assert the server metrics of the leader.
+/// - write logs, assert the data metrics reflect them, and the server metrics channel is not updated.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn server_and_data_metrics() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let mut server_rx = n0.server_metrics();
+    let data_rx = n0.data_metrics();
+
+    tracing::info!("--- server metrics of the leader");
+    {
+        // Consume the changes made during setting up the cluster.
+        while tokio::time::timeout(Duration::from_millis(200), server_rx.changed()).await.is_ok() {}
+
+        let m = server_rx.borrow().clone();
+        assert_eq!(0, m.id);
+        assert_eq!(Vote::new_committed(1, 0), m.vote);
+        assert_eq!(ServerState::Leader, m.state);
+        assert_eq!(Some(0), m.current_leader);
+        assert_eq!(
+            btreeset! {0,1,2},
+            m.membership_config.voter_ids().collect::<BTreeSet<_>>()
+        );
+    }
+
+    tracing::info!("--- write logs, only data metrics are updated");
+    {
+        log_index += router.client_request_many(0, "0", 10).await?;
+        router.wait(&0, timeout()).log(Some(log_index), "logs are applied").await?;
+
+        let want = Some(LogId::new(LeaderId::new(1, 0), log_index));
+        {
+            let m = data_rx.borrow();
+            assert_eq!(want, m.last_log);
+            assert_eq!(want, m.last_applied);
+            assert!(m.replication.is_some());
+        }
+
+        let res = tokio::time::timeout(Duration::from_millis(200), server_rx.changed()).await;
+        assert!(res.is_err(), "server metrics are not changed by writes");
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
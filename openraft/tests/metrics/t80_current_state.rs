@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::Vote;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// `Raft::current_state()` returns the same state as the metrics.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters, write some logs.
+/// - assert the state summary of the leader and a follower matches the corresponding fields in their metrics.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn current_state() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    log_index += router.client_request_many(0, "0", 10).await?;
+    router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "logs are applied").await?;
+
+    let want = Some(LogId::new(LeaderId::new(1, 0), log_index));
+
+    tracing::info!("--- the leader");
+    {
+        let st = router.get_raft_handle(&0)?.current_state().await?;
+        let m = router.get_metrics(&0)?;
+
+        assert_eq!(m.current_term, st.term);
+        assert_eq!(Vote::new_committed(1, 0), st.vote);
+        assert_eq!(m.last_log_index, st.last_log_id.map(|x| x.index));
+        assert_eq!(m.last_applied, st.last_applied);
+        assert_eq!(m.current_leader, st.current_leader);
+
+        assert_eq!(want, st.last_log_id);
+        assert_eq!(want, st.committed, "the leader has committed every log");
+        assert_eq!(Some(0), st.current_leader);
+    }
+
+    tracing::info!("--- a follower");
+    {
+        let st = router.get_raft_handle(&1)?.current_state().await?;
+        let m = router.get_metrics(&1)?;
+
+        assert_eq!(m.current_term, st.term);
+        assert_eq!(m.last_log_index, st.last_log_id.map(|x| x.index));
+        assert_eq!(m.last_applied, st.last_applied);
+        assert_eq!(m.current_leader, st.current_leader);
+        assert_eq!(Some(0), st.current_leader);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -8,6 +8,16 @@ mod t20_api_install_snapshot;
 mod t23_snapshot_chunk_size;
 mod t24_snapshot_ge_half_threshold;
 mod t25_snapshot_line_rate_to_snapshot;
+mod t26_snapshot_non_divisible_chunks;
+mod t27_snapshot_policy_custom;
+mod t28_trigger_snapshot;
+mod t29_snapshot_policy_bytes_and_interval;
+mod t30_snapshot_throttle;
+mod t31_snapshot_checksum;
+mod t32_full_snapshot;
+mod t33_snapshot_from_voters;
+mod t34_install_local_snapshot;
+mod t35_get_snapshot;
 mod t40_after_snapshot_add_learner_and_request_a_log;
 mod t41_snapshot_overrides_membership;
 mod t42_snapshot_uses_prev_snap_membership;
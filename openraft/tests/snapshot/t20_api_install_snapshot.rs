@@ -3,6 +3,7 @@ use std::time::Duration;
 
 use anyhow::Result;
 use maplit::btreeset;
+use openraft::error::InstallSnapshotError;
 use openraft::raft::InstallSnapshotRequest;
 use openraft::Config;
 use openraft::LeaderId;
@@ -19,7 +20,8 @@ use crate::fixtures::RaftRouter;
 /// What does this test do?
 ///
 /// - build a stable single node cluster.
-/// - send install_snapshot request with matched/mismatched id and offset
+/// - send install_snapshot request with matched/mismatched id and offset.
+/// - send duplicate, overlapping and out of order chunks, and a chunk with a mismatched checksum.
 #[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
 async fn snapshot_arguments() -> Result<()> {
     let config = Arc::new(Config::default().validate()?);
@@ -55,6 +57,8 @@ async fn snapshot_arguments() -> Result<()> {
         offset: 0,
         data: vec![1, 2, 3],
         done: false,
+        chunk_checksum: None,
+        snapshot_checksum: None,
     };
 
     tracing::info!("--- only allow to begin a new session when offset is 0");
@@ -99,12 +103,52 @@ async fn snapshot_arguments() -> Result<()> {
         n.0.install_snapshot(req).await?;
     }
 
-    tracing::info!("-- continue write with mismatched offset is allowed");
+    tracing::info!("-- a chunk that is already received is ignored, the next offset is returned");
+    {
+        let mut req = req0.clone();
+        req.offset = 0;
+        req.meta.snapshot_id = "ss2".into();
+        let resp = n.0.install_snapshot(req).await?;
+        assert_eq!(Some(6), resp.next_offset);
+    }
+
+    tracing::info!("-- only the part not received of an overlapping chunk is written");
+    {
+        let mut req = req0.clone();
+        req.offset = 4;
+        req.meta.snapshot_id = "ss2".into();
+        let resp = n.0.install_snapshot(req).await?;
+        assert_eq!(Some(7), resp.next_offset);
+    }
+
+    tracing::info!("-- continue write with a gap is rejected with the offset to resume from");
     {
         let mut req = req0.clone();
         req.offset = 8;
         req.meta.snapshot_id = "ss2".into();
-        n.0.install_snapshot(req).await?;
+        let res = n.0.install_snapshot(req).await;
+        assert_eq!(
+            "snapshot segment id mismatch, expect: ss2+7, got: ss2+8",
+            res.unwrap_err().to_string()
+        );
+    }
+
+    tracing::info!("-- a chunk with a mismatched checksum is rejected and can be re-sent");
+    {
+        let mut req = req0.clone();
+        req.offset = 7;
+        req.meta.snapshot_id = "ss2".into();
+        req.chunk_checksum = Some(crc32fast::hash(&req.data).wrapping_add(1));
+        let res = n.0.install_snapshot(req.clone()).await;
+        assert!(
+            matches!(res, Err(InstallSnapshotError::ChecksumMismatch(ref e)) if e.offset == Some(7)),
+            "got: {:?}",
+            res
+        );
+
+        req.chunk_checksum = Some(crc32fast::hash(&req.data));
+        let resp = n.0.install_snapshot(req).await?;
+        assert_eq!(Some(10), resp.next_offset);
     }
     Ok(())
 }
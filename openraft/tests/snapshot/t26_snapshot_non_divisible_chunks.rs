@@ -0,0 +1,132 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::raft::InstallSnapshotRequest;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftStorage;
+use openraft::RaftStorageDebug;
+use openraft::ServerState;
+use openraft::SnapshotPolicy;
+use openraft::Vote;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Install a snapshot that is split into several chunks, the last of which is smaller than the others.
+///
+/// What does this test do?
+///
+/// - build a stable single node cluster and write enough logs to trigger a snapshot.
+/// - split the snapshot data into chunks with a size that does not divide the data length.
+/// - send the chunks to a new node with `install_snapshot` and assert the state machine is rebuilt from the reassembled
+///   data.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_non_divisible_chunks() -> Result<()> {
+    let snapshot_threshold: u64 = 10;
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = 0;
+
+    tracing::info!("--- initializing cluster");
+    {
+        router.new_raft_node(0);
+
+        router.wait_for_log(&btreeset![0], None, timeout(), "empty").await?;
+        router.wait_for_state(&btreeset![0], ServerState::Learner, timeout(), "empty").await?;
+
+        router.initialize_from_single_node(0).await?;
+        log_index += 1;
+
+        router.wait_for_log(&btreeset![0], Some(log_index), timeout(), "init leader").await?;
+    }
+
+    tracing::info!("--- send just enough logs to trigger snapshot");
+    {
+        router.client_request_many(0, "0", (snapshot_threshold - 1 - log_index) as usize).await?;
+        log_index = snapshot_threshold - 1;
+
+        router.wait_for_log(&btreeset![0], Some(log_index), timeout(), "trigger snapshot").await?;
+        router
+            .wait_for_snapshot(
+                &btreeset![0],
+                LogId::new(LeaderId::new(1, 0), log_index),
+                None,
+                "snapshot",
+            )
+            .await?;
+    }
+
+    let mut sto0 = router.get_storage_handle(&0)?;
+    let snapshot = sto0.get_current_snapshot().await?.expect("snapshot is built");
+    let data = snapshot.snapshot.into_inner();
+
+    // Find a chunk size that gives several chunks and leaves a partial last chunk.
+    let mut chunk_size = data.len() / 4;
+    while data.len() % chunk_size == 0 {
+        chunk_size -= 1;
+    }
+    assert!(chunk_size > 0);
+    tracing::info!(len = data.len(), chunk_size, "split snapshot data");
+
+    tracing::info!("--- install the snapshot on a new node chunk by chunk");
+    {
+        router.new_raft_node(1);
+        let n1 = router.get_raft_handle(&1)?;
+
+        let chunks = data.chunks(chunk_size).collect::<Vec<_>>();
+        assert!(chunks.len() > 4);
+        assert!(chunks.last().unwrap().len() < chunk_size);
+
+        let mut offset = 0;
+        for (i, chunk) in chunks.iter().enumerate() {
+            let req = InstallSnapshotRequest {
+                vote: Vote::new_committed(1, 0),
+                meta: snapshot.meta.clone(),
+                offset,
+                data: chunk.to_vec(),
+                done: i == chunks.len() - 1,
+                chunk_checksum: None,
+                snapshot_checksum: None,
+            };
+            n1.install_snapshot(req).await?;
+            offset += chunk.len() as u64;
+        }
+
+        router
+            .wait_for_snapshot(
+                &btreeset![1],
+                LogId::new(LeaderId::new(1, 0), log_index),
+                None,
+                "installed",
+            )
+            .await?;
+    }
+
+    tracing::info!("--- the state machine is rebuilt from the reassembled snapshot");
+    {
+        let sm0 = sto0.get_state_machine().await;
+        let sm1 = router.get_storage_handle(&1)?.get_state_machine().await;
+
+        assert_eq!(sm0.last_applied_log, sm1.last_applied_log);
+        assert_eq!(sm0.client_status, sm1.client_status);
+        assert_eq!(sm0.client_serial_responses, sm1.client_serial_responses);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1000))
+}
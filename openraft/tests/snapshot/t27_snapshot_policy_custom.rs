@@ -0,0 +1,72 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::SnapshotPolicy;
+use openraft::SnapshotPolicyContext;
+use openraft::SnapshotPredicate;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A snapshot is built when the `SnapshotPolicy::Custom` predicate returns true.
+///
+/// What does this test do?
+///
+/// - build a stable single node cluster with a custom policy that snapshots once 5 logs are applied.
+/// - send logs to index 5, assert a snapshot is built at index 5.
+/// - assert the predicate is given the state of the last applied log.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_policy_custom() -> Result<()> {
+    let seen: Arc<Mutex<Vec<SnapshotPolicyContext>>> = Arc::new(Mutex::new(vec![]));
+
+    let predicate = {
+        let seen = seen.clone();
+        SnapshotPredicate::new(move |ctx| {
+            seen.lock().unwrap().push(ctx.clone());
+            ctx.last_applied_index >= 5 && ctx.logs_since_last > 0
+        })
+    };
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::Custom(predicate),
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- send logs to trigger snapshot");
+    {
+        router.client_request_many(0, "0", (5 - log_index) as usize).await?;
+        log_index = 5;
+
+        router.wait_for_log(&btreeset![0], Some(log_index), None, "write logs").await?;
+        router
+            .wait_for_snapshot(
+                &btreeset![0],
+                LogId::new(LeaderId::new(1, 0), log_index),
+                None,
+                "snapshot",
+            )
+            .await?;
+    }
+
+    tracing::info!("--- the predicate sees the last applied log");
+    {
+        let seen = seen.lock().unwrap();
+        let last = seen.iter().find(|c| c.last_applied_index == log_index).unwrap();
+
+        assert_eq!(1, last.last_applied_term);
+        assert_eq!(log_index + 1, last.logs_since_last);
+    }
+
+    Ok(())
+}
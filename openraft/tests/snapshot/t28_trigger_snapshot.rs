@@ -0,0 +1,74 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::raft::TriggerSnapshotResult;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Build a snapshot on demand with `Raft::trigger_snapshot()`.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 2 voters, with a snapshot policy that never triggers a snapshot.
+/// - write some logs, trigger a snapshot on the follower, assert it builds a local snapshot.
+/// - trigger again, assert nothing is done since no log is applied after the snapshot.
+/// - trigger on the leader, assert it builds a snapshot too.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn trigger_snapshot() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(10_000),
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1}, btreeset! {}).await?;
+
+    log_index += router.client_request_many(0, "0", 10).await?;
+    router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "write logs").await?;
+
+    let want = LogId::new(LeaderId::new(1, 0), log_index);
+
+    tracing::info!("--- trigger snapshot on the follower");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let res = n1.trigger_snapshot().await?;
+        assert_eq!(TriggerSnapshotResult::Scheduled, res);
+
+        router.wait(&1, timeout()).snapshot(want, "follower built a snapshot").await?;
+
+        let m = router.get_metrics(&0)?;
+        assert_eq!(None, m.snapshot, "the leader does not build a snapshot");
+    }
+
+    tracing::info!("--- trigger again, nothing to snapshot");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let res = n1.trigger_snapshot().await?;
+        assert_eq!(TriggerSnapshotResult::NothingToSnapshot, res);
+    }
+
+    tracing::info!("--- trigger snapshot on the leader");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let res = n0.trigger_snapshot().await?;
+        assert_eq!(TriggerSnapshotResult::Scheduled, res);
+
+        router.wait(&0, timeout()).snapshot(want, "leader built a snapshot").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
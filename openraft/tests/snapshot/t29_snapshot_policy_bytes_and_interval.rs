@@ -0,0 +1,96 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A snapshot is built by the `SinceLastBytes` policy once enough bytes of logs are appended.
+///
+/// What does this test do?
+///
+/// - build a stable single node cluster with a policy that snapshots once 4 client requests are appended.
+/// - write 3 logs, assert no snapshot is built.
+/// - write 1 more log, assert a snapshot is built at the last log.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_policy_since_last_bytes() -> Result<()> {
+    let size = std::mem::size_of::<ClientRequest>() as u64;
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::SinceLastBytes(size * 4),
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- write 3 logs, not enough bytes");
+    {
+        log_index += router.client_request_many(0, "0", 3).await?;
+        router.wait_for_log(&btreeset![0], Some(log_index), timeout(), "write logs").await?;
+
+        let m = router.get_metrics(&0)?;
+        assert_eq!(None, m.snapshot);
+    }
+
+    tracing::info!("--- write 1 more log, a snapshot is built");
+    {
+        log_index += router.client_request_many(0, "1", 1).await?;
+
+        router
+            .wait(&0, timeout())
+            .snapshot(LogId::new(LeaderId::new(1, 0), log_index), "snapshot by bytes")
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// A snapshot is built by the `Interval` policy, combined with another policy that does not trigger.
+///
+/// What does this test do?
+///
+/// - build a stable single node cluster with a policy that snapshots every 200 ms or every 10000 logs.
+/// - write some logs, assert a snapshot is built without writing more.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_policy_interval() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::Any(vec![
+                SnapshotPolicy::LogsSinceLast(10_000),
+                SnapshotPolicy::Interval(Duration::from_millis(200)),
+            ]),
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- write logs, a snapshot is built when the interval elapses");
+    {
+        log_index += router.client_request_many(0, "0", 3).await?;
+
+        router
+            .wait(&0, timeout())
+            .snapshot(LogId::new(LeaderId::new(1, 0), log_index), "snapshot by interval")
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,130 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftStorage;
+use openraft::ServerState;
+use openraft::SnapshotPolicy;
+use tokio::time::Instant;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Limit the rate at which a snapshot is streamed, while heartbeats keep flowing to the target.
+///
+/// What does this test do?
+///
+/// - build a single node cluster, write enough logs to build a snapshot and purge all logs.
+/// - add a learner, which has to be caught up by a snapshot streamed at 100 bytes per second.
+/// - while the snapshot is being streamed, assert the learner keeps acknowledging heartbeats.
+/// - assert the snapshot takes at least as long as the rate limit allows.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_throttle() -> Result<()> {
+    let snapshot_threshold: u64 = 10;
+    let bytes_per_sec: u64 = 100;
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
+            max_applied_log_to_keep: 0,
+            snapshot_max_chunk_size: 50,
+            install_snapshot_max_bytes_per_sec: Some(bytes_per_sec),
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- write logs to build a snapshot and purge logs");
+    let snapshot_log_id = {
+        router.client_request_many(0, "0", (snapshot_threshold - 1 - log_index) as usize).await?;
+        log_index = snapshot_threshold - 1;
+
+        let log_id = LogId::new(LeaderId::new(1, 0), log_index);
+        router.wait(&0, timeout()).snapshot(log_id, "build snapshot").await?;
+        router.wait(&0, timeout()).purged(log_id, "purge logs").await?;
+        log_id
+    };
+
+    let size = {
+        let mut sto0 = router.get_storage_handle(&0)?;
+        let snapshot = sto0.get_current_snapshot().await?.expect("snapshot is built");
+        snapshot.snapshot.into_inner().len() as u64
+    };
+    tracing::info!(size, "snapshot size");
+    assert!(
+        size >= 2 * bytes_per_sec,
+        "the snapshot is throttled for at least a second"
+    );
+
+    tracing::info!("--- add a learner, which is caught up by a throttled snapshot");
+    let start = Instant::now();
+    {
+        router.new_raft_node(1);
+        let n0 = router.get_raft_handle(&0)?;
+        n0.add_learner(1, None, false).await?;
+        log_index += 1;
+
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.replication_progress.get(&1).map(|p| p.snapshot.is_some()).unwrap_or(false),
+                "start to stream snapshot to node-1",
+            )
+            .await?;
+    }
+
+    tracing::info!("--- heartbeats are acknowledged while chunks are held back");
+    {
+        for _ in 0..10 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+
+            let m0 = router.get_metrics(&0)?;
+            assert_eq!(ServerState::Leader, m0.state);
+            assert_eq!(1, m0.current_term);
+
+            let progress = m0.replication_progress.get(&1).unwrap();
+            if progress.snapshot.is_none() {
+                break;
+            }
+
+            let acked = progress.last_acked.expect("node-1 acked");
+            let since_acked = Instant::now().saturating_duration_since(acked);
+            assert!(
+                since_acked < Duration::from_millis(300),
+                "heartbeats keep flowing, last acked {:?} ago",
+                since_acked
+            );
+        }
+    }
+
+    tracing::info!("--- the snapshot is installed no sooner than the rate limit allows");
+    {
+        let wait_timeout = Duration::from_millis(size * 1_000 / bytes_per_sec + 5_000);
+        router.wait(&1, Some(wait_timeout)).snapshot(snapshot_log_id, "install snapshot").await?;
+
+        // The first second worth of bytes is sent at once, and a chunk is sent before waiting for it.
+        let min_elapsed = Duration::from_millis((size - bytes_per_sec - 50) * 1_000 / bytes_per_sec);
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed >= min_elapsed,
+            "elapsed: {:?}, expect at least: {:?}",
+            elapsed,
+            min_elapsed
+        );
+
+        router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "node-1 catches up").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
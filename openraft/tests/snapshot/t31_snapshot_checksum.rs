@@ -0,0 +1,75 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Transfer a snapshot in small chunks with checksums enabled.
+///
+/// What does this test do?
+///
+/// - build a stable single node cluster with `enable_snapshot_checksum`.
+/// - send enough requests to the node that log compaction will be triggered.
+/// - add learner and assert that it receives and installs the snapshot.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_checksum() -> Result<()> {
+    let snapshot_threshold: u64 = 10;
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
+            snapshot_max_chunk_size: 10,
+            enable_snapshot_checksum: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- send just enough logs to trigger snapshot");
+    {
+        router.client_request_many(0, "0", (snapshot_threshold - 1 - log_index) as usize).await?;
+        log_index = snapshot_threshold - 1;
+
+        router
+            .wait_for_snapshot(
+                &btreeset![0],
+                LogId::new(LeaderId::new(1, 0), log_index),
+                timeout(),
+                "snapshot",
+            )
+            .await?;
+    }
+
+    tracing::info!("--- add learner to receive snapshot and logs");
+    {
+        router.new_raft_node(1);
+        router.add_learner(0, 1).await?;
+        log_index += 1;
+
+        router.wait_for_log(&btreeset![0, 1], Some(log_index), timeout(), "add learner").await?;
+        router
+            .wait_for_snapshot(
+                &btreeset![1],
+                LogId::new(LeaderId::new(1, 0), log_index),
+                timeout(),
+                "learner installed snapshot and built a new one",
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftLogReader;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Send a snapshot as a whole with `RaftNetwork::full_snapshot()` instead of streaming it in chunks.
+///
+/// What does this test do?
+///
+/// - build a stable single node cluster and trigger a snapshot.
+/// - assert `Raft::get_snapshot()` returns the snapshot.
+/// - enable full snapshot in the router, which installs the snapshot with `Raft::install_full_snapshot()`.
+/// - add learner and assert that it installs the snapshot and receives the logs after it.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn full_snapshot() -> Result<()> {
+    let snapshot_threshold: u64 = 10;
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- send just enough logs to trigger snapshot");
+    {
+        router.client_request_many(0, "0", (snapshot_threshold - 1 - log_index) as usize).await?;
+        log_index = snapshot_threshold - 1;
+
+        router
+            .wait_for_snapshot(
+                &btreeset![0],
+                LogId::new(LeaderId::new(1, 0), log_index),
+                timeout(),
+                "snapshot",
+            )
+            .await?;
+    }
+
+    tracing::info!("--- get the snapshot");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let snapshot = n0.get_snapshot().await?;
+        let snapshot = snapshot.ok_or_else(|| anyhow::anyhow!("no snapshot"))?;
+
+        assert_eq!(LogId::new(LeaderId::new(1, 0), log_index), snapshot.meta.last_log_id);
+    }
+
+    tracing::info!("--- add learner, it receives the full snapshot");
+    {
+        router.enable_full_snapshot(true);
+
+        router.new_raft_node(1);
+        router.add_learner(0, 1).await?;
+        log_index += 1;
+
+        router.wait_for_log(&btreeset![0, 1], Some(log_index), timeout(), "add learner").await?;
+
+        let mut sto1 = router.get_storage_handle(&1)?;
+        let log_state = sto1.get_log_state().await?;
+        assert_eq!(
+            Some(LogId::new(LeaderId::new(1, 0), snapshot_threshold - 1)),
+            log_state.last_purged_log_id,
+            "logs included in the snapshot are purged on the learner"
+        );
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
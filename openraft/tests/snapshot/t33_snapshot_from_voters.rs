@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::SnapshotPolicy;
+use openraft::SnapshotSource;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A learner fetches a snapshot from a voter instead of from the leader, with `SnapshotSource::Voters`.
+///
+/// What does this test do?
+///
+/// - bring up a cluster of 3 voters, with a snapshot policy that never triggers a snapshot.
+/// - build a snapshot on the leader, then write more logs and build newer snapshots on the followers.
+/// - add a learner, assert it installs the newer snapshot of a follower.
+/// - build a snapshot on the leader that is newer than the ones of the followers.
+/// - add another learner, assert the snapshots of the followers are stale and it installs the one of the leader.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_from_voters() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(10_000),
+            max_applied_log_to_keep: 0,
+            snapshot_source: SnapshotSource::Voters,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+    router.enable_full_snapshot(true);
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let snapshot_on = |router: &RaftRouter, id: u64, log_index: u64| {
+        let router = router.clone();
+        async move {
+            let n = router.get_raft_handle(&id)?;
+            n.trigger_snapshot().await?;
+            router
+                .wait(&id, timeout())
+                .snapshot(LogId::new(LeaderId::new(1, 0), log_index), "build snapshot")
+                .await?;
+            Ok::<(), anyhow::Error>(())
+        }
+    };
+
+    tracing::info!("--- build a snapshot on the leader, and newer ones on the followers");
+    {
+        log_index += router.client_request_many(0, "0", 5).await?;
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "write logs").await?;
+        snapshot_on(&router, 0, log_index).await?;
+
+        log_index += router.client_request_many(0, "0", 5).await?;
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "write more logs").await?;
+        snapshot_on(&router, 1, log_index).await?;
+        snapshot_on(&router, 2, log_index).await?;
+    }
+
+    tracing::info!("--- add learner-3, it fetches the snapshot of a follower");
+    {
+        let want = LogId::new(LeaderId::new(1, 0), log_index);
+
+        router.new_raft_node(3);
+        router.add_learner(0, 3).await?;
+        log_index += 1;
+
+        router.wait(&3, timeout()).snapshot(want, "learner-3 installs follower snapshot").await?;
+        router.wait_for_log(&btreeset! {0,3}, Some(log_index), timeout(), "learner-3 catches up").await?;
+    }
+
+    tracing::info!("--- build a newer snapshot on the leader, add learner-4, it receives the leader snapshot");
+    {
+        log_index += router.client_request_many(0, "0", 5).await?;
+        router.wait_for_log(&btreeset! {0,1,2,3}, Some(log_index), timeout(), "write logs").await?;
+        snapshot_on(&router, 0, log_index).await?;
+
+        let want = LogId::new(LeaderId::new(1, 0), log_index);
+
+        router.new_raft_node(4);
+        router.add_learner(0, 4).await?;
+        log_index += 1;
+
+        router.wait(&4, timeout()).snapshot(want, "learner-4 installs leader snapshot").await?;
+        router.wait_for_log(&btreeset! {0,4}, Some(log_index), timeout(), "learner-4 catches up").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,94 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::InstallLocalSnapshotError;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Install a snapshot provided locally with `Raft::install_local_snapshot()`, without a leader sending it.
+///
+/// What does this test do?
+///
+/// - build a stable single node cluster and trigger a snapshot.
+/// - create a new node that is not a member of the cluster, install the snapshot of node-0 on it.
+/// - assert the new node applies the snapshot and uses the membership config in it.
+/// - write more logs to node-0, assert installing the old snapshot on node-0 returns `SnapshotBehind`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn install_local_snapshot() -> Result<()> {
+    let snapshot_threshold: u64 = 10;
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    let snapshot_log_id = LogId::new(LeaderId::new(1, 0), snapshot_threshold - 1);
+
+    tracing::info!("--- send just enough logs to trigger snapshot");
+    {
+        router.client_request_many(0, "0", (snapshot_threshold - 1 - log_index) as usize).await?;
+        log_index = snapshot_threshold - 1;
+
+        router.wait_for_snapshot(&btreeset![0], snapshot_log_id.clone(), timeout(), "snapshot").await?;
+    }
+
+    tracing::info!("--- install the snapshot of node-0 on a new node");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let snapshot = n0.get_snapshot().await?.ok_or_else(|| anyhow::anyhow!("no snapshot"))?;
+
+        router.new_raft_node(1);
+        let n1 = router.get_raft_handle(&1)?;
+        n1.install_local_snapshot(snapshot.meta, snapshot.snapshot).await?;
+
+        let metrics = router
+            .wait(&1, timeout())
+            .metrics(
+                |m| m.last_applied == Some(snapshot_log_id.clone()),
+                "node-1 applied the snapshot",
+            )
+            .await?;
+        assert_eq!(
+            btreeset! {0},
+            metrics.membership_config.voter_ids().collect::<BTreeSet<_>>(),
+            "node-1 uses the membership config in the snapshot"
+        );
+    }
+
+    tracing::info!("--- a snapshot behind the last applied log is rejected");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let snapshot = n0.get_snapshot().await?.ok_or_else(|| anyhow::anyhow!("no snapshot"))?;
+
+        router.client_request_many(0, "0", 2).await?;
+        log_index += 2;
+        router.wait(&0, timeout()).log(Some(log_index), "write more logs").await?;
+
+        let res = n0.install_local_snapshot(snapshot.meta, snapshot.snapshot).await;
+        assert!(
+            matches!(&res, Err(InstallLocalSnapshotError::SnapshotBehind(e)) if e.last_log_id == snapshot_log_id),
+            "got: {:?}",
+            res
+        );
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::raft::TriggerSnapshotResult;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// `Raft::get_snapshot()` returns the current snapshot without building one, and holding it does not block
+/// building the next one.
+///
+/// What does this test do?
+///
+/// - bring up a single node cluster with a snapshot policy that never triggers a snapshot.
+/// - assert `get_snapshot()` returns `None` before any snapshot is built.
+/// - write some logs, trigger a snapshot, assert `get_snapshot()` returns it and its meta matches last-applied.
+/// - while holding the returned snapshot, write more logs and build another one, assert the held one is intact.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn get_snapshot() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(10_000),
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- no snapshot is built yet");
+    {
+        let snapshot = n0.get_snapshot().await?;
+        assert!(snapshot.is_none(), "get_snapshot() does not build a snapshot");
+    }
+
+    tracing::info!("--- build a snapshot and get it");
+    let held = {
+        log_index += router.client_request_many(0, "0", 10).await?;
+        router.wait_for_log(&btreeset! {0}, Some(log_index), timeout(), "write logs").await?;
+
+        let res = n0.trigger_snapshot().await?;
+        assert_eq!(TriggerSnapshotResult::Scheduled, res);
+
+        let want = LogId::new(LeaderId::new(1, 0), log_index);
+        router.wait(&0, timeout()).snapshot(want, "snapshot is built").await?;
+
+        let snapshot = n0.get_snapshot().await?.ok_or_else(|| anyhow::anyhow!("no snapshot"))?;
+
+        let m = router.get_metrics(&0)?;
+        assert_eq!(
+            m.last_applied,
+            Some(snapshot.meta.last_log_id),
+            "snapshot meta matches last-applied"
+        );
+        assert!(!snapshot.snapshot.get_ref().is_empty(), "snapshot data is readable");
+
+        snapshot
+    };
+
+    tracing::info!("--- build another snapshot while holding the previous one");
+    {
+        let held_data = held.snapshot.get_ref().clone();
+
+        log_index += router.client_request_many(0, "0", 10).await?;
+        router.wait_for_log(&btreeset! {0}, Some(log_index), timeout(), "write more logs").await?;
+
+        let res = n0.trigger_snapshot().await?;
+        assert_eq!(TriggerSnapshotResult::Scheduled, res);
+
+        let want = LogId::new(LeaderId::new(1, 0), log_index);
+        router.wait(&0, timeout()).snapshot(want, "next snapshot is built").await?;
+
+        let snapshot = n0.get_snapshot().await?.ok_or_else(|| anyhow::anyhow!("no snapshot"))?;
+        assert_eq!(want, snapshot.meta.last_log_id, "the current snapshot is replaced");
+
+        assert_eq!(
+            LogId::new(LeaderId::new(1, 0), log_index - 10),
+            held.meta.last_log_id,
+            "the held snapshot is not changed"
+        );
+        assert_eq!(
+            &held_data,
+            held.snapshot.get_ref(),
+            "the held snapshot data is not changed"
+        );
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
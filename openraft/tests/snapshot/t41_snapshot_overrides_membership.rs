@@ -91,11 +91,17 @@ async fn snapshot_overrides_membership() -> Result<()> {
             let req = AppendEntriesRequest {
                 vote: Vote::new_committed(1, 0),
                 prev_log_id: None,
-                entries: vec![blank(0, 0), Entry {
-                    log_id: LogId::new(LeaderId::new(1, 0), 1),
-                    payload: EntryPayload::Membership(Membership::new(vec![btreeset! {2,3}], None)),
-                }],
+                entries: vec![
+                    Arc::new(blank(0, 0)),
+                    Arc::new(Entry {
+                        log_id: LogId::new(LeaderId::new(1, 0), 1),
+                        payload: EntryPayload::Membership(Membership::new(vec![btreeset! {2,3}], None)),
+                        context: None,
+                    }),
+                ],
                 leader_commit: Some(LogId::new(LeaderId::new(0, 0), 0)),
+                compressed_entries: None,
+                leader_transfer_to: None,
             };
             router.connect(1, None).await.send_append_entries(req).await?;
 
@@ -60,6 +60,7 @@ async fn snapshot_delete_conflicting_logs() -> Result<()> {
             &Entry {
                 log_id: LogId::new(LeaderId::new(0, 0), 0),
                 payload: EntryPayload::Membership(Membership::new(vec![btreeset! {0}], None)),
+                context: None,
             },
         ])
         .await?;
@@ -91,28 +92,32 @@ async fn snapshot_delete_conflicting_logs() -> Result<()> {
             vote: Vote::new_committed(1, 0),
             prev_log_id: None,
             entries: vec![
-                blank(0, 0),
-                blank(1, 1),
+                Arc::new(blank(0, 0)),
+                Arc::new(blank(1, 1)),
                 // conflict membership will be replaced with membership in snapshot
-                Entry {
+                Arc::new(Entry {
                     log_id: LogId::new(LeaderId::new(1, 0), 2),
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {2,3}], None)),
-                },
-                blank(1, 3),
-                blank(1, 4),
-                blank(1, 5),
-                blank(1, 6),
-                blank(1, 7),
-                blank(1, 8),
-                blank(1, 9),
-                blank(1, 10),
+                    context: None,
+                }),
+                Arc::new(blank(1, 3)),
+                Arc::new(blank(1, 4)),
+                Arc::new(blank(1, 5)),
+                Arc::new(blank(1, 6)),
+                Arc::new(blank(1, 7)),
+                Arc::new(blank(1, 8)),
+                Arc::new(blank(1, 9)),
+                Arc::new(blank(1, 10)),
                 // another conflict membership, will be removed
-                Entry {
+                Arc::new(Entry {
                     log_id: LogId::new(LeaderId::new(1, 0), 11),
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {4,5}], None)),
-                },
+                    context: None,
+                }),
             ],
             leader_commit: Some(LogId::new(LeaderId::new(0, 0), 0)),
+            compressed_entries: None,
+            leader_transfer_to: None,
         };
         router.connect(1, None).await.send_append_entries(req).await?;
 
@@ -143,6 +148,8 @@ async fn snapshot_delete_conflicting_logs() -> Result<()> {
             offset: 0,
             data: snap.snapshot.into_inner(),
             done: true,
+            chunk_checksum: None,
+            snapshot_checksum: None,
         };
 
         router.connect(1, None).await.send_install_snapshot(req).await?;
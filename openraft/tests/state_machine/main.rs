@@ -9,4 +9,8 @@ mod fixtures;
 
 mod t10_total_order_apply;
 mod t20_state_machine_apply_membership;
+mod t30_subscribe_applied;
 mod t40_clean_applied_logs;
+mod t50_transform_payload;
+mod t60_pull_applied_entries;
+mod t70_leader_established_entry;
@@ -0,0 +1,117 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::async_runtime::broadcast;
+use openraft::raft::AppliedEvent;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Subscribe to the applied entries, across a snapshot installation.
+///
+/// What does this test do?
+///
+/// - bring up a single node cluster, subscribe to it, write logs to trigger a snapshot.
+/// - create node-1 and subscribe to it, add it as a learner, it receives the snapshot.
+/// - write one more log.
+/// - assert the leader's subscriber receives every entry in log order, including the membership entry.
+/// - assert the learner's subscriber receives the snapshot, then the entries after it in log order.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn subscribe_applied() -> Result<()> {
+    let snapshot_threshold: u64 = 10;
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
+            max_applied_log_to_keep: 1, // not 0: do not let add-learner log to trigger a snapshot.
+            purge_batch_size: 1,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+    let first_index = log_index + 1;
+    let snapshot_index = snapshot_threshold - 1;
+
+    let mut rx0 = router.get_raft_handle(&0)?.subscribe_applied();
+
+    tracing::info!("--- send logs to trigger snapshot");
+    {
+        router.client_request_many(0, "0", (snapshot_index - log_index) as usize).await?;
+        log_index = snapshot_index;
+
+        router
+            .wait(&0, timeout())
+            .snapshot(
+                LogId::new(LeaderId::new(1, 0), snapshot_index),
+                "leader-0 has built snapshot",
+            )
+            .await?;
+    }
+
+    tracing::info!("--- add learner, it receives the snapshot");
+    let mut rx1 = {
+        router.new_raft_node(1);
+        let rx1 = router.get_raft_handle(&1)?.subscribe_applied();
+
+        router.add_learner(0, 1).await?;
+        log_index += 1;
+
+        log_index += router.client_request_many(0, "0", 1).await?;
+        router.wait_for_log(&btreeset![0, 1], Some(log_index), timeout(), "learner catches up").await?;
+
+        rx1
+    };
+
+    tracing::info!("--- the leader's subscriber receives every entry");
+    {
+        for want in first_index..=log_index {
+            match recv(&mut rx0).await? {
+                AppliedEvent::Entry { log_id, payload, .. } => {
+                    assert_eq!(want, log_id.index);
+                    if want == snapshot_index + 1 {
+                        assert!(matches!(payload, EntryPayload::Membership(_)), "add-learner log");
+                    } else {
+                        assert!(matches!(payload, EntryPayload::Normal(_)), "client write log");
+                    }
+                }
+                ev => panic!("expect an entry, got: {:?}", ev),
+            }
+        }
+    }
+
+    tracing::info!("--- the learner's subscriber receives the snapshot, then entries");
+    {
+        match recv(&mut rx1).await? {
+            AppliedEvent::Snapshot { last_log_id } => assert_eq!(snapshot_index, last_log_id.index),
+            ev => panic!("expect a snapshot, got: {:?}", ev),
+        }
+
+        for want in snapshot_index + 1..=log_index {
+            match recv(&mut rx1).await? {
+                AppliedEvent::Entry { log_id, .. } => assert_eq!(want, log_id.index),
+                ev => panic!("expect an entry, got: {:?}", ev),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn recv(rx: &mut broadcast::Receiver<AppliedEvent<memstore::Config>>) -> Result<AppliedEvent<memstore::Config>> {
+    let ev = tokio::time::timeout(Duration::from_millis(1_000), rx.recv()).await??;
+    Ok(ev)
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
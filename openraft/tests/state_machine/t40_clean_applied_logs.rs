@@ -4,6 +4,8 @@ use std::time::Duration;
 use anyhow::Result;
 use maplit::btreeset;
 use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
 use openraft::RaftLogReader;
 use tokio::time::sleep;
 
@@ -43,6 +45,14 @@ async fn clean_applied_logs() -> Result<()> {
 
     tracing::info!("--- logs before max_applied_log_to_keep should be cleaned");
     {
+        router
+            .wait(&0, timeout())
+            .purged(
+                LogId::new(LeaderId::new(1, 0), log_index - 2),
+                "purged logs before the last 2",
+            )
+            .await?;
+
         for node_id in 0..1 {
             let mut sto = router.get_storage_handle(&node_id)?;
             let logs = sto.get_log_entries(..).await?;
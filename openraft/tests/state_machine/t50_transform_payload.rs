@@ -0,0 +1,100 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyerror::AnyError;
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::MemStore;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft::PayloadTransform;
+use openraft::RaftLogReader;
+use openraft::RaftStorageDebug;
+use openraft::TransformStore;
+use openraft::Wrapper;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::TypedRaftRouter;
+
+/// A transform that prefixes the status of a request with `enc:`.
+#[derive(Default)]
+struct Prefix;
+
+impl PayloadTransform<memstore::Config> for Prefix {
+    fn encode(&self, data: &ClientRequest) -> Result<ClientRequest, AnyError> {
+        let mut d = data.clone();
+        d.status = format!("enc:{}", data.status);
+        Ok(d)
+    }
+
+    fn decode(&self, data: &ClientRequest) -> Result<ClientRequest, AnyError> {
+        let mut d = data.clone();
+        d.status = match data.status.strip_prefix("enc:") {
+            Some(s) => s.to_string(),
+            None => return Err(AnyError::error(format!("not encoded: {}", data.status))),
+        };
+        Ok(d)
+    }
+}
+
+type Store = TransformStore<memstore::Config, Arc<MemStore>, Prefix>;
+
+/// Entries are encoded in the log store and decoded when replicated or applied.
+///
+/// What does this test do?
+///
+/// - bring a cluster of 3 voters, in which every node stores its log in a `TransformStore`.
+/// - write several logs to the leader.
+/// - assert the normal entries in the inner store of every node are encoded, and the membership entries are not.
+/// - assert the state machine of every node receives the decoded data.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn transform_payload() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = TypedRaftRouter::<memstore::Config, Store>::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- write to the leader");
+    {
+        router.client_request_many(0, "0", 10).await?;
+        log_index += 10;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "write 10 logs").await?;
+    }
+
+    tracing::info!("--- the inner store holds encoded entries, the state machine holds decoded data");
+    {
+        for id in [0, 1, 2] {
+            let mut sto = router.get_storage_handle(&id)?;
+
+            let raw = sto.inner().inner().try_get_log_entries(..).await?;
+            let mut normal = 0;
+            for ent in raw.iter() {
+                match &ent.payload {
+                    EntryPayload::Normal(d) => {
+                        assert!(d.status.starts_with("enc:"), "node {}: {:?}", id, ent);
+                        normal += 1;
+                    }
+                    EntryPayload::Membership(_) | EntryPayload::Blank => {}
+                }
+            }
+            assert_eq!(10, normal, "node {}", id);
+
+            let decoded = sto.try_get_log_entries(..).await?;
+            assert_eq!(raw.len(), decoded.len());
+            for (r, d) in raw.iter().zip(decoded.iter()) {
+                assert_eq!(r.log_id, d.log_id, "log id is not changed");
+            }
+
+            let sm = sto.get_state_machine().await;
+            assert_eq!(Some(&"request-9".to_string()), sm.client_status.get("0"), "node {}", id);
+        }
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
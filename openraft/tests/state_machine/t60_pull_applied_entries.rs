@@ -0,0 +1,123 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use memstore::MemStore;
+use openraft::storage::Adaptor;
+use openraft::storage::ApplyReceiver;
+use openraft::storage::ChannelStateMachine;
+use openraft::Config;
+use openraft::LogIdOptionExt;
+use openraft::Raft;
+use openraft::RaftStorage;
+use openraft::ServerState;
+use tokio::sync::mpsc;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The application pulls the committed logs from a `ChannelStateMachine` and acknowledges them.
+///
+/// What does this test do?
+///
+/// - bring up a single node cluster with a `ChannelStateMachine`, whose logs are applied by a consumer task.
+/// - write logs, assert every write is responded with the result the consumer acknowledged, in log order.
+/// - let the consumer drop a log without acknowledging it, assert the node shuts down.
+/// - restart the node with the same store, assert the unacknowledged log is delivered again.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn pull_applied_entries() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let router = RaftRouter::new(config.clone());
+
+    let sto = MemStore::new_async().await;
+
+    let (log_store, _) = Adaptor::new(sto.clone());
+    let (state_machine, rx) = ChannelStateMachine::new(sto.clone(), 2);
+    let raft = Raft::new(0, config.clone(), router.clone(), log_store, state_machine);
+
+    let (tx_applied, mut applied) = mpsc::unbounded_channel();
+    let consumer = tokio::spawn(consume(sto.clone(), rx, Some(4), tx_applied));
+
+    let mut log_index = 0;
+
+    tracing::info!("--- initialize a single node cluster");
+    {
+        raft.initialize(btreeset! {0}).await?;
+        log_index += 1;
+
+        raft.wait(timeout()).state(ServerState::Leader, "node 0 becomes leader").await?;
+        raft.wait(timeout()).log(Some(log_index), "the blank log is applied").await?;
+    }
+
+    tracing::info!("--- write logs, they are applied by the consumer");
+    {
+        for serial in 0..2 {
+            let resp = raft.client_write(ClientRequest::make_request("foo", serial)).await?;
+            log_index += 1;
+            assert_eq!(log_index, resp.log_id.index);
+        }
+
+        let mut got = vec![];
+        while let Ok(index) = applied.try_recv() {
+            got.push(index);
+        }
+        assert_eq!((0..=log_index).collect::<Vec<_>>(), got);
+    }
+
+    tracing::info!("--- a log that is not acknowledged shuts down the node");
+    {
+        let r = raft.clone();
+        tokio::spawn(async move { r.client_write(ClientRequest::make_request("foo", 2)).await });
+
+        raft.wait(timeout()).metrics(|m| m.running_state.is_err(), "node 0 shuts down").await?;
+        consumer.await?;
+
+        assert_eq!(Some(log_index), sto.clone().last_applied_state().await?.0.index());
+    }
+
+    tracing::info!("--- restart, the unacknowledged log is delivered again");
+    {
+        let (log_store, _) = Adaptor::new(sto.clone());
+        let (state_machine, rx) = ChannelStateMachine::new(sto.clone(), 2);
+        let raft = Raft::new(0, config.clone(), router.clone(), log_store, state_machine);
+
+        let (tx_applied, mut applied) = mpsc::unbounded_channel();
+        tokio::spawn(consume(sto.clone(), rx, None, tx_applied));
+
+        // The unacknowledged log and the blank log of the new leader.
+        raft.wait(timeout()).log(Some(log_index + 2), "the restarted node applied logs").await?;
+
+        assert_eq!(Some(log_index + 1), applied.recv().await);
+    }
+
+    Ok(())
+}
+
+/// Apply the logs pulled from `rx` to `sto`, and send the index of every applied log to `tx_applied` before
+/// acknowledging it.
+///
+/// It drops the log at `drop_at` without acknowledging it, and quits.
+async fn consume(
+    mut sto: Arc<MemStore>,
+    mut rx: ApplyReceiver<memstore::Config>,
+    drop_at: Option<u64>,
+    tx_applied: mpsc::UnboundedSender<u64>,
+) {
+    while let Some(a) = rx.recv().await {
+        let index = a.log_id().index;
+        if Some(index) == drop_at {
+            return;
+        }
+
+        let mut res = sto.try_apply_to_state_machine(&[&a.entry]).await.unwrap();
+        let _ = tx_applied.send(index);
+        a.ack(res.pop().unwrap());
+    }
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::async_runtime::broadcast;
+use openraft::raft::AppliedEvent;
+use openraft::Config;
+use openraft::Entry;
+use openraft::EntryPayload;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::Membership;
+use openraft::RaftLogReader;
+use openraft::RaftStorage;
+use openraft::RaftStorageDebug;
+use openraft::Vote;
+
+use crate::fixtures::blank;
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A leader appends the data supplied by `RaftStorage::leader_established_data()` when it is established, instead of
+/// a blank entry, and it still commits the entries of the previous leaders.
+///
+/// What does this test do?
+///
+/// - fake the logs of 2 voters: the initial membership, the blank log of leader-1 and an uncommitted log of leader-1.
+/// - let the stores supply the data of the leader-establishment entry, and bring up the cluster.
+/// - assert the new leader appends its data at index 3, and every log is applied, including the one of leader-1.
+/// - assert the applied events tag the blank log of leader-1 and the data of the new leader as leader-established.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn leader_established_entry() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- fake stores with an uncommitted log of leader-1");
+    let mut stores = vec![];
+    for _ in 0..2 {
+        let mut sto = router.new_store();
+        sto.save_vote(&Vote::new(1, 0)).await?;
+        sto.append_to_log(&[
+            &Entry {
+                log_id: LogId::new(LeaderId::new(0, 0), 0),
+                payload: EntryPayload::Membership(Membership::new(vec![btreeset! {0,1}], None)),
+                context: None,
+            },
+            &blank(1, 1),
+            &Entry {
+                log_id: LogId::new(LeaderId::new(1, 0), 2),
+                payload: EntryPayload::Normal(ClientRequest::make_request("foo", 1)),
+                context: None,
+            },
+        ])
+        .await?;
+
+        sto.inner().set_leader_established_data(Some(ClientRequest::make_request("leader", 0)));
+        stores.push(sto);
+    }
+
+    tracing::info!("--- bring up the cluster");
+    let mut rx0 = {
+        router.new_raft_node_with_sto(0, stores[0].clone());
+        let rx0 = router.get_raft_handle(&0)?.subscribe_applied();
+        router.new_raft_node_with_sto(1, stores[1].clone());
+        rx0
+    };
+
+    tracing::info!("--- the new leader commits its data and the log of leader-1");
+    let leader_log_id = {
+        router.wait_for_log(&btreeset! {0,1}, Some(3), timeout(), "leader-established entry").await?;
+
+        let m = router.get_metrics(&0)?;
+        let leader = m.current_leader.unwrap();
+        assert!(m.current_term > 1, "a new leader is elected");
+
+        let mut sto_leader = router.get_storage_handle(&leader)?;
+        let logs = sto_leader.try_get_log_entries(3..4).await?;
+        match &logs[0].payload {
+            EntryPayload::Normal(data) => {
+                assert_eq!("leader", data.client);
+                assert_eq!(m.current_term, data.serial, "serial is the term of the leader");
+            }
+            p => panic!("expect normal entry, got: {:?}", p),
+        }
+
+        for id in [0, 1] {
+            let mut sto = router.get_storage_handle(&id)?;
+            let sm = sto.get_state_machine().await;
+            assert!(
+                sm.client_status.contains_key("foo"),
+                "node-{} applied the log of leader-1",
+                id
+            );
+            assert!(
+                sm.client_status.contains_key("leader"),
+                "node-{} applied the leader data",
+                id
+            );
+        }
+
+        logs[0].log_id
+    };
+
+    tracing::info!("--- applied events tag the leader-established entries");
+    {
+        let want = [(0, false), (1, true), (2, false), (3, true)];
+        for (index, tagged) in want {
+            match recv(&mut rx0).await? {
+                AppliedEvent::Entry {
+                    log_id,
+                    leader_established,
+                    ..
+                } => {
+                    assert_eq!(index, log_id.index);
+                    assert_eq!(tagged, leader_established, "log {}", index);
+                    if index == 3 {
+                        assert_eq!(leader_log_id, log_id);
+                    }
+                }
+                ev => panic!("expect an entry, got: {:?}", ev),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn recv(rx: &mut broadcast::Receiver<AppliedEvent<memstore::Config>>) -> Result<AppliedEvent<memstore::Config>> {
+    let ev = tokio::time::timeout(Duration::from_millis(1_000), rx.recv()).await??;
+    Ok(ev)
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
@@ -40,6 +40,9 @@ use serde::Serialize;
 
 pub type RocksNodeId = u64;
 
+/// The id of a Raft group sharing a RocksDB with other groups.
+pub type RocksGroupId = u64;
+
 openraft::declare_raft_types!(
     /// Declare the type configuration for `MemStore`.
     pub Config: D = RocksRequest, R = RocksResponse, NodeId = RocksNodeId
@@ -97,11 +100,15 @@ pub struct SerializableRocksStateMachine {
 impl From<&RocksStateMachine> for SerializableRocksStateMachine {
     fn from(state: &RocksStateMachine) -> Self {
         let mut data = BTreeMap::new();
+        let prefix = id_to_bin(state.group_id);
         for (key, value) in state.db.iterator_cf(
             state.db.cf_handle("data").expect("cf_handle"),
-            rocksdb::IteratorMode::Start,
+            rocksdb::IteratorMode::From(&prefix, Direction::Forward),
         ) {
-            let key: &[u8] = &key;
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            let key: &[u8] = &key[prefix.len()..];
             let value: &[u8] = &value;
             data.insert(
                 String::from_utf8(key.to_vec()).expect("invalid key"),
@@ -120,6 +127,9 @@ impl From<&RocksStateMachine> for SerializableRocksStateMachine {
 pub struct RocksStateMachine {
     /// Application data.
     pub db: Arc<rocksdb::DB>,
+
+    /// The Raft group this state machine belongs to. Every key is prefixed with it.
+    group_id: RocksGroupId,
 }
 
 fn sm_r_err<E: Error + 'static>(e: E) -> StorageError<RocksNodeId> {
@@ -134,7 +144,7 @@ impl RocksStateMachine {
         self.db
             .get_cf(
                 self.db.cf_handle("state_machine").expect("cf_handle"),
-                "last_membership".as_bytes(),
+                group_key(self.group_id, b"last_membership"),
             )
             .map_err(sm_r_err)
             .and_then(|value| {
@@ -147,7 +157,7 @@ impl RocksStateMachine {
         self.db
             .put_cf(
                 self.db.cf_handle("state_machine").expect("cf_handle"),
-                "last_membership".as_bytes(),
+                group_key(self.group_id, b"last_membership"),
                 serde_json::to_vec(&membership).map_err(sm_w_err)?,
             )
             .map_err(sm_w_err)
@@ -156,7 +166,7 @@ impl RocksStateMachine {
         self.db
             .get_cf(
                 self.db.cf_handle("state_machine").expect("cf_handle"),
-                "last_applied_log".as_bytes(),
+                group_key(self.group_id, b"last_applied_log"),
             )
             .map_err(sm_r_err)
             .and_then(|value| value.map(|v| serde_json::from_slice(&v).map_err(sm_r_err)).transpose())
@@ -165,16 +175,25 @@ impl RocksStateMachine {
         self.db
             .put_cf(
                 self.db.cf_handle("state_machine").expect("cf_handle"),
-                "last_applied_log".as_bytes(),
+                group_key(self.group_id, b"last_applied_log"),
                 serde_json::to_vec(&log_id).map_err(sm_w_err)?,
             )
             .map_err(sm_w_err)
     }
-    fn from_serializable(sm: SerializableRocksStateMachine, db: Arc<rocksdb::DB>) -> StorageResult<Self> {
+    fn from_serializable(
+        sm: SerializableRocksStateMachine,
+        db: Arc<rocksdb::DB>,
+        group_id: RocksGroupId,
+    ) -> StorageResult<Self> {
         for (key, value) in sm.data {
-            db.put_cf(db.cf_handle("data").unwrap(), key.as_bytes(), value.as_bytes()).map_err(sm_w_err)?;
+            db.put_cf(
+                db.cf_handle("data").unwrap(),
+                group_key(group_id, key.as_bytes()),
+                value.as_bytes(),
+            )
+            .map_err(sm_w_err)?;
         }
-        let r = Self { db };
+        let r = Self { db, group_id };
         if let Some(log_id) = sm.last_applied_log {
             r.set_last_applied_log(log_id)?;
         }
@@ -183,16 +202,20 @@ impl RocksStateMachine {
         Ok(r)
     }
 
-    fn new(db: Arc<rocksdb::DB>) -> RocksStateMachine {
-        Self { db }
+    fn new(db: Arc<rocksdb::DB>, group_id: RocksGroupId) -> RocksStateMachine {
+        Self { db, group_id }
     }
     fn insert(&self, key: String, value: String) -> StorageResult<()> {
         self.db
-            .put_cf(self.db.cf_handle("data").unwrap(), key.as_bytes(), value.as_bytes())
+            .put_cf(
+                self.db.cf_handle("data").unwrap(),
+                group_key(self.group_id, key.as_bytes()),
+                value.as_bytes(),
+            )
             .map_err(|e| StorageIOError::new(ErrorSubject::Store, ErrorVerb::Write, AnyError::new(&e)).into())
     }
     pub fn get(&self, key: &str) -> StorageResult<Option<String>> {
-        let key = key.as_bytes();
+        let key = group_key(self.group_id, key.as_bytes());
         self.db
             .get_cf(self.db.cf_handle("data").unwrap(), key)
             .map(|value| value.map(|v| String::from_utf8(v).expect("invalid data")))
@@ -200,10 +223,17 @@ impl RocksStateMachine {
     }
 }
 
+/// A storage of a single Raft group.
+///
+/// Several groups can share one RocksDB instance: every key of a group is prefixed with its group id, thus the
+/// vote, logs, snapshot and state machine of one group are never seen by another. See [`RocksStore::new_group()`].
 #[derive(Debug)]
 pub struct RocksStore {
     db: Arc<rocksdb::DB>,
 
+    /// The Raft group this store belongs to.
+    group_id: RocksGroupId,
+
     /// The Raft state machine.
     pub state_machine: RwLock<RocksStateMachine>,
 }
@@ -221,6 +251,13 @@ fn bin_to_id(buf: &[u8]) -> u64 {
     (&buf[0..8]).read_u64::<BigEndian>().unwrap()
 }
 
+/// Scope a key to a Raft group, by prefixing it with the group id.
+fn group_key(group_id: RocksGroupId, key: &[u8]) -> Vec<u8> {
+    let mut buf = id_to_bin(group_id);
+    buf.extend_from_slice(key);
+    buf
+}
+
 impl RocksStore {
     fn store(&self) -> &ColumnFamily {
         self.db.cf_handle("store").unwrap()
@@ -228,10 +265,23 @@ impl RocksStore {
     fn logs(&self) -> &ColumnFamily {
         self.db.cf_handle("logs").unwrap()
     }
+
+    /// The key of a log entry: the group id followed by the log index.
+    fn log_key(&self, index: u64) -> Vec<u8> {
+        group_key(self.group_id, &id_to_bin(index))
+    }
+
+    /// Decode the log index from a key built by `log_key()`, or `None` if it belongs to another group.
+    fn log_index(&self, key: &[u8]) -> Option<u64> {
+        if key.len() != 16 || bin_to_id(key) != self.group_id {
+            return None;
+        }
+        Some(bin_to_id(&key[8..]))
+    }
     fn get_last_purged_(&self) -> StorageResult<Option<LogId<u64>>> {
         Ok(self
             .db
-            .get_cf(self.store(), b"last_purged_log_id")
+            .get_cf(self.store(), group_key(self.group_id, b"last_purged_log_id"))
             .map_err(|e| StorageIOError::new(ErrorSubject::Store, ErrorVerb::Read, AnyError::new(&e)))?
             .and_then(|v| serde_json::from_slice(&v).ok()))
     }
@@ -240,7 +290,7 @@ impl RocksStore {
         self.db
             .put_cf(
                 self.store(),
-                b"last_purged_log_id",
+                group_key(self.group_id, b"last_purged_log_id"),
                 serde_json::to_vec(&log_id).unwrap().as_slice(),
             )
             .map_err(|e| StorageIOError::new(ErrorSubject::Store, ErrorVerb::Write, AnyError::new(&e)).into())
@@ -249,7 +299,7 @@ impl RocksStore {
     fn get_snapshot_index_(&self) -> StorageResult<u64> {
         Ok(self
             .db
-            .get_cf(self.store(), b"snapshot_index")
+            .get_cf(self.store(), group_key(self.group_id, b"snapshot_index"))
             .map_err(|e| StorageIOError::new(ErrorSubject::Store, ErrorVerb::Read, AnyError::new(&e)))?
             .and_then(|v| serde_json::from_slice(&v).ok())
             .unwrap_or(0))
@@ -259,7 +309,7 @@ impl RocksStore {
         self.db
             .put_cf(
                 self.store(),
-                b"snapshot_index",
+                group_key(self.group_id, b"snapshot_index"),
                 serde_json::to_vec(&snapshot_index).unwrap().as_slice(),
             )
             .map_err(|e| StorageError::IO {
@@ -270,7 +320,11 @@ impl RocksStore {
 
     fn set_vote_(&self, vote: &Vote<RocksNodeId>) -> StorageResult<()> {
         self.db
-            .put_cf(self.store(), b"vote", serde_json::to_vec(vote).unwrap())
+            .put_cf(
+                self.store(),
+                group_key(self.group_id, b"vote"),
+                serde_json::to_vec(vote).unwrap(),
+            )
             .map_err(|e| StorageError::IO {
                 source: StorageIOError::new(ErrorSubject::Vote, ErrorVerb::Write, AnyError::new(&e)),
             })
@@ -279,7 +333,7 @@ impl RocksStore {
     fn get_vote_(&self) -> StorageResult<Option<Vote<RocksNodeId>>> {
         Ok(self
             .db
-            .get_cf(self.store(), b"vote")
+            .get_cf(self.store(), group_key(self.group_id, b"vote"))
             .map_err(|e| StorageError::IO {
                 source: StorageIOError::new(ErrorSubject::Vote, ErrorVerb::Write, AnyError::new(&e)),
             })?
@@ -289,7 +343,7 @@ impl RocksStore {
     fn get_current_snapshot_(&self) -> StorageResult<Option<RocksSnapshot>> {
         Ok(self
             .db
-            .get_cf(self.store(), b"snapshot")
+            .get_cf(self.store(), group_key(self.group_id, b"snapshot"))
             .map_err(|e| StorageError::IO {
                 source: StorageIOError::new(ErrorSubject::Store, ErrorVerb::Read, AnyError::new(&e)),
             })?
@@ -298,9 +352,17 @@ impl RocksStore {
 
     fn set_current_snapshot_(&self, snap: RocksSnapshot) -> StorageResult<()> {
         self.db
-            .put_cf(self.store(), b"snapshot", serde_json::to_vec(&snap).unwrap().as_slice())
+            .put_cf(
+                self.store(),
+                group_key(self.group_id, b"snapshot"),
+                serde_json::to_vec(&snap).unwrap().as_slice(),
+            )
             .map_err(|e| StorageError::IO {
-                source: StorageIOError::new(ErrorSubject::Snapshot(snap.meta), ErrorVerb::Write, AnyError::new(&e)),
+                source: StorageIOError::new(
+                    ErrorSubject::Snapshot(snap.meta.signature()),
+                    ErrorVerb::Write,
+                    AnyError::new(&e),
+                ),
             })?;
         Ok(())
     }
@@ -309,10 +371,12 @@ impl RocksStore {
 #[async_trait]
 impl RaftLogReader<Config> for Arc<RocksStore> {
     async fn get_log_state(&mut self) -> StorageResult<LogState<Config>> {
+        let end = self.log_key(u64::MAX);
         let last = self
             .db
-            .iterator_cf(self.logs(), rocksdb::IteratorMode::End)
+            .iterator_cf(self.logs(), rocksdb::IteratorMode::From(&end, Direction::Reverse))
             .next()
+            .filter(|(key, _)| self.log_index(key).is_some())
             .and_then(|(_, ent)| Some(serde_json::from_slice::<Entry<Config>>(&ent).ok()?.log_id));
 
         let last_purged_log_id = self.get_last_purged_()?;
@@ -332,17 +396,17 @@ impl RaftLogReader<Config> for Arc<RocksStore> {
         range: RB,
     ) -> StorageResult<Vec<Entry<Config>>> {
         let start = match range.start_bound() {
-            std::ops::Bound::Included(x) => id_to_bin(*x),
-            std::ops::Bound::Excluded(x) => id_to_bin(*x + 1),
-            std::ops::Bound::Unbounded => id_to_bin(0),
+            std::ops::Bound::Included(x) => self.log_key(*x),
+            std::ops::Bound::Excluded(x) => self.log_key(*x + 1),
+            std::ops::Bound::Unbounded => self.log_key(0),
         };
         self.db
             .iterator_cf(self.logs(), rocksdb::IteratorMode::From(&start, Direction::Forward))
+            .map_while(|(key, val)| Some((self.log_index(&key)?, val)))
             .map(|(id, val)| {
                 let entry: StorageResult<Entry<_>> = serde_json::from_slice(&val).map_err(|e| StorageError::IO {
                     source: StorageIOError::new(ErrorSubject::Logs, ErrorVerb::Read, AnyError::new(&e)),
                 });
-                let id = bin_to_id(&id);
 
                 assert_eq!(Ok(id), entry.as_ref().map(|e| e.log_id.index));
                 (id, entry)
@@ -409,7 +473,6 @@ impl RaftSnapshotBuilder<Config, Cursor<Vec<u8>>> for Arc<RocksStore> {
 
 #[async_trait]
 impl RaftStorage<Config> for Arc<RocksStore> {
-    type SnapshotData = Cursor<Vec<u8>>;
     type LogReader = Self;
     type SnapshotBuilder = Self;
 
@@ -425,8 +488,8 @@ impl RaftStorage<Config> for Arc<RocksStore> {
     #[tracing::instrument(level = "trace", skip(self, entries))]
     async fn append_to_log(&mut self, entries: &[&Entry<Config>]) -> StorageResult<()> {
         for entry in entries {
-            let id = id_to_bin(entry.log_id.index);
-            assert_eq!(bin_to_id(&id), entry.log_id.index);
+            let id = self.log_key(entry.log_id.index);
+            assert_eq!(self.log_index(&id), Some(entry.log_id.index));
             self.db
                 .put_cf(
                     self.logs(),
@@ -443,8 +506,8 @@ impl RaftStorage<Config> for Arc<RocksStore> {
     async fn delete_conflict_logs_since(&mut self, log_id: LogId<RocksNodeId>) -> StorageResult<()> {
         tracing::debug!("delete_log: [{:?}, +oo)", log_id);
 
-        let from = id_to_bin(log_id.index);
-        let to = id_to_bin(0xff_ff_ff_ff_ff_ff_ff_ff);
+        let from = self.log_key(log_id.index);
+        let to = self.log_key(0xff_ff_ff_ff_ff_ff_ff_ff);
         self.db
             .delete_range_cf(self.logs(), &from, &to)
             .map_err(|e| StorageIOError::new(ErrorSubject::Logs, ErrorVerb::Write, AnyError::new(&e)).into())
@@ -455,8 +518,8 @@ impl RaftStorage<Config> for Arc<RocksStore> {
         tracing::debug!("delete_log: [0, {:?}]", log_id);
 
         self.set_last_purged_(log_id)?;
-        let from = id_to_bin(0);
-        let to = id_to_bin(log_id.index + 1);
+        let from = self.log_key(0);
+        let to = self.log_key(log_id.index + 1);
         self.db
             .delete_range_cf(self.logs(), &from, &to)
             .map_err(|e| StorageIOError::new(ErrorSubject::Logs, ErrorVerb::Write, AnyError::new(&e)).into())
@@ -509,7 +572,7 @@ impl RaftStorage<Config> for Arc<RocksStore> {
     }
 
     #[tracing::instrument(level = "trace", skip(self))]
-    async fn begin_receiving_snapshot(&mut self) -> Result<Box<Self::SnapshotData>, StorageError<RocksNodeId>> {
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<Cursor<Vec<u8>>>, StorageError<RocksNodeId>> {
         Ok(Box::new(Cursor::new(Vec::new())))
     }
 
@@ -517,7 +580,7 @@ impl RaftStorage<Config> for Arc<RocksStore> {
     async fn install_snapshot(
         &mut self,
         meta: &SnapshotMeta<RocksNodeId>,
-        snapshot: Box<Self::SnapshotData>,
+        snapshot: Box<Cursor<Vec<u8>>>,
     ) -> Result<StateMachineChanges<Config>, StorageError<RocksNodeId>> {
         tracing::info!(
             { snapshot_size = snapshot.get_ref().len() },
@@ -534,13 +597,14 @@ impl RaftStorage<Config> for Arc<RocksStore> {
             let updated_state_machine: SerializableRocksStateMachine = serde_json::from_slice(&new_snapshot.data)
                 .map_err(|e| {
                     StorageIOError::new(
-                        ErrorSubject::Snapshot(new_snapshot.meta.clone()),
+                        ErrorSubject::Snapshot(new_snapshot.meta.signature()),
                         ErrorVerb::Read,
                         AnyError::new(&e),
                     )
                 })?;
             let mut state_machine = self.state_machine.write().await;
-            *state_machine = RocksStateMachine::from_serializable(updated_state_machine, self.db.clone())?;
+            *state_machine =
+                RocksStateMachine::from_serializable(updated_state_machine, self.db.clone(), self.group_id)?;
         }
 
         self.set_current_snapshot_(new_snapshot)?;
@@ -553,7 +617,7 @@ impl RaftStorage<Config> for Arc<RocksStore> {
     #[tracing::instrument(level = "trace", skip(self))]
     async fn get_current_snapshot(
         &mut self,
-    ) -> Result<Option<Snapshot<RocksNodeId, Self::SnapshotData>>, StorageError<RocksNodeId>> {
+    ) -> Result<Option<Snapshot<RocksNodeId, Cursor<Vec<u8>>>>, StorageError<RocksNodeId>> {
         match RocksStore::get_current_snapshot_(self)? {
             Some(snapshot) => {
                 let data = snapshot.data.clone();
@@ -575,7 +639,26 @@ impl RaftStorage<Config> for Arc<RocksStore> {
     }
 }
 impl RocksStore {
+    /// Open a RocksDB at `db_path` and create the store of the default group `0` on it.
     pub async fn new<P: AsRef<Path>>(db_path: P) -> Arc<RocksStore> {
+        let db = Self::open_db(db_path);
+        Self::new_group(db, 0).await
+    }
+
+    /// Create the store of the Raft group `group_id` on a RocksDB shared with other groups.
+    ///
+    /// The RocksDB has to be opened with [`RocksStore::open_db()`].
+    pub async fn new_group(db: Arc<rocksdb::DB>, group_id: RocksGroupId) -> Arc<RocksStore> {
+        let state_machine = RwLock::new(RocksStateMachine::new(db.clone(), group_id));
+        Arc::new(RocksStore {
+            db,
+            group_id,
+            state_machine,
+        })
+    }
+
+    /// Open a RocksDB with the column families a `RocksStore` requires.
+    pub fn open_db<P: AsRef<Path>>(db_path: P) -> Arc<rocksdb::DB> {
         let mut db_opts = Options::default();
         db_opts.create_missing_column_families(true);
         db_opts.create_if_missing(true);
@@ -587,8 +670,6 @@ impl RocksStore {
 
         let db = DB::open_cf_descriptors(&db_opts, db_path, vec![store, state_machine, data, logs]).unwrap();
 
-        let db = Arc::new(db);
-        let state_machine = RwLock::new(RocksStateMachine::new(db.clone()));
-        Arc::new(RocksStore { db, state_machine })
+        Arc::new(db)
     }
 }
@@ -4,7 +4,12 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use openraft::testing::StoreBuilder;
 use openraft::testing::Suite;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftLogReader;
+use openraft::RaftStorage;
 use openraft::StorageError;
+use openraft::Vote;
 
 use crate::Config;
 use crate::RocksNodeId;
@@ -26,7 +31,62 @@ impl StoreBuilder<Config, Arc<RocksStore>> for RocksBuilder {
         td.close().expect("could not close temp directory");
         r
     }
+
+    async fn restart(&self, store: Arc<RocksStore>) -> Result<Arc<RocksStore>, StorageError<RocksNodeId>> {
+        restart_rocks_store(store).await
+    }
+}
+/// Run the test on group 1, with group 0 sharing the same RocksDB.
+///
+/// Group 0 must not be affected by anything done to group 1.
+struct RocksGroupBuilder {}
+#[async_trait]
+impl StoreBuilder<Config, Arc<RocksStore>> for RocksGroupBuilder {
+    async fn run_test<Fun, Ret, Res>(&self, t: Fun) -> Result<Ret, StorageError<RocksNodeId>>
+    where
+        Res: Future<Output = Result<Ret, StorageError<RocksNodeId>>> + Send,
+        Fun: Fn(Arc<RocksStore>) -> Res + Sync + Send,
+    {
+        let td = tempdir::TempDir::new("RocksGroupBuilder").expect("couldn't create temp dir");
+        let r = {
+            let db = RocksStore::open_db(td.path());
+
+            let mut other = RocksStore::new_group(db.clone(), 0).await;
+            let vote = Vote::new(5, 0);
+            let log_id = LogId::new(LeaderId::new(5, 0), 100);
+            other.save_vote(&vote).await?;
+            other.purge_logs_upto(log_id).await?;
+
+            let store = RocksStore::new_group(db, 1).await;
+            let r = t(store).await;
+
+            assert_eq!(Some(vote), other.read_vote().await?);
+            let log_state = other.get_log_state().await?;
+            assert_eq!(Some(log_id), log_state.last_purged_log_id);
+            assert_eq!(Some(log_id), log_state.last_log_id);
+
+            r
+        };
+        td.close().expect("could not close temp directory");
+        r
+    }
+
+    async fn restart(&self, store: Arc<RocksStore>) -> Result<Arc<RocksStore>, StorageError<RocksNodeId>> {
+        restart_rocks_store(store).await
+    }
 }
+
+/// Drop a store and load a new one from the RocksDB it is built on.
+///
+/// The RocksDB itself is kept open, since the state machine built from the store may still be referring to it.
+async fn restart_rocks_store(store: Arc<RocksStore>) -> Result<Arc<RocksStore>, StorageError<RocksNodeId>> {
+    let db = store.db.clone();
+    let group_id = store.group_id;
+    drop(store);
+
+    Ok(RocksStore::new_group(db, group_id).await)
+}
+
 /// To customize a builder:
 ///
 /// ```ignore
@@ -53,3 +113,9 @@ pub fn test_mem_store() -> Result<(), StorageError<RocksNodeId>> {
     Suite::test_all(RocksBuilder {})?;
     Ok(())
 }
+
+#[test]
+pub fn test_rocks_store_groups() -> Result<(), StorageError<RocksNodeId>> {
+    Suite::test_all(RocksGroupBuilder {})?;
+    Ok(())
+}